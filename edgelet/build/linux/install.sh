@@ -86,7 +86,7 @@ install_toolchain $TOOLCHAIN true
 # Add trusty repo to get older version of libc6-armhf-cross
 sudo add-apt-repository "deb http://archive.ubuntu.com/ubuntu/ trusty main universe"
 
-# Install OpenSSL, curl and uuid and valgrind
+# Install OpenSSL, curl and uuid, valgrind and SoftHSM2 for the PKCS#11 tests
 sudo apt-get update && \
 sudo apt-get install -y \
     pkg-config \
@@ -94,6 +94,7 @@ sudo apt-get install -y \
     libcurl4-openssl-dev \
     debhelper \
     dh-systemd \
+    softhsm2 \
     valgrind && \
 sudo apt-get remove --yes libssl-dev && \
 sudo apt-get install --yes --target-release xenial-updates libssl-dev
@@ -58,8 +58,36 @@ process_args()
     done
 }
 
+###############################################################################
+# Set up a SoftHSM2 token for the PKCS#11 tests, which are skipped when
+# PKCS11_LIB_PATH is not set
+###############################################################################
+setup_softhsm()
+{
+    local softhsm_dir
+    softhsm_dir=$(mktemp -d)
+
+    echo "directories.tokendir = $softhsm_dir/tokens" > "$softhsm_dir/softhsm2.conf"
+    mkdir "$softhsm_dir/tokens"
+    export SOFTHSM2_CONF="$softhsm_dir/softhsm2.conf"
+
+    export PKCS11_LIB_PATH=/usr/lib/softhsm/libsofthsm2.so
+    export PKCS11_TOKEN=edgelet-test
+    export PKCS11_PIN=1234
+    export PKCS11_TEST_KEY_PEM="$softhsm_dir/device-ca.key.pem"
+
+    softhsm2-util --init-token --free --label "$PKCS11_TOKEN" --pin "$PKCS11_PIN" --so-pin 5678
+    openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 -out "$PKCS11_TEST_KEY_PEM"
+    softhsm2-util --import "$PKCS11_TEST_KEY_PEM" --token "$PKCS11_TOKEN" --pin "$PKCS11_PIN" \
+        --label device-ca --id 01
+}
+
 process_args "$@"
 
+if command -v softhsm2-util >/dev/null && [[ -f /usr/lib/softhsm/libsofthsm2.so ]]; then
+    setup_softhsm
+fi
+
 if [[ -z ${RELEASE} ]]; then
     cd "$PROJECT_ROOT" && IOTEDGE_HOMEDIR=/tmp $CARGO "+$TOOLCHAIN" test --all
 else
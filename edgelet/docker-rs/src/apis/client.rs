@@ -8,6 +8,7 @@ use hyper;
 pub struct APIClient<C: hyper::client::connect::Connect> {
     configuration: Arc<Configuration<C>>,
     container_api: Box<::apis::ContainerApi>,
+    exec_api: Box<::apis::ExecApi>,
     image_api: Box<::apis::ImageApi>,
     network_api: Box<::apis::NetworkApi>,
     system_api: Box<::apis::SystemApi>,
@@ -21,6 +22,7 @@ impl<C: hyper::client::connect::Connect + 'static> APIClient<C> {
         APIClient {
             configuration: configuration.clone(),
             container_api: Box::new(::apis::ContainerApiClient::new(configuration.clone())),
+            exec_api: Box::new(::apis::ExecApiClient::new(configuration.clone())),
             image_api: Box::new(::apis::ImageApiClient::new(configuration.clone())),
             network_api: Box::new(::apis::NetworkApiClient::new(configuration.clone())),
             system_api: Box::new(::apis::SystemApiClient::new(configuration.clone())),
@@ -32,6 +34,10 @@ impl<C: hyper::client::connect::Connect + 'static> APIClient<C> {
         self.container_api.as_ref()
     }
 
+    pub fn exec_api(&self) -> &::apis::ExecApi {
+        self.exec_api.as_ref()
+    }
+
     pub fn image_api(&self) -> &::apis::ImageApi {
         self.image_api.as_ref()
     }
@@ -38,7 +38,7 @@ pub trait ContainerApi: Send + Sync {
         &self,
         id: &str,
         path: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>> + Send>;
     fn container_archive_info(
         &self,
         id: &str,
@@ -121,7 +121,7 @@ pub trait ContainerApi: Send + Sync {
         &self,
         id: &str,
         name: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send>;
     fn container_resize(
         &self,
         id: &str,
@@ -186,7 +186,7 @@ where
         &self,
         id: &str,
         path: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::GET;
@@ -218,18 +218,16 @@ where
                 .map_err(|e| Error::from(e))
                 .and_then(|resp| {
                     let (http::response::Parts { status, .. }, body) = resp.into_parts();
-                    body.concat2()
-                        .and_then(move |body| Ok((status, body)))
-                        .map_err(|e| Error::from(e))
-                })
-                .and_then(|(status, body)| {
                     if status.is_success() {
-                        Ok(body)
+                        futures::future::Either::A(futures::future::ok(body))
                     } else {
-                        Err(Error::from((status, &*body)))
+                        futures::future::Either::B(
+                            body.concat2()
+                                .map_err(|e| Error::from(e))
+                                .and_then(move |body| Err(Error::from((status, &*body)))),
+                        )
                     }
-                })
-                .and_then(|_| futures::future::ok(())),
+                }),
         )
     }
 
@@ -952,7 +950,7 @@ where
         &self,
         id: &str,
         name: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
@@ -109,7 +109,7 @@ pub trait ImageApi: Send + Sync {
     fn image_inspect(
         &self,
         name: &str,
-    ) -> Box<Future<Item = ::models::Image, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::Image, Error = Error<serde_json::Value>> + Send>;
     fn image_list(
         &self,
         all: bool,
@@ -661,7 +661,7 @@ where
     fn image_inspect(
         &self,
         name: &str,
-    ) -> Box<Future<Item = ::models::Image, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::Image, Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::GET;
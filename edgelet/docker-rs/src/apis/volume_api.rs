@@ -37,24 +37,24 @@ pub trait VolumeApi: Send + Sync {
     fn volume_create(
         &self,
         volume_config: ::models::VolumeConfig,
-    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>> + Send>;
     fn volume_delete(
         &self,
         name: &str,
         force: bool,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send>;
     fn volume_inspect(
         &self,
         name: &str,
-    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>> + Send>;
     fn volume_list(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20015, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::InlineResponse20015, Error = Error<serde_json::Value>> + Send>;
     fn volume_prune(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>> + Send>;
 }
 
 impl<C> VolumeApi for VolumeApiClient<C>
@@ -66,7 +66,7 @@ where
     fn volume_create(
         &self,
         volume_config: ::models::VolumeConfig,
-    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
@@ -124,7 +124,7 @@ where
         &self,
         name: &str,
         force: bool,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::DELETE;
@@ -174,7 +174,7 @@ where
     fn volume_inspect(
         &self,
         name: &str,
-    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::GET;
@@ -224,7 +224,8 @@ where
     fn volume_list(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20015, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::InlineResponse20015, Error = Error<serde_json::Value>> + Send>
+    {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::GET;
@@ -278,7 +279,8 @@ where
     fn volume_prune(
         &self,
         filters: &str,
-    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::InlineResponse20016, Error = Error<serde_json::Value>> + Send>
+    {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
@@ -17,15 +17,15 @@ pub struct InlineResponse20015 {
     #[serde(rename = "Volumes")]
     volumes: Vec<::models::Volume>,
     /// Warnings that occurred when fetching the list of volumes
-    #[serde(rename = "Warnings")]
-    warnings: Vec<String>,
+    #[serde(rename = "Warnings", skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
 }
 
 impl InlineResponse20015 {
-    pub fn new(volumes: Vec<::models::Volume>, warnings: Vec<String>) -> Self {
+    pub fn new(volumes: Vec<::models::Volume>) -> Self {
         InlineResponse20015 {
             volumes: volumes,
-            warnings: warnings,
+            warnings: None,
         }
     }
 
@@ -43,15 +43,19 @@ impl InlineResponse20015 {
     }
 
     pub fn set_warnings(&mut self, warnings: Vec<String>) {
-        self.warnings = warnings;
+        self.warnings = Some(warnings);
     }
 
     pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
-        self.warnings = warnings;
+        self.warnings = Some(warnings);
         self
     }
 
-    pub fn warnings(&self) -> &[String] {
-        &self.warnings
+    pub fn warnings(&self) -> Option<&[String]> {
+        self.warnings.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_warnings(&mut self) {
+        self.warnings = None;
     }
 }
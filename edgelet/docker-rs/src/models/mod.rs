@@ -76,8 +76,12 @@ mod generic_resources_inner_named_resource_spec;
 pub use self::generic_resources_inner_named_resource_spec::GenericResourcesInnerNamedResourceSpec;
 mod graph_driver_data;
 pub use self::graph_driver_data::GraphDriverData;
+mod health;
+pub use self::health::Health;
 mod health_config;
 pub use self::health_config::HealthConfig;
+mod healthcheck_result;
+pub use self::healthcheck_result::HealthcheckResult;
 mod host_config_log_config;
 pub use self::host_config_log_config::HostConfigLogConfig;
 mod host_config_port_bindings;
@@ -29,36 +29,29 @@ pub struct Volume {
     #[serde(rename = "Status", skip_serializing_if = "Option::is_none")]
     status: Option<::std::collections::HashMap<String, Value>>,
     /// User-defined key/value metadata.
-    #[serde(rename = "Labels")]
-    labels: ::std::collections::HashMap<String, String>,
+    #[serde(rename = "Labels", skip_serializing_if = "Option::is_none")]
+    labels: Option<::std::collections::HashMap<String, String>>,
     /// The level at which the volume exists. Either `global` for cluster-wide, or `local` for machine level.
     #[serde(rename = "Scope")]
     scope: String,
     /// The driver specific options used when creating the volume.
-    #[serde(rename = "Options")]
-    options: ::std::collections::HashMap<String, String>,
+    #[serde(rename = "Options", skip_serializing_if = "Option::is_none")]
+    options: Option<::std::collections::HashMap<String, String>>,
     #[serde(rename = "UsageData", skip_serializing_if = "Option::is_none")]
     usage_data: Option<::models::VolumeUsageData>,
 }
 
 impl Volume {
-    pub fn new(
-        name: String,
-        driver: String,
-        mountpoint: String,
-        labels: ::std::collections::HashMap<String, String>,
-        scope: String,
-        options: ::std::collections::HashMap<String, String>,
-    ) -> Self {
+    pub fn new(name: String, driver: String, mountpoint: String, scope: String) -> Self {
         Volume {
             name: name,
             driver: driver,
             mountpoint: mountpoint,
             created_at: None,
             status: None,
-            labels: labels,
+            labels: None,
             scope: scope,
-            options: options,
+            options: None,
             usage_data: None,
         }
     }
@@ -137,16 +130,20 @@ impl Volume {
     }
 
     pub fn set_labels(&mut self, labels: ::std::collections::HashMap<String, String>) {
-        self.labels = labels;
+        self.labels = Some(labels);
     }
 
     pub fn with_labels(mut self, labels: ::std::collections::HashMap<String, String>) -> Self {
-        self.labels = labels;
+        self.labels = Some(labels);
         self
     }
 
-    pub fn labels(&self) -> &::std::collections::HashMap<String, String> {
-        &self.labels
+    pub fn labels(&self) -> Option<&::std::collections::HashMap<String, String>> {
+        self.labels.as_ref()
+    }
+
+    pub fn reset_labels(&mut self) {
+        self.labels = None;
     }
 
     pub fn set_scope(&mut self, scope: String) {
@@ -163,16 +160,20 @@ impl Volume {
     }
 
     pub fn set_options(&mut self, options: ::std::collections::HashMap<String, String>) {
-        self.options = options;
+        self.options = Some(options);
     }
 
     pub fn with_options(mut self, options: ::std::collections::HashMap<String, String>) -> Self {
-        self.options = options;
+        self.options = Some(options);
         self
     }
 
-    pub fn options(&self) -> &::std::collections::HashMap<String, String> {
-        &self.options
+    pub fn options(&self) -> Option<&::std::collections::HashMap<String, String>> {
+        self.options.as_ref()
+    }
+
+    pub fn reset_options(&mut self) {
+        self.options = None;
     }
 
     pub fn set_usage_data(&mut self, usage_data: ::models::VolumeUsageData) {
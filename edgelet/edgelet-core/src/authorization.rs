@@ -6,11 +6,16 @@ use futures::{future, Future, Stream};
 
 use error::{Error, ErrorKind};
 use module::{Module, ModuleRuntime};
-use pid::Pid;
+use pid::{Credentials, Pid};
 
+#[derive(Clone, Copy, Debug)]
 pub enum Policy {
     Anonymous,
     Caller,
+    /// Only allows privileged callers on the host, i.e. callers outside of
+    /// any container that run as root or as a member of the given group.
+    /// Callers whose credentials are unknown are rejected.
+    Host(Option<u32>),
     Module(&'static str),
 }
 
@@ -34,12 +39,16 @@ where
         &self,
         name: Option<String>,
         pid: Pid,
+        credentials: Option<Credentials>,
     ) -> impl Future<Item = bool, Error = Error> {
         let name = name.map(|n| n.trim_left_matches('$').to_string());
         match self.policy {
             Policy::Anonymous => Either::A(Either::A(self.auth_anonymous())),
             Policy::Caller => Either::A(Either::B(self.auth_caller(name, pid))),
-            Policy::Module(ref expected_name) => Either::B(self.auth_module(expected_name, pid)),
+            Policy::Module(ref expected_name) => {
+                Either::B(Either::A(self.auth_module(expected_name, pid)))
+            }
+            Policy::Host(group) => Either::B(Either::B(self.auth_host(pid, credentials, group))),
         }
     }
 
@@ -81,10 +90,55 @@ where
     ) -> impl Future<Item = bool, Error = Error> {
         self.auth_caller(Some(expected_name.to_string()), pid)
     }
+
+    fn auth_host(
+        &self,
+        pid: Pid,
+        credentials: Option<Credentials>,
+        group: Option<u32>,
+    ) -> impl Future<Item = bool, Error = Error> {
+        let privileged = credentials.map_or(false, |credentials| {
+            let privileged = credentials.host()
+                && (credentials.uid() == 0
+                    || group.map_or(false, |group| credentials.gids().contains(&group)));
+            if !privileged {
+                info!(
+                    "Request not authorized - caller pid {} with uid {} is not a privileged host process",
+                    pid,
+                    credentials.uid()
+                );
+            }
+            privileged
+        });
+
+        match pid {
+            Pid::Value(_) if privileged => Either::B(
+                self.runtime
+                    .list_with_details()
+                    .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime)))
+                    .filter(move |&(_, ref rs)| rs.pid() == pid)
+                    .into_future()
+                    .then(move |result| match result {
+                        Ok((Some((m, _)), _)) => {
+                            info!(
+                                "Request not authorized - caller pid {} belongs to module {}",
+                                pid,
+                                m.name()
+                            );
+                            Ok(false)
+                        }
+                        Ok((None, _)) => Ok(true),
+                        Err((err, _)) => Err(err),
+                    }),
+            ),
+            _ => Either::A(future::ok(false)),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::Cursor;
     use std::time::Duration;
 
     use super::*;
@@ -94,15 +148,15 @@ mod tests {
     use futures::stream::Empty;
     use futures::{future, stream};
     use module::{
-        LogOptions, Module, ModuleRegistry, ModuleRuntimeState, ModuleSpec,
-        SystemInfo as CoreSystemInfo,
+        ExecOptions, ExecStatus, LogOptions, Module, ModuleRegistry, ModuleRuntimeState,
+        ModuleSpec, ModuleVolume, SystemInfo as CoreSystemInfo,
     };
 
     #[test]
     fn should_authorize_anonymous() {
         let runtime = TestModuleList::new(vec![]);
         let auth = Authorization::new(runtime, Policy::Anonymous);
-        assert_eq!(true, auth.authorize(None, Pid::None).wait().unwrap());
+        assert_eq!(true, auth.authorize(None, Pid::None, None).wait().unwrap());
     }
 
     #[test]
@@ -114,7 +168,7 @@ mod tests {
         let auth = Authorization::new(runtime, Policy::Caller);
         assert_eq!(
             true,
-            auth.authorize(Some("abc".to_string()), Pid::Value(123))
+            auth.authorize(Some("abc".to_string()), Pid::Value(123), None)
                 .wait()
                 .unwrap()
         );
@@ -129,7 +183,7 @@ mod tests {
         let auth = Authorization::new(runtime, Policy::Caller);
         assert_eq!(
             true,
-            auth.authorize(Some("$edgeAgent".to_string()), Pid::Value(123))
+            auth.authorize(Some("$edgeAgent".to_string()), Pid::Value(123), None)
                 .wait()
                 .unwrap()
         );
@@ -139,7 +193,10 @@ mod tests {
     fn should_reject_caller_without_name() {
         let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
         let auth = Authorization::new(runtime, Policy::Caller);
-        assert_eq!(false, auth.authorize(None, Pid::Value(123)).wait().unwrap());
+        assert_eq!(
+            false,
+            auth.authorize(None, Pid::Value(123), None).wait().unwrap()
+        );
     }
 
     #[test]
@@ -148,7 +205,7 @@ mod tests {
         let auth = Authorization::new(runtime, Policy::Caller);
         assert_eq!(
             false,
-            auth.authorize(Some("xyz".to_string()), Pid::Value(123))
+            auth.authorize(Some("xyz".to_string()), Pid::Value(123), None)
                 .wait()
                 .unwrap()
         );
@@ -160,7 +217,7 @@ mod tests {
         let auth = Authorization::new(runtime, Policy::Caller);
         assert_eq!(
             false,
-            auth.authorize(Some("abc".to_string()), Pid::Value(456))
+            auth.authorize(Some("abc".to_string()), Pid::Value(456), None)
                 .wait()
                 .unwrap()
         );
@@ -173,21 +230,135 @@ mod tests {
             TestModule::new("abc", 123),
         ]);
         let auth = Authorization::new(runtime, Policy::Module("abc"));
-        assert_eq!(true, auth.authorize(None, Pid::Value(123)).wait().unwrap());
+        assert_eq!(
+            true,
+            auth.authorize(None, Pid::Value(123), None).wait().unwrap()
+        );
     }
 
     #[test]
     fn should_reject_module_whose_name_does_not_match_policy() {
         let runtime = TestModuleList::new(vec![TestModule::new("xyz", 123)]);
         let auth = Authorization::new(runtime, Policy::Module("abc"));
-        assert_eq!(false, auth.authorize(None, Pid::Value(123)).wait().unwrap());
+        assert_eq!(
+            false,
+            auth.authorize(None, Pid::Value(123), None).wait().unwrap()
+        );
     }
 
     #[test]
     fn should_reject_module_with_different_pid() {
         let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
         let auth = Authorization::new(runtime, Policy::Module("abc"));
-        assert_eq!(false, auth.authorize(None, Pid::Value(456)).wait().unwrap());
+        assert_eq!(
+            false,
+            auth.authorize(None, Pid::Value(456), None).wait().unwrap()
+        );
+    }
+
+    fn host_credentials(uid: u32, gids: Vec<u32>) -> Option<Credentials> {
+        Some(Credentials::new(uid, gids, true))
+    }
+
+    #[test]
+    fn should_authorize_host_root() {
+        let runtime = TestModuleList::new(vec![
+            TestModule::new("xyz", 987),
+            TestModule::new("abc", 123),
+        ]);
+        let auth = Authorization::new(runtime, Policy::Host(None));
+        assert_eq!(
+            true,
+            auth.authorize(None, Pid::Value(456), host_credentials(0, vec![0]))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_authorize_host_privileged_group() {
+        let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(runtime, Policy::Host(Some(998)));
+        assert_eq!(
+            true,
+            auth.authorize(
+                None,
+                Pid::Value(456),
+                host_credentials(1000, vec![1000, 998])
+            )
+            .wait()
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_unprivileged_user() {
+        let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(runtime, Policy::Host(Some(998)));
+        assert_eq!(
+            false,
+            auth.authorize(None, Pid::Value(456), host_credentials(1000, vec![1000]))
+                .wait()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_without_credentials() {
+        let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(runtime, Policy::Host(None));
+        assert_eq!(
+            false,
+            auth.authorize(None, Pid::Value(456), None).wait().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_root_in_container() {
+        // e.g. a child process of a module, or `docker exec`
+        let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(runtime, Policy::Host(None));
+        assert_eq!(
+            false,
+            auth.authorize(
+                None,
+                Pid::Value(456),
+                Some(Credentials::new(0, vec![0], false))
+            )
+            .wait()
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_when_caller_is_module() {
+        let runtime = TestModuleList::new(vec![
+            TestModule::new("xyz", 987),
+            TestModule::new("abc", 123),
+        ]);
+        let auth = Authorization::new(runtime, Policy::Host(None));
+        assert_eq!(
+            false,
+            auth.authorize(
+                Some("abc".to_string()),
+                Pid::Value(123),
+                host_credentials(0, vec![0])
+            )
+            .wait()
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_host_without_pid() {
+        let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
+        let auth = Authorization::new(runtime, Policy::Host(None));
+        assert_eq!(
+            false,
+            auth.authorize(None, Pid::None, host_credentials(0, vec![0]))
+                .wait()
+                .unwrap()
+        );
     }
 
     #[test]
@@ -200,7 +371,7 @@ mod tests {
         let auth = Authorization::new(runtime, Policy::Caller);
         assert_eq!(
             false,
-            auth.authorize(Some("abc".to_string()), Pid::Value(123))
+            auth.authorize(Some("abc".to_string()), Pid::Value(123), None)
                 .wait()
                 .unwrap()
         );
@@ -215,7 +386,7 @@ mod tests {
             TestModuleBehavior::FailRuntimeState,
         )]);
         let auth = Authorization::new(runtime, Policy::Caller);
-        auth.authorize(Some("abc".to_string()), Pid::Value(123))
+        auth.authorize(Some("abc".to_string()), Pid::Value(123), None)
             .wait()
             .unwrap();
     }
@@ -228,7 +399,7 @@ mod tests {
             TestModuleListBehavior::FailList,
         );
         let auth = Authorization::new(runtime, Policy::Caller);
-        auth.authorize(Some("abc".to_string()), Pid::Value(123))
+        auth.authorize(Some("abc".to_string()), Pid::Value(123), None)
             .wait()
             .unwrap();
     }
@@ -355,6 +526,8 @@ mod tests {
         type ModuleRegistry = Self;
         type Chunk = String;
         type Logs = Empty<Self::Chunk, Self::Error>;
+        type VolumeArchive = Empty<Self::Chunk, Self::Error>;
+        type ExecIo = Cursor<Vec<u8>>;
 
         type CreateFuture = FutureResult<(), Self::Error>;
         type InitFuture = FutureResult<(), Self::Error>;
@@ -363,11 +536,19 @@ mod tests {
             Box<Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error> + Send>;
         type LogsFuture = FutureResult<Self::Logs, Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type RenameFuture = FutureResult<(), Self::Error>;
         type RestartFuture = FutureResult<(), Self::Error>;
         type StartFuture = FutureResult<(), Self::Error>;
         type StopFuture = FutureResult<(), Self::Error>;
         type SystemInfoFuture = FutureResult<CoreSystemInfo, Self::Error>;
         type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type ListVolumesFuture = FutureResult<Vec<ModuleVolume>, Self::Error>;
+        type GetVolumeFuture = FutureResult<ModuleVolume, Self::Error>;
+        type BackupVolumeFuture = FutureResult<Self::VolumeArchive, Self::Error>;
+        type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+        type ExecFuture = FutureResult<String, Self::Error>;
+        type ExecStartFuture = FutureResult<Self::ExecIo, Self::Error>;
+        type ExecInspectFuture = FutureResult<ExecStatus, Self::Error>;
 
         fn init(&self) -> Self::InitFuture {
             notimpl_error!()
@@ -397,6 +578,10 @@ mod tests {
             notimpl_error!()
         }
 
+        fn rename(&self, _id: &str, _new_name: &str) -> Self::RenameFuture {
+            notimpl_error!()
+        }
+
         fn list(&self) -> Self::ListFuture {
             match self.behavior {
                 TestModuleListBehavior::Default => future::ok(self.modules.clone()),
@@ -424,7 +609,35 @@ mod tests {
             self
         }
 
-        fn remove_all(&self) -> Self::RemoveAllFuture {
+        fn remove_all(&self, _purge_volumes: bool) -> Self::RemoveAllFuture {
+            notimpl_error!()
+        }
+
+        fn list_volumes(&self, _module: &str) -> Self::ListVolumesFuture {
+            notimpl_error!()
+        }
+
+        fn get_volume(&self, _module: &str, _name: &str) -> Self::GetVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn backup_volume(&self, _module: &str, _name: &str) -> Self::BackupVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn remove_volume(&self, _module: &str, _name: &str) -> Self::RemoveVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+            notimpl_error!()
+        }
+
+        fn exec_start(&self, _exec_id: &str, _tty: bool) -> Self::ExecStartFuture {
+            notimpl_error!()
+        }
+
+        fn exec_inspect(&self, _exec_id: &str) -> Self::ExecInspectFuture {
             notimpl_error!()
         }
     }
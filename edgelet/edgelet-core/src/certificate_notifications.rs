@@ -0,0 +1,645 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Notifications that tell modules when to renew their certificates.
+//!
+//! The workload API records every identity and server certificate that it
+//! issues, along with the chain of the CA that issued it. Issuing a
+//! certificate from a different CA than before notifies every module that
+//! still holds a certificate from the old one, and a periodic check notifies
+//! modules whose certificates are close to expiring as well as all modules
+//! when the trust bundle changes.
+//!
+//! Every notification gets a sequence number. Modules long-poll for the ones
+//! that concern them by passing the sequence number they last saw.
+//!
+//! The issued certificates are kept on disk, so that modules holding
+//! certificates from before a restart are still notified. The daemon tells
+//! the notifier about the workload CA when it starts, which notifies every
+//! module with a certificate from a workload CA that has been regenerated
+//! since.
+
+use std::cmp;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use failure::{Fail, ResultExt};
+use futures::future::{self, Either};
+use futures::sync::oneshot;
+use futures::Future;
+use serde_json;
+use sha2::{Digest, Sha256};
+use tokio::timer::Delay;
+
+use certificate_properties::CertificateType;
+use error::{Error, ErrorKind};
+use trust_bundle::{hex, PEM_END};
+
+/// How many notifications are kept for modules that poll late.
+const MAX_NOTIFICATIONS: usize = 1000;
+
+/// Certificates are due for renewal once this percentage of their lifetime
+/// has passed.
+const RENEWAL_THRESHOLD_PERCENT: i32 = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NotificationKind {
+    /// The certificate is due for renewal.
+    Expiring,
+    /// The certificate was issued by a CA that no longer issues certificates.
+    CaRotated,
+    /// The trust bundle changed. These notifications concern all modules.
+    TrustBundleChanged,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CertificateNotification {
+    sequence: u64,
+    kind: NotificationKind,
+    module_id: Option<String>,
+    certificate_type: Option<CertificateType>,
+    expiration: Option<DateTime<Utc>>,
+    time: DateTime<Utc>,
+}
+
+impl CertificateNotification {
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn kind(&self) -> NotificationKind {
+        self.kind
+    }
+
+    /// The module the notification concerns, or `None` if it concerns all
+    /// modules.
+    pub fn module_id(&self) -> Option<&str> {
+        self.module_id.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn certificate_type(&self) -> Option<CertificateType> {
+        self.certificate_type
+    }
+
+    /// When the certificate the notification concerns expires.
+    pub fn expiration(&self) -> Option<&DateTime<Utc>> {
+        self.expiration.as_ref()
+    }
+
+    pub fn time(&self) -> &DateTime<Utc> {
+        &self.time
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Issuance {
+    module_id: String,
+    certificate_type: CertificateType,
+    issuer: String,
+    issued: DateTime<Utc>,
+    valid_to: DateTime<Utc>,
+    expiring_notified: bool,
+}
+
+/// The part of the state that is kept on disk.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Saved {
+    sequence: u64,
+    issuances: BTreeMap<String, Issuance>,
+    issuer: Option<String>,
+    workload_ca: Option<String>,
+    trust_bundle: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    saved: Saved,
+    path: Option<PathBuf>,
+    notifications: VecDeque<CertificateNotification>,
+    waiters: Vec<oneshot::Sender<()>>,
+}
+
+impl State {
+    fn push(
+        &mut self,
+        kind: NotificationKind,
+        module_id: Option<String>,
+        certificate_type: Option<CertificateType>,
+        expiration: Option<DateTime<Utc>>,
+        time: DateTime<Utc>,
+    ) {
+        self.saved.sequence += 1;
+        self.notifications.push_back(CertificateNotification {
+            sequence: self.saved.sequence,
+            kind,
+            module_id,
+            certificate_type,
+            expiration,
+            time,
+        });
+        if self.notifications.len() > MAX_NOTIFICATIONS {
+            self.notifications.pop_front();
+        }
+    }
+
+    /// Notifies the holders of the certificates issued by anything but
+    /// `issuer`, or of all certificates without one, that the CA rotated.
+    fn ca_rotated(&mut self, issuer: Option<&str>, now: DateTime<Utc>) {
+        let stale: Vec<_> = self
+            .saved
+            .issuances
+            .values()
+            .filter(|issuance| issuer.map_or(true, |issuer| issuance.issuer != issuer))
+            .map(|issuance| {
+                (
+                    issuance.module_id.clone(),
+                    issuance.certificate_type,
+                    issuance.valid_to,
+                )
+            })
+            .collect();
+        for (module_id, certificate_type, valid_to) in stale {
+            self.push(
+                NotificationKind::CaRotated,
+                Some(module_id),
+                Some(certificate_type),
+                Some(valid_to),
+                now,
+            );
+        }
+        if issuer.is_none() {
+            self.saved.issuances.clear();
+        }
+        self.wake();
+    }
+
+    fn save(&self) {
+        if let Some(ref path) = self.path {
+            if let Err(err) = save(path, &self.saved) {
+                warn!(
+                    "Could not save the issued certificates to {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+
+    fn wake(&mut self) {
+        for waiter in self.waiters.drain(..) {
+            waiter.send(()).unwrap_or(());
+        }
+    }
+
+    fn since(&self, module_id: &str, after: u64) -> (u64, Vec<CertificateNotification>) {
+        let notifications = self
+            .notifications
+            .iter()
+            .filter(|notification| {
+                notification.sequence > after
+                    && notification.module_id().map_or(true, |id| id == module_id)
+            })
+            .cloned()
+            .collect();
+        (self.saved.sequence, notifications)
+    }
+}
+
+fn save(path: &Path, saved: &Saved) -> io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    let mut file = File::create(&temp_path)?;
+    serde_json::to_writer(&mut file, saved)?;
+    file.flush()?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)
+}
+
+/// Keeps track of issued certificates and the notifications about them.
+#[derive(Clone, Debug, Default)]
+pub struct CertificateNotifier {
+    state: Arc<Mutex<State>>,
+}
+
+impl CertificateNotifier {
+    pub fn new() -> Self {
+        CertificateNotifier::default()
+    }
+
+    /// Loads the issued certificates kept in the file at `path`, and keeps
+    /// them there from now on.
+    pub fn load(path: PathBuf) -> Result<Self, Error> {
+        let saved = match File::open(&path) {
+            Ok(file) => {
+                serde_json::from_reader(file).context(ErrorKind::CertificateNotifications)?
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Saved::default(),
+            Err(err) => {
+                return Err(Error::from(
+                    err.context(ErrorKind::CertificateNotifications),
+                ))
+            }
+        };
+        let state = State {
+            saved,
+            path: Some(path),
+            ..State::default()
+        };
+        Ok(CertificateNotifier {
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Records the workload CA that certificates are issued by from now on.
+    /// If it differs from the one recorded before, the holders of every
+    /// certificate issued so far are notified.
+    pub fn workload_ca(&self, pem: &[u8]) {
+        let workload_ca = hex(&Sha256::digest(
+            String::from_utf8_lossy(pem).trim().as_bytes(),
+        ));
+
+        let mut state = self
+            .state
+            .lock()
+            .expect("certificate notifier lock poisoned");
+        let rotated = state
+            .saved
+            .workload_ca
+            .as_ref()
+            .map_or(false, |previous| *previous != workload_ca);
+        if rotated {
+            state.ca_rotated(None, Utc::now());
+        }
+        state.saved.workload_ca = Some(workload_ca);
+        state.save();
+    }
+
+    /// Records that the certificate `alias` was issued to `module_id`. `pem`
+    /// is the certificate followed by the chain of the CA that issued it.
+    pub fn issued(
+        &self,
+        alias: &str,
+        module_id: &str,
+        certificate_type: CertificateType,
+        pem: &[u8],
+        valid_to: DateTime<Utc>,
+    ) {
+        let issuer = issuer(pem);
+        let now = Utc::now();
+
+        let mut state = self
+            .state
+            .lock()
+            .expect("certificate notifier lock poisoned");
+        state.saved.issuances.remove(alias);
+
+        let rotated = state
+            .saved
+            .issuer
+            .as_ref()
+            .map_or(false, |previous| *previous != issuer);
+        if rotated {
+            state.ca_rotated(Some(&issuer), now);
+        }
+
+        state.saved.issuer = Some(issuer.clone());
+        state.saved.issuances.insert(
+            alias.to_string(),
+            Issuance {
+                module_id: module_id.to_string(),
+                certificate_type,
+                issuer,
+                issued: now,
+                valid_to,
+                expiring_notified: false,
+            },
+        );
+        state.save();
+    }
+
+    /// Notifies modules whose certificates are due for renewal at `now`, and
+    /// all modules if `trust_bundle_etag` differs from the one of the
+    /// previous check.
+    pub fn check(&self, trust_bundle_etag: &str, now: DateTime<Utc>) {
+        let mut state = self
+            .state
+            .lock()
+            .expect("certificate notifier lock poisoned");
+        let sequence = state.saved.sequence;
+
+        let changed = state
+            .saved
+            .trust_bundle
+            .as_ref()
+            .map_or(false, |previous| previous != trust_bundle_etag);
+        if changed {
+            state.push(NotificationKind::TrustBundleChanged, None, None, None, now);
+        }
+        let first = state.saved.trust_bundle.is_none();
+        state.saved.trust_bundle = Some(trust_bundle_etag.to_string());
+
+        let mut expiring = vec![];
+        for issuance in state.saved.issuances.values_mut() {
+            let lifetime = issuance.valid_to.signed_duration_since(issuance.issued);
+            let renew_at = issuance.issued + lifetime * RENEWAL_THRESHOLD_PERCENT / 100;
+            if !issuance.expiring_notified && renew_at <= now {
+                issuance.expiring_notified = true;
+                expiring.push((
+                    issuance.module_id.clone(),
+                    issuance.certificate_type,
+                    issuance.valid_to,
+                ));
+            }
+        }
+        for (module_id, certificate_type, valid_to) in expiring {
+            state.push(
+                NotificationKind::Expiring,
+                Some(module_id),
+                Some(certificate_type),
+                Some(valid_to),
+                now,
+            );
+        }
+
+        if state.saved.sequence != sequence {
+            state.wake();
+        }
+        if first || state.saved.sequence != sequence {
+            state.save();
+        }
+    }
+
+    /// Returns the notifications for `module_id` with a sequence number
+    /// greater than `after`, together with the current sequence number. If
+    /// there are none yet, waits up to `timeout` for one.
+    ///
+    /// Without `after`, only notifications from now on are returned.
+    /// Sequence numbers carry over restarts of the daemon, but notifications
+    /// from before a restart are not kept.
+    pub fn poll(
+        &self,
+        module_id: &str,
+        after: Option<u64>,
+        timeout: Duration,
+    ) -> Box<Future<Item = (u64, Vec<CertificateNotification>), Error = Error> + Send> {
+        let (after, waiter) = {
+            let mut state = self
+                .state
+                .lock()
+                .expect("certificate notifier lock poisoned");
+            let sequence = state.saved.sequence;
+            let after = after.map_or(sequence, |after| cmp::min(after, sequence));
+            let (sequence, notifications) = state.since(module_id, after);
+            if !notifications.is_empty() || timeout == Duration::from_secs(0) {
+                return Box::new(future::ok((sequence, notifications)));
+            }
+
+            let (tx, rx) = oneshot::channel();
+            state.waiters.retain(|waiter| !waiter.is_canceled());
+            state.waiters.push(tx);
+            (after, rx)
+        };
+
+        let state = self.state.clone();
+        let module_id = module_id.to_string();
+        let notifications =
+            waiter
+                .select2(Delay::new(Instant::now() + timeout))
+                .then(move |result| match result {
+                    Err(Either::B((err, _))) => Err(Error::from(
+                        err.context(ErrorKind::CertificateNotifications),
+                    )),
+                    _ => {
+                        let state = state.lock().expect("certificate notifier lock poisoned");
+                        Ok(state.since(&module_id, after))
+                    }
+                });
+        Box::new(notifications)
+    }
+}
+
+/// Returns a fingerprint of the CA chain that follows the certificate in
+/// `pem`.
+fn issuer(pem: &[u8]) -> String {
+    let pem = String::from_utf8_lossy(pem);
+    let chain = pem
+        .find(PEM_END)
+        .map_or("", |index| &pem[index + PEM_END.len()..]);
+    hex(&Sha256::digest(chain.trim().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Duration as ChronoDuration;
+    use tempdir::TempDir;
+    use tokio::runtime::current_thread::Runtime;
+
+    const CA: &str = "-----BEGIN CERTIFICATE-----\nCA\n-----END CERTIFICATE-----\n";
+    const OTHER_CA: &str = "-----BEGIN CERTIFICATE-----\nOTHER\n-----END CERTIFICATE-----\n";
+
+    fn cert(ca: &str) -> Vec<u8> {
+        format!(
+            "-----BEGIN CERTIFICATE-----\nLEAF\n-----END CERTIFICATE-----\n{}",
+            ca
+        )
+        .into_bytes()
+    }
+
+    fn poll_now(
+        notifier: &CertificateNotifier,
+        module_id: &str,
+        after: u64,
+    ) -> (u64, Vec<CertificateNotification>) {
+        notifier
+            .poll(module_id, Some(after), Duration::from_secs(0))
+            .wait()
+            .unwrap()
+    }
+
+    #[test]
+    fn new_ca_notifies_holders_of_old_certificates() {
+        let notifier = CertificateNotifier::new();
+        let valid_to = Utc::now() + ChronoDuration::hours(1);
+        notifier.issued(
+            "m1identity",
+            "m1",
+            CertificateType::Client,
+            &cert(CA),
+            valid_to,
+        );
+        notifier.issued(
+            "m2identity",
+            "m2",
+            CertificateType::Client,
+            &cert(CA),
+            valid_to,
+        );
+        notifier.issued(
+            "m1identity",
+            "m1",
+            CertificateType::Client,
+            &cert(OTHER_CA),
+            valid_to,
+        );
+
+        let (sequence, notifications) = poll_now(&notifier, "m2", 0);
+        assert_eq!(1, sequence);
+        assert_eq!(1, notifications.len());
+        assert_eq!(NotificationKind::CaRotated, notifications[0].kind());
+        assert_eq!(Some("m2"), notifications[0].module_id());
+        assert_eq!(
+            Some(CertificateType::Client),
+            notifications[0].certificate_type()
+        );
+        assert_eq!(Some(&valid_to), notifications[0].expiration());
+
+        assert!(poll_now(&notifier, "m1", 0).1.is_empty());
+    }
+
+    #[test]
+    fn issued_certificates_survive_restart() {
+        let dir = TempDir::new("notifications").unwrap();
+        let path = dir.path().join("certificates.json");
+        let valid_to = Utc::now() + ChronoDuration::hours(1);
+        {
+            let notifier = CertificateNotifier::load(path.clone()).unwrap();
+            notifier.issued(
+                "m1identity",
+                "m1",
+                CertificateType::Client,
+                &cert(CA),
+                valid_to,
+            );
+        }
+
+        let notifier = CertificateNotifier::load(path).unwrap();
+        notifier.issued(
+            "m2identity",
+            "m2",
+            CertificateType::Client,
+            &cert(OTHER_CA),
+            valid_to,
+        );
+
+        let (sequence, notifications) = poll_now(&notifier, "m1", 0);
+        assert_eq!(1, sequence);
+        assert_eq!(1, notifications.len());
+        assert_eq!(NotificationKind::CaRotated, notifications[0].kind());
+    }
+
+    #[test]
+    fn new_workload_ca_notifies_holders_of_all_certificates() {
+        let dir = TempDir::new("notifications").unwrap();
+        let path = dir.path().join("certificates.json");
+        let valid_to = Utc::now() + ChronoDuration::hours(1);
+        {
+            let notifier = CertificateNotifier::load(path.clone()).unwrap();
+            notifier.workload_ca(CA.as_bytes());
+            for module_id in &["m1", "m2"] {
+                notifier.issued(
+                    &format!("{}identity", module_id),
+                    module_id,
+                    CertificateType::Client,
+                    &cert(CA),
+                    valid_to,
+                );
+            }
+        }
+
+        let notifier = CertificateNotifier::load(path.clone()).unwrap();
+        notifier.workload_ca(CA.as_bytes());
+        assert!(poll_now(&notifier, "m1", 0).1.is_empty());
+
+        notifier.workload_ca(OTHER_CA.as_bytes());
+        for module_id in &["m1", "m2"] {
+            let (_, notifications) = poll_now(&notifier, module_id, 0);
+            assert_eq!(1, notifications.len());
+            assert_eq!(NotificationKind::CaRotated, notifications[0].kind());
+            assert_eq!(Some(&valid_to), notifications[0].expiration());
+        }
+
+        // The holders aren't notified again when the next certificate is
+        // issued by the new CA.
+        notifier.issued(
+            "m1identity",
+            "m1",
+            CertificateType::Client,
+            &cert(OTHER_CA),
+            valid_to,
+        );
+        assert_eq!(1, poll_now(&notifier, "m2", 0).1.len());
+    }
+
+    #[test]
+    fn check_notifies_expiring_certificates_once() {
+        let notifier = CertificateNotifier::new();
+        let valid_to = Utc::now() + ChronoDuration::hours(10);
+        notifier.issued(
+            "m1genserver",
+            "m1",
+            CertificateType::Server,
+            &cert(CA),
+            valid_to,
+        );
+
+        notifier.check("\"a\"", Utc::now() + ChronoDuration::hours(7));
+        assert!(poll_now(&notifier, "m1", 0).1.is_empty());
+
+        notifier.check("\"a\"", Utc::now() + ChronoDuration::hours(9));
+        notifier.check("\"a\"", Utc::now() + ChronoDuration::hours(9));
+        let (_, notifications) = poll_now(&notifier, "m1", 0);
+        assert_eq!(1, notifications.len());
+        assert_eq!(NotificationKind::Expiring, notifications[0].kind());
+        assert_eq!(
+            Some(CertificateType::Server),
+            notifications[0].certificate_type()
+        );
+    }
+
+    #[test]
+    fn check_notifies_all_modules_of_trust_bundle_changes() {
+        let notifier = CertificateNotifier::new();
+        notifier.check("\"a\"", Utc::now());
+        notifier.check("\"a\"", Utc::now());
+        assert!(poll_now(&notifier, "m1", 0).1.is_empty());
+
+        notifier.check("\"b\"", Utc::now());
+        for module_id in &["m1", "m2"] {
+            let (sequence, notifications) = poll_now(&notifier, module_id, 0);
+            assert_eq!(1, sequence);
+            assert_eq!(1, notifications.len());
+            assert_eq!(
+                NotificationKind::TrustBundleChanged,
+                notifications[0].kind()
+            );
+            assert_eq!(None, notifications[0].module_id());
+        }
+        assert!(poll_now(&notifier, "m1", 1).1.is_empty());
+    }
+
+    #[test]
+    fn poll_waits_for_notifications() {
+        let notifier = CertificateNotifier::new();
+        notifier.check("\"a\"", Utc::now());
+
+        let poll = notifier.poll("m1", None, Duration::from_secs(60));
+        notifier.check("\"b\"", Utc::now());
+        let (sequence, notifications) = Runtime::new().unwrap().block_on(poll).unwrap();
+        assert_eq!(1, sequence);
+        assert_eq!(1, notifications.len());
+    }
+
+    #[test]
+    fn poll_times_out_without_notifications() {
+        let notifier = CertificateNotifier::new();
+        let poll = notifier.poll("m1", Some(5), Duration::from_millis(10));
+        let (sequence, notifications) = Runtime::new().unwrap().block_on(poll).unwrap();
+        assert_eq!(0, sequence);
+        assert!(notifications.is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 /// Enumerator for `CERTIFICATE_TYPE`
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum CertificateType {
     Unknown,
     Client,
@@ -5,6 +5,7 @@ use std::convert::{AsRef, From};
 use std::fmt;
 use std::string::ToString;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
@@ -148,6 +149,94 @@ pub trait MasterEncryptionKey {
     fn destroy_key(&self) -> Result<(), Error>;
 }
 
+/// Encryption keys kept next to the master encryption key under aliases of
+/// their own, so that a key can be replaced by creating its successor before
+/// the key itself is destroyed.
+pub trait AliasedEncryptionKey {
+    type Buffer: AsRef<[u8]>;
+
+    fn create_aliased_key(&self, alias: &str) -> Result<(), Error>;
+    fn destroy_aliased_key(&self, alias: &str) -> Result<(), Error>;
+
+    fn encrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, Error>;
+
+    fn decrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, Error>;
+}
+
+/// A version of the master encryption key. Ciphertexts produced with a
+/// retired version can be decrypted until the version expires.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MasterKeyVersion {
+    version: u32,
+    created: DateTime<Utc>,
+    retired: Option<DateTime<Utc>>,
+    expires: Option<DateTime<Utc>>,
+}
+
+impl MasterKeyVersion {
+    pub fn new(version: u32, created: DateTime<Utc>) -> Self {
+        MasterKeyVersion {
+            version,
+            created,
+            retired: None,
+            expires: None,
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    pub fn retired(&self) -> Option<DateTime<Utc>> {
+        self.retired
+    }
+
+    pub fn expires(&self) -> Option<DateTime<Utc>> {
+        self.expires
+    }
+
+    pub fn is_current(&self) -> bool {
+        self.retired.is_none()
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.map_or(false, |expires| expires <= now)
+    }
+
+    pub fn retire(&mut self, now: DateTime<Utc>, expires: DateTime<Utc>) {
+        self.retired = Some(now);
+        self.expires = Some(expires);
+    }
+}
+
+pub trait RotateMasterEncryptionKey {
+    /// Returns the versions of the master encryption key that can still
+    /// decrypt, oldest first. The last one is the one used to encrypt.
+    fn key_versions(&self) -> Result<Vec<MasterKeyVersion>, Error>;
+
+    /// Replaces the current version of the master encryption key with a new
+    /// one. Ciphertexts of the retired version can be decrypted until
+    /// `grace_period` has passed, which gives their owners time to encrypt
+    /// them again.
+    fn rotate_key(&self, grace_period: Duration) -> Result<MasterKeyVersion, Error>;
+}
+
 pub trait Encrypt {
     type Buffer: AsRef<[u8]>;
 
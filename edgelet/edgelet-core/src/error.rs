@@ -2,7 +2,9 @@
 
 use std::fmt;
 use std::fmt::Display;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use failure::{Backtrace, Context, Fail};
 
 pub type Result<T> = ::std::result::Result<T, Error>;
@@ -19,6 +21,9 @@ pub enum ErrorKind {
     #[fail(display = "Identity error")]
     Certificate,
 
+    #[fail(display = "An error occurred while waiting for certificate notifications.")]
+    CertificateNotifications,
+
     #[fail(
         display = "Edge runtime module has not been created in IoT Hub. Please make sure this device is an IoT Edge capable device."
     )]
@@ -33,6 +38,12 @@ pub enum ErrorKind {
     #[fail(display = "A error occurred in the key store.")]
     KeyStore,
 
+    #[fail(display = "Invalid grace period {:?}", _0)]
+    InvalidGracePeriod(Duration),
+
+    #[fail(display = "Invalid key name {:?}", _0)]
+    InvalidKeyName(String),
+
     #[fail(display = "Invalid log tail {:?}", _0)]
     InvalidLogTail(String),
 
@@ -42,17 +53,44 @@ pub enum ErrorKind {
     #[fail(display = "Invalid module type {:?}", _0)]
     InvalidModuleType(String),
 
+    #[fail(display = "Invalid secret name {:?}", _0)]
+    InvalidSecretName(String),
+
+    #[fail(display = "Expected a single PEM encoded root certificate.")]
+    InvalidTrustedRoot,
+
     #[fail(display = "Item not found.")]
     KeyStoreItemNotFound,
 
+    #[fail(display = "Could not encrypt or decrypt with the master encryption key.")]
+    MasterKeyCipher,
+
+    #[fail(display = "Could not load or save the master encryption key versions.")]
+    MasterKeyRing,
+
+    #[fail(display = "Master encryption key version {} has expired.", _0)]
+    MasterKeyVersionExpired(u32),
+
     #[fail(display = "A module runtime error occurred.")]
     ModuleRuntime,
 
+    #[fail(display = "A error occurred in the secret store.")]
+    SecretStore,
+
     #[fail(display = "Signing error occurred.")]
     Sign,
 
     #[fail(display = "Signing error occurred. Invalid key length: {}", _0)]
     SignInvalidKeyLength(usize),
+
+    #[fail(display = "A error occurred in the trust bundle.")]
+    TrustBundle,
+
+    #[fail(display = "The root certificate expired at {}.", _0)]
+    TrustedRootExpired(DateTime<Utc>),
+
+    #[fail(display = "The root certificate is not a CA certificate.")]
+    TrustedRootNotCa,
 }
 
 impl Fail for Error {
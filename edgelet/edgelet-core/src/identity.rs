@@ -4,6 +4,7 @@ use std::fmt;
 
 use failure::Fail;
 use futures::Future;
+use serde_json::Value;
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum AuthType {
@@ -68,6 +69,78 @@ impl IdentitySpec {
     }
 }
 
+/// Whose twin is read or updated: the device's own twin or the twin of one
+/// of its modules.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TwinTarget {
+    Device,
+    Module(String),
+}
+
+impl fmt::Display for TwinTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TwinTarget::Device => write!(f, "device"),
+            TwinTarget::Module(name) => write!(f, "module {}", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TwinProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    desired: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reported: Option<Value>,
+}
+
+impl TwinProperties {
+    pub fn new() -> Self {
+        TwinProperties::default()
+    }
+
+    pub fn desired(&self) -> Option<&Value> {
+        self.desired.as_ref()
+    }
+
+    pub fn with_desired(mut self, desired: Option<Value>) -> Self {
+        self.desired = desired;
+        self
+    }
+
+    pub fn reported(&self) -> Option<&Value> {
+        self.reported.as_ref()
+    }
+
+    pub fn with_reported(mut self, reported: Option<Value>) -> Self {
+        self.reported = reported;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Twin {
+    version: i64,
+    properties: TwinProperties,
+}
+
+impl Twin {
+    pub fn new(version: i64, properties: TwinProperties) -> Self {
+        Twin {
+            version,
+            properties,
+        }
+    }
+
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    pub fn properties(&self) -> &TwinProperties {
+        &self.properties
+    }
+}
+
 pub trait IdentityManager {
     type Identity: Identity;
     type Error: Fail;
@@ -76,12 +149,23 @@ pub trait IdentityManager {
     type ListFuture: Future<Item = Vec<Self::Identity>, Error = Self::Error> + Send;
     type GetFuture: Future<Item = Option<Self::Identity>, Error = Self::Error> + Send;
     type DeleteFuture: Future<Item = (), Error = Self::Error> + Send;
+    type GetTwinFuture: Future<Item = Twin, Error = Self::Error> + Send;
+    type ReportPropertiesFuture: Future<Item = (), Error = Self::Error> + Send;
 
     fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture;
     fn update(&mut self, id: IdentitySpec) -> Self::UpdateFuture;
     fn list(&self) -> Self::ListFuture;
     fn get(&self, id: IdentitySpec) -> Self::GetFuture;
     fn delete(&mut self, id: IdentitySpec) -> Self::DeleteFuture;
+    fn get_twin(&self, target: TwinTarget) -> Self::GetTwinFuture;
+    /// Patches the reported properties of the twin of `target` the way the
+    /// device or module itself would. Desired properties belong to the
+    /// solution back end and can't be changed here.
+    fn report_properties(
+        &mut self,
+        target: TwinTarget,
+        reported: Value,
+    ) -> Self::ReportPropertiesFuture;
 }
 
 // Useful for error contexts
@@ -90,7 +174,9 @@ pub enum IdentityOperation {
     CreateIdentity(String),
     DeleteIdentity(String),
     GetIdentity(String),
+    GetTwin(TwinTarget),
     ListIdentities,
+    ReportProperties(TwinTarget),
     UpdateIdentity(String),
 }
 
@@ -104,7 +190,11 @@ impl fmt::Display for IdentityOperation {
                 write!(f, "Could not delete identity {}", name)
             }
             IdentityOperation::GetIdentity(name) => write!(f, "Could not get identity {}", name),
+            IdentityOperation::GetTwin(target) => write!(f, "Could not get twin of {}", target),
             IdentityOperation::ListIdentities => write!(f, "Could not list identities"),
+            IdentityOperation::ReportProperties(target) => {
+                write!(f, "Could not report properties of {}", target)
+            }
             IdentityOperation::UpdateIdentity(name) => {
                 write!(f, "Could not update identity {}", name)
             }
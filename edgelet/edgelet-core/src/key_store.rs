@@ -0,0 +1,379 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! A key store that keeps keys in files, for devices without a TPM or an HSM.
+//!
+//! Every key is stored in its own file that only the owner can read, under
+//! `device/` for the keys of the device and `modules/<module>/` for the keys
+//! of modules. Keys can additionally be encrypted at rest.
+
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use base64;
+use failure::{Fail, ResultExt};
+use rand;
+use serde_json;
+
+use crypto::{Activate, Decrypt, Encrypt, KeyIdentity, KeyStore, MemoryKey};
+use error::{Error, ErrorKind};
+
+const DEVICE_DIR: &str = "device";
+const MODULES_DIR: &str = "modules";
+
+/// The client id that keys are encrypted for.
+const CLIENT_ID: &[u8] = b"edgelet-key-store";
+
+const INITIALIZATION_VECTOR_LEN: usize = 16;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct KeyFile {
+    #[serde(rename = "initializationVector")]
+    initialization_vector: String,
+    key: String,
+}
+
+/// Stores keys as they are, relying on file permissions alone.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unencrypted;
+
+impl Encrypt for Unencrypted {
+    type Buffer = Vec<u8>;
+
+    fn encrypt(
+        &self,
+        _client_id: &[u8],
+        plaintext: &[u8],
+        _initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, Error> {
+        Ok(plaintext.to_vec())
+    }
+}
+
+impl Decrypt for Unencrypted {
+    type Buffer = Vec<u8>;
+
+    fn decrypt(
+        &self,
+        _client_id: &[u8],
+        ciphertext: &[u8],
+        _initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, Error> {
+        Ok(ciphertext.to_vec())
+    }
+}
+
+/// A `KeyStore` whose keys are kept in files under a directory and encrypted
+/// with `crypto`.
+#[derive(Clone, Debug)]
+pub struct FileKeyStore<C> {
+    path: PathBuf,
+    crypto: C,
+}
+
+impl FileKeyStore<Unencrypted> {
+    /// Opens the key store at `path` without encrypting the keys.
+    pub fn new(path: PathBuf) -> Result<Self, Error> {
+        FileKeyStore::with_crypto(path, Unencrypted)
+    }
+}
+
+impl<C> FileKeyStore<C> {
+    /// Opens the key store at `path`, encrypting the keys with `crypto`.
+    pub fn with_crypto(path: PathBuf, crypto: C) -> Result<Self, Error> {
+        create_dir(&path).context(ErrorKind::KeyStore)?;
+        Ok(FileKeyStore { path, crypto })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn key_path(&self, identity: &KeyIdentity, key_name: &str) -> Result<PathBuf, Error> {
+        validate_name(key_name)?;
+        let dir = match *identity {
+            KeyIdentity::Device => self.path.join(DEVICE_DIR),
+            KeyIdentity::Module(ref module_id) => {
+                validate_name(module_id)?;
+                self.path.join(MODULES_DIR).join(module_id)
+            }
+        };
+        Ok(dir.join(key_name))
+    }
+}
+
+impl<C> Activate for FileKeyStore<C>
+where
+    C: Encrypt,
+{
+    type Key = MemoryKey;
+
+    fn activate_identity_key<B: AsRef<[u8]>>(
+        &mut self,
+        identity: KeyIdentity,
+        key_name: String,
+        key: B,
+    ) -> Result<(), Error> {
+        let path = self.key_path(&identity, &key_name)?;
+        let initialization_vector = rand::random::<[u8; INITIALIZATION_VECTOR_LEN]>();
+        let ciphertext = self
+            .crypto
+            .encrypt(CLIENT_ID, key.as_ref(), &initialization_vector)
+            .context(ErrorKind::KeyStore)?;
+        let file = KeyFile {
+            initialization_vector: base64::encode(&initialization_vector),
+            key: base64::encode(ciphertext.as_ref()),
+        };
+
+        // Write to a temporary file first so that a crash never leaves a key
+        // half written. Key names can't start with a dot, so the temporary
+        // file can't clash with another key.
+        let dir = path.parent().expect("key path has a parent");
+        let temp_path = dir.join(format!(".{}.tmp", key_name));
+        create_dir(dir)
+            .and_then(|_| write_key(&temp_path, &file))
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .context(ErrorKind::KeyStore)?;
+        Ok(())
+    }
+}
+
+impl<C> KeyStore for FileKeyStore<C>
+where
+    C: Decrypt,
+{
+    type Key = MemoryKey;
+
+    fn get(&self, identity: &KeyIdentity, key_name: &str) -> Result<Self::Key, Error> {
+        let path = self.key_path(identity, key_name)?;
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(Error::from(ErrorKind::KeyStoreItemNotFound));
+            }
+            Err(err) => return Err(Error::from(err.context(ErrorKind::KeyStore))),
+        };
+
+        let file: KeyFile = serde_json::from_reader(file).context(ErrorKind::KeyStore)?;
+        let initialization_vector =
+            base64::decode(&file.initialization_vector).context(ErrorKind::KeyStore)?;
+        let ciphertext = base64::decode(&file.key).context(ErrorKind::KeyStore)?;
+        let key = self
+            .crypto
+            .decrypt(CLIENT_ID, &ciphertext, &initialization_vector)
+            .context(ErrorKind::KeyStore)?;
+        Ok(MemoryKey::new(key))
+    }
+}
+
+#[cfg(unix)]
+fn create_dir(path: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(path)
+}
+
+#[cfg(not(unix))]
+fn create_dir(path: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).create(path)
+}
+
+fn write_key(path: &Path, key: &KeyFile) -> io::Result<()> {
+    // A file left over from an earlier crash keeps the permissions it was
+    // created with, so start from scratch.
+    match fs::remove_file(path) {
+        Ok(()) => (),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => return Err(err),
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path)?;
+    serde_json::to_writer(&mut file, key)?;
+    file.flush()?;
+    file.sync_all()
+}
+
+/// Module and key names become file names, so they can't be empty, start with
+/// a dot or contain path separators.
+fn validate_name(name: &str) -> Result<(), Error> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || name.contains(|c: char| c == '/' || c == '\\' || c == '\0')
+    {
+        Err(Error::from(ErrorKind::InvalidKeyName(name.to_string())))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use crypto::{Sign, Signature, SignatureAlgorithm};
+
+    #[derive(Clone, Debug, Default)]
+    struct TestCrypto;
+
+    fn xor(data: &[u8], initialization_vector: &[u8]) -> Vec<u8> {
+        data.iter()
+            .map(|b| b ^ initialization_vector[0] ^ 0x5a)
+            .collect()
+    }
+
+    impl Encrypt for TestCrypto {
+        type Buffer = Vec<u8>;
+
+        fn encrypt(
+            &self,
+            _client_id: &[u8],
+            plaintext: &[u8],
+            initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            Ok(xor(plaintext, initialization_vector))
+        }
+    }
+
+    impl Decrypt for TestCrypto {
+        type Buffer = Vec<u8>;
+
+        fn decrypt(
+            &self,
+            _client_id: &[u8],
+            ciphertext: &[u8],
+            initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            Ok(xor(ciphertext, initialization_vector))
+        }
+    }
+
+    fn sign(key: &MemoryKey) -> Vec<u8> {
+        key.sign(SignatureAlgorithm::HMACSHA256, b"data")
+            .unwrap()
+            .as_bytes()
+            .to_vec()
+    }
+
+    #[test]
+    fn activate_and_get_keys() {
+        let dir = TempDir::new("keys").unwrap();
+        let mut store = FileKeyStore::new(dir.path().join("keys")).unwrap();
+
+        store
+            .activate_identity_key(KeyIdentity::Device, "primary".to_string(), b"device key")
+            .unwrap();
+        store
+            .activate_identity_key(
+                KeyIdentity::Module("m1".to_string()),
+                "primary".to_string(),
+                b"module key",
+            )
+            .unwrap();
+
+        let key = store.get(&KeyIdentity::Device, "primary").unwrap();
+        assert_eq!(b"device key", key.as_ref());
+        assert_eq!(sign(&MemoryKey::new(b"device key")), sign(&key));
+        let key = store
+            .get(&KeyIdentity::Module("m1".to_string()), "primary")
+            .unwrap();
+        assert_eq!(b"module key", key.as_ref());
+    }
+
+    #[test]
+    fn keys_survive_reopening() {
+        let dir = TempDir::new("keys").unwrap();
+        FileKeyStore::new(dir.path().to_path_buf())
+            .unwrap()
+            .activate_identity_key(KeyIdentity::Device, "primary".to_string(), b"key")
+            .unwrap();
+
+        let store = FileKeyStore::new(dir.path().to_path_buf()).unwrap();
+
+        assert_eq!(
+            b"key",
+            store.get(&KeyIdentity::Device, "primary").unwrap().as_ref()
+        );
+    }
+
+    #[test]
+    fn get_unknown_key_is_not_found() {
+        let dir = TempDir::new("keys").unwrap();
+        let store = FileKeyStore::new(dir.path().to_path_buf()).unwrap();
+
+        match *store
+            .get(&KeyIdentity::Device, "primary")
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::KeyStoreItemNotFound => (),
+            ref kind => panic!("unexpected error {}", kind),
+        }
+    }
+
+    #[test]
+    fn encrypted_keys_are_not_stored_in_plain_text() {
+        let dir = TempDir::new("keys").unwrap();
+        let mut store = FileKeyStore::with_crypto(dir.path().to_path_buf(), TestCrypto).unwrap();
+
+        store
+            .activate_identity_key(KeyIdentity::Device, "primary".to_string(), b"secret key")
+            .unwrap();
+
+        let stored = fs::read_to_string(dir.path().join("device").join("primary")).unwrap();
+        assert!(!stored.contains(&base64::encode(b"secret key")));
+        assert_eq!(
+            b"secret key",
+            store.get(&KeyIdentity::Device, "primary").unwrap().as_ref()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keys_are_only_readable_by_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("keys").unwrap();
+        let mut store = FileKeyStore::new(dir.path().join("keys")).unwrap();
+
+        store
+            .activate_identity_key(KeyIdentity::Device, "primary".to_string(), b"key")
+            .unwrap();
+
+        let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(0o700, mode(dir.path().join("keys")));
+        assert_eq!(0o700, mode(dir.path().join("keys").join("device")));
+        assert_eq!(
+            0o600,
+            mode(dir.path().join("keys").join("device").join("primary"))
+        );
+    }
+
+    #[test]
+    fn names_cannot_escape_store() {
+        let dir = TempDir::new("keys").unwrap();
+        let mut store = FileKeyStore::new(dir.path().to_path_buf()).unwrap();
+
+        for &(ref identity, key_name) in &[
+            (KeyIdentity::Device, ""),
+            (KeyIdentity::Device, ".hidden"),
+            (KeyIdentity::Device, "../primary"),
+            (KeyIdentity::Module("..".to_string()), "primary"),
+            (KeyIdentity::Module("m1/..".to_string()), "primary"),
+        ] {
+            let err = store
+                .activate_identity_key(identity.clone(), key_name.to_string(), b"key")
+                .unwrap_err();
+            match *err.kind() {
+                ErrorKind::InvalidKeyName(_) => (),
+                ref kind => panic!("unexpected error {}", kind),
+            }
+        }
+    }
+}
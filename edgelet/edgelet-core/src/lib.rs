@@ -9,49 +9,71 @@
 #![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
 #![cfg_attr(feature = "cargo-clippy", allow(stutter, use_self))]
 
-#[cfg(test)]
 extern crate base64;
 extern crate bytes;
 extern crate chrono;
 extern crate consistenttime;
 extern crate failure;
+extern crate foreign_types;
 extern crate futures;
 extern crate hmac;
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
 extern crate log;
+extern crate openssl;
+extern crate openssl_sys;
+extern crate rand;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
 extern crate sha2;
+#[cfg(test)]
+extern crate tempdir;
 extern crate tokio;
 
 extern crate edgelet_utils;
 
 mod authorization;
+pub mod certificate_notifications;
 mod certificate_properties;
 pub mod crypto;
 mod error;
 mod identity;
+pub mod key_store;
+pub mod master_key;
 mod module;
 pub mod pid;
+pub mod secret;
+pub mod trust_bundle;
 pub mod watchdog;
 pub mod workload;
 
 pub use authorization::{Authorization, Policy};
+pub use certificate_notifications::{
+    CertificateNotification, CertificateNotifier, NotificationKind,
+};
 pub use certificate_properties::{CertificateIssuer, CertificateProperties, CertificateType};
 pub use crypto::{
-    Certificate, CreateCertificate, Decrypt, Encrypt, GetTrustBundle, KeyBytes, KeyIdentity,
-    KeyStore, MasterEncryptionKey, PrivateKey, Signature, IOTEDGED_CA_ALIAS,
+    AliasedEncryptionKey, Certificate, CreateCertificate, Decrypt, Encrypt, GetTrustBundle,
+    KeyBytes, KeyIdentity, KeyStore, MasterEncryptionKey, MasterKeyVersion, PrivateKey,
+    RotateMasterEncryptionKey, Signature, IOTEDGED_CA_ALIAS,
 };
 pub use error::{Error, ErrorKind};
-pub use identity::{AuthType, Identity, IdentityManager, IdentityOperation, IdentitySpec};
+pub use identity::{
+    AuthType, Identity, IdentityManager, IdentityOperation, IdentitySpec, Twin, TwinProperties,
+    TwinTarget,
+};
+pub use key_store::{FileKeyStore, Unencrypted};
+pub use master_key::VersionedCrypto;
 pub use module::{
-    LogOptions, LogTail, Module, ModuleOperation, ModuleRegistry, ModuleRuntime,
-    ModuleRuntimeErrorReason, ModuleRuntimeState, ModuleSpec, ModuleStatus, RegistryOperation,
-    RuntimeOperation, SystemInfo,
+    ExecOptions, ExecStatus, HealthStatus, LogOptions, LogTail, Module, ModuleHealth,
+    ModuleOperation, ModuleRegistry, ModuleRuntime, ModuleRuntimeErrorReason, ModuleRuntimeState,
+    ModuleSpec, ModuleStatus, ModuleVolume, RegistryOperation, RuntimeOperation, SystemInfo,
+    NEXT_VERSION_SUFFIX,
 };
+pub use secret::{SecretStore, SecretWipingIdentityManager};
+pub use trust_bundle::{TrustBundle, TrustBundleStore, TrustedRoot};
 pub use workload::WorkloadConfig;
 
 lazy_static! {
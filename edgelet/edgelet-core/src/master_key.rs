@@ -0,0 +1,898 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Versioned master encryption keys.
+//!
+//! Every version but the first has a random data key of its own. Clients get
+//! keys derived from the data key of the current version, and encryption
+//! happens in software with AES-256-GCM. The data keys are kept on disk
+//! wrapped by a key of the HSM, which is replaced on rotation, so that neither
+//! an old HSM key nor an old data key exposes what later versions encrypt. The data key of a version is forgotten once
+//! the version expires, after which nothing it encrypted can be decrypted
+//! anymore.
+//!
+//! Version 0 is the HSM master key as it was used before versions existed,
+//! so ciphertexts that predate them keep working until it is rotated out.
+//! Since it can't be re-wrapped, the HSM master key is only replaced by the
+//! first rotation after version 0 has expired. Replacement keys get an alias
+//! of their own, and a key is only destroyed once the data keys have been
+//! wrapped by its successor and saved. Every ciphertext starts with a tag
+//! naming the version that produced it.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64;
+use chrono::{self, Utc};
+use failure::{Fail, ResultExt};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use rand;
+use serde_json;
+
+use certificate_properties::CertificateProperties;
+use crypto::{
+    AliasedEncryptionKey, CreateCertificate, Decrypt, Encrypt, GetTrustBundle, MasterEncryptionKey,
+    MasterKeyVersion, RotateMasterEncryptionKey,
+};
+use error::{Error, ErrorKind};
+
+/// Marks a ciphertext as produced by a versioned master key. It is followed
+/// by the version as a big endian `u32`.
+const VERSION_TAG: &[u8] = b"\xedMK\x01";
+const VERSION_TAG_LEN: usize = 8;
+
+/// The client id that the data keys are wrapped for by the HSM.
+const WRAP_CLIENT_ID: &[u8] = b"edgelet-master-key";
+
+/// The HSM keys that replace the master key are named after the version they
+/// were created for.
+const KEY_ALIAS_PREFIX: &str = "edgelet-master-v";
+
+const DATA_KEY_LEN: usize = 32;
+const INITIALIZATION_VECTOR_LEN: usize = 16;
+const AUTH_TAG_LEN: usize = 16;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct WrappedSecret {
+    #[serde(rename = "initializationVector")]
+    initialization_vector: String,
+    secret: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct KeyEntry {
+    #[serde(flatten)]
+    version: MasterKeyVersion,
+    wrapped: Option<WrappedSecret>,
+    #[serde(skip)]
+    secret: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct KeyRing {
+    /// The HSM key that wraps the data keys, or none for the master key.
+    #[serde(rename = "keyAlias", default, skip_serializing_if = "Option::is_none")]
+    key_alias: Option<String>,
+    versions: Vec<KeyEntry>,
+}
+
+impl Default for KeyRing {
+    fn default() -> Self {
+        KeyRing {
+            key_alias: None,
+            versions: vec![KeyEntry {
+                version: MasterKeyVersion::new(0, Utc::now()),
+                wrapped: None,
+                secret: None,
+            }],
+        }
+    }
+}
+
+impl KeyRing {
+    fn current(&self) -> &KeyEntry {
+        self.versions
+            .last()
+            .expect("key ring always has a current version")
+    }
+
+    fn find(&self, version: u32) -> Option<&KeyEntry> {
+        let now = Utc::now();
+        self.versions
+            .iter()
+            .find(|entry| entry.version.version() == version && !entry.version.is_expired(now))
+    }
+
+    /// Whether a version still encrypts with the HSM master key directly.
+    fn uses_master_key(&self) -> bool {
+        self.versions.iter().any(|entry| entry.secret.is_none())
+    }
+}
+
+/// Encrypts with the current version of the master key and decrypts with
+/// any version that hasn't expired. Clones share their keys.
+#[derive(Clone)]
+pub struct VersionedCrypto<C> {
+    inner: C,
+    path: PathBuf,
+    ring: Arc<Mutex<KeyRing>>,
+}
+
+impl<C> VersionedCrypto<C>
+where
+    C: Encrypt + Decrypt + AliasedEncryptionKey,
+{
+    /// Loads the key versions kept in the file at `path`. The data keys of
+    /// the versions are unwrapped with `inner`, so the key that wrapped them
+    /// must exist.
+    pub fn new(inner: C, path: PathBuf) -> Result<Self, Error> {
+        let (mut ring, created) = match File::open(&path) {
+            Ok(file) => (
+                serde_json::from_reader(file).context(ErrorKind::MasterKeyRing)?,
+                false,
+            ),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => (KeyRing::default(), true),
+            Err(err) => return Err(Error::from(err.context(ErrorKind::MasterKeyRing))),
+        };
+        if created {
+            save_ring(&path, &ring)?;
+        }
+
+        let now = Utc::now();
+        ring.versions.retain(|entry| !entry.version.is_expired(now));
+        for entry in &mut ring.versions {
+            entry.secret = match entry.wrapped {
+                Some(ref wrapped) => Some(unwrap_secret(
+                    &inner,
+                    ring.key_alias.as_ref().map(String::as_str),
+                    wrapped,
+                )?),
+                None => None,
+            };
+        }
+
+        Ok(VersionedCrypto {
+            inner,
+            path,
+            ring: Arc::new(Mutex::new(ring)),
+        })
+    }
+}
+
+impl<C> VersionedCrypto<C> {
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn save_ring(path: &Path, ring: &KeyRing) -> Result<(), Error> {
+    let temp_path = path.with_extension("tmp");
+    write_ring(&temp_path, ring)
+        .and_then(|_| fs::rename(&temp_path, path))
+        .context(ErrorKind::MasterKeyRing)?;
+    Ok(())
+}
+
+fn write_ring(path: &Path, ring: &KeyRing) -> io::Result<()> {
+    // A file left over from an earlier crash keeps the permissions it was
+    // created with, so start from scratch.
+    match fs::remove_file(path) {
+        Ok(()) => (),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => return Err(err),
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path)?;
+    serde_json::to_writer(&mut file, ring)?;
+    file.flush()?;
+    file.sync_all()
+}
+
+fn wrap_secret<C>(inner: &C, key_alias: Option<&str>, secret: &[u8]) -> Result<WrappedSecret, Error>
+where
+    C: Encrypt + AliasedEncryptionKey,
+{
+    let initialization_vector = rand::random::<[u8; INITIALIZATION_VECTOR_LEN]>();
+    let wrapped = match key_alias {
+        Some(alias) => inner
+            .encrypt_with_key(alias, WRAP_CLIENT_ID, secret, &initialization_vector)
+            .map(|wrapped| wrapped.as_ref().to_vec()),
+        None => inner
+            .encrypt(WRAP_CLIENT_ID, secret, &initialization_vector)
+            .map(|wrapped| wrapped.as_ref().to_vec()),
+    }
+    .context(ErrorKind::MasterKeyRing)?;
+    Ok(WrappedSecret {
+        initialization_vector: base64::encode(&initialization_vector),
+        secret: base64::encode(&wrapped),
+    })
+}
+
+fn unwrap_secret<C>(
+    inner: &C,
+    key_alias: Option<&str>,
+    wrapped: &WrappedSecret,
+) -> Result<Vec<u8>, Error>
+where
+    C: Decrypt + AliasedEncryptionKey,
+{
+    let initialization_vector =
+        base64::decode(&wrapped.initialization_vector).context(ErrorKind::MasterKeyRing)?;
+    let secret = base64::decode(&wrapped.secret).context(ErrorKind::MasterKeyRing)?;
+    let secret = match key_alias {
+        Some(alias) => inner
+            .decrypt_with_key(alias, WRAP_CLIENT_ID, &secret, &initialization_vector)
+            .map(|secret| secret.as_ref().to_vec()),
+        None => inner
+            .decrypt(WRAP_CLIENT_ID, &secret, &initialization_vector)
+            .map(|secret| secret.as_ref().to_vec()),
+    }
+    .context(ErrorKind::MasterKeyRing)?;
+    Ok(secret)
+}
+
+/// Every client gets a key of its own, derived from the data key.
+fn client_key(data_key: &[u8], client_id: &[u8]) -> Result<Vec<u8>, Error> {
+    let key = PKey::hmac(data_key)
+        .and_then(|key| {
+            let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+            signer.update(client_id)?;
+            signer.sign_to_vec()
+        })
+        .context(ErrorKind::MasterKeyCipher)?;
+    Ok(key)
+}
+
+fn encrypt_with(
+    data_key: &[u8],
+    client_id: &[u8],
+    plaintext: &[u8],
+    initialization_vector: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if initialization_vector.is_empty() {
+        return Err(Error::from(ErrorKind::MasterKeyCipher));
+    }
+    let key = client_key(data_key, client_id)?;
+
+    let mut tag = [0; AUTH_TAG_LEN];
+    let encrypted = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        &key,
+        Some(initialization_vector),
+        &[],
+        plaintext,
+        &mut tag,
+    )
+    .context(ErrorKind::MasterKeyCipher)?;
+
+    let mut ciphertext = Vec::with_capacity(AUTH_TAG_LEN + encrypted.len());
+    ciphertext.extend_from_slice(&tag);
+    ciphertext.extend_from_slice(&encrypted);
+    Ok(ciphertext)
+}
+
+fn decrypt_with(
+    data_key: &[u8],
+    client_id: &[u8],
+    ciphertext: &[u8],
+    initialization_vector: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if initialization_vector.is_empty() || ciphertext.len() < AUTH_TAG_LEN {
+        return Err(Error::from(ErrorKind::MasterKeyCipher));
+    }
+    let key = client_key(data_key, client_id)?;
+
+    let (tag, encrypted) = ciphertext.split_at(AUTH_TAG_LEN);
+    let plaintext = decrypt_aead(
+        Cipher::aes_256_gcm(),
+        &key,
+        Some(initialization_vector),
+        &[],
+        encrypted,
+        tag,
+    )
+    .context(ErrorKind::MasterKeyCipher)?;
+    Ok(plaintext)
+}
+
+impl<C> Encrypt for VersionedCrypto<C>
+where
+    C: Encrypt,
+{
+    type Buffer = Vec<u8>;
+
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn encrypt(
+        &self,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, Error> {
+        let ring = self.ring.lock().unwrap();
+        let current = ring.current();
+
+        let ciphertext = match current.secret {
+            Some(ref data_key) => {
+                encrypt_with(data_key, client_id, plaintext, initialization_vector)?
+            }
+            None => self
+                .inner
+                .encrypt(client_id, plaintext, initialization_vector)?
+                .as_ref()
+                .to_vec(),
+        };
+
+        let mut tagged = Vec::with_capacity(VERSION_TAG_LEN + ciphertext.len());
+        tagged.extend_from_slice(VERSION_TAG);
+        let version = current.version.version();
+        for shift in &[24, 16, 8, 0] {
+            tagged.push((version >> shift) as u8);
+        }
+        tagged.extend_from_slice(&ciphertext);
+        Ok(tagged)
+    }
+}
+
+impl<C> Decrypt for VersionedCrypto<C>
+where
+    C: Decrypt,
+{
+    type Buffer = Vec<u8>;
+
+    fn decrypt(
+        &self,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, Error> {
+        // Ciphertexts without a tag predate versions and belong to version 0.
+        let (version, ciphertext) =
+            if ciphertext.len() >= VERSION_TAG_LEN && ciphertext.starts_with(VERSION_TAG) {
+                let version = ciphertext[VERSION_TAG.len()..VERSION_TAG_LEN]
+                    .iter()
+                    .fold(0, |version, b| (version << 8) | u32::from(*b));
+                (version, &ciphertext[VERSION_TAG_LEN..])
+            } else {
+                (0, ciphertext)
+            };
+
+        let ring = self.ring.lock().unwrap();
+        let entry = ring
+            .find(version)
+            .ok_or_else(|| Error::from(ErrorKind::MasterKeyVersionExpired(version)))?;
+        match entry.secret {
+            Some(ref data_key) => {
+                decrypt_with(data_key, client_id, ciphertext, initialization_vector)
+            }
+            None => Ok(self
+                .inner
+                .decrypt(client_id, ciphertext, initialization_vector)?
+                .as_ref()
+                .to_vec()),
+        }
+    }
+}
+
+impl<C> VersionedCrypto<C>
+where
+    C: Encrypt + MasterEncryptionKey + AliasedEncryptionKey,
+{
+    /// Builds the ring that follows `ring` once the current version is
+    /// retired in favor of `created`, with `data_key` as its data key. With a
+    /// `key_alias`, every data key is wrapped anew by the HSM key of that
+    /// alias.
+    fn next_ring(
+        &self,
+        ring: &KeyRing,
+        key_alias: Option<String>,
+        created: &MasterKeyVersion,
+        data_key: Vec<u8>,
+        expires: chrono::DateTime<Utc>,
+    ) -> Result<KeyRing, Error> {
+        let mut next = KeyRing {
+            key_alias: ring.key_alias.clone(),
+            versions: ring.versions.clone(),
+        };
+        if let Some(key_alias) = key_alias {
+            for entry in &mut next.versions {
+                if let Some(ref secret) = entry.secret {
+                    entry.wrapped = Some(wrap_secret(&self.inner, Some(&key_alias), secret)?);
+                }
+            }
+            next.key_alias = Some(key_alias);
+        }
+        let wrapped = wrap_secret(
+            &self.inner,
+            next.key_alias.as_ref().map(String::as_str),
+            &data_key,
+        )?;
+
+        if let Some(current) = next.versions.last_mut() {
+            current.version.retire(created.created(), expires);
+        }
+        next.versions.push(KeyEntry {
+            version: created.clone(),
+            wrapped: Some(wrapped),
+            secret: Some(data_key),
+        });
+        Ok(next)
+    }
+
+    fn destroy_hsm_key(&self, key_alias: Option<&str>) -> Result<(), Error> {
+        match key_alias {
+            Some(alias) => self.inner.destroy_aliased_key(alias),
+            None => self.inner.destroy_key(),
+        }
+    }
+}
+
+impl<C> RotateMasterEncryptionKey for VersionedCrypto<C>
+where
+    C: Encrypt + MasterEncryptionKey + AliasedEncryptionKey,
+{
+    fn key_versions(&self) -> Result<Vec<MasterKeyVersion>, Error> {
+        let now = Utc::now();
+        let ring = self.ring.lock().unwrap();
+        Ok(ring
+            .versions
+            .iter()
+            .map(|entry| entry.version.clone())
+            .filter(|version| !version.is_expired(now))
+            .collect())
+    }
+
+    fn rotate_key(&self, grace_period: Duration) -> Result<MasterKeyVersion, Error> {
+        let now = Utc::now();
+        let expires = chrono::Duration::from_std(grace_period)
+            .ok()
+            .and_then(|grace_period| now.checked_add_signed(grace_period))
+            .ok_or_else(|| Error::from(ErrorKind::InvalidGracePeriod(grace_period)))?;
+
+        let mut ring = self.ring.lock().unwrap();
+        let version = ring.current().version.version() + 1;
+        let created = MasterKeyVersion::new(version, now);
+
+        let data_key = rand::random::<[u8; DATA_KEY_LEN]>().to_vec();
+
+        ring.versions.retain(|entry| !entry.version.is_expired(now));
+
+        // The HSM key is replaced by one under a new alias. The ring in
+        // memory and the old key are only given up once the data keys have
+        // been wrapped by the new key and saved, so that a failure on the way
+        // leaves everything as it was.
+        let key_alias = if ring.uses_master_key() {
+            None
+        } else {
+            let alias = format!("{}{}", KEY_ALIAS_PREFIX, version);
+            self.inner.create_aliased_key(&alias)?;
+            Some(alias)
+        };
+
+        let next = self
+            .next_ring(&ring, key_alias.clone(), &created, data_key, expires)
+            .and_then(|next| {
+                save_ring(&self.path, &next)?;
+                Ok(next)
+            });
+        let next = match next {
+            Ok(next) => next,
+            Err(err) => {
+                if let Some(alias) = key_alias {
+                    if let Err(err) = self.inner.destroy_aliased_key(&alias) {
+                        warn!(
+                            "Could not destroy the unused master encryption key {}: {}",
+                            alias, err
+                        );
+                    }
+                }
+                return Err(err);
+            }
+        };
+
+        let previous_alias = ring.key_alias.take();
+        *ring = next;
+        if key_alias.is_some() {
+            if let Err(err) = self.destroy_hsm_key(previous_alias.as_ref().map(String::as_str)) {
+                warn!(
+                    "Could not destroy the replaced master encryption key: {}",
+                    err
+                );
+            }
+        }
+
+        info!(
+            "Rotated master encryption key to version {}, the previous version expires at {}",
+            version,
+            expires.to_rfc3339()
+        );
+        Ok(created)
+    }
+}
+
+impl<C> MasterEncryptionKey for VersionedCrypto<C>
+where
+    C: MasterEncryptionKey + AliasedEncryptionKey,
+{
+    fn create_key(&self) -> Result<(), Error> {
+        self.inner.create_key()
+    }
+
+    /// Destroys the master key and with it every version.
+    fn destroy_key(&self) -> Result<(), Error> {
+        let mut ring = self.ring.lock().unwrap();
+        if let Some(ref alias) = ring.key_alias {
+            self.inner.destroy_aliased_key(alias)?;
+        }
+        self.inner.destroy_key()?;
+
+        *ring = KeyRing::default();
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(Error::from(err.context(ErrorKind::MasterKeyRing))),
+        }
+    }
+}
+
+impl<C> CreateCertificate for VersionedCrypto<C>
+where
+    C: CreateCertificate,
+{
+    type Certificate = C::Certificate;
+
+    fn create_certificate(
+        &self,
+        properties: &CertificateProperties,
+    ) -> Result<Self::Certificate, Error> {
+        self.inner.create_certificate(properties)
+    }
+
+    fn destroy_certificate(&self, alias: String) -> Result<(), Error> {
+        self.inner.destroy_certificate(alias)
+    }
+}
+
+impl<C> GetTrustBundle for VersionedCrypto<C>
+where
+    C: GetTrustBundle,
+{
+    type Certificate = C::Certificate;
+
+    fn get_trust_bundle(&self) -> Result<Self::Certificate, Error> {
+        self.inner.get_trust_bundle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::thread;
+
+    use tempdir::TempDir;
+
+    const MASTER_KEY: &str = "master";
+
+    /// "Encrypts" by xor-ing with a byte derived from the whole client id and
+    /// the generation of the key, so that decrypting with another client id
+    /// or another key yields garbage. Every key that is created gets a new
+    /// generation, and using a key that doesn't exist fails.
+    #[derive(Clone, Debug)]
+    struct TestCrypto {
+        generation: Arc<Mutex<u8>>,
+        keys: Arc<Mutex<HashMap<String, u8>>>,
+        fail_wrap: Arc<Mutex<bool>>,
+    }
+
+    impl Default for TestCrypto {
+        fn default() -> Self {
+            let crypto = TestCrypto {
+                generation: Arc::new(Mutex::new(0)),
+                keys: Arc::new(Mutex::new(HashMap::new())),
+                fail_wrap: Arc::new(Mutex::new(false)),
+            };
+            crypto.create_key().unwrap();
+            crypto
+        }
+    }
+
+    impl TestCrypto {
+        fn keys(&self) -> Vec<String> {
+            let mut keys: Vec<_> = self.keys.lock().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        }
+
+        fn fail_wrap(&self, fail: bool) {
+            *self.fail_wrap.lock().unwrap() = fail;
+        }
+
+        fn xor(&self, alias: &str, client_id: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+            let generation = *self
+                .keys
+                .lock()
+                .unwrap()
+                .get(alias)
+                .ok_or_else(|| Error::from(ErrorKind::KeyStore))?;
+            let key = client_id
+                .iter()
+                .fold(generation, |acc, b| acc.rotate_left(1) ^ b);
+            Ok(data.iter().map(|b| b ^ key).collect())
+        }
+    }
+
+    impl Encrypt for TestCrypto {
+        type Buffer = Vec<u8>;
+
+        fn encrypt(
+            &self,
+            client_id: &[u8],
+            plaintext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            self.xor(MASTER_KEY, client_id, plaintext)
+        }
+    }
+
+    impl Decrypt for TestCrypto {
+        type Buffer = Vec<u8>;
+
+        fn decrypt(
+            &self,
+            client_id: &[u8],
+            ciphertext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            self.xor(MASTER_KEY, client_id, ciphertext)
+        }
+    }
+
+    impl MasterEncryptionKey for TestCrypto {
+        fn create_key(&self) -> Result<(), Error> {
+            if !self.keys.lock().unwrap().contains_key(MASTER_KEY) {
+                self.create_aliased_key(MASTER_KEY)?;
+            }
+            Ok(())
+        }
+
+        fn destroy_key(&self) -> Result<(), Error> {
+            self.destroy_aliased_key(MASTER_KEY)
+        }
+    }
+
+    impl AliasedEncryptionKey for TestCrypto {
+        type Buffer = Vec<u8>;
+
+        fn create_aliased_key(&self, alias: &str) -> Result<(), Error> {
+            let mut generation = self.generation.lock().unwrap();
+            *generation = generation.wrapping_add(1);
+            self.keys
+                .lock()
+                .unwrap()
+                .insert(alias.to_string(), *generation);
+            Ok(())
+        }
+
+        fn destroy_aliased_key(&self, alias: &str) -> Result<(), Error> {
+            self.keys.lock().unwrap().remove(alias);
+            Ok(())
+        }
+
+        fn encrypt_with_key(
+            &self,
+            alias: &str,
+            client_id: &[u8],
+            plaintext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            if *self.fail_wrap.lock().unwrap() {
+                return Err(Error::from(ErrorKind::KeyStore));
+            }
+            self.xor(alias, client_id, plaintext)
+        }
+
+        fn decrypt_with_key(
+            &self,
+            alias: &str,
+            client_id: &[u8],
+            ciphertext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            self.xor(alias, client_id, ciphertext)
+        }
+    }
+
+    fn crypto(dir: &TempDir, inner: &TestCrypto) -> VersionedCrypto<TestCrypto> {
+        VersionedCrypto::new(inner.clone(), dir.path().join("master_keys.json")).unwrap()
+    }
+
+    #[test]
+    fn encrypt_tags_ciphertext_with_version() {
+        let dir = TempDir::new("master_key").unwrap();
+        let crypto = crypto(&dir, &TestCrypto::default());
+
+        let ciphertext = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+
+        assert_eq!(b"\xedMK\x01\0\0\0\0", &ciphertext[..VERSION_TAG_LEN]);
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &ciphertext, b"iv").unwrap()
+        );
+    }
+
+    #[test]
+    fn untagged_ciphertext_belongs_to_version_0() {
+        let dir = TempDir::new("master_key").unwrap();
+        let inner = TestCrypto::default();
+        let crypto = crypto(&dir, &inner);
+        let legacy = inner.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &legacy, b"iv").unwrap()
+        );
+    }
+
+    #[test]
+    fn rotated_key_decrypts_old_ciphertext_until_grace_period_ends() {
+        let dir = TempDir::new("master_key").unwrap();
+        let crypto = crypto(&dir, &TestCrypto::default());
+        let old = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+
+        let version = crypto.rotate_key(Duration::from_secs(60)).unwrap();
+        assert_eq!(1, version.version());
+        let new = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+        assert_eq!(b"\xedMK\x01\0\0\0\x01", &new[..VERSION_TAG_LEN]);
+        assert_ne!(old[VERSION_TAG_LEN..], new[VERSION_TAG_LEN..]);
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &old, b"iv").unwrap()
+        );
+
+        crypto.rotate_key(Duration::from_secs(0)).unwrap();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &old, b"iv").unwrap()
+        );
+        match *crypto.decrypt(b"m1", &new, b"iv").unwrap_err().kind() {
+            ErrorKind::MasterKeyVersionExpired(1) => (),
+            ref kind => panic!("unexpected error {}", kind),
+        }
+    }
+
+    #[test]
+    fn versions_survive_restart() {
+        let dir = TempDir::new("master_key").unwrap();
+        let inner = TestCrypto::default();
+        let ciphertext = {
+            let crypto = crypto(&dir, &inner);
+            crypto.rotate_key(Duration::from_secs(60)).unwrap();
+            crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap()
+        };
+
+        let crypto = crypto(&dir, &inner);
+
+        let versions: Vec<_> = crypto
+            .key_versions()
+            .unwrap()
+            .iter()
+            .map(|version| (version.version(), version.is_current()))
+            .collect();
+        assert_eq!(vec![(0, false), (1, true)], versions);
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &ciphertext, b"iv").unwrap()
+        );
+    }
+
+    #[test]
+    fn rotation_keeps_master_key_while_version_0_is_alive() {
+        let dir = TempDir::new("master_key").unwrap();
+        let inner = TestCrypto::default();
+        let crypto = crypto(&dir, &inner);
+
+        crypto.rotate_key(Duration::from_secs(60)).unwrap();
+        crypto.rotate_key(Duration::from_secs(60)).unwrap();
+
+        assert_eq!(vec![MASTER_KEY.to_string()], inner.keys());
+    }
+
+    #[test]
+    fn rotation_replaces_master_key_and_rewraps_data_keys() {
+        let dir = TempDir::new("master_key").unwrap();
+        let inner = TestCrypto::default();
+        let crypto = crypto(&dir, &inner);
+        crypto.rotate_key(Duration::from_secs(0)).unwrap();
+        let v1 = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+        thread::sleep(Duration::from_millis(10));
+
+        let version = crypto.rotate_key(Duration::from_secs(60)).unwrap();
+        assert_eq!(2, version.version());
+        assert_eq!(vec!["edgelet-master-v2".to_string()], inner.keys());
+        let v2 = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+
+        crypto.rotate_key(Duration::from_secs(60)).unwrap();
+        assert_eq!(vec!["edgelet-master-v3".to_string()], inner.keys());
+
+        // The data keys are unwrapped with the new key on restart.
+        let crypto = self::crypto(&dir, &inner);
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &v1, b"iv").unwrap()
+        );
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &v2, b"iv").unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_rotation_keeps_master_key_and_versions() {
+        let dir = TempDir::new("master_key").unwrap();
+        let inner = TestCrypto::default();
+        let crypto = crypto(&dir, &inner);
+        crypto.rotate_key(Duration::from_secs(0)).unwrap();
+        let v1 = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+        thread::sleep(Duration::from_millis(10));
+
+        inner.fail_wrap(true);
+        crypto.rotate_key(Duration::from_secs(60)).unwrap_err();
+        inner.fail_wrap(false);
+
+        // The new key is gone again, and the old one still wraps the ring.
+        assert_eq!(vec![MASTER_KEY.to_string()], inner.keys());
+        let current = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+        assert_eq!(b"\xedMK\x01\0\0\0\x01", &current[..VERSION_TAG_LEN]);
+
+        let crypto = self::crypto(&dir, &inner);
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto.decrypt(b"m1", &v1, b"iv").unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn key_ring_is_only_readable_by_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("master_key").unwrap();
+        let crypto = crypto(&dir, &TestCrypto::default());
+        crypto.rotate_key(Duration::from_secs(60)).unwrap();
+
+        let mode = fs::metadata(crypto.path()).unwrap().permissions().mode() & 0o777;
+        assert_eq!(0o600, mode);
+    }
+
+    #[test]
+    fn data_keys_differ_between_versions() {
+        let dir = TempDir::new("master_key").unwrap();
+        let crypto = crypto(&dir, &TestCrypto::default());
+        crypto.rotate_key(Duration::from_secs(60)).unwrap();
+        let v1 = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+
+        crypto.rotate_key(Duration::from_secs(60)).unwrap();
+        let v2 = crypto.encrypt(b"m1", b"plaintext", b"iv").unwrap();
+
+        assert_ne!(v1[VERSION_TAG_LEN..], v2[VERSION_TAG_LEN..]);
+        assert_ne!(&b"plaintext"[..], &v2[VERSION_TAG_LEN + AUTH_TAG_LEN..]);
+    }
+}
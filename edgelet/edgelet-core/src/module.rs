@@ -13,6 +13,7 @@ use failure::{Fail, ResultExt};
 use futures::{Future, Stream};
 use pid::Pid;
 use serde_json;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use edgelet_utils::{ensure_not_empty_with_context, serialize_ordered};
 
@@ -47,6 +48,74 @@ impl fmt::Display for ModuleStatus {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+impl FromStr for HealthStatus {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        serde_json::from_str(&format!("\"{}\"", s))
+    }
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}",
+            serde_json::to_string(self)
+                .map(|s| s.trim_matches('"').to_string())
+                .map_err(|_| fmt::Error)?
+        )
+    }
+}
+
+/// The result of the health check configured on a module, if it has one.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ModuleHealth {
+    status: HealthStatus,
+    failing_streak: u32,
+    last_output: Option<String>,
+}
+
+impl ModuleHealth {
+    pub fn new(status: HealthStatus) -> Self {
+        ModuleHealth {
+            status,
+            failing_streak: 0,
+            last_output: None,
+        }
+    }
+
+    pub fn status(&self) -> HealthStatus {
+        self.status
+    }
+
+    pub fn failing_streak(&self) -> u32 {
+        self.failing_streak
+    }
+
+    pub fn with_failing_streak(mut self, failing_streak: u32) -> Self {
+        self.failing_streak = failing_streak;
+        self
+    }
+
+    pub fn last_output(&self) -> Option<&str> {
+        self.last_output.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_last_output(mut self, last_output: Option<String>) -> Self {
+        self.last_output = last_output;
+        self
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ModuleRuntimeState {
     status: ModuleStatus,
@@ -56,6 +125,7 @@ pub struct ModuleRuntimeState {
     finished_at: Option<DateTime<Utc>>,
     image_id: Option<String>,
     pid: Pid,
+    health: Option<ModuleHealth>,
 }
 
 impl Default for ModuleRuntimeState {
@@ -68,6 +138,7 @@ impl Default for ModuleRuntimeState {
             finished_at: None,
             image_id: None,
             pid: Pid::None,
+            health: None,
         }
     }
 }
@@ -135,6 +206,15 @@ impl ModuleRuntimeState {
         self.pid = pid;
         self
     }
+
+    pub fn health(&self) -> Option<&ModuleHealth> {
+        self.health.as_ref()
+    }
+
+    pub fn with_health(mut self, health: Option<ModuleHealth>) -> Self {
+        self.health = health;
+        self
+    }
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -295,6 +375,126 @@ impl LogOptions {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExecOptions {
+    cmd: Vec<String>,
+    user: Option<String>,
+    attach_stdin: bool,
+    tty: bool,
+}
+
+impl ExecOptions {
+    pub fn new(cmd: Vec<String>) -> Self {
+        ExecOptions {
+            cmd,
+            user: None,
+            attach_stdin: false,
+            tty: false,
+        }
+    }
+
+    pub fn with_user(mut self, user: Option<String>) -> Self {
+        self.user = user;
+        self
+    }
+
+    pub fn with_attach_stdin(mut self, attach_stdin: bool) -> Self {
+        self.attach_stdin = attach_stdin;
+        self
+    }
+
+    pub fn with_tty(mut self, tty: bool) -> Self {
+        self.tty = tty;
+        self
+    }
+
+    pub fn cmd(&self) -> &[String] {
+        &self.cmd
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn attach_stdin(&self) -> bool {
+        self.attach_stdin
+    }
+
+    pub fn tty(&self) -> bool {
+        self.tty
+    }
+}
+
+/// The state of a command started with `ModuleRuntime::exec_start`. The exit
+/// code is only known once the command is no longer running.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExecStatus {
+    running: bool,
+    exit_code: Option<i32>,
+}
+
+impl ExecStatus {
+    pub fn new(running: bool, exit_code: Option<i32>) -> Self {
+        ExecStatus { running, exit_code }
+    }
+
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+}
+
+/// A volume owned by a module, i.e. created by the runtime for one of the
+/// module's named mounts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleVolume {
+    name: String,
+    module: String,
+    driver: String,
+    mount_point: String,
+    created_at: Option<String>,
+}
+
+impl ModuleVolume {
+    pub fn new(name: String, module: String, driver: String, mount_point: String) -> Self {
+        ModuleVolume {
+            name,
+            module,
+            driver,
+            mount_point,
+            created_at: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    pub fn driver(&self) -> &str {
+        &self.driver
+    }
+
+    pub fn mount_point(&self) -> &str {
+        &self.mount_point
+    }
+
+    pub fn created_at(&self) -> Option<&str> {
+        self.created_at.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn with_created_at(mut self, created_at: Option<String>) -> Self {
+        self.created_at = created_at;
+        self
+    }
+}
+
 pub trait Module {
     type Config;
     type Error: Fail;
@@ -348,6 +548,11 @@ impl SystemInfo {
     }
 }
 
+/// The suffix of the temporary name a new version of a module is created under
+/// while the module is being updated. Runtimes treat what they create for it,
+/// such as named volumes, as owned by the module itself.
+pub const NEXT_VERSION_SUFFIX: &str = ".next";
+
 pub trait ModuleRuntime {
     type Error: Fail;
 
@@ -356,6 +561,8 @@ pub trait ModuleRuntime {
     type ModuleRegistry: ModuleRegistry<Config = Self::Config, Error = Self::Error>;
     type Chunk: AsRef<[u8]>;
     type Logs: Stream<Item = Self::Chunk, Error = Self::Error> + Send;
+    type VolumeArchive: Stream<Item = Self::Chunk, Error = Self::Error> + Send;
+    type ExecIo: AsyncRead + AsyncWrite + Send;
 
     type CreateFuture: Future<Item = (), Error = Self::Error> + Send;
     type InitFuture: Future<Item = (), Error = Self::Error> + Send;
@@ -366,11 +573,19 @@ pub trait ModuleRuntime {
         > + Send;
     type LogsFuture: Future<Item = Self::Logs, Error = Self::Error> + Send;
     type RemoveFuture: Future<Item = (), Error = Self::Error> + Send;
+    type RenameFuture: Future<Item = (), Error = Self::Error> + Send;
     type RestartFuture: Future<Item = (), Error = Self::Error> + Send;
     type StartFuture: Future<Item = (), Error = Self::Error> + Send;
     type StopFuture: Future<Item = (), Error = Self::Error> + Send;
     type SystemInfoFuture: Future<Item = SystemInfo, Error = Self::Error> + Send;
     type RemoveAllFuture: Future<Item = (), Error = Self::Error> + Send;
+    type ListVolumesFuture: Future<Item = Vec<ModuleVolume>, Error = Self::Error> + Send;
+    type GetVolumeFuture: Future<Item = ModuleVolume, Error = Self::Error> + Send;
+    type BackupVolumeFuture: Future<Item = Self::VolumeArchive, Error = Self::Error> + Send;
+    type RemoveVolumeFuture: Future<Item = (), Error = Self::Error> + Send;
+    type ExecFuture: Future<Item = String, Error = Self::Error> + Send;
+    type ExecStartFuture: Future<Item = Self::ExecIo, Error = Self::Error> + Send;
+    type ExecInspectFuture: Future<Item = ExecStatus, Error = Self::Error> + Send;
 
     fn init(&self) -> Self::InitFuture;
     fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture;
@@ -378,12 +593,35 @@ pub trait ModuleRuntime {
     fn stop(&self, id: &str, wait_before_kill: Option<Duration>) -> Self::StopFuture;
     fn restart(&self, id: &str) -> Self::RestartFuture;
     fn remove(&self, id: &str) -> Self::RemoveFuture;
+    fn rename(&self, id: &str, new_name: &str) -> Self::RenameFuture;
     fn system_info(&self) -> Self::SystemInfoFuture;
     fn list(&self) -> Self::ListFuture;
     fn list_with_details(&self) -> Self::ListWithDetailsStream;
     fn logs(&self, id: &str, options: &LogOptions) -> Self::LogsFuture;
     fn registry(&self) -> &Self::ModuleRegistry;
-    fn remove_all(&self) -> Self::RemoveAllFuture;
+    /// Removes all modules. With `purge_volumes` the anonymous volumes of the
+    /// modules and the named volumes labeled as owned by them are removed as
+    /// well. A module never takes ownership of a named volume that already
+    /// exists when it is created, so volumes that existed before the module
+    /// are never removed, and a volume shared by several modules belongs only
+    /// to the module it was created for.
+    fn remove_all(&self, purge_volumes: bool) -> Self::RemoveAllFuture;
+    fn list_volumes(&self, module: &str) -> Self::ListVolumesFuture;
+    fn get_volume(&self, module: &str, name: &str) -> Self::GetVolumeFuture;
+    fn backup_volume(&self, module: &str, name: &str) -> Self::BackupVolumeFuture;
+    fn remove_volume(&self, module: &str, name: &str) -> Self::RemoveVolumeFuture;
+    /// Sets up a command to run in a module and returns the id of the exec
+    /// instance. The command doesn't run until it is started.
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture;
+    /// Starts an exec instance and attaches to it. Writes to the returned
+    /// connection go to the stdin of the command, if it was attached, and the
+    /// output of the command is read from it until the command exits. Without
+    /// a tty the output uses Docker's multiplexed stream format so stdout and
+    /// stderr can be told apart.
+    fn exec_start(&self, exec_id: &str, tty: bool) -> Self::ExecStartFuture;
+    /// Returns whether an exec instance is still running and, once its
+    /// command has exited, the exit code.
+    fn exec_inspect(&self, exec_id: &str) -> Self::ExecInspectFuture;
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -425,12 +663,20 @@ impl fmt::Display for RegistryOperation {
 // Useful for error contexts
 #[derive(Clone, Debug)]
 pub enum RuntimeOperation {
+    BackupVolume(String, String),
     CreateModule(String),
+    ExecModule(String),
     GetModuleLogs(String),
+    GetVolume(String, String),
     Init,
+    InspectExec(String),
     ListModules,
+    ListVolumes(String),
     RemoveModule(String),
+    RemoveVolume(String, String),
+    RenameModule(String),
     RestartModule(String),
+    StartExec(String),
     StartModule(String),
     StopModule(String),
     SystemInfo,
@@ -439,14 +685,32 @@ pub enum RuntimeOperation {
 impl fmt::Display for RuntimeOperation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            RuntimeOperation::BackupVolume(module, name) => {
+                write!(f, "Could not back up volume {} of module {}", name, module)
+            }
             RuntimeOperation::CreateModule(name) => write!(f, "Could not create module {}", name),
+            RuntimeOperation::ExecModule(name) => {
+                write!(f, "Could not run command in module {}", name)
+            }
             RuntimeOperation::GetModuleLogs(name) => {
                 write!(f, "Could not get logs for module {}", name)
             }
+            RuntimeOperation::GetVolume(module, name) => {
+                write!(f, "Could not get volume {} of module {}", name, module)
+            }
             RuntimeOperation::Init => write!(f, "Could not initialize module runtime"),
+            RuntimeOperation::InspectExec(id) => write!(f, "Could not inspect command {}", id),
             RuntimeOperation::ListModules => write!(f, "Could not list modules"),
+            RuntimeOperation::ListVolumes(module) => {
+                write!(f, "Could not list volumes of module {}", module)
+            }
             RuntimeOperation::RemoveModule(name) => write!(f, "Could not remove module {}", name),
+            RuntimeOperation::RemoveVolume(module, name) => {
+                write!(f, "Could not remove volume {} of module {}", name, module)
+            }
+            RuntimeOperation::RenameModule(name) => write!(f, "Could not rename module {}", name),
             RuntimeOperation::RestartModule(name) => write!(f, "Could not restart module {}", name),
+            RuntimeOperation::StartExec(id) => write!(f, "Could not start command {}", id),
             RuntimeOperation::StartModule(name) => write!(f, "Could not start module {}", name),
             RuntimeOperation::StopModule(name) => write!(f, "Could not stop module {}", name),
             RuntimeOperation::SystemInfo => write!(f, "Could not query system info"),
@@ -489,6 +753,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn health_status_round_trips() {
+        for &(name, status) in &[
+            ("starting", HealthStatus::Starting),
+            ("healthy", HealthStatus::Healthy),
+            ("unhealthy", HealthStatus::Unhealthy),
+        ] {
+            assert_eq!(name, &status.to_string());
+            assert_eq!(status, HealthStatus::from_str(name).unwrap());
+        }
+        assert!(HealthStatus::from_str("none").is_err());
+    }
+
     #[test]
     fn module_config_empty_name_fails() {
         let name = "".to_string();
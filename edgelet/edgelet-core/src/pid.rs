@@ -40,6 +40,35 @@ impl cmp::PartialEq for Pid {
     }
 }
 
+/// The user and groups of the process at the other end of a connection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Credentials {
+    uid: u32,
+    gids: Vec<u32>,
+    host: bool,
+}
+
+impl Credentials {
+    pub fn new(uid: u32, gids: Vec<u32>, host: bool) -> Self {
+        Credentials { uid, gids, host }
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// The primary and supplementary groups of the process.
+    pub fn gids(&self) -> &[u32] {
+        &self.gids
+    }
+
+    /// Whether the process shares the pid namespace of this daemon, i.e. it
+    /// does not run in a container.
+    pub fn host(&self) -> bool {
+        self.host
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,494 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Named secrets that modules keep in the daemon.
+//!
+//! Every secret is stored in its own file under a directory per module, and
+//! is encrypted with the master encryption key on behalf of that module. The
+//! initialization vector is generated for every write and stored next to the
+//! ciphertext, so callers never have to manage one. Only the user the daemon
+//! runs as can read the files.
+
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64;
+use failure::{Fail, ResultExt};
+use futures::Future;
+use rand;
+use serde_json::{self, Value};
+
+use crypto::{Decrypt, Encrypt, MasterKeyVersion, RotateMasterEncryptionKey};
+use error::{Error, ErrorKind};
+use identity::{IdentityManager, IdentitySpec, TwinTarget};
+
+const INITIALIZATION_VECTOR_LEN: usize = 16;
+
+/// Prefixed to the module id to get the client id that secrets are encrypted
+/// for. The workload API encrypts for the module name followed by its
+/// generation id, and neither can contain a '/', so the two never meet.
+const CLIENT_ID_PREFIX: &str = "secrets/";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SecretFile {
+    #[serde(rename = "initializationVector")]
+    initialization_vector: String,
+    ciphertext: String,
+}
+
+/// Stores the secrets of modules on disk, encrypted with `crypto`.
+#[derive(Clone, Debug)]
+pub struct SecretStore<C> {
+    path: PathBuf,
+    crypto: C,
+}
+
+impl<C> SecretStore<C> {
+    pub fn new(path: PathBuf, crypto: C) -> Result<Self, Error> {
+        create_dir(&path).context(ErrorKind::SecretStore)?;
+        Ok(SecretStore { path, crypto })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Removes all the secrets of `module_id`.
+    pub fn remove_all(&self, module_id: &str) -> Result<(), Error> {
+        let path = self.module_path(module_id)?;
+        match fs::remove_dir_all(&path) {
+            Ok(()) => Ok(()),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(Error::from(err.context(ErrorKind::SecretStore))),
+        }
+    }
+
+    fn module_path(&self, module_id: &str) -> Result<PathBuf, Error> {
+        validate_name(module_id)?;
+        Ok(self.path.join(module_id))
+    }
+
+    fn secret_path(&self, module_id: &str, name: &str) -> Result<PathBuf, Error> {
+        validate_name(name)?;
+        Ok(self.module_path(module_id)?.join(name))
+    }
+}
+
+impl<C> SecretStore<C>
+where
+    C: Encrypt + Decrypt,
+{
+    /// Returns the value of the secret `name` of `module_id`, or `None` if
+    /// it hasn't been set.
+    pub fn get(&self, module_id: &str, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        let path = self.secret_path(module_id, name)?;
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::from(err.context(ErrorKind::SecretStore))),
+        };
+
+        let secret: SecretFile = serde_json::from_reader(file).context(ErrorKind::SecretStore)?;
+        let initialization_vector =
+            base64::decode(&secret.initialization_vector).context(ErrorKind::SecretStore)?;
+        let ciphertext = base64::decode(&secret.ciphertext).context(ErrorKind::SecretStore)?;
+        let plaintext = self
+            .crypto
+            .decrypt(
+                client_id(module_id).as_bytes(),
+                &ciphertext,
+                &initialization_vector,
+            )
+            .context(ErrorKind::SecretStore)?;
+        Ok(Some(plaintext.as_ref().to_vec()))
+    }
+
+    /// Sets the secret `name` of `module_id` to `value`, replacing any value
+    /// it had.
+    pub fn set(&self, module_id: &str, name: &str, value: &[u8]) -> Result<(), Error> {
+        let path = self.secret_path(module_id, name)?;
+        let initialization_vector = rand::random::<[u8; INITIALIZATION_VECTOR_LEN]>();
+        let ciphertext = self
+            .crypto
+            .encrypt(
+                client_id(module_id).as_bytes(),
+                value,
+                &initialization_vector,
+            )
+            .context(ErrorKind::SecretStore)?;
+        let secret = SecretFile {
+            initialization_vector: base64::encode(&initialization_vector),
+            ciphertext: base64::encode(ciphertext.as_ref()),
+        };
+
+        // Write to a temporary file first so that a crash never leaves a
+        // secret half written. Secret names can't start with a dot, so the
+        // temporary file can't clash with another secret.
+        let temp_path = path.with_file_name(format!(".{}.tmp", name));
+        create_dir(&self.module_path(module_id)?)
+            .and_then(|_| write_secret(&temp_path, &secret))
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .context(ErrorKind::SecretStore)?;
+        Ok(())
+    }
+
+    /// Removes the secret `name` of `module_id`. Returns whether it existed.
+    pub fn remove(&self, module_id: &str, name: &str) -> Result<bool, Error> {
+        let path = self.secret_path(module_id, name)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(Error::from(err.context(ErrorKind::SecretStore))),
+        }
+    }
+
+    /// Encrypts every secret again with the current master encryption key.
+    /// Secrets that can't be re-encrypted are logged and left as they are.
+    pub fn reencrypt_all(&self) -> Result<(), Error> {
+        for module in fs::read_dir(&self.path).context(ErrorKind::SecretStore)? {
+            let module = module.context(ErrorKind::SecretStore)?;
+            let module_id = module.file_name().to_string_lossy().into_owned();
+            for secret in fs::read_dir(module.path()).context(ErrorKind::SecretStore)? {
+                let name = secret
+                    .context(ErrorKind::SecretStore)?
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned();
+                if validate_name(&name).is_err() {
+                    continue;
+                }
+
+                let result = self.get(&module_id, &name).and_then(|value| match value {
+                    Some(value) => self.set(&module_id, &name, &value),
+                    None => Ok(()),
+                });
+                if let Err(err) = result {
+                    warn!(
+                        "Could not re-encrypt secret {} of module {}: {}",
+                        name, module_id, err
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rotating the master encryption key through the store re-encrypts the
+/// secrets right away, so they don't expire with the retired key.
+impl<C> RotateMasterEncryptionKey for SecretStore<C>
+where
+    C: RotateMasterEncryptionKey + Encrypt + Decrypt,
+{
+    fn key_versions(&self) -> Result<Vec<MasterKeyVersion>, Error> {
+        self.crypto.key_versions()
+    }
+
+    fn rotate_key(&self, grace_period: Duration) -> Result<MasterKeyVersion, Error> {
+        let version = self.crypto.rotate_key(grace_period)?;
+        self.reencrypt_all()?;
+        Ok(version)
+    }
+}
+
+fn client_id(module_id: &str) -> String {
+    format!("{}{}", CLIENT_ID_PREFIX, module_id)
+}
+
+#[cfg(unix)]
+fn create_dir(path: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(path)
+}
+
+#[cfg(not(unix))]
+fn create_dir(path: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).create(path)
+}
+
+fn write_secret(path: &Path, secret: &SecretFile) -> io::Result<()> {
+    // A file left over from an earlier crash keeps the permissions it was
+    // created with, so start from scratch.
+    match fs::remove_file(path) {
+        Ok(()) => (),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => return Err(err),
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path)?;
+    serde_json::to_writer(&mut file, secret)?;
+    file.flush()?;
+    file.sync_all()
+}
+
+/// Module and secret names become file names, so they can't be empty, start
+/// with a dot or contain path separators.
+fn validate_name(name: &str) -> Result<(), Error> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || name.contains(|c: char| c == '/' || c == '\\' || c == '\0')
+    {
+        Err(Error::from(ErrorKind::InvalidSecretName(name.to_string())))
+    } else {
+        Ok(())
+    }
+}
+
+/// Wraps an identity manager so that deleting a module identity also removes
+/// the secrets of that module.
+#[derive(Clone)]
+pub struct SecretWipingIdentityManager<I, C> {
+    inner: I,
+    secrets: SecretStore<C>,
+}
+
+impl<I, C> SecretWipingIdentityManager<I, C> {
+    pub fn new(inner: I, secrets: SecretStore<C>) -> Self {
+        SecretWipingIdentityManager { inner, secrets }
+    }
+}
+
+impl<I, C> IdentityManager for SecretWipingIdentityManager<I, C>
+where
+    I: IdentityManager,
+    I::DeleteFuture: 'static,
+    C: 'static + Clone + Send,
+{
+    type Identity = I::Identity;
+    type Error = I::Error;
+    type CreateFuture = I::CreateFuture;
+    type UpdateFuture = I::UpdateFuture;
+    type ListFuture = I::ListFuture;
+    type GetFuture = I::GetFuture;
+    type DeleteFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type GetTwinFuture = I::GetTwinFuture;
+    type ReportPropertiesFuture = I::ReportPropertiesFuture;
+
+    fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture {
+        self.inner.create(id)
+    }
+
+    fn update(&mut self, id: IdentitySpec) -> Self::UpdateFuture {
+        self.inner.update(id)
+    }
+
+    fn list(&self) -> Self::ListFuture {
+        self.inner.list()
+    }
+
+    fn get(&self, id: IdentitySpec) -> Self::GetFuture {
+        self.inner.get(id)
+    }
+
+    fn delete(&mut self, id: IdentitySpec) -> Self::DeleteFuture {
+        let module_id = id.module_id().to_string();
+        let secrets = self.secrets.clone();
+
+        // The identity is gone at this point, so failing to remove its
+        // secrets is logged rather than reported to the caller.
+        Box::new(self.inner.delete(id).map(move |()| {
+            if let Err(err) = secrets.remove_all(&module_id) {
+                warn!(
+                    "Could not remove the secrets of module {}: {}",
+                    module_id, err
+                );
+            }
+        }))
+    }
+
+    fn get_twin(&self, target: TwinTarget) -> Self::GetTwinFuture {
+        self.inner.get_twin(target)
+    }
+
+    fn report_properties(
+        &mut self,
+        target: TwinTarget,
+        reported: Value,
+    ) -> Self::ReportPropertiesFuture {
+        self.inner.report_properties(target, reported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    #[derive(Clone, Debug, Default)]
+    struct TestCrypto;
+
+    /// "Encrypts" by xor-ing every byte with the first byte of the client id
+    /// and the initialization vector, so that the result depends on both.
+    fn xor(client_id: &[u8], data: &[u8], initialization_vector: &[u8]) -> Vec<u8> {
+        data.iter()
+            .map(|b| b ^ client_id[0] ^ initialization_vector[0] ^ 0x5a)
+            .collect()
+    }
+
+    impl Encrypt for TestCrypto {
+        type Buffer = Vec<u8>;
+
+        fn encrypt(
+            &self,
+            client_id: &[u8],
+            plaintext: &[u8],
+            initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            Ok(xor(client_id, plaintext, initialization_vector))
+        }
+    }
+
+    impl Decrypt for TestCrypto {
+        type Buffer = Vec<u8>;
+
+        fn decrypt(
+            &self,
+            client_id: &[u8],
+            ciphertext: &[u8],
+            initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, Error> {
+            Ok(xor(client_id, ciphertext, initialization_vector))
+        }
+    }
+
+    fn store(dir: &TempDir) -> SecretStore<TestCrypto> {
+        SecretStore::new(dir.path().join("secrets"), TestCrypto).unwrap()
+    }
+
+    #[test]
+    fn set_and_get_secret() {
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+
+        assert_eq!(None, store.get("m1", "password").unwrap());
+
+        store.set("m1", "password", b"hunter2").unwrap();
+        assert_eq!(
+            Some(b"hunter2".to_vec()),
+            store.get("m1", "password").unwrap()
+        );
+        assert_eq!(None, store.get("m2", "password").unwrap());
+
+        store.set("m1", "password", b"correct horse").unwrap();
+        assert_eq!(
+            Some(b"correct horse".to_vec()),
+            store.get("m1", "password").unwrap()
+        );
+    }
+
+    #[test]
+    fn secrets_are_not_stored_in_plain_text() {
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+
+        store.set("m1", "password", b"hunter2").unwrap();
+
+        let stored = fs::read_to_string(store.path().join("m1").join("password")).unwrap();
+        assert!(!stored.contains("hunter2"));
+        assert!(!stored.contains(&base64::encode(b"hunter2")));
+    }
+
+    #[test]
+    fn secrets_are_encrypted_for_their_own_client_id() {
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+
+        store.set("m1", "password", b"hunter2").unwrap();
+
+        let stored = fs::read(store.path().join("m1").join("password")).unwrap();
+        let secret: SecretFile = serde_json::from_slice(&stored).unwrap();
+        let initialization_vector = base64::decode(&secret.initialization_vector).unwrap();
+        let ciphertext = base64::decode(&secret.ciphertext).unwrap();
+        assert_eq!(
+            xor(b"secrets/m1", b"hunter2", &initialization_vector),
+            ciphertext
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secrets_are_only_readable_by_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+
+        store.set("m1", "password", b"hunter2").unwrap();
+
+        let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(0o700, mode(store.path().to_path_buf()));
+        assert_eq!(0o700, mode(store.path().join("m1")));
+        assert_eq!(0o600, mode(store.path().join("m1").join("password")));
+    }
+
+    #[test]
+    fn remove_secrets() {
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+        store.set("m1", "user", b"admin").unwrap();
+        store.set("m1", "password", b"hunter2").unwrap();
+        store.set("m2", "password", b"letmein").unwrap();
+
+        assert!(store.remove("m1", "user").unwrap());
+        assert!(!store.remove("m1", "user").unwrap());
+        assert_eq!(None, store.get("m1", "user").unwrap());
+
+        store.remove_all("m1").unwrap();
+        store.remove_all("m1").unwrap();
+        assert_eq!(None, store.get("m1", "password").unwrap());
+        assert_eq!(
+            Some(b"letmein".to_vec()),
+            store.get("m2", "password").unwrap()
+        );
+    }
+
+    #[test]
+    fn reencrypt_all_keeps_values() {
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+        store.set("m1", "password", b"hunter2").unwrap();
+        store.set("m2", "password", b"letmein").unwrap();
+        let before = fs::read_to_string(store.path().join("m1").join("password")).unwrap();
+
+        store.reencrypt_all().unwrap();
+
+        let after = fs::read_to_string(store.path().join("m1").join("password")).unwrap();
+        assert_ne!(before, after);
+        assert_eq!(
+            Some(b"hunter2".to_vec()),
+            store.get("m1", "password").unwrap()
+        );
+        assert_eq!(
+            Some(b"letmein".to_vec()),
+            store.get("m2", "password").unwrap()
+        );
+    }
+
+    #[test]
+    fn names_cannot_escape_store() {
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+
+        for &(module_id, name) in &[
+            ("m1", ""),
+            ("m1", ".hidden"),
+            ("m1", "../m2"),
+            ("..", "password"),
+            ("m1/..", "password"),
+            ("m1", "a\\b"),
+        ] {
+            match *store.set(module_id, name, b"value").unwrap_err().kind() {
+                ErrorKind::InvalidSecretName(_) => (),
+                ref kind => panic!("unexpected error {}", kind),
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use failure::Fail;
@@ -14,7 +15,8 @@ use edgelet_utils::log_failure;
 use error::{Error, ErrorKind};
 use identity::{Identity, IdentityManager, IdentitySpec};
 use module::{
-    Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeErrorReason, ModuleSpec, ModuleStatus,
+    HealthStatus, Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeErrorReason,
+    ModuleRuntimeState, ModuleSpec, ModuleStatus,
 };
 
 // Time to allow EdgeAgent to gracefully shutdown (including stopping all modules, and updating reported properties)
@@ -26,6 +28,10 @@ const MODULE_GENERATIONID: &str = "IOTEDGE_MODULEGENERATIONID";
 /// This is the frequency with which the watchdog checks for the status of the edge runtime module.
 const WATCHDOG_FREQUENCY_SECS: u64 = 60;
 
+/// This is the maximum number of watchdog checks to wait between two attempts to restart
+/// an edge runtime module that keeps failing.
+const MAX_RESTART_BACKOFF_CHECKS: u32 = 10;
+
 pub struct Watchdog<M, I> {
     runtime: M,
     id_mgr: I,
@@ -110,6 +116,7 @@ where
         "Starting watchdog with {} second frequency...",
         WATCHDOG_FREQUENCY_SECS
     );
+    let backoff = Arc::new(Mutex::new(RestartBackoff::default()));
     Interval::new(Instant::now(), Duration::from_secs(WATCHDOG_FREQUENCY_SECS))
         .map_err(|err| Error::from(err.context(ErrorKind::EdgeRuntimeStatusCheckerTimer)))
         .for_each(move |_| {
@@ -119,6 +126,7 @@ where
                 id_mgr.clone(),
                 spec.clone(),
                 module_id.clone(),
+                backoff.clone(),
             )
             .or_else(|e| {
                 warn!("Error in watchdog when checking for edge runtime status:");
@@ -128,12 +136,54 @@ where
         })
 }
 
-// Check if the edge runtime module is running, and if not, start it.
+// Tracks consecutive attempts to start or restart the edge runtime module, so that a
+// module that keeps failing is retried after an exponentially growing number of checks
+// instead of on every one of them.
+#[derive(Debug, Default)]
+struct RestartBackoff {
+    attempts: u32,
+    checks_to_skip: u32,
+}
+
+impl RestartBackoff {
+    // Returns true if the module may be (re)started on this check, and records the attempt.
+    fn try_attempt(&mut self) -> bool {
+        if self.checks_to_skip > 0 {
+            self.checks_to_skip -= 1;
+            false
+        } else {
+            self.attempts = self.attempts.saturating_add(1);
+            self.checks_to_skip = checks_between_attempts(self.attempts) - 1;
+            true
+        }
+    }
+
+    fn reset(&mut self) {
+        self.attempts = 0;
+        self.checks_to_skip = 0;
+    }
+}
+
+// The number of checks between the given attempt and the next one: 1, 2, 4, 8, ... capped
+// at MAX_RESTART_BACKOFF_CHECKS.
+fn checks_between_attempts(attempts: u32) -> u32 {
+    let exponent = attempts.saturating_sub(1);
+    if exponent >= 31 {
+        MAX_RESTART_BACKOFF_CHECKS
+    } else {
+        ::std::cmp::min(1 << exponent, MAX_RESTART_BACKOFF_CHECKS)
+    }
+}
+
+// Check if the edge runtime module is running and healthy. Start it if it is not
+// running and restart it if its health check reports it as unhealthy, backing off
+// exponentially while it keeps failing.
 fn check_runtime<M, I>(
     runtime: M,
     id_mgr: I,
     spec: ModuleSpec<<M::Module as Module>::Config>,
     module_id: String,
+    backoff: Arc<Mutex<RestartBackoff>>,
 ) -> impl Future<Item = (), Error = Error>
 where
     M: 'static + ModuleRuntime + Clone,
@@ -150,19 +200,35 @@ where
         })
         .and_then(move |state| match state {
             Some(state) => {
-                let res = if *state.status() == ModuleStatus::Running {
+                let mut backoff = backoff.lock().expect("watchdog backoff lock poisoned");
+                let res = if *state.status() == ModuleStatus::Running && !is_unhealthy(&state) {
                     info!("Edge runtime is running.");
+                    backoff.reset();
+                    future::Either::A(future::ok(()))
+                } else if !backoff.try_attempt() {
+                    info!(
+                        "Edge runtime status is {}, waiting before trying to start it again after {} attempt(s)",
+                        *state.status(),
+                        backoff.attempts,
+                    );
                     future::Either::A(future::ok(()))
+                } else if is_unhealthy(&state) {
+                    info!("Edge runtime is unhealthy, restarting module now...");
+                    future::Either::B(Either::A(
+                        runtime
+                            .restart(&module)
+                            .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime))),
+                    ))
                 } else {
                     info!(
                         "Edge runtime status is {}, starting module now...",
                         *state.status(),
                     );
-                    future::Either::B(
+                    future::Either::B(Either::B(
                         runtime
                             .start(&module)
                             .map_err(|e| Error::from(e.context(ErrorKind::ModuleRuntime))),
-                    )
+                    ))
                 };
                 Either::A(res)
             }
@@ -172,6 +238,15 @@ where
         .map(|_| ())
 }
 
+// A running module whose health check is failing is considered unhealthy. Modules
+// without a health check are never unhealthy.
+fn is_unhealthy(state: &ModuleRuntimeState) -> bool {
+    *state.status() == ModuleStatus::Running
+        && state
+            .health()
+            .map_or(false, |health| health.status() == HealthStatus::Unhealthy)
+}
+
 // Gets the edge runtime module, if it exists.
 fn get_edge_runtime_mod<M>(
     runtime: &M,
@@ -262,8 +337,12 @@ mod tests {
     use std::rc::Rc;
 
     use futures::future::{self, FutureResult};
+    use serde_json::Value;
 
-    use identity::{AuthType, Identity, IdentityManager, IdentitySpec};
+    use identity::{
+        AuthType, Identity, IdentityManager, IdentitySpec, Twin, TwinProperties, TwinTarget,
+    };
+    use module::ModuleHealth;
 
     #[derive(Clone, Copy, Debug, Fail)]
     pub enum Error {
@@ -365,6 +444,8 @@ mod tests {
         type ListFuture = FutureResult<Vec<Self::Identity>, Self::Error>;
         type GetFuture = FutureResult<Option<Self::Identity>, Self::Error>;
         type DeleteFuture = FutureResult<(), Self::Error>;
+        type GetTwinFuture = FutureResult<Twin, Self::Error>;
+        type ReportPropertiesFuture = FutureResult<(), Self::Error>;
 
         fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture {
             self.state.borrow_mut().gen_id_sentinel += 1;
@@ -440,6 +521,18 @@ mod tests {
                 .map(|index| self.state.borrow_mut().identities.remove(index))
                 .map_or_else(|| future::err(Error::ModuleNotFound), |_| future::ok(()))
         }
+
+        fn get_twin(&self, _target: TwinTarget) -> Self::GetTwinFuture {
+            future::ok(Twin::new(1, TwinProperties::new()))
+        }
+
+        fn report_properties(
+            &mut self,
+            _target: TwinTarget,
+            _reported: Value,
+        ) -> Self::ReportPropertiesFuture {
+            future::ok(())
+        }
     }
 
     #[test]
@@ -498,4 +591,53 @@ mod tests {
                 .auth_type
         );
     }
+
+    #[test]
+    fn running_module_with_failing_health_check_is_unhealthy() {
+        let state = ModuleRuntimeState::default()
+            .with_status(ModuleStatus::Running)
+            .with_health(Some(ModuleHealth::new(HealthStatus::Unhealthy)));
+        assert!(is_unhealthy(&state));
+    }
+
+    #[test]
+    fn module_without_failing_health_check_is_not_unhealthy() {
+        let running = ModuleRuntimeState::default().with_status(ModuleStatus::Running);
+        assert!(!is_unhealthy(&running));
+
+        let starting = running.with_health(Some(ModuleHealth::new(HealthStatus::Starting)));
+        assert!(!is_unhealthy(&starting));
+
+        let stopped = ModuleRuntimeState::default()
+            .with_status(ModuleStatus::Stopped)
+            .with_health(Some(ModuleHealth::new(HealthStatus::Unhealthy)));
+        assert!(!is_unhealthy(&stopped));
+    }
+
+    #[test]
+    fn checks_between_attempts_grows_exponentially_up_to_max() {
+        assert_eq!(1, checks_between_attempts(1));
+        assert_eq!(2, checks_between_attempts(2));
+        assert_eq!(4, checks_between_attempts(3));
+        assert_eq!(8, checks_between_attempts(4));
+        assert_eq!(MAX_RESTART_BACKOFF_CHECKS, checks_between_attempts(5));
+        assert_eq!(
+            MAX_RESTART_BACKOFF_CHECKS,
+            checks_between_attempts(u32::max_value())
+        );
+    }
+
+    #[test]
+    fn restart_backoff_skips_checks_between_attempts() {
+        let mut backoff = RestartBackoff::default();
+        let attempts = (0..8).map(|_| backoff.try_attempt()).collect::<Vec<_>>();
+        assert_eq!(
+            vec![true, true, false, true, false, false, false, true],
+            attempts
+        );
+
+        backoff.reset();
+        assert!(backoff.try_attempt());
+        assert!(backoff.try_attempt());
+    }
 }
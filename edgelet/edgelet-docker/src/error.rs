@@ -56,6 +56,15 @@ pub enum ErrorKind {
     #[fail(display = "{}", _0)]
     FormattedDockerRuntime(String),
 
+    #[fail(display = "Image {:?} does not match the pinned digest {:?}", _0, _1)]
+    ImageDigestMismatch(String, String),
+
+    #[fail(display = "Image {:?} does not have a valid signature", _0)]
+    ImageSignature(String),
+
+    #[fail(display = "Could not load the image trust root")]
+    ImageTrust,
+
     #[fail(display = "Could not initialize module runtime")]
     Initialization,
 
@@ -80,6 +89,12 @@ pub enum ErrorKind {
     #[fail(display = "{}", _0)]
     RegistryOperation(RegistryOperation),
 
+    #[fail(
+        display = "Requested {} of {} exceeds the maximum of {} allowed on this device",
+        _0, _1, _2
+    )]
+    ResourceLimitExceeded(String, i64, i64),
+
     #[fail(display = "{}", _0)]
     RuntimeOperation(RuntimeOperation),
 }
@@ -18,6 +18,7 @@ extern crate hyper;
 extern crate lazy_static;
 #[macro_use]
 extern crate log;
+extern crate openssl;
 #[macro_use]
 extern crate serde_derive;
 #[cfg(test)]
@@ -33,7 +34,6 @@ extern crate url;
 #[cfg(test)]
 #[macro_use]
 extern crate serde_json;
-#[cfg(unix)]
 #[cfg(test)]
 extern crate tempfile;
 #[cfg(test)]
@@ -50,11 +50,15 @@ extern crate edgelet_test_utils;
 mod client;
 mod config;
 mod error;
+mod limits;
 mod module;
 mod runtime;
+mod trust;
 
 pub use config::DockerConfig;
 pub use error::{Error, ErrorKind};
+pub use limits::{Enforcement, ResourceLimits, ResourceProfile, RestartPolicyName};
 pub use module::{DockerModule, MODULE_TYPE};
 
 pub use runtime::DockerModuleRuntime;
+pub use trust::ImageTrust;
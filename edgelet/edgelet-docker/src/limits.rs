@@ -0,0 +1,478 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt;
+
+use failure::Fail;
+
+use docker::models::{ContainerCreateBody, HostConfig, RestartPolicy};
+use edgelet_core::RuntimeOperation;
+
+use error::{Error, ErrorKind, Result};
+
+/// The CPU period Docker uses when a container doesn't set one, in
+/// microseconds.
+const DEFAULT_CPU_PERIOD: i64 = 100_000;
+
+/// The smallest CPU quota Docker accepts, in microseconds.
+const MIN_CPU_QUOTA: i64 = 1_000;
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// How a module that asks for more than the device maximum is handled.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Enforcement {
+    /// Lower the requested value to the maximum.
+    Clamp,
+    /// Refuse to create the module.
+    Reject,
+}
+
+impl Default for Enforcement {
+    fn default() -> Self {
+        Enforcement::Clamp
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicyName {
+    No,
+    Always,
+    UnlessStopped,
+    OnFailure,
+}
+
+impl fmt::Display for RestartPolicyName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            RestartPolicyName::No => "no",
+            RestartPolicyName::Always => "always",
+            RestartPolicyName::UnlessStopped => "unless-stopped",
+            RestartPolicyName::OnFailure => "on-failure",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Resource values applied to a module's `HostConfig`. Memory is in bytes and
+/// the CPU quota is in microseconds per CPU period.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ResourceProfile {
+    memory: Option<i64>,
+    cpu_quota: Option<i64>,
+    pids_limit: Option<i64>,
+}
+
+impl ResourceProfile {
+    pub fn new() -> Self {
+        ResourceProfile::default()
+    }
+
+    pub fn memory(&self) -> Option<i64> {
+        self.memory
+    }
+
+    pub fn with_memory(mut self, memory: Option<i64>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    pub fn cpu_quota(&self) -> Option<i64> {
+        self.cpu_quota
+    }
+
+    pub fn with_cpu_quota(mut self, cpu_quota: Option<i64>) -> Self {
+        self.cpu_quota = cpu_quota;
+        self
+    }
+
+    pub fn pids_limit(&self) -> Option<i64> {
+        self.pids_limit
+    }
+
+    pub fn with_pids_limit(mut self, pids_limit: Option<i64>) -> Self {
+        self.pids_limit = pids_limit;
+        self
+    }
+}
+
+/// Device-wide resource limits for module containers.
+///
+/// Resources a module leaves unset in its create options are taken from the
+/// `default` profile, and the result is checked against the `max` profile.
+/// A module without a value for a resource that has a maximum gets the
+/// maximum, so no module runs unbounded once a maximum is configured.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ResourceLimits {
+    #[serde(default)]
+    default: ResourceProfile,
+    #[serde(default)]
+    max: ResourceProfile,
+    restart_policy: Option<RestartPolicyName>,
+    #[serde(default)]
+    enforcement: Enforcement,
+}
+
+impl ResourceLimits {
+    pub fn new() -> Self {
+        ResourceLimits::default()
+    }
+
+    pub fn default_profile(&self) -> &ResourceProfile {
+        &self.default
+    }
+
+    pub fn with_default_profile(mut self, default: ResourceProfile) -> Self {
+        self.default = default;
+        self
+    }
+
+    pub fn max_profile(&self) -> &ResourceProfile {
+        &self.max
+    }
+
+    pub fn with_max_profile(mut self, max: ResourceProfile) -> Self {
+        self.max = max;
+        self
+    }
+
+    pub fn restart_policy(&self) -> Option<RestartPolicyName> {
+        self.restart_policy
+    }
+
+    pub fn with_restart_policy(mut self, restart_policy: Option<RestartPolicyName>) -> Self {
+        self.restart_policy = restart_policy;
+        self
+    }
+
+    pub fn enforcement(&self) -> Enforcement {
+        self.enforcement
+    }
+
+    pub fn with_enforcement(mut self, enforcement: Enforcement) -> Self {
+        self.enforcement = enforcement;
+        self
+    }
+
+    /// Merges the limits into the `HostConfig` of the create options for
+    /// module `name`.
+    pub fn apply(
+        &self,
+        name: &str,
+        create_options: ContainerCreateBody,
+    ) -> Result<ContainerCreateBody> {
+        let mut host_config = create_options
+            .host_config()
+            .cloned()
+            .unwrap_or_else(HostConfig::new);
+
+        let limit =
+            |resource, requested, default, max| self.limit(name, resource, requested, default, max);
+
+        let requested_memory = host_config.memory().filter(|memory| *memory > 0);
+        if let Some(memory) = limit(
+            "memory",
+            requested_memory,
+            self.default.memory,
+            self.max.memory,
+        )? {
+            host_config.set_memory(memory);
+
+            // MemorySwap is the memory plus the swap, so keep the swap the
+            // module asked for on top of the new memory limit.
+            if let Some(memory_swap) = host_config.memory_swap().filter(|swap| *swap > 0) {
+                let memory_swap = match requested_memory {
+                    Some(requested) => memory.saturating_add((memory_swap - requested).max(0)),
+                    None => memory_swap.max(memory),
+                };
+                host_config.set_memory_swap(memory_swap);
+            }
+            if host_config
+                .memory_reservation()
+                .map_or(false, |reservation| reservation > memory)
+            {
+                host_config.set_memory_reservation(memory);
+            }
+        }
+
+        // Docker refuses NanoCpus together with a CPU quota, so NanoCpus is
+        // converted to a quota over the container's CPU period.
+        let cpu_period = host_config
+            .cpu_period()
+            .filter(|period| *period > 0)
+            .unwrap_or(DEFAULT_CPU_PERIOD);
+        let requested_nano_cpus = host_config.nano_cp_us().filter(|nano_cpus| *nano_cpus > 0);
+        let requested_cpu_quota = host_config
+            .cpu_quota()
+            .filter(|quota| *quota > 0)
+            .or_else(|| requested_nano_cpus.map(|nano_cpus| cpu_quota_of(nano_cpus, cpu_period)));
+        if let Some(cpu_quota) = limit(
+            "CPU quota",
+            requested_cpu_quota,
+            self.default.cpu_quota,
+            self.max.cpu_quota,
+        )? {
+            host_config.set_cpu_quota(cpu_quota);
+            host_config.reset_nano_cp_us();
+        }
+        if let Some(pids_limit) = limit(
+            "pids limit",
+            host_config.pids_limit(),
+            self.default.pids_limit,
+            self.max.pids_limit,
+        )? {
+            host_config.set_pids_limit(pids_limit);
+        }
+
+        if let Some(restart_policy) = self.restart_policy {
+            let unset = host_config
+                .restart_policy()
+                .and_then(RestartPolicy::name)
+                .map_or(true, str::is_empty);
+            if unset {
+                host_config
+                    .set_restart_policy(RestartPolicy::new().with_name(restart_policy.to_string()));
+            }
+        }
+
+        Ok(create_options.with_host_config(host_config))
+    }
+
+    fn limit(
+        &self,
+        name: &str,
+        resource: &str,
+        requested: Option<i64>,
+        default: Option<i64>,
+        max: Option<i64>,
+    ) -> Result<Option<i64>> {
+        // Docker treats zero and negative values as "no limit".
+        let value = requested.filter(|value| *value > 0).or(default);
+
+        match (value, max) {
+            (Some(value), Some(max)) if value > max => match self.enforcement {
+                Enforcement::Clamp => {
+                    warn!(
+                        "Module {} requested {} of {}, clamping it to the maximum of {}",
+                        name, resource, value, max
+                    );
+                    Ok(Some(max))
+                }
+                Enforcement::Reject => Err(Error::from(
+                    ErrorKind::ResourceLimitExceeded(resource.to_string(), value, max).context(
+                        ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(
+                            name.to_string(),
+                        )),
+                    ),
+                )),
+            },
+            (None, Some(max)) => Ok(Some(max)),
+            (value, _) => Ok(value),
+        }
+    }
+}
+
+/// The CPU quota per `cpu_period` that gives the same share of CPU time as
+/// `nano_cpus`.
+fn cpu_quota_of(nano_cpus: i64, cpu_period: i64) -> i64 {
+    (nano_cpus.saturating_mul(cpu_period) / NANOS_PER_SEC).max(MIN_CPU_QUOTA)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(enforcement: Enforcement) -> ResourceLimits {
+        ResourceLimits::new()
+            .with_default_profile(
+                ResourceProfile::new()
+                    .with_memory(Some(256))
+                    .with_pids_limit(Some(100)),
+            )
+            .with_max_profile(
+                ResourceProfile::new()
+                    .with_memory(Some(1024))
+                    .with_cpu_quota(Some(50_000))
+                    .with_pids_limit(Some(200)),
+            )
+            .with_restart_policy(Some(RestartPolicyName::OnFailure))
+            .with_enforcement(enforcement)
+    }
+
+    fn create_options(host_config: HostConfig) -> ContainerCreateBody {
+        ContainerCreateBody::new().with_host_config(host_config)
+    }
+
+    #[test]
+    fn defaults_fill_unset_values() {
+        let options = limits(Enforcement::Reject)
+            .apply("m1", ContainerCreateBody::new())
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(Some(256), host_config.memory());
+        assert_eq!(Some(50_000), host_config.cpu_quota());
+        assert_eq!(Some(100), host_config.pids_limit());
+        assert_eq!(
+            Some("on-failure"),
+            host_config.restart_policy().and_then(RestartPolicy::name)
+        );
+    }
+
+    #[test]
+    fn values_within_max_are_kept() {
+        let options = limits(Enforcement::Reject)
+            .apply(
+                "m1",
+                create_options(
+                    HostConfig::new()
+                        .with_memory(512)
+                        .with_pids_limit(150)
+                        .with_restart_policy(RestartPolicy::new().with_name("always".to_string())),
+                ),
+            )
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(Some(512), host_config.memory());
+        assert_eq!(Some(150), host_config.pids_limit());
+        assert_eq!(
+            Some("always"),
+            host_config.restart_policy().and_then(RestartPolicy::name)
+        );
+    }
+
+    #[test]
+    fn values_over_max_are_clamped() {
+        let options = limits(Enforcement::Clamp)
+            .apply(
+                "m1",
+                create_options(HostConfig::new().with_memory(4096).with_cpu_quota(100_000)),
+            )
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(Some(1024), host_config.memory());
+        assert_eq!(Some(50_000), host_config.cpu_quota());
+    }
+
+    #[test]
+    fn nano_cpus_are_converted_to_cpu_quota() {
+        let options = limits(Enforcement::Reject)
+            .apply(
+                "m1",
+                create_options(HostConfig::new().with_nano_cp_us(250_000_000)),
+            )
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(Some(25_000), host_config.cpu_quota());
+        assert_eq!(None, host_config.nano_cp_us());
+    }
+
+    #[test]
+    fn nano_cpus_over_max_are_clamped() {
+        let options = limits(Enforcement::Clamp)
+            .apply(
+                "m1",
+                create_options(HostConfig::new().with_nano_cp_us(2_000_000_000)),
+            )
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(Some(50_000), host_config.cpu_quota());
+        assert_eq!(None, host_config.nano_cp_us());
+    }
+
+    #[test]
+    fn nano_cpus_over_max_are_rejected() {
+        let err = limits(Enforcement::Reject)
+            .apply(
+                "m1",
+                create_options(HostConfig::new().with_nano_cp_us(1_000_000_000)),
+            )
+            .unwrap_err();
+
+        match Fail::find_root_cause(&err).downcast_ref::<ErrorKind>() {
+            Some(ErrorKind::ResourceLimitExceeded(resource, requested, max)) => {
+                assert_eq!("CPU quota", resource);
+                assert_eq!(100_000, *requested);
+                assert_eq!(50_000, *max);
+            }
+            _ => panic!("Expected ResourceLimitExceeded but got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn memory_reservation_and_swap_follow_clamped_memory() {
+        let options = limits(Enforcement::Clamp)
+            .apply(
+                "m1",
+                create_options(
+                    HostConfig::new()
+                        .with_memory(4096)
+                        .with_memory_reservation(2048)
+                        .with_memory_swap(5120),
+                ),
+            )
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(Some(1024), host_config.memory());
+        assert_eq!(Some(1024), host_config.memory_reservation());
+        assert_eq!(Some(2048), host_config.memory_swap());
+    }
+
+    #[test]
+    fn memory_swap_is_not_below_default_memory() {
+        let options = limits(Enforcement::Reject)
+            .apply(
+                "m1",
+                create_options(HostConfig::new().with_memory_swap(128)),
+            )
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(Some(256), host_config.memory());
+        assert_eq!(Some(256), host_config.memory_swap());
+    }
+
+    #[test]
+    fn unlimited_value_gets_default() {
+        let options = limits(Enforcement::Reject)
+            .apply("m1", create_options(HostConfig::new().with_pids_limit(-1)))
+            .unwrap();
+
+        assert_eq!(Some(100), options.host_config().unwrap().pids_limit());
+    }
+
+    #[test]
+    fn values_over_max_are_rejected() {
+        let err = limits(Enforcement::Reject)
+            .apply("m1", create_options(HostConfig::new().with_memory(4096)))
+            .unwrap_err();
+
+        match Fail::find_root_cause(&err).downcast_ref::<ErrorKind>() {
+            Some(ErrorKind::ResourceLimitExceeded(resource, requested, max)) => {
+                assert_eq!("memory", resource);
+                assert_eq!(4096, *requested);
+                assert_eq!(1024, *max);
+            }
+            _ => panic!("Expected ResourceLimitExceeded but got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn empty_limits_leave_options_untouched() {
+        let options = ResourceLimits::new()
+            .apply("m1", ContainerCreateBody::new())
+            .unwrap();
+        let host_config = options.host_config().unwrap();
+
+        assert_eq!(None, host_config.memory());
+        assert_eq!(None, host_config.cpu_quota());
+        assert_eq!(None, host_config.pids_limit());
+        assert!(host_config.restart_policy().is_none());
+    }
+}
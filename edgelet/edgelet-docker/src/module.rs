@@ -6,12 +6,16 @@ use chrono::prelude::*;
 use futures::Future;
 use hyper::client::connect::Connect;
 
+use docker::models::Health;
+
 use edgelet_utils::ensure_not_empty_with_context;
 
 use client::DockerClient;
 use config::DockerConfig;
 use edgelet_core::pid::Pid;
-use edgelet_core::{Module, ModuleOperation, ModuleRuntimeState, ModuleStatus};
+use edgelet_core::{
+    HealthStatus, Module, ModuleHealth, ModuleOperation, ModuleRuntimeState, ModuleStatus,
+};
 use error::{Error, ErrorKind, Result};
 
 pub const MODULE_TYPE: &str = "docker";
@@ -45,6 +49,29 @@ fn status_from_exit_code(exit_code: Option<i64>) -> Option<ModuleStatus> {
     })
 }
 
+/// Docker reports a status of `none` for containers without a health check,
+/// which is mapped to no health at all.
+fn module_health(health: &Health) -> Option<ModuleHealth> {
+    health
+        .status()
+        .and_then(|status| HealthStatus::from_str(status).ok())
+        .map(|status| {
+            ModuleHealth::new(status)
+                .with_failing_streak(
+                    health
+                        .failing_streak()
+                        .map_or(0, |streak| streak.max(0) as u32),
+                )
+                .with_last_output(
+                    health
+                        .log()
+                        .and_then(|log| log.last())
+                        .and_then(|result| result.output())
+                        .map(ToOwned::to_owned),
+                )
+        })
+}
+
 impl<C: 'static + Connect> Module for DockerModule<C> {
     type Config = DockerConfig;
     type Error = Error;
@@ -103,6 +130,7 @@ impl<C: 'static + Connect> Module for DockerModule<C> {
                                 )
                                 .with_image_id(resp.id().map(ToOwned::to_owned))
                                 .with_pid(state.pid().map_or(Pid::None, Pid::Value))
+                                .with_health(state.health().and_then(module_health))
                         })
                 })
                 .map_err(|err| {
@@ -128,9 +156,11 @@ mod tests {
 
     use docker::apis::client::APIClient;
     use docker::apis::configuration::Configuration;
-    use docker::models::{ContainerCreateBody, InlineResponse200, InlineResponse200State};
+    use docker::models::{
+        ContainerCreateBody, Health, HealthcheckResult, InlineResponse200, InlineResponse200State,
+    };
     use edgelet_core::pid::Pid;
-    use edgelet_core::{Module, ModuleStatus};
+    use edgelet_core::{HealthStatus, Module, ModuleStatus};
     use edgelet_test_utils::JsonConnector;
 
     use client::DockerClient;
@@ -262,6 +292,53 @@ mod tests {
         assert_eq!(Pid::Value(1234), runtime_state.pid());
     }
 
+    fn runtime_state_with_health(health: Health) -> ModuleRuntimeState {
+        let docker_module = DockerModule::new(
+            create_api_client(
+                InlineResponse200::new()
+                    .with_state(
+                        InlineResponse200State::new()
+                            .with_status("running".to_string())
+                            .with_health(health),
+                    )
+                    .with_id("mod1".to_string()),
+            ),
+            "mod1".to_string(),
+            DockerConfig::new("ubuntu".to_string(), ContainerCreateBody::new(), None).unwrap(),
+        )
+        .unwrap();
+
+        tokio::runtime::current_thread::Runtime::new()
+            .unwrap()
+            .block_on(docker_module.runtime_state())
+            .unwrap()
+    }
+
+    #[test]
+    fn module_runtime_state_with_health() {
+        let runtime_state = runtime_state_with_health(
+            Health::new()
+                .with_status("unhealthy".to_string())
+                .with_failing_streak(3)
+                .with_log(vec![
+                    HealthcheckResult::new().with_output("first".to_string()),
+                    HealthcheckResult::new().with_output("last".to_string()),
+                ]),
+        );
+
+        let health = runtime_state.health().unwrap();
+        assert_eq!(HealthStatus::Unhealthy, health.status());
+        assert_eq!(3, health.failing_streak());
+        assert_eq!(Some("last"), health.last_output());
+    }
+
+    #[test]
+    fn module_runtime_state_without_health_check() {
+        let runtime_state =
+            runtime_state_with_health(Health::new().with_status("none".to_string()));
+        assert_eq!(None, runtime_state.health());
+    }
+
     #[test]
     fn module_runtime_state_failed_from_dead() {
         let started_at = Utc::now().to_rfc3339();
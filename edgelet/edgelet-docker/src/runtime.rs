@@ -9,6 +9,7 @@ use base64;
 use failure::{Fail, ResultExt};
 use futures::prelude::*;
 use futures::{future, stream, Async, Stream};
+use hyper::upgrade::Upgraded;
 use hyper::{Body, Chunk as HyperChunk, Client};
 use log::Level;
 use serde_json;
@@ -18,21 +19,27 @@ use client::DockerClient;
 use config::DockerConfig;
 use docker::apis::client::APIClient;
 use docker::apis::configuration::Configuration;
-use docker::models::{ContainerCreateBody, NetworkConfig};
+use docker::models::{
+    ContainerCreateBody, ExecConfig, ExecStartConfig, NetworkConfig, Volume, VolumeConfig,
+};
 use edgelet_core::{
-    LogOptions, Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeState, ModuleSpec,
-    RegistryOperation, RuntimeOperation, SystemInfo as CoreSystemInfo,
+    ExecOptions, ExecStatus, LogOptions, Module, ModuleRegistry, ModuleRuntime, ModuleRuntimeState,
+    ModuleSpec, ModuleVolume, RegistryOperation, RuntimeOperation, SystemInfo as CoreSystemInfo,
+    NEXT_VERSION_SUFFIX,
 };
 use edgelet_http::{UrlConnector, UrlExt};
 use edgelet_utils::{ensure_not_empty_with_context, log_failure};
 
 use error::{Error, ErrorKind, Result};
+use limits::ResourceLimits;
 use module::{DockerModule, MODULE_TYPE as DOCKER_MODULE_TYPE};
+use trust::{digest_matches, ImageTrust};
 
 const WAIT_BEFORE_KILL_SECONDS: i32 = 10;
 
 static LABEL_KEY: &str = "net.azure-devices.edge.owner";
 static LABEL_VALUE: &str = "Microsoft.Azure.Devices.Edge.Agent";
+static MODULE_LABEL_KEY: &str = "net.azure-devices.edge.module";
 
 lazy_static! {
     static ref LABELS: Vec<&'static str> = {
@@ -46,6 +53,8 @@ lazy_static! {
 pub struct DockerModuleRuntime {
     client: DockerClient<UrlConnector>,
     network_id: Option<String>,
+    image_trust: Option<ImageTrust>,
+    resource_limits: Option<ResourceLimits>,
 }
 
 impl DockerModuleRuntime {
@@ -73,6 +82,8 @@ impl DockerModuleRuntime {
         Ok(DockerModuleRuntime {
             client: DockerClient::new(APIClient::new(configuration)),
             network_id: None,
+            image_trust: None,
+            resource_limits: None,
         })
     }
 
@@ -81,6 +92,234 @@ impl DockerModuleRuntime {
         self
     }
 
+    pub fn with_image_trust(mut self, image_trust: ImageTrust) -> Self {
+        self.image_trust = Some(image_trust);
+        self
+    }
+
+    pub fn with_resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = Some(resource_limits);
+        self
+    }
+
+    /// Checks the local image against the digest pinned in the module config
+    /// (if any) and, when a trust root is configured, requires a valid
+    /// detached signature for the image id or one of its repository digests.
+    fn verify_image(
+        &self,
+        name: &str,
+        config: &DockerConfig,
+    ) -> Box<Future<Item = (), Error = Error> + Send> {
+        if config.image_id().is_none() && self.image_trust.is_none() {
+            return Box::new(future::ok(()));
+        }
+
+        let name = name.to_string();
+        let image = config.image().to_string();
+        let pinned = config.image_id().map(ToString::to_string);
+        let image_trust = self.image_trust.clone();
+
+        debug!("Verifying image {} for module {}", image, name);
+
+        let name_copy = name.clone();
+        Box::new(
+            self.client
+                .image_api()
+                .image_inspect(&image)
+                .map_err(|err| {
+                    Error::from_docker_error(
+                        err,
+                        ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(name_copy)),
+                    )
+                })
+                .and_then(move |inspected| {
+                    let context = ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(name));
+
+                    if let Some(pinned) = pinned {
+                        if !digest_matches(&inspected, &pinned) {
+                            return Err(Error::from(
+                                ErrorKind::ImageDigestMismatch(image, pinned).context(context),
+                            ));
+                        }
+                    }
+
+                    if let Some(image_trust) = image_trust {
+                        let signed = ::std::iter::once(inspected.id().as_str())
+                            .chain(
+                                inspected
+                                    .repo_digests()
+                                    .unwrap_or(&[])
+                                    .iter()
+                                    .filter_map(|d| d.rsplit('@').next()),
+                            )
+                            .any(|digest| image_trust.verify(digest));
+                        if !signed {
+                            return Err(Error::from(
+                                ErrorKind::ImageSignature(image).context(context),
+                            ));
+                        }
+                    }
+
+                    debug!("Successfully verified image {}", image);
+                    Ok(())
+                }),
+        )
+    }
+
+    /// Creates the named volumes used by a module up front so that they carry
+    /// the labels identifying the module that owns them. A volume that already
+    /// exists is left as it is: the module does not take ownership of it, so
+    /// purging the module's volumes never removes it.
+    fn create_volumes(
+        &self,
+        module: &str,
+        volumes: Vec<String>,
+    ) -> Box<Future<Item = (), Error = Error> + Send> {
+        let created = volumes
+            .into_iter()
+            .map(|volume| {
+                let mut labels = HashMap::new();
+                labels.insert(LABEL_KEY.to_string(), LABEL_VALUE.to_string());
+                labels.insert(MODULE_LABEL_KEY.to_string(), module.to_string());
+
+                let module = module.to_string();
+                let runtime = self.clone();
+                self.client
+                    .volume_api()
+                    .volume_inspect(&volume)
+                    .then(move |result| match result {
+                        Ok(_) => {
+                            debug!(
+                                "Volume {} already exists and is not owned by module {}",
+                                volume, module
+                            );
+                            future::Either::A(future::ok(()))
+                        }
+                        Err(err) => {
+                            let err = Error::from_docker_error(
+                                err,
+                                ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(
+                                    module.clone(),
+                                )),
+                            );
+                            let not_found = match err.kind() {
+                                ErrorKind::NotFound(_) => true,
+                                _ => false,
+                            };
+                            if !not_found {
+                                return future::Either::A(future::err(err));
+                            }
+
+                            debug!("Creating volume {} for module {}", volume, module);
+                            future::Either::B(
+                                runtime
+                                    .client
+                                    .volume_api()
+                                    .volume_create(
+                                        VolumeConfig::new().with_name(volume).with_labels(labels),
+                                    )
+                                    .map(|_| ())
+                                    .map_err(|err| {
+                                        Error::from_docker_error(
+                                            err,
+                                            ErrorKind::RuntimeOperation(
+                                                RuntimeOperation::CreateModule(module),
+                                            ),
+                                        )
+                                    }),
+                            )
+                        }
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        Box::new(future::join_all(created).map(|_| ()))
+    }
+
+    /// Removes a module's container. With `remove_volumes` docker also removes
+    /// the anonymous volumes of the container.
+    fn remove_container(
+        &self,
+        id: &str,
+        remove_volumes: bool,
+    ) -> Box<Future<Item = (), Error = Error> + Send> {
+        info!("Removing module {}...", id);
+
+        let id = id.to_string();
+
+        if let Err(err) = ensure_not_empty_with_context(&id, || {
+            ErrorKind::RuntimeOperation(RuntimeOperation::RemoveModule(id.clone()))
+        }) {
+            return Box::new(future::err(Error::from(err)));
+        }
+
+        Box::new(
+            self.client
+                .container_api()
+                .container_delete(
+                    &id,
+                    remove_volumes,
+                    /* force */ true,
+                    /* remove link */ false,
+                )
+                .then(|result| match result {
+                    Ok(_) => {
+                        info!("Successfully removed module {}", id);
+                        Ok(())
+                    }
+                    Err(err) => {
+                        let err = Error::from_docker_error(
+                            err,
+                            ErrorKind::RuntimeOperation(RuntimeOperation::RemoveModule(id)),
+                        );
+                        log_failure(Level::Warn, &err);
+                        Err(err)
+                    }
+                }),
+        )
+    }
+
+    /// Inspects a volume and makes sure it belongs to `module`. Volumes owned
+    /// by other modules (or not created by the runtime at all) are reported as
+    /// not found.
+    fn inspect_volume(
+        &self,
+        module: &str,
+        name: &str,
+        operation: fn(String, String) -> RuntimeOperation,
+    ) -> Box<Future<Item = ModuleVolume, Error = Error> + Send> {
+        let module = module.to_string();
+        let name = name.to_string();
+
+        if let Err(err) = ensure_not_empty_with_context(&name, || {
+            ErrorKind::RuntimeOperation(operation(module.clone(), name.clone()))
+        }) {
+            return Box::new(future::err(Error::from(err)));
+        }
+
+        Box::new(
+            self.client
+                .volume_api()
+                .volume_inspect(&name)
+                .then(move |result| match result {
+                    Ok(ref volume) if is_module_volume(volume, &module) => {
+                        Ok(module_volume(&module, volume))
+                    }
+                    Ok(_) => Err(Error::from(
+                        ErrorKind::NotFound(format!(
+                            "No such volume for module {}: {}",
+                            module, name
+                        ))
+                        .context(ErrorKind::RuntimeOperation(operation(module, name))),
+                    )),
+                    Err(err) => Err(Error::from_docker_error(
+                        err,
+                        ErrorKind::RuntimeOperation(operation(module, name)),
+                    )),
+                }),
+        )
+    }
+
     fn merge_env(cur_env: Option<&[String]>, new_env: &HashMap<String, String>) -> Vec<String> {
         // build a new merged hashmap containing string slices for keys and values
         // pointing into String instances in new_env
@@ -194,6 +433,8 @@ impl ModuleRuntime for DockerModuleRuntime {
     type ModuleRegistry = Self;
     type Chunk = Chunk;
     type Logs = Logs;
+    type VolumeArchive = VolumeArchive;
+    type ExecIo = Upgraded;
 
     type CreateFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type InitFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
@@ -202,11 +443,19 @@ impl ModuleRuntime for DockerModuleRuntime {
         Box<Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error> + Send>;
     type LogsFuture = Box<Future<Item = Self::Logs, Error = Self::Error> + Send>;
     type RemoveFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type RenameFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type RestartFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type StartFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type StopFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error> + Send>;
     type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type ListVolumesFuture = Box<Future<Item = Vec<ModuleVolume>, Error = Self::Error> + Send>;
+    type GetVolumeFuture = Box<Future<Item = ModuleVolume, Error = Self::Error> + Send>;
+    type BackupVolumeFuture = Box<Future<Item = Self::VolumeArchive, Error = Self::Error> + Send>;
+    type RemoveVolumeFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type ExecFuture = Box<Future<Item = String, Error = Self::Error> + Send>;
+    type ExecStartFuture = Box<Future<Item = Self::ExecIo, Error = Self::Error> + Send>;
+    type ExecInspectFuture = Box<Future<Item = ExecStatus, Error = Self::Error> + Send>;
 
     fn init(&self) -> Self::InitFuture {
         info!("Initializing module runtime...");
@@ -288,21 +537,38 @@ impl ModuleRuntime for DockerModuleRuntime {
                     .with_env(merged_env)
                     .with_labels(labels);
 
+                // Fill in and enforce the device's resource limits before anything
+                // is created on behalf of the module.
+                let create_options = match self.resource_limits {
+                    Some(ref limits) => limits.apply(module.name(), create_options)?,
+                    None => create_options,
+                };
+
                 // Here we don't add the container to the iot edge docker network as the edge-agent is expected to do that.
                 // It contains the logic to add a container to the iot edge network only if a network is not already specified.
 
+                // The container is only created once the image passes verification
+                // and the module's named volumes have been created. The volumes of
+                // the new version of a module being updated belong to the module.
+                let owner = module.name().trim_right_matches(NEXT_VERSION_SUFFIX);
+                let create_volumes = self.create_volumes(owner, named_volumes(&create_options));
+                let client = self.client.clone();
                 Ok(self
-                    .client
-                    .container_api()
-                    .container_create(create_options, module.name())
-                    .then(|result| match result {
-                        Ok(_) => Ok(module),
-                        Err(err) => Err(Error::from_docker_error(
-                            err,
-                            ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(
-                                module.name().to_string(),
-                            )),
-                        )),
+                    .verify_image(module.name(), module.config())
+                    .and_then(move |_| create_volumes)
+                    .and_then(move |_| {
+                        client
+                            .container_api()
+                            .container_create(create_options, module.name())
+                            .then(|result| match result {
+                                Ok(_) => Ok(module),
+                                Err(err) => Err(Error::from_docker_error(
+                                    err,
+                                    ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(
+                                        module.name().to_string(),
+                                    )),
+                                )),
+                            })
                     }))
             })
             .into_future()
@@ -462,12 +728,17 @@ impl ModuleRuntime for DockerModuleRuntime {
     }
 
     fn remove(&self, id: &str) -> Self::RemoveFuture {
-        info!("Removing module {}...", id);
+        self.remove_container(id, false)
+    }
+
+    fn rename(&self, id: &str, new_name: &str) -> Self::RenameFuture {
+        info!("Renaming module {} to {}...", id, new_name);
 
         let id = id.to_string();
+        let new_name = new_name.to_string();
 
-        if let Err(err) = ensure_not_empty_with_context(&id, || {
-            ErrorKind::RuntimeOperation(RuntimeOperation::RemoveModule(id.clone()))
+        if let Err(err) = ensure_not_empty_with_context(&new_name, || {
+            ErrorKind::RuntimeOperation(RuntimeOperation::RenameModule(id.clone()))
         }) {
             return Box::new(future::err(Error::from(err)));
         }
@@ -475,19 +746,16 @@ impl ModuleRuntime for DockerModuleRuntime {
         Box::new(
             self.client
                 .container_api()
-                .container_delete(
-                    &id, /* remove volumes */ false, /* force */ true,
-                    /* remove link */ false,
-                )
-                .then(|result| match result {
+                .container_rename(&id, &new_name)
+                .then(move |result| match result {
                     Ok(_) => {
-                        info!("Successfully removed module {}", id);
+                        info!("Successfully renamed module {} to {}", id, new_name);
                         Ok(())
                     }
                     Err(err) => {
                         let err = Error::from_docker_error(
                             err,
-                            ErrorKind::RuntimeOperation(RuntimeOperation::RemoveModule(id)),
+                            ErrorKind::RuntimeOperation(RuntimeOperation::RenameModule(id)),
                         );
                         log_failure(Level::Warn, &err);
                         Err(err)
@@ -597,15 +865,363 @@ impl ModuleRuntime for DockerModuleRuntime {
         self
     }
 
-    fn remove_all(&self) -> Self::RemoveAllFuture {
+    fn remove_all(&self, purge_volumes: bool) -> Self::RemoveAllFuture {
         let self_for_remove = self.clone();
         Box::new(self.list().and_then(move |list| {
             let n = list.into_iter().map(move |c| {
-                <DockerModuleRuntime as ModuleRuntime>::remove(&self_for_remove, c.name())
+                let removed = self_for_remove.remove_container(c.name(), purge_volumes);
+                if purge_volumes {
+                    // volumes can only be removed once the container using them is gone
+                    let self_for_purge = self_for_remove.clone();
+                    let name = c.name().to_string();
+                    Box::new(removed.and_then(move |_| {
+                        self_for_purge.list_volumes(&name).and_then(move |volumes| {
+                            future::join_all(
+                                volumes
+                                    .into_iter()
+                                    .map(move |v| {
+                                        self_for_purge.remove_volume(v.module(), v.name())
+                                    })
+                                    .collect::<Vec<_>>(),
+                            )
+                            .map(|_| ())
+                        })
+                    })) as <DockerModuleRuntime as ModuleRuntime>::RemoveFuture
+                } else {
+                    removed
+                }
             });
             future::join_all(n).map(|_| ())
         }))
     }
+
+    fn list_volumes(&self, module: &str) -> Self::ListVolumesFuture {
+        debug!("Listing volumes of module {}...", module);
+
+        let module = module.to_string();
+
+        let mut labels: Vec<String> = LABELS.iter().map(ToString::to_string).collect();
+        labels.push(format!("{}={}", MODULE_LABEL_KEY, module));
+        let mut filters = HashMap::new();
+        filters.insert("label", labels);
+
+        let result = serde_json::to_string(&filters)
+            .context(ErrorKind::RuntimeOperation(RuntimeOperation::ListVolumes(
+                module.clone(),
+            )))
+            .map_err(Error::from)
+            .map(|filters| {
+                let module_copy = module.clone();
+                self.client
+                    .volume_api()
+                    .volume_list(&filters)
+                    .map(move |response| {
+                        response
+                            .volumes()
+                            .iter()
+                            .map(|volume| module_volume(&module_copy, volume))
+                            .collect()
+                    })
+                    .map_err(|err| {
+                        Error::from_docker_error(
+                            err,
+                            ErrorKind::RuntimeOperation(RuntimeOperation::ListVolumes(module)),
+                        )
+                    })
+            })
+            .into_future()
+            .flatten()
+            .then(|result| {
+                match result {
+                    Ok(_) => debug!("Successfully listed volumes"),
+                    Err(ref err) => log_failure(Level::Warn, err),
+                }
+
+                result
+            });
+        Box::new(result)
+    }
+
+    fn get_volume(&self, module: &str, name: &str) -> Self::GetVolumeFuture {
+        debug!("Getting volume {} of module {}...", name, module);
+
+        Box::new(
+            self.inspect_volume(module, name, RuntimeOperation::GetVolume)
+                .then(|result| {
+                    if let Err(ref err) = result {
+                        log_failure(Level::Warn, err);
+                    }
+                    result
+                }),
+        )
+    }
+
+    fn backup_volume(&self, module: &str, name: &str) -> Self::BackupVolumeFuture {
+        info!("Backing up volume {} of module {}...", name, module);
+
+        let module = module.to_string();
+        let name = name.to_string();
+        let client = self.client.clone();
+        let client_for_archive = self.client.clone();
+
+        let result = self
+            .inspect_volume(&module, &name, RuntimeOperation::BackupVolume)
+            .and_then(move |_| {
+                let context = ErrorKind::RuntimeOperation(RuntimeOperation::BackupVolume(
+                    module.clone(),
+                    name.clone(),
+                ));
+                client
+                    .container_api()
+                    .container_inspect(&module, false)
+                    .then(move |result| -> Result<(String, String, String)> {
+                        let container = result.map_err(|err| {
+                            Error::from_docker_error(
+                                err,
+                                ErrorKind::RuntimeOperation(RuntimeOperation::BackupVolume(
+                                    module.clone(),
+                                    name.clone(),
+                                )),
+                            )
+                        })?;
+
+                        // the archive is taken through the container, from the
+                        // path the volume is mounted at
+                        let destination = container
+                            .mounts()
+                            .unwrap_or(&[])
+                            .iter()
+                            .find(|mount| mount.name() == Some(name.as_str()))
+                            .and_then(|mount| mount.destination())
+                            .map(ToString::to_string)
+                            .ok_or_else(|| {
+                                Error::from(
+                                    ErrorKind::NotFound(format!(
+                                        "Volume {} is not mounted in module {}",
+                                        name, module
+                                    ))
+                                    .context(context),
+                                )
+                            })?;
+
+                        Ok((module, name, destination))
+                    })
+            })
+            .and_then(move |(module, name, destination)| {
+                client_for_archive
+                    .container_api()
+                    .container_archive(&module, &destination)
+                    .then(|result| match result {
+                        Ok(body) => Ok(VolumeArchive(module, name, body)),
+                        Err(err) => Err(Error::from_docker_error(
+                            err,
+                            ErrorKind::RuntimeOperation(RuntimeOperation::BackupVolume(
+                                module, name,
+                            )),
+                        )),
+                    })
+            })
+            .then(|result| {
+                match result {
+                    Ok(ref archive) => info!(
+                        "Successfully started backup of volume {} of module {}",
+                        archive.1, archive.0
+                    ),
+                    Err(ref err) => log_failure(Level::Warn, err),
+                }
+                result
+            });
+        Box::new(result)
+    }
+
+    fn remove_volume(&self, module: &str, name: &str) -> Self::RemoveVolumeFuture {
+        info!("Removing volume {} of module {}...", name, module);
+
+        let module = module.to_string();
+        let name = name.to_string();
+        let client = self.client.clone();
+
+        Box::new(
+            self.inspect_volume(&module, &name, RuntimeOperation::RemoveVolume)
+                .and_then(move |_| {
+                    client
+                        .volume_api()
+                        .volume_delete(&name, false)
+                        .then(|result| match result {
+                            Ok(_) => {
+                                info!("Successfully removed volume {} of module {}", name, module);
+                                Ok(())
+                            }
+                            Err(err) => Err(Error::from_docker_error(
+                                err,
+                                ErrorKind::RuntimeOperation(RuntimeOperation::RemoveVolume(
+                                    module, name,
+                                )),
+                            )),
+                        })
+                })
+                .then(|result| {
+                    if let Err(ref err) = result {
+                        log_failure(Level::Warn, err);
+                    }
+                    result
+                }),
+        )
+    }
+
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture {
+        info!("Setting up command in module {}...", id);
+
+        let id = id.to_string();
+
+        if let Err(err) = ensure_not_empty_with_context(&id, || {
+            ErrorKind::RuntimeOperation(RuntimeOperation::ExecModule(id.clone()))
+        }) {
+            return Box::new(future::err(Error::from(err)));
+        }
+
+        let mut exec_config = ExecConfig::new()
+            .with_attach_stdin(options.attach_stdin())
+            .with_attach_stdout(true)
+            .with_attach_stderr(true)
+            .with_tty(options.tty())
+            .with_cmd(options.cmd().to_vec());
+        if let Some(user) = options.user() {
+            exec_config = exec_config.with_user(user.to_string());
+        }
+
+        let result = self
+            .client
+            .exec_api()
+            .container_exec(&id, exec_config)
+            .then(move |result| match result {
+                Ok(exec) => {
+                    info!("Successfully set up command {} in module {}", exec.id(), id);
+                    Ok(exec.id().to_string())
+                }
+                Err(err) => {
+                    let err = Error::from_docker_error(
+                        err,
+                        ErrorKind::RuntimeOperation(RuntimeOperation::ExecModule(id)),
+                    );
+                    log_failure(Level::Warn, &err);
+                    Err(err)
+                }
+            });
+        Box::new(result)
+    }
+
+    fn exec_start(&self, exec_id: &str, tty: bool) -> Self::ExecStartFuture {
+        info!("Starting command {}...", exec_id);
+
+        let exec_id = exec_id.to_string();
+
+        if let Err(err) = ensure_not_empty_with_context(&exec_id, || {
+            ErrorKind::RuntimeOperation(RuntimeOperation::StartExec(exec_id.clone()))
+        }) {
+            return Box::new(future::err(Error::from(err)));
+        }
+
+        let result = self
+            .client
+            .exec_api()
+            .exec_start(
+                &exec_id,
+                ExecStartConfig::new().with_detach(false).with_tty(tty),
+            )
+            .then(move |result| match result {
+                Ok(io) => {
+                    info!("Successfully started command {}", exec_id);
+                    Ok(io)
+                }
+                Err(err) => {
+                    let err = Error::from_docker_error(
+                        err,
+                        ErrorKind::RuntimeOperation(RuntimeOperation::StartExec(exec_id)),
+                    );
+                    log_failure(Level::Warn, &err);
+                    Err(err)
+                }
+            });
+        Box::new(result)
+    }
+
+    fn exec_inspect(&self, exec_id: &str) -> Self::ExecInspectFuture {
+        let exec_id = exec_id.to_string();
+
+        if let Err(err) = ensure_not_empty_with_context(&exec_id, || {
+            ErrorKind::RuntimeOperation(RuntimeOperation::InspectExec(exec_id.clone()))
+        }) {
+            return Box::new(future::err(Error::from(err)));
+        }
+
+        let result = self
+            .client
+            .exec_api()
+            .exec_inspect(&exec_id)
+            .then(move |result| match result {
+                Ok(exec) => {
+                    let running = exec.running().cloned().unwrap_or(false);
+                    Ok(ExecStatus::new(running, exec.exit_code()))
+                }
+                Err(err) => {
+                    let context =
+                        ErrorKind::RuntimeOperation(RuntimeOperation::InspectExec(exec_id));
+                    let err = Error::from_docker_error(err, context);
+                    log_failure(Level::Warn, &err);
+                    Err(err)
+                }
+            });
+        Box::new(result)
+    }
+}
+
+/// Returns the names of the named volumes (as opposed to bind mounted host
+/// paths) referenced by the create options of a module.
+fn named_volumes(create_options: &ContainerCreateBody) -> Vec<String> {
+    let host_config = match create_options.host_config() {
+        Some(host_config) => host_config,
+        None => return vec![],
+    };
+
+    let binds = host_config
+        .binds()
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|bind| bind.split(':').next())
+        // volume names are at least two characters long, which also rules out
+        // the drive letter of a windows host path
+        .filter(|source| source.len() > 1 && !source.contains('/') && !source.contains('\\'));
+
+    let mounts = host_config
+        .mounts()
+        .unwrap_or(&[])
+        .iter()
+        .filter(|mount| mount._type() == Some("volume"))
+        .filter_map(|mount| mount.source())
+        .filter(|source| !source.is_empty());
+
+    let mut volumes: Vec<String> = binds.chain(mounts).map(ToString::to_string).collect();
+    volumes.sort();
+    volumes.dedup();
+    volumes
+}
+
+fn is_module_volume(volume: &Volume, module: &str) -> bool {
+    volume.labels().map_or(false, |labels| {
+        labels.get(LABEL_KEY).map(String::as_str) == Some(LABEL_VALUE)
+            && labels.get(MODULE_LABEL_KEY).map(String::as_str) == Some(module)
+    })
+}
+
+fn module_volume(module: &str, volume: &Volume) -> ModuleVolume {
+    ModuleVolume::new(
+        volume.name().to_string(),
+        module.to_string(),
+        volume.driver().to_string(),
+        volume.mountpoint().to_string(),
+    )
+    .with_created_at(volume.created_at().map(ToString::to_string))
 }
 
 #[derive(Debug)]
@@ -632,6 +1248,31 @@ impl From<Logs> for Body {
     }
 }
 
+/// Tar archive of the contents of a module volume.
+#[derive(Debug)]
+pub struct VolumeArchive(String, String, Body);
+
+impl Stream for VolumeArchive {
+    type Item = Chunk;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.2.poll() {
+            Ok(Async::Ready(chunk)) => Ok(Async::Ready(chunk.map(Chunk))),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(err) => Err(Error::from(err.context(ErrorKind::RuntimeOperation(
+                RuntimeOperation::BackupVolume(self.0.clone(), self.1.clone()),
+            )))),
+        }
+    }
+}
+
+impl From<VolumeArchive> for Body {
+    fn from(archive: VolumeArchive) -> Self {
+        archive.2
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Chunk(HyperChunk);
 
@@ -698,6 +1339,8 @@ where
 mod tests {
     use super::*;
 
+    use std::io::Cursor;
+
     use futures::future::FutureResult;
     use futures::stream::Empty;
     #[cfg(unix)]
@@ -705,7 +1348,7 @@ mod tests {
     use tokio;
     use url::Url;
 
-    use docker::models::ContainerCreateBody;
+    use docker::models::{ContainerCreateBody, HostConfig, Mount};
     use edgelet_core::pid::Pid;
     use edgelet_core::ModuleRegistry;
 
@@ -824,6 +1467,36 @@ mod tests {
         assert_eq!(vec!["k1=v1", "k2=v2", "k3=v3"], merged_env);
     }
 
+    #[test]
+    fn named_volumes_empty_without_host_config() {
+        assert!(named_volumes(&ContainerCreateBody::new()).is_empty());
+    }
+
+    #[test]
+    fn named_volumes_skips_host_paths() {
+        let create_options = ContainerCreateBody::new().with_host_config(
+            HostConfig::new()
+                .with_binds(vec![
+                    "data:/data".to_string(),
+                    "/var/run/docker.sock:/var/run/docker.sock".to_string(),
+                    "C:\\data:C:\\data".to_string(),
+                    "data:/data2:ro".to_string(),
+                ])
+                .with_mounts(vec![
+                    Mount::new()
+                        .with__type("volume".to_string())
+                        .with_source("cache".to_string())
+                        .with_target("/cache".to_string()),
+                    Mount::new()
+                        .with__type("bind".to_string())
+                        .with_source("/tmp".to_string())
+                        .with_target("/tmp".to_string()),
+                ]),
+        );
+
+        assert_eq!(vec!["cache", "data"], named_volumes(&create_options));
+    }
+
     #[test]
     fn create_fails_for_non_docker_type() {
         let mri = DockerModuleRuntime::new(&Url::parse("http://localhost/").unwrap()).unwrap();
@@ -1158,6 +1831,8 @@ mod tests {
         type ModuleRegistry = Self;
         type Chunk = String;
         type Logs = Empty<Self::Chunk, Self::Error>;
+        type VolumeArchive = Empty<Self::Chunk, Self::Error>;
+        type ExecIo = Cursor<Vec<u8>>;
 
         type CreateFuture = FutureResult<(), Self::Error>;
         type InitFuture = FutureResult<(), Self::Error>;
@@ -1166,11 +1841,19 @@ mod tests {
             Box<Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error> + Send>;
         type LogsFuture = FutureResult<Self::Logs, Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type RenameFuture = FutureResult<(), Self::Error>;
         type RestartFuture = FutureResult<(), Self::Error>;
         type StartFuture = FutureResult<(), Self::Error>;
         type StopFuture = FutureResult<(), Self::Error>;
         type SystemInfoFuture = FutureResult<CoreSystemInfo, Self::Error>;
         type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type ListVolumesFuture = FutureResult<Vec<ModuleVolume>, Self::Error>;
+        type GetVolumeFuture = FutureResult<ModuleVolume, Self::Error>;
+        type BackupVolumeFuture = FutureResult<Self::VolumeArchive, Self::Error>;
+        type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+        type ExecFuture = FutureResult<String, Self::Error>;
+        type ExecStartFuture = FutureResult<Self::ExecIo, Self::Error>;
+        type ExecInspectFuture = FutureResult<ExecStatus, Self::Error>;
 
         fn init(&self) -> Self::InitFuture {
             unimplemented!()
@@ -1200,6 +1883,10 @@ mod tests {
             unimplemented!()
         }
 
+        fn rename(&self, _id: &str, _new_name: &str) -> Self::RenameFuture {
+            unimplemented!()
+        }
+
         fn list(&self) -> Self::ListFuture {
             future::ok(self.modules.clone())
         }
@@ -1216,7 +1903,35 @@ mod tests {
             self
         }
 
-        fn remove_all(&self) -> Self::RemoveAllFuture {
+        fn remove_all(&self, _purge_volumes: bool) -> Self::RemoveAllFuture {
+            unimplemented!()
+        }
+
+        fn list_volumes(&self, _module: &str) -> Self::ListVolumesFuture {
+            unimplemented!()
+        }
+
+        fn get_volume(&self, _module: &str, _name: &str) -> Self::GetVolumeFuture {
+            unimplemented!()
+        }
+
+        fn backup_volume(&self, _module: &str, _name: &str) -> Self::BackupVolumeFuture {
+            unimplemented!()
+        }
+
+        fn remove_volume(&self, _module: &str, _name: &str) -> Self::RemoveVolumeFuture {
+            unimplemented!()
+        }
+
+        fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+            unimplemented!()
+        }
+
+        fn exec_start(&self, _exec_id: &str, _tty: bool) -> Self::ExecStartFuture {
+            unimplemented!()
+        }
+
+        fn exec_inspect(&self, _exec_id: &str) -> Self::ExecInspectFuture {
             unimplemented!()
         }
     }
@@ -0,0 +1,207 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use base64;
+use failure::ResultExt;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Public};
+use openssl::sign::Verifier;
+
+use docker::models::Image;
+
+use error::{ErrorKind, Result};
+
+/// Trust root used to verify detached image signatures before a container
+/// is created.
+///
+/// Signatures are expected to be stored locally in `signatures_dir`, one file
+/// per image digest. The file for digest `sha256:abc...` is named
+/// `sha256_abc....sig` and contains the base64 encoded signature of the
+/// digest string, produced with the private key matching `public_key`.
+#[derive(Clone)]
+pub struct ImageTrust {
+    public_key: Arc<PKey<Public>>,
+    signatures_dir: PathBuf,
+}
+
+impl ImageTrust {
+    pub fn new(public_key_pem: &[u8], signatures_dir: PathBuf) -> Result<Self> {
+        let public_key =
+            PKey::public_key_from_pem(public_key_pem).context(ErrorKind::ImageTrust)?;
+
+        Ok(ImageTrust {
+            public_key: Arc::new(public_key),
+            signatures_dir,
+        })
+    }
+
+    pub fn from_files<P: AsRef<Path>>(public_key: P, signatures_dir: PathBuf) -> Result<Self> {
+        let pem = fs::read(public_key).context(ErrorKind::ImageTrust)?;
+        ImageTrust::new(&pem, signatures_dir)
+    }
+
+    pub fn signatures_dir(&self) -> &Path {
+        &self.signatures_dir
+    }
+
+    fn signature_path(&self, digest: &str) -> PathBuf {
+        self.signatures_dir
+            .join(format!("{}.sig", digest.replace(':', "_")))
+    }
+
+    /// Returns true if a valid signature for `digest` is stored in the
+    /// signatures directory. A missing or malformed signature file is treated
+    /// the same as an invalid signature.
+    pub fn verify(&self, digest: &str) -> bool {
+        fs::read_to_string(self.signature_path(digest))
+            .ok()
+            .and_then(|s| base64::decode(s.trim()).ok())
+            .and_then(|signature| {
+                Verifier::new(MessageDigest::sha256(), &self.public_key)
+                    .and_then(|mut verifier| {
+                        verifier.update(digest.as_bytes())?;
+                        verifier.verify(&signature)
+                    })
+                    .ok()
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Returns true if `pinned` is either the id of the image or one of its
+/// repository digests.
+pub fn digest_matches(image: &Image, pinned: &str) -> bool {
+    image.id() == pinned
+        || image.repo_digests().map_or(false, |digests| {
+            digests
+                .iter()
+                .any(|d| d.rsplit('@').next().map_or(false, |d| d == pinned))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Write;
+
+    use docker::models::{GraphDriverData, ImageRootFs};
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::nid::Nid;
+    use openssl::pkey::Private;
+    use openssl::sign::Signer;
+    use tempfile::TempDir;
+
+    fn new_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    fn sign(key: &PKey<Private>, digest: &str) -> String {
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        signer.update(digest.as_bytes()).unwrap();
+        base64::encode(&signer.sign_to_vec().unwrap())
+    }
+
+    fn write_signature(dir: &Path, digest: &str, signature: &str) {
+        let path = dir.join(format!("{}.sig", digest.replace(':', "_")));
+        let mut file = File::create(path).unwrap();
+        file.write_all(signature.as_bytes()).unwrap();
+    }
+
+    fn image() -> Image {
+        Image::new(
+            "sha256:1111".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            0,
+            0,
+            GraphDriverData::new(String::new()),
+            ImageRootFs::new(String::new()),
+        )
+        .with_repo_digests(vec!["registry.io/module@sha256:2222".to_string()])
+    }
+
+    #[test]
+    fn digest_matches_image_id() {
+        assert!(digest_matches(&image(), "sha256:1111"));
+    }
+
+    #[test]
+    fn digest_matches_repo_digest() {
+        assert!(digest_matches(&image(), "sha256:2222"));
+    }
+
+    #[test]
+    fn digest_mismatch() {
+        assert!(!digest_matches(&image(), "sha256:3333"));
+    }
+
+    #[test]
+    fn valid_signature_verifies() {
+        let dir = TempDir::new().unwrap();
+        let key = new_key();
+        write_signature(dir.path(), "sha256:1111", &sign(&key, "sha256:1111"));
+
+        let trust =
+            ImageTrust::new(&key.public_key_to_pem().unwrap(), dir.path().to_path_buf()).unwrap();
+        assert!(trust.verify("sha256:1111"));
+    }
+
+    #[test]
+    fn signature_for_other_digest_fails() {
+        let dir = TempDir::new().unwrap();
+        let key = new_key();
+        write_signature(dir.path(), "sha256:1111", &sign(&key, "sha256:2222"));
+
+        let trust =
+            ImageTrust::new(&key.public_key_to_pem().unwrap(), dir.path().to_path_buf()).unwrap();
+        assert!(!trust.verify("sha256:1111"));
+    }
+
+    #[test]
+    fn signature_from_untrusted_key_fails() {
+        let dir = TempDir::new().unwrap();
+        write_signature(dir.path(), "sha256:1111", &sign(&new_key(), "sha256:1111"));
+
+        let trust = ImageTrust::new(
+            &new_key().public_key_to_pem().unwrap(),
+            dir.path().to_path_buf(),
+        )
+        .unwrap();
+        assert!(!trust.verify("sha256:1111"));
+    }
+
+    #[test]
+    fn missing_signature_fails() {
+        let dir = TempDir::new().unwrap();
+        let trust = ImageTrust::new(
+            &new_key().public_key_to_pem().unwrap(),
+            dir.path().to_path_buf(),
+        )
+        .unwrap();
+        assert!(!trust.verify("sha256:1111"));
+    }
+
+    #[test]
+    fn invalid_public_key_fails() {
+        let dir = TempDir::new().unwrap();
+        match ImageTrust::new(b"not a key", dir.path().to_path_buf()) {
+            Err(ref err) => match err.kind() {
+                ErrorKind::ImageTrust => (),
+                kind => panic!("Expected `ImageTrust` error but got {:?}.", kind),
+            },
+            Ok(_) => panic!("Expected loading the trust root to fail"),
+        }
+    }
+}
@@ -10,7 +10,6 @@
 
 #[cfg(unix)]
 extern crate base64;
-#[cfg(unix)]
 extern crate failure;
 extern crate futures;
 extern crate hyper;
@@ -30,10 +29,10 @@ use std::str;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-#[cfg(unix)]
 use failure::Fail;
 use futures::prelude::*;
 use futures::{future, Stream};
+use hyper::header::{CONNECTION, UPGRADE};
 use hyper::{Body, Error as HyperError, Method, Request, Response};
 use typed_headers::{mime, ContentLength, ContentType, HeaderMapExt};
 use url::form_urlencoded::parse as parse_query;
@@ -43,10 +42,16 @@ use url::Url;
 use docker::models::AuthConfig;
 use docker::models::{
     ContainerCreateBody, ContainerHostConfig, ContainerNetworkSettings, ContainerSummary,
-    HostConfig, HostConfigPortBindings, ImageDeleteResponseItem,
+    ExecConfig, HostConfig, HostConfigPortBindings, ImageDeleteResponseItem, RestartPolicy,
+    VolumeConfig,
+};
+use edgelet_core::{
+    ExecOptions, LogOptions, LogTail, Module, ModuleRegistry, ModuleRuntime, ModuleSpec,
+};
+use edgelet_docker::{
+    DockerConfig, DockerModuleRuntime, Enforcement, ErrorKind, ResourceLimits, ResourceProfile,
+    RestartPolicyName,
 };
-use edgelet_core::{LogOptions, LogTail, Module, ModuleRegistry, ModuleRuntime, ModuleSpec};
-use edgelet_docker::{DockerConfig, DockerModuleRuntime};
 use edgelet_test_utils::{get_unused_tcp_port, run_tcp_server};
 
 const IMAGE_NAME: &str = "nginx:latest";
@@ -693,6 +698,112 @@ fn container_create_succeeds() {
     runtime.block_on(task).unwrap();
 }
 
+fn image_inspect_response() -> Response<Body> {
+    let response = json!({
+        "Id": "sha256:1111",
+        "RepoTags": ["nginx:latest"],
+        "RepoDigests": ["nginx@sha256:2222"],
+        "Parent": "",
+        "Comment": "",
+        "Created": "2018-10-01T00:00:00Z",
+        "Container": "",
+        "DockerVersion": "18.06.1",
+        "Author": "",
+        "Architecture": "amd64",
+        "Os": "linux",
+        "Size": 0,
+        "VirtualSize": 0,
+        "GraphDriver": { "Name": "overlay2" },
+        "RootFS": { "Type": "layers" }
+    })
+    .to_string();
+    let response_len = response.len();
+
+    let mut response = Response::new(response.into());
+    response
+        .headers_mut()
+        .typed_insert(&ContentLength(response_len as u64));
+    response
+        .headers_mut()
+        .typed_insert(&ContentType(mime::APPLICATION_JSON));
+    response
+}
+
+fn pinned_image_create_task(
+    port: u16,
+    image_id: &str,
+) -> impl Future<Item = (), Error = edgelet_docker::Error> {
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), ContainerCreateBody::new(), None)
+            .unwrap()
+            .with_image_id(image_id.to_string()),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    mri.create(module_config)
+}
+
+#[test]
+fn container_create_with_pinned_digest_succeeds() {
+    let port = get_unused_tcp_port();
+    let created = Arc::new(RwLock::new(false));
+    let created_copy = created.clone();
+
+    let server = run_tcp_server("127.0.0.1", port, move |req: Request<Body>| {
+        let response = match req.uri().path() {
+            "/images/nginx:latest/json" => image_inspect_response(),
+            "/containers/create" => {
+                *created_copy.write().unwrap() = true;
+                Response::new(json!({ "Id": "12345", "Warnings": [] }).to_string().into())
+            }
+            path => panic!("Unexpected request to {}", path),
+        };
+        Box::new(future::ok(response))
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime
+        .block_on(pinned_image_create_task(port, "sha256:2222"))
+        .unwrap();
+
+    assert!(*created.read().unwrap());
+}
+
+#[test]
+fn container_create_with_mismatched_digest_fails() {
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server("127.0.0.1", port, |req: Request<Body>| {
+        // the container must never be created when the digest does not match
+        assert_eq!(req.uri().path(), "/images/nginx:latest/json");
+        Box::new(future::ok(image_inspect_response()))
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let err = runtime
+        .block_on(pinned_image_create_task(port, "sha256:3333"))
+        .unwrap_err();
+
+    match Fail::find_root_cause(&err).downcast_ref::<ErrorKind>() {
+        Some(ErrorKind::ImageDigestMismatch(image, digest)) => {
+            assert_eq!("nginx:latest", image);
+            assert_eq!("sha256:3333", digest);
+        }
+        kind => panic!("Expected `ImageDigestMismatch` error but got {:?}.", kind),
+    }
+}
+
 #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
 fn container_start_handler(
     req: Request<Body>,
@@ -996,6 +1107,458 @@ fn container_logs_succeeds() {
     runtime.block_on(assert).unwrap();
 }
 
+fn volume_json(name: &str, module: &str) -> serde_json::Value {
+    json!({
+        "Name": name,
+        "Driver": "local",
+        "Mountpoint": format!("/var/lib/docker/volumes/{}/_data", name),
+        "CreatedAt": "2018-10-01T00:00:00Z",
+        "Labels": {
+            "net.azure-devices.edge.owner": "Microsoft.Azure.Devices.Edge.Agent",
+            "net.azure-devices.edge.module": module
+        },
+        "Scope": "local"
+    })
+}
+
+#[test]
+fn container_create_with_named_volume_creates_labelled_volume() {
+    let port = get_unused_tcp_port();
+    let volume_created = Arc::new(RwLock::new(false));
+    let volume_created_copy = volume_created.clone();
+
+    let server = run_tcp_server("127.0.0.1", port, move |req: Request<Body>| {
+        let volume_created = volume_created_copy.clone();
+        let response: Box<Future<Item = Response<Body>, Error = HyperError> + Send> =
+            match req.uri().path() {
+                "/volumes/data" => {
+                    let mut response = Response::new(
+                        json!({ "message": "get data: no such volume" })
+                            .to_string()
+                            .into(),
+                    );
+                    *response.status_mut() = hyper::StatusCode::NOT_FOUND;
+                    Box::new(future::ok(response))
+                }
+                "/volumes/create" => Box::new(req.into_body().concat2().map(move |body| {
+                    let config: VolumeConfig = serde_json::from_slice(body.as_ref()).unwrap();
+                    assert_eq!(Some("data"), config.name());
+                    assert_eq!(
+                        Some(&"m1".to_string()),
+                        config
+                            .labels()
+                            .unwrap()
+                            .get("net.azure-devices.edge.module")
+                    );
+                    *volume_created.write().unwrap() = true;
+                    Response::new(volume_json("data", "m1").to_string().into())
+                })),
+                "/containers/create" => {
+                    // the volume has to exist before the container is created
+                    assert!(*volume_created.read().unwrap());
+                    Box::new(future::ok(Response::new(
+                        json!({ "Id": "12345", "Warnings": [] }).to_string().into(),
+                    )))
+                }
+                path => panic!("Unexpected request to {}", path),
+            };
+        response
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let create_options = ContainerCreateBody::new().with_host_config(
+        HostConfig::new().with_binds(vec!["data:/data".to_string(), "/tmp:/tmp".to_string()]),
+    );
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), create_options, None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(mri.create(module_config)).unwrap();
+
+    assert!(*volume_created.read().unwrap());
+}
+
+#[test]
+fn container_create_with_existing_volume_does_not_take_ownership() {
+    let port = get_unused_tcp_port();
+
+    let server = run_tcp_server("127.0.0.1", port, move |req: Request<Body>| {
+        let response: Box<Future<Item = Response<Body>, Error = HyperError> + Send> =
+            match req.uri().path() {
+                "/volumes/data" => {
+                    let mut volume = volume_json("data", "m0");
+                    volume["Labels"] = json!({});
+                    Box::new(future::ok(Response::new(volume.to_string().into())))
+                }
+                "/containers/create" => Box::new(future::ok(Response::new(
+                    json!({ "Id": "12345", "Warnings": [] }).to_string().into(),
+                ))),
+                path => panic!("Unexpected request to {}", path),
+            };
+        response
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let create_options = ContainerCreateBody::new()
+        .with_host_config(HostConfig::new().with_binds(vec!["data:/data".to_string()]));
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), create_options, None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(mri.create(module_config)).unwrap();
+}
+
+#[test]
+fn container_create_applies_resource_limits() {
+    let port = get_unused_tcp_port();
+    let created = Arc::new(RwLock::new(false));
+    let created_copy = created.clone();
+
+    let server = run_tcp_server("127.0.0.1", port, move |req: Request<Body>| {
+        assert_eq!(req.uri().path(), "/containers/create");
+        let created = created_copy.clone();
+        req.into_body().concat2().map(move |body| {
+            let create_options: ContainerCreateBody =
+                serde_json::from_slice(body.as_ref()).unwrap();
+            let host_config = create_options.host_config().unwrap();
+            assert_eq!(Some(1024), host_config.memory());
+            assert_eq!(Some(100), host_config.pids_limit());
+            assert_eq!(
+                Some("unless-stopped"),
+                host_config.restart_policy().and_then(RestartPolicy::name)
+            );
+            *created.write().unwrap() = true;
+            Response::new(json!({ "Id": "12345", "Warnings": [] }).to_string().into())
+        })
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let create_options =
+        ContainerCreateBody::new().with_host_config(HostConfig::new().with_memory(4096));
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), create_options, None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let limits = ResourceLimits::new()
+        .with_default_profile(ResourceProfile::new().with_pids_limit(Some(100)))
+        .with_max_profile(ResourceProfile::new().with_memory(Some(1024)))
+        .with_restart_policy(Some(RestartPolicyName::UnlessStopped));
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_resource_limits(limits);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(mri.create(module_config)).unwrap();
+
+    assert!(*created.read().unwrap());
+}
+
+#[test]
+fn container_create_over_resource_limit_is_rejected() {
+    // nothing listens on the port, the module has to be rejected before any
+    // request is made to docker
+    let port = get_unused_tcp_port();
+
+    let create_options =
+        ContainerCreateBody::new().with_host_config(HostConfig::new().with_cpu_quota(200_000));
+    let module_config = ModuleSpec::new(
+        "m1".to_string(),
+        "docker".to_string(),
+        DockerConfig::new("nginx:latest".to_string(), create_options, None).unwrap(),
+        HashMap::new(),
+    )
+    .unwrap();
+
+    let limits = ResourceLimits::new()
+        .with_max_profile(ResourceProfile::new().with_cpu_quota(Some(50_000)))
+        .with_enforcement(Enforcement::Reject);
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap()
+            .with_resource_limits(limits);
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let err = runtime.block_on(mri.create(module_config)).unwrap_err();
+
+    match Fail::find_root_cause(&err).downcast_ref::<ErrorKind>() {
+        Some(ErrorKind::ResourceLimitExceeded(resource, requested, max)) => {
+            assert_eq!("CPU quota", resource);
+            assert_eq!(200_000, *requested);
+            assert_eq!(50_000, *max);
+        }
+        kind => panic!("Expected `ResourceLimitExceeded` error but got {:?}.", kind),
+    }
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn volume_list_handler(
+    req: Request<Body>,
+) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+    assert_eq!(req.method(), &Method::GET);
+    assert_eq!(req.uri().path(), "/volumes");
+
+    let query_map: HashMap<String, String> = parse_query(req.uri().query().unwrap().as_bytes())
+        .into_owned()
+        .collect();
+    let filters: HashMap<String, Vec<String>> =
+        serde_json::from_str(&query_map["filters"]).unwrap();
+    assert!(filters["label"].contains(&"net.azure-devices.edge.module=m1".to_string()));
+    assert!(filters["label"]
+        .contains(&"net.azure-devices.edge.owner=Microsoft.Azure.Devices.Edge.Agent".to_string()));
+
+    let response = json!({
+        "Volumes": [volume_json("data", "m1"), volume_json("cache", "m1")],
+        "Warnings": []
+    })
+    .to_string();
+
+    Box::new(future::ok(Response::new(response.into())))
+}
+
+#[test]
+fn volume_list_succeeds() {
+    let port = get_unused_tcp_port();
+    let server =
+        run_tcp_server("127.0.0.1", port, volume_list_handler).map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let volumes = runtime.block_on(mri.list_volumes("m1")).unwrap();
+
+    assert_eq!(2, volumes.len());
+    assert_eq!("data", volumes[0].name());
+    assert_eq!("m1", volumes[0].module());
+    assert_eq!("local", volumes[0].driver());
+    assert_eq!(
+        "/var/lib/docker/volumes/data/_data",
+        volumes[0].mount_point()
+    );
+    assert_eq!(Some("2018-10-01T00:00:00Z"), volumes[0].created_at());
+    assert_eq!("cache", volumes[1].name());
+}
+
+#[test]
+fn volume_of_other_module_is_not_found() {
+    let port = get_unused_tcp_port();
+    let server = run_tcp_server("127.0.0.1", port, |req: Request<Body>| {
+        // the volume must never be deleted when it belongs to another module
+        assert_eq!(req.method(), &Method::GET);
+        assert_eq!(req.uri().path(), "/volumes/data");
+        Box::new(future::ok(Response::new(
+            volume_json("data", "m2").to_string().into(),
+        )))
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let err = runtime
+        .block_on(mri.remove_volume("m1", "data"))
+        .unwrap_err();
+
+    match Fail::find_root_cause(&err).downcast_ref::<ErrorKind>() {
+        Some(ErrorKind::NotFound(_)) => (),
+        kind => panic!("Expected `NotFound` error but got {:?}.", kind),
+    }
+}
+
+#[test]
+fn remove_all_with_purge_removes_module_volumes() {
+    let port = get_unused_tcp_port();
+    let requests = Arc::new(RwLock::new(vec![]));
+    let requests_copy = requests.clone();
+    let server = run_tcp_server("127.0.0.1", port, move |req: Request<Body>| {
+        let path = req.uri().path().to_string();
+        requests_copy.write().unwrap().push(format!(
+            "{} {}?{}",
+            req.method(),
+            path,
+            req.uri().query().unwrap_or("")
+        ));
+
+        let response = match (req.method(), path.as_str()) {
+            (&Method::GET, "/containers/json") => {
+                serde_json::to_string(&vec![ContainerSummary::new(
+                    "m1".to_string(),
+                    vec!["/m1".to_string()],
+                    "nginx:latest".to_string(),
+                    "img1".to_string(),
+                    "".to_string(),
+                    10,
+                    vec![],
+                    10,
+                    10,
+                    HashMap::new(),
+                    "".to_string(),
+                    "".to_string(),
+                    ContainerHostConfig::new(""),
+                    ContainerNetworkSettings::new(HashMap::new()),
+                    vec![],
+                )])
+                .unwrap()
+            }
+            (&Method::GET, "/volumes") => json!({
+                "Volumes": [volume_json("data", "m1")],
+                "Warnings": []
+            })
+            .to_string(),
+            (&Method::GET, "/volumes/data") => volume_json("data", "m1").to_string(),
+            _ => String::new(),
+        };
+        Box::new(future::ok(Response::new(response.into())))
+    })
+    .map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    runtime.block_on(mri.remove_all(true)).unwrap();
+
+    let requests = requests.read().unwrap();
+    // the container is deleted together with its anonymous volumes
+    assert!(requests
+        .iter()
+        .any(|r| r.starts_with("DELETE /containers/m1?") && r.contains("v=true")));
+    assert!(requests
+        .iter()
+        .any(|r| r.starts_with("DELETE /volumes/data?")));
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+fn exec_handler(
+    req: Request<Body>,
+) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+    match (req.method(), req.uri().path()) {
+        (&Method::POST, "/containers/mod1/exec") => {
+            Box::new(req.into_body().concat2().map(|body| {
+                let config: ExecConfig = serde_json::from_slice(body.as_ref()).unwrap();
+                assert_eq!(Some(&["cat".to_string()][..]), config.cmd());
+                assert_eq!(Some(&true), config.attach_stdin());
+                assert_eq!(Some(&true), config.attach_stdout());
+                assert_eq!(Some(&true), config.attach_stderr());
+                assert_eq!(Some(&false), config.tty());
+                Response::new(json!({ "Id": "exec1" }).to_string().into())
+            }))
+        }
+        (&Method::POST, "/exec/exec1/start") => {
+            assert_eq!("tcp", req.headers().get(UPGRADE).unwrap());
+
+            // echo the stdin of the command back as its stdout
+            let echo = req
+                .into_body()
+                .on_upgrade()
+                .map_err(|err| panic!("{}", err))
+                .and_then(|io| tokio::io::read_to_end(io, vec![]))
+                .and_then(|(io, input)| {
+                    let mut frame = vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+                    frame.push(input.len() as u8);
+                    frame.extend(input);
+                    tokio::io::write_all(io, frame)
+                })
+                .and_then(|(io, _)| tokio::io::shutdown(io))
+                .map(|_| ())
+                .map_err(|err| panic!("{}", err));
+            tokio::spawn(echo);
+
+            let response = Response::builder()
+                .status(hyper::StatusCode::SWITCHING_PROTOCOLS)
+                .header(CONNECTION, "Upgrade")
+                .header(UPGRADE, "tcp")
+                .body(Body::empty())
+                .unwrap();
+            Box::new(future::ok(response))
+        }
+        (&Method::GET, "/exec/exec1/json") => Box::new(future::ok(Response::new(
+            json!({ "ID": "exec1", "Running": false, "ExitCode": 2 })
+                .to_string()
+                .into(),
+        ))),
+        (method, path) => panic!("Unexpected request {} {}", method, path),
+    }
+}
+
+#[test]
+fn exec_attaches_to_command_and_reports_exit_code() {
+    let port = get_unused_tcp_port();
+    let server =
+        run_tcp_server("127.0.0.1", port, exec_handler).map_err(|err| eprintln!("{}", err));
+
+    let mri =
+        DockerModuleRuntime::new(&Url::parse(&format!("http://localhost:{}/", port)).unwrap())
+            .unwrap();
+    let mri_for_start = mri.clone();
+    let mri_for_inspect = mri.clone();
+
+    let options = ExecOptions::new(vec!["cat".to_string()]).with_attach_stdin(true);
+    let task = mri
+        .exec("mod1", &options)
+        .and_then(move |exec_id| {
+            mri_for_start
+                .exec_start(&exec_id, false)
+                .map(|io| (exec_id, io))
+        })
+        .and_then(|(exec_id, io)| {
+            tokio::io::write_all(io, b"file\n".to_vec())
+                .and_then(|(io, _)| tokio::io::shutdown(io))
+                .and_then(|io| tokio::io::read_to_end(io, vec![]))
+                .map(move |(_, output)| (exec_id, output))
+                .map_err(|err| panic!("{}", err))
+        })
+        .and_then(move |(exec_id, output)| {
+            mri_for_inspect
+                .exec_inspect(&exec_id)
+                .map(|status| (output, status))
+        });
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let (output, status) = runtime.block_on(task).unwrap();
+
+    let expected = [
+        0x01_u8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x66, 0x69, 0x6c, 0x65, 0x0a,
+    ];
+    assert_eq!(&expected[..], &output[..]);
+    assert_eq!(false, status.running());
+    assert_eq!(Some(2), status.exit_code());
+}
+
 #[test]
 fn runtime_init_network_does_not_exist_create() {
     let list_got_called_lock = Arc::new(RwLock::new(false));
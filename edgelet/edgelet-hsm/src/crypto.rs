@@ -6,11 +6,11 @@ use std::sync::{Arc, Mutex};
 use failure::Fail;
 
 use edgelet_core::{
-    Certificate as CoreCertificate, CertificateProperties as CoreCertificateProperties,
-    CreateCertificate as CoreCreateCertificate, Decrypt as CoreDecrypt, Encrypt as CoreEncrypt,
-    Error as CoreError, ErrorKind as CoreErrorKind, GetTrustBundle as CoreGetTrustBundle,
-    KeyBytes as CoreKeyBytes, MasterEncryptionKey as CoreMasterEncryptionKey,
-    PrivateKey as CorePrivateKey,
+    AliasedEncryptionKey as CoreAliasedEncryptionKey, Certificate as CoreCertificate,
+    CertificateProperties as CoreCertificateProperties, CreateCertificate as CoreCreateCertificate,
+    Decrypt as CoreDecrypt, Encrypt as CoreEncrypt, Error as CoreError, ErrorKind as CoreErrorKind,
+    GetTrustBundle as CoreGetTrustBundle, KeyBytes as CoreKeyBytes,
+    MasterEncryptionKey as CoreMasterEncryptionKey, PrivateKey as CorePrivateKey,
 };
 
 use certificate_properties::convert_properties;
@@ -20,9 +20,11 @@ pub use hsm::{
     PrivateKey as HsmPrivateKey,
 };
 use hsm::{
-    CreateCertificate as HsmCreateCertificate,
+    CreateCertificate as HsmCreateCertificate, CreateEncryptionKey as HsmCreateEncryptionKey,
     CreateMasterEncryptionKey as HsmCreateMasterEncryptionKey, Crypto as HsmCrypto,
+    DecryptWithKey as HsmDecryptWithKey, DestroyEncryptionKey as HsmDestroyEncryptionKey,
     DestroyMasterEncryptionKey as HsmDestroyMasterEncryptionKey,
+    EncryptWithKey as HsmEncryptWithKey,
 };
 
 /// The TPM Key Store.
@@ -65,6 +67,58 @@ impl CoreMasterEncryptionKey for Crypto {
     }
 }
 
+impl CoreAliasedEncryptionKey for Crypto {
+    type Buffer = Buffer;
+
+    fn create_aliased_key(&self, alias: &str) -> Result<(), CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .create_encryption_key(alias)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+
+    fn destroy_aliased_key(&self, alias: &str) -> Result<(), CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .destroy_encryption_key(alias)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+
+    fn encrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .encrypt_with_key(alias, client_id, plaintext, initialization_vector)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+
+    fn decrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, CoreError> {
+        self.crypto
+            .lock()
+            .expect("Lock on crypto structure failed")
+            .decrypt_with_key(alias, client_id, ciphertext, initialization_vector)
+            .map_err(|err| Error::from(err.context(ErrorKind::Hsm)))
+            .map_err(|err| CoreError::from(err.context(CoreErrorKind::KeyStore)))
+    }
+}
+
 impl CoreCreateCertificate for Crypto {
     type Certificate = Certificate;
 
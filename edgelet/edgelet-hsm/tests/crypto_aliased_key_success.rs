@@ -0,0 +1,56 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_hsm;
+
+use edgelet_core::crypto::{AliasedEncryptionKey, MasterEncryptionKey};
+use edgelet_hsm::Crypto;
+
+/// Aliased encryption key tests
+#[test]
+fn crypto_aliased_key_outlives_master_key() {
+    // arrange
+    let crypto = Crypto::new().unwrap();
+
+    let alias = "test-aliased-key";
+    let client_id = b"module1";
+    let plaintext = b"plaintext";
+    let iv = b"initialization vector";
+
+    crypto
+        .create_key()
+        .expect("Create master key function returned error");
+    crypto
+        .create_aliased_key(alias)
+        .expect("Create aliased key function returned error");
+
+    //act
+    let ciphertext = crypto
+        .encrypt_with_key(alias, client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+
+    //assert
+    let plaintext_result = crypto
+        .decrypt_with_key(alias, client_id, ciphertext.as_ref(), iv)
+        .expect("Decrypt function returned error");
+    assert_eq!(plaintext, plaintext_result.as_ref());
+
+    // cleanup
+    crypto
+        .destroy_aliased_key(alias)
+        .expect("Destroy aliased key function returned error");
+    crypto
+        .decrypt_with_key(alias, client_id, ciphertext.as_ref(), iv)
+        .expect_err("Decrypt function returned unexpected success");
+}
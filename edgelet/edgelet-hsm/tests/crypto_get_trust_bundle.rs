@@ -8,27 +8,13 @@
 #![allow(renamed_and_removed_lints)]
 #![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
 
-extern crate edgelet_core;
 extern crate edgelet_hsm;
+extern crate edgelet_test_utils;
 
-use edgelet_core::{Certificate, GetTrustBundle};
 use edgelet_hsm::Crypto;
+use edgelet_test_utils::crypto;
 
 #[test]
 fn crypto_get_trust_bundle() {
-    // arrange
-    let crypto = Crypto::new().unwrap();
-
-    // act
-    let cert_info = crypto.get_trust_bundle().unwrap();
-
-    let buffer = cert_info.pem().unwrap();
-
-    if cert_info.get_private_key().unwrap().is_some() {
-        panic!("do not expect to find a key");
-    }
-
-    // assert
-    // assume cert_type is PEM(0)
-    assert!(!buffer.as_bytes().is_empty());
+    crypto::get_trust_bundle(&Crypto::new().unwrap());
 }
@@ -8,34 +8,13 @@
 #![allow(renamed_and_removed_lints)]
 #![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
 
-extern crate edgelet_core;
 extern crate edgelet_hsm;
+extern crate edgelet_test_utils;
 
-use edgelet_core::crypto::MasterEncryptionKey;
 use edgelet_hsm::Crypto;
+use edgelet_test_utils::crypto;
 
-/// Encryption master key tests
 #[test]
 fn crypto_master_key_success() {
-    let crypto = Crypto::new().unwrap();
-
-    crypto
-        .destroy_key()
-        .expect("Destroy master key returned error");
-
-    crypto
-        .create_key()
-        .expect("First create master key function returned error");
-
-    crypto
-        .create_key()
-        .expect("Second master key function returned error");
-
-    crypto
-        .destroy_key()
-        .expect("First destroy master key function returned error");
-
-    crypto
-        .destroy_key()
-        .expect("Second destroy master key function returned error");
+    crypto::master_key_success(&Crypto::new().unwrap());
 }
@@ -0,0 +1,250 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::sync::Arc;
+
+use failure::Fail;
+use futures::future;
+use futures::prelude::*;
+use management::apis::client::APIClient;
+use management::models::{
+    Identity as HttpIdentity, IdentitySpec as HttpIdentitySpec, Twin as HttpTwin,
+    TwinProperties as HttpTwinProperties, UpdateIdentity,
+};
+use serde_json::Value;
+use url::Url;
+
+use edgelet_core::{
+    AuthType, Identity as CoreIdentity, IdentityManager, IdentityOperation, IdentitySpec, Twin,
+    TwinProperties, TwinTarget,
+};
+use edgelet_http::API_VERSION;
+
+use super::api_client;
+use error::{Error, ErrorKind};
+
+/// Manages the module identities of a device through the management API of
+/// its daemon.
+pub struct IdentityClient {
+    client: Arc<APIClient>,
+}
+
+impl IdentityClient {
+    pub fn new(url: &Url) -> Result<Self, Error> {
+        let client = api_client(url, || ErrorKind::InitializeIdentityClient)?;
+        let identity_client = IdentityClient {
+            client: Arc::new(client),
+        };
+        Ok(identity_client)
+    }
+}
+
+impl Clone for IdentityClient {
+    fn clone(&self) -> Self {
+        IdentityClient {
+            client: self.client.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Identity(HttpIdentity, AuthType);
+
+impl Identity {
+    fn from_http(identity: HttpIdentity, operation: IdentityOperation) -> Result<Self, Error> {
+        let auth_type = match identity.auth_type().as_str() {
+            "None" => AuthType::None,
+            "Sas" => AuthType::Sas,
+            "X509" => AuthType::X509,
+            auth_type => {
+                return Err(Error::from(
+                    ErrorKind::InvalidAuthType(auth_type.to_string())
+                        .context(ErrorKind::IdentityOperation(operation)),
+                ));
+            }
+        };
+        Ok(Identity(identity, auth_type))
+    }
+}
+
+impl CoreIdentity for Identity {
+    fn module_id(&self) -> &str {
+        self.0.module_id()
+    }
+
+    fn managed_by(&self) -> &str {
+        self.0.managed_by()
+    }
+
+    fn generation_id(&self) -> &str {
+        self.0.generation_id()
+    }
+
+    fn auth_type(&self) -> AuthType {
+        self.1
+    }
+}
+
+fn twin_from_http(twin: &HttpTwin) -> Twin {
+    let properties = TwinProperties::new()
+        .with_desired(twin.properties().desired().cloned())
+        .with_reported(twin.properties().reported().cloned());
+    Twin::new(*twin.version(), properties)
+}
+
+impl IdentityManager for IdentityClient {
+    type Identity = Identity;
+    type Error = Error;
+    type CreateFuture = Box<Future<Item = Self::Identity, Error = Self::Error> + Send>;
+    type UpdateFuture = Box<Future<Item = Self::Identity, Error = Self::Error> + Send>;
+    type ListFuture = Box<Future<Item = Vec<Self::Identity>, Error = Self::Error> + Send>;
+    type GetFuture = Box<Future<Item = Option<Self::Identity>, Error = Self::Error> + Send>;
+    type DeleteFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type GetTwinFuture = Box<Future<Item = Twin, Error = Self::Error> + Send>;
+    type ReportPropertiesFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+
+    fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture {
+        let operation = IdentityOperation::CreateIdentity(id.module_id().to_string());
+
+        let mut spec = HttpIdentitySpec::new(id.module_id().to_string());
+        if let Some(managed_by) = id.managed_by() {
+            spec.set_managed_by(managed_by.to_string());
+        }
+
+        let identity = self
+            .client
+            .identity_api()
+            .create_identity(API_VERSION, spec)
+            .then(move |result| match result {
+                Ok(identity) => Identity::from_http(identity, operation),
+                Err(err) => Err(Error::from_mgmt_error(
+                    err,
+                    ErrorKind::IdentityOperation(operation),
+                )),
+            });
+        Box::new(identity)
+    }
+
+    fn update(&mut self, id: IdentitySpec) -> Self::UpdateFuture {
+        let operation = IdentityOperation::UpdateIdentity(id.module_id().to_string());
+
+        let mut update = match id.generation_id() {
+            Some(generation_id) => UpdateIdentity::new(generation_id.to_string()),
+            None => {
+                return Box::new(future::err(Error::from(
+                    ErrorKind::MissingRequiredParameter("generationId")
+                        .context(ErrorKind::IdentityOperation(operation)),
+                )));
+            }
+        };
+        if let Some(managed_by) = id.managed_by() {
+            update.set_managed_by(managed_by.to_string());
+        }
+
+        let identity = self
+            .client
+            .identity_api()
+            .update_identity(API_VERSION, id.module_id(), update)
+            .then(move |result| match result {
+                Ok(identity) => Identity::from_http(identity, operation),
+                Err(err) => Err(Error::from_mgmt_error(
+                    err,
+                    ErrorKind::IdentityOperation(operation),
+                )),
+            });
+        Box::new(identity)
+    }
+
+    fn list(&self) -> Self::ListFuture {
+        let identities = self
+            .client
+            .identity_api()
+            .list_identities(API_VERSION)
+            .then(|result| match result {
+                Ok(list) => list
+                    .identities()
+                    .iter()
+                    .cloned()
+                    .map(|identity| {
+                        Identity::from_http(identity, IdentityOperation::ListIdentities)
+                    })
+                    .collect(),
+                Err(err) => Err(Error::from_mgmt_error(
+                    err,
+                    ErrorKind::IdentityOperation(IdentityOperation::ListIdentities),
+                )),
+            });
+        Box::new(identities)
+    }
+
+    /// The management API has no endpoint for a single identity, so this
+    /// looks the identity up in the list of all of them.
+    fn get(&self, id: IdentitySpec) -> Self::GetFuture {
+        let module_id = id.module_id().to_string();
+
+        let identity = self.list().then(move |result| match result {
+            Ok(identities) => Ok(identities
+                .into_iter()
+                .find(|identity| identity.module_id() == module_id)),
+            Err(err) => Err(Error::from(err.context(ErrorKind::IdentityOperation(
+                IdentityOperation::GetIdentity(module_id.clone()),
+            )))),
+        });
+        Box::new(identity)
+    }
+
+    fn delete(&mut self, id: IdentitySpec) -> Self::DeleteFuture {
+        let module_id = id.module_id().to_string();
+
+        let deleted = self
+            .client
+            .identity_api()
+            .delete_identity(API_VERSION, &module_id)
+            .map_err(|err| {
+                Error::from_mgmt_error(
+                    err,
+                    ErrorKind::IdentityOperation(IdentityOperation::DeleteIdentity(module_id)),
+                )
+            });
+        Box::new(deleted)
+    }
+
+    fn get_twin(&self, target: TwinTarget) -> Self::GetTwinFuture {
+        let api = self.client.identity_api();
+        let twin = match target {
+            TwinTarget::Device => api.get_device_twin(API_VERSION),
+            TwinTarget::Module(ref name) => api.get_module_twin(API_VERSION, name),
+        };
+
+        let twin = twin.then(move |result| match result {
+            Ok(twin) => Ok(twin_from_http(&twin)),
+            Err(err) => Err(Error::from_mgmt_error(
+                err,
+                ErrorKind::IdentityOperation(IdentityOperation::GetTwin(target)),
+            )),
+        });
+        Box::new(twin)
+    }
+
+    fn report_properties(
+        &mut self,
+        target: TwinTarget,
+        reported: Value,
+    ) -> Self::ReportPropertiesFuture {
+        let patch = HttpTwinProperties::new().with_reported(reported);
+
+        let api = self.client.identity_api();
+        let twin = match target {
+            TwinTarget::Device => api.update_device_twin(API_VERSION, patch),
+            TwinTarget::Module(ref name) => api.update_module_twin(API_VERSION, name, patch),
+        };
+
+        let reported = twin.then(move |result| match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::from_mgmt_error(
+                err,
+                ErrorKind::IdentityOperation(IdentityOperation::ReportProperties(target)),
+            )),
+        });
+        Box::new(reported)
+    }
+}
@@ -1,5 +1,35 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+mod identity;
 mod module;
 
-pub use self::module::ModuleClient;
+use failure::ResultExt;
+use hyper::Client;
+use management::apis::client::APIClient;
+use management::apis::configuration::Configuration;
+use url::Url;
+
+use edgelet_http::{UrlConnector, UrlExt};
+
+use error::{Error, ErrorKind};
+
+pub use self::identity::IdentityClient;
+pub use self::module::{ModuleClient, ModuleConfig};
+
+fn api_client<F>(url: &Url, kind: F) -> Result<APIClient, Error>
+where
+    F: Fn() -> ErrorKind,
+{
+    let client = Client::builder().build(UrlConnector::new(url).with_context(|_| kind())?);
+
+    let base_path = url.to_base_path().with_context(|_| kind())?;
+    let mut configuration = Configuration::new(client);
+    configuration.base_path = base_path.to_str().ok_or_else(&kind)?.to_string();
+
+    let scheme = url.scheme().to_string();
+    configuration.uri_composer = Box::new(move |base_path, path| {
+        Ok(UrlConnector::build_hyper_uri(&scheme, base_path, path)?)
+    });
+
+    Ok(APIClient::new(configuration))
+}
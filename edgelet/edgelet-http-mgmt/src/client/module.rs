@@ -9,18 +9,23 @@ use failure::{Fail, ResultExt};
 use futures::future::{self, FutureResult};
 use futures::prelude::*;
 use futures::stream;
-use hyper::{Body, Chunk as HyperChunk, Client};
+use hyper::upgrade::Upgraded;
+use hyper::{Body, Chunk as HyperChunk};
 use management::apis::client::APIClient;
-use management::apis::configuration::Configuration;
-use management::models::{Config, ModuleDetails as HttpModuleDetails};
+use management::models::{
+    Config, EnvVar, ExecRequest, LogLevel, ModuleDetails as HttpModuleDetails,
+    ModuleSpec as HttpModuleSpec, Volume,
+};
+use serde::{Serialize, Serializer};
 use serde_json;
 use url::Url;
 
 use edgelet_core::*;
 use edgelet_core::{ModuleOperation, RuntimeOperation, SystemInfo as CoreSystemInfo};
 use edgelet_docker::{self, DockerConfig};
-use edgelet_http::{UrlConnector, UrlExt, API_VERSION};
+use edgelet_http::API_VERSION;
 
+use super::api_client;
 use error::{Error, ErrorKind};
 
 pub struct ModuleClient {
@@ -29,28 +34,49 @@ pub struct ModuleClient {
 
 impl ModuleClient {
     pub fn new(url: &Url) -> Result<Self, Error> {
-        let client = Client::builder()
-            .build(UrlConnector::new(url).context(ErrorKind::InitializeModuleClient)?);
-
-        let base_path = url
-            .to_base_path()
-            .context(ErrorKind::InitializeModuleClient)?;
-        let mut configuration = Configuration::new(client);
-        configuration.base_path = base_path
-            .to_str()
-            .ok_or(ErrorKind::InitializeModuleClient)?
-            .to_string();
-
-        let scheme = url.scheme().to_string();
-        configuration.uri_composer = Box::new(move |base_path, path| {
-            Ok(UrlConnector::build_hyper_uri(&scheme, base_path, path)?)
-        });
-
+        let client = api_client(url, || ErrorKind::InitializeModuleClient)?;
         let module_client = ModuleClient {
-            client: Arc::new(APIClient::new(configuration)),
+            client: Arc::new(client),
         };
         Ok(module_client)
     }
+
+    /// Changes the log level of `target` in the daemon, or of all targets if
+    /// it is `default`. The level reverts by itself after `revert_after`, if
+    /// given.
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_wrap))]
+    pub fn set_log_level(
+        &self,
+        target: &str,
+        level: &str,
+        revert_after: Option<Duration>,
+    ) -> Box<Future<Item = (), Error = Error> + Send> {
+        let target = target.to_string();
+        let mut log_level = LogLevel::new(level.to_string());
+        if let Some(revert_after) = revert_after {
+            log_level.set_revert_after(revert_after.as_secs() as i64);
+        }
+
+        let set = self
+            .client
+            .logging_api()
+            .set_log_level(API_VERSION, &target, log_level)
+            .map_err(|err| Error::from_mgmt_error(err, ErrorKind::LogLevel(target)));
+        Box::new(set)
+    }
+
+    /// Reverts the log level of `target` in the daemon, or of all targets if
+    /// it is `default`, to the configured one.
+    pub fn reset_log_level(&self, target: &str) -> Box<Future<Item = (), Error = Error> + Send> {
+        let target = target.to_string();
+
+        let reset = self
+            .client
+            .logging_api()
+            .reset_log_level(API_VERSION, &target)
+            .map_err(|err| Error::from_mgmt_error(err, ErrorKind::LogLevel(target)));
+        Box::new(reset)
+    }
 }
 
 impl Clone for ModuleClient {
@@ -64,9 +90,25 @@ impl Clone for ModuleClient {
 #[derive(Clone, Debug)]
 pub struct ModuleDetails(HttpModuleDetails, ModuleConfig);
 
+/// Serializes to the `ModuleDetails` returned by the management API.
+impl Serialize for ModuleDetails {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ModuleConfig(String, Config);
 
+impl ModuleConfig {
+    pub fn new(type_: String, config: Config) -> Self {
+        ModuleConfig(type_, config)
+    }
+}
+
 impl fmt::Display for ModuleConfig {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let edgelet_docker::MODULE_TYPE = self.0.as_ref() {
@@ -117,13 +159,21 @@ fn runtime_status(details: &HttpModuleDetails) -> Result<ModuleRuntimeState, Err
         .exit_status()
         .and_then(|e| e.exit_time().parse().ok());
     let start_time = details.status().start_time().and_then(|s| s.parse().ok());
+    let health = details.status().health().and_then(|h| {
+        HealthStatus::from_str(h.status()).ok().map(|status| {
+            ModuleHealth::new(status)
+                .with_failing_streak(*h.failing_streak() as u32)
+                .with_last_output(h.last_output().map(ToOwned::to_owned))
+        })
+    });
 
     let state = ModuleRuntimeState::default()
         .with_status(status)
         .with_status_description(description)
         .with_exit_code(exit_code)
         .with_started_at(start_time)
-        .with_finished_at(exit_time);
+        .with_finished_at(exit_time)
+        .with_health(health);
     Ok(state)
 }
 
@@ -133,10 +183,13 @@ impl ModuleRegistry for ModuleClient {
     type RemoveFuture = FutureResult<(), Self::Error>;
     type Config = ModuleConfig;
 
+    /// The daemon pulls the image of a module when it creates the module, so
+    /// there is nothing to do here.
     fn pull(&self, _config: &Self::Config) -> Self::PullFuture {
         future::ok(())
     }
 
+    /// The daemon owns the images of its modules and removes them itself.
     fn remove(&self, _name: &str) -> Self::RemoveFuture {
         future::ok(())
     }
@@ -149,30 +202,88 @@ impl ModuleRuntime for ModuleClient {
     type ModuleRegistry = Self;
     type Chunk = Chunk;
     type Logs = Logs;
+    type VolumeArchive = VolumeArchive;
+    type ExecIo = Upgraded;
 
     type CreateFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
-    type InitFuture = FutureResult<(), Self::Error>;
+    type InitFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type ListFuture = Box<Future<Item = Vec<Self::Module>, Error = Self::Error> + Send>;
     type ListWithDetailsStream =
         Box<Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error> + Send>;
     type LogsFuture = Box<Future<Item = Self::Logs, Error = Self::Error> + Send>;
     type RemoveFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type RenameFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type RestartFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type StartFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type StopFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
     type SystemInfoFuture = Box<Future<Item = CoreSystemInfo, Error = Self::Error> + Send>;
     type RemoveAllFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type ListVolumesFuture = Box<Future<Item = Vec<ModuleVolume>, Error = Self::Error> + Send>;
+    type GetVolumeFuture = Box<Future<Item = ModuleVolume, Error = Self::Error> + Send>;
+    type BackupVolumeFuture = Box<Future<Item = Self::VolumeArchive, Error = Self::Error> + Send>;
+    type RemoveVolumeFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type ExecFuture = Box<Future<Item = String, Error = Self::Error> + Send>;
+    type ExecStartFuture = Box<Future<Item = Self::ExecIo, Error = Self::Error> + Send>;
+    type ExecInspectFuture = Box<Future<Item = ExecStatus, Error = Self::Error> + Send>;
 
     fn system_info(&self) -> Self::SystemInfoFuture {
-        unimplemented!()
+        let info = self
+            .client
+            .system_information_api()
+            .get_system_info(API_VERSION)
+            .map(|info| {
+                CoreSystemInfo::new(info.os_type().to_string(), info.architecture().to_string())
+            })
+            .map_err(|err| {
+                Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::SystemInfo),
+                )
+            });
+        Box::new(info)
     }
 
+    /// The daemon initializes its own runtime, so this only checks that the
+    /// daemon can be reached.
     fn init(&self) -> Self::InitFuture {
-        future::ok(())
+        let init = self
+            .client
+            .system_information_api()
+            .get_system_info(API_VERSION)
+            .map(|_| ())
+            .map_err(|err| {
+                Error::from_mgmt_error(err, ErrorKind::RuntimeOperation(RuntimeOperation::Init))
+            });
+        Box::new(init)
     }
 
-    fn create(&self, _module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
-        unimplemented!()
+    fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+        let name = module.name().to_string();
+
+        let mut config = module.config().1.clone();
+        if !module.env().is_empty() {
+            let mut env: Vec<_> = module
+                .env()
+                .iter()
+                .map(|(key, value)| EnvVar::new(key.clone(), value.clone()))
+                .collect();
+            env.sort_by(|a, b| a.key().cmp(b.key()));
+            config.set_env(env);
+        }
+        let spec = HttpModuleSpec::new(name.clone(), module.type_().to_string(), config);
+
+        let created = self
+            .client
+            .module_api()
+            .create_module(API_VERSION, spec)
+            .map(|_| ())
+            .map_err(|err| {
+                Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(name)),
+                )
+            });
+        Box::new(created)
     }
 
     fn start(&self, id: &str) -> Self::StartFuture {
@@ -244,8 +355,26 @@ impl ModuleRuntime for ModuleClient {
         Box::new(restart)
     }
 
-    fn remove(&self, _id: &str) -> Self::RemoveFuture {
-        unimplemented!()
+    fn remove(&self, id: &str) -> Self::RemoveFuture {
+        let id = id.to_string();
+
+        let removed = self
+            .client
+            .module_api()
+            .delete_module(API_VERSION, &id)
+            .map_err(|err| {
+                Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::RemoveModule(id)),
+                )
+            });
+        Box::new(removed)
+    }
+
+    /// The daemon renames the containers of a module itself while it updates
+    /// the module, so there is nothing to do here.
+    fn rename(&self, _id: &str, _new_name: &str) -> Self::RenameFuture {
+        Box::new(future::ok(()))
     }
 
     fn list(&self) -> Self::ListFuture {
@@ -321,15 +450,175 @@ impl ModuleRuntime for ModuleClient {
         self
     }
 
-    fn remove_all(&self) -> Self::RemoveAllFuture {
+    fn remove_all(&self, purge_volumes: bool) -> Self::RemoveAllFuture {
         let self_for_remove = self.clone();
         Box::new(self.list().and_then(move |list| {
-            let n = list
-                .into_iter()
-                .map(move |c| <Self as ModuleRuntime>::remove(&self_for_remove, c.name()));
+            let n = list.into_iter().map(move |c| {
+                let removed = <Self as ModuleRuntime>::remove(&self_for_remove, c.name());
+                if purge_volumes {
+                    let self_for_purge = self_for_remove.clone();
+                    let name = c.name().to_string();
+                    Box::new(removed.and_then(move |_| {
+                        self_for_purge.list_volumes(&name).and_then(move |volumes| {
+                            future::join_all(
+                                volumes
+                                    .into_iter()
+                                    .map(move |v| {
+                                        self_for_purge.remove_volume(v.module(), v.name())
+                                    })
+                                    .collect::<Vec<_>>(),
+                            )
+                            .map(|_| ())
+                        })
+                    })) as <Self as ModuleRuntime>::RemoveFuture
+                } else {
+                    removed
+                }
+            });
             future::join_all(n).map(|_| ())
         }))
     }
+
+    fn list_volumes(&self, module: &str) -> Self::ListVolumesFuture {
+        let module = module.to_string();
+
+        let volumes = self
+            .client
+            .module_api()
+            .list_volumes(API_VERSION, &module)
+            .then(|result| match result {
+                Ok(list) => Ok(list
+                    .volumes()
+                    .iter()
+                    .map(|volume| module_volume(&module, volume))
+                    .collect()),
+                Err(err) => Err(Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::ListVolumes(module)),
+                )),
+            });
+        Box::new(volumes)
+    }
+
+    fn get_volume(&self, module: &str, name: &str) -> Self::GetVolumeFuture {
+        let module = module.to_string();
+        let name = name.to_string();
+
+        let volume = self
+            .client
+            .module_api()
+            .get_volume(API_VERSION, &module, &name)
+            .then(|result| match result {
+                Ok(volume) => Ok(module_volume(&module, &volume)),
+                Err(err) => Err(Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::GetVolume(module, name)),
+                )),
+            });
+        Box::new(volume)
+    }
+
+    fn backup_volume(&self, module: &str, name: &str) -> Self::BackupVolumeFuture {
+        let module = module.to_string();
+        let name = name.to_string();
+
+        let archive = self
+            .client
+            .module_api()
+            .backup_volume(API_VERSION, &module, &name)
+            .then(|result| match result {
+                Ok(body) => Ok(VolumeArchive(module, name, body)),
+                Err(err) => Err(Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::BackupVolume(module, name)),
+                )),
+            });
+        Box::new(archive)
+    }
+
+    fn remove_volume(&self, module: &str, name: &str) -> Self::RemoveVolumeFuture {
+        let module = module.to_string();
+        let name = name.to_string();
+
+        let removed = self
+            .client
+            .module_api()
+            .delete_volume(API_VERSION, &module, &name)
+            .map_err(|err| {
+                Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::RemoveVolume(module, name)),
+                )
+            });
+        Box::new(removed)
+    }
+
+    fn exec(&self, id: &str, options: &ExecOptions) -> Self::ExecFuture {
+        let id = id.to_string();
+
+        let mut exec = ExecRequest::new(options.cmd().to_vec())
+            .with_attach_stdin(options.attach_stdin())
+            .with_tty(options.tty());
+        if let Some(user) = options.user() {
+            exec.set_user(user.to_string());
+        }
+
+        let exec_id = self
+            .client
+            .module_api()
+            .exec_module(API_VERSION, &id, exec)
+            .then(|result| match result {
+                Ok(exec) => Ok(exec.id().to_string()),
+                Err(err) => Err(Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::ExecModule(id)),
+                )),
+            });
+        Box::new(exec_id)
+    }
+
+    fn exec_start(&self, exec_id: &str, tty: bool) -> Self::ExecStartFuture {
+        let exec_id = exec_id.to_string();
+
+        let io = self
+            .client
+            .module_api()
+            .start_exec(API_VERSION, &exec_id, tty)
+            .map_err(|err| {
+                Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::StartExec(exec_id)),
+                )
+            });
+        Box::new(io)
+    }
+
+    fn exec_inspect(&self, exec_id: &str) -> Self::ExecInspectFuture {
+        let exec_id = exec_id.to_string();
+
+        let status = self
+            .client
+            .module_api()
+            .inspect_exec(API_VERSION, &exec_id)
+            .map(|status| ExecStatus::new(*status.running(), status.exit_code().cloned()))
+            .map_err(|err| {
+                Error::from_mgmt_error(
+                    err,
+                    ErrorKind::RuntimeOperation(RuntimeOperation::InspectExec(exec_id)),
+                )
+            });
+        Box::new(status)
+    }
+}
+
+fn module_volume(module: &str, volume: &Volume) -> ModuleVolume {
+    ModuleVolume::new(
+        volume.name().to_string(),
+        module.to_string(),
+        volume.driver().to_string(),
+        volume.mount_point().to_string(),
+    )
+    .with_created_at(volume.created_at().map(ToString::to_string))
 }
 
 pub struct Logs(String, Body);
@@ -349,6 +638,23 @@ impl Stream for Logs {
     }
 }
 
+pub struct VolumeArchive(String, String, Body);
+
+impl Stream for VolumeArchive {
+    type Item = Chunk;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.2.poll() {
+            Ok(Async::Ready(chunk)) => Ok(Async::Ready(chunk.map(Chunk))),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(err) => Err(Error::from(err.context(ErrorKind::RuntimeOperation(
+                RuntimeOperation::BackupVolume(self.0.clone(), self.1.clone()),
+            )))),
+        }
+    }
+}
+
 pub struct Chunk(HyperChunk);
 
 impl AsRef<[u8]> for Chunk {
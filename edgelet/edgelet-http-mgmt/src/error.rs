@@ -22,45 +22,102 @@ pub struct Error {
 
 #[derive(Debug, Fail)]
 pub enum ErrorKind {
+    #[fail(display = "Could not add the root certificate to the trust bundle")]
+    AddTrustedRoot,
+
     // Note: This errorkind is always wrapped in another errorkind context
     #[fail(display = "Client error")]
     Client(MgmtError<serde_json::Value>),
 
+    #[fail(display = "Desired properties can only be changed by the solution back end")]
+    DesiredPropertiesReadOnly,
+
     #[fail(display = "{}", _0)]
     IdentityOperation(IdentityOperation),
 
+    #[fail(display = "Could not initialize identity client")]
+    InitializeIdentityClient,
+
     #[fail(display = "Could not initialize module client")]
     InitializeModuleClient,
 
+    #[fail(display = "Invalid authentication type {:?}", _0)]
+    InvalidAuthType(String),
+
     #[fail(display = "Invalid API version {:?}", _0)]
     InvalidApiVersion(String),
 
+    #[fail(display = "Invalid log level {:?}", _0)]
+    InvalidLogLevel(String),
+
     #[fail(display = "A request to Azure IoT Hub failed")]
     IotHub,
 
+    #[fail(display = "Could not change the log level of {}", _0)]
+    LogLevel(String),
+
+    #[fail(display = "The log level of {} has not been changed", _0)]
+    LogLevelNotChanged(String),
+
     #[fail(display = "Request body is malformed")]
     MalformedRequestBody,
 
     #[fail(display = "The request parameter `{}` is malformed", _0)]
     MalformedRequestParameter(&'static str),
 
+    #[fail(display = "Could not list the master encryption key versions")]
+    MasterKeyVersions,
+
     #[fail(display = "The request is missing required parameter `{}`", _0)]
     MissingRequiredParameter(&'static str),
 
     #[fail(display = "{}", _0)]
     ModuleOperation(ModuleOperation),
 
+    #[fail(display = "Module {} stopped running shortly after it was started", _0)]
+    ModuleStopped(String),
+
     #[fail(display = "State not modified")]
     NotModified,
 
+    #[fail(display = "The module name {:?} is reserved", _0)]
+    ReservedModuleName(String),
+
+    #[fail(display = "Could not remove root {} from the trust bundle", _0)]
+    RemoveTrustedRoot(String),
+
+    #[fail(display = "Could not rotate the master encryption key")]
+    RotateMasterKey,
+
     #[fail(display = "{}", _0)]
     RuntimeOperation(RuntimeOperation),
 
     #[fail(display = "Could not start management service")]
     StartService,
 
+    #[fail(display = "Root {} has not been added to the trust bundle", _0)]
+    TrustedRootNotFound(String),
+
+    #[fail(display = "Could not list the roots of the trust bundle")]
+    TrustedRoots,
+
     #[fail(display = "Could not update module")]
     UpdateModule(String),
+
+    #[fail(
+        display = "Could not update module {}, and the previous version could not be restored",
+        _0
+    )]
+    UpdateModuleRollback(String),
+
+    #[fail(
+        display = "Could not update module {}, the previous version was restored",
+        _0
+    )]
+    UpdateModuleRolledBack(String),
+
+    #[fail(display = "The request must ask for the connection to be upgraded")]
+    UpgradeRequired,
 }
 
 impl Fail for Error {
@@ -118,26 +175,38 @@ impl IntoResponse for Error {
         }
 
         // Specialize status code based on the underlying docker runtime error, if any
-        let status_code =
-            if let Some(cause) = Fail::find_root_cause(&self).downcast_ref::<DockerErrorKind>() {
-                match cause {
-                    DockerErrorKind::NotFound(_) => StatusCode::NOT_FOUND,
-                    DockerErrorKind::Conflict => StatusCode::CONFLICT,
-                    DockerErrorKind::NotModified => StatusCode::NOT_MODIFIED,
-                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+        let status_code = if let ErrorKind::UpdateModuleRolledBack(_) = self.kind() {
+            // The update failed, but the previous version of the module is back in place.
+            StatusCode::CONFLICT
+        } else if let Some(cause) = Fail::find_root_cause(&self).downcast_ref::<DockerErrorKind>() {
+            match cause {
+                DockerErrorKind::NotFound(_) => StatusCode::NOT_FOUND,
+                DockerErrorKind::Conflict => StatusCode::CONFLICT,
+                DockerErrorKind::NotModified => StatusCode::NOT_MODIFIED,
+                DockerErrorKind::ImageDigestMismatch(_, _) | DockerErrorKind::ImageSignature(_) => {
+                    StatusCode::FORBIDDEN
                 }
-            } else {
-                match self.kind() {
-                    ErrorKind::InvalidApiVersion(_)
-                    | ErrorKind::MalformedRequestBody
-                    | ErrorKind::MalformedRequestParameter(_)
-                    | ErrorKind::MissingRequiredParameter(_) => StatusCode::BAD_REQUEST,
-                    _ => {
-                        error!("Internal server error: {}", message);
-                        StatusCode::INTERNAL_SERVER_ERROR
-                    }
+                DockerErrorKind::ResourceLimitExceeded(_, _, _) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        } else {
+            match self.kind() {
+                ErrorKind::LogLevelNotChanged(_) | ErrorKind::TrustedRootNotFound(_) => {
+                    StatusCode::NOT_FOUND
+                }
+                ErrorKind::DesiredPropertiesReadOnly
+                | ErrorKind::InvalidApiVersion(_)
+                | ErrorKind::InvalidLogLevel(_)
+                | ErrorKind::MalformedRequestBody
+                | ErrorKind::MalformedRequestParameter(_)
+                | ErrorKind::MissingRequiredParameter(_) => StatusCode::BAD_REQUEST,
+                ErrorKind::UpgradeRequired => StatusCode::UPGRADE_REQUIRED,
+                _ => {
+                    error!("Internal server error: {}", message);
+                    StatusCode::INTERNAL_SERVER_ERROR
                 }
-            };
+            }
+        };
 
         // Per the RFC, status code NotModified should not have a body
         let body = if status_code == StatusCode::NOT_MODIFIED {
@@ -18,6 +18,7 @@ extern crate edgelet_http;
 extern crate edgelet_iothub;
 #[cfg(test)]
 extern crate edgelet_test_utils;
+extern crate edgelet_utils;
 extern crate failure;
 extern crate futures;
 extern crate hyper;
@@ -32,6 +33,9 @@ extern crate serde;
 extern crate serde_json;
 #[cfg(not(test))]
 extern crate serde_json;
+#[cfg(test)]
+extern crate tempdir;
+extern crate tokio;
 extern crate url;
 
 use hyper::{Body, Response};
@@ -40,7 +44,7 @@ mod client;
 mod error;
 mod server;
 
-pub use client::ModuleClient;
+pub use client::{IdentityClient, ModuleClient, ModuleConfig};
 pub use error::{Error, ErrorKind};
 pub use server::ListModules;
 pub use server::ManagementService;
@@ -0,0 +1,292 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::time::Duration;
+
+use failure::{Fail, ResultExt};
+use futures::{future, Future, Stream};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{
+    ErrorKind as CoreErrorKind, MasterKeyVersion as CoreMasterKeyVersion, RotateMasterEncryptionKey,
+};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::{MasterKeyVersion, MasterKeyVersionList, RotateMasterKeyRequest};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+/// How long ciphertexts of the retired version can be decrypted when the
+/// caller doesn't say.
+const DEFAULT_GRACE_PERIOD_SECS: u64 = 30 * 24 * 60 * 60;
+
+pub struct ListMasterKeyVersions<K> {
+    key: K,
+}
+
+impl<K> ListMasterKeyVersions<K> {
+    pub fn new(key: K) -> Self {
+        ListMasterKeyVersions { key }
+    }
+}
+
+impl<K> Handler<Parameters> for ListMasterKeyVersions<K>
+where
+    K: 'static + RotateMasterEncryptionKey + Send,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = self
+            .key
+            .key_versions()
+            .context(ErrorKind::MasterKeyVersions)
+            .map_err(Error::from)
+            .and_then(|versions| {
+                let body =
+                    MasterKeyVersionList::new(versions.iter().map(master_key_version).collect());
+                let b = serde_json::to_string(&body).context(ErrorKind::MasterKeyVersions)?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .context(ErrorKind::MasterKeyVersions)?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(future::result(response))
+    }
+}
+
+pub struct RotateMasterKey<K> {
+    key: K,
+}
+
+impl<K> RotateMasterKey<K> {
+    pub fn new(key: K) -> Self {
+        RotateMasterKey { key }
+    }
+}
+
+impl<K> Handler<Parameters> for RotateMasterKey<K>
+where
+    K: 'static + RotateMasterEncryptionKey + Clone + Send,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let key = self.key.clone();
+
+        let response = req
+            .into_body()
+            .concat2()
+            .then(move |b| -> Result<_, Error> {
+                let b = b.context(ErrorKind::MalformedRequestBody)?;
+                let request = serde_json::from_slice::<RotateMasterKeyRequest>(&b)
+                    .context(ErrorKind::MalformedRequestBody)?;
+                let grace_period = grace_period(&request)?;
+
+                let version = key.rotate_key(grace_period).map_err(|err| {
+                    let kind = match *err.kind() {
+                        CoreErrorKind::InvalidGracePeriod(_) => {
+                            ErrorKind::MalformedRequestParameter("gracePeriod")
+                        }
+                        _ => ErrorKind::RotateMasterKey,
+                    };
+                    Error::from(err.context(kind))
+                })?;
+                info!(
+                    "Rotated the master encryption key to version {}",
+                    version.version()
+                );
+
+                let b = serde_json::to_string(&master_key_version(&version))
+                    .context(ErrorKind::RotateMasterKey)?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .context(ErrorKind::RotateMasterKey)?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(cast_sign_loss))]
+fn grace_period(request: &RotateMasterKeyRequest) -> Result<Duration, Error> {
+    match request.grace_period() {
+        Some(secs) if secs < 0 => Err(Error::from(ErrorKind::MalformedRequestParameter(
+            "gracePeriod",
+        ))),
+        Some(secs) => Ok(Duration::from_secs(secs as u64)),
+        None => Ok(Duration::from_secs(DEFAULT_GRACE_PERIOD_SECS)),
+    }
+}
+
+fn master_key_version(version: &CoreMasterKeyVersion) -> MasterKeyVersion {
+    let mut model =
+        MasterKeyVersion::new(i64::from(version.version()), version.created().to_rfc3339());
+    if let Some(retired) = version.retired() {
+        model.set_retired_at(retired.to_rfc3339());
+    }
+    if let Some(expires) = version.expires() {
+        model.set_expires_at(expires.to_rfc3339());
+    }
+    model
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{TimeZone, Utc};
+    use edgelet_core::Error as CoreError;
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct TestKey {
+        grace_periods: Arc<Mutex<Vec<Duration>>>,
+    }
+
+    impl RotateMasterEncryptionKey for TestKey {
+        fn key_versions(&self) -> Result<Vec<CoreMasterKeyVersion>, CoreError> {
+            let mut old = CoreMasterKeyVersion::new(0, Utc.ymd(2019, 1, 1).and_hms(0, 0, 0));
+            old.retire(
+                Utc.ymd(2019, 2, 1).and_hms(0, 0, 0),
+                Utc.ymd(2019, 3, 1).and_hms(0, 0, 0),
+            );
+            let current = CoreMasterKeyVersion::new(1, Utc.ymd(2019, 2, 1).and_hms(0, 0, 0));
+            Ok(vec![old, current])
+        }
+
+        fn rotate_key(&self, grace_period: Duration) -> Result<CoreMasterKeyVersion, CoreError> {
+            self.grace_periods.lock().unwrap().push(grace_period);
+            Ok(CoreMasterKeyVersion::new(
+                2,
+                Utc.ymd(2019, 3, 1).and_hms(0, 0, 0),
+            ))
+        }
+    }
+
+    fn rotate_request(request: &RotateMasterKeyRequest) -> Request<Body> {
+        Request::post("http://localhost/encryption/keys/rotate")
+            .body(serde_json::to_string(request).unwrap().into())
+            .unwrap()
+    }
+
+    #[test]
+    fn list_versions() {
+        let handler = ListMasterKeyVersions::new(TestKey::default());
+
+        let response = handler
+            .handle(Request::default(), Parameters::default())
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|body| {
+                let list: MasterKeyVersionList = serde_json::from_slice(&body).unwrap();
+                let versions = list.versions();
+                assert_eq!(2, versions.len());
+                assert_eq!(0, versions[0].version());
+                assert_eq!(Some("2019-03-01T00:00:00+00:00"), versions[0].expires_at());
+                assert_eq!(1, versions[1].version());
+                assert_eq!(None, versions[1].retired_at());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn rotate_with_grace_period() {
+        let key = TestKey::default();
+        let handler = RotateMasterKey::new(key.clone());
+        let request = rotate_request(&RotateMasterKeyRequest::new().with_grace_period(3600));
+
+        let response = handler
+            .handle(request, Parameters::default())
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            vec![Duration::from_secs(3600)],
+            *key.grace_periods.lock().unwrap()
+        );
+        response
+            .into_body()
+            .concat2()
+            .and_then(|body| {
+                let version: MasterKeyVersion = serde_json::from_slice(&body).unwrap();
+                assert_eq!(2, version.version());
+                assert_eq!("2019-03-01T00:00:00+00:00", version.created_at());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn rotate_uses_default_grace_period() {
+        let key = TestKey::default();
+        let handler = RotateMasterKey::new(key.clone());
+        let request = rotate_request(&RotateMasterKeyRequest::new());
+
+        let response = handler
+            .handle(request, Parameters::default())
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            vec![Duration::from_secs(DEFAULT_GRACE_PERIOD_SECS)],
+            *key.grace_periods.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn rotate_with_negative_grace_period_fails() {
+        let key = TestKey::default();
+        let handler = RotateMasterKey::new(key.clone());
+        let request = rotate_request(&RotateMasterKeyRequest::new().with_grace_period(-1));
+
+        let response = handler
+            .handle(request, Parameters::default())
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert!(key.grace_periods.lock().unwrap().is_empty());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|body| {
+                let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+                assert_eq!(
+                    "The request parameter `gracePeriod` is malformed",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+mod keys;
+
+pub use self::keys::{ListMasterKeyVersions, RotateMasterKey};
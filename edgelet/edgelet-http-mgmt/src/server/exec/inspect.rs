@@ -0,0 +1,148 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::ResultExt;
+use futures::{future, Future, IntoFuture};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{ModuleRuntime, RuntimeOperation};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::ExecStatus;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct InspectExec<M> {
+    runtime: M,
+}
+
+impl<M> InspectExec<M> {
+    pub fn new(runtime: M) -> Self {
+        InspectExec { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for InspectExec<M>
+where
+    M: 'static + ModuleRuntime + Clone + Send,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let runtime = self.runtime.clone();
+
+        let response = params
+            .name("id")
+            .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("id")))
+            .map(|id| {
+                let id = id.to_string();
+                runtime
+                    .exec_inspect(&id)
+                    .then(|status| -> Result<_, Error> {
+                        let status = status.with_context(|_| {
+                            ErrorKind::RuntimeOperation(RuntimeOperation::InspectExec(id.clone()))
+                        })?;
+                        let mut body = ExecStatus::new(status.running());
+                        if let Some(exit_code) = status.exit_code() {
+                            body.set_exit_code(exit_code);
+                        }
+                        let b = serde_json::to_string(&body).with_context(|_| {
+                            ErrorKind::RuntimeOperation(RuntimeOperation::InspectExec(id.clone()))
+                        })?;
+                        let response = Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/json")
+                            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                            .body(b.into())
+                            .context(ErrorKind::RuntimeOperation(RuntimeOperation::InspectExec(
+                                id,
+                            )))?;
+                        Ok(response)
+                    })
+            })
+            .into_future()
+            .flatten()
+            .or_else(|e| future::ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::ModuleRuntimeState;
+    use edgelet_test_utils::module::*;
+    use futures::Stream;
+    use management::models::ErrorResponse;
+    use server::module::tests::Error;
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        Parameters::with_captures(vec![(Some("id".to_string()), "exec1".to_string())])
+    }
+
+    fn request() -> Request<Body> {
+        Request::get("http://localhost/exec/exec1")
+            .body(Body::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn success() {
+        // arrange
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(
+            "test-module".to_string(),
+            config,
+            Ok(ModuleRuntimeState::default()),
+        );
+        let handler = InspectExec::new(TestRuntime::new(Ok(module)));
+
+        // act
+        let response = handler.handle(request(), parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let status: ExecStatus = serde_json::from_slice(&b).unwrap();
+                assert!(!*status.running());
+                assert_eq!(Some(&0), status.exit_code());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn runtime_error() {
+        // arrange
+        let handler = InspectExec::new(TestRuntime::new(Err(Error::General)));
+
+        // act
+        let response = handler.handle(request(), parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not inspect command exec1\n\tcaused by: General error",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+mod inspect;
+mod start;
+
+pub use self::inspect::InspectExec;
+pub use self::start::StartExec;
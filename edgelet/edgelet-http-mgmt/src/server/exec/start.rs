@@ -0,0 +1,231 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::{Fail, ResultExt};
+use futures::{future, Future, IntoFuture};
+use hyper::header::{CONNECTION, UPGRADE};
+use hyper::{Body, Request, Response, StatusCode};
+use log::Level;
+use tokio;
+use tokio::io::{copy, shutdown, AsyncRead, AsyncWrite};
+use url::form_urlencoded;
+
+use edgelet_core::{ModuleRuntime, RuntimeOperation};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use edgelet_utils::log_failure;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct StartExec<M> {
+    runtime: M,
+}
+
+impl<M> StartExec<M> {
+    pub fn new(runtime: M) -> Self {
+        StartExec { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for StartExec<M>
+where
+    M: 'static + ModuleRuntime + Clone + Send,
+    M::ExecIo: 'static,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let runtime = self.runtime.clone();
+        let (parts, body) = req.into_parts();
+
+        let response = params
+            .name("id")
+            .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("id")))
+            .and_then(|id| {
+                if !parts.headers.contains_key(UPGRADE) {
+                    return Err(Error::from(ErrorKind::UpgradeRequired));
+                }
+                let tty = parts.uri.query().map_or_else(|| Ok(false), parse_tty)?;
+                Ok((id.to_string(), tty))
+            })
+            .map(move |(id, tty)| {
+                runtime.exec_start(&id, tty).then(|io| -> Result<_, Error> {
+                    let io = io.with_context(|_| {
+                        ErrorKind::RuntimeOperation(RuntimeOperation::StartExec(id.clone()))
+                    })?;
+                    let response = Response::builder()
+                        .status(StatusCode::SWITCHING_PROTOCOLS)
+                        .header(CONNECTION, "Upgrade")
+                        .header(UPGRADE, "tcp")
+                        .body(Body::empty())
+                        .with_context(|_| {
+                            ErrorKind::RuntimeOperation(RuntimeOperation::StartExec(id.clone()))
+                        })?;
+
+                    // The connection is only handed over once the response
+                    // has been sent, so the command is attached to it in the
+                    // background.
+                    tokio::spawn(attach(body, io, id));
+
+                    Ok(response)
+                })
+            })
+            .into_future()
+            .flatten()
+            .or_else(|e| future::ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+fn parse_tty(query: &str) -> Result<bool, Error> {
+    let tty = form_urlencoded::parse(query.as_bytes())
+        .find(|&(ref key, _)| key == "tty")
+        .map_or_else(|| Ok(false), |(_, val)| val.parse::<bool>())
+        .context(ErrorKind::MalformedRequestParameter("tty"))?;
+    Ok(tty)
+}
+
+/// Copies the upgraded connection to the stdin of the command and the output
+/// of the command back to the connection until the command exits.
+fn attach<T>(body: Body, io: T, id: String) -> impl Future<Item = (), Error = ()> + Send
+where
+    T: 'static + AsyncRead + AsyncWrite + Send,
+{
+    let output_id = id.clone();
+
+    body.on_upgrade()
+        .map_err(|err| {
+            Error::from(err.context(ErrorKind::RuntimeOperation(RuntimeOperation::StartExec(id))))
+        })
+        .and_then(move |upgraded| {
+            let (client_read, client_write) = upgraded.split();
+            let (exec_read, exec_write) = io.split();
+
+            // Closing the input doesn't end the command, so it is copied on
+            // its own and the connection lives until the output ends.
+            tokio::spawn(
+                copy(client_read, exec_write)
+                    .and_then(|(_, _, exec_write)| shutdown(exec_write))
+                    .then(|_| Ok(())),
+            );
+
+            copy(exec_read, client_write)
+                .and_then(|(_, _, client_write)| shutdown(client_write))
+                .map(|_| ())
+                .map_err(|err| {
+                    Error::from(err.context(ErrorKind::RuntimeOperation(
+                        RuntimeOperation::StartExec(output_id),
+                    )))
+                })
+        })
+        .map_err(|err| log_failure(Level::Error, &err))
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::ModuleRuntimeState;
+    use edgelet_test_utils::module::*;
+    use futures::Stream;
+    use management::models::ErrorResponse;
+    use serde_json;
+    use server::module::tests::Error;
+    use tokio::runtime::current_thread::Runtime;
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        Parameters::with_captures(vec![(Some("id".to_string()), "exec1".to_string())])
+    }
+
+    fn runtime() -> TestRuntime<Error> {
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(
+            "test-module".to_string(),
+            config,
+            Ok(ModuleRuntimeState::default()),
+        );
+        TestRuntime::new(Ok(module))
+    }
+
+    fn upgrade_request(uri: &str) -> Request<Body> {
+        Request::post(uri)
+            .header(CONNECTION, "Upgrade")
+            .header(UPGRADE, "tcp")
+            .body(Body::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn success() {
+        // arrange
+        let handler = StartExec::new(runtime());
+        let request = upgrade_request("http://localhost/exec/exec1/start?tty=true");
+
+        // act
+        let response = Runtime::new()
+            .unwrap()
+            .block_on(handler.handle(request, parameters()))
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::SWITCHING_PROTOCOLS, response.status());
+        assert_eq!("tcp", response.headers().get(UPGRADE).unwrap());
+    }
+
+    #[test]
+    fn missing_upgrade_is_upgrade_required() {
+        // arrange
+        let handler = StartExec::new(runtime());
+        let request = Request::post("http://localhost/exec/exec1/start")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::UPGRADE_REQUIRED, response.status());
+    }
+
+    #[test]
+    fn bad_tty_is_bad_request() {
+        // arrange
+        let handler = StartExec::new(runtime());
+        let request = upgrade_request("http://localhost/exec/exec1/start?tty=yes");
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn runtime_error() {
+        // arrange
+        let handler = StartExec::new(TestRuntime::new(Err(Error::General)));
+        let request = upgrade_request("http://localhost/exec/exec1/start");
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not start command exec1\n\tcaused by: General error",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
@@ -0,0 +1,348 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::sync::{Arc, Mutex};
+
+use failure::{Fail, ResultExt};
+use futures::{Future, Stream};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{IdentityManager, IdentityOperation, Twin as CoreTwin, TwinTarget};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::{Twin, TwinProperties};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct GetTwin<I> {
+    id_manager: I,
+}
+
+impl<I> GetTwin<I> {
+    pub fn new(id_manager: I) -> Self {
+        GetTwin { id_manager }
+    }
+}
+
+impl<I> Handler<Parameters> for GetTwin<I>
+where
+    I: 'static + IdentityManager + Send,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let target = twin_target(&params);
+
+        let response = self
+            .id_manager
+            .get_twin(target.clone())
+            .then(move |twin| -> Result<_, Error> {
+                let operation = IdentityOperation::GetTwin(target);
+                let twin = twin.context(ErrorKind::IdentityOperation(operation.clone()))?;
+                write_response(&twin, operation)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+pub struct ReportProperties<I> {
+    id_manager: Arc<Mutex<I>>,
+}
+
+impl<I> ReportProperties<I> {
+    pub fn new(id_manager: I) -> Self {
+        ReportProperties {
+            id_manager: Arc::new(Mutex::new(id_manager)),
+        }
+    }
+}
+
+impl<I> Handler<Parameters> for ReportProperties<I>
+where
+    I: 'static + IdentityManager + Send,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let id_manager = self.id_manager.clone();
+        let target = twin_target(&params);
+
+        let response = req
+            .into_body()
+            .concat2()
+            .then(|b| -> Result<_, Error> {
+                let b = b.context(ErrorKind::MalformedRequestBody)?;
+                let properties = serde_json::from_slice::<TwinProperties>(&b)
+                    .context(ErrorKind::MalformedRequestBody)?;
+
+                // Desired properties belong to the solution back end
+                if properties.desired().is_some() {
+                    return Err(Error::from(ErrorKind::DesiredPropertiesReadOnly));
+                }
+                properties
+                    .reported()
+                    .cloned()
+                    .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("reported")))
+            })
+            .and_then(move |reported| {
+                let operation = IdentityOperation::ReportProperties(target.clone());
+                let report = id_manager
+                    .lock()
+                    .unwrap()
+                    .report_properties(target.clone(), reported);
+                report
+                    .and_then(move |()| {
+                        let id_manager = id_manager.lock().unwrap();
+                        id_manager.get_twin(target)
+                    })
+                    .then(|twin| -> Result<_, Error> {
+                        let twin = twin.map_err(|err| {
+                            Error::from(
+                                err.context(ErrorKind::IdentityOperation(operation.clone())),
+                            )
+                        })?;
+                        write_response(&twin, operation)
+                    })
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+/// Routes with a `name` address the twin of that module identity, the others
+/// the device twin.
+fn twin_target(params: &Parameters) -> TwinTarget {
+    params.name("name").map_or(TwinTarget::Device, |name| {
+        TwinTarget::Module(name.to_string())
+    })
+}
+
+fn write_response(twin: &CoreTwin, operation: IdentityOperation) -> Result<Response<Body>, Error> {
+    let mut properties = TwinProperties::new();
+    if let Some(desired) = twin.properties().desired() {
+        properties.set_desired(desired.clone());
+    }
+    if let Some(reported) = twin.properties().reported() {
+        properties.set_reported(reported.clone());
+    }
+    let body = Twin::new(twin.version(), properties);
+
+    let b =
+        serde_json::to_string(&body).context(ErrorKind::IdentityOperation(operation.clone()))?;
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CONTENT_LENGTH, b.len().to_string().as_str())
+        .body(b.into())
+        .context(ErrorKind::IdentityOperation(operation))?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::TwinProperties as CoreTwinProperties;
+    use edgelet_test_utils::identity::TestIdentityManager;
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    fn manager() -> TestIdentityManager {
+        TestIdentityManager::new(vec![])
+            .with_twin(
+                TwinTarget::Device,
+                CoreTwin::new(
+                    1,
+                    CoreTwinProperties::new()
+                        .with_desired(Some(json!({ "logLevel": "info" })))
+                        .with_reported(Some(json!({ "version": "1.0.5" }))),
+                ),
+            )
+            .with_twin(
+                TwinTarget::Module("m1".to_string()),
+                CoreTwin::new(
+                    7,
+                    CoreTwinProperties::new().with_desired(Some(json!({ "interval": 5 }))),
+                ),
+            )
+    }
+
+    fn read_twin(response: Response<Body>) -> Twin {
+        response
+            .into_body()
+            .concat2()
+            .map(|body| serde_json::from_slice(&body).unwrap())
+            .wait()
+            .unwrap()
+    }
+
+    #[test]
+    fn get_device_twin() {
+        let handler = GetTwin::new(manager());
+        let request = Request::get("http://localhost/twin")
+            .body(Body::default())
+            .unwrap();
+
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        let twin = read_twin(response);
+        assert_eq!(1, *twin.version());
+        assert_eq!(
+            Some(&json!({ "logLevel": "info" })),
+            twin.properties().desired()
+        );
+        assert_eq!(
+            Some(&json!({ "version": "1.0.5" })),
+            twin.properties().reported()
+        );
+    }
+
+    #[test]
+    fn get_module_twin() {
+        let handler = GetTwin::new(manager());
+        let request = Request::get("http://localhost/identities/m1/twin")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "m1".to_string())]);
+
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        let twin = read_twin(response);
+        assert_eq!(7, *twin.version());
+        assert_eq!(Some(&json!({ "interval": 5 })), twin.properties().desired());
+        assert_eq!(None, twin.properties().reported());
+    }
+
+    #[test]
+    fn get_unknown_module_twin_fails() {
+        let handler = GetTwin::new(manager());
+        let request = Request::get("http://localhost/identities/m2/twin")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "m2".to_string())]);
+
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        response
+            .into_body()
+            .concat2()
+            .and_then(|body| {
+                let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+                assert_eq!(
+                    "Could not get twin of module m2\n\tcaused by: Module not found",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn report_device_twin_properties() {
+        let handler = ReportProperties::new(manager());
+        let patch = TwinProperties::new().with_reported(json!({ "version": "1.0.6" }));
+        let request = Request::patch("http://localhost/twin")
+            .body(serde_json::to_string(&patch).unwrap().into())
+            .unwrap();
+
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        let twin = read_twin(response);
+        assert_eq!(2, *twin.version());
+        assert_eq!(
+            Some(&json!({ "logLevel": "info" })),
+            twin.properties().desired()
+        );
+        assert_eq!(
+            Some(&json!({ "version": "1.0.6" })),
+            twin.properties().reported()
+        );
+    }
+
+    #[test]
+    fn report_module_twin_properties() {
+        let handler = ReportProperties::new(manager());
+        let patch = TwinProperties::new().with_reported(json!({ "status": "running" }));
+        let request = Request::patch("http://localhost/identities/m1/twin")
+            .body(serde_json::to_string(&patch).unwrap().into())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "m1".to_string())]);
+
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        let twin = read_twin(response);
+        assert_eq!(8, *twin.version());
+        assert_eq!(
+            Some(&json!({ "status": "running" })),
+            twin.properties().reported()
+        );
+    }
+
+    #[test]
+    fn desired_properties_cannot_be_patched() {
+        let handler = ReportProperties::new(manager());
+        let patch = TwinProperties::new()
+            .with_desired(json!({ "logLevel": "debug" }))
+            .with_reported(json!({ "version": "1.0.6" }));
+        let request = Request::patch("http://localhost/twin")
+            .body(serde_json::to_string(&patch).unwrap().into())
+            .unwrap();
+
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|body| {
+                let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+                assert_eq!(
+                    "Desired properties can only be changed by the solution back end",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn report_without_reported_properties_fails() {
+        let handler = ReportProperties::new(manager());
+        let request = Request::patch("http://localhost/twin")
+            .body("{}".into())
+            .unwrap();
+
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn report_properties_bad_body() {
+        let handler = ReportProperties::new(manager());
+        let request = Request::patch("http://localhost/twin")
+            .body("invalid".into())
+            .unwrap();
+
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
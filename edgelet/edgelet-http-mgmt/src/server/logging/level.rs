@@ -0,0 +1,261 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::time::Duration;
+
+use failure::ResultExt;
+use futures::{future, Future, Stream};
+use hyper::{Body, Request, Response, StatusCode};
+use log::LevelFilter;
+use serde_json;
+
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use edgelet_utils::{LogLevels, MAX_REVERT_AFTER_SECS};
+use management::models::LogLevel;
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+/// The target that stands for all targets.
+const DEFAULT_TARGET: &str = "default";
+
+pub struct SetLogLevel {
+    levels: LogLevels,
+}
+
+impl SetLogLevel {
+    pub fn new(levels: LogLevels) -> Self {
+        SetLogLevel { levels }
+    }
+}
+
+impl Handler<Parameters> for SetLogLevel {
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let levels = self.levels.clone();
+        let target = match params.name("target") {
+            Some(target) => target.to_string(),
+            None => {
+                return Box::new(future::ok(
+                    Error::from(ErrorKind::MissingRequiredParameter("target")).into_response(),
+                ));
+            }
+        };
+
+        let response = req
+            .into_body()
+            .concat2()
+            .then(move |b| -> Result<_, Error> {
+                let b = b.context(ErrorKind::MalformedRequestBody)?;
+                let level = serde_json::from_slice::<LogLevel>(&b)
+                    .context(ErrorKind::MalformedRequestBody)?;
+                let filter = level
+                    .level()
+                    .parse::<LevelFilter>()
+                    .map_err(|_| ErrorKind::InvalidLogLevel(level.level().to_string()))?;
+                let revert_after = revert_after(&level)?;
+
+                levels.set(log_target(&target), filter, revert_after);
+                match revert_after {
+                    Some(revert_after) => info!(
+                        "Log level of {} set to {} for {}s",
+                        target,
+                        filter,
+                        revert_after.as_secs()
+                    ),
+                    None => info!("Log level of {} set to {}", target, filter),
+                }
+
+                Ok(Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::default())
+                    .context(ErrorKind::LogLevel(target))?)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+pub struct ResetLogLevel {
+    levels: LogLevels,
+}
+
+impl ResetLogLevel {
+    pub fn new(levels: LogLevels) -> Self {
+        ResetLogLevel { levels }
+    }
+}
+
+impl Handler<Parameters> for ResetLogLevel {
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = params
+            .name("target")
+            .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("target")))
+            .and_then(|target| {
+                if !self.levels.reset(log_target(target)) {
+                    return Err(Error::from(ErrorKind::LogLevelNotChanged(
+                        target.to_string(),
+                    )));
+                }
+                info!("Log level of {} reverted to the configured level", target);
+
+                Ok(Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::default())
+                    .context(ErrorKind::LogLevel(target.to_string()))?)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(future::result(response))
+    }
+}
+
+fn log_target(target: &str) -> Option<&str> {
+    if target == DEFAULT_TARGET {
+        None
+    } else {
+        Some(target)
+    }
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(cast_sign_loss))]
+fn revert_after(level: &LogLevel) -> Result<Option<Duration>, Error> {
+    match level.revert_after() {
+        Some(secs) if secs < 0 || secs as u64 > MAX_REVERT_AFTER_SECS => Err(Error::from(
+            ErrorKind::MalformedRequestParameter("revertAfter"),
+        )),
+        Some(secs) => Ok(Some(Duration::from_secs(secs as u64))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    fn parameters(target: &str) -> Parameters {
+        Parameters::with_captures(vec![(Some("target".to_string()), target.to_string())])
+    }
+
+    fn set_request(level: &LogLevel) -> Request<Body> {
+        Request::put("http://localhost/logging/levels/edgelet_http")
+            .body(serde_json::to_string(level).unwrap().into())
+            .unwrap()
+    }
+
+    #[test]
+    fn set_level_of_target() {
+        let levels = LogLevels::new();
+        let handler = SetLogLevel::new(levels.clone());
+        let request = set_request(&LogLevel::new("debug".to_string()));
+
+        let response = handler
+            .handle(request, parameters("edgelet_http"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert_eq!(
+            Some(LevelFilter::Debug),
+            levels.level("edgelet_http::client")
+        );
+        assert_eq!(None, levels.level("iotedged"));
+    }
+
+    #[test]
+    fn set_level_of_all_targets() {
+        let levels = LogLevels::new();
+        let handler = SetLogLevel::new(levels.clone());
+        let request = set_request(&LogLevel::new("trace".to_string()).with_revert_after(600));
+
+        let response = handler
+            .handle(request, parameters("default"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert_eq!(Some(LevelFilter::Trace), levels.level("iotedged"));
+    }
+
+    #[test]
+    fn set_invalid_level_fails() {
+        let levels = LogLevels::new();
+        let handler = SetLogLevel::new(levels.clone());
+        let request = set_request(&LogLevel::new("loud".to_string()));
+
+        let response = handler
+            .handle(request, parameters("edgelet_http"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|body| {
+                let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+                assert_eq!("Invalid log level \"loud\"", error.message());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+        assert_eq!(None, levels.level("edgelet_http"));
+    }
+
+    #[test]
+    fn set_negative_revert_after_fails() {
+        let handler = SetLogLevel::new(LogLevels::new());
+        let request = set_request(&LogLevel::new("debug".to_string()).with_revert_after(-1));
+
+        let response = handler
+            .handle(request, parameters("edgelet_http"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn set_too_long_revert_after_fails() {
+        let handler = SetLogLevel::new(LogLevels::new());
+        let request =
+            set_request(&LogLevel::new("debug".to_string()).with_revert_after(i64::max_value()));
+
+        let response = handler
+            .handle(request, parameters("edgelet_http"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn reset_level() {
+        let levels = LogLevels::new();
+        levels.set(Some("edgelet_http"), LevelFilter::Debug, None);
+        let handler = ResetLogLevel::new(levels.clone());
+
+        let response = handler
+            .handle(Request::default(), parameters("edgelet_http"))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert_eq!(None, levels.level("edgelet_http"));
+
+        let response = handler
+            .handle(Request::default(), parameters("edgelet_http"))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+}
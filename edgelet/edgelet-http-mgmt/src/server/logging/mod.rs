@@ -0,0 +1,5 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+mod level;
+
+pub use self::level::{ResetLogLevel, SetLogLevel};
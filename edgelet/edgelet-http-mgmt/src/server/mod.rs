@@ -1,12 +1,20 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+mod encryption;
+mod exec;
 mod identity;
+mod logging;
 mod module;
 mod system_info;
+mod trust_bundle;
 
-use edgelet_core::{IdentityManager, Module, ModuleRuntime, Policy};
+use edgelet_core::{
+    IdentityManager, Module, ModuleRuntime, Policy, RotateMasterEncryptionKey, TrustBundleStore,
+};
 use edgelet_http::authorization::Authorization;
 use edgelet_http::route::*;
+use edgelet_http::ApiVersion;
+use edgelet_utils::LogLevels;
 use failure::{Compat, ResultExt};
 use futures::{future, Future};
 use hyper::service::{NewService, Service};
@@ -14,9 +22,13 @@ use hyper::{Body, Request};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use self::encryption::*;
+use self::exec::*;
 use self::identity::*;
+use self::logging::*;
 pub use self::module::*;
 use self::system_info::*;
+use self::trust_bundle::*;
 use error::{Error, ErrorKind};
 
 lazy_static! {
@@ -31,31 +43,65 @@ pub struct ManagementService {
 impl ManagementService {
     // clippy bug: https://github.com/rust-lang-nursery/rust-clippy/issues/3220
     #[cfg_attr(feature = "cargo-clippy", allow(new_ret_no_self))]
-    pub fn new<M, I>(runtime: &M, identity: &I) -> impl Future<Item = Self, Error = Error>
+    pub fn new<M, I, K, T>(
+        runtime: &M,
+        identity: &I,
+        log_levels: &LogLevels,
+        master_key: &K,
+        trust_bundle: &TrustBundleStore<T>,
+        privileged_group: Option<u32>,
+    ) -> impl Future<Item = Self, Error = Error>
     where
         M: 'static + ModuleRuntime + Clone + Send + Sync,
         <M::Module as Module>::Config: DeserializeOwned + Serialize,
         M::Logs: Into<Body>,
+        M::VolumeArchive: Into<Body>,
+        M::ExecIo: 'static,
         I: 'static + IdentityManager + Clone + Send + Sync,
         I::Identity: Serialize,
+        K: 'static + RotateMasterEncryptionKey + Clone + Send + Sync,
+        T: 'static + Clone + Send + Sync,
     {
+        let host = Policy::Host(privileged_group);
         let router = router!(
-            get    "/modules"                         => Authorization::new(ListModules::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            post   "/modules"                         => Authorization::new(CreateModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            get    "/modules/(?P<name>[^/]+)"         => Authorization::new(GetModule, Policy::Anonymous, runtime.clone()),
-            put    "/modules/(?P<name>[^/]+)"         => Authorization::new(UpdateModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            delete "/modules/(?P<name>[^/]+)"         => Authorization::new(DeleteModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/start"   => Authorization::new(StartModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/stop"    => Authorization::new(StopModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/restart" => Authorization::new(RestartModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            get    "/modules/(?P<name>[^/]+)/logs"    => Authorization::new(ModuleLogs::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-
-            get    "/identities"                      => Authorization::new(ListIdentities::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            post   "/identities"                      => Authorization::new(CreateIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            put    "/identities/(?P<name>[^/]+)"      => Authorization::new(UpdateIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-            delete "/identities/(?P<name>[^/]+)"      => Authorization::new(DeleteIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
-
-            get    "/systeminfo"                      => Authorization::new(GetSystemInfo::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            get    ApiVersion::V2018_06_28.. => "/modules"                         => Authorization::new(ListModules::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules"                         => Authorization::new(CreateModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            get    ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)"         => Authorization::new(GetModule, Policy::Anonymous, runtime.clone()),
+            put    ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)"         => Authorization::new(UpdateModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            delete ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)"         => Authorization::new(DeleteModule::new(runtime.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/start"   => Authorization::new(StartModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/stop"    => Authorization::new(StopModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/restart" => Authorization::new(RestartModule::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            get    ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/logs"    => Authorization::new(ModuleLogs::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            post   ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/exec"    => Authorization::new(ExecModule::new(runtime.clone()), host, runtime.clone()),
+            post   ApiVersion::V2019_01_30.. => "/exec/(?P<id>[^/]+)/start"        => Authorization::new(StartExec::new(runtime.clone()), host, runtime.clone()),
+            get    ApiVersion::V2019_01_30.. => "/exec/(?P<id>[^/]+)"              => Authorization::new(InspectExec::new(runtime.clone()), host, runtime.clone()),
+
+            get    ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/volumes"                          => Authorization::new(ListVolumes::new(runtime.clone()), host, runtime.clone()),
+            get    ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/volumes/(?P<volume>[^/]+)"        => Authorization::new(GetVolume::new(runtime.clone()), host, runtime.clone()),
+            delete ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/volumes/(?P<volume>[^/]+)"        => Authorization::new(DeleteVolume::new(runtime.clone()), host, runtime.clone()),
+            get    ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/volumes/(?P<volume>[^/]+)/backup" => Authorization::new(BackupVolume::new(runtime.clone()), host, runtime.clone()),
+
+            get    ApiVersion::V2018_06_28.. => "/identities"                      => Authorization::new(ListIdentities::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/identities"                      => Authorization::new(CreateIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            put    ApiVersion::V2018_06_28.. => "/identities/(?P<name>[^/]+)"      => Authorization::new(UpdateIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            delete ApiVersion::V2018_06_28.. => "/identities/(?P<name>[^/]+)"      => Authorization::new(DeleteIdentity::new(identity.clone()), Policy::Module(&*AGENT_NAME), runtime.clone()),
+            get    ApiVersion::V2019_01_30.. => "/identities/(?P<name>[^/]+)/twin" => Authorization::new(GetTwin::new(identity.clone()), host, runtime.clone()),
+            patch  ApiVersion::V2019_01_30.. => "/identities/(?P<name>[^/]+)/twin" => Authorization::new(ReportProperties::new(identity.clone()), Policy::Caller, runtime.clone()),
+            get    ApiVersion::V2019_01_30.. => "/twin"                            => Authorization::new(GetTwin::new(identity.clone()), host, runtime.clone()),
+            patch  ApiVersion::V2019_01_30.. => "/twin"                            => Authorization::new(ReportProperties::new(identity.clone()), host, runtime.clone()),
+
+            get    ApiVersion::V2018_06_28.. => "/systeminfo"                      => Authorization::new(GetSystemInfo::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+
+            put    ApiVersion::V2019_01_30.. => "/logging/levels/(?P<target>[^/]+)" => Authorization::new(SetLogLevel::new(log_levels.clone()), host, runtime.clone()),
+            delete ApiVersion::V2019_01_30.. => "/logging/levels/(?P<target>[^/]+)" => Authorization::new(ResetLogLevel::new(log_levels.clone()), host, runtime.clone()),
+
+            get    ApiVersion::V2019_01_30.. => "/encryption/keys"                 => Authorization::new(ListMasterKeyVersions::new(master_key.clone()), host, runtime.clone()),
+            post   ApiVersion::V2019_01_30.. => "/encryption/keys/rotate"          => Authorization::new(RotateMasterKey::new(master_key.clone()), host, runtime.clone()),
+
+            get    ApiVersion::V2019_01_30.. => "/trust-bundle/roots"               => Authorization::new(ListTrustedRoots::new(trust_bundle.clone()), host, runtime.clone()),
+            post   ApiVersion::V2019_01_30.. => "/trust-bundle/roots"               => Authorization::new(AddTrustedRoot::new(trust_bundle.clone()), host, runtime.clone()),
+            delete ApiVersion::V2019_01_30.. => "/trust-bundle/roots/(?P<id>[^/]+)" => Authorization::new(RemoveTrustedRoot::new(trust_bundle.clone()), host, runtime.clone()),
         );
 
         router.new_service().then(|inner| {
@@ -0,0 +1,134 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::{Fail, ResultExt};
+use futures::{Future, IntoFuture};
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, Request, Response, StatusCode};
+
+use edgelet_core::{ModuleRuntime, RuntimeOperation};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+
+use super::volume_params;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct BackupVolume<M> {
+    runtime: M,
+}
+
+impl<M> BackupVolume<M> {
+    pub fn new(runtime: M) -> Self {
+        BackupVolume { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for BackupVolume<M>
+where
+    M: 'static + ModuleRuntime + Send,
+    M::VolumeArchive: Into<Body>,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = volume_params(&params)
+            .map(|(name, volume)| {
+                self.runtime
+                    .backup_volume(&name, &volume)
+                    .then(|result| -> Result<_, Error> {
+                        let context = ErrorKind::RuntimeOperation(RuntimeOperation::BackupVolume(
+                            name.clone(),
+                            volume.clone(),
+                        ));
+                        let archive = result.map_err(|err| Error::from(err.context(context)))?;
+                        let response = Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/x-tar")
+                            .body(archive.into())
+                            .context(ErrorKind::RuntimeOperation(
+                                RuntimeOperation::BackupVolume(name, volume),
+                            ))?;
+                        Ok(response)
+                    })
+            })
+            .into_future()
+            .flatten()
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::ModuleRuntimeState;
+    use edgelet_test_utils::module::*;
+    use futures::Stream;
+    use management::models::ErrorResponse;
+    use serde_json;
+    use server::module::tests::Error;
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        Parameters::with_captures(vec![
+            (Some("name".to_string()), "mod1".to_string()),
+            (Some("volume".to_string()), "data".to_string()),
+        ])
+    }
+
+    #[test]
+    fn success() {
+        // arrange
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(
+            "test-module".to_string(),
+            config,
+            Ok(ModuleRuntimeState::default()),
+        );
+        let handler = BackupVolume::new(TestRuntime::new(Ok(module)));
+        let request = Request::get("http://localhost/modules/mod1/volumes/data/backup")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            "application/x-tar",
+            response.headers().get(CONTENT_TYPE).unwrap()
+        );
+    }
+
+    #[test]
+    fn runtime_error() {
+        // arrange
+        let handler = BackupVolume::new(TestRuntime::new(Err(Error::General)));
+        let request = Request::get("http://localhost/modules/mod1/volumes/data/backup")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not back up volume data of module mod1\n\tcaused by: General error",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
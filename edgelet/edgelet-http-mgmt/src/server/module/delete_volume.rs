@@ -0,0 +1,108 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::{Fail, ResultExt};
+use futures::{Future, IntoFuture};
+use hyper::{Body, Request, Response, StatusCode};
+
+use edgelet_core::{ModuleRuntime, RuntimeOperation};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+
+use super::volume_params;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct DeleteVolume<M> {
+    runtime: M,
+}
+
+impl<M> DeleteVolume<M> {
+    pub fn new(runtime: M) -> Self {
+        DeleteVolume { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for DeleteVolume<M>
+where
+    M: 'static + ModuleRuntime + Send,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = volume_params(&params)
+            .map(|(name, volume)| {
+                self.runtime
+                    .remove_volume(&name, &volume)
+                    .then(|result| match result {
+                        Ok(_) => Ok((name, volume)),
+                        Err(err) => Err(Error::from(err.context(ErrorKind::RuntimeOperation(
+                            RuntimeOperation::RemoveVolume(name, volume),
+                        )))),
+                    })
+            })
+            .into_future()
+            .flatten()
+            .and_then(|(name, volume)| {
+                Ok(Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::default())
+                    .context(ErrorKind::RuntimeOperation(RuntimeOperation::RemoveVolume(
+                        name, volume,
+                    )))?)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::ModuleRuntimeState;
+    use edgelet_test_utils::module::*;
+    use server::module::tests::Error;
+
+    use super::*;
+
+    #[test]
+    fn success() {
+        // arrange
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(
+            "test-module".to_string(),
+            config,
+            Ok(ModuleRuntimeState::default()),
+        );
+        let handler = DeleteVolume::new(TestRuntime::new(Ok(module)));
+        let request = Request::delete("http://localhost/modules/mod1/volumes/data")
+            .body(Body::default())
+            .unwrap();
+        let parameters = Parameters::with_captures(vec![
+            (Some("name".to_string()), "mod1".to_string()),
+            (Some("volume".to_string()), "data".to_string()),
+        ]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+    }
+
+    #[test]
+    fn delete_bad_params() {
+        // arrange
+        let handler = DeleteVolume::new(TestRuntime::new(Err(Error::General)));
+        let request = Request::delete("http://localhost/modules/mod1/volumes/data")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::{Fail, ResultExt};
+use futures::{Future, IntoFuture, Stream};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{ExecOptions, ModuleRuntime, RuntimeOperation};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::{ExecRequest, ExecResponse};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ExecModule<M> {
+    runtime: M,
+}
+
+impl<M> ExecModule<M> {
+    pub fn new(runtime: M) -> Self {
+        ExecModule { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for ExecModule<M>
+where
+    M: 'static + ModuleRuntime + Clone + Send,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let runtime = self.runtime.clone();
+
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("name")))
+            .map(|name| {
+                let name = name.to_string();
+                req.into_body()
+                    .concat2()
+                    .then(|b| -> Result<_, Error> {
+                        let b = b.context(ErrorKind::MalformedRequestBody)?;
+                        let exec = serde_json::from_slice::<ExecRequest>(&b)
+                            .context(ErrorKind::MalformedRequestBody)?;
+                        if exec.cmd().is_empty() {
+                            return Err(Error::from(ErrorKind::MalformedRequestBody));
+                        }
+                        Ok(ExecOptions::new(exec.cmd().to_vec())
+                            .with_user(exec.user().map(ToString::to_string))
+                            .with_attach_stdin(exec.attach_stdin().cloned().unwrap_or(false))
+                            .with_tty(exec.tty().cloned().unwrap_or(false)))
+                    })
+                    .and_then(move |options| {
+                        runtime
+                            .exec(&name, &options)
+                            .then(|result| -> Result<_, Error> {
+                                let context = ErrorKind::RuntimeOperation(
+                                    RuntimeOperation::ExecModule(name.clone()),
+                                );
+                                let id = result.map_err(|err| Error::from(err.context(context)))?;
+                                let b = serde_json::to_string(&ExecResponse::new(id)).context(
+                                    ErrorKind::RuntimeOperation(RuntimeOperation::ExecModule(
+                                        name.clone(),
+                                    )),
+                                )?;
+                                let response = Response::builder()
+                                    .status(StatusCode::CREATED)
+                                    .header(CONTENT_TYPE, "application/json")
+                                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                                    .body(b.into())
+                                    .context(ErrorKind::RuntimeOperation(
+                                        RuntimeOperation::ExecModule(name),
+                                    ))?;
+                                Ok(response)
+                            })
+                    })
+            })
+            .into_future()
+            .flatten()
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::ModuleRuntimeState;
+    use edgelet_test_utils::module::*;
+    use management::models::ErrorResponse;
+    use server::module::tests::Error;
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())])
+    }
+
+    fn request(exec: &ExecRequest) -> Request<Body> {
+        Request::post("http://localhost/modules/mod1/exec")
+            .body(serde_json::to_string(exec).unwrap().into())
+            .unwrap()
+    }
+
+    fn runtime() -> TestRuntime<Error> {
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(
+            "test-module".to_string(),
+            config,
+            Ok(ModuleRuntimeState::default()),
+        );
+        TestRuntime::new(Ok(module))
+    }
+
+    #[test]
+    fn success() {
+        // arrange
+        let handler = ExecModule::new(runtime());
+        let request = request(&ExecRequest::new(vec!["ls".to_string()]));
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::CREATED, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let exec: ExecResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!("test-exec", exec.id());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn empty_command_is_bad_request() {
+        // arrange
+        let handler = ExecModule::new(runtime());
+        let request = request(&ExecRequest::new(vec![]));
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn runtime_error() {
+        // arrange
+        let handler = ExecModule::new(TestRuntime::new(Err(Error::General)));
+        let request = request(&ExecRequest::new(vec!["ls".to_string()]));
+
+        // act
+        let response = handler.handle(request, parameters()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not run command in module mod1\n\tcaused by: General error",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::{Fail, ResultExt};
+use futures::{Future, IntoFuture};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{ModuleRuntime, RuntimeOperation};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+
+use super::{core_to_volume, volume_params};
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct GetVolume<M> {
+    runtime: M,
+}
+
+impl<M> GetVolume<M> {
+    pub fn new(runtime: M) -> Self {
+        GetVolume { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for GetVolume<M>
+where
+    M: 'static + ModuleRuntime + Send,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = volume_params(&params)
+            .map(|(name, volume)| {
+                self.runtime
+                    .get_volume(&name, &volume)
+                    .then(|result| -> Result<_, Error> {
+                        let context = ErrorKind::RuntimeOperation(RuntimeOperation::GetVolume(
+                            name.clone(),
+                            volume.clone(),
+                        ));
+                        let details = result.map_err(|err| Error::from(err.context(context)))?;
+                        let b = serde_json::to_string(&core_to_volume(&details)).context(
+                            ErrorKind::RuntimeOperation(RuntimeOperation::GetVolume(
+                                name.clone(),
+                                volume.clone(),
+                            )),
+                        )?;
+                        let response = Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/json")
+                            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                            .body(b.into())
+                            .context(ErrorKind::RuntimeOperation(RuntimeOperation::GetVolume(
+                                name, volume,
+                            )))?;
+                        Ok(response)
+                    })
+            })
+            .into_future()
+            .flatten()
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::{ModuleRuntimeState, ModuleVolume};
+    use edgelet_test_utils::module::*;
+    use futures::Stream;
+    use management::models::Volume;
+    use server::module::tests::Error;
+
+    use super::*;
+
+    #[test]
+    fn success() {
+        // arrange
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(
+            "test-module".to_string(),
+            config,
+            Ok(ModuleRuntimeState::default()),
+        );
+        let runtime = TestRuntime::new(Ok(module)).with_volumes(vec![ModuleVolume::new(
+            "data".to_string(),
+            "mod1".to_string(),
+            "local".to_string(),
+            "/var/lib/docker/volumes/data/_data".to_string(),
+        )]);
+        let handler = GetVolume::new(runtime);
+        let request = Request::get("http://localhost/modules/mod1/volumes/data")
+            .body(Body::default())
+            .unwrap();
+        let parameters = Parameters::with_captures(vec![
+            (Some("name".to_string()), "mod1".to_string()),
+            (Some("volume".to_string()), "data".to_string()),
+        ]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let volume: Volume = serde_json::from_slice(&b).unwrap();
+                assert_eq!("data", volume.name());
+                assert_eq!("local", volume.driver());
+                assert_eq!(None, volume.created_at());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn missing_volume_param() {
+        // arrange
+        let handler = GetVolume::new(TestRuntime::new(Err(Error::General)));
+        let request = Request::get("http://localhost/modules/mod1/volumes/data")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
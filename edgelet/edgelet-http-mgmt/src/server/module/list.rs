@@ -9,9 +9,10 @@ use serde_json;
 
 use edgelet_core::{Module, ModuleRuntime, ModuleRuntimeState, RuntimeOperation};
 use edgelet_http::route::{Handler, Parameters};
-use edgelet_http::Error as HttpError;
+use edgelet_http::{ApiVersion, Error as HttpError};
 use management::models::*;
 
+use super::is_update_version;
 use error::{Error, ErrorKind};
 use IntoResponse;
 
@@ -32,11 +33,17 @@ where
 {
     fn handle(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
         _params: Parameters,
     ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
         debug!("List modules");
 
+        let version = req
+            .extensions()
+            .get::<ApiVersion>()
+            .cloned()
+            .unwrap_or_else(ApiVersion::latest);
+
         let response = self
             .runtime
             .list_with_details()
@@ -45,7 +52,9 @@ where
                 let details: Result<_, Error> = result
                     .context(ErrorKind::RuntimeOperation(RuntimeOperation::ListModules))?
                     .into_iter()
-                    .map(|(module, state)| core_to_details(&module, &state))
+                    // hide the previous and next versions of a module that is being updated
+                    .filter(|&(ref module, _)| !is_update_version(module.name()))
+                    .map(|(module, state)| core_to_details(&module, &state, version))
                     .collect();
                 let body = ModuleList::new(details?);
                 let b = serde_json::to_string(&body)
@@ -64,7 +73,11 @@ where
     }
 }
 
-fn core_to_details<M>(module: &M, state: &ModuleRuntimeState) -> Result<ModuleDetails, Error>
+fn core_to_details<M>(
+    module: &M,
+    state: &ModuleRuntimeState,
+    version: ApiVersion,
+) -> Result<ModuleDetails, Error>
 where
     M: 'static + Module + Send,
     M::Config: Serialize,
@@ -85,6 +98,17 @@ where
             status.set_exit_status(ExitStatus::new(finished_at.to_rfc3339(), code.to_string()));
         }
     }
+    // health was added to the module status in 2019-01-30
+    if version >= ApiVersion::V2019_01_30 {
+        if let Some(health) = state.health() {
+            let mut details =
+                Health::new(health.status().to_string(), health.failing_streak() as i32);
+            if let Some(output) = health.last_output() {
+                details.set_last_output(output.to_string());
+            }
+            status.set_health(details);
+        }
+    }
 
     Ok(ModuleDetails::new(
         "id".to_string(),
@@ -98,7 +122,7 @@ where
 #[cfg(test)]
 mod tests {
     use chrono::prelude::*;
-    use edgelet_core::{ModuleRuntimeState, ModuleStatus};
+    use edgelet_core::{HealthStatus, ModuleHealth, ModuleRuntimeState, ModuleStatus};
     use edgelet_http::route::Parameters;
     use edgelet_test_utils::module::*;
     use futures::Stream;
@@ -116,7 +140,12 @@ mod tests {
             .with_status_description(Some("description".to_string()))
             .with_started_at(Some(Utc.ymd(2018, 4, 13).and_hms_milli(14, 20, 0, 1)))
             .with_finished_at(Some(Utc.ymd(2018, 4, 13).and_hms_milli(15, 20, 0, 1)))
-            .with_image_id(Some("image-id".to_string()));
+            .with_image_id(Some("image-id".to_string()))
+            .with_health(Some(
+                ModuleHealth::new(HealthStatus::Unhealthy)
+                    .with_failing_streak(2)
+                    .with_last_output(Some("probe failed".to_string())),
+            ));
         let config = TestConfig::new("microsoft/test-image".to_string());
         let module: TestModule<Error> =
             TestModule::new("test-module".to_string(), config, Ok(state));
@@ -160,6 +189,45 @@ mod tests {
                     "description",
                     module.status().runtime_status().description().unwrap()
                 );
+
+                let health = module.status().health().unwrap();
+                assert_eq!("unhealthy", health.status());
+                assert_eq!(2, *health.failing_streak());
+                assert_eq!("probe failed", health.last_output().unwrap());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn health_is_not_listed_for_older_api_version() {
+        // arrange
+        let state = ModuleRuntimeState::default()
+            .with_status(ModuleStatus::Running)
+            .with_health(Some(ModuleHealth::new(HealthStatus::Healthy)));
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> =
+            TestModule::new("test-module".to_string(), config, Ok(state));
+        let runtime = TestRuntime::new(Ok(module));
+        let handler = ListModules::new(runtime);
+        let mut request = Request::get("http://localhost/modules")
+            .body(Body::default())
+            .unwrap();
+        request.extensions_mut().insert(ApiVersion::V2018_06_28);
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let list: ModuleList = serde_json::from_slice(&b).unwrap();
+                let module = list.modules().iter().next().unwrap();
+                assert_eq!("running", module.status().runtime_status().status());
+                assert!(module.status().health().is_none());
                 Ok(())
             })
             .wait()
@@ -223,4 +291,32 @@ mod tests {
             .wait()
             .unwrap();
     }
+
+    fn hides(name: &str) {
+        let state = ModuleRuntimeState::default().with_status(ModuleStatus::Stopped);
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(name.to_string(), config, Ok(state));
+        let handler = ListModules::new(TestRuntime::new(Ok(module)));
+        let request = Request::get("http://localhost/modules")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        let b = response.into_body().concat2().wait().unwrap();
+        let list: ModuleList = serde_json::from_slice(&b).unwrap();
+        assert!(list.modules().is_empty());
+    }
+
+    #[test]
+    fn hides_previous_version_of_updated_module() {
+        hides("test-module.previous");
+    }
+
+    #[test]
+    fn hides_next_version_of_updated_module() {
+        hides("test-module.next");
+    }
 }
@@ -0,0 +1,185 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::{Fail, ResultExt};
+use futures::{Future, IntoFuture};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{ModuleRuntime, RuntimeOperation};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::VolumeList;
+
+use super::core_to_volume;
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ListVolumes<M> {
+    runtime: M,
+}
+
+impl<M> ListVolumes<M> {
+    pub fn new(runtime: M) -> Self {
+        ListVolumes { runtime }
+    }
+}
+
+impl<M> Handler<Parameters> for ListVolumes<M>
+where
+    M: 'static + ModuleRuntime + Send,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("name")))
+            .map(|name| {
+                let name = name.to_string();
+
+                self.runtime
+                    .list_volumes(&name)
+                    .then(|result| -> Result<_, Error> {
+                        let volumes = result.map_err(|err| {
+                            Error::from(err.context(ErrorKind::RuntimeOperation(
+                                RuntimeOperation::ListVolumes(name.clone()),
+                            )))
+                        })?;
+                        let body = VolumeList::new(volumes.iter().map(core_to_volume).collect());
+                        let b =
+                            serde_json::to_string(&body).context(ErrorKind::RuntimeOperation(
+                                RuntimeOperation::ListVolumes(name.clone()),
+                            ))?;
+                        let response = Response::builder()
+                            .status(StatusCode::OK)
+                            .header(CONTENT_TYPE, "application/json")
+                            .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                            .body(b.into())
+                            .context(ErrorKind::RuntimeOperation(RuntimeOperation::ListVolumes(
+                                name,
+                            )))?;
+                        Ok(response)
+                    })
+            })
+            .into_future()
+            .flatten()
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edgelet_core::{ModuleRuntimeState, ModuleVolume};
+    use edgelet_test_utils::module::*;
+    use futures::Stream;
+    use management::models::ErrorResponse;
+    use server::module::tests::Error;
+
+    use super::*;
+
+    fn runtime() -> TestRuntime<Error> {
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module: TestModule<Error> = TestModule::new(
+            "test-module".to_string(),
+            config,
+            Ok(ModuleRuntimeState::default()),
+        );
+        TestRuntime::new(Ok(module)).with_volumes(vec![
+            ModuleVolume::new(
+                "data".to_string(),
+                "mod1".to_string(),
+                "local".to_string(),
+                "/var/lib/docker/volumes/data/_data".to_string(),
+            )
+            .with_created_at(Some("2018-10-01T00:00:00Z".to_string())),
+            ModuleVolume::new(
+                "other".to_string(),
+                "mod2".to_string(),
+                "local".to_string(),
+                "/var/lib/docker/volumes/other/_data".to_string(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn success() {
+        // arrange
+        let handler = ListVolumes::new(runtime());
+        let request = Request::get("http://localhost/modules/mod1/volumes")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let list: VolumeList = serde_json::from_slice(&b).unwrap();
+                assert_eq!(1, list.volumes().len());
+                let volume = &list.volumes()[0];
+                assert_eq!("data", volume.name());
+                assert_eq!("local", volume.driver());
+                assert_eq!("/var/lib/docker/volumes/data/_data", volume.mount_point());
+                assert_eq!(Some("2018-10-01T00:00:00Z"), volume.created_at());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn bad_params() {
+        // arrange
+        let handler = ListVolumes::new(runtime());
+        let request = Request::get("http://localhost/modules/mod1/volumes")
+            .body(Body::default())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[test]
+    fn runtime_error() {
+        // arrange
+        let handler = ListVolumes::new(TestRuntime::new(Err(Error::General)));
+        let request = Request::get("http://localhost/modules/mod1/volumes")
+            .body(Body::default())
+            .unwrap();
+        let parameters =
+            Parameters::with_captures(vec![(Some("name".to_string()), "mod1".to_string())]);
+
+        // act
+        let response = handler.handle(request, parameters).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not list volumes of module mod1\n\tcaused by: General error",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+}
@@ -7,31 +7,54 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
 
-use edgelet_core::{Module, ModuleRuntime, ModuleSpec as CoreModuleSpec, ModuleStatus};
+use edgelet_core::{
+    Module, ModuleRuntime, ModuleSpec as CoreModuleSpec, ModuleStatus, ModuleVolume,
+    NEXT_VERSION_SUFFIX,
+};
+use edgelet_http::route::Parameters;
 use management::models::*;
 
 use error::{Error, ErrorKind};
 
+mod backup_volume;
 mod create;
 mod delete;
+mod delete_volume;
+mod exec;
 mod get;
+mod get_volume;
 mod list;
+mod list_volumes;
 mod logs;
 mod restart;
 mod start;
 mod stop;
 mod update;
 
+pub use self::backup_volume::BackupVolume;
 pub use self::create::CreateModule;
 pub use self::delete::DeleteModule;
+pub use self::delete_volume::DeleteVolume;
+pub use self::exec::ExecModule;
 pub use self::get::GetModule;
+pub use self::get_volume::GetVolume;
 pub use self::list::ListModules;
+pub use self::list_volumes::ListVolumes;
 pub use self::logs::ModuleLogs;
 pub use self::restart::RestartModule;
 pub use self::start::StartModule;
 pub use self::stop::StopModule;
 pub use self::update::UpdateModule;
 
+/// The suffix of the name an existing module is kept under while it is being
+/// updated. Neither it nor `NEXT_VERSION_SUFFIX` can end a module name, so a
+/// real module is never taken for a version of another one.
+const PREVIOUS_SUFFIX: &str = ".previous";
+
+fn is_update_version(name: &str) -> bool {
+    name.ends_with(PREVIOUS_SUFFIX) || name.ends_with(NEXT_VERSION_SUFFIX)
+}
+
 fn spec_to_core<M>(
     spec: &ModuleSpec,
     context: ErrorKind,
@@ -41,6 +64,11 @@ where
     <M::Module as Module>::Config: DeserializeOwned + Serialize,
 {
     let name = spec.name().to_string();
+    if is_update_version(&name) {
+        return Err(Error::from(
+            ErrorKind::ReservedModuleName(name).context(context),
+        ));
+    }
     let type_ = spec.type_().to_string();
     let env = spec.config().env().map_or_else(HashMap::new, |vars| {
         vars.into_iter()
@@ -81,6 +109,28 @@ fn spec_to_details(spec: &ModuleSpec, module_status: ModuleStatus) -> ModuleDeta
     ModuleDetails::new(id, name, type_, config, status)
 }
 
+fn volume_params(params: &Parameters) -> Result<(String, String), Error> {
+    let name = params
+        .name("name")
+        .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("name")))?;
+    let volume = params
+        .name("volume")
+        .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("volume")))?;
+    Ok((name.to_string(), volume.to_string()))
+}
+
+fn core_to_volume(volume: &ModuleVolume) -> Volume {
+    let mut details = Volume::new(
+        volume.name().to_string(),
+        volume.driver().to_string(),
+        volume.mount_point().to_string(),
+    );
+    if let Some(created_at) = volume.created_at() {
+        details.set_created_at(created_at.to_string());
+    }
+    details
+}
+
 #[cfg(test)]
 pub mod tests {
     use failure::Fail;
@@ -206,6 +256,80 @@ pub mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn image_digest_mismatch() {
+        // arrange
+        let error = MgmtError::from(
+            DockerError::from(
+                DockerErrorKind::ImageDigestMismatch(
+                    "nginx:latest".to_string(),
+                    "sha256:1234".to_string(),
+                )
+                .context(DockerErrorKind::RuntimeOperation(
+                    RuntimeOperation::CreateModule("m1".to_string()),
+                )),
+            )
+            .context(ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(
+                "m1".to_string(),
+            ))),
+        );
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not create module m1\n\tcaused by: Could not create module m1\n\tcaused by: Image \"nginx:latest\" does not match the pinned digest \"sha256:1234\"",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn resource_limit_exceeded() {
+        // arrange
+        let error = MgmtError::from(
+            DockerError::from(
+                DockerErrorKind::ResourceLimitExceeded("memory".to_string(), 4096, 1024).context(
+                    DockerErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(
+                        "m1".to_string(),
+                    )),
+                ),
+            )
+            .context(ErrorKind::RuntimeOperation(RuntimeOperation::CreateModule(
+                "m1".to_string(),
+            ))),
+        );
+
+        // act
+        let response = error.into_response();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+                assert_eq!(
+                    "Could not create module m1\n\tcaused by: Could not create module m1\n\tcaused by: Requested memory of 4096 exceeds the maximum of 1024 allowed on this device",
+                    error.message()
+                );
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
     #[test]
     fn internal_server() {
         // arrange
@@ -1,29 +1,60 @@
 // Copyright (c) Microsoft. All rights reserved.
 
-use failure::ResultExt;
-use futures::{future, Future, Stream};
+//! Updates a module without leaving the device without it when the update
+//! fails.
+//!
+//! The new image is pulled before the existing module is touched, and the new
+//! module is created under a temporary name next to the existing one. The
+//! existing module is only stopped to start the new one in its place, since
+//! they share volumes and ports. Once the new module is running the names are
+//! swapped and the existing module is kept aside for the grace period, which
+//! runs after the response has been sent. If creating or starting the new
+//! module fails, or it stops running within the grace period, it is removed
+//! and the existing module is put back and restarted.
+
+use std::time::{Duration, Instant};
+
+use failure::{Fail, ResultExt};
+use futures::future::{self, Either};
+use futures::{Future, Stream};
 use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use hyper::{Body, Request, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
+use tokio;
+use tokio::timer::Delay;
 use url::form_urlencoded::parse as parse_query;
 
-use edgelet_core::{Module, ModuleRegistry, ModuleRuntime, ModuleStatus};
+use edgelet_core::{
+    Module, ModuleRegistry, ModuleRuntime, ModuleSpec, ModuleStatus, NEXT_VERSION_SUFFIX,
+};
 use edgelet_http::route::{Handler, Parameters};
 use edgelet_http::Error as HttpError;
 
-use super::{spec_to_core, spec_to_details};
+use super::{spec_to_core, spec_to_details, PREVIOUS_SUFFIX};
 use error::{Error, ErrorKind};
 use IntoResponse;
 
+/// How long a started module has to keep running for the update to succeed.
+const DEFAULT_GRACE_PERIOD_SECS: u64 = 10;
+
 pub struct UpdateModule<M> {
     runtime: M,
+    grace_period: Duration,
 }
 
 impl<M> UpdateModule<M> {
     pub fn new(runtime: M) -> Self {
-        UpdateModule { runtime }
+        UpdateModule {
+            runtime,
+            grace_period: Duration::from_secs(DEFAULT_GRACE_PERIOD_SECS),
+        }
+    }
+
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
     }
 }
 
@@ -38,6 +69,7 @@ where
         _params: Parameters,
     ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
         let runtime = self.runtime.clone();
+        let grace_period = self.grace_period;
 
         let start: bool = req
             .uri()
@@ -68,36 +100,7 @@ where
                     info!("Updating module {}", name);
                 }
 
-                runtime.remove(&name).then(|result| {
-                    result.with_context(|_| ErrorKind::UpdateModule(name.clone()))?;
-                    Ok((core_spec, spec, name, runtime))
-                })
-            })
-            .and_then(|(core_spec, spec, name, runtime)| {
-                debug!("Removed existing module {}", name);
-                runtime.registry().pull(core_spec.config()).then(|result| {
-                    result.with_context(|_| ErrorKind::UpdateModule(name.clone()))?;
-                    Ok((core_spec, spec, name, runtime))
-                })
-            })
-            .and_then(|(core_spec, spec, name, runtime)| {
-                debug!("Successfully pulled new image for module {}", name);
-                runtime.create(core_spec).then(|result| {
-                    result.with_context(|_| ErrorKind::UpdateModule(name.clone()))?;
-                    Ok((name, spec, runtime))
-                })
-            })
-            .and_then(move |(name, spec, runtime)| {
-                debug!("Created module {}", name);
-                if start {
-                    info!("Starting module {}", name);
-                    future::Either::A(runtime.start(&name).then(|result| {
-                        result.with_context(|_| ErrorKind::UpdateModule(name.clone()))?;
-                        Ok((ModuleStatus::Running, spec, name))
-                    }))
-                } else {
-                    future::Either::B(future::ok((ModuleStatus::Stopped, spec, name)))
-                }
+                update(runtime, core_spec, start, grace_period).map(|status| (status, spec, name))
             })
             .and_then(|(status, spec, name)| -> Result<_, Error> {
                 let details = spec_to_details(&spec, status);
@@ -117,6 +120,366 @@ where
     }
 }
 
+fn update<M>(
+    runtime: M,
+    spec: ModuleSpec<<M::Module as Module>::Config>,
+    start: bool,
+    grace_period: Duration,
+) -> Box<Future<Item = ModuleStatus, Error = Error> + Send>
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+{
+    let name = spec.name().to_string();
+    let next = format!("{}{}", name, NEXT_VERSION_SUFFIX);
+    let previous = format!("{}{}", name, PREVIOUS_SUFFIX);
+
+    // Pull first so that a failed pull leaves the existing module as it is.
+    let pulled = runtime
+        .registry()
+        .pull(spec.config())
+        .map_err(update_error(&name));
+
+    let existing = {
+        let runtime = runtime.clone();
+        let name = name.clone();
+        let next = next.clone();
+        let previous = previous.clone();
+        pulled.and_then(move |_| {
+            debug!("Successfully pulled new image for module {}", name);
+            existing_status(runtime, name, next, previous)
+        })
+    };
+
+    let created = {
+        let runtime = runtime.clone();
+        let name = name.clone();
+        let next = next.clone();
+        existing.and_then(move |existing| {
+            runtime
+                .create(spec.with_name(next.clone()))
+                .then(move |result| match result {
+                    Ok(()) => {
+                        debug!("Created module {}", next);
+                        Ok(existing)
+                    }
+                    Err(err) => {
+                        let err = update_error(&name)(err);
+                        match existing {
+                            Some(_) => Err(rolled_back(&name, err)),
+                            None => Err(err),
+                        }
+                    }
+                })
+        })
+    };
+
+    let started = {
+        let runtime = runtime.clone();
+        let name = name.clone();
+        let next = next.clone();
+        created.and_then(move |existing| {
+            let started = stop_and_start(runtime.clone(), &name, &next, existing, start);
+            started.then(move |result| match result {
+                Ok(()) => Either::A(future::ok(existing)),
+                Err(err) => Either::B(roll_back(&runtime, &name, &next, None, existing, err)),
+            })
+        })
+    };
+
+    let swapped = {
+        let runtime = runtime.clone();
+        let name = name.clone();
+        let previous = previous.clone();
+        started.and_then(move |existing| swap(runtime, name, next, previous, existing))
+    };
+
+    Box::new(swapped.and_then(move |existing| {
+        let status = if start {
+            ModuleStatus::Running
+        } else {
+            ModuleStatus::Stopped
+        };
+
+        let existing = match existing {
+            Some(existing) => existing,
+            None => return Either::A(future::ok(status)),
+        };
+
+        if start && grace_period != Duration::from_secs(0) {
+            tokio::spawn(watch(runtime, name, previous, existing, grace_period));
+            return Either::A(future::ok(status));
+        }
+
+        Either::B(remove_previous(&runtime, &name, &previous).map(move |_| status))
+    }))
+}
+
+/// Returns the status of the existing module, if there is one, after removing
+/// the versions of it an interrupted update left behind.
+fn existing_status<M>(
+    runtime: M,
+    name: String,
+    next: String,
+    previous: String,
+) -> Box<Future<Item = Option<ModuleStatus>, Error = Error> + Send>
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+{
+    let statuses = runtime
+        .list_with_details()
+        .collect()
+        .map_err(update_error(&name));
+
+    Box::new(statuses.and_then(move |modules| {
+        let status_of = |name: &str| {
+            modules
+                .iter()
+                .find(|&&(ref module, _)| module.name() == name)
+                .map(|&(_, ref state)| *state.status())
+        };
+        let existing = status_of(&name);
+
+        let stale: Vec<_> = vec![next, previous]
+            .into_iter()
+            .filter(|stale| status_of(stale).is_some())
+            .map(|stale| runtime.remove(&stale))
+            .collect();
+
+        future::join_all(stale)
+            .map(move |_| existing)
+            .map_err(update_error(&name))
+    }))
+}
+
+/// Stops the existing module, if it is running, and starts the new module in
+/// its place when `start` is set.
+fn stop_and_start<M>(
+    runtime: M,
+    name: &str,
+    next: &str,
+    existing: Option<ModuleStatus>,
+    start: bool,
+) -> Box<Future<Item = (), Error = Error> + Send>
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+{
+    let stopped = if existing == Some(ModuleStatus::Running) {
+        Either::A(runtime.stop(name, None).map_err(update_error(name)))
+    } else {
+        Either::B(future::ok(()))
+    };
+
+    if !start {
+        return Box::new(stopped);
+    }
+
+    let name = name.to_string();
+    let next = next.to_string();
+    Box::new(stopped.and_then(move |_| {
+        info!("Starting module {}", name);
+        let started = runtime.start(&next).map_err(update_error(&name));
+        started.and_then(move |_| check_running(&runtime, name, next))
+    }))
+}
+
+/// Gives the new module the real name and keeps the existing module, if there
+/// is one, aside under `previous`.
+fn swap<M>(
+    runtime: M,
+    name: String,
+    next: String,
+    previous: String,
+    existing: Option<ModuleStatus>,
+) -> Box<Future<Item = Option<ModuleStatus>, Error = Error> + Send>
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+{
+    let set_aside = if existing.is_some() {
+        Either::A(runtime.rename(&name, &previous))
+    } else {
+        Either::B(future::ok(()))
+    };
+
+    Box::new(set_aside.then(move |result| {
+        if let Err(err) = result {
+            let err = update_error(&name)(err);
+            return Either::A(roll_back(&runtime, &name, &next, None, existing, err));
+        }
+
+        Either::B(
+            runtime
+                .rename(&next, &name)
+                .then(move |result| match result {
+                    Ok(()) => {
+                        debug!("Swapped module {} for its new version", name);
+                        Either::A(future::ok(existing))
+                    }
+                    Err(err) => {
+                        let err = update_error(&name)(err);
+                        let set_aside = existing.map(|_| previous.as_str());
+                        Either::B(roll_back(&runtime, &name, &next, set_aside, existing, err))
+                    }
+                }),
+        )
+    }))
+}
+
+/// Checks that the module is still running once the grace period is over,
+/// and otherwise puts the existing module back.
+fn watch<M>(
+    runtime: M,
+    name: String,
+    previous: String,
+    existing: ModuleStatus,
+    grace_period: Duration,
+) -> impl Future<Item = (), Error = ()> + Send
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+{
+    let checked = {
+        let runtime = runtime.clone();
+        let name = name.clone();
+        Delay::new(Instant::now() + grace_period)
+            .map_err(update_error(&name))
+            .and_then(move |_| check_running(&runtime, name.clone(), name))
+    };
+
+    checked.then(move |result| match result {
+        Ok(()) => Either::A(remove_previous(&runtime, &name, &previous)),
+        Err(err) => Either::B(
+            roll_back(&runtime, &name, &name, Some(&previous), Some(existing), err)
+                .then(|_: Result<(), Error>| Ok(())),
+        ),
+    })
+}
+
+/// Checks that the module running under `id` as the new version of module
+/// `name` is still running.
+fn check_running<M>(
+    runtime: &M,
+    name: String,
+    id: String,
+) -> Box<Future<Item = (), Error = Error> + Send>
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+{
+    Box::new(
+        runtime
+            .list_with_details()
+            .collect()
+            .map_err(update_error(&name))
+            .and_then(move |modules| {
+                let running = modules.iter().any(|&(ref module, ref state)| {
+                    module.name() == id && *state.status() == ModuleStatus::Running
+                });
+                if running {
+                    Ok(())
+                } else {
+                    Err(Error::from(ErrorKind::ModuleStopped(name)))
+                }
+            }),
+    )
+}
+
+/// Removes the existing module once the new one has replaced it. A failure
+/// is only logged, since the update itself succeeded.
+fn remove_previous<M, E>(
+    runtime: &M,
+    name: &str,
+    previous: &str,
+) -> impl Future<Item = (), Error = E> + Send
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+    E: 'static + Send,
+{
+    let name = name.to_string();
+    runtime.remove(previous).then(move |result| {
+        if let Err(err) = result {
+            warn!(
+                "Could not remove the previous version of module {}: {}",
+                name, err
+            );
+        }
+        Ok(())
+    })
+}
+
+/// Puts the existing module back in place and fails with `cause`, reporting
+/// whether it was restored. The failed new module, `failed`, is removed, the
+/// existing module is renamed back if it was `set_aside`, and it is restarted
+/// if it was running.
+fn roll_back<M, T>(
+    runtime: &M,
+    name: &str,
+    failed: &str,
+    set_aside: Option<&str>,
+    existing: Option<ModuleStatus>,
+    cause: Error,
+) -> Box<Future<Item = T, Error = Error> + Send>
+where
+    M: 'static + ModuleRuntime + Clone + Send + Sync,
+    T: 'static + Send,
+{
+    let removed = runtime.remove(failed);
+
+    if existing.is_none() {
+        return Box::new(removed.then(|_| Err(cause)));
+    }
+
+    warn!(
+        "Could not update module {}, restoring the previous version: {}",
+        name, cause
+    );
+
+    let renamed = {
+        let runtime = runtime.clone();
+        let name = name.to_string();
+        let set_aside = set_aside.map(ToString::to_string);
+        removed.and_then(move |_| match set_aside {
+            Some(set_aside) => Either::A(runtime.rename(&set_aside, &name)),
+            None => Either::B(future::ok(())),
+        })
+    };
+
+    let restarted = {
+        let runtime = runtime.clone();
+        let name = name.to_string();
+        renamed.and_then(move |_| {
+            if existing == Some(ModuleStatus::Running) {
+                Either::A(runtime.start(&name))
+            } else {
+                Either::B(future::ok(()))
+            }
+        })
+    };
+
+    let name = name.to_string();
+    Box::new(restarted.then(move |result| match result {
+        Ok(()) => {
+            info!("Restored the previous version of module {}", name);
+            Err(rolled_back(&name, cause))
+        }
+        Err(err) => {
+            error!(
+                "Could not restore the previous version of module {}: {}",
+                name, err
+            );
+            Err(Error::from(
+                cause.context(ErrorKind::UpdateModuleRollback(name)),
+            ))
+        }
+    }))
+}
+
+fn rolled_back(name: &str, cause: Error) -> Error {
+    Error::from(cause.context(ErrorKind::UpdateModuleRolledBack(name.to_string())))
+}
+
+fn update_error<E: Fail>(name: &str) -> impl Fn(E) -> Error {
+    let name = name.to_string();
+    move |err| Error::from(err.context(ErrorKind::UpdateModule(name.clone())))
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::prelude::*;
@@ -125,6 +488,7 @@ mod tests {
     use edgelet_test_utils::module::*;
     use management::models::{Config, ErrorResponse, ModuleDetails, ModuleSpec};
     use server::module::tests::Error;
+    use tokio::runtime::current_thread::Runtime;
 
     use super::*;
 
@@ -145,7 +509,7 @@ mod tests {
 
     #[test]
     fn success() {
-        let handler = UpdateModule::new(RUNTIME.clone());
+        let handler = UpdateModule::new(runtime(ModuleStatus::Running));
         let config = Config::new(json!({"image":"microsoft/test-image"}));
         let spec = ModuleSpec::new("test-module".to_string(), "docker".to_string(), config);
         let request = Request::put("http://localhost/modules/test-module")
@@ -184,7 +548,8 @@ mod tests {
 
     #[test]
     fn success_start() {
-        let handler = UpdateModule::new(RUNTIME.clone());
+        let handler = UpdateModule::new(runtime(ModuleStatus::Running))
+            .with_grace_period(Duration::from_secs(0));
         let config = Config::new(json!({"image":"microsoft/test-image"}));
         let spec = ModuleSpec::new("test-module".to_string(), "docker".to_string(), config);
         let request = Request::put("http://localhost/modules/test-module?start")
@@ -221,6 +586,173 @@ mod tests {
             .unwrap();
     }
 
+    fn runtime(status: ModuleStatus) -> TestRuntime<Error> {
+        let state = ModuleRuntimeState::default().with_status(status);
+        let config = TestConfig::new("microsoft/test-image".to_string());
+        let module = TestModule::new("test-module".to_string(), config, Ok(state));
+        TestRuntime::new(Ok(module))
+    }
+
+    fn update(runtime: &TestRuntime<Error>) -> Response<Body> {
+        let handler = UpdateModule::new(runtime.clone()).with_grace_period(Duration::from_secs(0));
+        let config = Config::new(json!({"image":"microsoft/test-image"}));
+        let spec = ModuleSpec::new("test-module".to_string(), "docker".to_string(), config);
+        let request = Request::put("http://localhost/modules/test-module?start")
+            .body(serde_json::to_string(&spec).unwrap().into())
+            .unwrap();
+
+        handler.handle(request, Parameters::new()).wait().unwrap()
+    }
+
+    fn error_message(response: Response<Body>) -> String {
+        let b = response.into_body().concat2().wait().unwrap();
+        let error: ErrorResponse = serde_json::from_slice(&b).unwrap();
+        error.message().to_string()
+    }
+
+    #[test]
+    fn success_replaces_previous_module() {
+        let runtime = runtime(ModuleStatus::Running);
+
+        // act
+        let response = update(&runtime);
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            vec![
+                "create test-module.next",
+                "stop test-module",
+                "start test-module.next",
+                "rename test-module test-module.previous",
+                "rename test-module.next test-module",
+                "remove test-module.previous",
+            ],
+            runtime.operations()
+        );
+    }
+
+    #[test]
+    fn create_error_rolls_back() {
+        let runtime = runtime(ModuleStatus::Running).with_create_error(Error::General);
+
+        // act
+        let response = update(&runtime);
+
+        // assert
+        assert_eq!(StatusCode::CONFLICT, response.status());
+        assert_eq!(vec!["create test-module.next"], runtime.operations());
+        assert_eq!(
+            "Could not update module test-module, the previous version was restored\n\tcaused by: Could not update module\n\tcaused by: General error",
+            error_message(response)
+        );
+    }
+
+    #[test]
+    fn start_error_rolls_back() {
+        let runtime = runtime(ModuleStatus::Running).with_start_error(Error::General);
+
+        // act
+        let response = update(&runtime);
+
+        // assert
+        assert_eq!(StatusCode::CONFLICT, response.status());
+        assert_eq!(
+            vec![
+                "create test-module.next",
+                "stop test-module",
+                "start test-module.next",
+                "remove test-module.next",
+                "start test-module",
+            ],
+            runtime.operations()
+        );
+        assert_eq!(
+            "Could not update module test-module, the previous version was restored\n\tcaused by: Could not update module\n\tcaused by: General error",
+            error_message(response)
+        );
+    }
+
+    #[test]
+    fn stopped_module_rolls_back() {
+        let runtime = runtime(ModuleStatus::Failed);
+
+        // act
+        let response = update(&runtime);
+
+        // assert
+        assert_eq!(StatusCode::CONFLICT, response.status());
+        assert_eq!(
+            vec![
+                "create test-module.next",
+                "start test-module.next",
+                "remove test-module.next",
+            ],
+            runtime.operations()
+        );
+        assert_eq!(
+            "Could not update module test-module, the previous version was restored\n\tcaused by: Module test-module stopped running shortly after it was started",
+            error_message(response)
+        );
+    }
+
+    #[test]
+    fn grace_period_runs_after_response() {
+        let runtime = runtime(ModuleStatus::Running);
+        let handler =
+            UpdateModule::new(runtime.clone()).with_grace_period(Duration::from_millis(10));
+        let config = Config::new(json!({"image":"microsoft/test-image"}));
+        let spec = ModuleSpec::new("test-module".to_string(), "docker".to_string(), config);
+        let request = Request::put("http://localhost/modules/test-module?start")
+            .body(serde_json::to_string(&spec).unwrap().into())
+            .unwrap();
+        let mut tokio_runtime = Runtime::new().unwrap();
+
+        // act
+        let response = tokio_runtime
+            .block_on(handler.handle(request, Parameters::new()))
+            .unwrap();
+
+        // assert
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            Some(&"rename test-module.next test-module".to_string()),
+            runtime.operations().last()
+        );
+
+        tokio_runtime.run().unwrap();
+        assert_eq!(
+            Some(&"remove test-module.previous".to_string()),
+            runtime.operations().last()
+        );
+    }
+
+    #[test]
+    fn reserved_name_is_rejected() {
+        let runtime = runtime(ModuleStatus::Running);
+        let handler = UpdateModule::new(runtime.clone());
+        let config = Config::new(json!({"image":"microsoft/test-image"}));
+        let spec = ModuleSpec::new(
+            "test-module.previous".to_string(),
+            "docker".to_string(),
+            config,
+        );
+        let request = Request::put("http://localhost/modules/test-module.previous")
+            .body(serde_json::to_string(&spec).unwrap().into())
+            .unwrap();
+
+        // act
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+
+        // assert
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert!(runtime.operations().is_empty());
+        assert_eq!(
+            "Request body is malformed\n\tcaused by: The module name \"test-module.previous\" is reserved",
+            error_message(response)
+        );
+    }
+
     #[test]
     fn bad_body() {
         let handler = UpdateModule::new(RUNTIME.clone());
@@ -0,0 +1,5 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+mod roots;
+
+pub use self::roots::{AddTrustedRoot, ListTrustedRoots, RemoveTrustedRoot};
@@ -0,0 +1,297 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use failure::{Fail, ResultExt};
+use futures::{future, Future, Stream};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{ErrorKind as CoreErrorKind, TrustBundleStore, TrustedRoot as CoreTrustedRoot};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use management::models::{AddTrustedRootRequest, TrustedRoot, TrustedRootList};
+
+use error::{Error, ErrorKind};
+use IntoResponse;
+
+pub struct ListTrustedRoots<C> {
+    store: TrustBundleStore<C>,
+}
+
+impl<C> ListTrustedRoots<C> {
+    pub fn new(store: TrustBundleStore<C>) -> Self {
+        ListTrustedRoots { store }
+    }
+}
+
+impl<C> Handler<Parameters> for ListTrustedRoots<C>
+where
+    C: 'static + Send + Sync,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = self
+            .store
+            .roots()
+            .context(ErrorKind::TrustedRoots)
+            .map_err(Error::from)
+            .and_then(|roots| {
+                let body = TrustedRootList::new(roots.iter().map(trusted_root).collect());
+                let b = serde_json::to_string(&body).context(ErrorKind::TrustedRoots)?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .context(ErrorKind::TrustedRoots)?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(future::result(response))
+    }
+}
+
+pub struct AddTrustedRoot<C> {
+    store: TrustBundleStore<C>,
+}
+
+impl<C> AddTrustedRoot<C> {
+    pub fn new(store: TrustBundleStore<C>) -> Self {
+        AddTrustedRoot { store }
+    }
+}
+
+impl<C> Handler<Parameters> for AddTrustedRoot<C>
+where
+    C: 'static + Clone + Send + Sync,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        _params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let store = self.store.clone();
+
+        let response = req
+            .into_body()
+            .concat2()
+            .then(move |b| -> Result<_, Error> {
+                let b = b.context(ErrorKind::MalformedRequestBody)?;
+                let request = serde_json::from_slice::<AddTrustedRootRequest>(&b)
+                    .context(ErrorKind::MalformedRequestBody)?;
+
+                let root = store.add_root(request.certificate()).map_err(|err| {
+                    let kind = match *err.kind() {
+                        CoreErrorKind::InvalidTrustedRoot
+                        | CoreErrorKind::TrustedRootExpired(_)
+                        | CoreErrorKind::TrustedRootNotCa => {
+                            ErrorKind::MalformedRequestParameter("certificate")
+                        }
+                        _ => ErrorKind::AddTrustedRoot,
+                    };
+                    Error::from(err.context(kind))
+                })?;
+                info!("Added root {} to the trust bundle", root.id());
+
+                let b = serde_json::to_string(&trusted_root(&root))
+                    .context(ErrorKind::AddTrustedRoot)?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, b.len().to_string().as_str())
+                    .body(b.into())
+                    .context(ErrorKind::AddTrustedRoot)?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+pub struct RemoveTrustedRoot<C> {
+    store: TrustBundleStore<C>,
+}
+
+impl<C> RemoveTrustedRoot<C> {
+    pub fn new(store: TrustBundleStore<C>) -> Self {
+        RemoveTrustedRoot { store }
+    }
+}
+
+impl<C> Handler<Parameters> for RemoveTrustedRoot<C>
+where
+    C: 'static + Send + Sync,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = params
+            .name("id")
+            .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("id")))
+            .and_then(|id| {
+                let removed = self
+                    .store
+                    .remove_root(id)
+                    .context(ErrorKind::RemoveTrustedRoot(id.to_string()))?;
+                if !removed {
+                    return Err(Error::from(ErrorKind::TrustedRootNotFound(id.to_string())));
+                }
+                info!("Removed root {} from the trust bundle", id);
+
+                Ok(Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::default())
+                    .context(ErrorKind::RemoveTrustedRoot(id.to_string()))?)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(future::result(response))
+    }
+}
+
+fn trusted_root(root: &CoreTrustedRoot) -> TrustedRoot {
+    TrustedRoot::new(root.id().to_string(), root.pem().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use management::models::ErrorResponse;
+
+    use super::*;
+
+    const ROOT: &str = "\
+    -----BEGIN CERTIFICATE-----\n\
+    MIIBijCCAS+gAwIBAgIUTHcTOZBFqx6HgTkpXHnlFxcfbKEwCgYIKoZIzj0EAwIw\n\
+    GTEXMBUGA1UEAwwOVGVzdCBSb290IHJvb3QwIBcNMjYxMDE4MTgzMDM5WhgPMjEy\n\
+    NjA5MjQxODMwMzlaMBkxFzAVBgNVBAMMDlRlc3QgUm9vdCByb290MFkwEwYHKoZI\n\
+    zj0CAQYIKoZIzj0DAQcDQgAE5LWi8mEQfGyNuZydQNAeNqIOkjD+u7VYmL5Agehs\n\
+    7iWfqpldam9U7YnUJ6UbhmTHKI01FaZBblVoY9CcXdy9n6NTMFEwHQYDVR0OBBYE\n\
+    FMX8GwMUWUis4nNkeZpa3qIApwCnMB8GA1UdIwQYMBaAFMX8GwMUWUis4nNkeZpa\n\
+    3qIApwCnMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSQAwRgIhAL3l1eRM\n\
+    KNZw5P3JmN/ej+507K5S+pkvdbpcWEBs81YGAiEAo5trk9gFSo7/9+RymZJIYRz3\n\
+    2+/Af7Jz0MA1DFTE3Fw=\n\
+    -----END CERTIFICATE-----\n";
+    const LEAF: &str = "\
+    -----BEGIN CERTIFICATE-----\n\
+    MIIBfDCCASKgAwIBAgIUcjSSjUYKFibzH37MzquWA6lW0+4wCgYIKoZIzj0EAwIw\n\
+    FDESMBAGA1UEAwwJVGVzdCBMZWFmMCAXDTI2MTAxODE4MzAzOVoYDzIxMjYwOTI0\n\
+    MTgzMDM5WjAUMRIwEAYDVQQDDAlUZXN0IExlYWYwWTATBgcqhkjOPQIBBggqhkjO\n\
+    PQMBBwNCAATq7ZurXTsyyh1hh4xbjLetIVUp265iRwmwS2fc9rH31PKzSFeRYl+u\n\
+    0kXCbZ4YJ3sqWotjEoTSIdSDlngwRUPPo1AwTjAdBgNVHQ4EFgQUH5oAEVS5EA1F\n\
+    SOTXNTg68cvl6cYwHwYDVR0jBBgwFoAUH5oAEVS5EA1FSOTXNTg68cvl6cYwDAYD\n\
+    VR0TAQH/BAIwADAKBggqhkjOPQQDAgNIADBFAiEA48QwJuoookem6Vzr5C7doPq6\n\
+    4nBRMJWR3H81eDBRHpcCIBVv43XXqiNKmFX14MDuZMAA0K4NQTq2nueA/6kkDy3P\n\
+    -----END CERTIFICATE-----\n";
+
+    fn store(dir: &TempDir) -> TrustBundleStore<()> {
+        TrustBundleStore::new(dir.path().join("trust_bundle"), ()).unwrap()
+    }
+
+    fn add_request(certificate: &str) -> Request<Body> {
+        let request = AddTrustedRootRequest::new(certificate.to_string());
+        Request::post("http://localhost/trust-bundle/roots")
+            .body(serde_json::to_string(&request).unwrap().into())
+            .unwrap()
+    }
+
+    fn id_parameters(id: &str) -> Parameters {
+        Parameters::with_captures(vec![(Some("id".to_string()), id.to_string())])
+    }
+
+    #[test]
+    fn add_and_list_roots() {
+        let dir = TempDir::new("trust_bundle").unwrap();
+        let store = store(&dir);
+
+        let response = AddTrustedRoot::new(store.clone())
+            .handle(add_request(ROOT), Parameters::default())
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        let id = response
+            .into_body()
+            .concat2()
+            .map(|body| {
+                let root: TrustedRoot = serde_json::from_slice(&body).unwrap();
+                assert_eq!(ROOT, root.certificate());
+                root.id().to_string()
+            })
+            .wait()
+            .unwrap();
+
+        let response = ListTrustedRoots::new(store)
+            .handle(Request::default(), Parameters::default())
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|body| {
+                let list: TrustedRootList = serde_json::from_slice(&body).unwrap();
+                assert_eq!(1, list.roots().len());
+                assert_eq!(&id, list.roots()[0].id());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+    }
+
+    #[test]
+    fn add_invalid_root_fails() {
+        let dir = TempDir::new("trust_bundle").unwrap();
+        let store = store(&dir);
+
+        for certificate in &["not a certificate", LEAF] {
+            let response = AddTrustedRoot::new(store.clone())
+                .handle(add_request(certificate), Parameters::default())
+                .wait()
+                .unwrap();
+
+            assert_eq!(StatusCode::BAD_REQUEST, response.status());
+            response
+                .into_body()
+                .concat2()
+                .and_then(|body| {
+                    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+                    assert!(error
+                        .message()
+                        .starts_with("The request parameter `certificate` is malformed"));
+                    Ok(())
+                })
+                .wait()
+                .unwrap();
+        }
+        assert!(store.roots().unwrap().is_empty());
+    }
+
+    #[test]
+    fn remove_root() {
+        let dir = TempDir::new("trust_bundle").unwrap();
+        let store = store(&dir);
+        let root = store.add_root(ROOT).unwrap();
+        let handler = RemoveTrustedRoot::new(store.clone());
+
+        let response = handler
+            .handle(Request::default(), id_parameters(root.id()))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert!(store.roots().unwrap().is_empty());
+
+        let response = handler
+            .handle(Request::default(), id_parameters(root.id()))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+}
@@ -40,6 +40,12 @@ pub enum ErrorKind {
     #[fail(display = "Module not found")]
     ModuleNotFound(String),
 
+    #[fail(display = "Secret {} not found", _0)]
+    SecretNotFound(String),
+
+    #[fail(display = "{}", _0)]
+    SecretOperation(SecretOperation),
+
     #[fail(display = "Could not start workload service")]
     StartService,
 }
@@ -90,7 +96,7 @@ impl IntoResponse for Error {
         }
 
         let status_code = match *self.kind() {
-            ErrorKind::ModuleNotFound(_) => StatusCode::NOT_FOUND,
+            ErrorKind::ModuleNotFound(_) | ErrorKind::SecretNotFound(_) => StatusCode::NOT_FOUND,
             ErrorKind::MalformedRequestBody
             | ErrorKind::MalformedRequestParameter(_)
             | ErrorKind::MissingRequiredParameter(_) => StatusCode::BAD_REQUEST,
@@ -126,6 +132,7 @@ impl IntoResponse for Error {
 #[derive(Clone, Copy, Debug)]
 pub enum CertOperation {
     CreateIdentityCert,
+    GetCertificateNotifications,
     GetServerCert,
 }
 
@@ -133,6 +140,9 @@ impl fmt::Display for CertOperation {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             CertOperation::CreateIdentityCert => write!(f, "Could not create identity cert"),
+            CertOperation::GetCertificateNotifications => {
+                write!(f, "Could not get certificate notifications")
+            }
             CertOperation::GetServerCert => write!(f, "Could not get server cert"),
         }
     }
@@ -156,3 +166,20 @@ impl fmt::Display for EncryptionOperation {
         }
     }
 }
+
+#[derive(Clone, Debug)]
+pub enum SecretOperation {
+    Delete(String),
+    Get(String),
+    Set(String),
+}
+
+impl fmt::Display for SecretOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SecretOperation::Delete(key) => write!(f, "Could not delete secret {}", key),
+            SecretOperation::Get(key) => write!(f, "Could not get secret {}", key),
+            SecretOperation::Set(key) => write!(f, "Could not set secret {}", key),
+        }
+    }
+}
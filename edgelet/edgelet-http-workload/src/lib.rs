@@ -25,6 +25,9 @@ extern crate hyper;
 extern crate log;
 extern crate serde;
 extern crate serde_json;
+#[cfg(test)]
+extern crate tempdir;
+extern crate url;
 extern crate workload;
 
 use hyper::{Body, Response};
@@ -7,7 +7,8 @@ use hyper::{Body, Request, Response};
 use serde_json;
 
 use edgelet_core::{
-    Certificate, CertificateProperties, CertificateType, CreateCertificate, WorkloadConfig,
+    Certificate, CertificateNotifier, CertificateProperties, CertificateType, CreateCertificate,
+    WorkloadConfig,
 };
 use edgelet_http::route::{Handler, Parameters};
 use edgelet_http::Error as HttpError;
@@ -20,11 +21,16 @@ use IntoResponse;
 pub struct IdentityCertHandler<T: CreateCertificate, W: WorkloadConfig> {
     hsm: T,
     config: W,
+    notifier: CertificateNotifier,
 }
 
 impl<T: CreateCertificate, W: WorkloadConfig> IdentityCertHandler<T, W> {
-    pub fn new(hsm: T, config: W) -> Self {
-        IdentityCertHandler { hsm, config }
+    pub fn new(hsm: T, config: W, notifier: CertificateNotifier) -> Self {
+        IdentityCertHandler {
+            hsm,
+            config,
+            notifier,
+        }
     }
 }
 
@@ -41,6 +47,7 @@ where
     ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
         let hsm = self.hsm.clone();
         let cfg = self.config.clone();
+        let notifier = self.notifier.clone();
         let max_duration = cfg.get_cert_max_duration(CertificateType::Client);
 
         let response = params
@@ -80,6 +87,7 @@ where
                     ErrorKind::MalformedRequestParameter("name")
                 })?;
 
+                let module_id = cn.clone();
                 let sans = vec![module_uri];
                 let props = CertificateProperties::new(
                     expiration,
@@ -90,6 +98,8 @@ where
                 .with_san_entries(sans);
                 refresh_cert(
                     &hsm,
+                    &notifier,
+                    &module_id,
                     alias,
                     &props,
                     ErrorKind::CertOperation(CertOperation::CreateIdentityCert),
@@ -215,7 +225,11 @@ mod tests {
 
     #[test]
     fn missing_name_in_path() {
-        let handler = IdentityCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = IdentityCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
         let request = Request::get("http://localhost/modules//certificate/identity")
             .body("{}".into())
             .unwrap();
@@ -241,6 +255,7 @@ mod tests {
                     .with_private_key(PrivateKey::Key(KeyBytes::Pem("Betelgeuse".to_string()))))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new()
@@ -279,6 +294,7 @@ mod tests {
                 Ok(TestCert::default().with_private_key(PrivateKey::Ref("Betelgeuse".to_string())))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new()
@@ -318,6 +334,7 @@ mod tests {
                     .with_private_key(PrivateKey::Key(KeyBytes::Pem("Betelgeuse".to_string()))))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new();
@@ -356,6 +373,7 @@ mod tests {
                     .with_private_key(PrivateKey::Key(KeyBytes::Pem("Betelgeuse".to_string()))))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new()
@@ -383,7 +401,11 @@ mod tests {
 
     #[test]
     fn whitespace_expiration_fails() {
-        let handler = IdentityCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = IdentityCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req = IdentityCertificateRequest::new().with_expiration("       ".to_string());
 
@@ -404,7 +426,11 @@ mod tests {
 
     #[test]
     fn invalid_expiration_fails() {
-        let handler = IdentityCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = IdentityCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req =
             IdentityCertificateRequest::new().with_expiration("Umm.. No.. Just no..".to_string());
@@ -426,7 +452,11 @@ mod tests {
 
     #[test]
     fn past_expiration_fails() {
-        let handler = IdentityCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = IdentityCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req = IdentityCertificateRequest::new()
             .with_expiration("1999-06-28T16:39:57-08:00".to_string());
@@ -460,6 +490,7 @@ mod tests {
                     .with_private_key(PrivateKey::Key(KeyBytes::Pem("Betelgeuse".to_string()))))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let request = Request::get("http://localhost/modules/beeblebrox/certificate/identity")
@@ -489,6 +520,7 @@ mod tests {
                 Err(CoreError::from(CoreErrorKind::KeyStore))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new()
@@ -522,6 +554,7 @@ mod tests {
                 Ok(TestCert::default().with_fail_pem(true))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new()
@@ -555,6 +588,7 @@ mod tests {
                 Ok(TestCert::default().with_fail_private_key(true))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new()
@@ -588,6 +622,7 @@ mod tests {
                 Ok(TestCert::default().with_fail_valid_to(true))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = IdentityCertificateRequest::new()
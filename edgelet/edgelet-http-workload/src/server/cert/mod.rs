@@ -8,16 +8,21 @@ use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use hyper::{Body, Response, StatusCode};
 use serde_json;
 
-use edgelet_core::{Certificate, CertificateProperties, CreateCertificate, KeyBytes, PrivateKey};
+use edgelet_core::{
+    Certificate, CertificateNotifier, CertificateProperties, CreateCertificate, KeyBytes,
+    PrivateKey,
+};
 use edgelet_utils::ensure_not_empty_with_context;
 use workload::models::{CertificateResponse, PrivateKey as PrivateKeyResponse};
 
 use error::{Error, ErrorKind, Result};
 
 mod identity;
+mod notifications;
 mod server;
 
 pub use self::identity::IdentityCertHandler;
+pub use self::notifications::CertificateNotificationsHandler;
 pub use self::server::ServerCertHandler;
 
 fn cert_to_response<T: Certificate>(cert: &T, context: ErrorKind) -> Result<CertificateResponse> {
@@ -65,6 +70,8 @@ fn compute_validity(expiration: &str, max_duration_sec: i64, context: ErrorKind)
 
 fn refresh_cert<T: CreateCertificate>(
     hsm: &T,
+    notifier: &CertificateNotifier,
+    module_id: &str,
     alias: String,
     props: &CertificateProperties,
     context: ErrorKind,
@@ -78,9 +85,20 @@ fn refresh_cert<T: CreateCertificate>(
         Err(err) => return Err(Error::from(err.context(context))),
     };
 
-    let cert = cert_to_response(&cert, context.clone())?;
+    let cert_response = cert_to_response(&cert, context.clone())?;
 
-    let body = match serde_json::to_string(&cert) {
+    // cert_to_response has already read both of these successfully.
+    if let (Ok(pem), Ok(valid_to)) = (cert.pem(), cert.get_valid_to()) {
+        notifier.issued(
+            props.alias(),
+            module_id,
+            *props.certificate_type(),
+            pem.as_ref(),
+            valid_to,
+        );
+    }
+
+    let body = match serde_json::to_string(&cert_response) {
         Ok(body) => body,
         Err(err) => return Err(Error::from(err.context(context))),
     };
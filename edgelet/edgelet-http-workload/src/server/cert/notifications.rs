@@ -0,0 +1,209 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cmp;
+use std::time::Duration;
+
+use failure::{Fail, ResultExt};
+use futures::{future, Future, IntoFuture};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+use url::form_urlencoded;
+
+use edgelet_core::{
+    CertificateNotification as CoreCertificateNotification, CertificateNotifier, CertificateType,
+    NotificationKind,
+};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use workload::models::{CertificateNotification, CertificateNotificationsResponse};
+
+use error::{CertOperation, Error, ErrorKind};
+use IntoResponse;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+const MAX_TIMEOUT_SECS: u64 = 300;
+
+pub struct CertificateNotificationsHandler {
+    notifier: CertificateNotifier,
+}
+
+impl CertificateNotificationsHandler {
+    pub fn new(notifier: CertificateNotifier) -> Self {
+        CertificateNotificationsHandler { notifier }
+    }
+}
+
+impl Handler<Parameters> for CertificateNotificationsHandler {
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let notifier = self.notifier.clone();
+
+        let response = params
+            .name("name")
+            .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("name")))
+            .and_then(|name| {
+                let (after, timeout) = req.uri().query().map_or_else(
+                    || Ok((None, Duration::from_secs(DEFAULT_TIMEOUT_SECS))),
+                    parse_options,
+                )?;
+                let notifications = notifier.poll(name, after, timeout).map_err(|err| {
+                    Error::from(err.context(ErrorKind::CertOperation(
+                        CertOperation::GetCertificateNotifications,
+                    )))
+                });
+                Ok(notifications)
+            })
+            .into_future()
+            .flatten()
+            .then(|result| -> Result<_, Error> {
+                let (sequence, notifications) = result?;
+                let notifications = notifications.iter().map(notification_to_model).collect();
+                #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_wrap))]
+                let body = CertificateNotificationsResponse::new(sequence as i64, notifications);
+                let body = serde_json::to_string(&body).context(ErrorKind::CertOperation(
+                    CertOperation::GetCertificateNotifications,
+                ))?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, body.len().to_string().as_str())
+                    .body(body.into())
+                    .context(ErrorKind::CertOperation(
+                        CertOperation::GetCertificateNotifications,
+                    ))?;
+                Ok(response)
+            })
+            .or_else(|e| future::ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+fn parse_options(query: &str) -> Result<(Option<u64>, Duration), Error> {
+    let parse: Vec<_> = form_urlencoded::parse(query.as_bytes()).collect();
+    let after = match parse.iter().find(|&(ref key, _)| key == "after") {
+        Some((_, val)) => Some(
+            val.parse::<u64>()
+                .context(ErrorKind::MalformedRequestParameter("after"))?,
+        ),
+        None => None,
+    };
+    let timeout = parse
+        .iter()
+        .find(|&(ref key, _)| key == "timeout")
+        .map_or_else(|| Ok(DEFAULT_TIMEOUT_SECS), |(_, val)| val.parse::<u64>())
+        .context(ErrorKind::MalformedRequestParameter("timeout"))?;
+    Ok((
+        after,
+        Duration::from_secs(cmp::min(timeout, MAX_TIMEOUT_SECS)),
+    ))
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_wrap))]
+fn notification_to_model(notification: &CoreCertificateNotification) -> CertificateNotification {
+    let kind = match notification.kind() {
+        NotificationKind::Expiring => "expiring",
+        NotificationKind::CaRotated => "caRotated",
+        NotificationKind::TrustBundleChanged => "trustBundleChanged",
+    };
+    let mut model = CertificateNotification::new(
+        notification.sequence() as i64,
+        kind.to_string(),
+        notification.time().to_rfc3339(),
+    );
+    match notification.certificate_type() {
+        Some(CertificateType::Client) => model.set_certificate_type("identity".to_string()),
+        Some(CertificateType::Server) => model.set_certificate_type("server".to_string()),
+        _ => (),
+    }
+    if let Some(expiration) = notification.expiration() {
+        model.set_expiration(expiration.to_rfc3339());
+    }
+    model
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{Duration as ChronoDuration, Utc};
+    use futures::Stream;
+    use workload::models::ErrorResponse;
+
+    const LEAF: &str = "-----BEGIN CERTIFICATE-----\nLEAF\n-----END CERTIFICATE-----\n";
+
+    fn get(handler: &CertificateNotificationsHandler, query: &str) -> Response<Body> {
+        let uri = format!(
+            "http://localhost/modules/beeblebrox/certificate/notifications?{}",
+            query
+        );
+        let request = Request::get(uri.as_str()).body(Body::default()).unwrap();
+        let params =
+            Parameters::with_captures(vec![(Some("name".to_string()), "beeblebrox".to_string())]);
+        handler.handle(request, params).wait().unwrap()
+    }
+
+    #[test]
+    fn returns_notifications_for_module() {
+        let notifier = CertificateNotifier::new();
+        let valid_to = Utc::now() + ChronoDuration::hours(1);
+        notifier.issued(
+            "beeblebroxidentity",
+            "beeblebrox",
+            CertificateType::Client,
+            format!("{}{}", LEAF, "ca").as_bytes(),
+            valid_to,
+        );
+        notifier.issued(
+            "marvinidentity",
+            "marvin",
+            CertificateType::Client,
+            format!("{}{}", LEAF, "other ca").as_bytes(),
+            valid_to,
+        );
+        notifier.check("\"a\"", Utc::now());
+        notifier.check("\"b\"", Utc::now());
+        let handler = CertificateNotificationsHandler::new(notifier);
+
+        let response = get(&handler, "after=0&timeout=0");
+        assert_eq!(StatusCode::OK, response.status());
+        let body = response.into_body().concat2().wait().unwrap();
+        let response: CertificateNotificationsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(2, response.sequence());
+        let notifications = response.notifications();
+        assert_eq!(2, notifications.len());
+        assert_eq!("caRotated", notifications[0].kind());
+        assert_eq!(Some("identity"), notifications[0].certificate_type());
+        assert_eq!(
+            Some(valid_to.to_rfc3339().as_str()),
+            notifications[0].expiration()
+        );
+        assert_eq!("trustBundleChanged", notifications[1].kind());
+        assert_eq!(None, notifications[1].certificate_type());
+
+        let response = get(&handler, "after=2&timeout=0");
+        let body = response.into_body().concat2().wait().unwrap();
+        let response: CertificateNotificationsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(2, response.sequence());
+        assert!(response.notifications().is_empty());
+    }
+
+    #[test]
+    fn malformed_query_fails() {
+        let handler = CertificateNotificationsHandler::new(CertificateNotifier::new());
+        for (query, parameter) in &[("after=-1", "after"), ("timeout=soon", "timeout")] {
+            let response = get(&handler, query);
+            assert_eq!(StatusCode::BAD_REQUEST, response.status());
+            let body = response.into_body().concat2().wait().unwrap();
+            let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+            assert!(error.message().starts_with(&format!(
+                "The request parameter `{}` is malformed",
+                parameter
+            )));
+        }
+    }
+}
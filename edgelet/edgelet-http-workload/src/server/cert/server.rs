@@ -7,7 +7,8 @@ use hyper::{Body, Request, Response};
 use serde_json;
 
 use edgelet_core::{
-    Certificate, CertificateProperties, CertificateType, CreateCertificate, WorkloadConfig,
+    Certificate, CertificateNotifier, CertificateProperties, CertificateType, CreateCertificate,
+    WorkloadConfig,
 };
 use edgelet_http::route::{Handler, Parameters};
 use edgelet_http::Error as HttpError;
@@ -20,11 +21,16 @@ use IntoResponse;
 pub struct ServerCertHandler<T: CreateCertificate, W: WorkloadConfig> {
     hsm: T,
     config: W,
+    notifier: CertificateNotifier,
 }
 
 impl<T: CreateCertificate, W: WorkloadConfig> ServerCertHandler<T, W> {
-    pub fn new(hsm: T, config: W) -> Self {
-        ServerCertHandler { hsm, config }
+    pub fn new(hsm: T, config: W, notifier: CertificateNotifier) -> Self {
+        ServerCertHandler {
+            hsm,
+            config,
+            notifier,
+        }
     }
 }
 impl<T, W> Handler<Parameters> for ServerCertHandler<T, W>
@@ -40,6 +46,7 @@ where
     ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
         let hsm = self.hsm.clone();
         let cfg = self.config.clone();
+        let notifier = self.notifier.clone();
         let max_duration = cfg.get_cert_max_duration(CertificateType::Server);
 
         let response = params
@@ -99,6 +106,8 @@ where
                 .with_san_entries(sans);
                 let body = refresh_cert(
                     &hsm,
+                    &notifier,
+                    &module_id,
                     alias,
                     &props,
                     ErrorKind::CertOperation(CertOperation::GetServerCert),
@@ -220,7 +229,11 @@ mod tests {
 
     #[test]
     fn missing_name() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
         let request = Request::get("http://localhost/modules//genid/I/certificate/server")
             .body("".into())
             .unwrap();
@@ -234,7 +247,11 @@ mod tests {
 
     #[test]
     fn missing_genid() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
         let request = Request::get("http://localhost/modules/beelebrox/genid//certificate/server")
             .body("".into())
             .unwrap();
@@ -248,7 +265,11 @@ mod tests {
 
     #[test]
     fn empty_body() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
         let request =
             Request::get("http://localhost/modules/beeblebrox/genid/II/certificate/server")
                 .body("".into())
@@ -268,7 +289,11 @@ mod tests {
 
     #[test]
     fn bad_body() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
         let request =
             Request::get("http://localhost/modules/beeblebrox/genid/III/certificate/server")
                 .body("The answer is 42.".into())
@@ -288,7 +313,11 @@ mod tests {
 
     #[test]
     fn empty_expiration() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req = ServerCertificateRequest::new("".to_string(), "".to_string());
 
@@ -311,7 +340,11 @@ mod tests {
 
     #[test]
     fn whitespace_expiration() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req = ServerCertificateRequest::new("".to_string(), "       ".to_string());
 
@@ -334,7 +367,11 @@ mod tests {
 
     #[test]
     fn invalid_expiration() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req =
             ServerCertificateRequest::new("".to_string(), "Umm.. No.. Just no..".to_string());
@@ -358,7 +395,11 @@ mod tests {
 
     #[test]
     fn past_expiration() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req =
             ServerCertificateRequest::new("".to_string(), "1999-06-28T16:39:57-08:00".to_string());
@@ -382,7 +423,11 @@ mod tests {
 
     #[test]
     fn empty_common_name() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req = ServerCertificateRequest::new(
             "".to_string(),
@@ -409,7 +454,11 @@ mod tests {
 
     #[test]
     fn white_space_common_name() {
-        let handler = ServerCertHandler::new(TestHsm::default(), TestWorkloadData::default());
+        let handler = ServerCertHandler::new(
+            TestHsm::default(),
+            TestWorkloadData::default(),
+            CertificateNotifier::new(),
+        );
 
         let cert_req = ServerCertificateRequest::new(
             "      ".to_string(),
@@ -445,6 +494,7 @@ mod tests {
                 Err(CoreError::from(CoreErrorKind::KeyStore))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = ServerCertificateRequest::new(
@@ -481,6 +531,7 @@ mod tests {
                 Ok(TestCert::default().with_fail_pem(true))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = ServerCertificateRequest::new(
@@ -517,6 +568,7 @@ mod tests {
                 Ok(TestCert::default().with_fail_private_key(true))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = ServerCertificateRequest::new(
@@ -557,6 +609,7 @@ mod tests {
                     .with_private_key(PrivateKey::Key(KeyBytes::Pem("Betelgeuse".to_string()))))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = ServerCertificateRequest::new(
@@ -598,6 +651,7 @@ mod tests {
                 Ok(TestCert::default().with_private_key(PrivateKey::Ref("Betelgeuse".to_string())))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = ServerCertificateRequest::new(
@@ -640,6 +694,7 @@ mod tests {
                     .with_private_key(PrivateKey::Key(KeyBytes::Pem("Betelgeuse".to_string()))))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = ServerCertificateRequest::new(
@@ -681,6 +736,7 @@ mod tests {
                 Ok(TestCert::default().with_fail_valid_to(true))
             }),
             TestWorkloadData::default(),
+            CertificateNotifier::new(),
         );
 
         let cert_req = ServerCertificateRequest::new(
@@ -3,15 +3,17 @@
 mod cert;
 mod decrypt;
 mod encrypt;
+mod secret;
 mod sign;
 mod trust_bundle;
 
 use edgelet_core::{
-    CreateCertificate, Decrypt, Encrypt, GetTrustBundle, KeyStore, Module, ModuleRuntime, Policy,
-    WorkloadConfig,
+    CertificateNotifier, CreateCertificate, Decrypt, Encrypt, GetTrustBundle, KeyStore, Module,
+    ModuleRuntime, Policy, SecretStore, TrustBundleStore, WorkloadConfig,
 };
 use edgelet_http::authorization::Authorization;
 use edgelet_http::route::*;
+use edgelet_http::ApiVersion;
 use edgelet_http_mgmt::ListModules;
 use failure::{Compat, ResultExt};
 use futures::{future, Future};
@@ -19,9 +21,10 @@ use hyper::service::{NewService, Service};
 use hyper::{Body, Request};
 use serde::Serialize;
 
-use self::cert::{IdentityCertHandler, ServerCertHandler};
+use self::cert::{CertificateNotificationsHandler, IdentityCertHandler, ServerCertHandler};
 use self::decrypt::DecryptHandler;
 use self::encrypt::EncryptHandler;
+use self::secret::{DeleteSecretHandler, GetSecretHandler, SetSecretHandler};
 use self::sign::SignHandler;
 use self::trust_bundle::TrustBundleHandler;
 use error::{Error, ErrorKind};
@@ -39,6 +42,9 @@ impl WorkloadService {
         hsm: H,
         runtime: &M,
         config: W,
+        secrets: &SecretStore<H>,
+        trust_bundle: &TrustBundleStore<H>,
+        notifier: &CertificateNotifier,
     ) -> impl Future<Item = Self, Error = Error>
     where
         K: KeyStore + Clone + Send + Sync + 'static,
@@ -49,14 +55,19 @@ impl WorkloadService {
         W: WorkloadConfig + Clone + Send + Sync + 'static,
     {
         let router = router!(
-            get    "/modules" => Authorization::new(ListModules::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/sign" => Authorization::new(SignHandler::new(key_store.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/decrypt" => Authorization::new(DecryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/encrypt" => Authorization::new(EncryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/certificate/identity" => Authorization::new(IdentityCertHandler::new(hsm.clone(), config.clone()), Policy::Caller, runtime.clone()),
-            post   "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/certificate/server" => Authorization::new(ServerCertHandler::new(hsm.clone(), config), Policy::Caller, runtime.clone()),
+            get    ApiVersion::V2018_06_28.. => "/modules" => Authorization::new(ListModules::new(runtime.clone()), Policy::Anonymous, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/sign" => Authorization::new(SignHandler::new(key_store.clone()), Policy::Caller, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/decrypt" => Authorization::new(DecryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/encrypt" => Authorization::new(EncryptHandler::new(hsm.clone()), Policy::Caller, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/certificate/identity" => Authorization::new(IdentityCertHandler::new(hsm.clone(), config.clone(), notifier.clone()), Policy::Caller, runtime.clone()),
+            post   ApiVersion::V2018_06_28.. => "/modules/(?P<name>[^/]+)/genid/(?P<genid>[^/]+)/certificate/server" => Authorization::new(ServerCertHandler::new(hsm.clone(), config, notifier.clone()), Policy::Caller, runtime.clone()),
 
-            get    "/trust-bundle" => Authorization::new(TrustBundleHandler::new(hsm), Policy::Anonymous, runtime.clone()),
+            get    ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/certificate/notifications" => Authorization::new(CertificateNotificationsHandler::new(notifier.clone()), Policy::Caller, runtime.clone()),
+            get    ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/secrets/(?P<key>[^/]+)" => Authorization::new(GetSecretHandler::new(secrets.clone()), Policy::Caller, runtime.clone()),
+            put    ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/secrets/(?P<key>[^/]+)" => Authorization::new(SetSecretHandler::new(secrets.clone()), Policy::Caller, runtime.clone()),
+            delete ApiVersion::V2019_01_30.. => "/modules/(?P<name>[^/]+)/secrets/(?P<key>[^/]+)" => Authorization::new(DeleteSecretHandler::new(secrets.clone()), Policy::Caller, runtime.clone()),
+
+            get    ApiVersion::V2018_06_28.. => "/trust-bundle" => Authorization::new(TrustBundleHandler::new(trust_bundle.clone()), Policy::Anonymous, runtime.clone()),
         );
 
         router.new_service().then(|inner| {
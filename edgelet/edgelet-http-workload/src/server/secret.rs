@@ -0,0 +1,350 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use base64;
+use failure::{Fail, ResultExt};
+use futures::{Future, IntoFuture, Stream};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde_json;
+
+use edgelet_core::{Decrypt, Encrypt, Error as CoreError, ErrorKind as CoreErrorKind, SecretStore};
+use edgelet_http::route::{Handler, Parameters};
+use edgelet_http::Error as HttpError;
+use workload::models::Secret;
+
+use error::{Error, ErrorKind, SecretOperation};
+use IntoResponse;
+
+pub struct GetSecretHandler<C> {
+    secrets: SecretStore<C>,
+}
+
+impl<C> GetSecretHandler<C> {
+    pub fn new(secrets: SecretStore<C>) -> Self {
+        GetSecretHandler { secrets }
+    }
+}
+
+impl<C> Handler<Parameters> for GetSecretHandler<C>
+where
+    C: 'static + Decrypt + Encrypt + Clone + Send + Sync,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = secret_params(&params)
+            .and_then(|(name, key)| {
+                let operation = SecretOperation::Get(key.clone());
+                let value = self
+                    .secrets
+                    .get(&name, &key)
+                    .map_err(|err| store_error(err, &key, operation.clone()))?
+                    .ok_or_else(|| Error::from(ErrorKind::SecretNotFound(key)))?;
+
+                let body = serde_json::to_string(&Secret::new(base64::encode(&value)))
+                    .context(ErrorKind::SecretOperation(operation.clone()))?;
+                let response = Response::builder()
+                    .status(StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_LENGTH, body.len().to_string().as_str())
+                    .body(body.into())
+                    .context(ErrorKind::SecretOperation(operation))?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()))
+            .into_future();
+
+        Box::new(response)
+    }
+}
+
+pub struct SetSecretHandler<C> {
+    secrets: SecretStore<C>,
+}
+
+impl<C> SetSecretHandler<C> {
+    pub fn new(secrets: SecretStore<C>) -> Self {
+        SetSecretHandler { secrets }
+    }
+}
+
+impl<C> Handler<Parameters> for SetSecretHandler<C>
+where
+    C: 'static + Decrypt + Encrypt + Clone + Send + Sync,
+{
+    fn handle(
+        &self,
+        req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let secrets = self.secrets.clone();
+
+        let response = secret_params(&params)
+            .map(|(name, key)| {
+                req.into_body().concat2().then(move |body| {
+                    let body = body.context(ErrorKind::SecretOperation(SecretOperation::Set(
+                        key.clone(),
+                    )))?;
+                    Ok((name, key, body))
+                })
+            })
+            .into_future()
+            .flatten()
+            .and_then(move |(name, key, body)| -> Result<_, Error> {
+                let secret: Secret =
+                    serde_json::from_slice(&body).context(ErrorKind::MalformedRequestBody)?;
+                let value =
+                    base64::decode(secret.value()).context(ErrorKind::MalformedRequestBody)?;
+
+                let operation = SecretOperation::Set(key.clone());
+                secrets
+                    .set(&name, &key, &value)
+                    .map_err(|err| store_error(err, &key, operation.clone()))?;
+                info!("Set secret {} of module {}", key, name);
+
+                let response = Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::default())
+                    .context(ErrorKind::SecretOperation(operation))?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()));
+
+        Box::new(response)
+    }
+}
+
+pub struct DeleteSecretHandler<C> {
+    secrets: SecretStore<C>,
+}
+
+impl<C> DeleteSecretHandler<C> {
+    pub fn new(secrets: SecretStore<C>) -> Self {
+        DeleteSecretHandler { secrets }
+    }
+}
+
+impl<C> Handler<Parameters> for DeleteSecretHandler<C>
+where
+    C: 'static + Decrypt + Encrypt + Clone + Send + Sync,
+{
+    fn handle(
+        &self,
+        _req: Request<Body>,
+        params: Parameters,
+    ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let response = secret_params(&params)
+            .and_then(|(name, key)| {
+                let operation = SecretOperation::Delete(key.clone());
+                let removed = self
+                    .secrets
+                    .remove(&name, &key)
+                    .map_err(|err| store_error(err, &key, operation.clone()))?;
+                if !removed {
+                    return Err(Error::from(ErrorKind::SecretNotFound(key)));
+                }
+                info!("Deleted secret {} of module {}", key, name);
+
+                let response = Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::default())
+                    .context(ErrorKind::SecretOperation(operation))?;
+                Ok(response)
+            })
+            .or_else(|e| Ok(e.into_response()))
+            .into_future();
+
+        Box::new(response)
+    }
+}
+
+fn secret_params(params: &Parameters) -> Result<(String, String), Error> {
+    let name = params
+        .name("name")
+        .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("name")))?;
+    let key = params
+        .name("key")
+        .ok_or_else(|| Error::from(ErrorKind::MissingRequiredParameter("key")))?;
+    Ok((name.to_string(), key.to_string()))
+}
+
+/// Names the store can't use as file names are the caller's fault, anything
+/// else is an internal error.
+fn store_error(err: CoreError, key: &str, operation: SecretOperation) -> Error {
+    let kind = match *err.kind() {
+        CoreErrorKind::InvalidSecretName(ref invalid) if invalid == key => {
+            ErrorKind::MalformedRequestParameter("key")
+        }
+        CoreErrorKind::InvalidSecretName(_) => ErrorKind::MalformedRequestParameter("name"),
+        _ => ErrorKind::SecretOperation(operation),
+    };
+    Error::from(err.context(kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+    use workload::models::ErrorResponse;
+
+    use super::*;
+
+    #[derive(Clone, Debug, Default)]
+    struct TestHsm;
+
+    impl Encrypt for TestHsm {
+        type Buffer = Vec<u8>;
+
+        fn encrypt(
+            &self,
+            _client_id: &[u8],
+            plaintext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, CoreError> {
+            let mut rev = plaintext.to_vec();
+            rev.reverse(); // this "encrypt" function simply reverses the buffer's contents
+            Ok(rev)
+        }
+    }
+
+    impl Decrypt for TestHsm {
+        type Buffer = Vec<u8>;
+
+        fn decrypt(
+            &self,
+            _client_id: &[u8],
+            ciphertext: &[u8],
+            _initialization_vector: &[u8],
+        ) -> Result<Self::Buffer, CoreError> {
+            let mut rev = ciphertext.to_vec();
+            rev.reverse();
+            Ok(rev)
+        }
+    }
+
+    fn store(dir: &TempDir) -> SecretStore<TestHsm> {
+        SecretStore::new(dir.path().to_path_buf(), TestHsm).unwrap()
+    }
+
+    fn params(key: &str) -> Parameters {
+        Parameters::with_captures(vec![
+            (Some("name".to_string()), "m1".to_string()),
+            (Some("key".to_string()), key.to_string()),
+        ])
+    }
+
+    fn set_request(value: &[u8]) -> Request<Body> {
+        let secret = Secret::new(base64::encode(value));
+        Request::put("http://localhost/modules/m1/secrets/password")
+            .body(serde_json::to_string(&secret).unwrap().into())
+            .unwrap()
+    }
+
+    fn empty_request() -> Request<Body> {
+        Request::get("http://localhost/modules/m1/secrets/password")
+            .body(Body::default())
+            .unwrap()
+    }
+
+    fn error_message(response: Response<Body>) -> String {
+        response
+            .into_body()
+            .concat2()
+            .map(|body| {
+                let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+                error.message().to_string()
+            })
+            .wait()
+            .unwrap()
+    }
+
+    #[test]
+    fn set_get_and_delete_secret() {
+        let dir = TempDir::new("secrets").unwrap();
+        let store = store(&dir);
+
+        let response = SetSecretHandler::new(store.clone())
+            .handle(set_request(b"hunter2"), params("password"))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+
+        let response = GetSecretHandler::new(store.clone())
+            .handle(empty_request(), params("password"))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        let secret: Secret = response
+            .into_body()
+            .concat2()
+            .map(|body| serde_json::from_slice(&body).unwrap())
+            .wait()
+            .unwrap();
+        assert_eq!(&base64::encode(b"hunter2"), secret.value());
+
+        let response = DeleteSecretHandler::new(store.clone())
+            .handle(empty_request(), params("password"))
+            .wait()
+            .unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, response.status());
+        assert_eq!(None, store.get("m1", "password").unwrap());
+    }
+
+    #[test]
+    fn get_unknown_secret_is_not_found() {
+        let dir = TempDir::new("secrets").unwrap();
+        let handler = GetSecretHandler::new(store(&dir));
+
+        let response = handler
+            .handle(empty_request(), params("password"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        assert_eq!("Secret password not found", error_message(response));
+    }
+
+    #[test]
+    fn delete_unknown_secret_is_not_found() {
+        let dir = TempDir::new("secrets").unwrap();
+        let handler = DeleteSecretHandler::new(store(&dir));
+
+        let response = handler
+            .handle(empty_request(), params("password"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+    }
+
+    #[test]
+    fn set_secret_with_invalid_key_fails() {
+        let dir = TempDir::new("secrets").unwrap();
+        let handler = SetSecretHandler::new(store(&dir));
+
+        let response = handler
+            .handle(set_request(b"hunter2"), params(".password"))
+            .wait()
+            .unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!(
+            "The request parameter `key` is malformed\n\tcaused by: Invalid secret name \".password\"",
+            error_message(response)
+        );
+    }
+
+    #[test]
+    fn set_secret_with_bad_body_fails() {
+        let dir = TempDir::new("secrets").unwrap();
+        let handler = SetSecretHandler::new(store(&dir));
+        let request = Request::put("http://localhost/modules/m1/secrets/password")
+            .body("invalid".into())
+            .unwrap();
+
+        let response = handler.handle(request, params("password")).wait().unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+}
@@ -4,10 +4,11 @@ use std::str;
 
 use failure::ResultExt;
 use futures::{Future, IntoFuture};
-use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
 use hyper::{Body, Request, Response, StatusCode};
 use serde_json;
 
+use edgelet_core::trust_bundle::etag;
 use edgelet_core::{Certificate, GetTrustBundle};
 use edgelet_http::route::{Handler, Parameters};
 use edgelet_http::Error as HttpError;
@@ -35,9 +36,15 @@ where
 {
     fn handle(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
         _params: Parameters,
     ) -> Box<Future<Item = Response<Body>, Error = HttpError> + Send> {
+        let if_none_match = req
+            .headers()
+            .get(IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+
         let response = self
             .hsm
             .get_trust_bundle()
@@ -49,6 +56,22 @@ where
                 let cert = cert.pem().context(ErrorKind::EncryptionOperation(
                     EncryptionOperation::GetTrustBundle,
                 ))?;
+
+                // Modules poll for changes to the trust bundle, so let them
+                // skip the body when they already have the current one.
+                let etag = etag(cert.as_ref());
+                if if_none_match.map_or(false, |value| matches(&value, &etag)) {
+                    let response = Response::builder()
+                        .status(StatusCode::NOT_MODIFIED)
+                        .header(ETAG, etag.as_str())
+                        .header(CONTENT_LENGTH, "0")
+                        .body(Body::default())
+                        .context(ErrorKind::EncryptionOperation(
+                            EncryptionOperation::GetTrustBundle,
+                        ))?;
+                    return Ok(response);
+                }
+
                 let cert = str::from_utf8(cert.as_ref())
                     .context(ErrorKind::EncryptionOperation(
                         EncryptionOperation::GetTrustBundle,
@@ -61,6 +84,7 @@ where
                     .status(StatusCode::OK)
                     .header(CONTENT_TYPE, "application/json")
                     .header(CONTENT_LENGTH, body.len().to_string().as_str())
+                    .header(ETAG, etag.as_str())
                     .body(body.into())
                     .context(ErrorKind::EncryptionOperation(
                         EncryptionOperation::GetTrustBundle,
@@ -74,6 +98,15 @@ where
     }
 }
 
+/// Returns whether the value of an `If-None-Match` header matches `etag`.
+/// Weak comparison is enough, since the body is the same either way.
+fn matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
 #[cfg(test)]
 mod tests {
     use futures::Future;
@@ -183,4 +216,47 @@ mod tests {
             .wait()
             .unwrap();
     }
+
+    #[test]
+    fn not_modified_when_etag_matches() {
+        let handler = TrustBundleHandler::new(
+            TestHsm::default().with_cert(TestCert::default().with_cert(b"boo".to_vec())),
+        );
+        let request = Request::get("http://localhost/trust-bundle")
+            .body("".into())
+            .unwrap();
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+        let etag = response.headers().get(ETAG).unwrap().clone();
+
+        let request = Request::get("http://localhost/trust-bundle")
+            .header(IF_NONE_MATCH, etag.clone())
+            .body("".into())
+            .unwrap();
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+        assert_eq!(&etag, response.headers().get(ETAG).unwrap());
+        response
+            .into_body()
+            .concat2()
+            .and_then(|b| {
+                assert!(b.is_empty());
+                Ok(())
+            })
+            .wait()
+            .unwrap();
+
+        let request = Request::get("http://localhost/trust-bundle")
+            .header(IF_NONE_MATCH, "\"stale\"")
+            .body("".into())
+            .unwrap();
+        let response = handler.handle(request, Parameters::new()).wait().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[test]
+    fn if_none_match_list() {
+        assert!(matches("\"a\", W/\"b\"", "\"b\""));
+        assert!(matches("*", "\"b\""));
+        assert!(!matches("\"a\"", "\"b\""));
+    }
 }
@@ -30,6 +30,7 @@ extern crate tokio_tls;
 extern crate workload;
 
 use std::env;
+use std::path::Path;
 use std::str;
 
 use chrono::{Duration, Utc};
@@ -50,8 +51,9 @@ use tokio::prelude::*;
 use edgelet_core::crypto::MemoryKeyStore;
 use edgelet_core::pid::Pid;
 use edgelet_core::{
-    Certificate, CertificateIssuer, CertificateProperties, CertificateType, CreateCertificate,
-    ModuleRuntimeState, ModuleStatus, WorkloadConfig, IOTEDGED_CA_ALIAS,
+    Certificate, CertificateIssuer, CertificateNotifier, CertificateProperties, CertificateType,
+    CreateCertificate, ModuleRuntimeState, ModuleStatus, SecretStore, TrustBundleStore,
+    WorkloadConfig, IOTEDGED_CA_ALIAS,
 };
 use edgelet_hsm::Crypto;
 use edgelet_http_workload::WorkloadService;
@@ -167,7 +169,7 @@ fn generate_server_cert(
     request(service, req)
 }
 
-fn create_workload_service(module_id: &str) -> (WorkloadService, Crypto) {
+fn create_workload_service(module_id: &str, home_dir: &Path) -> (WorkloadService, Crypto) {
     let key_store = MemoryKeyStore::new();
     let crypto = init_crypto();
     let runtime = TestRuntime::<Error>::new(Ok(TestModule::new(
@@ -182,11 +184,22 @@ fn create_workload_service(module_id: &str) -> (WorkloadService, Crypto) {
         device_id: "d1".to_string(),
         cert_max_duration: 10_000_000,
     };
+    let secrets = SecretStore::new(home_dir.join("secrets"), crypto.clone()).unwrap();
+    let trust_bundle =
+        TrustBundleStore::new(home_dir.join("trust_bundle"), crypto.clone()).unwrap();
 
     (
-        WorkloadService::new(&key_store, crypto.clone(), &runtime, config)
-            .wait()
-            .unwrap(),
+        WorkloadService::new(
+            &key_store,
+            crypto.clone(),
+            &runtime,
+            config,
+            &secrets,
+            &trust_bundle,
+            &CertificateNotifier::new(),
+        )
+        .wait()
+        .unwrap(),
         crypto,
     )
 }
@@ -265,7 +278,7 @@ fn init_test(module_id: &str, generation_id: &str) -> (WorkloadService, Identity
     env::set_var(HOMEDIR_KEY, &home_dir.path());
     println!("IOTEDGE_HOMEDIR set to {:#?}", home_dir.path());
 
-    let (mut service, crypto) = create_workload_service(module_id);
+    let (mut service, crypto) = create_workload_service(module_id, home_dir.path());
     let cert = generate_server_cert(module_id, generation_id, &mut service);
     assert_eq!(cert.private_key().type_().as_str(), "key");
 
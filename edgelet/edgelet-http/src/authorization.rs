@@ -6,7 +6,7 @@ use failure::ResultExt;
 use futures::{future, Future};
 use hyper::{Body, Request, Response};
 
-use edgelet_core::pid::Pid;
+use edgelet_core::pid::{Credentials, Pid};
 use edgelet_core::{Authorization as CoreAuth, ModuleRuntime, Policy};
 
 use error::{Error, ErrorKind};
@@ -45,18 +45,19 @@ where
         req: Request<Body>,
         params: Parameters,
     ) -> Box<Future<Item = Response<Body>, Error = Error> + Send> {
-        let (name, pid) = (
+        let (name, pid, credentials) = (
             params.name("name").map(|n| n.to_string()),
             req.extensions()
                 .get::<Pid>()
                 .cloned()
                 .unwrap_or_else(|| Pid::None),
+            req.extensions().get::<Credentials>().cloned(),
         );
         let inner = self.inner.clone();
 
         let response =
             self.auth
-                .authorize(name.clone(), pid)
+                .authorize(name.clone(), pid, credentials)
                 .then(|authorized| {
                     authorized
                         .context(ErrorKind::Authorization)
@@ -81,6 +82,7 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::io::Cursor;
     use std::time::Duration;
 
     use futures::future::FutureResult;
@@ -89,7 +91,8 @@ mod tests {
     use hyper::{Body, Request, Response, StatusCode};
 
     use edgelet_core::{
-        LogOptions, Module, ModuleRegistry, ModuleRuntimeState, ModuleSpec, SystemInfo,
+        ExecOptions, ExecStatus, LogOptions, Module, ModuleRegistry, ModuleRuntimeState,
+        ModuleSpec, ModuleVolume, SystemInfo,
     };
 
     use super::*;
@@ -166,6 +169,37 @@ mod tests {
         assert_eq!(404, response.status());
     }
 
+    #[test]
+    fn handler_calls_inner_handler_for_privileged_host_caller() {
+        let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
+        let mut request = Request::default();
+        request.extensions_mut().insert(Pid::Value(456));
+        request
+            .extensions_mut()
+            .insert(Credentials::new(1000, vec![1000, 998], true));
+
+        let auth = Authorization::new(TestHandler::new(), Policy::Host(Some(998)), runtime);
+        let response = auth
+            .handle(request, Parameters::with_captures(vec![]))
+            .wait()
+            .unwrap();
+        assert_eq!(200, response.status());
+    }
+
+    #[test]
+    fn handler_responds_with_not_found_when_host_credentials_are_omitted() {
+        let runtime = TestModuleList::new(vec![TestModule::new("abc", 123)]);
+        let mut request = Request::default();
+        request.extensions_mut().insert(Pid::Value(456));
+
+        let auth = Authorization::new(TestHandler::new(), Policy::Host(Some(998)), runtime);
+        let response = auth
+            .handle(request, Parameters::with_captures(vec![]))
+            .wait()
+            .unwrap();
+        assert_eq!(404, response.status());
+    }
+
     #[derive(Clone)]
     struct TestHandler {}
 
@@ -286,6 +320,8 @@ mod tests {
         type ModuleRegistry = Self;
         type Chunk = String;
         type Logs = Empty<Self::Chunk, Self::Error>;
+        type VolumeArchive = Empty<Self::Chunk, Self::Error>;
+        type ExecIo = Cursor<Vec<u8>>;
         type CreateFuture = FutureResult<(), Self::Error>;
         type InitFuture = FutureResult<(), Self::Error>;
         type ListFuture = FutureResult<Vec<Self::Module>, Self::Error>;
@@ -293,11 +329,19 @@ mod tests {
             Box<Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error> + Send>;
         type LogsFuture = FutureResult<Self::Logs, Self::Error>;
         type RemoveFuture = FutureResult<(), Self::Error>;
+        type RenameFuture = FutureResult<(), Self::Error>;
         type RestartFuture = FutureResult<(), Self::Error>;
         type StartFuture = FutureResult<(), Self::Error>;
         type StopFuture = FutureResult<(), Self::Error>;
         type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
         type RemoveAllFuture = FutureResult<(), Self::Error>;
+        type ListVolumesFuture = FutureResult<Vec<ModuleVolume>, Self::Error>;
+        type GetVolumeFuture = FutureResult<ModuleVolume, Self::Error>;
+        type BackupVolumeFuture = FutureResult<Self::VolumeArchive, Self::Error>;
+        type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+        type ExecFuture = FutureResult<String, Self::Error>;
+        type ExecStartFuture = FutureResult<Self::ExecIo, Self::Error>;
+        type ExecInspectFuture = FutureResult<ExecStatus, Self::Error>;
 
         fn init(&self) -> Self::InitFuture {
             notimpl_error!()
@@ -327,6 +371,10 @@ mod tests {
             notimpl_error!()
         }
 
+        fn rename(&self, _id: &str, _new_name: &str) -> Self::RenameFuture {
+            notimpl_error!()
+        }
+
         fn list(&self) -> Self::ListFuture {
             future::ok(self.modules.clone())
         }
@@ -348,7 +396,35 @@ mod tests {
             self
         }
 
-        fn remove_all(&self) -> Self::RemoveAllFuture {
+        fn remove_all(&self, _purge_volumes: bool) -> Self::RemoveAllFuture {
+            notimpl_error!()
+        }
+
+        fn list_volumes(&self, _module: &str) -> Self::ListVolumesFuture {
+            notimpl_error!()
+        }
+
+        fn get_volume(&self, _module: &str, _name: &str) -> Self::GetVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn backup_volume(&self, _module: &str, _name: &str) -> Self::BackupVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn remove_volume(&self, _module: &str, _name: &str) -> Self::RemoveVolumeFuture {
+            notimpl_error!()
+        }
+
+        fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+            notimpl_error!()
+        }
+
+        fn exec_start(&self, _exec_id: &str, _tty: bool) -> Self::ExecStartFuture {
+            notimpl_error!()
+        }
+
+        fn exec_inspect(&self, _exec_id: &str) -> Self::ExecInspectFuture {
             notimpl_error!()
         }
     }
@@ -2,14 +2,17 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration as StdDuration, Instant};
 
 use chrono::{DateTime, Duration, Utc};
 use failure::{Fail, ResultExt};
+use futures::future::{self, Either, Loop};
 use futures::{Future, IntoFuture, Stream};
-use hyper::{self, Body, Method, Request, Response};
+use hyper::{self, Body, Chunk, Method, Request, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
+use tokio::timer::{Delay, Timeout};
 use typed_headers::{http, mime, ContentLength, ContentType, HeaderMapExt};
 use url::form_urlencoded::Serializer as UrlSerializer;
 use url::Url;
@@ -17,6 +20,7 @@ use url::Url;
 use edgelet_utils::ensure_not_empty_with_context;
 
 use error::{Error, ErrorKind};
+use retry::{is_transient, retry_after, CircuitBreaker, RetryPolicy};
 
 pub trait TokenSource {
     type Error;
@@ -61,6 +65,9 @@ pub struct Client<C, T> {
     api_version: String,
     host_name: Url,
     user_agent: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    timeout: Option<StdDuration>,
+    circuit_breaker: Option<CircuitBreaker>,
 }
 
 impl<C, T> Client<C, T>
@@ -85,6 +92,9 @@ where
             api_version,
             host_name,
             user_agent: None,
+            retry_policy: None,
+            timeout: None,
+            circuit_breaker: None,
         };
 
         Ok(client)
@@ -100,6 +110,24 @@ where
         self
     }
 
+    /// Retries requests that fail with transient errors. Without a policy
+    /// every request is attempted once.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Fails each attempt at a request that takes longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: StdDuration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
     pub fn inner(&self) -> &C {
         &self.inner
     }
@@ -116,6 +144,18 @@ where
         &self.host_name
     }
 
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    pub fn timeout(&self) -> Option<StdDuration> {
+        self.timeout
+    }
+
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_ref()
+    }
+
     fn add_sas_token(&self, req: &mut Request<Body>, path: &str) -> Result<(), Error> {
         if let Some(ref source) = self.token_source {
             let token_duration = Duration::hours(1);
@@ -161,14 +201,66 @@ where
 
         // build the full url
         let path_query = format!("{}?{}", path, query);
+        let client = self.clone();
+        let path = path.to_string();
+
         self.host_name
             .join(&path_query)
             .with_context(|_| ErrorKind::UrlJoin(self.host_name.clone(), path_query))
             .context(ErrorKind::Http)
             .map_err(Error::from)
             .and_then(|url| {
+                // serialize the body up front so that it can be sent again on retries
+                let body = match body {
+                    Some(body) => Some(serde_json::to_string(&body).context(ErrorKind::Http)?),
+                    None => None,
+                };
+                Ok((url, body))
+            })
+            .into_future()
+            .and_then(move |(url, body)| {
+                future::loop_fn(0, move |retries| {
+                    let client_copy = client.clone();
+                    let method_copy = method.clone();
+                    client
+                        .send(
+                            &method,
+                            &url,
+                            body.as_ref().map(String::as_str),
+                            add_if_match,
+                            &path,
+                        )
+                        .then(move |result| client_copy.next_attempt(retries, &method_copy, result))
+                })
+            })
+            .and_then(|body| {
+                if body.len() == 0 {
+                    Ok(None)
+                } else {
+                    Ok(Some(
+                        serde_json::from_slice::<ResponseT>(&body).context(ErrorKind::Http)?,
+                    ))
+                }
+            })
+    }
+
+    /// Makes a single attempt at the request and returns the status, the
+    /// `Retry-After` header and the body of the response.
+    fn send(
+        &self,
+        method: &Method,
+        url: &Url,
+        body: Option<&str>,
+        add_if_match: bool,
+        path: &str,
+    ) -> impl Future<Item = (StatusCode, Option<StdDuration>, Chunk), Error = Error> {
+        let req = self
+            .circuit_breaker
+            .as_ref()
+            .map_or(Ok(()), CircuitBreaker::check)
+            .and_then(|_| {
                 let mut req = Request::builder();
-                req.method(method).uri(url.as_str());
+                req.method(method.clone()).uri(url.as_str());
 
                 // add user agent header
                 if let Some(ref user_agent) = self.user_agent {
@@ -182,13 +274,13 @@ where
 
                 // add request body if there is any
                 let mut req = if let Some(body) = body {
-                    let serialized = serde_json::to_string(&body).context(ErrorKind::Http)?;
-                    let serialized_len = serialized.len();
-                    let mut req = req.body(Body::from(serialized)).context(ErrorKind::Http)?;
+                    let mut req = req
+                        .body(Body::from(body.clone()))
+                        .context(ErrorKind::Http)?;
                     req.headers_mut()
                         .typed_insert(&ContentType(mime::APPLICATION_JSON));
                     req.headers_mut()
-                        .typed_insert(&ContentLength(serialized_len as u64));
+                        .typed_insert(&ContentLength(body.len() as u64));
                     req
                 } else {
                     req.body(Body::empty()).context(ErrorKind::Http)?
@@ -198,38 +290,90 @@ where
                 self.add_sas_token(&mut req, path)?;
 
                 Ok(req)
-            })
-            .map(|req| {
-                self.inner
+            });
+
+        let response = req.into_future().and_then({
+            let inner = self.inner.clone();
+            move |req| {
+                inner
                     .call(req)
                     .then(|resp| resp.context(ErrorKind::Http).map_err(Error::from))
                     .and_then(|resp| {
-                        let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                        let (
+                            http::response::Parts {
+                                status, headers, ..
+                            },
+                            body,
+                        ) = resp.into_parts();
+                        let retry_after = retry_after(&headers);
                         body.concat2().then(move |res| {
                             let body = res.context(ErrorKind::Http)?;
-                            Ok((status, body))
+                            Ok((status, retry_after, body))
                         })
                     })
-                    .and_then(|(status, body)| {
-                        if status.is_success() {
-                            Ok(body)
-                        } else {
-                            Err(Error::http_with_error_response(status, &*body))
-                        }
-                    })
-                    .and_then(|body| {
-                        if body.len() == 0 {
-                            Ok(None)
-                        } else {
-                            Ok(Some(
-                                serde_json::from_slice::<ResponseT>(&body)
-                                    .context(ErrorKind::Http)?,
-                            ))
-                        }
-                    })
-            })
-            .into_future()
-            .flatten()
+            }
+        });
+
+        match self.timeout {
+            Some(timeout) => Either::A(Timeout::new(response, timeout).map_err(|err| {
+                if err.is_elapsed() {
+                    Error::from(ErrorKind::Timeout)
+                } else {
+                    err.into_inner()
+                        .unwrap_or_else(|| Error::from(ErrorKind::Http))
+                }
+            })),
+            None => Either::B(response),
+        }
+    }
+
+    /// Decides what to do after an attempt: finish with the body of a
+    /// successful response, wait and try again, or give up.
+    fn next_attempt(
+        &self,
+        retries: u32,
+        method: &Method,
+        result: Result<(StatusCode, Option<StdDuration>, Chunk), Error>,
+    ) -> impl Future<Item = Loop<Chunk, u32>, Error = Error> {
+        let (err, retry_after) = match result {
+            Ok((status, _, body)) if status.is_success() => {
+                if let Some(ref breaker) = self.circuit_breaker {
+                    breaker.record_success();
+                }
+                return Either::A(future::ok(Loop::Break(body)));
+            }
+            Ok((status, retry_after, body)) => {
+                (Error::http_with_error_response(status, &*body), retry_after)
+            }
+            Err(err) => (err, None),
+        };
+
+        if let Some(ref breaker) = self.circuit_breaker {
+            if is_transient(&err) {
+                breaker.record_failure();
+            } else if *err.kind() != ErrorKind::CircuitBreakerOpen {
+                // the service answered, it just didn't like the request
+                breaker.record_success();
+            }
+        }
+
+        let backoff = self
+            .retry_policy
+            .as_ref()
+            .and_then(|policy| policy.backoff(retries, method, &err, retry_after));
+        match backoff {
+            Some(backoff) => {
+                debug!(
+                    "Retrying request to {} in {:?} after error: {}",
+                    self.host_name, backoff, err
+                );
+                Either::B(
+                    Delay::new(Instant::now() + backoff)
+                        .then(move |_| Ok(Loop::Continue(retries + 1))),
+                )
+            }
+            None => Either::A(future::err(err)),
+        }
     }
 }
 
@@ -244,6 +388,9 @@ where
             api_version: self.api_version.clone(),
             host_name: self.host_name.clone(),
             user_agent: self.user_agent.clone(),
+            retry_policy: self.retry_policy.clone(),
+            timeout: self.timeout,
+            circuit_breaker: self.circuit_breaker.clone(),
         }
     }
 }
@@ -253,6 +400,7 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
     use std::str;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use chrono::{DateTime, Utc};
     use futures::future;
@@ -580,6 +728,165 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn request_retries_transient_errors() {
+        let api_version = "2018-04-10".to_string();
+        let host_name = Url::parse("http://localhost").unwrap();
+        let token_source: Option<StaticTokenSource> = None;
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        let attempts_copy = attempts.clone();
+        let handler = move |_req: Request<Body>| {
+            if attempts_copy.fetch_add(1, Ordering::SeqCst) < 2 {
+                Ok(Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .header(hyper::header::RETRY_AFTER, "0")
+                    .body(Body::empty())
+                    .unwrap())
+            } else {
+                Ok(Response::new(r#""response""#.into()))
+            }
+        };
+        let client = Client::new(handler, token_source, api_version, host_name)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new(2));
+
+        let task = client.request::<String, String>(Method::GET, "/boo", None, None, false);
+
+        let result: String = tokio::runtime::current_thread::Runtime::new()
+            .unwrap()
+            .block_on(task)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result, "response");
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn request_does_not_retry_client_errors() {
+        let api_version = "2018-04-10".to_string();
+        let host_name = Url::parse("http://localhost").unwrap();
+        let token_source: Option<StaticTokenSource> = None;
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        let attempts_copy = attempts.clone();
+        let handler = move |_req: Request<Body>| {
+            attempts_copy.fetch_add(1, Ordering::SeqCst);
+            Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::empty())
+                .unwrap())
+        };
+        let client = Client::new(handler, token_source, api_version, host_name)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new(2));
+
+        let task = client.request::<String, String>(Method::GET, "/boo", None, None, false);
+
+        let err = tokio::runtime::current_thread::Runtime::new()
+            .unwrap()
+            .block_on(task)
+            .unwrap_err();
+        assert_eq!(
+            &ErrorKind::HttpWithErrorResponse(StatusCode::NOT_FOUND, String::new()),
+            err.kind()
+        );
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn request_does_not_retry_changes_the_service_may_have_made() {
+        let api_version = "2018-04-10".to_string();
+        let host_name = Url::parse("http://localhost").unwrap();
+        let token_source: Option<StaticTokenSource> = None;
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        let attempts_copy = attempts.clone();
+        let handler = move |_req: Request<Body>| {
+            attempts_copy.fetch_add(1, Ordering::SeqCst);
+            Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::empty())
+                .unwrap())
+        };
+        let client = Client::new(handler, token_source, api_version, host_name)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::new(2));
+
+        let task = client.request::<String, String>(
+            Method::PUT,
+            "/boo",
+            None,
+            Some("Here be dragons".to_string()),
+            false,
+        );
+
+        let err = tokio::runtime::current_thread::Runtime::new()
+            .unwrap()
+            .block_on(task)
+            .unwrap_err();
+        assert_eq!(
+            &ErrorKind::HttpWithErrorResponse(StatusCode::INTERNAL_SERVER_ERROR, String::new()),
+            err.kind()
+        );
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn request_times_out() {
+        let api_version = "2018-04-10".to_string();
+        let host_name = Url::parse("http://localhost").unwrap();
+        let token_source: Option<StaticTokenSource> = None;
+
+        let handler = |_req: Request<Body>| future::empty::<Response<Body>, hyper::Error>();
+        let client = Client::new(handler, token_source, api_version, host_name)
+            .unwrap()
+            .with_timeout(StdDuration::from_millis(10));
+
+        let task = client.request::<String, String>(Method::GET, "/boo", None, None, false);
+
+        let err = tokio::runtime::current_thread::Runtime::new()
+            .unwrap()
+            .block_on(task)
+            .unwrap_err();
+        assert_eq!(&ErrorKind::Timeout, err.kind());
+    }
+
+    #[test]
+    fn open_circuit_breaker_fails_requests_fast() {
+        let api_version = "2018-04-10".to_string();
+        let host_name = Url::parse("http://localhost").unwrap();
+        let token_source: Option<StaticTokenSource> = None;
+        let attempts = Arc::new(AtomicUsize::new(0));
+
+        let attempts_copy = attempts.clone();
+        let handler = move |_req: Request<Body>| {
+            attempts_copy.fetch_add(1, Ordering::SeqCst);
+            Ok(Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .body(Body::empty())
+                .unwrap())
+        };
+        let client = Client::new(handler, token_source, api_version, host_name)
+            .unwrap()
+            .with_circuit_breaker(CircuitBreaker::new(1, StdDuration::from_secs(60)));
+
+        let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+        let first = runtime
+            .block_on(client.request::<String, String>(Method::GET, "/boo", None, None, false))
+            .unwrap_err();
+        let second = runtime
+            .block_on(client.request::<String, String>(Method::GET, "/boo", None, None, false))
+            .unwrap_err();
+
+        assert_eq!(
+            &ErrorKind::HttpWithErrorResponse(StatusCode::SERVICE_UNAVAILABLE, String::new()),
+            first.kind()
+        );
+        assert_eq!(&ErrorKind::CircuitBreakerOpen, second.kind());
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+    }
+
     #[test]
     fn request_returns_response() {
         let api_version = "2018-04-10".to_string();
@@ -25,6 +25,9 @@ pub enum ErrorKind {
     #[fail(display = "An error occurred while binding a listener to {}", _0)]
     BindListener(BindListenerType),
 
+    #[fail(display = "Request was not sent because the service has been failing")]
+    CircuitBreakerOpen,
+
     #[fail(display = "Could not perform HTTP request")]
     Http,
 
@@ -65,6 +68,9 @@ pub enum ErrorKind {
     #[fail(display = "An error occurred in the service")]
     ServiceError,
 
+    #[fail(display = "HTTP request timed out")]
+    Timeout,
+
     #[fail(display = "Token source error")]
     TokenSource,
 
@@ -36,6 +36,7 @@ extern crate mio_uds_windows;
 #[cfg(unix)]
 extern crate nix;
 extern crate percent_encoding;
+extern crate rand;
 extern crate regex;
 #[cfg(unix)]
 #[macro_use]
@@ -91,15 +92,17 @@ pub mod client;
 pub mod error;
 pub mod logging;
 mod pid;
+pub mod retry;
 pub mod route;
 mod unix;
 mod util;
 mod version;
 
 pub use self::error::{BindListenerType, Error, ErrorKind, InvalidUrlReason};
-pub use self::util::proxy::MaybeProxyClient;
+pub use self::pid::group_id;
+pub use self::util::proxy::{MaybeProxyClient, NoProxy, ProxyConfig};
 pub use self::util::UrlConnector;
-pub use self::version::{ApiVersionService, API_VERSION};
+pub use self::version::{ApiVersion, ApiVersionRange, ApiVersionService, API_VERSION};
 
 use self::pid::PidService;
 use self::util::incoming::Incoming;
@@ -167,6 +170,7 @@ where
 
             debug!("accepted new connection ({})", addr);
             let pid = socket.pid()?;
+            let credentials = socket.credentials()?;
             let fut = new_service
                 .new_service()
                 .then(move |srv| match srv {
@@ -178,9 +182,12 @@ where
                     }
                 })
                 .and_then(move |(srv, addr)| {
-                    let service = PidService::new(pid, srv);
+                    let service = PidService::new(pid, credentials, srv);
+                    // upgrades let handlers take over the connection, e.g. to
+                    // attach to a command running in a module
                     protocol
                         .serve_connection(socket, service)
+                        .with_upgrades()
                         .then(move |result| match result {
                             Ok(_) => Ok(()),
                             Err(err) => {
@@ -1,12 +1,112 @@
 // Copyright (c) Microsoft. All rights reserved.
 #![allow(deprecated)]
 
+use std::cell::RefCell;
+
 use chrono::prelude::*;
 use edgelet_core::pid::Pid;
 use futures::prelude::*;
-use hyper::header::{CONTENT_LENGTH, USER_AGENT};
+use hyper::header::{HeaderValue, CONTENT_LENGTH, USER_AGENT};
 use hyper::service::{NewService, Service};
 use hyper::Request;
+use rand;
+
+/// Header that carries the correlation id of a request. A valid id sent by
+/// the client is reused, otherwise one is generated. Either way it is returned
+/// on the response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+thread_local! {
+    static CURRENT_REQUEST: RefCell<Option<RequestContext>> = RefCell::new(None);
+}
+
+/// Details of the request being handled on the current thread. Loggers can
+/// attach these to the records emitted while it is handled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestContext {
+    request_id: String,
+    service: String,
+    route: String,
+    client_pid: Option<String>,
+}
+
+impl RequestContext {
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+
+    pub fn client_pid(&self) -> Option<&str> {
+        self.client_pid.as_ref().map(AsRef::as_ref)
+    }
+
+    fn enter<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        struct Reset(Option<RequestContext>);
+
+        impl Drop for Reset {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                CURRENT_REQUEST.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+
+        let previous = CURRENT_REQUEST.with(|current| current.replace(Some(self.clone())));
+        let _reset = Reset(previous);
+        f()
+    }
+}
+
+/// Calls `f` with the context of the request being handled on the current
+/// thread, if any.
+pub fn with_request_context<F, R>(f: F) -> R
+where
+    F: FnOnce(Option<&RequestContext>) -> R,
+{
+    CURRENT_REQUEST.with(|current| f(current.borrow().as_ref()))
+}
+
+/// Polls `inner` with its request context set, so that everything logged while
+/// handling the request can be correlated.
+struct InRequestContext<F> {
+    context: RequestContext,
+    inner: F,
+}
+
+impl<F> Future for InRequestContext<F>
+where
+    F: Future,
+{
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let inner = &mut self.inner;
+        self.context.enter(|| inner.poll())
+    }
+}
+
+fn request_id<B>(req: &Request<B>) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map_or_else(
+            || format!("{:016x}", rand::random::<u64>()),
+            ToString::to_string,
+        )
+}
 
 #[derive(Clone)]
 pub struct LoggingService<T> {
@@ -48,14 +148,18 @@ where
             .and_then(|ua| ua.to_str().ok())
             .unwrap_or_else(|| "-")
             .to_string();
-        let pid = req
-            .extensions()
-            .get::<Pid>()
-            .map_or_else(|| "-".to_string(), |p| p.to_string());
+        let pid = req.extensions().get::<Pid>().map(ToString::to_string);
+        let context = RequestContext {
+            request_id: request_id(&req),
+            service: label.clone(),
+            route: format!("{} {}", req.method(), req.uri().path()),
+            client_pid: pid.clone(),
+        };
+        let pid = pid.unwrap_or_else(|| "-".to_string());
+        let request_id = context.request_id.clone();
 
-        let inner = self.inner.call(req);
-
-        Box::new(inner.map(move |response| {
+        let inner = context.enter(|| self.inner.call(req));
+        let response = inner.map(move |mut response| {
             let body_length = response
                 .headers()
                 .get(CONTENT_LENGTH)
@@ -63,7 +167,7 @@ where
                 .unwrap_or_else(|| "-".to_string());
 
             info!(
-                "[{}] - - - [{}] \"{}\" {} {} \"-\" \"{}\" pid({})",
+                "[{}] - - - [{}] \"{}\" {} {} \"-\" \"{}\" pid({}) request_id({})",
                 label,
                 Utc::now(),
                 request,
@@ -71,10 +175,19 @@ where
                 body_length,
                 user_agent,
                 pid,
+                request_id,
             );
 
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                response.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
             response
-        }))
+        });
+
+        Box::new(InRequestContext {
+            context,
+            inner: response,
+        })
     }
 }
 
@@ -101,3 +214,100 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures::future;
+    use hyper::{Body, Error as HyperError, Response};
+
+    use super::*;
+
+    struct TestService {
+        contexts: Arc<Mutex<Vec<Option<RequestContext>>>>,
+    }
+
+    impl Service for TestService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = HyperError;
+        type Future = Box<Future<Item = Response<Body>, Error = HyperError> + Send>;
+
+        fn call(&mut self, _req: Request<Body>) -> Self::Future {
+            let contexts = self.contexts.clone();
+            contexts
+                .lock()
+                .unwrap()
+                .push(with_request_context(|context| context.cloned()));
+            Box::new(future::lazy(move || {
+                contexts
+                    .lock()
+                    .unwrap()
+                    .push(with_request_context(|context| context.cloned()));
+                Ok(Response::new(Body::empty()))
+            }))
+        }
+    }
+
+    fn call(req: Request<Body>) -> (Response<Body>, Vec<Option<RequestContext>>) {
+        let contexts = Arc::new(Mutex::new(vec![]));
+        let mut service = LoggingService::new(
+            "test".to_string(),
+            TestService {
+                contexts: contexts.clone(),
+            },
+        );
+
+        let response = service.call(req).wait().unwrap();
+        let contexts = contexts.lock().unwrap().clone();
+        (response, contexts)
+    }
+
+    #[test]
+    fn request_id_is_generated_and_returned() {
+        let req = Request::get("http://localhost/modules?api-version=2018-06-28")
+            .body(Body::empty())
+            .unwrap();
+
+        let (response, contexts) = call(req);
+
+        let request_id = response
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(16, request_id.len());
+        assert_eq!(2, contexts.len());
+        for context in contexts {
+            let context = context.unwrap();
+            assert_eq!(request_id, context.request_id());
+            assert_eq!("test", context.service());
+            assert_eq!("GET /modules", context.route());
+            assert_eq!(None, context.client_pid());
+        }
+        assert_eq!(None, with_request_context(|context| context.cloned()));
+    }
+
+    #[test]
+    fn request_id_from_client_is_reused() {
+        let mut req = Request::get("http://localhost/modules")
+            .body(Body::empty())
+            .unwrap();
+        req.headers_mut()
+            .insert(REQUEST_ID_HEADER, HeaderValue::from_static("abc-123"));
+        req.extensions_mut().insert(Pid::Value(42));
+
+        let (response, contexts) = call(req);
+
+        assert_eq!(
+            &"abc-123",
+            response.headers().get(REQUEST_ID_HEADER).unwrap()
+        );
+        let context = contexts[0].clone().unwrap();
+        assert_eq!("abc-123", context.request_id());
+        assert_eq!(Some("42"), context.client_pid());
+    }
+}
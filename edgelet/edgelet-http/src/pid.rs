@@ -2,7 +2,7 @@
 
 use std::io;
 
-use edgelet_core::pid::Pid;
+use edgelet_core::pid::{Credentials, Pid};
 use futures::prelude::*;
 use hyper::service::Service;
 use hyper::{Body, Error as HyperError, Request};
@@ -14,12 +14,17 @@ use tokio_uds_windows::UnixStream;
 #[derive(Clone)]
 pub struct PidService<T> {
     pid: Pid,
+    credentials: Option<Credentials>,
     inner: T,
 }
 
 impl<T> PidService<T> {
-    pub fn new(pid: Pid, inner: T) -> Self {
-        PidService { pid, inner }
+    pub fn new(pid: Pid, credentials: Option<Credentials>, inner: T) -> Self {
+        PidService {
+            pid,
+            credentials,
+            inner,
+        }
     }
 }
 
@@ -37,26 +42,41 @@ where
     fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
         let mut req = req;
         req.extensions_mut().insert(self.pid);
+        if let Some(ref credentials) = self.credentials {
+            req.extensions_mut().insert(credentials.clone());
+        }
         self.inner.call(req)
     }
 }
 
 pub trait UnixStreamExt {
     fn pid(&self) -> io::Result<Pid>;
+    fn credentials(&self) -> io::Result<Option<Credentials>>;
 }
 
 impl UnixStreamExt for UnixStream {
     fn pid(&self) -> io::Result<Pid> {
         get_pid(self)
     }
+
+    fn credentials(&self) -> io::Result<Option<Credentials>> {
+        get_credentials(self)
+    }
+}
+
+/// Resolves a group name, or a numeric group id, to the group id.
+pub fn group_id(group: &str) -> io::Result<u32> {
+    group.parse().or_else(|_| get_group_id(group))
 }
 
 #[cfg(unix)]
-use self::impl_unix::get_pid;
+use self::impl_unix::{get_credentials, get_group_id, get_pid};
 
 #[cfg(unix)]
 mod impl_unix {
-    use libc::{c_void, getsockopt, ucred, SOL_SOCKET, SO_PEERCRED};
+    use libc::{c_char, c_void, getgrnam_r, getsockopt, group, ucred, SOL_SOCKET, SO_PEERCRED};
+    use std::ffi::CString;
+    use std::fs;
     use std::os::unix::io::AsRawFd;
     use std::{io, mem};
     #[cfg(unix)]
@@ -67,6 +87,69 @@ mod impl_unix {
     use super::*;
 
     pub fn get_pid(sock: &UnixStream) -> io::Result<Pid> {
+        get_ucred(sock).map(|ucred| Pid::Value(ucred.pid))
+    }
+
+    pub fn get_credentials(sock: &UnixStream) -> io::Result<Option<Credentials>> {
+        let ucred = get_ucred(sock)?;
+
+        // SO_PEERCRED only reports the primary group, so the supplementary
+        // groups are read from procfs. If the process is already gone its
+        // credentials are unknown.
+        let status = match fs::read_to_string(format!("/proc/{}/status", ucred.pid)) {
+            Ok(status) => status,
+            Err(_) => return Ok(None),
+        };
+        let mut gids = vec![ucred.gid];
+        gids.extend(
+            status
+                .lines()
+                .find(|line| line.starts_with("Groups:"))
+                .map_or("", |line| &line["Groups:".len()..])
+                .split_whitespace()
+                .filter_map(|gid| gid.parse::<u32>().ok()),
+        );
+
+        // A process in a container has its own pid namespace.
+        let host = match (
+            fs::read_link(format!("/proc/{}/ns/pid", ucred.pid)),
+            fs::read_link("/proc/self/ns/pid"),
+        ) {
+            (Ok(peer), Ok(own)) => peer == own,
+            _ => false,
+        };
+
+        Ok(Some(Credentials::new(ucred.uid, gids, host)))
+    }
+
+    pub fn get_group_id(name: &str) -> io::Result<u32> {
+        let name =
+            CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let mut group: group = unsafe { mem::zeroed() };
+        let mut result = ::std::ptr::null_mut();
+        let mut buf = vec![0 as c_char; 16 * 1024];
+        let ret = unsafe {
+            getgrnam_r(
+                name.as_ptr(),
+                &mut group,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+        if ret != 0 {
+            Err(io::Error::from_raw_os_error(ret))
+        } else if result.is_null() {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("group {:?} does not exist", name),
+            ))
+        } else {
+            Ok(group.gr_gid)
+        }
+    }
+
+    fn get_ucred(sock: &UnixStream) -> io::Result<ucred> {
         let raw_fd = sock.as_raw_fd();
         let mut ucred = ucred {
             pid: 0,
@@ -92,7 +175,7 @@ mod impl_unix {
             )
         };
         if ret == 0 && ucred_size as usize == mem::size_of::<ucred>() {
-            Ok(Pid::Value(ucred.pid))
+            Ok(ucred)
         } else {
             Err(io::Error::last_os_error())
         }
@@ -100,7 +183,7 @@ mod impl_unix {
 }
 
 #[cfg(windows)]
-use self::impl_windows::get_pid;
+use self::impl_windows::{get_credentials, get_group_id, get_pid};
 
 #[cfg(windows)]
 mod impl_windows {
@@ -132,4 +215,37 @@ mod impl_windows {
             Ok(Pid::Value(pid as _))
         }
     }
+
+    /// The peer's account can't be determined from an AF_UNIX socket on
+    /// Windows, so such callers are never treated as privileged.
+    pub fn get_credentials(_sock: &UnixStream) -> io::Result<Option<Credentials>> {
+        Ok(None)
+    }
+
+    pub fn get_group_id(name: &str) -> io::Result<u32> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("group {:?} can't be resolved on Windows", name),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_id_accepts_numeric_id() {
+        assert_eq!(998, group_id("998").unwrap());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn group_id_resolves_name() {
+        assert_eq!(0, group_id("root").unwrap());
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            group_id("no-such-group").unwrap_err().kind()
+        );
+    }
 }
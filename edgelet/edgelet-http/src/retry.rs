@@ -0,0 +1,375 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cmp;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use hyper::{HeaderMap, Method, StatusCode};
+use typed_headers::http;
+
+use error::{Error, ErrorKind};
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF_SECS: u64 = 1;
+const DEFAULT_MAX_BACKOFF_SECS: u64 = 60;
+
+/// Decides whether and when a failed request is sent again.
+///
+/// Requests that only read, like `GET`, are retried when they couldn't be sent,
+/// timed out or were answered with a status that says the service is
+/// temporarily unable to handle them, such as 429 or 503. Requests that change
+/// something may already have been carried out when they fail that way, so
+/// they are only retried when the service answered that it didn't handle them
+/// (429 or 503). The wait between attempts starts at `initial_backoff` and
+/// doubles with every retry up to `max_backoff`, unless the response has a
+/// `Retry-After` header, in which case that is honored up to `max_backoff`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_secs(DEFAULT_INITIAL_BACKOFF_SECS),
+            max_backoff: Duration::from_secs(DEFAULT_MAX_BACKOFF_SECS),
+        }
+    }
+
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub fn initial_backoff(&self) -> Duration {
+        self.initial_backoff
+    }
+
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    /// Returns how long to wait before retrying a `method` request that
+    /// failed with `err` after it had already been retried `retries` times, or
+    /// `None` if it shouldn't be retried.
+    pub fn backoff(
+        &self,
+        retries: u32,
+        method: &Method,
+        err: &Error,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        let retriable = if is_safe(method) {
+            is_transient(err)
+        } else {
+            is_refused(err)
+        };
+        if retries >= self.max_retries || !retriable {
+            return None;
+        }
+
+        let backoff = match retry_after {
+            Some(retry_after) => retry_after,
+            None => {
+                let factor = 1_u32.checked_shl(retries).unwrap_or(u32::max_value());
+                self.initial_backoff
+                    .checked_mul(factor)
+                    .unwrap_or(self.max_backoff)
+            }
+        };
+        Some(cmp::min(backoff, self.max_backoff))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(DEFAULT_MAX_RETRIES)
+    }
+}
+
+/// Stops sending requests to a service that keeps failing.
+///
+/// After `failure_threshold` consecutive transient failures the breaker opens
+/// and requests fail straight away with `ErrorKind::CircuitBreakerOpen`. Once
+/// `reset_timeout` has passed, requests are let through again. The first one
+/// that succeeds closes the breaker and the first one that fails opens it
+/// again. Clones share their state.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    reset_timeout: Duration,
+    state: Arc<Mutex<BreakerState>>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold,
+            reset_timeout,
+            state: Arc::new(Mutex::new(BreakerState::default())),
+        }
+    }
+
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+    }
+
+    pub fn reset_timeout(&self) -> Duration {
+        self.reset_timeout
+    }
+
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .opened_at
+            .map_or(false, |opened_at| opened_at.elapsed() < self.reset_timeout)
+    }
+
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.is_open() {
+            Err(Error::from(ErrorKind::CircuitBreakerOpen))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.failures = 0;
+        state.opened_at = None;
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.failures = state.failures.saturating_add(1);
+        if state.failures == self.failure_threshold {
+            warn!(
+                "Failing requests fast for {:?} after {} consecutive failures",
+                self.reset_timeout, state.failures
+            );
+        }
+        if state.failures >= self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Whether `err` is likely to go away if the request is sent again.
+pub fn is_transient(err: &Error) -> bool {
+    match err.kind() {
+        ErrorKind::Http | ErrorKind::Timeout => true,
+        ErrorKind::HttpWithErrorResponse(status, _) => match *status {
+            StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether requests with `method` only read, so that sending them again has no
+/// further effect.
+fn is_safe(method: &Method) -> bool {
+    *method == Method::GET
+        || *method == Method::HEAD
+        || *method == Method::OPTIONS
+        || *method == Method::TRACE
+}
+
+/// Whether the service answered that it didn't handle the request, so that
+/// sending it again can't carry it out twice.
+fn is_refused(err: &Error) -> bool {
+    match err.kind() {
+        ErrorKind::HttpWithErrorResponse(status, _) => match *status {
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Reads the `Retry-After` header, which holds either a number of seconds or
+/// an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    DateTime::parse_from_rfc2822(value).ok().map(|date| {
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_else(|_| Duration::from_secs(0))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hyper::header::HeaderValue;
+
+    fn unavailable() -> Error {
+        Error::http_with_error_response(StatusCode::SERVICE_UNAVAILABLE, b"")
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RetryPolicy::new(5)
+            .with_initial_backoff(Duration::from_secs(2))
+            .with_max_backoff(Duration::from_secs(10));
+
+        let backoffs: Vec<_> = (0..6)
+            .map(|retries| policy.backoff(retries, &Method::GET, &unavailable(), None))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(8)),
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(10)),
+                None,
+            ],
+            backoffs
+        );
+    }
+
+    #[test]
+    fn backoff_honors_retry_after() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            policy.backoff(
+                0,
+                &Method::GET,
+                &unavailable(),
+                Some(Duration::from_secs(30))
+            )
+        );
+    }
+
+    #[test]
+    fn retry_after_is_capped_at_max_backoff() {
+        let policy = RetryPolicy::default().with_max_backoff(Duration::from_secs(10));
+
+        assert_eq!(
+            Some(Duration::from_secs(10)),
+            policy.backoff(
+                0,
+                &Method::GET,
+                &unavailable(),
+                Some(Duration::from_secs(3600))
+            )
+        );
+    }
+
+    #[test]
+    fn changes_are_only_retried_when_refused() {
+        let policy = RetryPolicy::default();
+        let timeout = Error::from(ErrorKind::Timeout);
+        let server_error = Error::http_with_error_response(StatusCode::INTERNAL_SERVER_ERROR, b"");
+
+        for method in &[Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+            assert_eq!(None, policy.backoff(0, method, &timeout, None));
+            assert_eq!(None, policy.backoff(0, method, &server_error, None));
+            assert!(policy.backoff(0, method, &unavailable(), None).is_some());
+        }
+        assert!(policy.backoff(0, &Method::GET, &timeout, None).is_some());
+        assert!(policy
+            .backoff(0, &Method::HEAD, &server_error, None)
+            .is_some());
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let policy = RetryPolicy::default();
+        let err = Error::http_with_error_response(StatusCode::NOT_FOUND, b"");
+
+        assert_eq!(None, policy.backoff(0, &Method::GET, &err, None));
+        assert_eq!(
+            None,
+            policy.backoff(
+                0,
+                &Method::GET,
+                &Error::from(ErrorKind::CircuitBreakerOpen),
+                None
+            )
+        );
+        assert!(policy
+            .backoff(0, &Method::GET, &Error::from(ErrorKind::Timeout), None)
+            .is_some());
+    }
+
+    #[test]
+    fn retry_after_parses_seconds_and_dates() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(None, retry_after(&headers));
+
+        headers.insert(http::header::RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(Some(Duration::from_secs(120)), retry_after(&headers));
+
+        headers.insert(
+            http::header::RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(Some(Duration::from_secs(0)), retry_after(&headers));
+
+        headers.insert(http::header::RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(None, retry_after(&headers));
+    }
+
+    #[test]
+    fn circuit_breaker_opens_after_threshold_and_closes_on_success() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+        breaker.record_failure();
+        assert_eq!(
+            &ErrorKind::CircuitBreakerOpen,
+            breaker.check().unwrap_err().kind()
+        );
+
+        breaker.record_success();
+        assert!(breaker.check().is_ok());
+    }
+
+    #[test]
+    fn circuit_breaker_lets_requests_through_after_reset_timeout() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(0));
+
+        breaker.record_failure();
+
+        assert!(!breaker.is_open());
+    }
+}
@@ -4,8 +4,8 @@
 ///
 /// ```ignore
 /// let router = router!(
-///     get "/" => index_handler,
-///     get "/hello" => hello_handler,
+///     get ApiVersion::V2018_06_28.. => "/" => index_handler,
+///     get ApiVersion::V2019_01_30.. => "/hello" => hello_handler,
 /// );
 /// ```
 ///
@@ -14,21 +14,31 @@
 /// ```ignore
 /// let router = Router::from(
 ///     RegexRoutesBuilder::default()
-///         .get("/", index_handler)
-///         .get("/hello", hello_handler)
+///         .get(ApiVersion::V2018_06_28.., "/", index_handler)
+///         .get(ApiVersion::V2019_01_30.., "/hello", hello_handler)
 ///         .finish()
 /// );
 /// ```
 ///
+/// The range of API versions a route is available in can be left out, in
+/// which case all routes are available in every version.
+///
 /// The method names must be lowercase and must be one of:
 ///
-/// `get`, `post`, `put` and `delete`
+/// `get`, `post`, `put`, `delete` and `patch`
 #[macro_export]
 macro_rules! router {
+    ($($method:ident $versions:expr => $glob:expr => $handler:expr),+ $(,)*) => ({
+        Router::from(
+            $crate::route::RegexRoutesBuilder::default()
+            $(.$method($versions, $glob, $handler))*
+            .finish()
+        )
+    });
     ($($method:ident $glob:expr => $handler:expr),+ $(,)*) => ({
         Router::from(
             $crate::route::RegexRoutesBuilder::default()
-            $(.$method($glob, $handler))*
+            $(.$method(.., $glob, $handler))*
             .finish()
         )
     });
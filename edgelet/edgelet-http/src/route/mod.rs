@@ -12,6 +12,7 @@ use hyper::service::{NewService, Service};
 use hyper::{Body, Method, Request, Response, StatusCode};
 
 use error::Error;
+use version::{ApiVersion, ApiVersionRange};
 
 pub mod macros;
 mod regex;
@@ -46,53 +47,70 @@ pub type HandlerParamsPair<'a, P> = (&'a Handler<P>, P);
 pub trait Recognizer {
     type Parameters: 'static;
 
+    /// Finds the handler for `path`. When the request negotiated an API
+    /// version, only routes available in that version are considered.
     fn recognize(
         &self,
         method: &Method,
         path: &str,
+        version: Option<ApiVersion>,
     ) -> Result<HandlerParamsPair<Self::Parameters>, StatusCode>;
 }
 
 pub trait Builder: Sized {
     type Recognizer: Recognizer;
 
-    fn route<S, H>(self, method: Method, pattern: S, handler: H) -> Self
+    fn route<V, S, H>(self, method: Method, versions: V, pattern: S, handler: H) -> Self
     where
+        V: Into<ApiVersionRange>,
         S: AsRef<str>,
         H: Handler<<Self::Recognizer as Recognizer>::Parameters> + Sync;
 
     fn finish(self) -> Self::Recognizer;
 
-    fn get<S, H>(self, pattern: S, handler: H) -> Self
+    fn get<V, S, H>(self, versions: V, pattern: S, handler: H) -> Self
     where
+        V: Into<ApiVersionRange>,
         S: AsRef<str>,
         H: Handler<<Self::Recognizer as Recognizer>::Parameters> + Sync,
     {
-        self.route(Method::GET, pattern, handler)
+        self.route(Method::GET, versions, pattern, handler)
     }
 
-    fn post<S, H>(self, pattern: S, handler: H) -> Self
+    fn post<V, S, H>(self, versions: V, pattern: S, handler: H) -> Self
     where
+        V: Into<ApiVersionRange>,
         S: AsRef<str>,
         H: Handler<<Self::Recognizer as Recognizer>::Parameters> + Sync,
     {
-        self.route(Method::POST, pattern, handler)
+        self.route(Method::POST, versions, pattern, handler)
     }
 
-    fn put<S, H>(self, pattern: S, handler: H) -> Self
+    fn put<V, S, H>(self, versions: V, pattern: S, handler: H) -> Self
     where
+        V: Into<ApiVersionRange>,
         S: AsRef<str>,
         H: Handler<<Self::Recognizer as Recognizer>::Parameters> + Sync,
     {
-        self.route(Method::PUT, pattern, handler)
+        self.route(Method::PUT, versions, pattern, handler)
     }
 
-    fn delete<S, H>(self, pattern: S, handler: H) -> Self
+    fn delete<V, S, H>(self, versions: V, pattern: S, handler: H) -> Self
     where
+        V: Into<ApiVersionRange>,
         S: AsRef<str>,
         H: Handler<<Self::Recognizer as Recognizer>::Parameters> + Sync,
     {
-        self.route(Method::DELETE, pattern, handler)
+        self.route(Method::DELETE, versions, pattern, handler)
+    }
+
+    fn patch<V, S, H>(self, versions: V, pattern: S, handler: H) -> Self
+    where
+        V: Into<ApiVersionRange>,
+        S: AsRef<str>,
+        H: Handler<<Self::Recognizer as Recognizer>::Parameters> + Sync,
+    {
+        self.route(Method::PATCH, versions, pattern, handler)
     }
 }
 
@@ -153,7 +171,8 @@ where
     fn call(&mut self, req: Request<Body>) -> Self::Future {
         let method = req.method().clone();
         let path = req.uri().path().to_owned();
-        match self.inner.recognize(&method, &path) {
+        let version = req.extensions().get::<ApiVersion>().cloned();
+        match self.inner.recognize(&method, &path, version) {
             Ok((handler, params)) => {
                 Box::new(handler.handle(req, params).map_err(|err| err.compat()))
             }
@@ -9,6 +9,7 @@ use percent_encoding::percent_decode;
 use regex::Regex;
 
 use super::{Builder, Handler, HandlerParamsPair, Recognizer};
+use version::{ApiVersion, ApiVersionRange};
 
 pub trait IntoCaptures {
     fn into_captures(self) -> Vec<(Option<String>, String)>;
@@ -65,6 +66,7 @@ impl Default for Parameters {
 
 struct RegexRoute {
     pattern: Regex,
+    versions: ApiVersionRange,
     handler: Box<Handler<Parameters> + Sync>,
 }
 
@@ -76,18 +78,24 @@ pub struct RegexRoutesBuilder {
 impl Builder for RegexRoutesBuilder {
     type Recognizer = RegexRecognizer;
 
-    fn route<S, H>(mut self, method: Method, pattern: S, handler: H) -> Self
+    fn route<V, S, H>(mut self, method: Method, versions: V, pattern: S, handler: H) -> Self
     where
+        V: Into<ApiVersionRange>,
         S: AsRef<str>,
         H: Handler<<Self::Recognizer as Recognizer>::Parameters> + Sync,
     {
         let pattern = normalize_pattern(pattern.as_ref());
         let pattern = Regex::new(&pattern).expect("failed to compile regex");
+        let versions = versions.into();
         let handler = Box::new(handler);
         self.routes
             .entry(method)
             .or_insert_with(Vec::new)
-            .push(RegexRoute { pattern, handler });
+            .push(RegexRoute {
+                pattern,
+                versions,
+                handler,
+            });
         self
     }
 
@@ -109,8 +117,12 @@ impl Recognizer for RegexRecognizer {
         &self,
         method: &Method,
         path: &str,
+        version: Option<ApiVersion>,
     ) -> Result<HandlerParamsPair<Self::Parameters>, StatusCode> {
         let routes = self.routes.get(method).ok_or(StatusCode::NOT_FOUND)?;
+        let routes = routes
+            .iter()
+            .filter(|route| version.map_or(true, |version| route.versions.contains(version)));
         for route in routes {
             if let Some(params) = match_route(&route.pattern, path) {
                 return Ok((&*route.handler, params));
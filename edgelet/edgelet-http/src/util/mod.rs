@@ -8,7 +8,7 @@ use std::os::unix::net::SocketAddr as UnixSocketAddr;
 use std::path::Path;
 
 use bytes::{Buf, BufMut};
-use edgelet_core::pid::Pid;
+use edgelet_core::pid::{Credentials, Pid};
 use futures::Poll;
 #[cfg(windows)]
 use mio_uds_windows::net::SocketAddr as UnixSocketAddr;
@@ -48,6 +48,16 @@ impl StreamSelector {
             StreamSelector::Unix(ref stream) => stream.pid(),
         }
     }
+
+    #[cfg_attr(feature = "cargo-clippy", allow(match_same_arms))]
+    pub fn credentials(&self) -> io::Result<Option<Credentials>> {
+        match *self {
+            StreamSelector::Tcp(_) => Ok(None),
+            #[cfg(windows)]
+            StreamSelector::Pipe(_) => Ok(None),
+            StreamSelector::Unix(ref stream) => stream.credentials(),
+        }
+    }
 }
 
 impl Read for StreamSelector {
@@ -232,4 +242,14 @@ mod tests {
             Pid::Value(_) => (),
         }
     }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_credentials() {
+        let pair = socket_pair();
+        let credentials = pair.a.credentials().unwrap().unwrap();
+        assert_eq!(unsafe { ::libc::getuid() }, credentials.uid());
+        assert!(credentials.gids().contains(&unsafe { ::libc::getgid() }));
+        assert!(credentials.host());
+    }
 }
@@ -1,14 +1,141 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::fmt;
+use std::ops::{RangeFrom, RangeFull, RangeInclusive, RangeToInclusive};
+use std::str::FromStr;
+
 use futures::{future, Future};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use hyper::service::{NewService, Service};
-use hyper::{Body, Request, Response};
+use hyper::{Body, Method, Request, Response, StatusCode};
 use url::form_urlencoded::parse as parse_query;
 
 use error::{Error, ErrorKind};
 use IntoResponse;
 
-pub const API_VERSION: &str = "2018-06-28";
+/// The latest version of the API. Clients built from this tree send it in the
+/// `api-version` query parameter.
+pub const API_VERSION: &str = "2019-01-30";
+
+/// Path of the endpoint that lists the supported API versions. It can be
+/// called without an `api-version` so clients can discover what to send.
+const VERSIONS_PATH: &str = "/versions";
+
+/// The versions of the API this daemon serves, oldest first.
+///
+/// Requests for any of these are accepted so that clients built against an
+/// older version keep working after the daemon is upgraded. Routes declare
+/// the range of versions they are available in, and handlers that need to
+/// shape a request or response differently per version can read the
+/// negotiated `ApiVersion` from the request extensions.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ApiVersion {
+    V2018_06_28,
+    V2019_01_30,
+}
+
+const API_VERSIONS: &[ApiVersion] = &[ApiVersion::V2018_06_28, ApiVersion::V2019_01_30];
+
+impl ApiVersion {
+    pub fn all() -> &'static [ApiVersion] {
+        API_VERSIONS
+    }
+
+    pub fn latest() -> ApiVersion {
+        ApiVersion::V2019_01_30
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiVersion::V2018_06_28 => "2018-06-28",
+            ApiVersion::V2019_01_30 => "2019-01-30",
+        }
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        API_VERSIONS
+            .iter()
+            .find(|version| version.as_str() == s)
+            .cloned()
+            .ok_or_else(|| Error::from(ErrorKind::InvalidApiVersion(s.to_string())))
+    }
+}
+
+/// The versions of the API a route is available in. Both ends are inclusive
+/// and a missing end is unbounded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ApiVersionRange {
+    min: Option<ApiVersion>,
+    max: Option<ApiVersion>,
+}
+
+impl ApiVersionRange {
+    pub fn contains(&self, version: ApiVersion) -> bool {
+        self.min.map_or(true, |min| min <= version) && self.max.map_or(true, |max| version <= max)
+    }
+}
+
+impl From<RangeFull> for ApiVersionRange {
+    fn from(_: RangeFull) -> Self {
+        ApiVersionRange {
+            min: None,
+            max: None,
+        }
+    }
+}
+
+impl From<RangeFrom<ApiVersion>> for ApiVersionRange {
+    fn from(range: RangeFrom<ApiVersion>) -> Self {
+        ApiVersionRange {
+            min: Some(range.start),
+            max: None,
+        }
+    }
+}
+
+impl From<RangeToInclusive<ApiVersion>> for ApiVersionRange {
+    fn from(range: RangeToInclusive<ApiVersion>) -> Self {
+        ApiVersionRange {
+            min: None,
+            max: Some(range.end),
+        }
+    }
+}
+
+impl From<RangeInclusive<ApiVersion>> for ApiVersionRange {
+    fn from(range: RangeInclusive<ApiVersion>) -> Self {
+        ApiVersionRange {
+            min: Some(*range.start()),
+            max: Some(*range.end()),
+        }
+    }
+}
+
+fn versions_response() -> Response<Body> {
+    let versions: Vec<&str> = API_VERSIONS.iter().map(|v| v.as_str()).collect();
+    let body = json!({
+        "versions": versions,
+        "latest": ApiVersion::latest().as_str(),
+    })
+    .to_string();
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CONTENT_LENGTH, body.len().to_string().as_str())
+        .body(body.into())
+        .expect("response builder failure")
+}
 
 #[derive(Clone)]
 pub struct ApiVersionService<T> {
@@ -32,7 +159,12 @@ where
     type Error = T::Error;
     type Future = Box<Future<Item = Response<Self::ResBody>, Error = Self::Error> + Send>;
 
-    fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+    fn call(&mut self, mut req: Request<Self::ReqBody>) -> Self::Future {
+        if *req.method() == Method::GET && req.uri().path().trim_right_matches('/') == VERSIONS_PATH
+        {
+            return Box::new(future::ok(versions_response()));
+        }
+
         let response = {
             let query = req.uri().query();
             let api_version = query.and_then(|query| {
@@ -42,19 +174,21 @@ where
             });
 
             match api_version {
-                Some(ref api_version) if api_version == API_VERSION => Ok(()),
-                Some(api_version) => Err(ErrorKind::InvalidApiVersion(api_version.into_owned())),
-                None => Err(ErrorKind::InvalidApiVersion(String::new())),
+                Some(api_version) => api_version.parse::<ApiVersion>(),
+                None => Err(Error::from(ErrorKind::InvalidApiVersion(String::new()))),
             }
         };
 
         match response {
-            Ok(()) => Box::new(
-                self.upstream
-                    .call(req)
-                    .or_else(|e| future::ok(e.into_response())),
-            ),
-            Err(kind) => Box::new(future::ok(Error::from(kind).into_response())),
+            Ok(api_version) => {
+                req.extensions_mut().insert(api_version);
+                Box::new(
+                    self.upstream
+                        .call(req)
+                        .or_else(|e| future::ok(e.into_response())),
+                )
+            }
+            Err(err) => Box::new(future::ok(err.into_response())),
         }
     }
 }
@@ -85,7 +219,9 @@ where
 mod tests {
     use failure::{Compat, Fail};
     use futures::future::FutureResult;
+    use futures::Stream;
     use hyper::StatusCode;
+    use serde_json::Value;
 
     use super::*;
 
@@ -101,7 +237,10 @@ mod tests {
         type Error = Compat<Error>;
         type Future = FutureResult<Response<Self::ResBody>, Self::Error>;
 
-        fn call(&mut self, _req: Request<Self::ReqBody>) -> Self::Future {
+        fn call(&mut self, req: Request<Self::ReqBody>) -> Self::Future {
+            // every request that reaches the upstream service carries its version
+            assert!(req.extensions().get::<ApiVersion>().is_some());
+
             if self.error {
                 future::err(Error::from(ErrorKind::ServiceError).compat())
             } else {
@@ -174,4 +313,59 @@ mod tests {
         let response = Service::call(&mut api_service, req).wait().unwrap();
         assert_eq!(StatusCode::BAD_REQUEST, response.status());
     }
+
+    #[test]
+    fn api_version_matches_latest() {
+        assert_eq!(API_VERSION, ApiVersion::latest().to_string());
+        assert_eq!(Some(&ApiVersion::latest()), ApiVersion::all().last());
+    }
+
+    #[test]
+    fn older_api_version_is_accepted() {
+        let url = "http://localhost?api-version=2018-06-28";
+        let req = Request::get(url).body(Body::default()).unwrap();
+        let mut api_service = ApiVersionService::new(TestService {
+            status_code: StatusCode::OK,
+            error: false,
+        });
+        let response = Service::call(&mut api_service, req).wait().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[test]
+    fn versions_are_listed_without_api_version() {
+        let req = Request::get("http://localhost/versions")
+            .body(Body::default())
+            .unwrap();
+        let mut api_service = ApiVersionService::new(TestService {
+            status_code: StatusCode::IM_A_TEAPOT,
+            error: false,
+        });
+        let response = Service::call(&mut api_service, req).wait().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+
+        let body = response.into_body().concat2().wait().unwrap();
+        let versions: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json!(["2018-06-28", "2019-01-30"]), versions["versions"]);
+        assert_eq!(json!(API_VERSION), versions["latest"]);
+    }
+
+    #[test]
+    fn version_range_contains() {
+        let all = ApiVersionRange::from(..);
+        assert!(all.contains(ApiVersion::V2018_06_28));
+        assert!(all.contains(ApiVersion::V2019_01_30));
+
+        let since = ApiVersionRange::from(ApiVersion::V2019_01_30..);
+        assert!(!since.contains(ApiVersion::V2018_06_28));
+        assert!(since.contains(ApiVersion::V2019_01_30));
+
+        let until = ApiVersionRange::from(..=ApiVersion::V2018_06_28);
+        assert!(until.contains(ApiVersion::V2018_06_28));
+        assert!(!until.contains(ApiVersion::V2019_01_30));
+
+        let only = ApiVersionRange::from(ApiVersion::V2019_01_30..=ApiVersion::V2019_01_30);
+        assert!(!only.contains(ApiVersion::V2018_06_28));
+        assert!(only.contains(ApiVersion::V2019_01_30));
+    }
 }
@@ -16,8 +16,10 @@ use futures::{future, Future, Stream};
 use hyper::service::{NewService, Service};
 use hyper::{Body, Chunk, Request, Response, StatusCode};
 
-use edgelet_http::route::{Builder, Parameters, RegexRoutesBuilder, Router};
-use edgelet_http::Error as HttpError;
+use edgelet_http::route::{
+    Builder, Parameters, RegexRecognizer, RegexRoutesBuilder, Router, RouterService,
+};
+use edgelet_http::{ApiVersion, Error as HttpError};
 
 #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
 fn route1(
@@ -66,8 +68,8 @@ fn route2(
 #[test]
 fn simple_route() {
     let recognizer = RegexRoutesBuilder::default()
-        .get("/route1/(?P<name>[^/]+)", route1)
-        .get("/route2/(?P<name>[^/]+)", route2)
+        .get(.., "/route1/(?P<name>[^/]+)", route1)
+        .get(.., "/route2/(?P<name>[^/]+)", route2)
         .finish();
     let router = Router::from(recognizer);
     let mut service = router.new_service().wait().unwrap();
@@ -101,8 +103,8 @@ fn simple_route() {
 #[test]
 fn not_found() {
     let recognizer = RegexRoutesBuilder::default()
-        .get("/route1/(?P<name>[^/]+)", route1)
-        .get("/route2/(?P<name>[^/]+)", route2)
+        .get(.., "/route1/(?P<name>[^/]+)", route1)
+        .get(.., "/route2/(?P<name>[^/]+)", route2)
         .finish();
     let router = Router::from(recognizer);
     let mut service = router.new_service().wait().unwrap();
@@ -114,3 +116,45 @@ fn not_found() {
 
     assert_eq!(StatusCode::NOT_FOUND, response1.status());
 }
+
+fn call_with_version(
+    service: &mut RouterService<RegexRecognizer>,
+    uri: &str,
+    version: ApiVersion,
+) -> Response<Body> {
+    let mut request = Request::get(uri).body(Body::default()).unwrap();
+    request.extensions_mut().insert(version);
+    service.call(request).wait().unwrap()
+}
+
+#[test]
+fn route_for_api_version() {
+    let recognizer = RegexRoutesBuilder::default()
+        .get(..=ApiVersion::V2018_06_28, "/route/(?P<name>[^/]+)", route1)
+        .get(ApiVersion::V2019_01_30.., "/route/(?P<name>[^/]+)", route2)
+        .get(ApiVersion::V2019_01_30.., "/new/(?P<name>[^/]+)", route2)
+        .finish();
+    let router = Router::from(recognizer);
+    let mut service = router.new_service().wait().unwrap();
+
+    let response = call_with_version(
+        &mut service,
+        "http://example.com/route/thename",
+        ApiVersion::V2018_06_28,
+    );
+    assert_eq!(StatusCode::OK, response.status());
+
+    let response = call_with_version(
+        &mut service,
+        "http://example.com/route/thename",
+        ApiVersion::V2019_01_30,
+    );
+    assert_eq!(StatusCode::CREATED, response.status());
+
+    let response = call_with_version(
+        &mut service,
+        "http://example.com/new/thename",
+        ApiVersion::V2018_06_28,
+    );
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+}
@@ -0,0 +1,970 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! A persistent cache of the module identities in IoT Hub.
+//!
+//! Devices on ships and in mines run disconnected for days, and they still
+//! need to start the modules whose identities they already know about.
+//! `CachedIdentityManager` wraps another identity manager and keeps a copy
+//! of the identities it returns on disk. While IoT Hub can't be reached,
+//! `list` and `get` are served from that copy, and identity changes are
+//! applied to it and queued until `reconcile` can replay them against IoT
+//! Hub.
+//!
+//! The cache belongs to one device in one hub. A cache left behind by another
+//! device, for example after the device was provisioned again, is discarded.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use failure::{Fail, ResultExt};
+use futures::future::{self, Either, Loop};
+use futures::Future;
+use serde_json::{self, Value};
+
+use edgelet_core::{IdentityManager, IdentityOperation, IdentitySpec, Twin, TwinTarget};
+use edgelet_http::{Error as HttpError, ErrorKind as HttpErrorKind};
+use iothubservice::Module;
+
+use error::{Error, ErrorKind};
+use HubIdentity;
+
+/// An identity change that was made while IoT Hub couldn't be reached.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "operation", rename_all = "lowercase")]
+enum PendingOperation {
+    Create {
+        module_id: String,
+        managed_by: Option<String>,
+    },
+    Update {
+        module_id: String,
+        generation_id: Option<String>,
+        managed_by: Option<String>,
+    },
+    Delete {
+        module_id: String,
+    },
+}
+
+impl PendingOperation {
+    fn module_id(&self) -> &str {
+        match self {
+            PendingOperation::Create { module_id, .. }
+            | PendingOperation::Update { module_id, .. }
+            | PendingOperation::Delete { module_id } => module_id,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CacheState {
+    /// The hub and device the identities belong to, as `<hub>/<device>`.
+    #[serde(default)]
+    device: Option<String>,
+    identities: Vec<Module>,
+    synced_at: Option<DateTime<Utc>>,
+    pending: Vec<PendingOperation>,
+}
+
+impl CacheState {
+    fn find(&self, module_id: &str) -> Option<&Module> {
+        self.identities
+            .iter()
+            .find(|module| module.module_id() == Some(module_id))
+    }
+
+    fn insert(&mut self, module: Module) {
+        let existing = self
+            .identities
+            .iter()
+            .position(|cached| cached.module_id() == module.module_id());
+        match existing {
+            Some(index) => self.identities[index] = module,
+            None => self.identities.push(module),
+        }
+    }
+
+    fn remove(&mut self, module_id: &str) {
+        self.identities
+            .retain(|module| module.module_id() != Some(module_id));
+    }
+
+    /// Replaces the cached identities with the ones IoT Hub just returned.
+    /// Changes that haven't been replayed yet are applied on top so they
+    /// aren't lost from view.
+    fn refresh(&mut self, identities: Vec<Module>) {
+        self.identities = identities;
+        self.synced_at = Some(Utc::now());
+        for operation in self.pending.clone() {
+            self.apply(&operation);
+        }
+    }
+
+    fn apply(&mut self, operation: &PendingOperation) -> Option<Module> {
+        match operation {
+            PendingOperation::Create {
+                module_id,
+                managed_by,
+            } => {
+                let mut module = Module::new().with_module_id(module_id.clone());
+                if let Some(managed_by) = managed_by {
+                    module = module.with_managed_by(managed_by.clone());
+                }
+                self.insert(module.clone());
+                Some(module)
+            }
+            PendingOperation::Update {
+                module_id,
+                generation_id,
+                managed_by,
+            } => {
+                let mut module = self
+                    .find(module_id)
+                    .cloned()
+                    .unwrap_or_else(|| Module::new().with_module_id(module_id.clone()));
+                if let Some(generation_id) = generation_id {
+                    module = module.with_generation_id(generation_id.clone());
+                }
+                if let Some(managed_by) = managed_by {
+                    module = module.with_managed_by(managed_by.clone());
+                }
+                self.insert(module.clone());
+                Some(module)
+            }
+            PendingOperation::Delete { module_id } => {
+                self.remove(module_id);
+                None
+            }
+        }
+    }
+
+    /// Applies `operation` to the cached identities and queues it. Earlier
+    /// operations on the same module are folded into it.
+    fn queue(&mut self, operation: PendingOperation) -> Option<Module> {
+        let module = self.apply(&operation);
+
+        let module_id = operation.module_id().to_string();
+        let created = self.pending.iter().any(|pending| match pending {
+            PendingOperation::Create { module_id: id, .. } => *id == module_id,
+            _ => false,
+        });
+        self.pending
+            .retain(|pending| pending.module_id() != module_id);
+
+        if created {
+            // The module never made it to IoT Hub. Deleting it leaves nothing
+            // to do, while creating it also brings it up to date.
+            if let PendingOperation::Update { managed_by, .. } = operation {
+                self.pending.push(PendingOperation::Create {
+                    module_id,
+                    managed_by,
+                });
+            }
+        } else {
+            self.pending.push(operation);
+        }
+
+        module
+    }
+}
+
+struct Cache {
+    path: PathBuf,
+    state: Mutex<CacheState>,
+}
+
+impl Cache {
+    fn load(path: PathBuf, device: String) -> Result<Self, Error> {
+        let mut state = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|err| {
+                warn!(
+                    "Discarding identity cache {} that could not be read: {}",
+                    path.display(),
+                    err
+                );
+                CacheState::default()
+            }),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => CacheState::default(),
+            Err(err) => {
+                return Err(Error::from(
+                    err.context(ErrorKind::IdentityCache(path.display().to_string())),
+                ))
+            }
+        };
+
+        if state
+            .device
+            .as_ref()
+            .map_or(false, |cached| *cached != device)
+        {
+            warn!(
+                "Discarding identity cache {} of another device with {} pending change(s)",
+                path.display(),
+                state.pending.len()
+            );
+            state = CacheState::default();
+        }
+        state.device = Some(device);
+
+        Ok(Cache {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    fn read<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&CacheState) -> T,
+    {
+        f(&self.state.lock().unwrap())
+    }
+
+    /// Changes the cached state and writes it to disk. Failing to write it
+    /// only costs the changes if the daemon restarts before the next write,
+    /// so it is logged rather than failing the operation.
+    fn update<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut CacheState) -> T,
+    {
+        let mut state = self.state.lock().unwrap();
+        let result = f(&mut state);
+        if let Err(err) = self.save(&state) {
+            warn!("{}", err);
+            if let Some(cause) = err.cause() {
+                warn!("\tcaused by: {}", cause);
+            }
+        }
+        result
+    }
+
+    fn save(&self, state: &CacheState) -> Result<(), Error> {
+        // Write to a temporary file first so that a crash can't leave a
+        // truncated cache behind.
+        let temp_path = self.path.with_extension("tmp");
+        write_state(&temp_path, state)
+            .and_then(|_| fs::rename(&temp_path, &self.path))
+            .with_context(|_| ErrorKind::IdentityCache(self.path.display().to_string()))?;
+        Ok(())
+    }
+}
+
+fn write_state(path: &Path, state: &CacheState) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    serde_json::to_writer(&mut file, state)?;
+    file.flush()?;
+    file.sync_all()
+}
+
+/// Whether `err` was caused by IoT Hub being unreachable, as opposed to IoT
+/// Hub rejecting the request.
+fn is_unreachable(err: &Fail) -> bool {
+    let mut fail = Some(err);
+    while let Some(err) = fail {
+        if let Some(http_err) = err.downcast_ref::<HttpError>() {
+            return match http_err.kind() {
+                HttpErrorKind::Http
+                | HttpErrorKind::Timeout
+                | HttpErrorKind::CircuitBreakerOpen => true,
+                _ => false,
+            };
+        }
+        fail = err.cause();
+    }
+    false
+}
+
+pub struct CachedIdentityManager<M> {
+    inner: M,
+    cache: Arc<Cache>,
+}
+
+impl<M> CachedIdentityManager<M>
+where
+    M: 'static + IdentityManager<Identity = HubIdentity> + Clone + Send,
+{
+    /// Wraps `inner` with the cache stored at `path` for the identities of
+    /// `device_id` in `hub_name`. A cache that can't be parsed or that belongs
+    /// to another device is discarded.
+    pub fn new(inner: M, path: PathBuf, hub_name: &str, device_id: &str) -> Result<Self, Error> {
+        Ok(CachedIdentityManager {
+            inner,
+            cache: Arc::new(Cache::load(path, format!("{}/{}", hub_name, device_id))?),
+        })
+    }
+
+    /// When the cached identities were last refreshed from IoT Hub, if ever.
+    pub fn synced_at(&self) -> Option<DateTime<Utc>> {
+        self.cache.read(|state| state.synced_at)
+    }
+
+    /// The number of identity changes waiting to be replayed against IoT Hub.
+    pub fn pending(&self) -> usize {
+        self.cache.read(|state| state.pending.len())
+    }
+
+    /// Replays the queued identity changes against IoT Hub in the order they
+    /// were made and then refreshes the cached identities. Stops at the first
+    /// change that can't be sent because IoT Hub is still unreachable. Changes
+    /// that IoT Hub rejects are logged and dropped.
+    ///
+    /// Resolves to the modules whose identities were created while IoT Hub
+    /// was unreachable and just got their generation id. Their keys are
+    /// derived from the generation id, so these modules have to be started
+    /// again with the new one.
+    pub fn reconcile(&self) -> Box<Future<Item = Vec<String>, Error = Error> + Send> {
+        let cache = self.cache.clone();
+        let inner = self.inner.clone();
+        let list = self.clone();
+
+        let replay = future::loop_fn(vec![], move |mut generated| {
+            let operation = match cache.read(|state| state.pending.first().cloned()) {
+                Some(operation) => operation,
+                None => return Either::A(future::ok(Loop::Break(generated))),
+            };
+
+            let cache = cache.clone();
+            Either::B(replay(inner.clone(), &operation).then(move |result| {
+                let module = match result {
+                    Ok(module) => module,
+                    Err(err) => {
+                        if is_unreachable(&err) {
+                            return Err(err);
+                        }
+                        warn!(
+                            "IoT Hub rejected the change to identity {} made while it was unreachable: {}",
+                            operation.module_id(),
+                            err
+                        );
+                        None
+                    }
+                };
+
+                if let (PendingOperation::Create { .. }, Some(generation_id)) = (
+                    &operation,
+                    module.as_ref().and_then(Module::generation_id),
+                ) {
+                    info!(
+                        "Identity {} created while IoT Hub was unreachable got generation id {}",
+                        operation.module_id(),
+                        generation_id
+                    );
+                    generated.push(operation.module_id().to_string());
+                }
+
+                cache.update(|state| {
+                    if let Some(index) = state
+                        .pending
+                        .iter()
+                        .position(|pending| *pending == operation)
+                    {
+                        state.pending.remove(index);
+                    }
+                    match module {
+                        Some(module) => state.insert(module),
+                        None => state.remove(operation.module_id()),
+                    }
+                });
+                Ok(Loop::Continue(generated))
+            }))
+        });
+
+        Box::new(replay.and_then(move |generated| list.list().map(|_| generated)))
+    }
+
+    /// Logs that IoT Hub couldn't be reached and how old the cached
+    /// identities being used instead are.
+    fn warn_stale(&self, err: &Fail) {
+        let (synced_at, pending) = self
+            .cache
+            .read(|state| (state.synced_at, state.pending.len()));
+        warn!(
+            "IoT Hub is unreachable ({}); using identities cached at {} with {} pending change(s)",
+            err,
+            synced_at.map_or_else(|| "<never>".to_string(), |time| time.to_rfc3339()),
+            pending
+        );
+    }
+}
+
+fn replay<M>(
+    mut inner: M,
+    operation: &PendingOperation,
+) -> impl Future<Item = Option<Module>, Error = Error>
+where
+    M: IdentityManager<Identity = HubIdentity>,
+{
+    let module_id = operation.module_id().to_string();
+    match operation {
+        PendingOperation::Create { managed_by, .. } => {
+            let spec = identity_spec(module_id.clone(), None, managed_by.clone());
+            Either::A(Either::A(
+                inner
+                    .create(spec)
+                    .map(|identity| Some(identity.hub_module().clone()))
+                    .map_err(|err| {
+                        Error::from(err.context(ErrorKind::IdentityOperation(
+                            IdentityOperation::CreateIdentity(module_id),
+                        )))
+                    }),
+            ))
+        }
+        PendingOperation::Update {
+            generation_id,
+            managed_by,
+            ..
+        } => {
+            let spec = identity_spec(module_id.clone(), generation_id.clone(), managed_by.clone());
+            Either::A(Either::B(
+                inner
+                    .update(spec)
+                    .map(|identity| Some(identity.hub_module().clone()))
+                    .map_err(|err| {
+                        Error::from(err.context(ErrorKind::IdentityOperation(
+                            IdentityOperation::UpdateIdentity(module_id),
+                        )))
+                    }),
+            ))
+        }
+        PendingOperation::Delete { .. } => Either::B(
+            inner
+                .delete(IdentitySpec::new(module_id.clone()))
+                .map(|_| None)
+                .map_err(|err| {
+                    Error::from(err.context(ErrorKind::IdentityOperation(
+                        IdentityOperation::DeleteIdentity(module_id),
+                    )))
+                }),
+        ),
+    }
+}
+
+fn identity_spec(
+    module_id: String,
+    generation_id: Option<String>,
+    managed_by: Option<String>,
+) -> IdentitySpec {
+    let mut spec = IdentitySpec::new(module_id);
+    if let Some(generation_id) = generation_id {
+        spec = spec.with_generation_id(generation_id);
+    }
+    if let Some(managed_by) = managed_by {
+        spec = spec.with_managed_by(managed_by);
+    }
+    spec
+}
+
+impl<M> Clone for CachedIdentityManager<M>
+where
+    M: Clone,
+{
+    fn clone(&self) -> Self {
+        CachedIdentityManager {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<M> IdentityManager for CachedIdentityManager<M>
+where
+    M: 'static + IdentityManager<Identity = HubIdentity> + Clone + Send,
+{
+    type Identity = HubIdentity;
+    type Error = Error;
+    type CreateFuture = Box<Future<Item = Self::Identity, Error = Self::Error> + Send>;
+    type UpdateFuture = Box<Future<Item = Self::Identity, Error = Self::Error> + Send>;
+    type ListFuture = Box<Future<Item = Vec<Self::Identity>, Error = Self::Error> + Send>;
+    type GetFuture = Box<Future<Item = Option<Self::Identity>, Error = Self::Error> + Send>;
+    type DeleteFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type GetTwinFuture = Box<Future<Item = Twin, Error = Self::Error> + Send>;
+    type ReportPropertiesFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+
+    fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture {
+        let module_id = id.module_id().to_string();
+        let managed_by = id.managed_by().map(ToString::to_string);
+        let this = self.clone();
+
+        Box::new(self.inner.create(id).then(move |result| match result {
+            Ok(identity) => {
+                this.cache
+                    .update(|state| state.insert(identity.hub_module().clone()));
+                Ok(identity)
+            }
+            Err(ref err) if is_unreachable(err) => {
+                this.warn_stale(err);
+                let module = this.cache.update(|state| {
+                    state.queue(PendingOperation::Create {
+                        module_id,
+                        managed_by,
+                    })
+                });
+                Ok(HubIdentity::new(module.unwrap_or_else(Module::new)))
+            }
+            Err(err) => Err(Error::from(err.context(ErrorKind::IdentityOperation(
+                IdentityOperation::CreateIdentity(module_id),
+            )))),
+        }))
+    }
+
+    fn update(&mut self, id: IdentitySpec) -> Self::UpdateFuture {
+        let module_id = id.module_id().to_string();
+        let generation_id = id.generation_id().map(ToString::to_string);
+        let managed_by = id.managed_by().map(ToString::to_string);
+        let this = self.clone();
+
+        Box::new(self.inner.update(id).then(move |result| match result {
+            Ok(identity) => {
+                this.cache
+                    .update(|state| state.insert(identity.hub_module().clone()));
+                Ok(identity)
+            }
+            Err(ref err) if is_unreachable(err) => {
+                this.warn_stale(err);
+                let module = this.cache.update(|state| {
+                    state.queue(PendingOperation::Update {
+                        module_id,
+                        generation_id,
+                        managed_by,
+                    })
+                });
+                Ok(HubIdentity::new(module.unwrap_or_else(Module::new)))
+            }
+            Err(err) => Err(Error::from(err.context(ErrorKind::IdentityOperation(
+                IdentityOperation::UpdateIdentity(module_id),
+            )))),
+        }))
+    }
+
+    fn list(&self) -> Self::ListFuture {
+        let this = self.clone();
+
+        Box::new(self.inner.list().then(move |result| match result {
+            Ok(identities) => {
+                this.cache.update(|state| {
+                    state.refresh(
+                        identities
+                            .iter()
+                            .map(|identity| identity.hub_module().clone())
+                            .collect(),
+                    )
+                });
+                Ok(identities)
+            }
+            Err(ref err) if is_unreachable(err) && this.synced_at().is_some() => {
+                this.warn_stale(err);
+                Ok(this.cache.read(|state| {
+                    state
+                        .identities
+                        .iter()
+                        .cloned()
+                        .map(HubIdentity::new)
+                        .collect()
+                }))
+            }
+            Err(err) => Err(Error::from(err.context(ErrorKind::IdentityOperation(
+                IdentityOperation::ListIdentities,
+            )))),
+        }))
+    }
+
+    fn get(&self, id: IdentitySpec) -> Self::GetFuture {
+        let module_id = id.module_id().to_string();
+        let this = self.clone();
+
+        Box::new(self.inner.get(id).then(move |result| match result {
+            Ok(identity) => {
+                this.cache.update(|state| match identity {
+                    Some(ref identity) => state.insert(identity.hub_module().clone()),
+                    None => state.remove(&module_id),
+                });
+                Ok(identity)
+            }
+            Err(ref err) if is_unreachable(err) && this.synced_at().is_some() => {
+                this.warn_stale(err);
+                Ok(this
+                    .cache
+                    .read(|state| state.find(&module_id).cloned().map(HubIdentity::new)))
+            }
+            Err(err) => Err(Error::from(err.context(ErrorKind::IdentityOperation(
+                IdentityOperation::GetIdentity(module_id),
+            )))),
+        }))
+    }
+
+    fn delete(&mut self, id: IdentitySpec) -> Self::DeleteFuture {
+        let module_id = id.module_id().to_string();
+        let this = self.clone();
+
+        Box::new(self.inner.delete(id).then(move |result| match result {
+            Ok(()) => {
+                this.cache.update(|state| state.remove(&module_id));
+                Ok(())
+            }
+            Err(ref err) if is_unreachable(err) => {
+                this.warn_stale(err);
+                this.cache
+                    .update(|state| state.queue(PendingOperation::Delete { module_id }));
+                Ok(())
+            }
+            Err(err) => Err(Error::from(err.context(ErrorKind::IdentityOperation(
+                IdentityOperation::DeleteIdentity(module_id),
+            )))),
+        }))
+    }
+
+    fn get_twin(&self, target: TwinTarget) -> Self::GetTwinFuture {
+        Box::new(self.inner.get_twin(target.clone()).map_err(|err| {
+            Error::from(
+                err.context(ErrorKind::IdentityOperation(IdentityOperation::GetTwin(
+                    target,
+                ))),
+            )
+        }))
+    }
+
+    fn report_properties(
+        &mut self,
+        target: TwinTarget,
+        reported: Value,
+    ) -> Self::ReportPropertiesFuture {
+        Box::new(
+            self.inner
+                .report_properties(target.clone(), reported)
+                .map_err(|err| {
+                    Error::from(err.context(ErrorKind::IdentityOperation(
+                        IdentityOperation::ReportProperties(target),
+                    )))
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use hyper::StatusCode;
+    use tempdir::TempDir;
+
+    use edgelet_core::{Identity, TwinProperties};
+
+    /// An identity manager that behaves like IoT Hub and can be taken
+    /// offline.
+    #[derive(Clone)]
+    struct TestHub {
+        online: Arc<AtomicBool>,
+        modules: Arc<Mutex<Vec<Module>>>,
+        generation: Arc<Mutex<u32>>,
+    }
+
+    impl TestHub {
+        fn new(module_ids: &[&str]) -> Self {
+            let hub = TestHub {
+                online: Arc::new(AtomicBool::new(true)),
+                modules: Arc::new(Mutex::new(vec![])),
+                generation: Arc::new(Mutex::new(0)),
+            };
+            for module_id in module_ids {
+                let module = hub.new_module(module_id);
+                hub.modules.lock().unwrap().push(module);
+            }
+            hub
+        }
+
+        fn set_online(&self, online: bool) {
+            self.online.store(online, Ordering::SeqCst);
+        }
+
+        fn module_ids(&self) -> Vec<String> {
+            self.modules
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|module| module.module_id().unwrap().to_string())
+                .collect()
+        }
+
+        fn new_module(&self, module_id: &str) -> Module {
+            let mut generation = self.generation.lock().unwrap();
+            *generation += 1;
+            Module::new()
+                .with_module_id(module_id.to_string())
+                .with_generation_id(format!("g{}", *generation))
+        }
+
+        fn check<T>(&self, result: Result<T, HttpError>) -> Result<T, HttpError> {
+            if self.online.load(Ordering::SeqCst) {
+                result
+            } else {
+                Err(HttpError::from(HttpErrorKind::Http))
+            }
+        }
+    }
+
+    impl IdentityManager for TestHub {
+        type Identity = HubIdentity;
+        type Error = HttpError;
+        type CreateFuture = future::FutureResult<Self::Identity, Self::Error>;
+        type UpdateFuture = future::FutureResult<Self::Identity, Self::Error>;
+        type ListFuture = future::FutureResult<Vec<Self::Identity>, Self::Error>;
+        type GetFuture = future::FutureResult<Option<Self::Identity>, Self::Error>;
+        type DeleteFuture = future::FutureResult<(), Self::Error>;
+        type GetTwinFuture = future::FutureResult<Twin, Self::Error>;
+        type ReportPropertiesFuture = future::FutureResult<(), Self::Error>;
+
+        fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture {
+            let result = self.check(Ok(())).and_then(|_| {
+                if self.module_ids().iter().any(|m| m == id.module_id()) {
+                    Err(HttpError::http_with_error_response(
+                        StatusCode::CONFLICT,
+                        b"module exists",
+                    ))
+                } else {
+                    let module = self.new_module(id.module_id());
+                    self.modules.lock().unwrap().push(module.clone());
+                    Ok(HubIdentity::new(module))
+                }
+            });
+            future::result(result)
+        }
+
+        fn update(&mut self, id: IdentitySpec) -> Self::UpdateFuture {
+            let module = self
+                .modules
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|module| module.module_id() == Some(id.module_id()))
+                .cloned()
+                .map(HubIdentity::new)
+                .ok_or_else(|| {
+                    HttpError::http_with_error_response(StatusCode::NOT_FOUND, b"not found")
+                });
+            future::result(self.check(module))
+        }
+
+        fn list(&self) -> Self::ListFuture {
+            let modules = self
+                .modules
+                .lock()
+                .unwrap()
+                .iter()
+                .cloned()
+                .map(HubIdentity::new)
+                .collect();
+            future::result(self.check(Ok(modules)))
+        }
+
+        fn get(&self, id: IdentitySpec) -> Self::GetFuture {
+            let module = self
+                .modules
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|module| module.module_id() == Some(id.module_id()))
+                .cloned()
+                .map(HubIdentity::new);
+            future::result(self.check(Ok(module)))
+        }
+
+        fn delete(&mut self, id: IdentitySpec) -> Self::DeleteFuture {
+            let result = self.check(Ok(()));
+            if result.is_ok() {
+                self.modules
+                    .lock()
+                    .unwrap()
+                    .retain(|module| module.module_id() != Some(id.module_id()));
+            }
+            future::result(result)
+        }
+
+        fn get_twin(&self, _target: TwinTarget) -> Self::GetTwinFuture {
+            future::result(self.check(Ok(Twin::new(1, TwinProperties::new()))))
+        }
+
+        fn report_properties(
+            &mut self,
+            _target: TwinTarget,
+            _reported: Value,
+        ) -> Self::ReportPropertiesFuture {
+            future::result(self.check(Ok(())))
+        }
+    }
+
+    fn cached(hub: &TestHub, dir: &TempDir) -> CachedIdentityManager<TestHub> {
+        cached_for(hub, dir, "d1")
+    }
+
+    fn cached_for(hub: &TestHub, dir: &TempDir, device_id: &str) -> CachedIdentityManager<TestHub> {
+        CachedIdentityManager::new(
+            hub.clone(),
+            dir.path().join("identities.json"),
+            "hub.example.com",
+            device_id,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn identities_are_served_from_cache_while_offline() {
+        let dir = TempDir::new("identity-cache").unwrap();
+        let hub = TestHub::new(&["edgeAgent", "m1"]);
+        cached(&hub, &dir).list().wait().unwrap();
+        hub.set_online(false);
+
+        // a fresh manager reads the identities back from disk
+        let manager = cached(&hub, &dir);
+        let identities = manager.list().wait().unwrap();
+        let agent = manager
+            .get(IdentitySpec::new("edgeAgent".to_string()))
+            .wait()
+            .unwrap()
+            .unwrap();
+        let unknown = manager
+            .get(IdentitySpec::new("m2".to_string()))
+            .wait()
+            .unwrap();
+
+        assert_eq!(2, identities.len());
+        assert_eq!("g1", agent.generation_id());
+        assert!(unknown.is_none());
+        assert!(manager.synced_at().is_some());
+    }
+
+    #[test]
+    fn offline_without_cache_fails() {
+        let dir = TempDir::new("identity-cache").unwrap();
+        let hub = TestHub::new(&["edgeAgent"]);
+        hub.set_online(false);
+        let manager = cached(&hub, &dir);
+
+        assert!(manager.list().wait().is_err());
+        assert!(manager
+            .get(IdentitySpec::new("edgeAgent".to_string()))
+            .wait()
+            .is_err());
+        assert_eq!(None, manager.synced_at());
+    }
+
+    #[test]
+    fn hub_errors_are_not_hidden_by_cache() {
+        let dir = TempDir::new("identity-cache").unwrap();
+        let hub = TestHub::new(&["m1"]);
+        let mut manager = cached(&hub, &dir);
+        manager.list().wait().unwrap();
+
+        let err = manager
+            .create(IdentitySpec::new("m1".to_string()))
+            .wait()
+            .unwrap_err();
+
+        assert_eq!("Could not create identity m1", err.to_string());
+        assert_eq!(0, manager.pending());
+    }
+
+    #[test]
+    fn offline_changes_are_replayed_by_reconcile() {
+        let dir = TempDir::new("identity-cache").unwrap();
+        let hub = TestHub::new(&["edgeAgent", "m1", "m2"]);
+        let mut manager = cached(&hub, &dir);
+        manager.list().wait().unwrap();
+        hub.set_online(false);
+
+        let created = manager
+            .create(IdentitySpec::new("m3".to_string()).with_managed_by("iotedge".to_string()))
+            .wait()
+            .unwrap();
+        let updated = manager
+            .update(IdentitySpec::new("edgeAgent".to_string()).with_generation_id("g1".to_string()))
+            .wait()
+            .unwrap();
+        manager
+            .delete(IdentitySpec::new("m2".to_string()))
+            .wait()
+            .unwrap();
+
+        assert_eq!("m3", created.module_id());
+        assert_eq!("iotedge", created.managed_by());
+        assert_eq!("g1", updated.generation_id());
+        assert_eq!(3, manager.pending());
+        let mut cached_ids: Vec<String> = manager
+            .list()
+            .wait()
+            .unwrap()
+            .iter()
+            .map(|identity| identity.module_id().to_string())
+            .collect();
+        cached_ids.sort();
+        assert_eq!(vec!["edgeAgent", "m1", "m3"], cached_ids);
+
+        // still offline, so nothing is replayed
+        assert!(manager.reconcile().wait().is_err());
+        assert_eq!(3, manager.pending());
+
+        hub.set_online(true);
+        let generated = manager.reconcile().wait().unwrap();
+
+        assert_eq!(vec!["m3"], generated);
+        assert_eq!(0, manager.pending());
+        assert_eq!(vec!["edgeAgent", "m1", "m3"], hub.module_ids());
+        let m3 = manager
+            .get(IdentitySpec::new("m3".to_string()))
+            .wait()
+            .unwrap()
+            .unwrap();
+        assert_eq!("g4", m3.generation_id());
+    }
+
+    #[test]
+    fn offline_create_and_delete_cancel_out() {
+        let dir = TempDir::new("identity-cache").unwrap();
+        let hub = TestHub::new(&[]);
+        let mut manager = cached(&hub, &dir);
+        manager.list().wait().unwrap();
+        hub.set_online(false);
+
+        manager
+            .create(IdentitySpec::new("m1".to_string()))
+            .wait()
+            .unwrap();
+        manager
+            .update(IdentitySpec::new("m1".to_string()).with_managed_by("iotedge".to_string()))
+            .wait()
+            .unwrap();
+        assert_eq!(1, manager.pending());
+
+        manager
+            .delete(IdentitySpec::new("m1".to_string()))
+            .wait()
+            .unwrap();
+        assert_eq!(0, manager.pending());
+        assert!(manager.list().wait().unwrap().is_empty());
+    }
+
+    #[test]
+    fn cache_of_another_device_is_discarded() {
+        let dir = TempDir::new("identity-cache").unwrap();
+        let hub = TestHub::new(&["m1"]);
+        let mut manager = cached_for(&hub, &dir, "d1");
+        manager.list().wait().unwrap();
+        hub.set_online(false);
+        manager
+            .create(IdentitySpec::new("m2".to_string()))
+            .wait()
+            .unwrap();
+        assert_eq!(1, manager.pending());
+
+        // the same device keeps its cache and pending changes
+        assert_eq!(1, cached_for(&hub, &dir, "d1").pending());
+
+        let manager = cached_for(&hub, &dir, "d2");
+        assert_eq!(0, manager.pending());
+        assert_eq!(None, manager.synced_at());
+        assert!(manager.list().wait().is_err());
+    }
+}
@@ -23,9 +23,15 @@ pub enum ErrorKind {
     #[fail(display = "Could not get SAS token")]
     GetToken,
 
+    #[fail(display = "Could not access the identity cache at {}", _0)]
+    IdentityCache(String),
+
     #[fail(display = "{}", _0)]
     IdentityOperation(IdentityOperation),
 
+    #[fail(display = "Could not report properties of module {}: {}", _0, _1)]
+    ReportPropertiesWithReason(String, IdentityOperationReason),
+
     #[fail(display = "Could not update identity {}: {}", _0, _1)]
     UpdateIdentityWithReason(String, IdentityOperationReason),
 }
@@ -18,12 +18,16 @@ extern crate futures;
 #[cfg(test)]
 extern crate hyper;
 #[macro_use]
+extern crate log;
+#[macro_use]
 extern crate percent_encoding;
 #[macro_use]
 extern crate serde_derive;
-#[cfg(test)]
+#[cfg_attr(test, macro_use)]
 extern crate serde_json;
 #[cfg(test)]
+extern crate tempdir;
+#[cfg(test)]
 extern crate tokio;
 #[cfg(test)]
 extern crate typed_headers;
@@ -33,31 +37,38 @@ extern crate edgelet_core;
 extern crate edgelet_http;
 extern crate iothubservice;
 
+mod cache;
 mod error;
 
 use std::convert::AsRef;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use failure::{Fail, ResultExt};
 use futures::future::{self, Either};
 use futures::Future;
 use percent_encoding::{percent_encode, PATH_SEGMENT_ENCODE_SET};
+use serde_json::Value;
 use url::form_urlencoded::Serializer as UrlSerializer;
 
 use edgelet_core::crypto::{KeyIdentity, KeyStore, Sign, Signature, SignatureAlgorithm};
-use edgelet_core::{AuthType, Identity, IdentityManager, IdentityOperation, IdentitySpec};
+use edgelet_core::{
+    AuthType, Identity, IdentityManager, IdentityOperation, IdentitySpec, Twin, TwinProperties,
+    TwinTarget,
+};
 use edgelet_http::client::{ClientImpl, TokenSource};
 use iothubservice::{
     AuthMechanism, AuthType as HubAuthType, DeviceClient, ErrorKind as HubErrorKind, Module,
-    ModuleOperationReason as HubReason, SymmetricKey,
+    ModuleOperationReason as HubReason, SymmetricKey, Twin as HubTwin, TwinReporter,
 };
 
+pub use cache::CachedIdentityManager;
 pub use error::{Error, ErrorKind, IdentityOperationReason};
 
 const KEY_PRIMARY: &str = "primary";
 const KEY_SECONDARY: &str = "secondary";
+const REPORT_TOKEN_LIFETIME_MINS: i64 = 5;
 
 define_encode_set! {
     pub IOTHUB_ENCODE_SET = [PATH_SEGMENT_ENCODE_SET] | { '=' }
@@ -107,6 +118,20 @@ fn convert_auth_type(hub_auth_type: HubAuthType) -> AuthType {
     }
 }
 
+fn convert_twin(hub_twin: &HubTwin) -> Twin {
+    // IoT Hub leaves out the properties a twin doesn't have
+    let properties = hub_twin.properties();
+    let desired = Some(properties.desired().clone()).filter(|desired| !desired.is_null());
+    let reported = Some(properties.reported().clone()).filter(|reported| !reported.is_null());
+
+    Twin::new(
+        i64::from(*hub_twin.version()),
+        TwinProperties::new()
+            .with_desired(desired)
+            .with_reported(reported),
+    )
+}
+
 struct State<K, C, D>
 where
     K: KeyStore,
@@ -116,6 +141,8 @@ where
 {
     key_store: K,
     client: DeviceClient<C, SasTokenSource<D>>,
+    reporter: TwinReporter,
+    token_source: SasTokenSource<D>,
 }
 
 pub struct SasTokenSource<K>
@@ -138,6 +165,24 @@ where
             key,
         }
     }
+
+    /// Gets a token for one of the modules of the device, signed with the
+    /// module's own key.
+    pub fn module_token<M>(
+        &self,
+        module_id: &str,
+        key: &M,
+        expiry: &DateTime<Utc>,
+    ) -> Result<String, Error>
+    where
+        M: Sign,
+    {
+        let audience = format!(
+            "{}/devices/{}/modules/{}",
+            self.hub_id, self.device_id, module_id
+        );
+        sas_token(&audience, key, expiry)
+    }
 }
 
 impl<K> TokenSource for SasTokenSource<K>
@@ -147,27 +192,33 @@ where
     type Error = Error;
 
     fn get(&self, expiry: &DateTime<Utc>) -> Result<String, Error> {
-        let expiry = expiry.timestamp().to_string();
         let audience = format!("{}/devices/{}", self.hub_id, self.device_id);
-
-        let resource_uri =
-            percent_encode(audience.to_lowercase().as_bytes(), IOTHUB_ENCODE_SET).to_string();
-        let sig_data = format!("{}\n{}", &resource_uri, expiry);
-
-        let signature = self
-            .key
-            .sign(SignatureAlgorithm::HMACSHA256, sig_data.as_bytes())
-            .map(|s| base64::encode(s.as_bytes()))
-            .context(ErrorKind::GetToken)?;
-
-        let token = UrlSerializer::new(format!("sr={}", resource_uri))
-            .append_pair("sig", &signature)
-            .append_pair("se", &expiry)
-            .finish();
-        Ok(token)
+        sas_token(&audience, &self.key, expiry)
     }
 }
 
+fn sas_token<K>(audience: &str, key: &K, expiry: &DateTime<Utc>) -> Result<String, Error>
+where
+    K: Sign,
+{
+    let expiry = expiry.timestamp().to_string();
+
+    let resource_uri =
+        percent_encode(audience.to_lowercase().as_bytes(), IOTHUB_ENCODE_SET).to_string();
+    let sig_data = format!("{}\n{}", &resource_uri, expiry);
+
+    let signature = key
+        .sign(SignatureAlgorithm::HMACSHA256, sig_data.as_bytes())
+        .map(|s| base64::encode(s.as_bytes()))
+        .context(ErrorKind::GetToken)?;
+
+    let token = UrlSerializer::new(format!("sr={}", resource_uri))
+        .append_pair("sig", &signature)
+        .append_pair("se", &expiry)
+        .finish();
+    Ok(token)
+}
+
 impl<K> Clone for SasTokenSource<K>
 where
     K: Sign + Clone,
@@ -199,9 +250,21 @@ where
     C: ClientImpl,
     D: 'static + Sign + Clone,
 {
-    pub fn new(key_store: K, client: DeviceClient<C, SasTokenSource<D>>) -> Self {
+    /// `reporter` and `token_source` report twin properties on behalf of the
+    /// device and its modules, which the service API used by `client` can't.
+    pub fn new(
+        key_store: K,
+        client: DeviceClient<C, SasTokenSource<D>>,
+        reporter: TwinReporter,
+        token_source: SasTokenSource<D>,
+    ) -> Self {
         HubIdentityManager {
-            state: Arc::new(State { key_store, client }),
+            state: Arc::new(State {
+                key_store,
+                client,
+                reporter,
+                token_source,
+            }),
             phantom: PhantomData,
         }
     }
@@ -260,6 +323,8 @@ where
     type ListFuture = Box<Future<Item = Vec<Self::Identity>, Error = Self::Error> + Send>;
     type GetFuture = Box<Future<Item = Option<Self::Identity>, Error = Self::Error> + Send>;
     type DeleteFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
+    type GetTwinFuture = Box<Future<Item = Twin, Error = Self::Error> + Send>;
+    type ReportPropertiesFuture = Box<Future<Item = (), Error = Self::Error> + Send>;
 
     fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture {
         // This code first creates a module in the hub with the auth type
@@ -408,21 +473,134 @@ where
             )))
         }))
     }
+
+    fn get_twin(&self, target: TwinTarget) -> Self::GetTwinFuture {
+        let twin = match target {
+            TwinTarget::Device => Either::A(self.state.client.get_twin()),
+            TwinTarget::Module(ref module_id) => {
+                Either::B(self.state.client.get_module_twin(module_id))
+            }
+        };
+
+        Box::new(twin.map(|twin| convert_twin(&twin)).map_err(|err| {
+            Error::from(
+                err.context(ErrorKind::IdentityOperation(IdentityOperation::GetTwin(
+                    target,
+                ))),
+            )
+        }))
+    }
+
+    fn report_properties(
+        &mut self,
+        target: TwinTarget,
+        reported: Value,
+    ) -> Self::ReportPropertiesFuture {
+        // The token is only needed to connect
+        let expiry = Utc::now() + Duration::minutes(REPORT_TOKEN_LIFETIME_MINS);
+        let module_id = match target {
+            TwinTarget::Device => None,
+            TwinTarget::Module(ref module_id) => Some(module_id.clone()),
+        };
+
+        let token = match module_id.clone() {
+            None => Either::A(future::result(self.state.token_source.get(&expiry))),
+            Some(module_id) => {
+                // Modules report with their own identity, so the token is
+                // signed with the key derived for the module's generation
+                let idman = self.clone();
+                Either::B(self.state.client.get_module_by_id(module_id.clone()).then(
+                    move |module| -> Result<String, Error> {
+                        let module = module.with_context(|_| {
+                            ErrorKind::IdentityOperation(IdentityOperation::GetIdentity(
+                                module_id.clone(),
+                            ))
+                        })?;
+                        let generation_id = module.generation_id().ok_or_else(|| {
+                            ErrorKind::ReportPropertiesWithReason(
+                                module_id.clone(),
+                                IdentityOperationReason::MissingGenerationId,
+                            )
+                        })?;
+                        let key = idman
+                            .state
+                            .key_store
+                            .get(
+                                &KeyIdentity::Module(module_id.clone()),
+                                &build_key_name(KEY_PRIMARY, generation_id),
+                            )
+                            .context(ErrorKind::CannotGetKey(module_id.clone()))?;
+                        idman
+                            .state
+                            .token_source
+                            .module_token(&module_id, &key, &expiry)
+                    },
+                ))
+            }
+        };
+
+        let reporter = self.state.reporter.clone();
+        Box::new(token.and_then(move |token| {
+            reporter
+                .report(module_id.as_ref().map(AsRef::as_ref), &token, &reported)
+                .map_err(|err| {
+                    Error::from(err.context(ErrorKind::IdentityOperation(
+                        IdentityOperation::ReportProperties(target),
+                    )))
+                })
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::io::{self, Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+
     use bytes::Bytes;
     use chrono::TimeZone;
     use futures::Stream;
     use hyper::{self, Body, Method, Request, Response, StatusCode};
+    use tokio::net::TcpStream;
     use typed_headers::{mime, ContentType, HeaderMapExt};
     use url::Url;
 
     use edgelet_core::crypto::{MemoryKey, MemoryKeyStore};
     use edgelet_http::client::Client;
+    use iothubservice::{Connector, Transport};
+
+    struct UnreachableHub;
+
+    impl Connector for UnreachableHub {
+        fn connect(&self) -> Box<Future<Item = Box<Transport>, Error = io::Error> + Send> {
+            Box::new(future::err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "IoT Hub is not used in this test",
+            )))
+        }
+    }
+
+    fn token_source() -> SasTokenSource<MemoryKey> {
+        SasTokenSource::new(
+            "hub".to_string(),
+            "d1".to_string(),
+            MemoryKey::new("device"),
+        )
+    }
+
+    fn identity_manager<C>(
+        key_store: MemoryKeyStore,
+        device_client: DeviceClient<C, SasTokenSource<MemoryKey>>,
+    ) -> HubIdentityManager<MemoryKeyStore, C, MemoryKey>
+    where
+        C: ClientImpl,
+    {
+        let reporter = TwinReporter::new(UnreachableHub, "hub".to_string(), "d1".to_string());
+        HubIdentityManager::new(key_store, device_client, reporter, token_source())
+    }
 
     #[test]
     fn hub_identity_empty_prop() {
@@ -457,7 +635,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let identity_manager = HubIdentityManager::new(key_store, device_client);
+        let identity_manager = identity_manager(key_store, device_client);
         let (pkey, skey) = identity_manager.get_key_pair("m1", "g1").unwrap();
 
         assert_eq!(pkey.as_ref(), &Bytes::from("pkey"));
@@ -479,7 +657,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let identity_manager = HubIdentityManager::new(key_store, device_client);
+        let identity_manager = identity_manager(key_store, device_client);
         identity_manager.get_key_pair("m1", "g1").unwrap();
     }
 
@@ -504,7 +682,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let identity_manager = HubIdentityManager::new(key_store, device_client);
+        let identity_manager = identity_manager(key_store, device_client);
         identity_manager.get_key_pair("m1", "g1").unwrap();
     }
 
@@ -529,7 +707,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let identity_manager = HubIdentityManager::new(key_store, device_client);
+        let identity_manager = identity_manager(key_store, device_client);
         identity_manager.get_key_pair("m1", "g1").unwrap();
     }
 
@@ -614,7 +792,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let mut identity_manager = HubIdentityManager::new(key_store, device_client);
+        let mut identity_manager = identity_manager(key_store, device_client);
         let task = identity_manager.create(IdentitySpec::new("m1".to_string()));
 
         let hub_identity = tokio::runtime::current_thread::Runtime::new()
@@ -729,7 +907,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let identity_manager = HubIdentityManager::new(key_store, device_client);
+        let identity_manager = identity_manager(key_store, device_client);
         let task = identity_manager.list();
 
         let hub_identities = tokio::runtime::current_thread::Runtime::new()
@@ -811,7 +989,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let identity_manager = HubIdentityManager::new(key_store, device_client);
+        let identity_manager = identity_manager(key_store, device_client);
         let task = identity_manager.get(IdentitySpec::new("m1".to_string()));
 
         let hub_identity = tokio::runtime::current_thread::Runtime::new()
@@ -847,7 +1025,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let identity_manager = HubIdentityManager::new(key_store, device_client);
+        let identity_manager = identity_manager(key_store, device_client);
         let task = identity_manager.get(IdentitySpec::new("m1".to_string()));
 
         let hub_identity = tokio::runtime::current_thread::Runtime::new()
@@ -878,7 +1056,7 @@ mod tests {
         let client = Client::new(handler, Some(token_source), api_version, host_name).unwrap();
         let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
 
-        let mut identity_manager = HubIdentityManager::new(key_store, device_client);
+        let mut identity_manager = identity_manager(key_store, device_client);
         let task = identity_manager
             .delete(IdentitySpec::new("m1".to_string()))
             .then(|result: Result<(), _>| result);
@@ -889,6 +1067,117 @@ mod tests {
             .unwrap();
     }
 
+    /// Accepts one connection, refuses it and returns the password the
+    /// reporter tried to connect with.
+    fn run_hub() -> (SocketAddr, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let hub = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut byte = [0_u8; 1];
+            stream.read_exact(&mut byte).unwrap();
+            assert_eq!(0x10, byte[0]);
+            let mut length = 0;
+            let mut shift = 0;
+            loop {
+                stream.read_exact(&mut byte).unwrap();
+                length |= usize::from(byte[0] & 0x7f) << shift;
+                if byte[0] & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+            }
+            let mut body = vec![0_u8; length];
+            stream.read_exact(&mut body).unwrap();
+
+            // skip the protocol name, level, flags and keep alive; then the
+            // client ID, username and password follow
+            let mut rest = &body[10..];
+            let mut strings = vec![];
+            for _ in 0..3 {
+                let length = usize::from(rest[0]) << 8 | usize::from(rest[1]);
+                strings.push(String::from_utf8(rest[2..2 + length].to_vec()).unwrap());
+                rest = &rest[2 + length..];
+            }
+
+            // 5: not authorized
+            stream.write_all(&[0x20, 2, 0, 5]).unwrap();
+            strings.pop().unwrap()
+        });
+
+        (addr, hub)
+    }
+
+    struct LocalHub(SocketAddr);
+
+    impl Connector for LocalHub {
+        fn connect(&self) -> Box<Future<Item = Box<Transport>, Error = io::Error> + Send> {
+            Box::new(TcpStream::connect(&self.0).map(|stream| Box::new(stream) as Box<Transport>))
+        }
+    }
+
+    #[test]
+    fn report_module_properties_signs_with_module_key() {
+        let mut key_store = MemoryKeyStore::new();
+        key_store.insert(
+            &KeyIdentity::Module("m1".to_string()),
+            &format!("{}{}", KEY_PRIMARY, "g1"),
+            MemoryKey::new("pkey"),
+        );
+
+        let api_version = "2018-04-10".to_string();
+        let host_name = Url::parse("http://localhost").unwrap();
+        let handler = |req: Request<Body>| {
+            assert_eq!(req.method(), &Method::GET);
+            assert_eq!(req.uri().path(), "/devices/d1/modules/m1");
+
+            let module = Module::default()
+                .with_device_id("d1".to_string())
+                .with_module_id("m1".to_string())
+                .with_generation_id("g1".to_string());
+            let mut response = Response::new(serde_json::to_string(&module).unwrap().into());
+            response
+                .headers_mut()
+                .typed_insert(&ContentType(mime::APPLICATION_JSON));
+            Ok(response)
+        };
+        let client = Client::new(handler, Some(token_source()), api_version, host_name).unwrap();
+        let device_client = DeviceClient::new(client, "d1".to_string()).unwrap();
+
+        let (addr, hub) = run_hub();
+        let reporter = TwinReporter::new(LocalHub(addr), "hub".to_string(), "d1".to_string());
+        let mut identity_manager =
+            HubIdentityManager::new(key_store, device_client, reporter, token_source());
+        let task = identity_manager.report_properties(
+            TwinTarget::Module("m1".to_string()),
+            json!({ "version": "1.0" }),
+        );
+
+        let err = tokio::runtime::current_thread::Runtime::new()
+            .unwrap()
+            .block_on(task)
+            .unwrap_err();
+        if let ErrorKind::IdentityOperation(IdentityOperation::ReportProperties(target)) =
+            err.kind()
+        {
+            assert_eq!(&TwinTarget::Module("m1".to_string()), target);
+        } else {
+            panic!("Unexpected error {}", err);
+        }
+
+        // the module connects with its own identity
+        let password = hub.join().unwrap();
+        let token = password.trim_start_matches("SharedAccessSignature ");
+        let expiry = token.rsplit("&se=").next().unwrap().parse().unwrap();
+        let expected = token_source()
+            .module_token("m1", &MemoryKey::new("pkey"), &Utc.timestamp(expiry, 0))
+            .unwrap();
+        assert_eq!(expected, token);
+        assert!(token.starts_with("sr=hub%2Fdevices%2Fd1%2Fmodules%2Fm1&"));
+    }
+
     #[test]
     fn token_source_success() {
         // arrange
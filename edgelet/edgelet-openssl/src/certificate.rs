@@ -0,0 +1,348 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::cmp;
+use std::ptr;
+
+use chrono::{DateTime, TimeZone, Utc};
+use failure::{Fail, ResultExt};
+use foreign_types::ForeignType;
+use libc::c_long;
+use openssl::asn1::{Asn1Time, Asn1TimeRef};
+use openssl::bn::{BigNum, MsbOption};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use openssl::x509::{X509Builder, X509NameBuilder, X509Ref, X509};
+use openssl_sys;
+
+use edgelet_core::{
+    Certificate as CoreCertificate, CertificateProperties, CertificateType, Error as CoreError,
+    ErrorKind as CoreErrorKind, KeyBytes, PrivateKey,
+};
+
+use edgelet_pkcs11::Pkcs11PrivateKey;
+
+use error::{Error, ErrorKind};
+
+/// The longest validity `X509_gmtime_adj` can handle on every platform,
+/// since `long` is 32 bits wide on Windows.
+const MAX_VALIDITY_SECS: i64 = 2_147_483_647;
+
+/// The format of `Asn1Time` when it is displayed.
+const ASN1_TIME_FORMAT: &str = "%b %e %H:%M:%S %Y GMT";
+
+/// A certificate followed by the chain of certificates that issued it, in PEM
+/// format.
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    pem: String,
+    private_key: Option<Vec<u8>>,
+    valid_to: DateTime<Utc>,
+}
+
+impl Certificate {
+    pub(crate) fn new(pem: String, private_key: Option<Vec<u8>>) -> Result<Self, Error> {
+        let cert = X509::from_pem(pem.as_bytes()).context(ErrorKind::Openssl)?;
+        let valid_to = valid_to(&cert)?;
+        Ok(Certificate {
+            pem,
+            private_key,
+            valid_to,
+        })
+    }
+}
+
+impl CoreCertificate for Certificate {
+    type Buffer = String;
+    type KeyBuffer = Vec<u8>;
+
+    fn pem(&self) -> Result<Self::Buffer, CoreError> {
+        Ok(self.pem.clone())
+    }
+
+    fn get_private_key(&self) -> Result<Option<PrivateKey<Self::KeyBuffer>>, CoreError> {
+        Ok(self
+            .private_key
+            .clone()
+            .map(|key| PrivateKey::Key(KeyBytes::Pem(key))))
+    }
+
+    fn get_valid_to(&self) -> Result<DateTime<Utc>, CoreError> {
+        Ok(self.valid_to)
+    }
+}
+
+/// A certificate that other certificates can be issued by.
+pub(crate) struct Issuer {
+    pub(crate) alias: String,
+    pub(crate) cert: X509,
+    pub(crate) chain_pem: String,
+    pub(crate) key: IssuerKey,
+}
+
+/// The private key of an issuer, which is either held by OpenSSL or kept on
+/// a PKCS#11 token.
+pub(crate) enum IssuerKey {
+    Software(PKey<Private>),
+    Pkcs11(Pkcs11PrivateKey),
+}
+
+pub(crate) fn valid_to(cert: &X509Ref) -> Result<DateTime<Utc>, Error> {
+    parse_asn1_time(cert.not_after())
+}
+
+fn parse_asn1_time(time: &Asn1TimeRef) -> Result<DateTime<Utc>, Error> {
+    let valid_to = Utc
+        .datetime_from_str(&time.to_string(), ASN1_TIME_FORMAT)
+        .context(ErrorKind::Openssl)?;
+    Ok(valid_to)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SanKind {
+    Dns,
+    Email,
+    Ip,
+    Uri,
+}
+
+/// Parses subject alternative names of the form `"DNS: example.com"`, which
+/// is how edgelet passes them to the HSM.
+fn parse_san_entry(entry: &str) -> Result<(SanKind, &str), Error> {
+    let invalid = || Error::from(ErrorKind::InvalidSanEntry(entry.to_string()));
+
+    let separator = entry.find(':').ok_or_else(invalid)?;
+    let value = entry[separator + 1..].trim();
+    let kind = match entry[..separator].trim().to_uppercase().as_str() {
+        "DNS" => SanKind::Dns,
+        "EMAIL" => SanKind::Email,
+        "IP" => SanKind::Ip,
+        "URI" => SanKind::Uri,
+        _ => return Err(invalid()),
+    };
+    if value.is_empty() {
+        Err(invalid())
+    } else {
+        Ok((kind, value))
+    }
+}
+
+/// Generates a key pair and a certificate for it. The certificate is
+/// self-signed when there is no issuer, and never outlives its issuer.
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_wrap, cast_sign_loss))]
+pub(crate) fn issue(
+    properties: &CertificateProperties,
+    issuer: Option<&Issuer>,
+) -> Result<(X509, PKey<Private>), Error> {
+    let san_entries = properties
+        .san_entries()
+        .unwrap_or(&[])
+        .iter()
+        .map(|entry| parse_san_entry(entry))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut validity = cmp::min(*properties.validity_in_secs(), MAX_VALIDITY_SECS as u64) as i64;
+    if let Some(issuer) = issuer {
+        let remaining = valid_to(&issuer.cert)?
+            .signed_duration_since(Utc::now())
+            .num_seconds();
+        if remaining <= 0 {
+            return Err(Error::from(ErrorKind::IssuerExpired(issuer.alias.clone())));
+        }
+        validity = cmp::min(validity, remaining);
+    }
+
+    let (mut builder, key) =
+        build(properties, &san_entries, validity, issuer).context(ErrorKind::Openssl)?;
+    let cert = match issuer.map(|issuer| &issuer.key) {
+        None => {
+            builder
+                .sign(&key, MessageDigest::sha256())
+                .context(ErrorKind::Openssl)?;
+            builder.build()
+        }
+        Some(&IssuerKey::Software(ref issuer_key)) => {
+            builder
+                .sign(issuer_key, MessageDigest::sha256())
+                .context(ErrorKind::Openssl)?;
+            builder.build()
+        }
+        Some(&IssuerKey::Pkcs11(ref issuer_key)) => {
+            issuer_key.sign_x509(builder).context(ErrorKind::Pkcs11)?
+        }
+    };
+    Ok((cert, key))
+}
+
+fn build(
+    properties: &CertificateProperties,
+    san_entries: &[(SanKind, &str)],
+    validity: i64,
+    issuer: Option<&Issuer>,
+) -> Result<(X509Builder, PKey<Private>), ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_nid(Nid::COMMONNAME, properties.common_name())?;
+    let name = name.build();
+
+    let serial_number = {
+        let mut serial_number = BigNum::new()?;
+        serial_number.rand(127, MsbOption::MAYBE_ZERO, false)?;
+        serial_number.to_asn1_integer()?
+    };
+
+    let issuer_cert = issuer.map(|issuer| &*issuer.cert);
+
+    let mut builder = X509Builder::new()?;
+    builder.set_version(2)?;
+    builder.set_serial_number(&serial_number)?;
+    builder.set_subject_name(&name)?;
+    builder.set_issuer_name(issuer_cert.map_or(&*name, |cert| cert.subject_name()))?;
+    builder.set_pubkey(&key)?;
+    builder.set_not_before(&asn1_time_from_now(0)?)?;
+    builder.set_not_after(&asn1_time_from_now(validity)?)?;
+
+    match *properties.certificate_type() {
+        CertificateType::Ca => {
+            builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+            builder.append_extension(
+                KeyUsage::new()
+                    .critical()
+                    .digital_signature()
+                    .key_cert_sign()
+                    .crl_sign()
+                    .build()?,
+            )?;
+        }
+        CertificateType::Server => {
+            builder.append_extension(BasicConstraints::new().critical().build()?)?;
+            builder.append_extension(
+                KeyUsage::new()
+                    .critical()
+                    .digital_signature()
+                    .key_encipherment()
+                    .key_agreement()
+                    .build()?,
+            )?;
+            builder.append_extension(ExtendedKeyUsage::new().server_auth().build()?)?;
+        }
+        CertificateType::Client | CertificateType::Unknown => {
+            builder.append_extension(BasicConstraints::new().critical().build()?)?;
+            builder.append_extension(
+                KeyUsage::new()
+                    .critical()
+                    .digital_signature()
+                    .key_agreement()
+                    .build()?,
+            )?;
+            builder.append_extension(ExtendedKeyUsage::new().client_auth().build()?)?;
+        }
+    }
+
+    if !san_entries.is_empty() {
+        let mut subject_alt_name = SubjectAlternativeName::new();
+        for &(kind, value) in san_entries {
+            match kind {
+                SanKind::Dns => subject_alt_name.dns(value),
+                SanKind::Email => subject_alt_name.email(value),
+                SanKind::Ip => subject_alt_name.ip(value),
+                SanKind::Uri => subject_alt_name.uri(value),
+            };
+        }
+        let subject_alt_name =
+            subject_alt_name.build(&builder.x509v3_context(issuer_cert, None))?;
+        builder.append_extension(subject_alt_name)?;
+    }
+
+    let subject_key_identifier =
+        SubjectKeyIdentifier::new().build(&builder.x509v3_context(issuer_cert, None))?;
+    builder.append_extension(subject_key_identifier)?;
+    if issuer_cert.is_some() {
+        let authority_key_identifier = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&builder.x509v3_context(issuer_cert, None))?;
+        builder.append_extension(authority_key_identifier)?;
+    }
+
+    Ok((builder, key))
+}
+
+/// `Asn1Time` can only be created a whole number of days from now, which is
+/// too coarse for short-lived certificates.
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+fn asn1_time_from_now(secs: i64) -> Result<Asn1Time, ErrorStack> {
+    let secs = cmp::min(secs, MAX_VALIDITY_SECS) as c_long;
+    unsafe {
+        let time = openssl_sys::X509_gmtime_adj(ptr::null_mut(), secs);
+        if time.is_null() {
+            Err(ErrorStack::get())
+        } else {
+            Ok(Asn1Time::from_ptr(time))
+        }
+    }
+}
+
+pub(crate) fn to_core_error(err: Error) -> CoreError {
+    CoreError::from(err.context(CoreErrorKind::KeyStore))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use edgelet_core::CertificateIssuer;
+    use openssl::x509::X509VerifyResult;
+
+    fn properties(certificate_type: CertificateType) -> CertificateProperties {
+        CertificateProperties::new(
+            3600,
+            "test".to_string(),
+            certificate_type,
+            "test".to_string(),
+        )
+        .with_issuer(CertificateIssuer::DeviceCa)
+    }
+
+    #[test]
+    fn parse_san_entries() {
+        assert_eq!(
+            (SanKind::Uri, "bar:://pity/foo"),
+            parse_san_entry("URI: bar:://pity/foo").unwrap()
+        );
+        assert_eq!(
+            (SanKind::Dns, "foo.bar"),
+            parse_san_entry("dns:foo.bar").unwrap()
+        );
+        assert!(parse_san_entry("foo.bar").is_err());
+        assert!(parse_san_entry("RID: 1.2.3").is_err());
+        assert!(parse_san_entry("DNS: ").is_err());
+    }
+
+    #[test]
+    fn issued_certificate_never_outlives_issuer() {
+        let (cert, key) = issue(&properties(CertificateType::Ca), None).unwrap();
+        let issuer = Issuer {
+            alias: "ca".to_string(),
+            chain_pem: String::from_utf8(cert.to_pem().unwrap()).unwrap(),
+            cert,
+            key: IssuerKey::Software(key),
+        };
+
+        let (cert, _) = issue(
+            &properties(CertificateType::Server).with_validity_in_secs(7200),
+            Some(&issuer),
+        )
+        .unwrap();
+
+        assert!(valid_to(&cert).unwrap() <= valid_to(&issuer.cert).unwrap());
+        assert_eq!(X509VerifyResult::OK, issuer.cert.issued(&cert));
+    }
+}
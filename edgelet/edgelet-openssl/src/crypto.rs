@@ -0,0 +1,714 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{Duration, Utc};
+use failure::{Fail, ResultExt};
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::rand::rand_bytes;
+use openssl::sign::Signer;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use openssl::x509::{X509VerifyResult, X509};
+
+use edgelet_core::{
+    AliasedEncryptionKey as CoreAliasedEncryptionKey, CertificateIssuer, CertificateProperties,
+    CertificateType, CreateCertificate as CoreCreateCertificate, Decrypt as CoreDecrypt,
+    Encrypt as CoreEncrypt, Error as CoreError, GetTrustBundle as CoreGetTrustBundle,
+    MasterEncryptionKey as CoreMasterEncryptionKey, IOTEDGED_CA_ALIAS,
+};
+use edgelet_pkcs11::Pkcs11PrivateKey;
+
+use certificate::{issue, to_core_error, valid_to, Certificate, Issuer, IssuerKey};
+use error::{Error, ErrorKind};
+
+const CERTS_DIR: &str = "certs";
+const KEYS_DIR: &str = "keys";
+const DEVICE_CA_DIR: &str = "device_ca";
+const CERT_FILE: &str = "cert.pem";
+const KEY_FILE: &str = "key.pem";
+const MASTER_KEY_FILE: &str = "master.key";
+const ALIASED_KEY_EXTENSION: &str = "enc.key";
+
+const DEVICE_CA_ALIAS: &str = "device_ca";
+const DEVICE_CA_COMMON_NAME: &str = "iotedged quickstart device ca";
+const DEVICE_CA_VALIDITY_SECS: u64 = 90 * 24 * 60 * 60;
+/// The generated device CA certificate is replaced this long before it
+/// expires, so the certificates it issues aren't cut short.
+const DEVICE_CA_RENEW_SECS: i64 = 24 * 60 * 60;
+
+const MASTER_KEY_LEN: usize = 32;
+const CIPHERTEXT_VERSION: u8 = 1;
+const TAG_LEN: usize = 16;
+
+enum DeviceCa {
+    /// A self-signed certificate that is generated in the store.
+    Generated,
+    /// A certificate chain and key provided by the owner of the device.
+    Files {
+        cert: PathBuf,
+        key: DeviceCaKey,
+        trusted_ca_certs: PathBuf,
+    },
+}
+
+enum DeviceCaKey {
+    File(PathBuf),
+    Pkcs11(Pkcs11PrivateKey),
+}
+
+/// A certificate chain and the private key of the first certificate in it,
+/// as they are kept in the store.
+struct Stored {
+    cert: X509,
+    chain_pem: String,
+    key: PKey<Private>,
+}
+
+impl Stored {
+    fn into_issuer(self, alias: &str) -> Issuer {
+        Issuer {
+            alias: alias.to_string(),
+            cert: self.cert,
+            chain_pem: self.chain_pem,
+            key: IssuerKey::Software(self.key),
+        }
+    }
+}
+
+struct Store {
+    path: PathBuf,
+    device_ca: DeviceCa,
+}
+
+/// Keeps certificates, keys and the master encryption key in files under a
+/// directory, for devices without a hardware security module.
+///
+/// Private keys are only protected by the permissions of their files.
+#[derive(Clone)]
+pub struct Crypto {
+    store: Arc<Mutex<Store>>,
+}
+
+impl Crypto {
+    /// Opens the store at `path`. Certificates are issued by a self-signed
+    /// device CA certificate that is generated in the store, which is only
+    /// meant for getting started and for devices that are not gateways.
+    pub fn new(path: PathBuf) -> Result<Self, Error> {
+        Crypto::with_device_ca(path, DeviceCa::Generated)
+    }
+
+    /// Opens the store at `path`. Certificates are issued by the device CA
+    /// certificate chain and private key in the given files, and the trust
+    /// bundle is read from `trusted_ca_certs`.
+    pub fn from_device_ca(
+        path: PathBuf,
+        device_ca_cert: PathBuf,
+        device_ca_pk: PathBuf,
+        trusted_ca_certs: PathBuf,
+    ) -> Result<Self, Error> {
+        Crypto::with_device_ca(
+            path,
+            DeviceCa::Files {
+                cert: device_ca_cert,
+                key: DeviceCaKey::File(device_ca_pk),
+                trusted_ca_certs,
+            },
+        )
+    }
+
+    /// Like `from_device_ca`, but the private key of the device CA
+    /// certificate is kept on a PKCS#11 token and never leaves it.
+    pub fn from_device_ca_pkcs11(
+        path: PathBuf,
+        device_ca_cert: PathBuf,
+        device_ca_pk: Pkcs11PrivateKey,
+        trusted_ca_certs: PathBuf,
+    ) -> Result<Self, Error> {
+        Crypto::with_device_ca(
+            path,
+            DeviceCa::Files {
+                cert: device_ca_cert,
+                key: DeviceCaKey::Pkcs11(device_ca_pk),
+                trusted_ca_certs,
+            },
+        )
+    }
+
+    fn with_device_ca(path: PathBuf, device_ca: DeviceCa) -> Result<Self, Error> {
+        for dir in &[CERTS_DIR, KEYS_DIR, DEVICE_CA_DIR] {
+            create_dir(&path.join(dir)).context(ErrorKind::Store)?;
+        }
+
+        let store = Store { path, device_ca };
+        // Fail early rather than on the first certificate request.
+        store.device_ca()?;
+
+        Ok(Crypto {
+            store: Arc::new(Mutex::new(store)),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<Store> {
+        self.store.lock().expect("Lock on crypto store failed")
+    }
+}
+
+impl Store {
+    fn device_ca(&self) -> Result<Issuer, Error> {
+        match self.device_ca {
+            DeviceCa::Files {
+                ref cert,
+                key: DeviceCaKey::File(ref key),
+                ..
+            } => {
+                let stored = read_stored(cert, key).context(ErrorKind::DeviceCa)?;
+                let stored = stored.ok_or(ErrorKind::DeviceCa)?;
+                Ok(stored.into_issuer(DEVICE_CA_ALIAS))
+            }
+            DeviceCa::Files {
+                ref cert,
+                key: DeviceCaKey::Pkcs11(ref key),
+                ..
+            } => {
+                let chain_pem = fs::read_to_string(cert).context(ErrorKind::DeviceCa)?;
+                let cert = X509::from_pem(chain_pem.as_bytes()).context(ErrorKind::DeviceCa)?;
+                Ok(Issuer {
+                    alias: DEVICE_CA_ALIAS.to_string(),
+                    cert,
+                    chain_pem,
+                    key: IssuerKey::Pkcs11(key.clone()),
+                })
+            }
+            DeviceCa::Generated => {
+                let cert_path = self.path.join(DEVICE_CA_DIR).join(CERT_FILE);
+                let key_path = self.path.join(DEVICE_CA_DIR).join(KEY_FILE);
+                let renew_at = Utc::now() + Duration::seconds(DEVICE_CA_RENEW_SECS);
+                if let Some(stored) =
+                    read_stored(&cert_path, &key_path).context(ErrorKind::DeviceCa)?
+                {
+                    if valid_to(&stored.cert)? > renew_at {
+                        return Ok(stored.into_issuer(DEVICE_CA_ALIAS));
+                    }
+                }
+
+                let properties = CertificateProperties::new(
+                    DEVICE_CA_VALIDITY_SECS,
+                    DEVICE_CA_COMMON_NAME.to_string(),
+                    CertificateType::Ca,
+                    DEVICE_CA_ALIAS.to_string(),
+                );
+                let (cert, key) = issue(&properties, None).context(ErrorKind::DeviceCa)?;
+                let stored = Stored {
+                    chain_pem: to_pem(&cert)?,
+                    cert,
+                    key,
+                };
+                write_stored(&stored, &cert_path, &key_path).context(ErrorKind::DeviceCa)?;
+                Ok(stored.into_issuer(DEVICE_CA_ALIAS))
+            }
+        }
+    }
+
+    fn paths(&self, alias: &str) -> Result<(PathBuf, PathBuf), Error> {
+        validate_alias(alias)?;
+        let file_name = format!("{}.pem", alias);
+        Ok((
+            self.path.join(CERTS_DIR).join(&file_name),
+            self.path.join(KEYS_DIR).join(&file_name),
+        ))
+    }
+
+    fn create_certificate(&self, properties: &CertificateProperties) -> Result<Certificate, Error> {
+        validate_properties(properties)?;
+        let (cert_path, key_path) = self.paths(properties.alias())?;
+
+        let issuer = match *properties.issuer() {
+            CertificateIssuer::DeviceCa => self.device_ca()?,
+            CertificateIssuer::DefaultCa => {
+                let (issuer_cert, issuer_key) = self.paths(IOTEDGED_CA_ALIAS)?;
+                read_stored(&issuer_cert, &issuer_key)?
+                    .ok_or_else(|| ErrorKind::IssuerNotFound(IOTEDGED_CA_ALIAS.to_string()))?
+                    .into_issuer(IOTEDGED_CA_ALIAS)
+            }
+        };
+
+        // Like the HSM, hand out the certificate that was created for the
+        // alias before, as long as it is still good.
+        if let Some(existing) = read_stored(&cert_path, &key_path)? {
+            if valid_to(&existing.cert)? > Utc::now()
+                && issuer.cert.issued(&existing.cert) == X509VerifyResult::OK
+            {
+                let key = existing
+                    .key
+                    .private_key_to_pem_pkcs8()
+                    .context(ErrorKind::Openssl)?;
+                return Certificate::new(existing.chain_pem, Some(key));
+            }
+        }
+
+        let (cert, key) = issue(properties, Some(&issuer))?;
+        let issued = Stored {
+            chain_pem: to_pem(&cert)? + &issuer.chain_pem,
+            cert,
+            key,
+        };
+        write_stored(&issued, &cert_path, &key_path)?;
+
+        let key = issued
+            .key
+            .private_key_to_pem_pkcs8()
+            .context(ErrorKind::Openssl)?;
+        Certificate::new(issued.chain_pem, Some(key))
+    }
+
+    fn destroy_certificate(&self, alias: &str) -> Result<(), Error> {
+        let (cert_path, key_path) = self.paths(alias)?;
+        remove_file(&cert_path).context(ErrorKind::Store)?;
+        remove_file(&key_path).context(ErrorKind::Store)?;
+        Ok(())
+    }
+
+    fn get_trust_bundle(&self) -> Result<Certificate, Error> {
+        match self.device_ca {
+            DeviceCa::Files {
+                ref trusted_ca_certs,
+                ..
+            } => {
+                let pem = fs::read_to_string(trusted_ca_certs).context(ErrorKind::TrustBundle)?;
+                let bundle = Certificate::new(pem, None).context(ErrorKind::TrustBundle)?;
+                Ok(bundle)
+            }
+            DeviceCa::Generated => {
+                let device_ca = self.device_ca()?;
+                Certificate::new(device_ca.chain_pem, None)
+            }
+        }
+    }
+
+    fn master_key_path(&self) -> PathBuf {
+        self.path.join(KEYS_DIR).join(MASTER_KEY_FILE)
+    }
+
+    /// Aliased encryption keys are kept next to the master key.
+    fn aliased_key_path(&self, alias: &str) -> Result<PathBuf, Error> {
+        validate_alias(alias)?;
+        Ok(self
+            .path
+            .join(KEYS_DIR)
+            .join(format!("{}.{}", alias, ALIASED_KEY_EXTENSION)))
+    }
+
+    fn create_master_key(&self) -> Result<(), Error> {
+        create_encryption_key(&self.master_key_path())
+    }
+
+    fn destroy_master_key(&self) -> Result<(), Error> {
+        remove_file(&self.master_key_path()).context(ErrorKind::Store)?;
+        Ok(())
+    }
+
+    fn create_aliased_key(&self, alias: &str) -> Result<(), Error> {
+        create_encryption_key(&self.aliased_key_path(alias)?)
+    }
+
+    fn destroy_aliased_key(&self, alias: &str) -> Result<(), Error> {
+        remove_file(&self.aliased_key_path(alias)?).context(ErrorKind::Store)?;
+        Ok(())
+    }
+
+    /// Every client gets a key of its own, derived from the encryption key
+    /// with the given alias, or from the master key.
+    fn client_key(&self, alias: Option<&str>, client_id: &[u8]) -> Result<Vec<u8>, Error> {
+        let path = match alias {
+            Some(alias) => self.aliased_key_path(alias)?,
+            None => self.master_key_path(),
+        };
+        let encryption_key = match fs::read(path) {
+            Ok(key) => key,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(Error::from(match alias {
+                    Some(alias) => ErrorKind::EncryptionKeyNotFound(alias.to_string()),
+                    None => ErrorKind::MasterKeyNotFound,
+                }))
+            }
+            Err(err) => return Err(Error::from(err.context(ErrorKind::Store))),
+        };
+
+        let key = PKey::hmac(&encryption_key)
+            .and_then(|key| {
+                let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+                signer.update(client_id)?;
+                signer.sign_to_vec()
+            })
+            .context(ErrorKind::Openssl)?;
+        Ok(key)
+    }
+
+    fn encrypt(
+        &self,
+        alias: Option<&str>,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if initialization_vector.is_empty() {
+            return Err(Error::from(ErrorKind::InvalidInitializationVector));
+        }
+        let key = self.client_key(alias, client_id)?;
+
+        let mut tag = [0; TAG_LEN];
+        let encrypted = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(initialization_vector),
+            &[],
+            plaintext,
+            &mut tag,
+        )
+        .context(ErrorKind::Openssl)?;
+
+        let mut ciphertext = Vec::with_capacity(1 + TAG_LEN + encrypted.len());
+        ciphertext.push(CIPHERTEXT_VERSION);
+        ciphertext.extend_from_slice(&tag);
+        ciphertext.extend_from_slice(&encrypted);
+        Ok(ciphertext)
+    }
+
+    fn decrypt(
+        &self,
+        alias: Option<&str>,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if initialization_vector.is_empty() {
+            return Err(Error::from(ErrorKind::InvalidInitializationVector));
+        }
+        if ciphertext.len() < 1 + TAG_LEN || ciphertext[0] != CIPHERTEXT_VERSION {
+            return Err(Error::from(ErrorKind::InvalidCiphertext));
+        }
+        let key = self.client_key(alias, client_id)?;
+
+        let (tag, encrypted) = ciphertext[1..].split_at(TAG_LEN);
+        let plaintext = decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(initialization_vector),
+            &[],
+            encrypted,
+            tag,
+        )
+        .context(ErrorKind::InvalidCiphertext)?;
+        Ok(plaintext)
+    }
+}
+
+impl CoreCreateCertificate for Crypto {
+    type Certificate = Certificate;
+
+    fn create_certificate(
+        &self,
+        properties: &CertificateProperties,
+    ) -> Result<Self::Certificate, CoreError> {
+        self.lock()
+            .create_certificate(properties)
+            .map_err(to_core_error)
+    }
+
+    fn destroy_certificate(&self, alias: String) -> Result<(), CoreError> {
+        self.lock()
+            .destroy_certificate(&alias)
+            .map_err(to_core_error)
+    }
+}
+
+impl CoreGetTrustBundle for Crypto {
+    type Certificate = Certificate;
+
+    fn get_trust_bundle(&self) -> Result<Self::Certificate, CoreError> {
+        self.lock().get_trust_bundle().map_err(to_core_error)
+    }
+}
+
+impl CoreMasterEncryptionKey for Crypto {
+    fn create_key(&self) -> Result<(), CoreError> {
+        self.lock().create_master_key().map_err(to_core_error)
+    }
+
+    fn destroy_key(&self) -> Result<(), CoreError> {
+        self.lock().destroy_master_key().map_err(to_core_error)
+    }
+}
+
+impl CoreEncrypt for Crypto {
+    type Buffer = Vec<u8>;
+
+    fn encrypt(
+        &self,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, CoreError> {
+        self.lock()
+            .encrypt(None, client_id, plaintext, initialization_vector)
+            .map_err(to_core_error)
+    }
+}
+
+impl CoreDecrypt for Crypto {
+    type Buffer = Vec<u8>;
+
+    fn decrypt(
+        &self,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, CoreError> {
+        self.lock()
+            .decrypt(None, client_id, ciphertext, initialization_vector)
+            .map_err(to_core_error)
+    }
+}
+
+impl CoreAliasedEncryptionKey for Crypto {
+    type Buffer = Vec<u8>;
+
+    fn create_aliased_key(&self, alias: &str) -> Result<(), CoreError> {
+        self.lock().create_aliased_key(alias).map_err(to_core_error)
+    }
+
+    fn destroy_aliased_key(&self, alias: &str) -> Result<(), CoreError> {
+        self.lock()
+            .destroy_aliased_key(alias)
+            .map_err(to_core_error)
+    }
+
+    fn encrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, CoreError> {
+        self.lock()
+            .encrypt(Some(alias), client_id, plaintext, initialization_vector)
+            .map_err(to_core_error)
+    }
+
+    fn decrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Self::Buffer, CoreError> {
+        self.lock()
+            .decrypt(Some(alias), client_id, ciphertext, initialization_vector)
+            .map_err(to_core_error)
+    }
+}
+
+fn validate_properties(properties: &CertificateProperties) -> Result<(), Error> {
+    if *properties.validity_in_secs() == 0 {
+        Err(Error::from(ErrorKind::InvalidCertificateProperties(
+            "validity must be greater than zero",
+        )))
+    } else if properties.common_name().is_empty() {
+        Err(Error::from(ErrorKind::InvalidCertificateProperties(
+            "common name must not be empty",
+        )))
+    } else if *properties.certificate_type() == CertificateType::Unknown {
+        Err(Error::from(ErrorKind::InvalidCertificateProperties(
+            "certificate type must be known",
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Aliases become file names, so they can't be empty, start with a dot or
+/// contain path separators.
+fn validate_alias(alias: &str) -> Result<(), Error> {
+    if alias.is_empty()
+        || alias.starts_with('.')
+        || alias.contains(|c: char| c == '/' || c == '\\' || c == '\0')
+    {
+        Err(Error::from(ErrorKind::InvalidAlias(alias.to_string())))
+    } else {
+        Ok(())
+    }
+}
+
+fn to_pem(cert: &X509) -> Result<String, Error> {
+    let pem = cert.to_pem().context(ErrorKind::Openssl)?;
+    let pem = String::from_utf8(pem).context(ErrorKind::Openssl)?;
+    Ok(pem)
+}
+
+/// Reads a certificate chain and the private key of the first certificate in
+/// it. Returns `None` if either file doesn't exist.
+fn read_stored(cert_path: &Path, key_path: &Path) -> Result<Option<Stored>, Error> {
+    let chain_pem = match fs::read_to_string(cert_path) {
+        Ok(pem) => pem,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(Error::from(err.context(ErrorKind::Store))),
+    };
+    let key_pem = match fs::read(key_path) {
+        Ok(pem) => pem,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(Error::from(err.context(ErrorKind::Store))),
+    };
+
+    let cert = X509::from_pem(chain_pem.as_bytes()).context(ErrorKind::Openssl)?;
+    let key = PKey::private_key_from_pem(&key_pem).context(ErrorKind::Openssl)?;
+    Ok(Some(Stored {
+        cert,
+        chain_pem,
+        key,
+    }))
+}
+
+fn write_stored(stored: &Stored, cert_path: &Path, key_path: &Path) -> Result<(), Error> {
+    let key = stored
+        .key
+        .private_key_to_pem_pkcs8()
+        .context(ErrorKind::Openssl)?;
+    write_file(key_path, &key).context(ErrorKind::Store)?;
+    write_file(cert_path, stored.chain_pem.as_bytes()).context(ErrorKind::Store)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_dir(path: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(path)
+}
+
+#[cfg(not(unix))]
+fn create_dir(path: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).create(path)
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Creates a random encryption key at `path`, unless one exists already.
+fn create_encryption_key(path: &Path) -> Result<(), Error> {
+    if path.exists() {
+        return Ok(());
+    }
+
+    let mut key = [0; MASTER_KEY_LEN];
+    rand_bytes(&mut key).context(ErrorKind::Openssl)?;
+    write_file(path, &key).context(ErrorKind::Store)?;
+    Ok(())
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    // A file left over from an earlier crash keeps the permissions it was
+    // created with, so start from scratch.
+    remove_file(path)?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use edgelet_core::Certificate as CoreCertificate;
+
+    #[test]
+    fn invalid_aliases_are_rejected() {
+        for alias in &["", ".hidden", "../escape", "a\\b", "nul\0"] {
+            match validate_alias(alias) {
+                Err(ref err) if *err.kind() == ErrorKind::InvalidAlias(alias.to_string()) => (),
+                _ => panic!("alias {:?} was not rejected", alias),
+            }
+        }
+        validate_alias("iotedged-workload-ca").unwrap();
+    }
+
+    #[test]
+    fn create_reuses_certificate_until_issuer_changes() {
+        let dir = TempDir::new("crypto").unwrap();
+        let crypto = Crypto::new(dir.path().to_path_buf()).unwrap();
+        let properties = CertificateProperties::new(
+            3600,
+            "test-iotedge-cn".to_string(),
+            CertificateType::Ca,
+            IOTEDGED_CA_ALIAS.to_string(),
+        )
+        .with_issuer(CertificateIssuer::DeviceCa);
+
+        let first = crypto.create_certificate(&properties).unwrap();
+        let second = crypto.create_certificate(&properties).unwrap();
+        assert_eq!(first.pem().unwrap(), second.pem().unwrap());
+
+        // A new device CA certificate invalidates everything it issued.
+        fs::remove_file(dir.path().join(DEVICE_CA_DIR).join(CERT_FILE)).unwrap();
+        let third = crypto.create_certificate(&properties).unwrap();
+        assert_ne!(first.pem().unwrap(), third.pem().unwrap());
+    }
+
+    #[test]
+    fn encrypt_without_master_key_fails() {
+        let dir = TempDir::new("crypto").unwrap();
+        let crypto = Crypto::new(dir.path().to_path_buf()).unwrap();
+
+        let err = crypto
+            .lock()
+            .encrypt(None, b"module1", b"plaintext", b"iv")
+            .unwrap_err();
+
+        assert_eq!(&ErrorKind::MasterKeyNotFound, err.kind());
+    }
+
+    #[test]
+    fn aliased_key_outlives_master_key() {
+        let dir = TempDir::new("crypto").unwrap();
+        let crypto = Crypto::new(dir.path().to_path_buf()).unwrap();
+        crypto.create_key().unwrap();
+        crypto.create_aliased_key("master").unwrap();
+
+        let ciphertext = crypto
+            .encrypt_with_key("master", b"module1", b"plaintext", b"iv")
+            .unwrap();
+        crypto.destroy_key().unwrap();
+        assert_eq!(
+            b"plaintext".to_vec(),
+            crypto
+                .decrypt_with_key("master", b"module1", &ciphertext, b"iv")
+                .unwrap()
+        );
+
+        crypto.destroy_aliased_key("master").unwrap();
+        let err = crypto
+            .lock()
+            .decrypt(Some("master"), b"module1", &ciphertext, b"iv")
+            .unwrap_err();
+        assert_eq!(
+            &ErrorKind::EncryptionKeyNotFound("master".to_string()),
+            err.kind()
+        );
+    }
+}
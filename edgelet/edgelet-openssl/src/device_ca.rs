@@ -0,0 +1,156 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Checks of the device CA certificate that the owner of the device provides,
+//! so that mistakes in it show up at startup rather than as TLS failures in
+//! the modules that use the certificates it issues.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::{Duration, Utc};
+use failure::ResultExt;
+use foreign_types::ForeignType;
+use libc::c_int;
+use openssl::asn1::Asn1Time;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::error::ErrorStack;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::stack::Stack;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::{X509Builder, X509StoreContext, X509};
+use openssl_sys;
+
+use edgelet_pkcs11::Pkcs11PrivateKey;
+
+use certificate::valid_to;
+use error::{Error, ErrorKind};
+
+extern "C" {
+    fn X509_check_ca(x: *mut openssl_sys::X509) -> c_int;
+    fn X509_verify(x: *mut openssl_sys::X509, pkey: *mut openssl_sys::EVP_PKEY) -> c_int;
+}
+
+/// Checks that the private key in `device_ca_pk` belongs to the first
+/// certificate in `device_ca_cert`, that the certificate is a CA certificate
+/// that is valid for at least `min_validity`, and that the rest of the chain
+/// in `device_ca_cert` leads to a certificate in `trusted_ca_certs`.
+pub fn validate_device_ca(
+    device_ca_cert: &Path,
+    device_ca_pk: &Path,
+    trusted_ca_certs: &Path,
+    min_validity: Duration,
+) -> Result<(), Error> {
+    let key = fs::read(device_ca_pk).context(ErrorKind::DeviceCa)?;
+    let key = PKey::private_key_from_pem(&key).context(ErrorKind::DeviceCa)?;
+    validate(device_ca_cert, trusted_ca_certs, min_validity, |cert| {
+        let public_key = cert.public_key().context(ErrorKind::Openssl)?;
+        Ok(public_key.public_eq(&key))
+    })
+}
+
+/// Like `validate_device_ca`, but with a private key on a PKCS#11 token. The
+/// key can't be compared with the certificate directly, so it signs a
+/// throwaway certificate that the device CA certificate has to verify.
+pub fn validate_device_ca_pkcs11(
+    device_ca_cert: &Path,
+    device_ca_pk: &Pkcs11PrivateKey,
+    trusted_ca_certs: &Path,
+    min_validity: Duration,
+) -> Result<(), Error> {
+    validate(device_ca_cert, trusted_ca_certs, min_validity, |cert| {
+        let builder = throwaway_builder(cert).context(ErrorKind::Openssl)?;
+        let throwaway = device_ca_pk.sign_x509(builder).context(ErrorKind::Pkcs11)?;
+        let public_key = cert.public_key().context(ErrorKind::Openssl)?;
+        let verified = unsafe { X509_verify(throwaway.as_ptr(), public_key.as_ptr()) } == 1;
+        if !verified {
+            // Don't leave the reason on the error queue for whoever uses
+            // OpenSSL next.
+            ErrorStack::get();
+        }
+        Ok(verified)
+    })
+}
+
+fn validate<F>(
+    device_ca_cert: &Path,
+    trusted_ca_certs: &Path,
+    min_validity: Duration,
+    key_matches: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(&X509) -> Result<bool, Error>,
+{
+    let chain = fs::read(device_ca_cert).context(ErrorKind::DeviceCa)?;
+    let mut chain = X509::stack_from_pem(&chain).context(ErrorKind::DeviceCa)?;
+    if chain.is_empty() {
+        return Err(Error::from(ErrorKind::DeviceCa));
+    }
+    let cert = chain.remove(0);
+    let trusted = fs::read(trusted_ca_certs).context(ErrorKind::TrustBundle)?;
+    let trusted = X509::stack_from_pem(&trusted).context(ErrorKind::TrustBundle)?;
+
+    if !key_matches(&cert)? {
+        return Err(Error::from(ErrorKind::DeviceCaKeyMismatch));
+    }
+
+    // Unlike OpenSSL, only accept certificates that say they are a CA, not
+    // ones that merely may sign certificates or are self-signed.
+    if unsafe { X509_check_ca(cert.as_ptr()) } != 1 {
+        return Err(Error::from(ErrorKind::DeviceCaNotCa));
+    }
+
+    let valid_to = valid_to(&cert)?;
+    if valid_to <= Utc::now() + min_validity {
+        return Err(Error::from(ErrorKind::DeviceCaExpiring(valid_to)));
+    }
+
+    verify_chain(&cert, chain, trusted)
+}
+
+fn verify_chain(cert: &X509, chain: Vec<X509>, trusted: Vec<X509>) -> Result<(), Error> {
+    match verify(cert, chain, trusted).context(ErrorKind::Openssl)? {
+        None => Ok(()),
+        Some(reason) => Err(Error::from(ErrorKind::DeviceCaChain(reason.to_string()))),
+    }
+}
+
+/// Returns why `cert` could not be verified, if it couldn't.
+fn verify(
+    cert: &X509,
+    chain: Vec<X509>,
+    trusted: Vec<X509>,
+) -> Result<Option<&'static str>, ErrorStack> {
+    let mut store = X509StoreBuilder::new()?;
+    for trusted_cert in trusted {
+        store.add_cert(trusted_cert)?;
+    }
+    let store = store.build();
+
+    let mut intermediates = Stack::new()?;
+    for intermediate in chain {
+        intermediates.push(intermediate)?;
+    }
+
+    X509StoreContext::new()?.init(&store, cert, &intermediates, |context| {
+        if context.verify_cert()? {
+            Ok(None)
+        } else {
+            Ok(Some(context.error().error_string()))
+        }
+    })
+}
+
+fn throwaway_builder(issuer: &X509) -> Result<X509Builder, ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+
+    let mut builder = X509Builder::new()?;
+    builder.set_version(2)?;
+    builder.set_subject_name(issuer.subject_name())?;
+    builder.set_issuer_name(issuer.subject_name())?;
+    builder.set_pubkey(&key)?;
+    builder.set_not_before(&Asn1Time::days_from_now(0)?)?;
+    builder.set_not_after(&Asn1Time::days_from_now(1)?)?;
+    Ok(builder)
+}
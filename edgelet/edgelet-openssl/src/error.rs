@@ -0,0 +1,112 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt;
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use failure::{Backtrace, Context, Fail};
+
+#[derive(Debug)]
+pub struct Error {
+    inner: Context<ErrorKind>,
+}
+
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+pub enum ErrorKind {
+    #[fail(display = "Invalid alias {:?}", _0)]
+    InvalidAlias(String),
+
+    #[fail(display = "Invalid certificate properties: {}", _0)]
+    InvalidCertificateProperties(&'static str),
+
+    #[fail(display = "Invalid subject alternative name {:?}", _0)]
+    InvalidSanEntry(String),
+
+    #[fail(display = "Invalid ciphertext")]
+    InvalidCiphertext,
+
+    #[fail(display = "Invalid initialization vector")]
+    InvalidInitializationVector,
+
+    #[fail(display = "The issuing certificate {} was not found", _0)]
+    IssuerNotFound(String),
+
+    #[fail(display = "The issuing certificate {} has expired", _0)]
+    IssuerExpired(String),
+
+    #[fail(display = "The master encryption key has not been created")]
+    MasterKeyNotFound,
+
+    #[fail(display = "The encryption key {:?} has not been created", _0)]
+    EncryptionKeyNotFound(String),
+
+    #[fail(display = "Could not load the device CA certificate")]
+    DeviceCa,
+
+    #[fail(display = "The device CA private key does not belong to the device CA certificate")]
+    DeviceCaKeyMismatch,
+
+    #[fail(display = "The device CA certificate is not a CA certificate")]
+    DeviceCaNotCa,
+
+    #[fail(display = "The device CA certificate expires too soon, at {}", _0)]
+    DeviceCaExpiring(DateTime<Utc>),
+
+    #[fail(
+        display = "The device CA certificate chain does not lead to a trusted CA certificate: {}",
+        _0
+    )]
+    DeviceCaChain(String),
+
+    #[fail(display = "Could not load the trust bundle")]
+    TrustBundle,
+
+    #[fail(display = "Could not access the crypto store")]
+    Store,
+
+    #[fail(display = "OpenSSL failure")]
+    Openssl,
+
+    #[fail(display = "PKCS#11 failure")]
+    Pkcs11,
+}
+
+impl Fail for Error {
+    fn cause(&self) -> Option<&Fail> {
+        self.inner.cause()
+    }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        self.inner.backtrace()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl Error {
+    pub fn new(inner: Context<ErrorKind>) -> Self {
+        Error { inner }
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        self.inner.get_context()
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            inner: Context::new(kind),
+        }
+    }
+}
+
+impl From<Context<ErrorKind>> for Error {
+    fn from(inner: Context<ErrorKind>) -> Self {
+        Error { inner }
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+#![cfg_attr(feature = "cargo-clippy", allow(stutter, use_self))]
+
+//! A software implementation of the crypto traits of `edgelet-core` on top of
+//! OpenSSL, for devices that don't have the `iothsm` library.
+//!
+//! The private key of the device CA certificate can be kept on a PKCS#11
+//! token instead of in a file, see `Crypto::from_device_ca_pkcs11`.
+
+extern crate chrono;
+extern crate edgelet_core;
+extern crate edgelet_pkcs11;
+extern crate failure;
+extern crate foreign_types;
+extern crate libc;
+extern crate openssl;
+extern crate openssl_sys;
+#[cfg(test)]
+extern crate tempdir;
+
+mod certificate;
+mod crypto;
+mod device_ca;
+mod error;
+
+pub use certificate::Certificate;
+pub use crypto::Crypto;
+pub use device_ca::{validate_device_ca, validate_device_ca_pkcs11};
+pub use error::{Error, ErrorKind};
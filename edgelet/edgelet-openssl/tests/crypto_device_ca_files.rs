@@ -0,0 +1,73 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_core;
+extern crate edgelet_openssl;
+extern crate tempdir;
+
+use std::fs;
+
+use edgelet_core::{
+    Certificate, CertificateIssuer, CertificateProperties, CertificateType, CreateCertificate,
+    GetTrustBundle, IOTEDGED_CA_ALIAS,
+};
+use edgelet_openssl::Crypto;
+use tempdir::TempDir;
+
+#[test]
+fn crypto_device_ca_files() {
+    // arrange: a quick start store provides a device CA certificate to use
+    let quickstart = TempDir::new("quickstart").unwrap();
+    Crypto::new(quickstart.path().to_path_buf()).unwrap();
+    let device_ca_cert = quickstart.path().join("device_ca").join("cert.pem");
+    let device_ca_pk = quickstart.path().join("device_ca").join("key.pem");
+
+    let dir = TempDir::new("crypto").unwrap();
+    let trusted_ca_certs = dir.path().join("trusted_ca_certs.pem");
+    fs::copy(&device_ca_cert, &trusted_ca_certs).unwrap();
+
+    let crypto = Crypto::from_device_ca(
+        dir.path().join("store"),
+        device_ca_cert.clone(),
+        device_ca_pk,
+        trusted_ca_certs,
+    )
+    .unwrap();
+
+    // act
+    let props = CertificateProperties::new(
+        3600,
+        "test-iotedge-cn".to_string(),
+        CertificateType::Ca,
+        IOTEDGED_CA_ALIAS.to_string(),
+    )
+    .with_issuer(CertificateIssuer::DeviceCa);
+    let workload_ca_cert = crypto.create_certificate(&props).unwrap();
+    let trust_bundle = crypto.get_trust_bundle().unwrap();
+
+    // assert
+    let device_ca_pem = fs::read_to_string(&device_ca_cert).unwrap();
+    assert!(workload_ca_cert.pem().unwrap().ends_with(&device_ca_pem));
+    assert_eq!(device_ca_pem, trust_bundle.pem().unwrap());
+}
+
+#[test]
+fn crypto_device_ca_files_missing() {
+    let dir = TempDir::new("crypto").unwrap();
+
+    let result = Crypto::from_device_ca(
+        dir.path().join("store"),
+        dir.path().join("device_ca_cert.pem"),
+        dir.path().join("device_ca_pk.pem"),
+        dir.path().join("trusted_ca_certs.pem"),
+    );
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,23 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate edgelet_openssl;
+extern crate edgelet_test_utils;
+extern crate tempdir;
+
+use edgelet_openssl::Crypto;
+use edgelet_test_utils::crypto;
+use tempdir::TempDir;
+
+#[test]
+fn crypto_get_trust_bundle() {
+    let dir = TempDir::new("crypto").unwrap();
+    crypto::get_trust_bundle(&Crypto::new(dir.path().to_path_buf()).unwrap());
+}
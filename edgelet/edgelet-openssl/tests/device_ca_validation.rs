@@ -0,0 +1,111 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate chrono;
+extern crate edgelet_core;
+extern crate edgelet_openssl;
+extern crate tempdir;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Duration;
+use edgelet_core::{
+    Certificate, CertificateIssuer, CertificateProperties, CertificateType, CreateCertificate,
+    KeyBytes, PrivateKey,
+};
+use edgelet_openssl::{validate_device_ca, Crypto, ErrorKind};
+use tempdir::TempDir;
+
+/// Generates a quick start device CA certificate and returns the paths of it
+/// and its private key.
+fn quickstart(dir: &Path) -> (Crypto, PathBuf, PathBuf) {
+    let crypto = Crypto::new(dir.to_path_buf()).unwrap();
+    let device_ca = dir.join("device_ca");
+    (
+        crypto,
+        device_ca.join("cert.pem"),
+        device_ca.join("key.pem"),
+    )
+}
+
+#[test]
+fn device_ca_validates() {
+    let dir = TempDir::new("quickstart").unwrap();
+    let (_, cert, key) = quickstart(dir.path());
+
+    validate_device_ca(&cert, &key, &cert, Duration::days(1)).unwrap();
+}
+
+#[test]
+fn device_ca_with_other_key_fails() {
+    let dir = TempDir::new("quickstart").unwrap();
+    let (_, cert, _) = quickstart(&dir.path().join("first"));
+    let (_, _, other_key) = quickstart(&dir.path().join("second"));
+
+    let err = validate_device_ca(&cert, &other_key, &cert, Duration::days(1)).unwrap_err();
+
+    assert_eq!(&ErrorKind::DeviceCaKeyMismatch, err.kind());
+}
+
+#[test]
+fn device_ca_expiring_within_threshold_fails() {
+    let dir = TempDir::new("quickstart").unwrap();
+    let (_, cert, key) = quickstart(dir.path());
+
+    let err = validate_device_ca(&cert, &key, &cert, Duration::days(365)).unwrap_err();
+
+    match err.kind() {
+        ErrorKind::DeviceCaExpiring(_) => (),
+        kind => panic!("unexpected error {:?}", kind),
+    }
+}
+
+#[test]
+fn device_ca_from_untrusted_chain_fails() {
+    let dir = TempDir::new("quickstart").unwrap();
+    let (_, cert, key) = quickstart(&dir.path().join("first"));
+    let (_, other_cert, _) = quickstart(&dir.path().join("second"));
+
+    let err = validate_device_ca(&cert, &key, &other_cert, Duration::days(1)).unwrap_err();
+
+    match err.kind() {
+        ErrorKind::DeviceCaChain(_) => (),
+        kind => panic!("unexpected error {:?}", kind),
+    }
+}
+
+#[test]
+fn device_ca_that_is_not_a_ca_fails() {
+    // arrange: a server certificate issued by a quick start device CA
+    let dir = TempDir::new("quickstart").unwrap();
+    let (crypto, device_ca_cert, _) = quickstart(&dir.path().join("store"));
+    let props = CertificateProperties::new(
+        3600,
+        "server".to_string(),
+        CertificateType::Server,
+        "server".to_string(),
+    )
+    .with_issuer(CertificateIssuer::DeviceCa);
+    let server = crypto.create_certificate(&props).unwrap();
+    let cert = dir.path().join("server.cert.pem");
+    let key = dir.path().join("server.key.pem");
+    fs::write(&cert, server.pem().unwrap()).unwrap();
+    match server.get_private_key().unwrap() {
+        Some(PrivateKey::Key(KeyBytes::Pem(pem))) => fs::write(&key, pem).unwrap(),
+        _ => panic!("server certificate has no private key"),
+    }
+
+    // act
+    let err = validate_device_ca(&cert, &key, &device_ca_cert, Duration::days(1)).unwrap_err();
+
+    // assert
+    assert_eq!(&ErrorKind::DeviceCaNotCa, err.kind());
+}
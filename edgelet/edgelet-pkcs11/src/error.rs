@@ -0,0 +1,85 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt;
+use std::fmt::Display;
+
+use failure::{Backtrace, Context, Fail};
+use libc::c_ulong;
+
+#[derive(Debug)]
+pub struct Error {
+    inner: Context<ErrorKind>,
+}
+
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+pub enum ErrorKind {
+    #[fail(display = "Could not load PKCS#11 module {}", _0)]
+    LoadLibrary(String),
+
+    #[fail(display = "The PKCS#11 module does not implement {}", _0)]
+    MissingFunction(&'static str),
+
+    #[fail(display = "{} failed with error {:#x}", _0, _1)]
+    Call(&'static str, c_ulong),
+
+    #[fail(display = "PKCS#11 token {:?} was not found", _0)]
+    TokenNotFound(String),
+
+    #[fail(display = "PKCS#11 object {} was not found", _0)]
+    ObjectNotFound(String),
+
+    #[fail(display = "Invalid PKCS#11 URI {:?}", _0)]
+    InvalidUri(String),
+
+    #[fail(display = "Unsupported PKCS#11 key type {:#x}", _0)]
+    UnsupportedKeyType(c_ulong),
+
+    #[fail(display = "The PKCS#11 module returned an invalid signature")]
+    InvalidSignature,
+
+    #[fail(display = "Invalid certificate")]
+    InvalidCertificate,
+
+    #[fail(display = "OpenSSL failure")]
+    Openssl,
+}
+
+impl Fail for Error {
+    fn cause(&self) -> Option<&Fail> {
+        self.inner.cause()
+    }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        self.inner.backtrace()
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl Error {
+    pub fn new(inner: Context<ErrorKind>) -> Self {
+        Error { inner }
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        self.inner.get_context()
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            inner: Context::new(kind),
+        }
+    }
+}
+
+impl From<Context<ErrorKind>> for Error {
+    fn from(inner: Context<ErrorKind>) -> Self {
+        Error { inner }
+    }
+}
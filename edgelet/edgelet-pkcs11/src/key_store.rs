@@ -0,0 +1,121 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use edgelet_core::crypto::{Digest, SignatureAlgorithm};
+use edgelet_core::{
+    Activate, Error as CoreError, ErrorKind as CoreErrorKind, KeyIdentity, KeyStore, Sign,
+};
+use failure::Fail;
+
+use error::Error;
+use library::{attribute, bytes_attribute, Session};
+use sys::*;
+
+/// The label of the token object that holds a key. Objects of other
+/// applications on the same token never start with `iotedge/`.
+fn label(identity: &KeyIdentity, key_name: &str) -> String {
+    match *identity {
+        KeyIdentity::Device => format!("iotedge/device/{}", key_name),
+        KeyIdentity::Module(ref module_id) => format!("iotedge/module/{}/{}", module_id, key_name),
+    }
+}
+
+fn to_core_error(err: Error) -> CoreError {
+    CoreError::from(err.context(CoreErrorKind::KeyStore))
+}
+
+/// A `KeyStore` that keeps identity keys on a PKCS#11 token. Keys can't be
+/// read back once they are activated, they only sign.
+#[derive(Clone)]
+pub struct Pkcs11KeyStore {
+    session: Arc<Session>,
+}
+
+impl Pkcs11KeyStore {
+    pub(crate) fn new(session: Arc<Session>) -> Self {
+        Pkcs11KeyStore { session }
+    }
+
+    fn activate(&self, label: &str, key: &[u8]) -> Result<(), Error> {
+        while let Some(object) = self
+            .session
+            .find_object(CKO_SECRET_KEY, Some(label), None)?
+        {
+            self.session.destroy_object(object)?;
+        }
+
+        let class = CKO_SECRET_KEY;
+        let key_type = CKK_GENERIC_SECRET;
+        let (yes, no) = (CK_TRUE, CK_FALSE);
+        let mut template = [
+            attribute(CKA_CLASS, &class),
+            attribute(CKA_KEY_TYPE, &key_type),
+            attribute(CKA_TOKEN, &yes),
+            attribute(CKA_PRIVATE, &yes),
+            attribute(CKA_SENSITIVE, &yes),
+            attribute(CKA_SIGN, &yes),
+            attribute(CKA_EXTRACTABLE, &no),
+            bytes_attribute(CKA_LABEL, label.as_bytes()),
+            bytes_attribute(CKA_VALUE, key),
+        ];
+        self.session.create_object(&mut template)?;
+        Ok(())
+    }
+}
+
+impl Activate for Pkcs11KeyStore {
+    type Key = Pkcs11Key;
+
+    fn activate_identity_key<B: AsRef<[u8]>>(
+        &mut self,
+        identity: KeyIdentity,
+        key_name: String,
+        key: B,
+    ) -> Result<(), CoreError> {
+        self.activate(&label(&identity, &key_name), key.as_ref())
+            .map_err(to_core_error)
+    }
+}
+
+impl KeyStore for Pkcs11KeyStore {
+    type Key = Pkcs11Key;
+
+    fn get(&self, identity: &KeyIdentity, key_name: &str) -> Result<Self::Key, CoreError> {
+        let handle = self
+            .session
+            .find_object(CKO_SECRET_KEY, Some(&label(identity, key_name)), None)
+            .map_err(to_core_error)?
+            .ok_or_else(|| CoreError::from(CoreErrorKind::KeyStoreItemNotFound))?;
+        Ok(Pkcs11Key {
+            session: self.session.clone(),
+            handle,
+        })
+    }
+}
+
+/// An identity key on a PKCS#11 token.
+#[derive(Clone)]
+pub struct Pkcs11Key {
+    session: Arc<Session>,
+    handle: CK_OBJECT_HANDLE,
+}
+
+impl Sign for Pkcs11Key {
+    type Signature = Digest;
+
+    fn sign(
+        &self,
+        signature_algorithm: SignatureAlgorithm,
+        data: &[u8],
+    ) -> Result<Self::Signature, CoreError> {
+        match signature_algorithm {
+            SignatureAlgorithm::HMACSHA256 => self
+                .session
+                .sign(CKM_SHA256_HMAC, self.handle, data)
+                .map(|signature| Digest::new(Bytes::from(signature)))
+                .map_err(to_core_error),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+#![cfg_attr(feature = "cargo-clippy", allow(stutter, use_self))]
+
+//! Keys kept in secure elements and smart-card HSMs that are exposed through
+//! a PKCS#11 module, such as SoftHSM2.
+//!
+//! The module is loaded at runtime, so nothing has to be linked against it.
+//! Identity keys are stored as generic secret objects on a token and sign with
+//! HMAC-SHA256 inside of it. Private keys, such as the one of the device CA
+//! certificate, are referenced by `pkcs11:` URIs and sign certificates without
+//! ever leaving the token.
+
+extern crate bytes;
+extern crate edgelet_core;
+extern crate failure;
+extern crate libc;
+extern crate openssl;
+
+mod error;
+mod key_store;
+mod library;
+mod private_key;
+mod sys;
+mod uri;
+
+pub use error::{Error, ErrorKind};
+pub use key_store::{Pkcs11Key, Pkcs11KeyStore};
+pub use library::Pkcs11;
+pub use private_key::Pkcs11PrivateKey;
+pub use uri::{is_pkcs11_uri, Pkcs11Uri};
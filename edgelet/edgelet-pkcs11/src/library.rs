@@ -0,0 +1,429 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::mem;
+use std::path::Path;
+use std::ptr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use failure::ResultExt;
+use libc::c_void;
+
+use error::{Error, ErrorKind};
+use key_store::Pkcs11KeyStore;
+use private_key::Pkcs11PrivateKey;
+use sys::*;
+use uri::Pkcs11Uri;
+
+/// A loaded PKCS#11 module.
+///
+/// The module is initialized when it is loaded and finalized when the last
+/// clone of this and of the key stores and keys that came from it is dropped.
+#[derive(Clone)]
+pub struct Pkcs11 {
+    library: Arc<Library>,
+}
+
+impl Pkcs11 {
+    /// Loads the PKCS#11 module at `lib_path`, for example
+    /// `/usr/lib/softhsm/libsofthsm2.so`.
+    pub fn load(lib_path: &Path) -> Result<Self, Error> {
+        let library = Library::load(lib_path)?;
+        Ok(Pkcs11 {
+            library: Arc::new(library),
+        })
+    }
+
+    /// Opens the token with the given label to keep identity keys in.
+    pub fn key_store(&self, token: &str, pin: Option<&str>) -> Result<Pkcs11KeyStore, Error> {
+        let session = self.open_session(token, pin)?;
+        Ok(Pkcs11KeyStore::new(Arc::new(session)))
+    }
+
+    /// Finds the private key that `uri` refers to. A PIN in the URI takes
+    /// precedence over `pin`.
+    pub fn private_key(
+        &self,
+        uri: &Pkcs11Uri,
+        pin: Option<&str>,
+    ) -> Result<Pkcs11PrivateKey, Error> {
+        let token = uri
+            .token()
+            .ok_or_else(|| ErrorKind::InvalidUri(uri.to_string()))?;
+        if uri.object().is_none() && uri.id().is_none() {
+            return Err(Error::from(ErrorKind::InvalidUri(uri.to_string())));
+        }
+
+        let session = self.open_session(token, uri.pin_value().or(pin))?;
+        let handle = session
+            .find_object(CKO_PRIVATE_KEY, uri.object(), uri.id())?
+            .ok_or_else(|| ErrorKind::ObjectNotFound(uri.to_string()))?;
+        let key_type = session.ulong_attribute(handle, CKA_KEY_TYPE)?;
+        Pkcs11PrivateKey::new(Arc::new(session), handle, key_type)
+    }
+
+    fn open_session(&self, token: &str, pin: Option<&str>) -> Result<Session, Error> {
+        let slot = self.library.find_slot(token)?;
+        Session::open(self.library.clone(), slot, pin)
+    }
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+struct Library {
+    handle: *mut c_void,
+    functions: *const CK_FUNCTION_LIST,
+    finalize: bool,
+}
+
+// PKCS#11 modules are initialized with CKF_OS_LOCKING_OK, which makes them
+// safe to call from any thread. Sessions are not, so `Session` serializes the
+// calls made with it.
+unsafe impl Send for Library {}
+unsafe impl Sync for Library {}
+
+fn function<F>(name: &'static str, function: Option<F>) -> Result<F, Error> {
+    function.ok_or_else(|| Error::from(ErrorKind::MissingFunction(name)))
+}
+
+fn check(name: &'static str, rv: CK_RV) -> Result<(), Error> {
+    if rv == CKR_OK {
+        Ok(())
+    } else {
+        Err(Error::from(ErrorKind::Call(name, rv)))
+    }
+}
+
+impl Library {
+    #[cfg(unix)]
+    fn load(lib_path: &Path) -> Result<Self, Error> {
+        use std::ffi::{CStr, CString};
+        use std::os::unix::ffi::OsStrExt;
+
+        use libc::{self, c_char};
+
+        let load_error = |reason: &str| {
+            Error::from(ErrorKind::LoadLibrary(format!(
+                "{}: {}",
+                lib_path.display(),
+                reason
+            )))
+        };
+
+        let path = CString::new(lib_path.as_os_str().as_bytes())
+            .context(ErrorKind::LoadLibrary(lib_path.display().to_string()))?;
+        let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            let reason = unsafe { CStr::from_ptr(libc::dlerror()) };
+            return Err(load_error(&reason.to_string_lossy()));
+        }
+
+        let mut library = Library {
+            handle,
+            functions: ptr::null(),
+            finalize: false,
+        };
+
+        let symbol =
+            unsafe { libc::dlsym(handle, b"C_GetFunctionList\0".as_ptr() as *const c_char) };
+        if symbol.is_null() {
+            return Err(load_error("C_GetFunctionList not found"));
+        }
+        let get_function_list: CK_C_GetFunctionList = unsafe { mem::transmute(symbol) };
+        let mut functions = ptr::null_mut();
+        check("C_GetFunctionList", unsafe {
+            get_function_list(&mut functions)
+        })?;
+        if functions.is_null() {
+            return Err(load_error("C_GetFunctionList returned no functions"));
+        }
+        library.functions = functions;
+
+        let initialize = function("C_Initialize", library.functions().C_Initialize)?;
+        let mut args = CK_C_INITIALIZE_ARGS {
+            CreateMutex: ptr::null_mut(),
+            DestroyMutex: ptr::null_mut(),
+            LockMutex: ptr::null_mut(),
+            UnlockMutex: ptr::null_mut(),
+            flags: CKF_OS_LOCKING_OK,
+            pReserved: ptr::null_mut(),
+        };
+        match unsafe { initialize(&mut args as *mut CK_C_INITIALIZE_ARGS as *mut c_void) } {
+            CKR_OK => library.finalize = true,
+            // Someone else in this process uses the module as well, and
+            // finalizing it is up to them.
+            CKR_CRYPTOKI_ALREADY_INITIALIZED => (),
+            rv => check("C_Initialize", rv)?,
+        }
+
+        Ok(library)
+    }
+
+    #[cfg(not(unix))]
+    fn load(lib_path: &Path) -> Result<Self, Error> {
+        Err(Error::from(ErrorKind::LoadLibrary(format!(
+            "{}: PKCS#11 modules are not supported on this platform",
+            lib_path.display()
+        ))))
+    }
+
+    fn functions(&self) -> &CK_FUNCTION_LIST {
+        unsafe { &*self.functions }
+    }
+
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn find_slot(&self, token: &str) -> Result<CK_SLOT_ID, Error> {
+        let get_slot_list = function("C_GetSlotList", self.functions().C_GetSlotList)?;
+        let get_token_info = function("C_GetTokenInfo", self.functions().C_GetTokenInfo)?;
+
+        let mut count = 0;
+        check("C_GetSlotList", unsafe {
+            get_slot_list(CK_TRUE, ptr::null_mut(), &mut count)
+        })?;
+        let mut slots = vec![0; count as usize];
+        check("C_GetSlotList", unsafe {
+            get_slot_list(CK_TRUE, slots.as_mut_ptr(), &mut count)
+        })?;
+        slots.truncate(count as usize);
+
+        for slot in slots {
+            let mut info: CK_TOKEN_INFO = unsafe { mem::zeroed() };
+            check("C_GetTokenInfo", unsafe { get_token_info(slot, &mut info) })?;
+            // Labels are padded with blanks to their full length.
+            let label = String::from_utf8_lossy(&info.label);
+            if label.trim_right_matches(|c| c == ' ' || c == '\0') == token {
+                return Ok(slot);
+            }
+        }
+
+        Err(Error::from(ErrorKind::TokenNotFound(token.to_string())))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe {
+            if self.finalize {
+                if let Some(finalize) = self.functions().C_Finalize {
+                    finalize(ptr::null_mut());
+                }
+            }
+            ::libc::dlclose(self.handle);
+        }
+    }
+}
+
+/// A session on a token. PKCS#11 sessions can only do one thing at a time,
+/// so every call made with it holds a lock.
+pub(crate) struct Session {
+    library: Arc<Library>,
+    handle: CK_SESSION_HANDLE,
+    lock: Mutex<()>,
+}
+
+impl Session {
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn open(library: Arc<Library>, slot: CK_SLOT_ID, pin: Option<&str>) -> Result<Self, Error> {
+        let open_session = function("C_OpenSession", library.functions().C_OpenSession)?;
+        let login = function("C_Login", library.functions().C_Login)?;
+
+        let mut handle = 0;
+        check("C_OpenSession", unsafe {
+            open_session(
+                slot,
+                CKF_SERIAL_SESSION | CKF_RW_SESSION,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut handle,
+            )
+        })?;
+        let session = Session {
+            library,
+            handle,
+            lock: Mutex::new(()),
+        };
+
+        if let Some(pin) = pin {
+            // Logging in applies to every session of the application, so
+            // another session may already have done it.
+            match unsafe { login(handle, CKU_USER, pin.as_ptr(), pin.len() as CK_ULONG) } {
+                CKR_OK | CKR_USER_ALREADY_LOGGED_IN => (),
+                rv => check("C_Login", rv)?,
+            }
+        }
+
+        Ok(session)
+    }
+
+    fn lock(&self) -> MutexGuard<()> {
+        self.lock.lock().expect("Lock on PKCS#11 session failed")
+    }
+
+    /// Returns the first object of the given class with the given label and
+    /// id, if there is one.
+    pub(crate) fn find_object(
+        &self,
+        class: CK_OBJECT_CLASS,
+        label: Option<&str>,
+        id: Option<&[u8]>,
+    ) -> Result<Option<CK_OBJECT_HANDLE>, Error> {
+        let functions = self.library.functions();
+        let find_objects_init = function("C_FindObjectsInit", functions.C_FindObjectsInit)?;
+        let find_objects = function("C_FindObjects", functions.C_FindObjects)?;
+        let find_objects_final = function("C_FindObjectsFinal", functions.C_FindObjectsFinal)?;
+
+        let mut template = vec![attribute(CKA_CLASS, &class)];
+        if let Some(label) = label {
+            template.push(bytes_attribute(CKA_LABEL, label.as_bytes()));
+        }
+        if let Some(id) = id {
+            template.push(bytes_attribute(CKA_ID, id));
+        }
+
+        let _lock = self.lock();
+        check("C_FindObjectsInit", unsafe {
+            find_objects_init(
+                self.handle,
+                template.as_mut_ptr(),
+                template.len() as CK_ULONG,
+            )
+        })?;
+        let mut object = 0;
+        let mut count = 0;
+        let found = check("C_FindObjects", unsafe {
+            find_objects(self.handle, &mut object, 1, &mut count)
+        });
+        let finished = check("C_FindObjectsFinal", unsafe {
+            find_objects_final(self.handle)
+        });
+        found?;
+        finished?;
+
+        Ok(if count == 0 { None } else { Some(object) })
+    }
+
+    pub(crate) fn create_object(
+        &self,
+        template: &mut [CK_ATTRIBUTE],
+    ) -> Result<CK_OBJECT_HANDLE, Error> {
+        let create_object = function("C_CreateObject", self.library.functions().C_CreateObject)?;
+
+        let _lock = self.lock();
+        let mut object = 0;
+        check("C_CreateObject", unsafe {
+            create_object(
+                self.handle,
+                template.as_mut_ptr(),
+                template.len() as CK_ULONG,
+                &mut object,
+            )
+        })?;
+        Ok(object)
+    }
+
+    pub(crate) fn destroy_object(&self, object: CK_OBJECT_HANDLE) -> Result<(), Error> {
+        let destroy_object = function("C_DestroyObject", self.library.functions().C_DestroyObject)?;
+
+        let _lock = self.lock();
+        check("C_DestroyObject", unsafe {
+            destroy_object(self.handle, object)
+        })
+    }
+
+    pub(crate) fn ulong_attribute(
+        &self,
+        object: CK_OBJECT_HANDLE,
+        type_: CK_ATTRIBUTE_TYPE,
+    ) -> Result<CK_ULONG, Error> {
+        let get_attribute_value = function(
+            "C_GetAttributeValue",
+            self.library.functions().C_GetAttributeValue,
+        )?;
+
+        let mut value: CK_ULONG = 0;
+        let mut template = [CK_ATTRIBUTE {
+            type_,
+            pValue: &mut value as *mut CK_ULONG as *mut c_void,
+            ulValueLen: mem::size_of::<CK_ULONG>() as CK_ULONG,
+        }];
+        let _lock = self.lock();
+        check("C_GetAttributeValue", unsafe {
+            get_attribute_value(self.handle, object, template.as_mut_ptr(), 1)
+        })?;
+        Ok(value)
+    }
+
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    pub(crate) fn sign(
+        &self,
+        mechanism: CK_MECHANISM_TYPE,
+        key: CK_OBJECT_HANDLE,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let sign_init = function("C_SignInit", self.library.functions().C_SignInit)?;
+        let sign = function("C_Sign", self.library.functions().C_Sign)?;
+
+        let mut mechanism = CK_MECHANISM {
+            mechanism,
+            pParameter: ptr::null_mut(),
+            ulParameterLen: 0,
+        };
+        let _lock = self.lock();
+        check("C_SignInit", unsafe {
+            sign_init(self.handle, &mut mechanism, key)
+        })?;
+
+        // The first call only asks for the length of the signature.
+        let mut len = 0;
+        check("C_Sign", unsafe {
+            sign(
+                self.handle,
+                data.as_ptr(),
+                data.len() as CK_ULONG,
+                ptr::null_mut(),
+                &mut len,
+            )
+        })?;
+        let mut signature = vec![0; len as usize];
+        check("C_Sign", unsafe {
+            sign(
+                self.handle,
+                data.as_ptr(),
+                data.len() as CK_ULONG,
+                signature.as_mut_ptr(),
+                &mut len,
+            )
+        })?;
+        signature.truncate(len as usize);
+        Ok(signature)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Some(close_session) = self.library.functions().C_CloseSession {
+            unsafe {
+                close_session(self.handle);
+            }
+        }
+    }
+}
+
+/// An attribute whose value is `value` itself. The attribute points into
+/// `value`, so it must not outlive it.
+pub(crate) fn attribute<T>(type_: CK_ATTRIBUTE_TYPE, value: &T) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE {
+        type_,
+        pValue: value as *const T as *mut c_void,
+        ulValueLen: mem::size_of::<T>() as CK_ULONG,
+    }
+}
+
+/// An attribute whose value is the bytes of `value`. The attribute points
+/// into `value`, so it must not outlive it.
+pub(crate) fn bytes_attribute(type_: CK_ATTRIBUTE_TYPE, value: &[u8]) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE {
+        type_,
+        pValue: value.as_ptr() as *mut c_void,
+        ulValueLen: value.len() as CK_ULONG,
+    }
+}
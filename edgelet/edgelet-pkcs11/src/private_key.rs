@@ -0,0 +1,207 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::mem;
+use std::sync::Arc;
+
+use failure::ResultExt;
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::ecdsa::EcdsaSig;
+use openssl::hash::{hash, MessageDigest};
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::x509::{X509Builder, X509};
+
+use error::{Error, ErrorKind};
+use library::Session;
+use sys::*;
+
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_BIT_STRING: u8 = 0x03;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum KeyType {
+    Ec,
+    Rsa,
+}
+
+/// A private key on a PKCS#11 token that signs certificates.
+#[derive(Clone)]
+pub struct Pkcs11PrivateKey {
+    session: Arc<Session>,
+    handle: CK_OBJECT_HANDLE,
+    key_type: KeyType,
+}
+
+impl Pkcs11PrivateKey {
+    pub(crate) fn new(
+        session: Arc<Session>,
+        handle: CK_OBJECT_HANDLE,
+        key_type: CK_KEY_TYPE,
+    ) -> Result<Self, Error> {
+        let key_type = match key_type {
+            CKK_EC => KeyType::Ec,
+            CKK_RSA => KeyType::Rsa,
+            key_type => return Err(Error::from(ErrorKind::UnsupportedKeyType(key_type))),
+        };
+        Ok(Pkcs11PrivateKey {
+            session,
+            handle,
+            key_type,
+        })
+    }
+
+    /// Signs the certificate that `builder` describes with SHA-256.
+    ///
+    /// OpenSSL can't sign with a key it doesn't hold, so the certificate is
+    /// first signed with a throwaway key of the same type. That puts the right
+    /// signature algorithm into it, after which its signature is replaced with
+    /// one that the token makes over the same to-be-signed part.
+    pub fn sign_x509(&self, mut builder: X509Builder) -> Result<X509, Error> {
+        let placeholder = self.placeholder_key().context(ErrorKind::Openssl)?;
+        builder
+            .sign(&placeholder, MessageDigest::sha256())
+            .context(ErrorKind::Openssl)?;
+        let der = builder.build().to_der().context(ErrorKind::Openssl)?;
+
+        let (tbs, algorithm) =
+            split_certificate(&der).ok_or_else(|| ErrorKind::InvalidCertificate)?;
+        let signature = self.sign(tbs)?;
+        let der = join_certificate(tbs, algorithm, &signature);
+        let cert = X509::from_der(&der).context(ErrorKind::InvalidCertificate)?;
+        Ok(cert)
+    }
+
+    fn placeholder_key(&self) -> Result<PKey<Private>, ::openssl::error::ErrorStack> {
+        match self.key_type {
+            KeyType::Ec => {
+                let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+                PKey::from_ec_key(EcKey::generate(&group)?)
+            }
+            KeyType::Rsa => PKey::from_rsa(Rsa::generate(2048)?),
+        }
+    }
+
+    /// Signs `data` the way OpenSSL would with `MessageDigest::sha256()`.
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self.key_type {
+            KeyType::Rsa => self.session.sign(CKM_SHA256_RSA_PKCS, self.handle, data),
+            KeyType::Ec => {
+                // CKM_ECDSA signs a digest and returns r and s back to back,
+                // whereas certificates hold them DER encoded.
+                let digest = hash(MessageDigest::sha256(), data).context(ErrorKind::Openssl)?;
+                let signature = self.session.sign(CKM_ECDSA, self.handle, &digest)?;
+                if signature.is_empty() || signature.len() % 2 != 0 {
+                    return Err(Error::from(ErrorKind::InvalidSignature));
+                }
+                let (r, s) = signature.split_at(signature.len() / 2);
+                let signature = BigNum::from_slice(r)
+                    .and_then(|r| BigNum::from_slice(s).map(|s| (r, s)))
+                    .and_then(|(r, s)| EcdsaSig::from_private_components(r, s))
+                    .and_then(|signature| signature.to_der())
+                    .context(ErrorKind::InvalidSignature)?;
+                Ok(signature)
+            }
+        }
+    }
+}
+
+/// Returns the to-be-signed part and the signature algorithm of a DER
+/// encoded certificate.
+fn split_certificate(der: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (_, certificate, _) = next_element(der)?;
+    let (tbs, _, rest) = next_element(certificate)?;
+    let (algorithm, _, _) = next_element(rest)?;
+    Some((tbs, algorithm))
+}
+
+fn join_certificate(tbs: &[u8], algorithm: &[u8], signature: &[u8]) -> Vec<u8> {
+    // Bit strings start with the number of unused bits in their last byte.
+    let mut bits = Vec::with_capacity(signature.len() + 1);
+    bits.push(0);
+    bits.extend_from_slice(signature);
+
+    let mut certificate = Vec::with_capacity(tbs.len() + algorithm.len() + bits.len() + 8);
+    certificate.extend_from_slice(tbs);
+    certificate.extend_from_slice(algorithm);
+    push_element(&mut certificate, TAG_BIT_STRING, &bits);
+
+    let mut der = Vec::with_capacity(certificate.len() + 4);
+    push_element(&mut der, TAG_SEQUENCE, &certificate);
+    der
+}
+
+/// Splits the first DER element off of `der`, returning the whole element,
+/// its contents and what follows it.
+fn next_element(der: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let first = *der.get(1)?;
+    let (header_len, len) = if first < 0x80 {
+        (2, first as usize)
+    } else {
+        let len_len = (first & 0x7f) as usize;
+        if len_len == 0 || len_len > 4 {
+            return None;
+        }
+        let len = der
+            .get(2..2 + len_len)?
+            .iter()
+            .fold(0, |len, &b| (len << 8) | b as usize);
+        (2 + len_len, len)
+    };
+    let end = header_len.checked_add(len)?;
+    let element = der.get(..end)?;
+    Some((element, &element[header_len..], &der[end..]))
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+fn push_element(der: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+    der.push(tag);
+    let len = contents.len();
+    if len < 0x80 {
+        der.push(len as u8);
+    } else {
+        let len_bytes: Vec<u8> = (0..mem::size_of::<usize>())
+            .rev()
+            .map(|i| (len >> (i * 8)) as u8)
+            .skip_while(|&b| b == 0)
+            .collect();
+        der.push(0x80 | len_bytes.len() as u8);
+        der.extend(len_bytes);
+    }
+    der.extend_from_slice(contents);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use openssl::x509::X509NameBuilder;
+
+    #[test]
+    fn split_and_join_certificate() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "split and join").unwrap();
+        let name = name.build();
+        let mut builder = X509::builder().unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        let cert = builder.build();
+        let der = cert.to_der().unwrap();
+
+        let (tbs, algorithm) = split_certificate(&der).unwrap();
+        assert_eq!(
+            der,
+            join_certificate(tbs, algorithm, cert.signature().as_slice())
+        );
+    }
+
+    #[test]
+    fn split_truncated_certificate_fails() {
+        assert_eq!(None, split_certificate(&[TAG_SEQUENCE, 0x82, 0x01]));
+        assert_eq!(None, split_certificate(&[TAG_SEQUENCE, 0x05, 0x30, 0x00]));
+    }
+}
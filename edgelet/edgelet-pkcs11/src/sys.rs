@@ -0,0 +1,234 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! The parts of the PKCS#11 v2.40 API that edgelet uses, named as in
+//! `pkcs11t.h` and `pkcs11f.h`.
+//!
+//! Only the unix ABI is declared. On Windows the structures are packed.
+
+#![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+use libc::{c_uchar, c_ulong, c_void};
+
+pub type CK_BYTE = c_uchar;
+pub type CK_BBOOL = CK_BYTE;
+pub type CK_ULONG = c_ulong;
+pub type CK_RV = CK_ULONG;
+pub type CK_FLAGS = CK_ULONG;
+pub type CK_SLOT_ID = CK_ULONG;
+pub type CK_SESSION_HANDLE = CK_ULONG;
+pub type CK_OBJECT_HANDLE = CK_ULONG;
+pub type CK_OBJECT_CLASS = CK_ULONG;
+pub type CK_KEY_TYPE = CK_ULONG;
+pub type CK_USER_TYPE = CK_ULONG;
+pub type CK_ATTRIBUTE_TYPE = CK_ULONG;
+pub type CK_MECHANISM_TYPE = CK_ULONG;
+
+pub const CK_TRUE: CK_BBOOL = 1;
+pub const CK_FALSE: CK_BBOOL = 0;
+
+pub const CKR_OK: CK_RV = 0x0000_0000;
+pub const CKR_USER_ALREADY_LOGGED_IN: CK_RV = 0x0000_0100;
+pub const CKR_CRYPTOKI_ALREADY_INITIALIZED: CK_RV = 0x0000_0191;
+
+pub const CKF_OS_LOCKING_OK: CK_FLAGS = 0x0000_0002;
+pub const CKF_RW_SESSION: CK_FLAGS = 0x0000_0002;
+pub const CKF_SERIAL_SESSION: CK_FLAGS = 0x0000_0004;
+
+pub const CKU_USER: CK_USER_TYPE = 1;
+
+pub const CKO_PRIVATE_KEY: CK_OBJECT_CLASS = 0x0000_0003;
+pub const CKO_SECRET_KEY: CK_OBJECT_CLASS = 0x0000_0004;
+
+pub const CKK_RSA: CK_KEY_TYPE = 0x0000_0000;
+pub const CKK_EC: CK_KEY_TYPE = 0x0000_0003;
+pub const CKK_GENERIC_SECRET: CK_KEY_TYPE = 0x0000_0010;
+
+pub const CKA_CLASS: CK_ATTRIBUTE_TYPE = 0x0000_0000;
+pub const CKA_TOKEN: CK_ATTRIBUTE_TYPE = 0x0000_0001;
+pub const CKA_PRIVATE: CK_ATTRIBUTE_TYPE = 0x0000_0002;
+pub const CKA_LABEL: CK_ATTRIBUTE_TYPE = 0x0000_0003;
+pub const CKA_VALUE: CK_ATTRIBUTE_TYPE = 0x0000_0011;
+pub const CKA_KEY_TYPE: CK_ATTRIBUTE_TYPE = 0x0000_0100;
+pub const CKA_ID: CK_ATTRIBUTE_TYPE = 0x0000_0102;
+pub const CKA_SENSITIVE: CK_ATTRIBUTE_TYPE = 0x0000_0103;
+pub const CKA_SIGN: CK_ATTRIBUTE_TYPE = 0x0000_0108;
+pub const CKA_EXTRACTABLE: CK_ATTRIBUTE_TYPE = 0x0000_0162;
+
+pub const CKM_SHA256_RSA_PKCS: CK_MECHANISM_TYPE = 0x0000_0040;
+pub const CKM_SHA256_HMAC: CK_MECHANISM_TYPE = 0x0000_0251;
+pub const CKM_ECDSA: CK_MECHANISM_TYPE = 0x0000_1041;
+
+#[repr(C)]
+pub struct CK_VERSION {
+    pub major: CK_BYTE,
+    pub minor: CK_BYTE,
+}
+
+#[repr(C)]
+pub struct CK_TOKEN_INFO {
+    pub label: [CK_BYTE; 32],
+    pub manufacturerID: [CK_BYTE; 32],
+    pub model: [CK_BYTE; 16],
+    pub serialNumber: [CK_BYTE; 16],
+    pub flags: CK_FLAGS,
+    pub ulMaxSessionCount: CK_ULONG,
+    pub ulSessionCount: CK_ULONG,
+    pub ulMaxRwSessionCount: CK_ULONG,
+    pub ulRwSessionCount: CK_ULONG,
+    pub ulMaxPinLen: CK_ULONG,
+    pub ulMinPinLen: CK_ULONG,
+    pub ulTotalPublicMemory: CK_ULONG,
+    pub ulFreePublicMemory: CK_ULONG,
+    pub ulTotalPrivateMemory: CK_ULONG,
+    pub ulFreePrivateMemory: CK_ULONG,
+    pub hardwareVersion: CK_VERSION,
+    pub firmwareVersion: CK_VERSION,
+    pub utcTime: [CK_BYTE; 16],
+}
+
+#[repr(C)]
+pub struct CK_ATTRIBUTE {
+    pub type_: CK_ATTRIBUTE_TYPE,
+    pub pValue: *mut c_void,
+    pub ulValueLen: CK_ULONG,
+}
+
+#[repr(C)]
+pub struct CK_MECHANISM {
+    pub mechanism: CK_MECHANISM_TYPE,
+    pub pParameter: *mut c_void,
+    pub ulParameterLen: CK_ULONG,
+}
+
+#[repr(C)]
+pub struct CK_C_INITIALIZE_ARGS {
+    pub CreateMutex: *mut c_void,
+    pub DestroyMutex: *mut c_void,
+    pub LockMutex: *mut c_void,
+    pub UnlockMutex: *mut c_void,
+    pub flags: CK_FLAGS,
+    pub pReserved: *mut c_void,
+}
+
+/// A function of the list that edgelet never calls. It only holds the place of
+/// the function so the ones after it are found at the right offset.
+pub type Unused = Option<unsafe extern "C" fn()>;
+
+pub type CK_C_GetFunctionList =
+    unsafe extern "C" fn(ppFunctionList: *mut *mut CK_FUNCTION_LIST) -> CK_RV;
+
+/// The function list up to `C_Sign`. Modules return a pointer to a longer
+/// list, of which the rest is never read.
+#[repr(C)]
+pub struct CK_FUNCTION_LIST {
+    pub version: CK_VERSION,
+    pub C_Initialize: Option<unsafe extern "C" fn(pInitArgs: *mut c_void) -> CK_RV>,
+    pub C_Finalize: Option<unsafe extern "C" fn(pReserved: *mut c_void) -> CK_RV>,
+    pub C_GetInfo: Unused,
+    pub C_GetFunctionList: Unused,
+    pub C_GetSlotList: Option<
+        unsafe extern "C" fn(
+            tokenPresent: CK_BBOOL,
+            pSlotList: *mut CK_SLOT_ID,
+            pulCount: *mut CK_ULONG,
+        ) -> CK_RV,
+    >,
+    pub C_GetSlotInfo: Unused,
+    pub C_GetTokenInfo:
+        Option<unsafe extern "C" fn(slotID: CK_SLOT_ID, pInfo: *mut CK_TOKEN_INFO) -> CK_RV>,
+    pub C_GetMechanismList: Unused,
+    pub C_GetMechanismInfo: Unused,
+    pub C_InitToken: Unused,
+    pub C_InitPIN: Unused,
+    pub C_SetPIN: Unused,
+    pub C_OpenSession: Option<
+        unsafe extern "C" fn(
+            slotID: CK_SLOT_ID,
+            flags: CK_FLAGS,
+            pApplication: *mut c_void,
+            Notify: *mut c_void,
+            phSession: *mut CK_SESSION_HANDLE,
+        ) -> CK_RV,
+    >,
+    pub C_CloseSession: Option<unsafe extern "C" fn(hSession: CK_SESSION_HANDLE) -> CK_RV>,
+    pub C_CloseAllSessions: Unused,
+    pub C_GetSessionInfo: Unused,
+    pub C_GetOperationState: Unused,
+    pub C_SetOperationState: Unused,
+    pub C_Login: Option<
+        unsafe extern "C" fn(
+            hSession: CK_SESSION_HANDLE,
+            userType: CK_USER_TYPE,
+            pPin: *const CK_BYTE,
+            ulPinLen: CK_ULONG,
+        ) -> CK_RV,
+    >,
+    pub C_Logout: Unused,
+    pub C_CreateObject: Option<
+        unsafe extern "C" fn(
+            hSession: CK_SESSION_HANDLE,
+            pTemplate: *mut CK_ATTRIBUTE,
+            ulCount: CK_ULONG,
+            phObject: *mut CK_OBJECT_HANDLE,
+        ) -> CK_RV,
+    >,
+    pub C_CopyObject: Unused,
+    pub C_DestroyObject: Option<
+        unsafe extern "C" fn(hSession: CK_SESSION_HANDLE, hObject: CK_OBJECT_HANDLE) -> CK_RV,
+    >,
+    pub C_GetObjectSize: Unused,
+    pub C_GetAttributeValue: Option<
+        unsafe extern "C" fn(
+            hSession: CK_SESSION_HANDLE,
+            hObject: CK_OBJECT_HANDLE,
+            pTemplate: *mut CK_ATTRIBUTE,
+            ulCount: CK_ULONG,
+        ) -> CK_RV,
+    >,
+    pub C_SetAttributeValue: Unused,
+    pub C_FindObjectsInit: Option<
+        unsafe extern "C" fn(
+            hSession: CK_SESSION_HANDLE,
+            pTemplate: *mut CK_ATTRIBUTE,
+            ulCount: CK_ULONG,
+        ) -> CK_RV,
+    >,
+    pub C_FindObjects: Option<
+        unsafe extern "C" fn(
+            hSession: CK_SESSION_HANDLE,
+            phObject: *mut CK_OBJECT_HANDLE,
+            ulMaxObjectCount: CK_ULONG,
+            pulObjectCount: *mut CK_ULONG,
+        ) -> CK_RV,
+    >,
+    pub C_FindObjectsFinal: Option<unsafe extern "C" fn(hSession: CK_SESSION_HANDLE) -> CK_RV>,
+    pub C_EncryptInit: Unused,
+    pub C_Encrypt: Unused,
+    pub C_EncryptUpdate: Unused,
+    pub C_EncryptFinal: Unused,
+    pub C_DecryptInit: Unused,
+    pub C_Decrypt: Unused,
+    pub C_DecryptUpdate: Unused,
+    pub C_DecryptFinal: Unused,
+    pub C_DigestInit: Unused,
+    pub C_Digest: Unused,
+    pub C_DigestUpdate: Unused,
+    pub C_DigestKey: Unused,
+    pub C_DigestFinal: Unused,
+    pub C_SignInit: Option<
+        unsafe extern "C" fn(
+            hSession: CK_SESSION_HANDLE,
+            pMechanism: *mut CK_MECHANISM,
+            hKey: CK_OBJECT_HANDLE,
+        ) -> CK_RV,
+    >,
+    pub C_Sign: Option<
+        unsafe extern "C" fn(
+            hSession: CK_SESSION_HANDLE,
+            pData: *const CK_BYTE,
+            ulDataLen: CK_ULONG,
+            pSignature: *mut CK_BYTE,
+            pulSignatureLen: *mut CK_ULONG,
+        ) -> CK_RV,
+    >,
+}
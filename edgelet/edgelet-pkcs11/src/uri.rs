@@ -0,0 +1,181 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::fmt;
+use std::str::FromStr;
+
+use error::{Error, ErrorKind};
+
+const SCHEME: &str = "pkcs11:";
+
+/// Returns whether `value` is a PKCS#11 URI rather than, say, a file path.
+pub fn is_pkcs11_uri(value: &str) -> bool {
+    value.starts_with(SCHEME)
+}
+
+/// The parts of an [RFC 7512](https://tools.ietf.org/html/rfc7512) PKCS#11
+/// URI that identify a key, for example
+/// `pkcs11:token=edge;object=device-ca?pin-value=1234`.
+///
+/// Attributes that aren't needed to find a key are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pkcs11Uri {
+    token: Option<String>,
+    object: Option<String>,
+    id: Option<Vec<u8>>,
+    pin_value: Option<String>,
+}
+
+impl Pkcs11Uri {
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn object(&self) -> Option<&str> {
+        self.object.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn id(&self) -> Option<&[u8]> {
+        self.id.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn pin_value(&self) -> Option<&str> {
+        self.pin_value.as_ref().map(AsRef::as_ref)
+    }
+}
+
+impl FromStr for Pkcs11Uri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::from(ErrorKind::InvalidUri(s.to_string()));
+
+        if !is_pkcs11_uri(s) {
+            return Err(invalid());
+        }
+        let mut parts = s[SCHEME.len()..].splitn(2, '?');
+        let path = parts.next().unwrap_or("");
+        let query = parts.next().unwrap_or("");
+
+        let mut uri = Pkcs11Uri {
+            token: None,
+            object: None,
+            id: None,
+            pin_value: None,
+        };
+        let path_attributes = path.split(';').map(|attribute| (attribute, true));
+        let query_attributes = query.split('&').map(|attribute| (attribute, false));
+        for (attribute, in_path) in path_attributes.chain(query_attributes) {
+            if attribute.is_empty() {
+                continue;
+            }
+            let mut pair = attribute.splitn(2, '=');
+            let name = pair.next().unwrap_or("");
+            let value = pair.next().ok_or_else(invalid)?;
+            let value = percent_decode(value).ok_or_else(invalid)?;
+            match (name, in_path) {
+                ("token", true) => {
+                    uri.token = Some(String::from_utf8(value).map_err(|_| invalid())?)
+                }
+                ("object", true) => {
+                    uri.object = Some(String::from_utf8(value).map_err(|_| invalid())?)
+                }
+                ("id", true) => uri.id = Some(value),
+                ("pin-value", false) => {
+                    uri.pin_value = Some(String::from_utf8(value).map_err(|_| invalid())?)
+                }
+                _ => (),
+            }
+        }
+
+        Ok(uri)
+    }
+}
+
+/// Writes the URI without the PIN, so that it can be logged.
+impl fmt::Display for Pkcs11Uri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut attributes = vec![];
+        if let Some(ref token) = self.token {
+            attributes.push(format!("token={}", percent_encode(token.as_bytes())));
+        }
+        if let Some(ref object) = self.object {
+            attributes.push(format!("object={}", percent_encode(object.as_bytes())));
+        }
+        if let Some(ref id) = self.id {
+            attributes.push(format!("id={}", percent_encode(id)));
+        }
+        write!(f, "{}{}", SCHEME, attributes.join(";"))
+    }
+}
+
+fn percent_decode(value: &str) -> Option<Vec<u8>> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(decoded)
+}
+
+fn percent_encode(value: &[u8]) -> String {
+    value
+        .iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_uri() {
+        let uri: Pkcs11Uri =
+            "pkcs11:token=edge%20token;object=device-ca;id=%01%ff;manufacturer=x?pin-value=1234"
+                .parse()
+                .unwrap();
+        assert_eq!(Some("edge token"), uri.token());
+        assert_eq!(Some("device-ca"), uri.object());
+        assert_eq!(Some(&[0x01, 0xff][..]), uri.id());
+        assert_eq!(Some("1234"), uri.pin_value());
+        assert_eq!(
+            "pkcs11:token=edge%20token;object=device-ca;id=%01%FF",
+            uri.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_invalid_uri_fails() {
+        for value in &[
+            "/var/lib/iotedge/device-ca.key.pem",
+            "pkcs11:token",
+            "pkcs11:token=%4",
+            "pkcs11:token=%zz",
+            "pkcs11:object=%ff",
+        ] {
+            assert_eq!(
+                &ErrorKind::InvalidUri(value.to_string()),
+                value.parse::<Pkcs11Uri>().unwrap_err().kind()
+            );
+        }
+    }
+
+    #[test]
+    fn is_pkcs11_uri_checks_scheme() {
+        assert!(is_pkcs11_uri("pkcs11:token=edge;object=device-ca"));
+        assert!(!is_pkcs11_uri("/var/lib/iotedge/device-ca.key.pem"));
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+//! These tests need a PKCS#11 module with an initialized token, such as
+//! SoftHSM2. They are skipped unless `PKCS11_LIB_PATH`, `PKCS11_TOKEN` and
+//! `PKCS11_PIN` describe one; see build/linux/test.sh.
+
+extern crate edgelet_core;
+extern crate edgelet_pkcs11;
+
+use std::env;
+use std::path::Path;
+
+use edgelet_core::crypto::{MemoryKey, Sign, Signature, SignatureAlgorithm};
+use edgelet_core::{Activate, ErrorKind, KeyIdentity, KeyStore};
+use edgelet_pkcs11::{Pkcs11, Pkcs11KeyStore};
+
+fn key_store() -> Option<Pkcs11KeyStore> {
+    let lib_path = env::var("PKCS11_LIB_PATH").ok()?;
+    let token = env::var("PKCS11_TOKEN").unwrap();
+    let pin = env::var("PKCS11_PIN").unwrap();
+    let pkcs11 = Pkcs11::load(Path::new(&lib_path)).unwrap();
+    Some(pkcs11.key_store(&token, Some(&pin)).unwrap())
+}
+
+#[test]
+fn activated_key_signs_like_memory_key() {
+    let mut store = match key_store() {
+        Some(store) => store,
+        None => return,
+    };
+
+    for key in &[&b"first key"[..], &b"second key"[..]] {
+        store
+            .activate_identity_key(
+                KeyIdentity::Module("pkcs11-test".to_string()),
+                "primary".to_string(),
+                key,
+            )
+            .unwrap();
+
+        let signature = store
+            .get(&KeyIdentity::Module("pkcs11-test".to_string()), "primary")
+            .unwrap()
+            .sign(SignatureAlgorithm::HMACSHA256, b"data")
+            .unwrap();
+        let expected = MemoryKey::new(key)
+            .sign(SignatureAlgorithm::HMACSHA256, b"data")
+            .unwrap();
+        assert_eq!(expected.as_bytes(), signature.as_bytes());
+    }
+}
+
+#[test]
+fn unknown_key_is_not_found() {
+    let store = match key_store() {
+        Some(store) => store,
+        None => return,
+    };
+
+    let err = store
+        .get(
+            &KeyIdentity::Module("pkcs11-unknown".to_string()),
+            "primary",
+        )
+        .err()
+        .unwrap();
+    assert_eq!(&ErrorKind::KeyStoreItemNotFound, err.kind());
+}
@@ -0,0 +1,111 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+//! These tests need a PKCS#11 module with a token that holds a private key
+//! labeled `device-ca`, such as SoftHSM2. They are skipped unless
+//! `PKCS11_LIB_PATH`, `PKCS11_TOKEN` and `PKCS11_PIN` describe one and
+//! `PKCS11_TEST_KEY_PEM` is the file the key was imported from; see
+//! build/linux/test.sh.
+
+extern crate edgelet_pkcs11;
+extern crate openssl;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use edgelet_pkcs11::{ErrorKind, Pkcs11, Pkcs11Uri};
+use openssl::asn1::Asn1Time;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, PKeyRef, Private};
+use openssl::stack::Stack;
+use openssl::x509::extension::BasicConstraints;
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::{X509Builder, X509Name, X509NameBuilder, X509StoreContext};
+
+fn pkcs11() -> Option<(Pkcs11, String, String)> {
+    let lib_path = env::var("PKCS11_LIB_PATH").ok()?;
+    let token = env::var("PKCS11_TOKEN").unwrap();
+    let pin = env::var("PKCS11_PIN").unwrap();
+    Some((Pkcs11::load(Path::new(&lib_path)).unwrap(), token, pin))
+}
+
+fn name(common_name: &str) -> X509Name {
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    name.build()
+}
+
+fn builder(subject: &str, issuer: &str, key: &PKeyRef<Private>) -> X509Builder {
+    let mut builder = X509Builder::new().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name(subject)).unwrap();
+    builder.set_issuer_name(&name(issuer)).unwrap();
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    builder
+}
+
+#[test]
+fn private_key_signs_certificate() {
+    let (pkcs11, token, pin) = match pkcs11() {
+        Some(pkcs11) => pkcs11,
+        None => return,
+    };
+    let pem = fs::read(env::var("PKCS11_TEST_KEY_PEM").unwrap()).unwrap();
+    let ca_key = PKey::private_key_from_pem(&pem).unwrap();
+
+    // arrange: a CA certificate for the key on the token
+    let mut ca = builder("pkcs11 test ca", "pkcs11 test ca", &ca_key);
+    ca.append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+        .unwrap();
+    ca.sign(&ca_key, MessageDigest::sha256()).unwrap();
+    let ca = ca.build();
+
+    // act: issue a certificate with the key on the token
+    let uri: Pkcs11Uri = format!("pkcs11:token={};object=device-ca", token)
+        .parse()
+        .unwrap();
+    let private_key = pkcs11.private_key(&uri, Some(&pin)).unwrap();
+    let cert = private_key
+        .sign_x509(builder("pkcs11 test leaf", "pkcs11 test ca", &ca_key))
+        .unwrap();
+
+    // assert: the certificate verifies against the CA certificate
+    let mut store = X509StoreBuilder::new().unwrap();
+    store.add_cert(ca).unwrap();
+    let store = store.build();
+    let verified = X509StoreContext::new()
+        .unwrap()
+        .init(&store, &cert, &Stack::new().unwrap(), |context| {
+            context.verify_cert()
+        })
+        .unwrap();
+    assert!(verified);
+}
+
+#[test]
+fn unknown_object_is_not_found() {
+    let (pkcs11, token, pin) = match pkcs11() {
+        Some(pkcs11) => pkcs11,
+        None => return,
+    };
+
+    let uri: Pkcs11Uri = format!("pkcs11:token={};object=unknown", token)
+        .parse()
+        .unwrap();
+    let err = pkcs11.private_key(&uri, Some(&pin)).err().unwrap();
+    assert_eq!(&ErrorKind::ObjectNotFound(uri.to_string()), err.kind());
+}
@@ -0,0 +1,40 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+#![cfg_attr(feature = "cargo-clippy", allow(stutter, use_self))]
+
+extern crate base64;
+extern crate config;
+extern crate failure;
+#[macro_use]
+extern crate log;
+extern crate regex;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate sha2;
+#[cfg(test)]
+extern crate tempdir;
+extern crate url;
+extern crate url_serde;
+
+extern crate edgelet_core;
+extern crate edgelet_docker;
+extern crate edgelet_utils;
+
+mod error;
+mod settings;
+
+pub use error::{Error, ErrorKind};
+pub use settings::{
+    Certificates, Connect, Crypto, CryptoBackend, Dps, ImageTrust, KeyStoreBackend, Listen,
+    LogFormat, Logging, Manual, MobyRuntime, Pkcs11, Provisioning, Proxy, Retry, Settings,
+    DEFAULT_CONNECTION_STRING,
+};
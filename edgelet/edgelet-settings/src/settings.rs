@@ -0,0 +1,1121 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{File as FsFile, OpenOptions};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use base64;
+use config::{Config, ConfigError, Environment, File, FileFormat};
+use failure::{Fail, ResultExt};
+use log::Level;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+use sha2::{Digest, Sha256};
+use url::Url;
+use url_serde;
+
+use edgelet_core::crypto::MemoryKey;
+use edgelet_core::ModuleSpec;
+use edgelet_docker::ResourceLimits;
+use edgelet_utils::{ensure_not_empty_with_context, log_failure};
+
+use error::{Error, ErrorKind};
+
+/// This is the name of the network created by the iotedged
+const DEFAULT_NETWORKID: &str = "azure-iot-edge";
+
+/// This is the default connection string
+pub const DEFAULT_CONNECTION_STRING: &str = "<ADD DEVICE CONNECTION STRING HERE>";
+
+const DEVICEID_KEY: &str = "DeviceId";
+const HOSTNAME_KEY: &str = "HostName";
+const SHAREDACCESSKEY_KEY: &str = "SharedAccessKey";
+
+const DEVICEID_REGEX: &str = r"^[A-Za-z0-9\-:.+%_#*?!(),=@;$']{1,128}$";
+const HOSTNAME_REGEX: &str = r"^[a-zA-Z0-9_\-\.]+$";
+const SHAREDACCESSKEY_REGEX: &str = r"^.+$";
+
+#[cfg(unix)]
+static DEFAULTS: &str = include_str!("config/unix/default.yaml");
+
+#[cfg(windows)]
+static DEFAULTS: &str = include_str!("config/windows/default.yaml");
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub struct Manual {
+    device_connection_string: String,
+}
+
+impl Manual {
+    pub fn device_connection_string(&self) -> &str {
+        &self.device_connection_string
+    }
+
+    /// Parses the connection string into the device key, the device id and
+    /// the hub name.
+    pub fn parse_device_connection_string(&self) -> Result<(MemoryKey, String, String), Error> {
+        ensure_not_empty_with_context(&self.device_connection_string, || {
+            ErrorKind::InvalidConnString
+        })?;
+
+        let hash_map = parse_connection_string(&self.device_connection_string);
+
+        let key = hash_map.get(SHAREDACCESSKEY_KEY).ok_or(
+            ErrorKind::ConnStringMissingRequiredParameter(SHAREDACCESSKEY_KEY),
+        )?;
+        let key_regex = Regex::new(SHAREDACCESSKEY_REGEX)
+            .expect("This hard-coded regex is expected to be valid.");
+        if !key_regex.is_match(&key) {
+            return Err(Error::from(ErrorKind::ConnStringMalformedParameter(
+                SHAREDACCESSKEY_REGEX,
+            )));
+        }
+        let key = MemoryKey::new(base64::decode(&key).context(
+            ErrorKind::ConnStringMalformedParameter(SHAREDACCESSKEY_REGEX),
+        )?);
+
+        let device_id = hash_map
+            .get(DEVICEID_KEY)
+            .ok_or(ErrorKind::ConnStringMissingRequiredParameter(DEVICEID_KEY))?;
+        let device_id_regex =
+            Regex::new(DEVICEID_REGEX).expect("This hard-coded regex is expected to be valid.");
+        if !device_id_regex.is_match(&device_id) {
+            return Err(Error::from(ErrorKind::ConnStringMalformedParameter(
+                DEVICEID_KEY,
+            )));
+        }
+
+        let hub = hash_map
+            .get(HOSTNAME_KEY)
+            .ok_or(ErrorKind::ConnStringMissingRequiredParameter(HOSTNAME_KEY))?;
+        let hub_regex =
+            Regex::new(HOSTNAME_REGEX).expect("This hard-coded regex is expected to be valid.");
+        if !hub_regex.is_match(&hub) {
+            return Err(Error::from(ErrorKind::ConnStringMalformedParameter(
+                HOSTNAME_KEY,
+            )));
+        }
+
+        Ok((key, device_id.to_owned(), hub.to_owned()))
+    }
+}
+
+fn parse_connection_string(conn_string: &str) -> HashMap<String, String> {
+    let mut hash_map = HashMap::new();
+    let parts: Vec<&str> = conn_string.split(';').collect();
+    for p in parts {
+        let s: Vec<&str> = p.split('=').collect();
+        match s[0] {
+            SHAREDACCESSKEY_KEY | DEVICEID_KEY | HOSTNAME_KEY => {
+                hash_map.insert(s[0].to_string(), s[1].to_string());
+            }
+            _ => (), // Ignore extraneous component in the connection string
+        }
+    }
+    hash_map
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub struct Dps {
+    #[serde(with = "url_serde")]
+    global_endpoint: Url,
+    scope_id: String,
+    registration_id: String,
+}
+
+impl Dps {
+    pub fn global_endpoint(&self) -> &Url {
+        &self.global_endpoint
+    }
+
+    pub fn scope_id(&self) -> &str {
+        &self.scope_id
+    }
+
+    pub fn registration_id(&self) -> &str {
+        &self.registration_id
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "source")]
+#[serde(rename_all = "lowercase")]
+pub enum Provisioning {
+    Manual(Manual),
+    Dps(Dps),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Connect {
+    #[serde(with = "url_serde")]
+    workload_uri: Url,
+    #[serde(with = "url_serde")]
+    management_uri: Url,
+}
+
+impl Connect {
+    pub fn workload_uri(&self) -> &Url {
+        &self.workload_uri
+    }
+
+    pub fn management_uri(&self) -> &Url {
+        &self.management_uri
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Listen {
+    #[serde(with = "url_serde")]
+    workload_uri: Url,
+    #[serde(with = "url_serde")]
+    management_uri: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    privileged_group: Option<String>,
+}
+
+impl Listen {
+    pub fn workload_uri(&self) -> &Url {
+        &self.workload_uri
+    }
+
+    pub fn management_uri(&self) -> &Url {
+        &self.management_uri
+    }
+
+    pub fn privileged_group(&self) -> Option<&str> {
+        self.privileged_group.as_ref().map(AsRef::as_ref)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ImageTrust {
+    public_key: PathBuf,
+    signatures_dir: PathBuf,
+}
+
+impl ImageTrust {
+    pub fn public_key(&self) -> &Path {
+        &self.public_key
+    }
+
+    pub fn signatures_dir(&self) -> &Path {
+        &self.signatures_dir
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MobyRuntime {
+    #[serde(with = "url_serde")]
+    uri: Url,
+    network: String,
+    image_trust: Option<ImageTrust>,
+    resource_limits: Option<ResourceLimits>,
+    // Not part of the settings state, so that turning it on does not by itself
+    // reconfigure the device and remove the volumes.
+    #[serde(default, skip_serializing)]
+    purge_volumes: bool,
+}
+
+impl MobyRuntime {
+    pub fn uri(&self) -> &Url {
+        &self.uri
+    }
+
+    pub fn network(&self) -> &str {
+        if self.network.is_empty() {
+            &DEFAULT_NETWORKID
+        } else {
+            &self.network
+        }
+    }
+
+    pub fn image_trust(&self) -> Option<&ImageTrust> {
+        self.image_trust.as_ref()
+    }
+
+    pub fn resource_limits(&self) -> Option<&ResourceLimits> {
+        self.resource_limits.as_ref()
+    }
+
+    pub fn purge_volumes(&self) -> bool {
+        self.purge_volumes
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Certificates {
+    device_ca_cert: PathBuf,
+    device_ca_pk: PathBuf,
+    trusted_ca_certs: PathBuf,
+}
+
+impl Certificates {
+    pub fn device_ca_cert(&self) -> &Path {
+        &self.device_ca_cert
+    }
+
+    pub fn device_ca_pk(&self) -> &Path {
+        &self.device_ca_pk
+    }
+
+    pub fn trusted_ca_certs(&self) -> &Path {
+        &self.trusted_ca_certs
+    }
+}
+
+/// The HTTPS proxy used to reach IoT Hub and DPS. When present, this overrides
+/// the `HTTPS_PROXY` and `NO_PROXY` environment variables.
+#[derive(Deserialize, Serialize)]
+pub struct Proxy {
+    #[serde(with = "url_serde")]
+    uri: Url,
+    username: Option<String>,
+    password: Option<String>,
+    no_proxy: Option<String>,
+}
+
+impl Proxy {
+    pub fn uri(&self) -> &Url {
+        &self.uri
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn password(&self) -> Option<&str> {
+        self.password.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn no_proxy(&self) -> Option<&str> {
+        self.no_proxy.as_ref().map(AsRef::as_ref)
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut uri = self.uri.clone();
+        let _ = uri.set_username("");
+        let _ = uri.set_password(None);
+        f.debug_struct("Proxy")
+            .field("uri", &uri.as_str())
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("no_proxy", &self.no_proxy)
+            .finish()
+    }
+}
+
+/// How failed requests to IoT Hub and DPS are retried. Values that aren't set
+/// keep their defaults.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Retry {
+    max_retries: Option<u32>,
+    initial_backoff_secs: Option<u64>,
+    max_backoff_secs: Option<u64>,
+}
+
+impl Retry {
+    pub fn max_retries(&self) -> Option<u32> {
+        self.max_retries
+    }
+
+    pub fn initial_backoff_secs(&self) -> Option<u64> {
+        self.initial_backoff_secs
+    }
+
+    pub fn max_backoff_secs(&self) -> Option<u64> {
+        self.max_backoff_secs
+    }
+}
+
+/// How log records are written.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Syslog-prefixed lines on stderr.
+    Text,
+    /// One JSON object per line on stderr.
+    Json,
+    /// Native journald entries with structured fields.
+    Journald,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Text
+    }
+}
+
+/// Log format and levels. Levels from the `IOTEDGE_LOG` environment variable
+/// take precedence over these.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Logging {
+    #[serde(default)]
+    format: LogFormat,
+    level: Option<String>,
+    #[serde(default)]
+    targets: BTreeMap<String, String>,
+}
+
+impl Logging {
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    pub fn level(&self) -> Option<&str> {
+        self.level.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn targets(&self) -> &BTreeMap<String, String> {
+        &self.targets
+    }
+
+    /// The levels as `env_logger` directives, e.g. `info,edgelet_http=debug`.
+    pub fn directives(&self) -> String {
+        self.level
+            .iter()
+            .cloned()
+            .chain(
+                self.targets
+                    .iter()
+                    .map(|(target, level)| format!("{}={}", target, level)),
+            )
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Where certificates, keys and the master encryption key are kept.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CryptoBackend {
+    /// The `iothsm` library.
+    Hsm,
+    /// Files under the home directory, managed with OpenSSL.
+    Software,
+}
+
+impl Default for CryptoBackend {
+    fn default() -> Self {
+        CryptoBackend::Hsm
+    }
+}
+
+/// Where the device key of a manually provisioned device is kept.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyStoreBackend {
+    /// In memory, read from the connection string at every start.
+    Memory,
+    /// In a file under the home directory, encrypted with the master
+    /// encryption key.
+    File,
+}
+
+impl Default for KeyStoreBackend {
+    fn default() -> Self {
+        KeyStoreBackend::Memory
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Crypto {
+    #[serde(default)]
+    backend: CryptoBackend,
+    #[serde(default)]
+    key_store: KeyStoreBackend,
+    pkcs11: Option<Pkcs11>,
+}
+
+impl Crypto {
+    pub fn backend(&self) -> CryptoBackend {
+        self.backend
+    }
+
+    pub fn key_store(&self) -> KeyStoreBackend {
+        self.key_store
+    }
+
+    pub fn pkcs11(&self) -> Option<&Pkcs11> {
+        self.pkcs11.as_ref()
+    }
+}
+
+/// A PKCS#11 module and token. When present, the device identity key is kept
+/// on the token, and `certificates.device_ca_pk` may be a `pkcs11:` URI of a
+/// key on it.
+#[derive(Deserialize, Serialize)]
+pub struct Pkcs11 {
+    lib_path: PathBuf,
+    token: String,
+    pin: Option<String>,
+}
+
+impl Pkcs11 {
+    pub fn lib_path(&self) -> &Path {
+        &self.lib_path
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn pin(&self) -> Option<&str> {
+        self.pin.as_ref().map(AsRef::as_ref)
+    }
+}
+
+impl fmt::Debug for Pkcs11 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pkcs11")
+            .field("lib_path", &self.lib_path)
+            .field("token", &self.token)
+            .field("pin", &self.pin.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Settings<T> {
+    provisioning: Provisioning,
+    agent: ModuleSpec<T>,
+    hostname: String,
+    connect: Connect,
+    listen: Listen,
+    homedir: PathBuf,
+    moby_runtime: MobyRuntime,
+    certificates: Option<Certificates>,
+    proxy: Option<Proxy>,
+    retry: Option<Retry>,
+    logging: Option<Logging>,
+    crypto: Option<Crypto>,
+}
+
+/// The settings that require the device to be reconfigured when they change.
+/// Runtime-only settings, like the proxy and logging, are applied on every
+/// start and are left out so that changing them keeps the modules and caches.
+#[derive(Serialize)]
+struct SettingsState<'a, T: 'a> {
+    provisioning: &'a Provisioning,
+    agent: &'a ModuleSpec<T>,
+    hostname: &'a str,
+    connect: &'a Connect,
+    listen: &'a Listen,
+    homedir: &'a Path,
+    moby_runtime: &'a MobyRuntime,
+    certificates: Option<&'a Certificates>,
+    crypto: Option<&'a Crypto>,
+}
+
+impl<T> Settings<T>
+where
+    T: DeserializeOwned + Serialize,
+{
+    pub fn new(filename: Option<&str>) -> Result<Self, Error> {
+        let mut config = defaults()?;
+        if let Some(file) = filename {
+            config
+                .merge(File::with_name(file).required(true))
+                .context(ErrorKind::LoadSettings)?;
+        }
+
+        config
+            .merge(Environment::with_prefix("iotedge"))
+            .context(ErrorKind::LoadSettings)?;
+
+        let settings: Self = config.try_into().context(ErrorKind::LoadSettings)?;
+
+        Ok(settings)
+    }
+
+    /// Loads the settings from the contents of a YAML config file on top of
+    /// the same defaults as `new`. Environment variables are not merged in.
+    /// If a value is invalid, the error is `InvalidSetting` with its key.
+    pub fn from_yaml(contents: &str) -> Result<Self, Error> {
+        let config = yaml_config(contents)?;
+
+        let settings: Self = config.try_into().map_err(|err| {
+            // The config was consumed, so the key is looked for in a new one.
+            let kind = yaml_config(contents)
+                .ok()
+                .and_then(|config| invalid_key::<T>(&config))
+                .map_or(ErrorKind::LoadSettings, ErrorKind::InvalidSetting);
+            Error::from(err.context(kind))
+        })?;
+
+        Ok(settings)
+    }
+
+    pub fn provisioning(&self) -> &Provisioning {
+        &self.provisioning
+    }
+
+    pub fn agent(&self) -> &ModuleSpec<T> {
+        &self.agent
+    }
+
+    pub fn agent_mut(&mut self) -> &mut ModuleSpec<T> {
+        &mut self.agent
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    pub fn connect(&self) -> &Connect {
+        &self.connect
+    }
+
+    pub fn listen(&self) -> &Listen {
+        &self.listen
+    }
+
+    pub fn homedir(&self) -> &Path {
+        &self.homedir
+    }
+
+    pub fn moby_runtime(&self) -> &MobyRuntime {
+        &self.moby_runtime
+    }
+
+    pub fn certificates(&self) -> Option<&Certificates> {
+        self.certificates.as_ref()
+    }
+
+    pub fn proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref()
+    }
+
+    pub fn retry(&self) -> Option<&Retry> {
+        self.retry.as_ref()
+    }
+
+    pub fn logging(&self) -> Option<&Logging> {
+        self.logging.as_ref()
+    }
+
+    pub fn crypto(&self) -> Option<&Crypto> {
+        self.crypto.as_ref()
+    }
+
+    /// The digest of the settings that is cached to detect configuration
+    /// changes.
+    pub fn state_digest(&self) -> Result<String, serde_json::Error> {
+        let state = SettingsState {
+            provisioning: &self.provisioning,
+            agent: &self.agent,
+            hostname: &self.hostname,
+            connect: &self.connect,
+            listen: &self.listen,
+            homedir: &self.homedir,
+            moby_runtime: &self.moby_runtime,
+            certificates: self.certificates.as_ref(),
+            crypto: self.crypto.as_ref(),
+        };
+        let s = serde_json::to_string(&state)?;
+        Ok(base64::encode(&Sha256::digest_str(&s)))
+    }
+
+    pub fn diff_with_cached(&self, path: PathBuf) -> Result<bool, Error> {
+        OpenOptions::new()
+            .read(true)
+            .open(path)
+            .map_err(|err| err.context(ErrorKind::LoadSettings))
+            .and_then(|mut file: FsFile| {
+                let mut buffer = String::new();
+                file.read_to_string(&mut buffer)
+                    .context(ErrorKind::LoadSettings)?;
+                let encoded = self.state_digest().context(ErrorKind::LoadSettings)?;
+                if encoded == buffer {
+                    debug!("Config state matches supplied config.");
+                    Ok(false)
+                } else {
+                    Ok(true)
+                }
+            })
+            .or_else(|err| {
+                log_failure(Level::Debug, &err);
+                debug!("Error reading config backup.");
+                Ok(true)
+            })
+    }
+}
+
+fn defaults() -> Result<Config, Error> {
+    let mut config = Config::default();
+    config
+        .merge(File::from_str(DEFAULTS, FileFormat::Yaml))
+        .context(ErrorKind::LoadSettings)?;
+    Ok(config)
+}
+
+fn yaml_config(contents: &str) -> Result<Config, Error> {
+    let mut config = defaults()?;
+    config
+        .merge(File::from_str(contents, FileFormat::Yaml))
+        .context(ErrorKind::LoadSettings)?;
+    Ok(config)
+}
+
+/// Finds the key whose value keeps `config` from loading as settings, by
+/// loading one section at a time.
+fn invalid_key<T>(config: &Config) -> Option<&'static str>
+where
+    T: DeserializeOwned,
+{
+    fn invalid<S>(config: &Config, key: &'static str) -> Option<&'static str>
+    where
+        S: DeserializeOwned,
+    {
+        match config.get::<S>(key) {
+            Ok(_) | Err(ConfigError::NotFound(_)) => None,
+            Err(_) => Some(key),
+        }
+    }
+
+    // The source decides which fields the rest of the section needs, so an
+    // unknown source is reported on its own rather than as the section.
+    match config.get_str("provisioning.source") {
+        Ok(ref source) if source != "manual" && source != "dps" => {
+            return Some("provisioning.source");
+        }
+        _ => (),
+    }
+
+    invalid::<Provisioning>(config, "provisioning")
+        .or_else(|| invalid::<ModuleSpec<T>>(config, "agent"))
+        .or_else(|| invalid::<String>(config, "hostname"))
+        .or_else(|| invalid::<Connect>(config, "connect"))
+        .or_else(|| invalid::<Listen>(config, "listen"))
+        .or_else(|| invalid::<PathBuf>(config, "homedir"))
+        .or_else(|| invalid::<MobyRuntime>(config, "moby_runtime"))
+        .or_else(|| invalid::<Certificates>(config, "certificates"))
+        .or_else(|| invalid::<Proxy>(config, "proxy"))
+        .or_else(|| invalid::<Retry>(config, "retry"))
+        .or_else(|| invalid::<Logging>(config, "logging"))
+        .or_else(|| invalid::<Crypto>(config, "crypto"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::{Config, File, FileFormat};
+    use edgelet_docker::{DockerConfig, Enforcement, RestartPolicyName};
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    #[cfg(unix)]
+    static GOOD_SETTINGS: &str = "test/linux/sample_settings.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS1: &str = "test/linux/sample_settings1.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS2: &str = "test/linux/sample_settings2.yaml";
+    #[cfg(unix)]
+    static BAD_SETTINGS: &str = "test/linux/bad_sample_settings.yaml";
+    #[cfg(unix)]
+    static GOOD_SETTINGS_TG: &str = "test/linux/sample_settings.tg.yaml";
+
+    #[cfg(windows)]
+    static GOOD_SETTINGS: &str = "test/windows/sample_settings.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS1: &str = "test/windows/sample_settings1.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS2: &str = "test/windows/sample_settings2.yaml";
+    #[cfg(windows)]
+    static BAD_SETTINGS: &str = "test/windows/bad_sample_settings.yaml";
+    #[cfg(windows)]
+    static GOOD_SETTINGS_TG: &str = "test/windows/sample_settings.tg.yaml";
+
+    fn unwrap_manual_provisioning(p: &Provisioning) -> String {
+        match p {
+            Provisioning::Manual(manual) => manual.device_connection_string().to_string(),
+            _ => "not implemented".to_string(),
+        }
+    }
+
+    #[test]
+    fn default_in_yaml_matches_constant() {
+        let mut config = Config::default();
+        config
+            .merge(File::from_str(DEFAULTS, FileFormat::Yaml))
+            .unwrap();
+        let settings: Settings<DockerConfig> = config.try_into().unwrap();
+
+        match settings.provisioning() {
+            Provisioning::Manual(ref manual) => {
+                assert_eq!(manual.device_connection_string(), DEFAULT_CONNECTION_STRING)
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn from_yaml_merges_defaults() {
+        let contents = "provisioning:\n  source: \"dps\"\n  global_endpoint: \"https://global.azure-devices-provisioning.net\"\n  scope_id: \"scope\"\n  registration_id: \"device1\"\n";
+        let settings = Settings::<DockerConfig>::from_yaml(contents).unwrap();
+
+        match settings.provisioning() {
+            Provisioning::Dps(ref dps) => assert_eq!("scope", dps.scope_id()),
+            _ => panic!("provisioning is not dps"),
+        }
+        assert_eq!("edgeAgent", settings.agent().name());
+    }
+
+    #[test]
+    fn from_yaml_with_bad_source_gets_error() {
+        let settings = Settings::<DockerConfig>::from_yaml("provisioning:\n  source: \"manaul\"\n");
+        match *settings.unwrap_err().kind() {
+            ErrorKind::InvalidSetting(key) => assert_eq!("provisioning.source", key),
+            ref kind => panic!("unexpected error {}", kind),
+        }
+    }
+
+    #[test]
+    fn from_yaml_names_invalid_section() {
+        let settings =
+            Settings::<DockerConfig>::from_yaml("listen:\n  management_uri: \"not a url\"\n");
+        match *settings.unwrap_err().kind() {
+            ErrorKind::InvalidSetting(key) => assert_eq!("listen", key),
+            ref kind => panic!("unexpected error {}", kind),
+        }
+    }
+
+    #[test]
+    fn parse_connection_string_success() {
+        let manual = Manual {
+            device_connection_string: "HostName=test.com;DeviceId=test;SharedAccessKey=a2V5"
+                .to_string(),
+        };
+
+        let (key, device_id, hub) = manual.parse_device_connection_string().unwrap();
+        assert_eq!(b"key", key.as_ref());
+        assert_eq!("test", device_id);
+        assert_eq!("test.com", hub);
+    }
+
+    #[test]
+    fn parse_connection_string_in_any_order_with_extra_parts() {
+        let manual = Manual {
+            device_connection_string:
+                "DeviceId=test;Extra=something;SharedAccessKey=a2V5;HostName=test.com".to_string(),
+        };
+
+        let (_, device_id, hub) = manual.parse_device_connection_string().unwrap();
+        assert_eq!("test", device_id);
+        assert_eq!("test.com", hub);
+    }
+
+    #[test]
+    fn parse_malformed_connection_string_gets_error() {
+        for conn_string in &[
+            "",
+            "HostName=test.com;DeviceId=test;",
+            "DeviceId=test;SharedAccessKey=a2V5",
+            "HostName=test.com;SharedAccessKey=a2V5",
+            "HostName=test.com;DeviceId=test;SharedAccessKey=not base64",
+            "HostName=test/com;DeviceId=test;SharedAccessKey=a2V5",
+            DEFAULT_CONNECTION_STRING,
+        ] {
+            let manual = Manual {
+                device_connection_string: conn_string.to_string(),
+            };
+            assert!(manual.parse_device_connection_string().is_err());
+        }
+    }
+
+    #[test]
+    fn no_file_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some("garbage"));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn bad_file_gets_error() {
+        let settings = Settings::<DockerConfig>::new(Some(BAD_SETTINGS));
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn manual_file_gets_sample_connection_string() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS));
+        println!("{:?}", settings);
+        assert!(settings.is_ok());
+        let s = settings.unwrap();
+        let p = s.provisioning();
+        let connection_string = unwrap_manual_provisioning(p);
+        assert_eq!(
+            connection_string,
+            "HostName=something.something.com;DeviceId=something;SharedAccessKey=something"
+        );
+    }
+
+    #[test]
+    fn manual_file_gets_sample_tg_paths() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG));
+        println!("{:?}", settings);
+        assert!(settings.is_ok());
+        let s = settings.unwrap();
+        let certificates = s.certificates();
+        certificates
+            .map(|c| {
+                assert_eq!(c.device_ca_cert().to_str().unwrap(), "device_ca_cert.pem");
+                assert_eq!(c.device_ca_pk().to_str().unwrap(), "device_ca_pk.pem");
+                assert_eq!(
+                    c.trusted_ca_certs().to_str().unwrap(),
+                    "trusted_ca_certs.pem"
+                );
+            })
+            .expect("certificates not configured");
+    }
+
+    #[test]
+    fn image_trust_is_optional() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().image_trust().is_none());
+    }
+
+    #[test]
+    fn image_trust_settings() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        let image_trust = settings
+            .moby_runtime()
+            .image_trust()
+            .expect("image trust not configured");
+        assert_eq!(
+            image_trust.public_key().to_str().unwrap(),
+            "image_trust.pem"
+        );
+        assert_eq!(image_trust.signatures_dir().to_str().unwrap(), "signatures");
+        assert_eq!(settings.moby_runtime().network(), "azure-iot-edge");
+    }
+
+    #[test]
+    fn resource_limits_are_optional() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.moby_runtime().resource_limits().is_none());
+    }
+
+    #[test]
+    fn resource_limits_settings() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        let limits = settings
+            .moby_runtime()
+            .resource_limits()
+            .expect("resource limits not configured");
+        assert_eq!(Some(268_435_456), limits.default_profile().memory());
+        assert_eq!(None, limits.default_profile().cpu_quota());
+        assert_eq!(Some(1_073_741_824), limits.max_profile().memory());
+        assert_eq!(Some(100_000), limits.max_profile().cpu_quota());
+        assert_eq!(Some(512), limits.max_profile().pids_limit());
+        assert_eq!(Some(RestartPolicyName::OnFailure), limits.restart_policy());
+        assert_eq!(Enforcement::Reject, limits.enforcement());
+    }
+
+    #[test]
+    fn purge_volumes_defaults_to_false() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(!settings.moby_runtime().purge_volumes());
+
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        assert!(settings.moby_runtime().purge_volumes());
+    }
+
+    #[test]
+    fn proxy_is_optional() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.proxy().is_none());
+    }
+
+    #[test]
+    fn proxy_settings() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        let proxy = settings.proxy().expect("proxy not configured");
+        assert_eq!("http://proxy.example.com:3128/", proxy.uri().as_str());
+        assert_eq!(Some("edge"), proxy.username());
+        assert_eq!(Some("secret"), proxy.password());
+        assert_eq!(Some("localhost,.corp.local,10.0.0.0/8"), proxy.no_proxy());
+
+        let debug = format!("{:?}", settings);
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn retry_is_optional() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.retry().is_none());
+    }
+
+    #[test]
+    fn retry_settings() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        let retry = settings.retry().expect("retry not configured");
+        assert_eq!(Some(5), retry.max_retries());
+        assert_eq!(None, retry.initial_backoff_secs());
+        assert_eq!(Some(120), retry.max_backoff_secs());
+    }
+
+    #[test]
+    fn logging_is_optional() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.logging().is_none());
+    }
+
+    #[test]
+    fn logging_settings() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        let logging = settings.logging().expect("logging not configured");
+        assert_eq!(LogFormat::Json, logging.format());
+        assert_eq!(Some("warn"), logging.level());
+        assert_eq!(
+            "warn,edgelet_http=debug,iotedged=info",
+            logging.directives()
+        );
+    }
+
+    #[test]
+    fn privileged_group_is_optional() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.listen().privileged_group().is_none());
+    }
+
+    #[test]
+    fn privileged_group_settings() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        assert_eq!(Some("iotedge"), settings.listen().privileged_group());
+    }
+
+    #[test]
+    fn crypto_backend_defaults_to_hsm() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert!(settings.crypto().is_none());
+        assert_eq!(CryptoBackend::Hsm, Crypto::default().backend());
+        assert_eq!(KeyStoreBackend::Memory, Crypto::default().key_store());
+    }
+
+    #[test]
+    fn crypto_settings() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        let crypto = settings.crypto().expect("crypto not configured");
+        assert_eq!(CryptoBackend::Software, crypto.backend());
+        assert_eq!(KeyStoreBackend::File, crypto.key_store());
+
+        let pkcs11 = crypto.pkcs11().expect("pkcs11 not configured");
+        #[cfg(unix)]
+        assert_eq!(
+            Path::new("/usr/lib/softhsm/libsofthsm2.so"),
+            pkcs11.lib_path()
+        );
+        #[cfg(windows)]
+        assert_eq!(
+            Path::new(r"C:\SoftHSM2\lib\softhsm2-x64.dll"),
+            pkcs11.lib_path()
+        );
+        assert_eq!("edge", pkcs11.token());
+        assert_eq!(Some("1234"), pkcs11.pin());
+        assert!(!format!("{:?}", pkcs11).contains("1234"));
+    }
+
+    #[test]
+    fn diff_with_same_cached_returns_false() {
+        let tmp_dir = TempDir::new("blah").unwrap();
+        let path = tmp_dir.path().join("cache");
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        let base64_to_write = settings.state_digest().unwrap();
+        FsFile::create(path.clone())
+            .unwrap()
+            .write_all(base64_to_write.as_bytes())
+            .unwrap();
+        assert_eq!(settings.diff_with_cached(path).unwrap(), false);
+    }
+
+    #[test]
+    fn diff_with_same_cached_env_var_unordered_returns_false() {
+        let tmp_dir = TempDir::new("blah").unwrap();
+        let path = tmp_dir.path().join("cache");
+        let settings1 = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS2)).unwrap();
+        let base64_to_write = settings1.state_digest().unwrap();
+        FsFile::create(path.clone())
+            .unwrap()
+            .write_all(base64_to_write.as_bytes())
+            .unwrap();
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(settings.diff_with_cached(path).unwrap(), false);
+    }
+
+    #[test]
+    fn diff_with_different_cached_returns_true() {
+        let tmp_dir = TempDir::new("blah").unwrap();
+        let path = tmp_dir.path().join("cache");
+        let settings1 = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS1)).unwrap();
+        let base64_to_write = settings1.state_digest().unwrap();
+        FsFile::create(path.clone())
+            .unwrap()
+            .write_all(base64_to_write.as_bytes())
+            .unwrap();
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(settings.diff_with_cached(path).unwrap(), true);
+    }
+
+    #[test]
+    fn diff_with_different_logging_returns_false() {
+        let tmp_dir = TempDir::new("blah").unwrap();
+        let path = tmp_dir.path().join("cache");
+        let mut settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        FsFile::create(path.clone())
+            .unwrap()
+            .write_all(settings.state_digest().unwrap().as_bytes())
+            .unwrap();
+        settings.logging = None;
+        assert_eq!(settings.diff_with_cached(path).unwrap(), false);
+    }
+
+    #[test]
+    fn diff_with_different_proxy_returns_false() {
+        let tmp_dir = TempDir::new("blah").unwrap();
+        let path = tmp_dir.path().join("cache");
+        let mut settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS_TG)).unwrap();
+        FsFile::create(path.clone())
+            .unwrap()
+            .write_all(settings.state_digest().unwrap().as_bytes())
+            .unwrap();
+        settings.proxy = None;
+        assert_eq!(settings.diff_with_cached(path).unwrap(), false);
+    }
+
+    #[test]
+    fn diff_with_no_file_returns_true() {
+        let settings = Settings::<DockerConfig>::new(Some(GOOD_SETTINGS)).unwrap();
+        assert_eq!(
+            settings
+                .diff_with_cached(PathBuf::from("i dont exist"))
+                .unwrap(),
+            true
+        );
+    }
+
+    #[test]
+    fn network_default() {
+        let moby1 = MobyRuntime {
+            uri: Url::parse("http://test").unwrap(),
+            network: "".to_string(),
+            image_trust: None,
+            resource_limits: None,
+        };
+        assert_eq!(DEFAULT_NETWORKID, moby1.network());
+
+        let moby2 = MobyRuntime {
+            uri: Url::parse("http://test").unwrap(),
+            network: "some-network".to_string(),
+            image_trust: None,
+            resource_limits: None,
+        };
+        assert_eq!("some-network", moby2.network());
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Tests that every crypto backend has to pass. Each backend's integration
+//! tests run them against an instance of the backend.
+
+use edgelet_core::crypto::{Decrypt, Encrypt, MasterEncryptionKey};
+use edgelet_core::{
+    Certificate, CertificateIssuer, CertificateProperties, CertificateType, CreateCertificate,
+    GetTrustBundle, KeyBytes, PrivateKey, IOTEDGED_CA_ALIAS,
+};
+
+fn workload_ca_props() -> CertificateProperties {
+    CertificateProperties::new(
+        3600,
+        "test-iotedge-cn".to_string(),
+        CertificateType::Ca,
+        IOTEDGED_CA_ALIAS.to_string(),
+    )
+    .with_issuer(CertificateIssuer::DeviceCa)
+}
+
+pub fn create_cert_success<C: CreateCertificate>(crypto: &C) {
+    // act create the default issuing CA cert
+    let workload_ca_cert = crypto.create_certificate(&workload_ca_props()).unwrap();
+
+    // assert (CA cert)
+    let buffer = workload_ca_cert.pem().unwrap();
+    assert!(!buffer.as_ref().is_empty());
+
+    let san_entries: Vec<String> = vec![
+        "URI: bar:://pity/foo".to_string(),
+        "DNS: foo.bar".to_string(),
+    ];
+
+    // act
+    let props = CertificateProperties::new(
+        3600,
+        "Common Name".to_string(),
+        CertificateType::Ca,
+        "Alias".to_string(),
+    )
+    .with_san_entries(san_entries);
+
+    let cert_info = crypto.create_certificate(&props).unwrap();
+
+    assert!(cert_info.get_valid_to().is_ok());
+
+    let buffer = cert_info.pem().unwrap();
+
+    let pk = match cert_info.get_private_key().unwrap() {
+        Some(pk) => pk,
+        None => panic!("Expected to find a key"),
+    };
+
+    // assert
+    assert!(!buffer.as_ref().is_empty());
+    match pk {
+        PrivateKey::Ref(_) => panic!("did not expect reference private key"),
+        PrivateKey::Key(KeyBytes::Pem(k)) => assert!(!k.as_ref().is_empty()),
+    }
+
+    // cleanup
+    crypto.destroy_certificate("Alias".to_string()).unwrap();
+    crypto
+        .destroy_certificate(IOTEDGED_CA_ALIAS.to_string())
+        .unwrap();
+}
+
+pub fn create_cert_input_fail<C: CreateCertificate>(crypto: &C) {
+    // arrange
+    let _workload_ca_cert = crypto.create_certificate(&workload_ca_props()).unwrap();
+
+    let props_time = CertificateProperties::new(
+        0,
+        "Common Name".to_string(),
+        CertificateType::Ca,
+        "Alias".to_string(),
+    );
+    let props_cn = CertificateProperties::new(
+        3600,
+        "".to_string(),
+        CertificateType::Ca,
+        "Alias".to_string(),
+    );
+    let props_type = CertificateProperties::new(
+        3600,
+        "Common Name".to_string(),
+        CertificateType::Unknown,
+        "Alias".to_string(),
+    );
+    let props_a = CertificateProperties::new(
+        3600,
+        "Common Name".to_string(),
+        CertificateType::Ca,
+        "".to_string(),
+    );
+
+    // act
+    crypto
+        .create_certificate(&props_time)
+        .err()
+        .expect("Expected an error from bad time");
+
+    crypto
+        .create_certificate(&props_cn)
+        .err()
+        .expect("Expected an error from bad common name");
+
+    crypto
+        .create_certificate(&props_type)
+        .err()
+        .expect("Expected an error from bad cert type");
+
+    crypto
+        .create_certificate(&props_a)
+        .err()
+        .expect("Expected an error from bad alias");
+
+    crypto
+        .destroy_certificate("unknown_cert_alias".to_string())
+        .expect("Expected no error when destroying a certificate that does not exist");
+
+    // cleanup
+    crypto
+        .destroy_certificate(IOTEDGED_CA_ALIAS.to_string())
+        .unwrap();
+}
+
+pub fn get_trust_bundle<C: GetTrustBundle>(crypto: &C) {
+    // act
+    let cert_info = crypto.get_trust_bundle().unwrap();
+
+    let buffer = cert_info.pem().unwrap();
+
+    if cert_info.get_private_key().unwrap().is_some() {
+        panic!("do not expect to find a key");
+    }
+
+    // assert
+    // assume cert_type is PEM(0)
+    assert!(!buffer.as_ref().is_empty());
+}
+
+pub fn encrypt_decrypt_success<C>(crypto: &C)
+where
+    C: MasterEncryptionKey + Encrypt + Decrypt,
+{
+    // arrange
+    let client_id = b"module1";
+    let plaintext = b"plaintext";
+    let iv = b"initialization vector";
+
+    crypto
+        .create_key()
+        .expect("Create master key function returned error");
+
+    //act
+    let ciphertext = crypto
+        .encrypt(client_id, plaintext, iv)
+        .expect("Encrypt function returned error");
+    assert_ne!(ciphertext.as_ref().len(), 0);
+
+    //act
+    let plaintext_result = crypto
+        .decrypt(client_id, ciphertext.as_ref(), iv)
+        .expect("Decrypt function returned error");
+    assert_eq!(
+        plaintext,
+        plaintext_result.as_ref(),
+        "Failure plaintext after decrypt did not match {:?} and {:?}",
+        plaintext,
+        plaintext_result.as_ref()
+    );
+
+    let bad_client_id = b"module2";
+    crypto
+        .decrypt(bad_client_id, ciphertext.as_ref(), iv)
+        .err()
+        .expect("Decrypt function returned unexpected success");
+
+    let bad_iv = b"inconsistent_iv";
+    crypto
+        .decrypt(client_id, ciphertext.as_ref(), bad_iv)
+        .err()
+        .expect("Decrypt function returned unexpected success");
+
+    // cleanup
+    crypto
+        .destroy_key()
+        .expect("Destroy master key function returned error");
+}
+
+pub fn master_key_success<C: MasterEncryptionKey>(crypto: &C) {
+    crypto
+        .destroy_key()
+        .expect("Destroy master key returned error");
+
+    crypto
+        .create_key()
+        .expect("First create master key function returned error");
+
+    crypto
+        .create_key()
+        .expect("Second master key function returned error");
+
+    crypto
+        .destroy_key()
+        .expect("First destroy master key function returned error");
+
+    crypto
+        .destroy_key()
+        .expect("Second destroy master key function returned error");
+}
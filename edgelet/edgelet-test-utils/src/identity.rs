@@ -1,8 +1,9 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use futures::future::{self, FutureResult, IntoFuture};
+use serde_json::Value;
 
-use edgelet_core::{AuthType, Identity, IdentityManager, IdentitySpec};
+use edgelet_core::{AuthType, Identity, IdentityManager, IdentitySpec, Twin, TwinTarget};
 
 #[derive(Clone, Copy, Debug, Fail)]
 pub enum Error {
@@ -69,6 +70,7 @@ pub struct TestIdentityManager {
     fail_list: bool,
     fail_get: bool,
     fail_create: bool,
+    twins: Vec<(TwinTarget, Twin)>,
 }
 
 impl TestIdentityManager {
@@ -79,6 +81,7 @@ impl TestIdentityManager {
             fail_list: false,
             fail_get: true,
             fail_create: false,
+            twins: vec![],
         }
     }
 
@@ -96,6 +99,11 @@ impl TestIdentityManager {
         self.fail_create = fail_create;
         self
     }
+
+    pub fn with_twin(mut self, target: TwinTarget, twin: Twin) -> Self {
+        self.twins.push((target, twin));
+        self
+    }
 }
 
 impl IdentityManager for TestIdentityManager {
@@ -106,6 +114,8 @@ impl IdentityManager for TestIdentityManager {
     type ListFuture = FutureResult<Vec<Self::Identity>, Self::Error>;
     type GetFuture = FutureResult<Option<Self::Identity>, Self::Error>;
     type DeleteFuture = FutureResult<(), Self::Error>;
+    type GetTwinFuture = FutureResult<Twin, Self::Error>;
+    type ReportPropertiesFuture = FutureResult<(), Self::Error>;
 
     fn create(&mut self, id: IdentitySpec) -> Self::CreateFuture {
         if self.fail_create {
@@ -187,4 +197,29 @@ impl IdentityManager for TestIdentityManager {
             .ok_or(Error::ModuleNotFound)
             .into_future()
     }
+
+    fn get_twin(&self, target: TwinTarget) -> Self::GetTwinFuture {
+        self.twins
+            .iter()
+            .find(|(t, _)| *t == target)
+            .map(|(_, twin)| twin.clone())
+            .ok_or(Error::ModuleNotFound)
+            .into_future()
+    }
+
+    fn report_properties(
+        &mut self,
+        target: TwinTarget,
+        reported: Value,
+    ) -> Self::ReportPropertiesFuture {
+        self.twins
+            .iter_mut()
+            .find(|(t, _)| *t == target)
+            .map(|(_, twin)| {
+                let properties = twin.properties().clone().with_reported(Some(reported));
+                *twin = Twin::new(twin.version() + 1, properties);
+            })
+            .ok_or(Error::ModuleNotFound)
+            .into_future()
+    }
 }
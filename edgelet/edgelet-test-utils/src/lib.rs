@@ -39,6 +39,7 @@ extern crate tokio;
 use std::net::TcpListener;
 
 pub mod cert;
+pub mod crypto;
 pub mod identity;
 mod json_connector;
 pub mod module;
@@ -1,6 +1,8 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::io::Cursor;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use edgelet_core::*;
@@ -103,6 +105,11 @@ impl<E: Clone + Fail> Module for TestModule<E> {
 pub struct TestRuntime<E: Fail> {
     module: Result<TestModule<E>, E>,
     registry: NullRegistry<E>,
+    volumes: Vec<ModuleVolume>,
+    create_error: Arc<Mutex<Option<E>>>,
+    start_error: Arc<Mutex<Option<E>>>,
+    operations: Arc<Mutex<Vec<String>>>,
+    created: Arc<Mutex<Vec<String>>>,
 }
 
 impl<E: Fail> TestRuntime<E> {
@@ -110,8 +117,61 @@ impl<E: Fail> TestRuntime<E> {
         TestRuntime {
             module,
             registry: NullRegistry::new(),
+            volumes: vec![],
+            create_error: Arc::new(Mutex::new(None)),
+            start_error: Arc::new(Mutex::new(None)),
+            operations: Arc::new(Mutex::new(vec![])),
+            created: Arc::new(Mutex::new(vec![])),
         }
     }
+
+    pub fn with_volumes(mut self, volumes: Vec<ModuleVolume>) -> Self {
+        self.volumes = volumes;
+        self
+    }
+
+    /// Makes the next call to `create` fail with `err`.
+    pub fn with_create_error(self, err: E) -> Self {
+        *self.create_error.lock().unwrap() = Some(err);
+        self
+    }
+
+    /// Makes the next call to `start` fail with `err`.
+    pub fn with_start_error(self, err: E) -> Self {
+        *self.start_error.lock().unwrap() = Some(err);
+        self
+    }
+
+    /// The create, start, stop, rename and remove calls made so far, in
+    /// order, for example `"rename m1 m1_previous"`.
+    pub fn operations(&self) -> Vec<String> {
+        self.operations.lock().unwrap().clone()
+    }
+
+    fn record(&self, operation: String) {
+        self.operations.lock().unwrap().push(operation);
+    }
+
+    /// The module, followed by copies of it under the names of the modules
+    /// created since, which have the same state.
+    fn modules(&self, module: &TestModule<E>) -> Vec<TestModule<E>>
+    where
+        E: Clone,
+    {
+        let mut modules = vec![module.clone()];
+        modules.extend(
+            self.created
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|name| **name != module.name)
+                .map(|name| TestModule {
+                    name: name.clone(),
+                    ..module.clone()
+                }),
+        );
+        modules
+    }
 }
 
 pub struct EmptyBody<E> {
@@ -154,6 +214,8 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
     type ModuleRegistry = NullRegistry<E>;
     type Chunk = String;
     type Logs = EmptyBody<Self::Error>;
+    type VolumeArchive = EmptyBody<Self::Error>;
+    type ExecIo = Cursor<Vec<u8>>;
 
     type CreateFuture = FutureResult<(), Self::Error>;
     type InitFuture = FutureResult<(), Self::Error>;
@@ -162,11 +224,19 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
         Box<Stream<Item = (Self::Module, ModuleRuntimeState), Error = Self::Error> + Send>;
     type LogsFuture = FutureResult<Self::Logs, Self::Error>;
     type RemoveFuture = FutureResult<(), Self::Error>;
+    type RenameFuture = FutureResult<(), Self::Error>;
     type RestartFuture = FutureResult<(), Self::Error>;
     type StartFuture = FutureResult<(), Self::Error>;
     type StopFuture = FutureResult<(), Self::Error>;
     type SystemInfoFuture = FutureResult<SystemInfo, Self::Error>;
     type RemoveAllFuture = FutureResult<(), Self::Error>;
+    type ListVolumesFuture = FutureResult<Vec<ModuleVolume>, Self::Error>;
+    type GetVolumeFuture = FutureResult<ModuleVolume, Self::Error>;
+    type BackupVolumeFuture = FutureResult<Self::VolumeArchive, Self::Error>;
+    type RemoveVolumeFuture = FutureResult<(), Self::Error>;
+    type ExecFuture = FutureResult<String, Self::Error>;
+    type ExecStartFuture = FutureResult<Self::ExecIo, Self::Error>;
+    type ExecInspectFuture = FutureResult<ExecStatus, Self::Error>;
 
     fn system_info(&self) -> Self::SystemInfoFuture {
         match self.module {
@@ -185,21 +255,33 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
         }
     }
 
-    fn create(&self, _module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+    fn create(&self, module: ModuleSpec<Self::Config>) -> Self::CreateFuture {
+        self.record(format!("create {}", module.name()));
+        if let Some(err) = self.create_error.lock().unwrap().take() {
+            return future::err(err);
+        }
         match self.module {
-            Ok(_) => future::ok(()),
+            Ok(_) => {
+                self.created.lock().unwrap().push(module.name().to_string());
+                future::ok(())
+            }
             Err(ref e) => future::err(e.clone()),
         }
     }
 
-    fn start(&self, _id: &str) -> Self::StartFuture {
+    fn start(&self, id: &str) -> Self::StartFuture {
+        self.record(format!("start {}", id));
+        if let Some(err) = self.start_error.lock().unwrap().take() {
+            return future::err(err);
+        }
         match self.module {
             Ok(_) => future::ok(()),
             Err(ref e) => future::err(e.clone()),
         }
     }
 
-    fn stop(&self, _id: &str, _wait_before_kill: Option<Duration>) -> Self::StopFuture {
+    fn stop(&self, id: &str, _wait_before_kill: Option<Duration>) -> Self::StopFuture {
+        self.record(format!("stop {}", id));
         match self.module {
             Ok(_) => future::ok(()),
             Err(ref e) => future::err(e.clone()),
@@ -213,7 +295,22 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
         }
     }
 
-    fn remove(&self, _id: &str) -> Self::RemoveFuture {
+    fn remove(&self, id: &str) -> Self::RemoveFuture {
+        self.record(format!("remove {}", id));
+        self.created.lock().unwrap().retain(|name| name != id);
+        match self.module {
+            Ok(_) => future::ok(()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn rename(&self, id: &str, new_name: &str) -> Self::RenameFuture {
+        self.record(format!("rename {} {}", id, new_name));
+        for name in self.created.lock().unwrap().iter_mut() {
+            if name == id {
+                *name = new_name.to_string();
+            }
+        }
         match self.module {
             Ok(_) => future::ok(()),
             Err(ref e) => future::err(e.clone()),
@@ -229,10 +326,11 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
 
     fn list_with_details(&self) -> Self::ListWithDetailsStream {
         match self.module {
-            Ok(ref m) => {
-                let m = m.clone();
-                Box::new(m.runtime_state().map(|rs| (m, rs)).into_stream())
-            }
+            Ok(ref m) => Box::new(stream::futures_ordered(
+                self.modules(m)
+                    .into_iter()
+                    .map(|m| m.runtime_state().map(|rs| (m, rs))),
+            )),
             Err(ref e) => Box::new(stream::once(Err(e.clone()))),
         }
     }
@@ -248,7 +346,68 @@ impl<E: Clone + Fail> ModuleRuntime for TestRuntime<E> {
         &self.registry
     }
 
-    fn remove_all(&self) -> Self::RemoveAllFuture {
+    fn remove_all(&self, _purge_volumes: bool) -> Self::RemoveAllFuture {
         future::ok(())
     }
+
+    fn list_volumes(&self, module: &str) -> Self::ListVolumesFuture {
+        match self.module {
+            Ok(_) => future::ok(
+                self.volumes
+                    .iter()
+                    .filter(|v| v.module() == module)
+                    .cloned()
+                    .collect(),
+            ),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn get_volume(&self, module: &str, name: &str) -> Self::GetVolumeFuture {
+        match self.module {
+            Ok(_) => future::ok(
+                self.volumes
+                    .iter()
+                    .find(|v| v.module() == module && v.name() == name)
+                    .cloned()
+                    .expect("volume not configured in test runtime"),
+            ),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn backup_volume(&self, _module: &str, _name: &str) -> Self::BackupVolumeFuture {
+        match self.module {
+            Ok(_) => future::ok(EmptyBody::new()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn remove_volume(&self, _module: &str, _name: &str) -> Self::RemoveVolumeFuture {
+        match self.module {
+            Ok(_) => future::ok(()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn exec(&self, _id: &str, _options: &ExecOptions) -> Self::ExecFuture {
+        match self.module {
+            Ok(_) => future::ok("test-exec".to_string()),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn exec_start(&self, _exec_id: &str, _tty: bool) -> Self::ExecStartFuture {
+        match self.module {
+            Ok(_) => future::ok(Cursor::new(vec![])),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
+
+    fn exec_inspect(&self, _exec_id: &str) -> Self::ExecInspectFuture {
+        match self.module {
+            Ok(_) => future::ok(ExecStatus::new(false, Some(0))),
+            Err(ref e) => future::err(e.clone()),
+        }
+    }
 }
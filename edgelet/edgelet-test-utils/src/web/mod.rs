@@ -39,7 +39,7 @@ where
     serve.for_each(|connecting| {
         connecting
             .then(|connection| {
-                let connection = connection.unwrap();
+                let connection = connection.unwrap().with_upgrades();
                 Ok::<_, hyper::Error>(connection)
             })
             .flatten()
@@ -39,7 +39,7 @@ use std::cmp;
 use std::collections::HashMap;
 
 pub use error::{Error, ErrorKind};
-pub use logging::log_failure;
+pub use logging::{log_failure, LogLevels, MAX_REVERT_AFTER_SECS};
 pub use macros::ensure_not_empty_with_context;
 pub use ser_de::{serde_clone, serialize_ordered, string_or_struct};
 
@@ -1,7 +1,11 @@
 // Copyright (c) Microsoft. All rights reserved.
 
+use std::cmp;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
 use failure::Fail;
-use log::Level;
+use log::{self, Level, LevelFilter};
 
 pub fn log_failure(level: Level, fail: &dyn Fail) {
     log!(level, "{}", fail);
@@ -9,3 +13,260 @@ pub fn log_failure(level: Level, fail: &dyn Fail) {
         log!(level, "\tcaused by: {}", cause);
     }
 }
+
+/// The longest a changed level can be kept before it reverts by itself.
+pub const MAX_REVERT_AFTER_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Log levels changed while the process is running. They take precedence over
+/// the levels the logger was configured with. Clones share their state.
+///
+/// A level set for a target also applies to the targets below it, so
+/// `edgelet_http` covers `edgelet_http::client`, and the most specific one
+/// wins. Levels can be set to revert by themselves after a while.
+///
+/// The global maximum level of the `log` crate follows the most verbose level
+/// in effect, so that it drops back once a changed level is reverted.
+#[derive(Clone, Debug, Default)]
+pub struct LogLevels {
+    state: Arc<RwLock<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The most verbose level the logger was configured with, if known.
+    configured: Option<LevelFilter>,
+    overrides: Vec<LevelOverride>,
+}
+
+impl State {
+    fn has_expired(&self, now: Instant) -> bool {
+        self.overrides.iter().any(|o| o.is_expired(now))
+    }
+
+    fn remove(&mut self, target: Option<&str>, now: Instant) {
+        self.overrides
+            .retain(|o| !o.is_for(target) && !o.is_expired(now));
+    }
+
+    fn remove_expired(&mut self, now: Instant) {
+        self.overrides.retain(|o| !o.is_expired(now));
+    }
+
+    fn level(&self, target: &str, now: Instant) -> Option<LevelFilter> {
+        self.overrides
+            .iter()
+            .filter(|o| !o.is_expired(now) && o.matches(target))
+            .max_by_key(|o| o.target.as_ref().map_or(0, |prefix| prefix.len() + 1))
+            .map(|o| o.level)
+    }
+
+    /// The most verbose level in effect. Without a configured level only the
+    /// changed levels are known, so the current maximum is kept as a floor.
+    fn max_level(&self, current: LevelFilter) -> LevelFilter {
+        let overridden = self
+            .overrides
+            .iter()
+            .map(|o| o.level)
+            .max()
+            .unwrap_or(LevelFilter::Off);
+        cmp::max(self.configured.unwrap_or(current), overridden)
+    }
+
+    fn update_max_level(&self) {
+        log::set_max_level(self.max_level(log::max_level()));
+    }
+}
+
+#[derive(Debug)]
+struct LevelOverride {
+    target: Option<String>,
+    level: LevelFilter,
+    expires_at: Option<Instant>,
+}
+
+impl LevelOverride {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= now)
+    }
+
+    fn is_for(&self, target: Option<&str>) -> bool {
+        self.target.as_ref().map(AsRef::as_ref) == target
+    }
+
+    fn matches(&self, target: &str) -> bool {
+        self.target.as_ref().map_or(true, |prefix| {
+            target.starts_with(prefix.as_str())
+                && (target.len() == prefix.len() || target[prefix.len()..].starts_with("::"))
+        })
+    }
+}
+
+impl LogLevels {
+    pub fn new() -> Self {
+        LogLevels::default()
+    }
+
+    /// Records the most verbose level the logger was configured with and sets
+    /// the global maximum level accordingly.
+    pub fn set_configured_level(&self, level: LevelFilter) {
+        let mut state = self.state.write().unwrap();
+        state.configured = Some(level);
+        state.remove_expired(Instant::now());
+        state.update_max_level();
+    }
+
+    /// Sets the level of `target`, or of all targets if it is `None`, until
+    /// it is reset or `revert_after` has passed. `revert_after` is limited to
+    /// `MAX_REVERT_AFTER_SECS`.
+    pub fn set(&self, target: Option<&str>, level: LevelFilter, revert_after: Option<Duration>) {
+        let now = Instant::now();
+        let revert_after = revert_after
+            .map(|revert_after| cmp::min(revert_after, Duration::from_secs(MAX_REVERT_AFTER_SECS)));
+        let mut state = self.state.write().unwrap();
+        state.remove(target, now);
+        state.overrides.push(LevelOverride {
+            target: target.map(ToString::to_string),
+            level,
+            expires_at: revert_after.map(|revert_after| now + revert_after),
+        });
+        state.update_max_level();
+    }
+
+    /// Reverts `target`, or all targets if it is `None`, to the configured
+    /// level. Returns whether its level had been changed.
+    pub fn reset(&self, target: Option<&str>) -> bool {
+        let now = Instant::now();
+        let mut state = self.state.write().unwrap();
+        let changed = state
+            .overrides
+            .iter()
+            .any(|o| o.is_for(target) && !o.is_expired(now));
+        state.remove(target, now);
+        state.update_max_level();
+        changed
+    }
+
+    /// The level set for `target`, if any. Levels that have reverted by now
+    /// are dropped.
+    pub fn level(&self, target: &str) -> Option<LevelFilter> {
+        let now = Instant::now();
+        {
+            let state = self.state.read().unwrap();
+            if !state.has_expired(now) {
+                return state.level(target, now);
+            }
+        }
+
+        let mut state = self.state.write().unwrap();
+        state.remove_expired(now);
+        state.update_max_level();
+        state.level(target, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_level_wins() {
+        let levels = LogLevels::new();
+        levels.set(None, LevelFilter::Warn, None);
+        levels.set(Some("edgelet_http"), LevelFilter::Debug, None);
+        levels.set(Some("edgelet_http::client"), LevelFilter::Trace, None);
+
+        assert_eq!(Some(LevelFilter::Warn), levels.level("iotedged"));
+        assert_eq!(Some(LevelFilter::Debug), levels.level("edgelet_http"));
+        assert_eq!(
+            Some(LevelFilter::Debug),
+            levels.level("edgelet_http::route")
+        );
+        assert_eq!(
+            Some(LevelFilter::Trace),
+            levels.level("edgelet_http::client::retry")
+        );
+        assert_eq!(Some(LevelFilter::Warn), levels.level("edgelet_http_mgmt"));
+    }
+
+    #[test]
+    fn set_replaces_and_reset_reverts() {
+        let levels = LogLevels::new();
+        assert_eq!(None, levels.level("iotedged"));
+
+        levels.set(Some("iotedged"), LevelFilter::Debug, None);
+        levels.set(Some("iotedged"), LevelFilter::Error, None);
+        assert_eq!(Some(LevelFilter::Error), levels.level("iotedged"));
+
+        assert!(levels.reset(Some("iotedged")));
+        assert!(!levels.reset(Some("iotedged")));
+        assert_eq!(None, levels.level("iotedged"));
+    }
+
+    #[test]
+    fn levels_revert_after_timeout() {
+        let levels = LogLevels::new();
+        levels.set(
+            Some("iotedged"),
+            LevelFilter::Debug,
+            Some(Duration::from_secs(0)),
+        );
+        levels.set(
+            Some("edgelet_http"),
+            LevelFilter::Debug,
+            Some(Duration::from_secs(3600)),
+        );
+
+        assert_eq!(None, levels.level("iotedged"));
+        assert!(!levels.reset(Some("iotedged")));
+        assert_eq!(Some(LevelFilter::Debug), levels.level("edgelet_http"));
+    }
+
+    #[test]
+    fn max_level_follows_levels_in_effect() {
+        let levels = LogLevels::new();
+        let max_level = || levels.state.read().unwrap().max_level(LevelFilter::Off);
+
+        levels.set_configured_level(LevelFilter::Info);
+        assert_eq!(LevelFilter::Info, max_level());
+
+        levels.set(Some("iotedged"), LevelFilter::Trace, None);
+        levels.set(Some("edgelet_http"), LevelFilter::Debug, None);
+        assert_eq!(LevelFilter::Trace, max_level());
+
+        assert!(levels.reset(Some("iotedged")));
+        assert_eq!(LevelFilter::Debug, max_level());
+
+        levels.set(
+            Some("edgelet_http"),
+            LevelFilter::Debug,
+            Some(Duration::from_secs(0)),
+        );
+
+        // the reverted level is dropped the next time a level is looked up
+        assert_eq!(None, levels.level("edgelet_http"));
+        assert_eq!(LevelFilter::Info, max_level());
+    }
+
+    #[test]
+    fn max_level_is_only_raised_without_configured_level() {
+        let levels = LogLevels::new();
+        levels.set(Some("iotedged"), LevelFilter::Debug, None);
+
+        let state = levels.state.read().unwrap();
+        assert_eq!(LevelFilter::Debug, state.max_level(LevelFilter::Info));
+        assert_eq!(LevelFilter::Trace, state.max_level(LevelFilter::Trace));
+    }
+
+    #[test]
+    fn long_revert_after_is_limited() {
+        let levels = LogLevels::new();
+        levels.set(
+            Some("iotedged"),
+            LevelFilter::Debug,
+            Some(Duration::from_secs(u64::max_value())),
+        );
+
+        assert_eq!(Some(LevelFilter::Debug), levels.level("iotedged"));
+    }
+}
@@ -27,9 +27,13 @@ pub enum CertificateType {
 /// - [`MakeRandom`]
 /// - [`CreateMasterEncryptionKey`]
 /// - [`DestroyMasterEncryptionKey`]
+/// - [`CreateEncryptionKey`]
+/// - [`DestroyEncryptionKey`]
 /// - [`CreateCertificate`]
 /// - [`Encrypt`]
 /// - [`Decrypt`]
+/// - [`EncryptWithKey`]
+/// - [`DecryptWithKey`]
 ///
 #[derive(Clone, Debug)]
 pub struct Crypto {
@@ -133,6 +137,36 @@ impl DestroyMasterEncryptionKey for Crypto {
     }
 }
 
+impl CreateEncryptionKey for Crypto {
+    fn create_encryption_key(&self, alias: &str) -> Result<(), Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_create_encryption_key
+            .ok_or(ErrorKind::NoneFn)?;
+        let c_alias = CString::new(alias).map_err(|_| ErrorKind::ToCStr)?;
+        let result = unsafe { if_fn(self.handle, c_alias.as_ptr()) };
+        match result {
+            0 => Ok(()),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+}
+
+impl DestroyEncryptionKey for Crypto {
+    fn destroy_encryption_key(&self, alias: &str) -> Result<(), Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_destroy_encryption_key
+            .ok_or(ErrorKind::NoneFn)?;
+        let c_alias = CString::new(alias).map_err(|_| ErrorKind::ToCStr)?;
+        let result = unsafe { if_fn(self.handle, c_alias.as_ptr()) };
+        match result {
+            0 => Ok(()),
+            r => Err(ErrorKind::Api(r))?,
+        }
+    }
+}
+
 fn make_certification_props(props: &CertificateProperties) -> Result<CERT_PROPS_HANDLE, Error> {
     let handle = unsafe { cert_properties_create() };
     if handle.is_null() {
@@ -364,6 +398,100 @@ impl Decrypt for Crypto {
     }
 }
 
+impl EncryptWithKey for Crypto {
+    fn encrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Buffer, Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_encrypt_data_with_key
+            .ok_or(ErrorKind::NoneFn)?;
+
+        let c_alias = CString::new(alias).map_err(|_| ErrorKind::ToCStr)?;
+        let c_client_id = SIZED_BUFFER {
+            buffer: client_id.as_ptr() as *mut c_uchar,
+            size: client_id.len(),
+        };
+        let c_plaintext = SIZED_BUFFER {
+            buffer: plaintext.as_ptr() as *mut c_uchar,
+            size: plaintext.len(),
+        };
+        let c_initialization_vector = SIZED_BUFFER {
+            buffer: initialization_vector.as_ptr() as *mut c_uchar,
+            size: initialization_vector.len(),
+        };
+        let mut encrypted = SIZED_BUFFER {
+            buffer: std::ptr::null_mut() as *mut c_uchar,
+            size: 0,
+        };
+        let result = unsafe {
+            if_fn(
+                self.handle,
+                c_alias.as_ptr(),
+                &c_client_id,
+                &c_plaintext,
+                &c_initialization_vector,
+                &mut encrypted,
+            )
+        };
+        match result {
+            0 => Ok(Buffer::new(self.interface, encrypted)),
+            r => Err(r)?,
+        }
+    }
+}
+
+impl DecryptWithKey for Crypto {
+    fn decrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Buffer, Error> {
+        let if_fn = self
+            .interface
+            .hsm_client_decrypt_data_with_key
+            .ok_or(ErrorKind::NoneFn)?;
+
+        let c_alias = CString::new(alias).map_err(|_| ErrorKind::ToCStr)?;
+        let c_client_id = SIZED_BUFFER {
+            buffer: client_id.as_ptr() as *mut c_uchar,
+            size: client_id.len(),
+        };
+        let c_ciphertext = SIZED_BUFFER {
+            buffer: ciphertext.as_ptr() as *mut c_uchar,
+            size: ciphertext.len(),
+        };
+        let c_initialization_vector = SIZED_BUFFER {
+            buffer: initialization_vector.as_ptr() as *mut c_uchar,
+            size: initialization_vector.len(),
+        };
+        let mut decrypted = SIZED_BUFFER {
+            buffer: std::ptr::null_mut() as *mut c_uchar,
+            size: 0,
+        };
+        let result = unsafe {
+            if_fn(
+                self.handle,
+                c_alias.as_ptr(),
+                &c_client_id,
+                &c_ciphertext,
+                &c_initialization_vector,
+                &mut decrypted,
+            )
+        };
+        match result {
+            0 => Ok(Buffer::new(self.interface, decrypted)),
+            r => Err(r)?,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CertificateProperties {
     validity_in_secs: u64,
@@ -626,8 +754,9 @@ mod tests {
     use std::os::raw::{c_char, c_int, c_uchar, c_void};
 
     use super::super::{
-        CreateCertificate, CreateMasterEncryptionKey, Decrypt, DestroyMasterEncryptionKey, Encrypt,
-        GetTrustBundle, MakeRandom,
+        CreateCertificate, CreateEncryptionKey, CreateMasterEncryptionKey, Decrypt, DecryptWithKey,
+        DestroyEncryptionKey, DestroyMasterEncryptionKey, Encrypt, EncryptWithKey, GetTrustBundle,
+        MakeRandom,
     };
     use super::{Buffer, CertificateProperties, Crypto};
     use hsm_sys::*;
@@ -922,6 +1051,60 @@ mod tests {
             1
         }
     }
+    unsafe extern "C" fn fake_create_key(
+        handle: HSM_CLIENT_HANDLE,
+        _alias: *const c_char,
+    ) -> c_int {
+        let n = handle as isize;
+        if n == 0 {
+            0
+        } else {
+            1
+        }
+    }
+    unsafe extern "C" fn fake_destroy_key(
+        handle: HSM_CLIENT_HANDLE,
+        _alias: *const c_char,
+    ) -> c_int {
+        let n = handle as isize;
+        if n == 0 {
+            0
+        } else {
+            1
+        }
+    }
+    unsafe extern "C" fn fake_encrypt_with_key(
+        handle: HSM_CLIENT_HANDLE,
+        _alias: *const c_char,
+        client_id: *const SIZED_BUFFER,
+        plaintext: *const SIZED_BUFFER,
+        initialization_vector: *const SIZED_BUFFER,
+        ciphertext: *mut SIZED_BUFFER,
+    ) -> c_int {
+        fake_encrypt(
+            handle,
+            client_id,
+            plaintext,
+            initialization_vector,
+            ciphertext,
+        )
+    }
+    unsafe extern "C" fn fake_decrypt_with_key(
+        handle: HSM_CLIENT_HANDLE,
+        _alias: *const c_char,
+        client_id: *const SIZED_BUFFER,
+        ciphertext: *const SIZED_BUFFER,
+        initialization_vector: *const SIZED_BUFFER,
+        plaintext: *mut SIZED_BUFFER,
+    ) -> c_int {
+        fake_decrypt(
+            handle,
+            client_id,
+            ciphertext,
+            initialization_vector,
+            plaintext,
+        )
+    }
 
     unsafe extern "C" fn fake_trust_bundle(handle: HSM_CLIENT_HANDLE) -> CERT_INFO_HANDLE {
         let n = handle as isize;
@@ -1036,6 +1219,50 @@ mod tests {
             .unwrap();
         println!("You should never see this print {:?}", result);
     }
+    #[test]
+    #[should_panic(expected = "HSM API Not Implemented")]
+    fn no_create_encryption_key_api_fail() {
+        let hsm_crypto = fake_no_if_hsm_crypto();
+        hsm_crypto.create_encryption_key("alias").unwrap();
+        println!("You should never see this print");
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API Not Implemented")]
+    fn no_destroy_encryption_key_api_fail() {
+        let hsm_crypto = fake_no_if_hsm_crypto();
+        hsm_crypto.destroy_encryption_key("alias").unwrap();
+        println!("You should never see this print");
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API Not Implemented")]
+    fn no_encrypt_with_key_api_fail() {
+        let hsm_crypto = fake_no_if_hsm_crypto();
+        let result = hsm_crypto
+            .encrypt_with_key("alias", b"client_id", b"plaintext", b"init_vector")
+            .unwrap();
+        println!("You should never see this print {:?}", result);
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API Not Implemented")]
+    fn no_decrypt_with_key_api_fail() {
+        let hsm_crypto = fake_no_if_hsm_crypto();
+        let result = hsm_crypto
+            .decrypt_with_key("alias", b"client_id", b"ciphertext", b"init_vector")
+            .unwrap();
+        println!("You should never see this print {:?}", result);
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not convert parameter to c string")]
+    fn create_encryption_key_nul_alias_fail() {
+        let hsm_crypto = fake_good_hsm_crypto();
+        hsm_crypto.create_encryption_key("ali\0as").unwrap();
+        println!("You should never see this print");
+    }
+
     fn fake_bad_hsm_crypto() -> Crypto {
         Crypto {
             handle: unsafe { fake_handle_create_bad() },
@@ -1051,6 +1278,10 @@ mod tests {
                 hsm_client_decrypt_data: Some(fake_decrypt),
                 hsm_client_get_trust_bundle: Some(fake_trust_bundle),
                 hsm_client_free_buffer: Some(real_buffer_destroy),
+                hsm_client_create_encryption_key: Some(fake_create_key),
+                hsm_client_destroy_encryption_key: Some(fake_destroy_key),
+                hsm_client_encrypt_data_with_key: Some(fake_encrypt_with_key),
+                hsm_client_decrypt_data_with_key: Some(fake_decrypt_with_key),
             },
         }
     }
@@ -1117,6 +1348,42 @@ mod tests {
         println!("You should never see this print {:?}", result);
     }
 
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_create_encryption_key_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        hsm_crypto.create_encryption_key("alias").unwrap();
+        println!("You should never see this print");
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_destroy_encryption_key_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        hsm_crypto.destroy_encryption_key("alias").unwrap();
+        println!("You should never see this print");
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_encrypt_with_key_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        let result = hsm_crypto
+            .encrypt_with_key("alias", b"client_id", b"plaintext", b"init_vector")
+            .unwrap();
+        println!("You should never see this print {:?}", result);
+    }
+
+    #[test]
+    #[should_panic(expected = "HSM API failure occurred")]
+    fn hsm_decrypt_with_key_errors() {
+        let hsm_crypto = fake_bad_hsm_crypto();
+        let result = hsm_crypto
+            .decrypt_with_key("alias", b"client_id", b"ciphertext", b"init_vector")
+            .unwrap();
+        println!("You should never see this print {:?}", result);
+    }
+
     fn fake_good_hsm_crypto() -> Crypto {
         Crypto {
             handle: unsafe { fake_handle_create_good() },
@@ -1132,6 +1399,10 @@ mod tests {
                 hsm_client_decrypt_data: Some(fake_decrypt),
                 hsm_client_get_trust_bundle: Some(fake_trust_bundle),
                 hsm_client_free_buffer: Some(real_buffer_destroy),
+                hsm_client_create_encryption_key: Some(fake_create_key),
+                hsm_client_destroy_encryption_key: Some(fake_destroy_key),
+                hsm_client_encrypt_data_with_key: Some(fake_encrypt_with_key),
+                hsm_client_decrypt_data_with_key: Some(fake_decrypt_with_key),
             },
         }
     }
@@ -1170,6 +1441,20 @@ mod tests {
 
         assert_eq!(plain1.len(), DEFAULT_BUF_LEN);
         assert_eq!(plain2.len(), DEFAULT_BUF_LEN);
+
+        let _alias_key: () = hsm_crypto.create_encryption_key("alias").unwrap();
+
+        let crypt3 = hsm_crypto
+            .encrypt_with_key("alias", b"client_id", b"plaintext", b"init_vector")
+            .unwrap();
+        assert_eq!(crypt3.len(), DEFAULT_BUF_LEN);
+
+        let plain3 = hsm_crypto
+            .decrypt_with_key("alias", b"client_id", b"ciphertext", b"init_vector")
+            .unwrap();
+        assert_eq!(plain3.len(), DEFAULT_BUF_LEN);
+
+        let _destroy_alias_key: () = hsm_crypto.destroy_encryption_key("alias").unwrap();
     }
 
 }
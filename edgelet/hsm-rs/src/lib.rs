@@ -71,6 +71,14 @@ pub trait DestroyMasterEncryptionKey {
     fn destroy_master_encryption_key(&self) -> Result<(), Error>;
 }
 
+pub trait CreateEncryptionKey {
+    fn create_encryption_key(&self, alias: &str) -> Result<(), Error>;
+}
+
+pub trait DestroyEncryptionKey {
+    fn destroy_encryption_key(&self, alias: &str) -> Result<(), Error>;
+}
+
 pub trait CreateCertificate {
     fn create_certificate(
         &self,
@@ -98,6 +106,26 @@ pub trait Decrypt {
     ) -> Result<Buffer, Error>;
 }
 
+pub trait EncryptWithKey {
+    fn encrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        plaintext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Buffer, Error>;
+}
+
+pub trait DecryptWithKey {
+    fn decrypt_with_key(
+        &self,
+        alias: &str,
+        client_id: &[u8],
+        ciphertext: &[u8],
+        initialization_vector: &[u8],
+    ) -> Result<Buffer, Error>;
+}
+
 pub trait GetTrustBundle {
     fn get_trust_bundle(&self) -> Result<HsmCertificate, Error>;
 }
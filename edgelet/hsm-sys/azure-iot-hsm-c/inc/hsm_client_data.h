@@ -249,6 +249,62 @@ typedef int (*HSM_CLIENT_ENCRYPT_DATA)(HSM_CLIENT_HANDLE handle, const SIZED_BUF
 */
 typedef int (*HSM_CLIENT_DECRYPT_DATA)(HSM_CLIENT_HANDLE handle, const SIZED_BUFFER* identity, const SIZED_BUFFER* ciphertext, const SIZED_BUFFER* init_vector, SIZED_BUFFER* plaintext);
 
+/**
+* @brief        Provisions a symmetric encryption key under the given alias, next to
+*               the master encryption key. Keys under different aliases are independent,
+*               so a key can be replaced by creating its successor before it is destroyed.
+*
+* @param handle A valid HSM client handle
+* @param alias  The alias of the key
+*
+* @return       Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_CREATE_ENCRYPTION_KEY)(HSM_CLIENT_HANDLE handle, const char* alias);
+
+/**
+* @brief        Removes the encryption key with the given alias from the HSM.
+*
+* @note         Once this is erased, all data encrypted with the key is lost.
+*
+* @param handle A valid HSM client handle
+* @param alias  The alias of the key
+*
+* @return       Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_DESTROY_ENCRYPTION_KEY)(HSM_CLIENT_HANDLE handle, const char* alias);
+
+/**
+* @brief    Encrypts a blob of plaintext data with the encryption key of the given alias,
+*           like ::HSM_CLIENT_ENCRYPT_DATA does with the master encryption key.
+*
+* @param handle             A valid HSM client handle
+* @param alias              The alias of the key
+* @param client_id          Module or client identity string used in key generation
+* @param plaintext          Plaintext payload to encrypt
+* @param init_vector        Initialization vector used for any CBC cipher
+* @param[out] ciphertext    The returned cipher. This function allocates memory for a buffer
+*                           which must be freed by a call to ::HSM_CLIENT_FREE_BUFFER.
+*
+* @return   Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_ENCRYPT_DATA_WITH_KEY)(HSM_CLIENT_HANDLE handle, const char* alias, const SIZED_BUFFER* identity, const SIZED_BUFFER* plaintext, const SIZED_BUFFER* init_vector, SIZED_BUFFER* ciphertext);
+
+/**
+* @brief    Decrypts a blob of cipher text data with the encryption key of the given alias,
+*           like ::HSM_CLIENT_DECRYPT_DATA does with the master encryption key.
+*
+* @param handle         A valid HSM client handle
+* @param alias          The alias of the key
+* @param client_id      Module or client identity string used in key generation
+* @param ciphertext     Cipher text payload to decrypt
+* @param init_vector    Initialization vector used for any CBC cipher
+* @param[out] plaintext Returned plaintext. This function allocates memory for a buffer
+*                       which must be freed by a call to ::HSM_CLIENT_FREE_BUFFER.
+*
+* @return   Zero on success, nonzero otherwise
+*/
+typedef int (*HSM_CLIENT_DECRYPT_DATA_WITH_KEY)(HSM_CLIENT_HANDLE handle, const char* alias, const SIZED_BUFFER* identity, const SIZED_BUFFER* ciphertext, const SIZED_BUFFER* init_vector, SIZED_BUFFER* plaintext);
+
 /**
 * @brief    Retrieves the trusted certificate bundle used to authenticate the server.
 *
@@ -297,6 +353,10 @@ typedef struct HSM_CLIENT_CRYPTO_INTERFACE_TAG
     HSM_CLIENT_DECRYPT_DATA hsm_client_decrypt_data;
     HSM_CLIENT_GET_TRUST_BUNDLE hsm_client_get_trust_bundle;
     HSM_CLIENT_FREE_BUFFER hsm_client_free_buffer;
+    HSM_CLIENT_CREATE_ENCRYPTION_KEY hsm_client_create_encryption_key;
+    HSM_CLIENT_DESTROY_ENCRYPTION_KEY hsm_client_destroy_encryption_key;
+    HSM_CLIENT_ENCRYPT_DATA_WITH_KEY hsm_client_encrypt_data_with_key;
+    HSM_CLIENT_DECRYPT_DATA_WITH_KEY hsm_client_decrypt_data_with_key;
 } HSM_CLIENT_CRYPTO_INTERFACE;
 
 extern const HSM_CLIENT_TPM_INTERFACE* hsm_client_tpm_interface();
@@ -169,7 +169,12 @@ static int edge_hsm_client_get_random_bytes(HSM_CLIENT_HANDLE handle, unsigned c
     return result;
 }
 
-static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle)
+static bool validate_key_alias(const char *alias)
+{
+    return (alias != NULL) && (strlen(alias) != 0);
+}
+
+static int create_encryption_key(HSM_CLIENT_HANDLE handle, const char *key_name)
 {
     int result;
 
@@ -183,13 +188,18 @@ static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle
         LOG_ERROR("Invalid handle value specified");
         result = __FAILURE__;
     }
+    else if (!validate_key_alias(key_name))
+    {
+        LOG_ERROR("Invalid key alias specified");
+        result = __FAILURE__;
+    }
     else
     {
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
         if (g_hsm_store_if->hsm_client_store_insert_encryption_key(edge_crypto->hsm_store_handle,
-                                                                   EDGELET_ENC_KEY_NAME) != 0)
+                                                                   key_name) != 0)
         {
-            LOG_ERROR("Could not insert encryption key %s", EDGELET_ENC_KEY_NAME);
+            LOG_ERROR("Could not insert encryption key %s", key_name);
             result = __FAILURE__;
         }
         else
@@ -201,7 +211,7 @@ static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle
     return result;
 }
 
-static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handle)
+static int destroy_encryption_key(HSM_CLIENT_HANDLE handle, const char *key_name)
 {
     int result;
 
@@ -215,14 +225,19 @@ static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handl
         LOG_ERROR("Invalid handle value specified");
         result = __FAILURE__;
     }
+    else if (!validate_key_alias(key_name))
+    {
+        LOG_ERROR("Invalid key alias specified");
+        result = __FAILURE__;
+    }
     else
     {
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
         if (g_hsm_store_if->hsm_client_store_remove_key(edge_crypto->hsm_store_handle,
                                                         HSM_KEY_ENCRYPTION,
-                                                        EDGELET_ENC_KEY_NAME) != 0)
+                                                        key_name) != 0)
         {
-            LOG_ERROR("Could not remove encryption key %s", EDGELET_ENC_KEY_NAME);
+            LOG_ERROR("Could not remove encryption key %s", key_name);
             result = __FAILURE__;
         }
         else
@@ -234,6 +249,26 @@ static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handl
     return result;
 }
 
+static int edge_hsm_client_create_master_encryption_key(HSM_CLIENT_HANDLE handle)
+{
+    return create_encryption_key(handle, EDGELET_ENC_KEY_NAME);
+}
+
+static int edge_hsm_client_destroy_master_encryption_key(HSM_CLIENT_HANDLE handle)
+{
+    return destroy_encryption_key(handle, EDGELET_ENC_KEY_NAME);
+}
+
+static int edge_hsm_client_create_encryption_key(HSM_CLIENT_HANDLE handle, const char *alias)
+{
+    return create_encryption_key(handle, alias);
+}
+
+static int edge_hsm_client_destroy_encryption_key(HSM_CLIENT_HANDLE handle, const char *alias)
+{
+    return destroy_encryption_key(handle, alias);
+}
+
 static CERT_INFO_HANDLE edge_hsm_client_create_certificate(HSM_CLIENT_HANDLE handle, CERT_PROPS_HANDLE certificate_props)
 {
     CERT_INFO_HANDLE result;
@@ -345,6 +380,7 @@ static bool validate_sized_buffer(const SIZED_BUFFER *sized_buffer)
 static int encrypt_data
 (
     EDGE_CRYPTO *edge_crypto,
+    const char *key_name,
     const SIZED_BUFFER *id,
     const SIZED_BUFFER *pt,
     const SIZED_BUFFER *iv,
@@ -357,10 +393,10 @@ static int encrypt_data
     const HSM_CLIENT_KEY_INTERFACE *key_if = g_hsm_key_if;
     key_handle = store_if->hsm_client_store_open_key(edge_crypto->hsm_store_handle,
                                                      HSM_KEY_ENCRYPTION,
-                                                     EDGELET_ENC_KEY_NAME);
+                                                     key_name);
     if (key_handle == NULL)
     {
-        LOG_ERROR("Could not get encryption key by name '%s'", EDGELET_ENC_KEY_NAME);
+        LOG_ERROR("Could not get encryption key by name '%s'", key_name);
         result = __FAILURE__;
     }
     else
@@ -390,6 +426,7 @@ static int encrypt_data
 static int decrypt_data
 (
     EDGE_CRYPTO *edge_crypto,
+    const char *key_name,
     const SIZED_BUFFER *id,
     const SIZED_BUFFER *ct,
     const SIZED_BUFFER *iv,
@@ -402,10 +439,10 @@ static int decrypt_data
     const HSM_CLIENT_KEY_INTERFACE *key_if = g_hsm_key_if;
     key_handle = store_if->hsm_client_store_open_key(edge_crypto->hsm_store_handle,
                                                      HSM_KEY_ENCRYPTION,
-                                                     EDGELET_ENC_KEY_NAME);
+                                                     key_name);
     if (key_handle == NULL)
     {
-        LOG_ERROR("Could not get encryption key by name '%s'", EDGELET_ENC_KEY_NAME);
+        LOG_ERROR("Could not get encryption key by name '%s'", key_name);
         result = __FAILURE__;
     }
     else
@@ -432,9 +469,10 @@ static int decrypt_data
     return result;
 }
 
-static int edge_hsm_client_encrypt_data
+static int encrypt_data_with_key
 (
     HSM_CLIENT_HANDLE handle,
+    const char *key_name,
     const SIZED_BUFFER *identity,
     const SIZED_BUFFER *plaintext,
     const SIZED_BUFFER *initialization_vector,
@@ -448,6 +486,11 @@ static int edge_hsm_client_encrypt_data
         LOG_ERROR("hsm_client_crypto_init not called");
         result = __FAILURE__;
     }
+    else if (!validate_key_alias(key_name))
+    {
+        LOG_ERROR("Invalid key alias specified");
+        result = __FAILURE__;
+    }
     else if (!validate_sized_buffer(identity))
     {
         LOG_ERROR("Invalid identity buffer provided");
@@ -471,15 +514,16 @@ static int edge_hsm_client_encrypt_data
     else
     {
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
-        result = encrypt_data(edge_crypto, identity, plaintext, initialization_vector, ciphertext);
+        result = encrypt_data(edge_crypto, key_name, identity, plaintext, initialization_vector, ciphertext);
     }
 
     return result;
 }
 
-static int edge_hsm_client_decrypt_data
+static int decrypt_data_with_key
 (
     HSM_CLIENT_HANDLE handle,
+    const char *key_name,
     const SIZED_BUFFER *identity,
     const SIZED_BUFFER *ciphertext,
     const SIZED_BUFFER *initialization_vector,
@@ -493,6 +537,11 @@ static int edge_hsm_client_decrypt_data
         LOG_ERROR("hsm_client_crypto_init not called");
         result = __FAILURE__;
     }
+    else if (!validate_key_alias(key_name))
+    {
+        LOG_ERROR("Invalid key alias specified");
+        result = __FAILURE__;
+    }
     else if (!validate_sized_buffer(identity))
     {
         LOG_ERROR("Invalid identity buffer provided");
@@ -516,12 +565,62 @@ static int edge_hsm_client_decrypt_data
     else
     {
         EDGE_CRYPTO *edge_crypto = (EDGE_CRYPTO*)handle;
-        result = decrypt_data(edge_crypto, identity, ciphertext, initialization_vector, plaintext);
+        result = decrypt_data(edge_crypto, key_name, identity, ciphertext, initialization_vector, plaintext);
     }
 
     return result;
 }
 
+static int edge_hsm_client_encrypt_data
+(
+    HSM_CLIENT_HANDLE handle,
+    const SIZED_BUFFER *identity,
+    const SIZED_BUFFER *plaintext,
+    const SIZED_BUFFER *initialization_vector,
+    SIZED_BUFFER *ciphertext
+)
+{
+    return encrypt_data_with_key(handle, EDGELET_ENC_KEY_NAME, identity, plaintext, initialization_vector, ciphertext);
+}
+
+static int edge_hsm_client_decrypt_data
+(
+    HSM_CLIENT_HANDLE handle,
+    const SIZED_BUFFER *identity,
+    const SIZED_BUFFER *ciphertext,
+    const SIZED_BUFFER *initialization_vector,
+    SIZED_BUFFER *plaintext
+)
+{
+    return decrypt_data_with_key(handle, EDGELET_ENC_KEY_NAME, identity, ciphertext, initialization_vector, plaintext);
+}
+
+static int edge_hsm_client_encrypt_data_with_key
+(
+    HSM_CLIENT_HANDLE handle,
+    const char *alias,
+    const SIZED_BUFFER *identity,
+    const SIZED_BUFFER *plaintext,
+    const SIZED_BUFFER *initialization_vector,
+    SIZED_BUFFER *ciphertext
+)
+{
+    return encrypt_data_with_key(handle, alias, identity, plaintext, initialization_vector, ciphertext);
+}
+
+static int edge_hsm_client_decrypt_data_with_key
+(
+    HSM_CLIENT_HANDLE handle,
+    const char *alias,
+    const SIZED_BUFFER *identity,
+    const SIZED_BUFFER *ciphertext,
+    const SIZED_BUFFER *initialization_vector,
+    SIZED_BUFFER *plaintext
+)
+{
+    return decrypt_data_with_key(handle, alias, identity, ciphertext, initialization_vector, plaintext);
+}
+
 static const HSM_CLIENT_CRYPTO_INTERFACE edge_hsm_crypto_interface =
 {
     edge_hsm_client_crypto_create,
@@ -534,7 +633,11 @@ static const HSM_CLIENT_CRYPTO_INTERFACE edge_hsm_crypto_interface =
     edge_hsm_client_encrypt_data,
     edge_hsm_client_decrypt_data,
     edge_hsm_client_get_trust_bundle,
-    edge_hsm_crypto_free_buffer
+    edge_hsm_crypto_free_buffer,
+    edge_hsm_client_create_encryption_key,
+    edge_hsm_client_destroy_encryption_key,
+    edge_hsm_client_encrypt_data_with_key,
+    edge_hsm_client_decrypt_data_with_key
 };
 
 const HSM_CLIENT_CRYPTO_INTERFACE* hsm_client_crypto_interface(void)
@@ -33,6 +33,7 @@ static TEST_MUTEX_HANDLE g_dllByDll;
 #define TEST_CA_ALIAS "test_ca_alias"
 #define TEST_SERVER_ALIAS "test_server_alias"
 #define TEST_CLIENT_ALIAS "test_client_alias"
+#define TEST_ENC_KEY_ALIAS "test_enc_key_alias"
 #define TEST_CA_COMMON_NAME "test_ca_cert"
 #define TEST_SERVER_COMMON_NAME "test_server_cert"
 #define TEST_CLIENT_COMMON_NAME "test_client_cert"
@@ -852,6 +853,48 @@ BEGIN_TEST_SUITE(edge_hsm_crypto_int_tests)
         test_helper_crypto_deinit(hsm_handle);
     }
 
+    TEST_FUNCTION(hsm_client_aliased_encryption_key_outlives_master_key_smoke)
+    {
+        // arrange
+        int status;
+        HSM_CLIENT_HANDLE hsm_handle = test_helper_crypto_init();
+        const HSM_CLIENT_CRYPTO_INTERFACE* interface = hsm_client_crypto_interface();
+        SIZED_BUFFER id = {TEST_ID, TEST_ID_SIZE};
+        SIZED_BUFFER pt = {TEST_PLAINTEXT, TEST_PLAINTEXT_SIZE};
+        SIZED_BUFFER iv = {TEST_IV, TEST_IV_SIZE};
+        SIZED_BUFFER ciphertext_result = { NULL, 0 };
+        SIZED_BUFFER plaintext_result = { NULL, 0 };
+        SIZED_BUFFER lost_result = { NULL, 0 };
+
+        status = interface->hsm_client_create_master_encryption_key(hsm_handle);
+        ASSERT_ARE_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_create_encryption_key(hsm_handle, TEST_ENC_KEY_ALIAS);
+        ASSERT_ARE_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+        status = interface->hsm_client_encrypt_data_with_key(hsm_handle, TEST_ENC_KEY_ALIAS, &id, &pt, &iv, &ciphertext_result);
+        ASSERT_ARE_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // act, assert
+        status = interface->hsm_client_destroy_master_encryption_key(hsm_handle);
+        ASSERT_ARE_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = interface->hsm_client_decrypt_data_with_key(hsm_handle, TEST_ENC_KEY_ALIAS, &id, &ciphertext_result, &iv, &plaintext_result);
+        ASSERT_ARE_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+        ASSERT_ARE_EQUAL(size_t, TEST_PLAINTEXT_SIZE, plaintext_result.size, "Line:" TOSTRING(__LINE__));
+        status = memcmp(TEST_PLAINTEXT, plaintext_result.buffer, TEST_PLAINTEXT_SIZE);
+        ASSERT_ARE_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = interface->hsm_client_destroy_encryption_key(hsm_handle, TEST_ENC_KEY_ALIAS);
+        ASSERT_ARE_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        status = interface->hsm_client_decrypt_data_with_key(hsm_handle, TEST_ENC_KEY_ALIAS, &id, &ciphertext_result, &iv, &lost_result);
+        ASSERT_ARE_NOT_EQUAL(int, 0, status, "Line:" TOSTRING(__LINE__));
+
+        // cleanup
+        free(plaintext_result.buffer);
+        free(ciphertext_result.buffer);
+        test_helper_crypto_deinit(hsm_handle);
+    }
+
     TEST_FUNCTION(hsm_client_transparent_gateway_trust_bundle_smoke)
     {
         // arrange
@@ -672,6 +672,10 @@ BEGIN_TEST_SUITE(edge_hsm_crypto_unittests)
             ASSERT_IS_NOT_NULL(result->hsm_client_decrypt_data, "Line:" TOSTRING(__LINE__));
             ASSERT_IS_NOT_NULL(result->hsm_client_get_trust_bundle, "Line:" TOSTRING(__LINE__));
             ASSERT_IS_NOT_NULL(result->hsm_client_free_buffer, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL(result->hsm_client_create_encryption_key, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL(result->hsm_client_destroy_encryption_key, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL(result->hsm_client_encrypt_data_with_key, "Line:" TOSTRING(__LINE__));
+            ASSERT_IS_NOT_NULL(result->hsm_client_decrypt_data_with_key, "Line:" TOSTRING(__LINE__));
 
             //cleanup
         }
@@ -259,6 +259,77 @@ pub type HSM_CLIENT_DECRYPT_DATA = Option<
     ) -> c_int,
 >;
 
+/// API to provision a symmetric encryption key under an alias, next to the
+/// master encryption key. Keys under different aliases are independent, so a
+/// key can be replaced by creating its successor before it is destroyed.
+///
+/// handle[in] -- A valid HSM client handle
+/// alias[in]  -- The alias of the key
+///
+/// Return
+/// 0  -- On success
+/// Non 0 -- otherwise
+pub type HSM_CLIENT_CREATE_ENCRYPTION_KEY =
+    Option<unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE, alias: *const c_char) -> c_int>;
+/// API to remove the encryption key with the given alias from the HSM.
+///
+/// @note: Once this is erased, all data encrypted with the key is lost.
+///
+/// handle[in] -- A valid HSM client handle
+/// alias[in]  -- The alias of the key
+///
+/// Return
+/// 0  -- On success
+/// Non 0 -- otherwise
+pub type HSM_CLIENT_DESTROY_ENCRYPTION_KEY =
+    Option<unsafe extern "C" fn(handle: HSM_CLIENT_HANDLE, alias: *const c_char) -> c_int>;
+/// API to encrypt a blob of plaintext data with the encryption key of the
+/// given alias, like `HSM_CLIENT_ENCRYPT_DATA` does with the master key.
+///
+/// handle[in]       -- A valid HSM client handle
+/// alias[in]        -- The alias of the key
+/// client_id[in]    -- Module or client identity string used in key generation
+/// plaintext[in]    -- Plaintext payload to encrypt
+/// initialization_vector[in] -- Initialization vector used for any CBC cipher
+/// ciphertext[out]  -- Encrypted cipher text
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_ENCRYPT_DATA_WITH_KEY = Option<
+    unsafe extern "C" fn(
+        handle: HSM_CLIENT_HANDLE,
+        alias: *const c_char,
+        client_id: *const SIZED_BUFFER,
+        plaintext: *const SIZED_BUFFER,
+        initialization_vector: *const SIZED_BUFFER,
+        ciphertext: *mut SIZED_BUFFER,
+    ) -> c_int,
+>;
+/// API to decrypt a blob of cipher text data with the encryption key of the
+/// given alias, like `HSM_CLIENT_DECRYPT_DATA` does with the master key.
+///
+/// handle[in]      -- A valid HSM client handle
+/// alias[in]       -- The alias of the key
+/// client_id[in]   -- Module or client identity string used in key generation
+/// ciphertext[in]  -- Cipher text payload to decrypt
+/// initialization_vector[in] -- Initialization vector used for any CBC cipher
+/// plaintext[out]  -- Decrypted plain text
+///
+/// Return
+/// 0 - Success
+/// Non 0 otherwise
+pub type HSM_CLIENT_DECRYPT_DATA_WITH_KEY = Option<
+    unsafe extern "C" fn(
+        handle: HSM_CLIENT_HANDLE,
+        alias: *const c_char,
+        client_id: *const SIZED_BUFFER,
+        ciphertext: *const SIZED_BUFFER,
+        initialization_vector: *const SIZED_BUFFER,
+        plaintext: *mut SIZED_BUFFER,
+    ) -> c_int,
+>;
+
 pub type CRYPTO_ENCODING_TAG = u32;
 pub const CRYPTO_ENCODING_TAG_PEM: CRYPTO_ENCODING_TAG = 0;
 
@@ -778,6 +849,10 @@ pub struct HSM_CLIENT_CRYPTO_INTERFACE_TAG {
     pub hsm_client_decrypt_data: HSM_CLIENT_DECRYPT_DATA,
     pub hsm_client_get_trust_bundle: HSM_CLIENT_GET_TRUST_BUNDLE,
     pub hsm_client_free_buffer: HSM_CLIENT_FREE_BUFFER,
+    pub hsm_client_create_encryption_key: HSM_CLIENT_CREATE_ENCRYPTION_KEY,
+    pub hsm_client_destroy_encryption_key: HSM_CLIENT_DESTROY_ENCRYPTION_KEY,
+    pub hsm_client_encrypt_data_with_key: HSM_CLIENT_ENCRYPT_DATA_WITH_KEY,
+    pub hsm_client_decrypt_data_with_key: HSM_CLIENT_DECRYPT_DATA_WITH_KEY,
 }
 pub type HSM_CLIENT_CRYPTO_INTERFACE = HSM_CLIENT_CRYPTO_INTERFACE_TAG;
 
@@ -795,6 +870,10 @@ impl Default for HSM_CLIENT_CRYPTO_INTERFACE_TAG {
             hsm_client_decrypt_data: None,
             hsm_client_get_trust_bundle: None,
             hsm_client_free_buffer: None,
+            hsm_client_create_encryption_key: None,
+            hsm_client_destroy_encryption_key: None,
+            hsm_client_encrypt_data_with_key: None,
+            hsm_client_decrypt_data_with_key: None,
         }
     }
 }
@@ -803,7 +882,7 @@ impl Default for HSM_CLIENT_CRYPTO_INTERFACE_TAG {
 fn bindgen_test_layout_HSM_CLIENT_CRYPTO_INTERFACE_TAG() {
     assert_eq!(
         ::std::mem::size_of::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>(),
-        11_usize * ::std::mem::size_of::<usize>(),
+        15_usize * ::std::mem::size_of::<usize>(),
         concat!("Size of: ", stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG))
     );
     assert_eq!(
@@ -954,6 +1033,58 @@ fn bindgen_test_layout_HSM_CLIENT_CRYPTO_INTERFACE_TAG() {
             stringify!(hsm_client_free_buffer)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_create_encryption_key as *const _ as usize
+        },
+        11_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_create_encryption_key)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_destroy_encryption_key as *const _ as usize
+        },
+        12_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_destroy_encryption_key)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_encrypt_data_with_key as *const _ as usize
+        },
+        13_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_encrypt_data_with_key)
+        )
+    );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<HSM_CLIENT_CRYPTO_INTERFACE_TAG>()))
+                .hsm_client_decrypt_data_with_key as *const _ as usize
+        },
+        14_usize * ::std::mem::size_of::<usize>(),
+        concat!(
+            "Offset of field: ",
+            stringify!(HSM_CLIENT_CRYPTO_INTERFACE_TAG),
+            "::",
+            stringify!(hsm_client_decrypt_data_with_key)
+        )
+    );
 }
 
 extern "C" {
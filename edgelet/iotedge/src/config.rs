@@ -0,0 +1,470 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use failure::{Fail, ResultExt};
+use futures::future::{self, FutureResult};
+#[cfg(unix)]
+use libc;
+use serde_json;
+use serde_yaml;
+
+use edgelet_docker::DockerConfig;
+use edgelet_settings::{ErrorKind as SettingsErrorKind, Provisioning, Settings};
+
+use error::{Error, ErrorKind};
+use Command;
+
+/// Checks that a config file can be loaded by iotedged.
+///
+/// Syntax errors are reported with the line and column they were found at.
+/// The file is then loaded into the same `Settings` the daemon uses, on top of
+/// the same built-in defaults, so sections the defaults provide may be left
+/// out just like they can for the daemon. Invalid values are reported with
+/// their key and the line it is on.
+pub struct ValidateConfig<W> {
+    path: PathBuf,
+    output: W,
+}
+
+impl<W> ValidateConfig<W> {
+    pub fn new(path: PathBuf, output: W) -> Self {
+        ValidateConfig { path, output }
+    }
+}
+
+impl<W> Command for ValidateConfig<W>
+where
+    W: Write,
+{
+    type Future = FutureResult<(), Error>;
+
+    fn execute(&mut self) -> Self::Future {
+        let source = self.path.display().to_string();
+        let result = read_config(&self.path)
+            .and_then(|contents| validate(&contents, &source))
+            .and_then(|_| {
+                writeln!(self.output, "{} is valid", source).context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        future::result(result)
+    }
+}
+
+/// A section of the config file that `SetConfig` rewrites.
+pub enum ConfigUpdate {
+    Manual {
+        connection_string: String,
+    },
+    Dps {
+        global_endpoint: String,
+        scope_id: String,
+        registration_id: String,
+    },
+    Certificates {
+        device_ca_cert: String,
+        device_ca_pk: String,
+        trusted_ca_certs: String,
+    },
+}
+
+impl ConfigUpdate {
+    fn key(&self) -> &'static str {
+        match *self {
+            ConfigUpdate::Manual { .. } | ConfigUpdate::Dps { .. } => "provisioning",
+            ConfigUpdate::Certificates { .. } => "certificates",
+        }
+    }
+
+    fn section(&self) -> String {
+        match *self {
+            ConfigUpdate::Manual {
+                ref connection_string,
+            } => format!(
+                "provisioning:\n  source: \"manual\"\n  device_connection_string: {}",
+                quote(connection_string),
+            ),
+            ConfigUpdate::Dps {
+                ref global_endpoint,
+                ref scope_id,
+                ref registration_id,
+            } => format!(
+                "provisioning:\n  source: \"dps\"\n  global_endpoint: {}\n  scope_id: {}\n  registration_id: {}",
+                quote(global_endpoint),
+                quote(scope_id),
+                quote(registration_id),
+            ),
+            ConfigUpdate::Certificates {
+                ref device_ca_cert,
+                ref device_ca_pk,
+                ref trusted_ca_certs,
+            } => format!(
+                "certificates:\n  device_ca_cert: {}\n  device_ca_pk: {}\n  trusted_ca_certs: {}",
+                quote(device_ca_cert),
+                quote(device_ca_pk),
+                quote(trusted_ca_certs),
+            ),
+        }
+    }
+}
+
+/// Replaces one top-level section of a config file.
+///
+/// Only the lines of the section itself are rewritten, so comments around it,
+/// including the commented-out templates of the default config file, are kept.
+/// The result is validated before it replaces the file, and the file is
+/// replaced atomically.
+pub struct SetConfig<W> {
+    path: PathBuf,
+    update: ConfigUpdate,
+    output: W,
+}
+
+impl<W> SetConfig<W> {
+    pub fn new(path: PathBuf, update: ConfigUpdate, output: W) -> Self {
+        SetConfig {
+            path,
+            update,
+            output,
+        }
+    }
+}
+
+impl<W> Command for SetConfig<W>
+where
+    W: Write,
+{
+    type Future = FutureResult<(), Error>;
+
+    fn execute(&mut self) -> Self::Future {
+        let source = self.path.display().to_string();
+        let result = read_config(&self.path)
+            .and_then(|contents| {
+                let contents =
+                    replace_section(&contents, self.update.key(), &self.update.section());
+                validate(&contents, &source)?;
+                write_config(&self.path, &contents)
+                    .with_context(|_| ErrorKind::WriteConfig(source.clone()))?;
+                Ok(())
+            })
+            .and_then(|_| {
+                writeln!(
+                    self.output,
+                    "Updated {}. Restart iotedged for the change to take effect.",
+                    source
+                )
+                .context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        future::result(result)
+    }
+}
+
+fn read_config(path: &Path) -> Result<String, Error> {
+    let mut contents = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .with_context(|_| ErrorKind::ReadConfig(path.display().to_string()))?;
+    Ok(contents)
+}
+
+fn validate(contents: &str, source: &str) -> Result<(), Error> {
+    serde_yaml::from_str::<serde_yaml::Value>(contents)
+        .with_context(|_| ErrorKind::InvalidConfig(source.to_string()))?;
+
+    let settings = Settings::<DockerConfig>::from_yaml(contents).map_err(|err| {
+        let location = match *err.kind() {
+            SettingsErrorKind::InvalidSetting(key) => locate(contents, source, key),
+            _ => source.to_string(),
+        };
+        Error::from(err.context(ErrorKind::InvalidConfig(location)))
+    })?;
+
+    if let Provisioning::Manual(ref manual) = *settings.provisioning() {
+        manual.parse_device_connection_string().with_context(|_| {
+            ErrorKind::InvalidConfig(locate(
+                contents,
+                source,
+                "provisioning.device_connection_string",
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// Names the key, and the line it is on if the file sets it, for errors
+/// about its value.
+fn locate(contents: &str, source: &str, key: &str) -> String {
+    match find_key_line(contents, key) {
+        Some(line) => format!("{}: {} at line {}", source, key, line),
+        None => format!("{}: {}", source, key),
+    }
+}
+
+/// Finds the line, counting from 1, that sets a dotted key like
+/// `provisioning.source` in a block-style YAML file. Each part of the key is
+/// looked for among the lines indented below the part before it.
+fn find_key_line(contents: &str, key: &str) -> Option<usize> {
+    let mut parts = key.split('.');
+    let mut part = parts.next()?;
+    let mut parent_indent = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        match parent_indent {
+            Some(parent) if indent <= parent => return None,
+            None if indent > 0 => continue,
+            _ => (),
+        }
+
+        if is_section_header(trimmed, part) {
+            match parts.next() {
+                Some(next) => {
+                    part = next;
+                    parent_indent = Some(indent);
+                }
+                None => return Some(index + 1),
+            }
+        }
+    }
+    None
+}
+
+/// Quotes a value as a double-quoted YAML scalar. JSON strings are valid
+/// double-quoted YAML scalars, so the JSON escaping rules apply.
+fn quote(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+fn is_section_header(line: &str, key: &str) -> bool {
+    line.starts_with(key) && line[key.len()..].starts_with(':')
+}
+
+fn replace_section(contents: &str, key: &str, section: &str) -> String {
+    let newline = if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len());
+
+    match lines.iter().position(|line| is_section_header(line, key)) {
+        Some(start) => {
+            // The section ends at its last indented line. Blank lines and
+            // unindented comments after it belong to what follows.
+            let end = lines[start + 1..]
+                .iter()
+                .take_while(|line| {
+                    line.trim().is_empty() || line.starts_with(' ') || line.starts_with('\t')
+                })
+                .enumerate()
+                .filter(|&(_, line)| !line.trim().is_empty())
+                .map(|(i, _)| start + i + 2)
+                .last()
+                .unwrap_or(start + 1);
+
+            result.extend(&lines[..start]);
+            result.extend(section.lines());
+            result.extend(&lines[end..]);
+        }
+        None => {
+            result.extend(&lines);
+            if lines.last().map_or(false, |line| !line.trim().is_empty()) {
+                result.push("");
+            }
+            result.extend(section.lines());
+        }
+    }
+
+    let mut contents = result.join(newline);
+    contents.push_str(newline);
+    contents
+}
+
+/// Writes the file next to the original first and renames it over the
+/// original, so readers never see a partially written config.
+fn write_config(path: &Path, contents: &str) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let metadata = fs::metadata(path)?;
+    let written = File::create(&temp_path).and_then(|mut file| {
+        copy_owner(&metadata, &file)?;
+        file.set_permissions(metadata.permissions())?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    });
+
+    written
+        .and_then(|_| fs::rename(&temp_path, path))
+        .or_else(|err| {
+            let _ = fs::remove_file(&temp_path);
+            Err(err)
+        })
+}
+
+/// The config file is usually only readable by the user iotedged runs as,
+/// so the replacement has to keep the owner of the original.
+#[cfg(unix)]
+fn copy_owner(metadata: &fs::Metadata, file: &File) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsRawFd;
+
+    let ret = unsafe { libc::fchown(file.as_raw_fd(), metadata.uid(), metadata.gid()) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(windows)]
+fn copy_owner(_metadata: &fs::Metadata, _file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CONFIG: &str = "# Provisioning
+provisioning:
+  source: \"manual\"
+  # replace me
+  device_connection_string: \"<ADD DEVICE CONNECTION STRING HERE>\"
+
+# provisioning:
+#   source: \"dps\"
+
+agent:
+  name: \"edgeAgent\"
+  type: \"docker\"
+  env: {}
+  config:
+    image: \"mcr.microsoft.com/azureiotedge-agent:1.0\"
+    auth: {}
+
+hostname: \"localhost\"
+
+connect:
+  management_uri: \"unix:///var/run/iotedge/mgmt.sock\"
+  workload_uri: \"unix:///var/run/iotedge/workload.sock\"
+
+listen:
+  management_uri: \"unix:///var/run/iotedge/mgmt.sock\"
+  workload_uri: \"unix:///var/run/iotedge/workload.sock\"
+
+homedir: \"/var/lib/iotedge\"
+
+moby_runtime:
+  uri: \"unix:///var/run/docker.sock\"
+  network: \"azure-iot-edge\"
+";
+
+    static CONNECTION_STRING: &str =
+        "HostName=example.azure-devices.net;DeviceId=device1;SharedAccessKey=c2VjcmV0";
+
+    #[test]
+    fn replace_section_keeps_surrounding_comments() {
+        let update = ConfigUpdate::Manual {
+            connection_string: CONNECTION_STRING.to_string(),
+        };
+
+        let contents = replace_section(CONFIG, update.key(), &update.section());
+
+        assert!(contents.starts_with(&format!(
+            "# Provisioning\nprovisioning:\n  source: \"manual\"\n  device_connection_string: \"{}\"\n\n# provisioning:\n#   source: \"dps\"\n\nagent:",
+            CONNECTION_STRING
+        )));
+        assert!(!contents.contains("# replace me"));
+        validate(&contents, "config.yaml").unwrap();
+    }
+
+    #[test]
+    fn replace_section_appends_missing_section() {
+        let update = ConfigUpdate::Certificates {
+            device_ca_cert: "/certs/device_ca_cert.pem".to_string(),
+            device_ca_pk: "/certs/device_ca_pk.pem".to_string(),
+            trusted_ca_certs: "C:\\certs\\trusted_ca_certs.pem".to_string(),
+        };
+
+        let contents = replace_section(CONFIG, update.key(), &update.section());
+
+        assert!(contents.starts_with(CONFIG));
+        assert!(contents.ends_with(
+            "network: \"azure-iot-edge\"\n\ncertificates:\n  device_ca_cert: \"/certs/device_ca_cert.pem\"\n  device_ca_pk: \"/certs/device_ca_pk.pem\"\n  trusted_ca_certs: \"C:\\\\certs\\\\trusted_ca_certs.pem\"\n"
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_unknown_source() {
+        let contents = CONFIG.replace("source: \"manual\"", "source: \"manaul\"");
+
+        let err = validate(&contents, "config.yaml").unwrap_err();
+
+        assert_eq!(
+            "Invalid config file config.yaml: provisioning.source at line 3",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn validate_merges_daemon_defaults() {
+        let contents = format!(
+            "provisioning:\n  source: \"manual\"\n  device_connection_string: \"{}\"\n",
+            CONNECTION_STRING
+        );
+
+        validate(&contents, "config.yaml").unwrap();
+    }
+
+    #[test]
+    fn validate_rejects_placeholder_connection_string() {
+        let err = validate(CONFIG, "config.yaml").unwrap_err();
+
+        assert_eq!(
+            "Invalid config file config.yaml: provisioning.device_connection_string at line 5",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn validate_names_key_left_to_defaults() {
+        let contents = format!(
+            "provisioning:\n  source: \"manual\"\n  device_connection_string: \"{}\"\nlisten:\n  management_uri: \"not a url\"\n",
+            CONNECTION_STRING
+        );
+
+        let err = validate(&contents, "config.yaml").unwrap_err();
+
+        assert_eq!(
+            "Invalid config file config.yaml: listen at line 4",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn find_key_line_skips_comments_and_other_sections() {
+        assert_eq!(Some(2), find_key_line(CONFIG, "provisioning"));
+        assert_eq!(
+            Some(5),
+            find_key_line(CONFIG, "provisioning.device_connection_string")
+        );
+        assert_eq!(Some(15), find_key_line(CONFIG, "agent.config.image"));
+        assert_eq!(None, find_key_line(CONFIG, "provisioning.image"));
+        assert_eq!(None, find_key_line(CONFIG, "certificates"));
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use failure::{Fail, ResultExt};
+use futures::Future;
+use serde_json;
+
+use edgelet_core::{ModuleRuntime, ModuleSpec};
+use edgelet_http_mgmt::ModuleConfig;
+use management::models::ModuleSpec as HttpModuleSpec;
+
+use error::{Error, ErrorKind};
+use Command;
+
+/// Creates a module from a spec in the format of the management API.
+pub struct Create<M, W>
+where
+    M: ModuleRuntime,
+{
+    spec: ModuleSpec<M::Config>,
+    runtime: M,
+    output: Arc<Mutex<W>>,
+}
+
+impl<M, W> Create<M, W>
+where
+    M: ModuleRuntime,
+{
+    pub fn new(spec: ModuleSpec<M::Config>, runtime: M, output: W) -> Self {
+        Create {
+            spec,
+            runtime,
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+}
+
+impl<M, W> Command for Create<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Config: Clone,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let name = self.spec.name().to_string();
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .create(self.spec.clone())
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+            .and_then(move |_| {
+                let mut w = write.lock().unwrap();
+                writeln!(w, "{}", name).context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
+
+/// Reads the spec of a module from a JSON file, for example
+///
+/// ```json
+/// {
+///   "name": "tempSensor",
+///   "type": "docker",
+///   "config": { "settings": { "image": "mcr.microsoft.com/azureiotedge-simulated-temperature-sensor:1.0" } }
+/// }
+/// ```
+pub fn read_spec(path: &Path) -> Result<ModuleSpec<ModuleConfig>, Error> {
+    let source = path.display().to_string();
+    let file = File::open(path).with_context(|_| ErrorKind::BadModuleSpec(source.clone()))?;
+    spec_from_reader(file, source)
+}
+
+fn spec_from_reader<R: Read>(reader: R, source: String) -> Result<ModuleSpec<ModuleConfig>, Error> {
+    let spec: HttpModuleSpec = serde_json::from_reader(reader)
+        .with_context(|_| ErrorKind::BadModuleSpec(source.clone()))?;
+
+    let env: HashMap<String, String> = spec
+        .config()
+        .env()
+        .unwrap_or(&[])
+        .iter()
+        .map(|var| (var.key().clone(), var.value().clone()))
+        .collect();
+    let config = ModuleConfig::new(spec.type_().clone(), spec.config().clone());
+    let spec = ModuleSpec::new(spec.name().clone(), spec.type_().clone(), config, env)
+        .context(ErrorKind::BadModuleSpec(source))?;
+    Ok(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_spec_with_env() {
+        let json = r#"{
+            "name": "m1",
+            "type": "docker",
+            "config": {
+                "settings": { "image": "microsoft/test-image" },
+                "env": [{ "key": "k1", "value": "v1" }]
+            }
+        }"#;
+
+        let spec = spec_from_reader(json.as_bytes(), "spec.json".to_string()).unwrap();
+
+        assert_eq!("m1", spec.name());
+        assert_eq!("docker", spec.type_());
+        assert_eq!("microsoft/test-image", spec.config().to_string());
+        assert_eq!(Some(&"v1".to_string()), spec.env().get("k1"));
+    }
+
+    #[test]
+    fn read_spec_without_name_fails() {
+        let json = r#"{ "name": "", "type": "docker", "config": { "settings": {} } }"#;
+
+        assert!(spec_from_reader(json.as_bytes(), "spec.json".to_string()).is_err());
+    }
+}
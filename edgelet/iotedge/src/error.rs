@@ -10,22 +10,55 @@ pub struct Error {
     inner: Context<ErrorKind>,
 }
 
-#[derive(Clone, Copy, Debug, Fail)]
+#[derive(Clone, Debug, Fail)]
 pub enum ErrorKind {
     #[fail(display = "Invalid value for --host parameter")]
     BadHostParameter,
 
+    #[fail(display = "Invalid module spec in {}", _0)]
+    BadModuleSpec(String),
+
+    #[fail(display = "Invalid value for --output parameter")]
+    BadOutputParameter,
+
+    #[fail(display = "Invalid value for --revert-after parameter")]
+    BadRevertAfterParameter,
+
+    #[fail(display = "Invalid config file {}", _0)]
+    InvalidConfig(String),
+
     #[fail(display = "Missing --host parameter")]
     MissingHostParameter,
 
+    #[fail(display = "An identity manager error occurred")]
+    IdentityManager,
+
     #[fail(display = "A module runtime error occurred")]
     ModuleRuntime,
 
+    #[fail(display = "No module named {}", _0)]
+    ModuleNotFound(String),
+
     #[fail(display = "Could not initialize tokio runtime")]
     InitializeTokio,
 
     #[fail(display = "Could not write to stdout")]
     WriteToStdout,
+
+    #[fail(display = "Could not write volume backup")]
+    WriteBackup,
+
+    #[fail(display = "Could not read config file {}", _0)]
+    ReadConfig(String),
+
+    #[fail(display = "Could not write config file {}", _0)]
+    WriteConfig(String),
+
+    #[fail(display = "The command did not report an exit code")]
+    MissingExitCode,
+
+    #[fail(display = "Could not change the log level")]
+    LogLevel,
 }
 
 impl Fail for Error {
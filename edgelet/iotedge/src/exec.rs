@@ -0,0 +1,109 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use failure::Fail;
+use futures::prelude::*;
+use tokio;
+use tokio::io::{copy, shutdown, stdin, stdout, AsyncRead};
+
+use edgelet_core::{ExecOptions, ModuleRuntime};
+
+use error::{Error, ErrorKind};
+use logs::{LogChunk, LogDecode};
+use Command;
+
+pub struct Exec<M> {
+    id: String,
+    options: ExecOptions,
+    runtime: M,
+    exit_code: Arc<Mutex<Option<i32>>>,
+}
+
+impl<M> Exec<M> {
+    pub fn new(id: String, options: ExecOptions, runtime: M) -> Self {
+        Exec {
+            id,
+            options,
+            runtime,
+            exit_code: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The exit code of the command, once `execute` has completed.
+    pub fn exit_code(&self) -> Option<i32> {
+        *self.exit_code.lock().unwrap()
+    }
+}
+
+impl<M> Command for Exec<M>
+where
+    M: 'static + ModuleRuntime + Clone + Send,
+    M::ExecIo: 'static,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let runtime = self.runtime.clone();
+        let interactive = self.options.attach_stdin();
+        let tty = self.options.tty();
+
+        let started = self
+            .runtime
+            .exec(&self.id, &self.options)
+            .and_then(move |exec_id| {
+                runtime
+                    .exec_start(&exec_id, tty)
+                    .map(|io| (runtime, exec_id, io))
+            })
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)));
+
+        let finished = started.and_then(move |(runtime, exec_id, io)| {
+            let (output, input) = io.split();
+
+            // The command keeps running when its input ends, so stdin is
+            // copied on its own and the command is done once its output ends.
+            if interactive {
+                tokio::spawn(
+                    copy(stdin(), input)
+                        .and_then(|(_, _, input)| shutdown(input))
+                        .then(|_| Ok(())),
+                );
+            }
+
+            let output: Box<Future<Item = (), Error = io::Error> + Send> = if tty {
+                Box::new(copy(output, stdout()).map(|_| ()))
+            } else {
+                Box::new(LogDecode::new(output).for_each(|chunk| {
+                    match chunk {
+                        LogChunk::Stdin(b) | LogChunk::Stdout(b) | LogChunk::Unknown(b) => {
+                            let mut stdout = io::stdout();
+                            stdout.write_all(&b)?;
+                            stdout.flush()?;
+                        }
+                        LogChunk::Stderr(b) => io::stderr().write_all(&b)?,
+                    };
+                    Ok(())
+                }))
+            };
+
+            output
+                .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+                .and_then(move |_| {
+                    runtime
+                        .exec_inspect(&exec_id)
+                        .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+                })
+        });
+
+        let exit_code = self.exit_code.clone();
+        Box::new(finished.and_then(move |status| match status.exit_code() {
+            Some(code) => {
+                *exit_code.lock().unwrap() = Some(code);
+                Ok(())
+            }
+            None => Err(Error::from(ErrorKind::MissingExitCode)),
+        }))
+    }
+}
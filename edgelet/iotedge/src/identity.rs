@@ -0,0 +1,236 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use failure::{Fail, ResultExt};
+use futures::Future;
+use tabwriter::TabWriter;
+
+use edgelet_core::{Identity, IdentityManager, IdentitySpec};
+
+use error::{Error, ErrorKind};
+use output::{write_value, OutputFormat};
+use Command;
+
+#[derive(Serialize)]
+struct IdentityDetails {
+    #[serde(rename = "moduleId")]
+    module_id: String,
+    #[serde(rename = "managedBy")]
+    managed_by: String,
+    #[serde(rename = "generationId")]
+    generation_id: String,
+    #[serde(rename = "authType")]
+    auth_type: String,
+}
+
+impl IdentityDetails {
+    fn new<I: Identity>(identity: &I) -> Self {
+        IdentityDetails {
+            module_id: identity.module_id().to_string(),
+            managed_by: identity.managed_by().to_string(),
+            generation_id: identity.generation_id().to_string(),
+            auth_type: identity.auth_type().to_string(),
+        }
+    }
+}
+
+fn write_identities<W: Write>(
+    w: &mut TabWriter<W>,
+    format: OutputFormat,
+    identities: &[IdentityDetails],
+) -> Result<(), Error> {
+    if format != OutputFormat::Table {
+        return write_value(w, format, &identities);
+    }
+
+    writeln!(w, "MODULE ID\tMANAGED BY\tGENERATION ID\tAUTH TYPE")
+        .context(ErrorKind::WriteToStdout)?;
+    for identity in identities {
+        writeln!(
+            w,
+            "{}\t{}\t{}\t{}",
+            identity.module_id, identity.managed_by, identity.generation_id, identity.auth_type,
+        )
+        .context(ErrorKind::WriteToStdout)?;
+    }
+    w.flush().context(ErrorKind::WriteToStdout)?;
+    Ok(())
+}
+
+pub struct ListIdentities<I, W> {
+    id_manager: I,
+    format: OutputFormat,
+    output: Arc<Mutex<TabWriter<W>>>,
+}
+
+impl<I, W> ListIdentities<I, W>
+where
+    W: Write,
+{
+    pub fn new(id_manager: I, output: W) -> Self {
+        let tab = TabWriter::new(output).minwidth(15);
+        ListIdentities {
+            id_manager,
+            format: OutputFormat::default(),
+            output: Arc::new(Mutex::new(tab)),
+        }
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl<I, W> Command for ListIdentities<I, W>
+where
+    I: 'static + IdentityManager,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let format = self.format;
+        let write = self.output.clone();
+        let result = self
+            .id_manager
+            .list()
+            .map_err(|err| Error::from(err.context(ErrorKind::IdentityManager)))
+            .and_then(move |identities| {
+                let identities: Vec<_> = identities.iter().map(IdentityDetails::new).collect();
+                let mut w = write.lock().unwrap();
+                write_identities(&mut *w, format, &identities)
+            });
+        Box::new(result)
+    }
+}
+
+pub struct CreateIdentity<I, W> {
+    name: String,
+    managed_by: Option<String>,
+    id_manager: I,
+    format: OutputFormat,
+    output: Arc<Mutex<TabWriter<W>>>,
+}
+
+impl<I, W> CreateIdentity<I, W>
+where
+    W: Write,
+{
+    pub fn new(name: String, id_manager: I, output: W) -> Self {
+        let tab = TabWriter::new(output).minwidth(15);
+        CreateIdentity {
+            name,
+            managed_by: None,
+            id_manager,
+            format: OutputFormat::default(),
+            output: Arc::new(Mutex::new(tab)),
+        }
+    }
+
+    pub fn with_managed_by(mut self, managed_by: Option<String>) -> Self {
+        self.managed_by = managed_by;
+        self
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl<I, W> Command for CreateIdentity<I, W>
+where
+    I: 'static + IdentityManager,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let mut spec = IdentitySpec::new(self.name.clone());
+        if let Some(ref managed_by) = self.managed_by {
+            spec = spec.with_managed_by(managed_by.clone());
+        }
+
+        let format = self.format;
+        let write = self.output.clone();
+        let result = self
+            .id_manager
+            .create(spec)
+            .map_err(|err| Error::from(err.context(ErrorKind::IdentityManager)))
+            .and_then(move |identity| {
+                let identities = [IdentityDetails::new(&identity)];
+                let mut w = write.lock().unwrap();
+                if format == OutputFormat::Table {
+                    write_identities(&mut *w, format, &identities)
+                } else {
+                    write_value(&mut *w, format, &identities[0])
+                }
+            });
+        Box::new(result)
+    }
+}
+
+pub struct DeleteIdentity<I, W> {
+    name: String,
+    id_manager: I,
+    output: Arc<Mutex<W>>,
+}
+
+impl<I, W> DeleteIdentity<I, W> {
+    pub fn new(name: String, id_manager: I, output: W) -> Self {
+        DeleteIdentity {
+            name,
+            id_manager,
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+}
+
+impl<I, W> Command for DeleteIdentity<I, W>
+where
+    I: 'static + IdentityManager,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let name = self.name.clone();
+        let write = self.output.clone();
+        let result = self
+            .id_manager
+            .delete(IdentitySpec::new(name.clone()))
+            .map_err(|err| Error::from(err.context(ErrorKind::IdentityManager)))
+            .and_then(move |_| {
+                let mut w = write.lock().unwrap();
+                writeln!(w, "{}", name).context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_identity_table() {
+        let identities = [IdentityDetails {
+            module_id: "m1".to_string(),
+            managed_by: "iotedge".to_string(),
+            generation_id: "gen1".to_string(),
+            auth_type: "Sas".to_string(),
+        }];
+        let mut output = TabWriter::new(vec![]).minwidth(15);
+        write_identities(&mut output, OutputFormat::Table, &identities).unwrap();
+
+        assert_eq!(
+            "MODULE ID      MANAGED BY     GENERATION ID  AUTH TYPE\n\
+             m1             iotedge        gen1           Sas\n",
+            String::from_utf8(output.into_inner().unwrap()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use failure::Fail;
+use futures::Future;
+use serde::Serialize;
+
+use edgelet_core::{Module, ModuleRuntime};
+
+use error::{Error, ErrorKind};
+use output::{write_value, OutputFormat};
+use Command;
+
+/// Prints everything the daemon knows about a module.
+pub struct Inspect<M, W> {
+    id: String,
+    runtime: M,
+    format: OutputFormat,
+    output: Arc<Mutex<W>>,
+}
+
+impl<M, W> Inspect<M, W> {
+    pub fn new(id: String, runtime: M, output: W) -> Self {
+        Inspect {
+            id,
+            runtime,
+            format: OutputFormat::default(),
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl<M, W> Command for Inspect<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    M::Module: Serialize,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let id = self.id.clone();
+        let format = self.format;
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .list()
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+            .and_then(move |modules| {
+                let module = modules
+                    .into_iter()
+                    .find(|module| module.name() == id)
+                    .ok_or_else(|| ErrorKind::ModuleNotFound(id.clone()))?;
+                let mut w = write.lock().unwrap();
+                write_value(&mut *w, format, &module)
+            });
+        Box::new(result)
+    }
+}
@@ -15,27 +15,62 @@ extern crate chrono_humanize;
 #[macro_use]
 extern crate clap;
 extern crate edgelet_core;
+extern crate edgelet_docker;
+extern crate edgelet_http_mgmt;
+extern crate edgelet_settings;
 extern crate failure;
 #[macro_use]
 extern crate futures;
+#[cfg(unix)]
+extern crate libc;
+extern crate management;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate serde_yaml;
 extern crate tabwriter;
 extern crate tokio;
 
 use futures::Future;
 
+mod config;
+mod create;
 mod error;
+mod exec;
+mod identity;
+mod inspect;
 mod list;
+mod log_level;
 mod logs;
+mod output;
+mod remove;
 mod restart;
+mod start;
+mod stop;
+mod system_info;
 mod unknown;
 mod version;
+mod volumes;
 
+pub use config::{ConfigUpdate, SetConfig, ValidateConfig};
+pub use create::{read_spec, Create};
 pub use error::{Error, ErrorKind};
+pub use exec::Exec;
+pub use identity::{CreateIdentity, DeleteIdentity, ListIdentities};
+pub use inspect::Inspect;
 pub use list::List;
+pub use log_level::LogLevel;
 pub use logs::Logs;
+pub use output::OutputFormat;
+pub use remove::Remove;
 pub use restart::Restart;
+pub use start::Start;
+pub use stop::Stop;
+pub use system_info::SystemInfo;
 pub use unknown::Unknown;
 pub use version::Version;
+pub use volumes::{BackupVolume, InspectVolume, ListVolumes, RemoveVolume};
 
 pub trait Command {
     type Future: Future<Item = (), Error = Error> + Send;
@@ -8,15 +8,18 @@ use chrono::{Duration, Utc};
 use chrono_humanize::{Accuracy, HumanTime, Tense};
 use failure::{Fail, ResultExt};
 use futures::{Future, Stream};
+use serde::Serialize;
 use tabwriter::TabWriter;
 
 use edgelet_core::{Module, ModuleRuntime, ModuleRuntimeState, ModuleStatus};
 
 use error::{Error, ErrorKind};
+use output::{write_value, OutputFormat};
 use Command;
 
 pub struct List<M, W> {
     runtime: M,
+    format: OutputFormat,
     output: Arc<Mutex<TabWriter<W>>>,
 }
 
@@ -28,21 +31,28 @@ where
         let tab = TabWriter::new(output).minwidth(15);
         List {
             runtime,
+            format: OutputFormat::default(),
             output: Arc::new(Mutex::new(tab)),
         }
     }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
 }
 
 impl<M, W> Command for List<M, W>
 where
     M: 'static + ModuleRuntime + Clone,
-    M::Module: Clone,
+    M::Module: Clone + Serialize,
     M::Config: Display,
     W: 'static + Write + Send,
 {
     type Future = Box<Future<Item = (), Error = Error> + Send>;
 
     fn execute(&mut self) -> Self::Future {
+        let format = self.format;
         let write = self.output.clone();
         let result = self
             .runtime
@@ -51,6 +61,11 @@ where
             .collect()
             .and_then(move |result| {
                 let mut w = write.lock().unwrap();
+                if format != OutputFormat::Table {
+                    let modules: Vec<_> = result.into_iter().map(|(module, _)| module).collect();
+                    return write_value(&mut *w, format, &modules);
+                }
+
                 writeln!(w, "NAME\tSTATUS\tDESCRIPTION\tCONFIG")
                     .context(ErrorKind::WriteToStdout)?;
                 for (module, state) in result {
@@ -95,15 +110,21 @@ fn humanize_state(state: &ModuleRuntimeState) -> String {
                 })
             })
             .unwrap_or_else(|| "Failed".to_string()),
-        ModuleStatus::Running => state.started_at().map_or_else(
-            || "Up".to_string(),
-            |time| {
-                format!(
-                    "Up {}",
-                    time_string(&HumanTime::from(Utc::now() - *time), Tense::Present)
-                )
-            },
-        ),
+        ModuleStatus::Running => {
+            let up = state.started_at().map_or_else(
+                || "Up".to_string(),
+                |time| {
+                    format!(
+                        "Up {}",
+                        time_string(&HumanTime::from(Utc::now() - *time), Tense::Present)
+                    )
+                },
+            );
+            match state.health() {
+                Some(health) => format!("{} ({})", up, health.status()),
+                None => up,
+            }
+        }
     }
 }
 
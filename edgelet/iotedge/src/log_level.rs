@@ -0,0 +1,80 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use failure::{Fail, ResultExt};
+use futures::Future;
+
+use edgelet_http_mgmt::ModuleClient;
+
+use error::{Error, ErrorKind};
+use Command;
+
+const RESET: &str = "reset";
+
+pub struct LogLevel<W> {
+    target: String,
+    level: String,
+    revert_after: Option<Duration>,
+    client: ModuleClient,
+    output: Arc<Mutex<W>>,
+}
+
+impl<W> LogLevel<W> {
+    pub fn new(
+        target: String,
+        level: String,
+        revert_after: Option<Duration>,
+        client: ModuleClient,
+        output: W,
+    ) -> Self {
+        LogLevel {
+            target,
+            level,
+            revert_after,
+            client,
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+}
+
+impl<W> Command for LogLevel<W>
+where
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let write = self.output.clone();
+        let message = if self.level == RESET {
+            format!("Reset log level of {}", self.target)
+        } else {
+            match self.revert_after {
+                Some(revert_after) => format!(
+                    "Set log level of {} to {} for {}s",
+                    self.target,
+                    self.level,
+                    revert_after.as_secs()
+                ),
+                None => format!("Set log level of {} to {}", self.target, self.level),
+            }
+        };
+
+        let changed = if self.level == RESET {
+            self.client.reset_log_level(&self.target)
+        } else {
+            self.client
+                .set_log_level(&self.target, &self.level, self.revert_after)
+        };
+        let result = changed
+            .map_err(|err| Error::from(err.context(ErrorKind::LogLevel)))
+            .and_then(move |_| {
+                let mut w = write.lock().unwrap();
+                writeln!(w, "{}", message).context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
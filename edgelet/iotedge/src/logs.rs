@@ -79,14 +79,14 @@ where
 /// is then constructed from these [`BytesMut`]s
 
 #[derive(Debug, PartialEq)]
-enum LogChunk {
+pub enum LogChunk {
     Stdin(Bytes),
     Stdout(Bytes),
     Stderr(Bytes),
     Unknown(Bytes),
 }
 
-struct LogDecode<T: AsyncRead> {
+pub struct LogDecode<T: AsyncRead> {
     inner: FramedRead<T, length_delimited::LengthDelimitedCodec>,
 }
 
@@ -17,16 +17,19 @@ extern crate iotedge;
 extern crate tokio;
 extern crate url;
 
+use std::fs::File;
 use std::io;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Duration;
 
-use clap::{App, AppSettings, Arg, SubCommand};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use failure::{Fail, ResultExt};
 use url::Url;
 
-use edgelet_core::{LogOptions, LogTail};
-use edgelet_http_mgmt::ModuleClient;
+use edgelet_core::{ExecOptions, LogOptions, LogTail};
+use edgelet_http_mgmt::{IdentityClient, ModuleClient};
 
 use iotedge::*;
 
@@ -35,6 +38,13 @@ const MGMT_URI: &str = "unix:///var/run/iotedge/mgmt.sock";
 #[cfg(windows)]
 const MGMT_URI: &str = "unix:///C:/ProgramData/iotedge/mgmt/sock";
 
+#[cfg(unix)]
+const CONFIG_FILE: &str = "/etc/iotedge/config.yaml";
+#[cfg(windows)]
+const CONFIG_FILE: &str = "C:\\ProgramData\\iotedge\\config.yaml";
+
+const DPS_GLOBAL_ENDPOINT: &str = "https://global.azure-devices-provisioning.net";
+
 fn main() {
     if let Err(ref error) = run() {
         let stderr = &mut io::stderr();
@@ -69,7 +79,59 @@ fn run() -> Result<(), Error> {
                 .env("IOTEDGE_HOST")
                 .default_value(default_uri),
         )
+        .arg(
+            Arg::with_name("output")
+                .help("Output format of the commands that print modules, identities or system information")
+                .long("output")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(&["table", "json", "yaml"])
+                .global(true)
+                .default_value("table"),
+        )
         .subcommand(SubCommand::with_name("list").about("List modules"))
+        .subcommand(
+            SubCommand::with_name("inspect")
+                .about("Show the details of a module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module to inspect")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create")
+                .about("Create a module")
+                .arg(
+                    Arg::with_name("from-file")
+                        .help("Sets the JSON file with the spec of the module")
+                        .long("from-file")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("start")
+                .about("Start a module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module to start")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stop")
+                .about("Stop a module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module to stop")
+                        .required(true)
+                        .index(1),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("restart")
                 .about("Restart a module")
@@ -80,6 +142,16 @@ fn run() -> Result<(), Error> {
                         .index(1),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("rm")
+                .about("Remove a module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module to remove")
+                        .required(true)
+                        .index(1),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("logs")
                 .about("Fetch the logs of a module")
@@ -104,6 +176,263 @@ fn run() -> Result<(), Error> {
                         .long("follow"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("exec")
+                .about("Run a command in a module")
+                .arg(
+                    Arg::with_name("MODULE")
+                        .help("Sets the module to run the command in")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("user")
+                        .help("Run the command as this user")
+                        .short("u")
+                        .long("user")
+                        .takes_value(true)
+                        .value_name("USER"),
+                )
+                .arg(
+                    Arg::with_name("interactive")
+                        .help("Attach the stdin of the command")
+                        .short("i")
+                        .long("interactive"),
+                )
+                .arg(
+                    Arg::with_name("tty")
+                        .help("Allocate a tty for the command")
+                        .short("t")
+                        .long("tty"),
+                )
+                .arg(
+                    Arg::with_name("CMD")
+                        .help("The command to run and its arguments, after --")
+                        .required(true)
+                        .multiple(true)
+                        .last(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("volumes")
+                .about("Manage module volumes")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("List the volumes of a module")
+                        .arg(
+                            Arg::with_name("MODULE")
+                                .help("Sets the module to list volumes for")
+                                .required(true)
+                                .index(1),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("inspect")
+                        .about("Show details of a module volume")
+                        .arg(
+                            Arg::with_name("MODULE")
+                                .help("Sets the module owning the volume")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("VOLUME")
+                                .help("Sets the volume to inspect")
+                                .required(true)
+                                .index(2),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("backup")
+                        .about("Write a tar archive of a module volume")
+                        .arg(
+                            Arg::with_name("MODULE")
+                                .help("Sets the module owning the volume")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("VOLUME")
+                                .help("Sets the volume to back up")
+                                .required(true)
+                                .index(2),
+                        )
+                        .arg(
+                            Arg::with_name("file")
+                                .help("Write the archive to a file instead of stdout")
+                                .short("f")
+                                .long("file")
+                                .takes_value(true)
+                                .value_name("FILE"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("rm")
+                        .about("Remove a module volume")
+                        .arg(
+                            Arg::with_name("MODULE")
+                                .help("Sets the module owning the volume")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("VOLUME")
+                                .help("Sets the volume to remove")
+                                .required(true)
+                                .index(2),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("log-level")
+                .about("Change the log level of the daemon while it is running")
+                .arg(
+                    Arg::with_name("TARGET")
+                        .help("Sets the log target to change, or \"default\" for all targets")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("LEVEL")
+                        .help("Sets the log level, or \"reset\" to go back to the configured one")
+                        .possible_values(&[
+                            "off", "error", "warn", "info", "debug", "trace", "reset",
+                        ])
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("revert-after")
+                        .help("Reverts to the configured log level after this many seconds")
+                        .long("revert-after")
+                        .takes_value(true)
+                        .value_name("SECS"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("identity")
+                .about("Manage module identities")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(SubCommand::with_name("list").about("List module identities"))
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about("Create a module identity")
+                        .arg(
+                            Arg::with_name("MODULE")
+                                .help("Sets the module to create an identity for")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("managed-by")
+                                .help("Sets who manages the identity")
+                                .long("managed-by")
+                                .takes_value(true)
+                                .value_name("MANAGER"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("delete")
+                        .about("Delete a module identity")
+                        .arg(
+                            Arg::with_name("MODULE")
+                                .help("Sets the module whose identity to delete")
+                                .required(true)
+                                .index(1),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("system-info")
+                .about("Show the operating system and architecture of the device"),
+        )
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("Validate and edit the configuration of the daemon")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("validate")
+                        .about("Check that a config file can be loaded by the daemon")
+                        .arg(
+                            Arg::with_name("FILE")
+                                .help("Sets the config file to validate")
+                                .index(1)
+                                .default_value(CONFIG_FILE),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-manual")
+                        .about("Provision the device with an IoT Hub connection string")
+                        .arg(config_file_arg())
+                        .arg(
+                            Arg::with_name("connection-string")
+                                .help("Sets the device connection string")
+                                .long("connection-string")
+                                .takes_value(true)
+                                .value_name("CONNECTION_STRING")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-dps")
+                        .about("Provision the device with the Device Provisioning Service")
+                        .arg(config_file_arg())
+                        .arg(
+                            Arg::with_name("scope-id")
+                                .help("Sets the ID scope of the DPS instance")
+                                .long("scope-id")
+                                .takes_value(true)
+                                .value_name("SCOPE_ID")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("registration-id")
+                                .help("Sets the registration ID of the device")
+                                .long("registration-id")
+                                .takes_value(true)
+                                .value_name("REGISTRATION_ID")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("global-endpoint")
+                                .help("Sets the global endpoint of DPS")
+                                .long("global-endpoint")
+                                .takes_value(true)
+                                .value_name("URI")
+                                .default_value(DPS_GLOBAL_ENDPOINT),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-certs")
+                        .about("Set the certificates used when running as a gateway")
+                        .arg(config_file_arg())
+                        .arg(
+                            Arg::with_name("device-ca-cert")
+                                .help("Sets the path to the device CA certificate and its chain")
+                                .long("device-ca-cert")
+                                .takes_value(true)
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("device-ca-pk")
+                                .help("Sets the path to the private key of the device CA")
+                                .long("device-ca-pk")
+                                .takes_value(true)
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("trusted-ca-certs")
+                                .help("Sets the path to the trusted CA certificates")
+                                .long("trusted-ca-certs")
+                                .takes_value(true)
+                                .value_name("FILE")
+                                .required(true),
+                        ),
+                ),
+        )
         .subcommand(SubCommand::with_name("version").about("Show the version information"))
         .get_matches();
 
@@ -116,11 +445,53 @@ fn run() -> Result<(), Error> {
         },
     )?;
     let runtime = ModuleClient::new(&url).context(ErrorKind::ModuleRuntime)?;
+    let id_manager = IdentityClient::new(&url).context(ErrorKind::IdentityManager)?;
 
     let mut tokio_runtime = tokio::runtime::Runtime::new().context(ErrorKind::InitializeTokio)?;
 
     match matches.subcommand() {
-        ("list", Some(_args)) => tokio_runtime.block_on(List::new(runtime, io::stdout()).execute()),
+        ("list", Some(args)) => tokio_runtime.block_on(
+            List::new(runtime, io::stdout())
+                .with_format(output_format(args)?)
+                .execute(),
+        ),
+        ("inspect", Some(args)) => tokio_runtime.block_on(
+            Inspect::new(
+                args.value_of("MODULE").unwrap().to_string(),
+                runtime,
+                io::stdout(),
+            )
+            .with_format(output_format(args)?)
+            .execute(),
+        ),
+        ("create", Some(args)) => {
+            let spec = read_spec(Path::new(args.value_of("from-file").unwrap()))?;
+            tokio_runtime.block_on(Create::new(spec, runtime, io::stdout()).execute())
+        }
+        ("start", Some(args)) => tokio_runtime.block_on(
+            Start::new(
+                args.value_of("MODULE").unwrap().to_string(),
+                runtime,
+                io::stdout(),
+            )
+            .execute(),
+        ),
+        ("stop", Some(args)) => tokio_runtime.block_on(
+            Stop::new(
+                args.value_of("MODULE").unwrap().to_string(),
+                runtime,
+                io::stdout(),
+            )
+            .execute(),
+        ),
+        ("rm", Some(args)) => tokio_runtime.block_on(
+            Remove::new(
+                args.value_of("MODULE").unwrap().to_string(),
+                runtime,
+                io::stdout(),
+            )
+            .execute(),
+        ),
         ("restart", Some(args)) => tokio_runtime.block_on(
             Restart::new(
                 args.value_of("MODULE").unwrap().to_string(),
@@ -139,7 +510,172 @@ fn run() -> Result<(), Error> {
             let options = LogOptions::new().with_follow(follow).with_tail(tail);
             tokio_runtime.block_on(Logs::new(id, options, runtime).execute())
         }
+        ("exec", Some(args)) => {
+            let id = args.value_of("MODULE").unwrap().to_string();
+            let cmd = args
+                .values_of("CMD")
+                .unwrap()
+                .map(ToString::to_string)
+                .collect();
+            let options = ExecOptions::new(cmd)
+                .with_user(args.value_of("user").map(ToString::to_string))
+                .with_attach_stdin(args.is_present("interactive"))
+                .with_tty(args.is_present("tty"));
+            let mut exec = Exec::new(id, options, runtime);
+            tokio_runtime.block_on(exec.execute())?;
+
+            // exit with the exit code of the command
+            match exec.exit_code() {
+                Some(0) | None => Ok(()),
+                Some(code) => process::exit(code),
+            }
+        }
+        ("volumes", Some(args)) => match args.subcommand() {
+            ("list", Some(args)) => tokio_runtime.block_on(
+                ListVolumes::new(
+                    args.value_of("MODULE").unwrap().to_string(),
+                    runtime,
+                    io::stdout(),
+                )
+                .execute(),
+            ),
+            ("inspect", Some(args)) => tokio_runtime.block_on(
+                InspectVolume::new(
+                    args.value_of("MODULE").unwrap().to_string(),
+                    args.value_of("VOLUME").unwrap().to_string(),
+                    runtime,
+                    io::stdout(),
+                )
+                .execute(),
+            ),
+            ("backup", Some(args)) => {
+                let output: Box<Write + Send> = match args.value_of("file") {
+                    Some(path) => Box::new(File::create(path).context(ErrorKind::WriteBackup)?),
+                    None => Box::new(io::stdout()),
+                };
+                tokio_runtime.block_on(
+                    BackupVolume::new(
+                        args.value_of("MODULE").unwrap().to_string(),
+                        args.value_of("VOLUME").unwrap().to_string(),
+                        runtime,
+                        output,
+                    )
+                    .execute(),
+                )
+            }
+            ("rm", Some(args)) => tokio_runtime.block_on(
+                RemoveVolume::new(
+                    args.value_of("MODULE").unwrap().to_string(),
+                    args.value_of("VOLUME").unwrap().to_string(),
+                    runtime,
+                    io::stdout(),
+                )
+                .execute(),
+            ),
+            (command, _) => tokio_runtime.block_on(Unknown::new(command.to_string()).execute()),
+        },
+        ("log-level", Some(args)) => {
+            let revert_after = args
+                .value_of("revert-after")
+                .map(|secs| secs.parse::<u64>().map(Duration::from_secs))
+                .map_or(Ok(None), |secs| secs.map(Some))
+                .context(ErrorKind::BadRevertAfterParameter)?;
+            tokio_runtime.block_on(
+                LogLevel::new(
+                    args.value_of("TARGET").unwrap().to_string(),
+                    args.value_of("LEVEL").unwrap().to_string(),
+                    revert_after,
+                    runtime,
+                    io::stdout(),
+                )
+                .execute(),
+            )
+        }
+        ("identity", Some(args)) => match args.subcommand() {
+            ("list", Some(args)) => tokio_runtime.block_on(
+                ListIdentities::new(id_manager, io::stdout())
+                    .with_format(output_format(args)?)
+                    .execute(),
+            ),
+            ("create", Some(args)) => tokio_runtime.block_on(
+                CreateIdentity::new(
+                    args.value_of("MODULE").unwrap().to_string(),
+                    id_manager,
+                    io::stdout(),
+                )
+                .with_managed_by(args.value_of("managed-by").map(ToString::to_string))
+                .with_format(output_format(args)?)
+                .execute(),
+            ),
+            ("delete", Some(args)) => tokio_runtime.block_on(
+                DeleteIdentity::new(
+                    args.value_of("MODULE").unwrap().to_string(),
+                    id_manager,
+                    io::stdout(),
+                )
+                .execute(),
+            ),
+            (command, _) => tokio_runtime.block_on(Unknown::new(command.to_string()).execute()),
+        },
+        ("system-info", Some(args)) => tokio_runtime.block_on(
+            SystemInfo::new(runtime, io::stdout())
+                .with_format(output_format(args)?)
+                .execute(),
+        ),
+        ("config", Some(args)) => match args.subcommand() {
+            ("validate", Some(args)) => tokio_runtime.block_on(
+                ValidateConfig::new(PathBuf::from(args.value_of("FILE").unwrap()), io::stdout())
+                    .execute(),
+            ),
+            ("set-manual", Some(args)) => {
+                let update = ConfigUpdate::Manual {
+                    connection_string: args.value_of("connection-string").unwrap().to_string(),
+                };
+                tokio_runtime
+                    .block_on(SetConfig::new(config_file(args), update, io::stdout()).execute())
+            }
+            ("set-dps", Some(args)) => {
+                let update = ConfigUpdate::Dps {
+                    global_endpoint: args.value_of("global-endpoint").unwrap().to_string(),
+                    scope_id: args.value_of("scope-id").unwrap().to_string(),
+                    registration_id: args.value_of("registration-id").unwrap().to_string(),
+                };
+                tokio_runtime
+                    .block_on(SetConfig::new(config_file(args), update, io::stdout()).execute())
+            }
+            ("set-certs", Some(args)) => {
+                let update = ConfigUpdate::Certificates {
+                    device_ca_cert: args.value_of("device-ca-cert").unwrap().to_string(),
+                    device_ca_pk: args.value_of("device-ca-pk").unwrap().to_string(),
+                    trusted_ca_certs: args.value_of("trusted-ca-certs").unwrap().to_string(),
+                };
+                tokio_runtime
+                    .block_on(SetConfig::new(config_file(args), update, io::stdout()).execute())
+            }
+            (command, _) => tokio_runtime.block_on(Unknown::new(command.to_string()).execute()),
+        },
         ("version", Some(_args)) => tokio_runtime.block_on(Version::new().execute()),
         (command, _) => tokio_runtime.block_on(Unknown::new(command.to_string()).execute()),
     }
 }
+
+fn config_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("config-file")
+        .help("Sets the config file to edit")
+        .short("c")
+        .long("config-file")
+        .takes_value(true)
+        .value_name("FILE")
+        .default_value(CONFIG_FILE)
+}
+
+fn config_file(args: &ArgMatches) -> PathBuf {
+    PathBuf::from(args.value_of("config-file").unwrap())
+}
+
+// Global arguments are only passed down to subcommands, so the output format
+// is read from the matches of the command being run.
+fn output_format(args: &ArgMatches) -> Result<OutputFormat, Error> {
+    args.value_of("output")
+        .map_or_else(|| Ok(OutputFormat::default()), str::parse)
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io::Write;
+use std::str::FromStr;
+
+use failure::ResultExt;
+use serde::Serialize;
+use serde_json;
+use serde_yaml;
+
+use error::{Error, ErrorKind};
+
+/// How commands print what they read from the daemon. Tables are meant for
+/// people, JSON and YAML for scripts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Yaml,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        OutputFormat::Table
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            _ => Err(Error::from(ErrorKind::BadOutputParameter)),
+        }
+    }
+}
+
+/// Writes `value` as JSON or YAML. Values that don't fit in a table are
+/// written as JSON when the format is `Table`.
+pub fn write_value<W, T>(w: &mut W, format: OutputFormat, value: &T) -> Result<(), Error>
+where
+    W: Write,
+    T: Serialize,
+{
+    match format {
+        OutputFormat::Table | OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *w, value).context(ErrorKind::WriteToStdout)?
+        }
+        OutputFormat::Yaml => {
+            serde_yaml::to_writer(&mut *w, value).context(ErrorKind::WriteToStdout)?
+        }
+    }
+    writeln!(w).context(ErrorKind::WriteToStdout)?;
+    w.flush().context(ErrorKind::WriteToStdout)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Value {
+        name: &'static str,
+    }
+
+    #[test]
+    fn parse_formats() {
+        assert_eq!(OutputFormat::Table, "table".parse().unwrap());
+        assert_eq!(OutputFormat::Json, "json".parse().unwrap());
+        assert_eq!(OutputFormat::Yaml, "yaml".parse().unwrap());
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn write_json_and_yaml() {
+        let value = Value { name: "m1" };
+
+        let mut json = vec![];
+        write_value(&mut json, OutputFormat::Json, &value).unwrap();
+        assert_eq!(
+            "{\n  \"name\": \"m1\"\n}\n",
+            String::from_utf8(json).unwrap()
+        );
+
+        let mut yaml = vec![];
+        write_value(&mut yaml, OutputFormat::Yaml, &value).unwrap();
+        assert_eq!("---\nname: m1\n", String::from_utf8(yaml).unwrap());
+    }
+}
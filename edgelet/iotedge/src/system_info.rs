@@ -0,0 +1,79 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use failure::{Fail, ResultExt};
+use futures::Future;
+
+use edgelet_core::ModuleRuntime;
+
+use error::{Error, ErrorKind};
+use output::{write_value, OutputFormat};
+use Command;
+
+#[derive(Serialize)]
+struct SystemInfoDetails {
+    #[serde(rename = "osType")]
+    os_type: String,
+    architecture: String,
+}
+
+/// Prints the operating system and architecture of the device.
+pub struct SystemInfo<M, W> {
+    runtime: M,
+    format: OutputFormat,
+    output: Arc<Mutex<W>>,
+}
+
+impl<M, W> SystemInfo<M, W> {
+    pub fn new(runtime: M, output: W) -> Self {
+        SystemInfo {
+            runtime,
+            format: OutputFormat::default(),
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl<M, W> Command for SystemInfo<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let format = self.format;
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .system_info()
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+            .and_then(move |info| {
+                let details = SystemInfoDetails {
+                    os_type: info.os_type().to_string(),
+                    architecture: info.architecture().to_string(),
+                };
+                let mut w = write.lock().unwrap();
+                if format == OutputFormat::Table {
+                    write_table(&mut *w, &details).context(ErrorKind::WriteToStdout)?;
+                    Ok(())
+                } else {
+                    write_value(&mut *w, format, &details)
+                }
+            });
+        Box::new(result)
+    }
+}
+
+fn write_table<W: Write>(w: &mut W, details: &SystemInfoDetails) -> ::std::io::Result<()> {
+    writeln!(w, "OS type:      {}", details.os_type)?;
+    writeln!(w, "Architecture: {}", details.architecture)?;
+    w.flush()
+}
@@ -0,0 +1,236 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use failure::{Fail, ResultExt};
+use futures::{Future, Stream};
+use tabwriter::TabWriter;
+
+use edgelet_core::{ModuleRuntime, ModuleVolume};
+
+use error::{Error, ErrorKind};
+use Command;
+
+pub struct ListVolumes<M, W> {
+    module: String,
+    runtime: M,
+    output: Arc<Mutex<TabWriter<W>>>,
+}
+
+impl<M, W> ListVolumes<M, W>
+where
+    W: Write,
+{
+    pub fn new(module: String, runtime: M, output: W) -> Self {
+        let tab = TabWriter::new(output).minwidth(15);
+        ListVolumes {
+            module,
+            runtime,
+            output: Arc::new(Mutex::new(tab)),
+        }
+    }
+}
+
+impl<M, W> Command for ListVolumes<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .list_volumes(&self.module)
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+            .and_then(move |volumes| {
+                let mut w = write.lock().unwrap();
+                writeln!(w, "NAME\tDRIVER\tCREATED\tMOUNTPOINT")
+                    .context(ErrorKind::WriteToStdout)?;
+                for volume in volumes {
+                    writeln!(
+                        w,
+                        "{}\t{}\t{}\t{}",
+                        volume.name(),
+                        volume.driver(),
+                        volume.created_at().unwrap_or("-"),
+                        volume.mount_point(),
+                    )
+                    .context(ErrorKind::WriteToStdout)?;
+                }
+                w.flush().context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
+
+pub struct InspectVolume<M, W> {
+    module: String,
+    name: String,
+    runtime: M,
+    output: Arc<Mutex<W>>,
+}
+
+impl<M, W> InspectVolume<M, W> {
+    pub fn new(module: String, name: String, runtime: M, output: W) -> Self {
+        InspectVolume {
+            module,
+            name,
+            runtime,
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+}
+
+impl<M, W> Command for InspectVolume<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .get_volume(&self.module, &self.name)
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+            .and_then(move |volume| {
+                let mut w = write.lock().unwrap();
+                write_volume(&mut *w, &volume).context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
+
+fn write_volume<W: Write>(w: &mut W, volume: &ModuleVolume) -> ::std::io::Result<()> {
+    writeln!(w, "Name:        {}", volume.name())?;
+    writeln!(w, "Module:      {}", volume.module())?;
+    writeln!(w, "Driver:      {}", volume.driver())?;
+    writeln!(w, "Mount point: {}", volume.mount_point())?;
+    writeln!(w, "Created:     {}", volume.created_at().unwrap_or("-"))?;
+    w.flush()
+}
+
+/// Writes a tar archive of the contents of a module volume to the output.
+pub struct BackupVolume<M, W> {
+    module: String,
+    name: String,
+    runtime: M,
+    output: Arc<Mutex<W>>,
+}
+
+impl<M, W> BackupVolume<M, W> {
+    pub fn new(module: String, name: String, runtime: M, output: W) -> Self {
+        BackupVolume {
+            module,
+            name,
+            runtime,
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+}
+
+impl<M, W> Command for BackupVolume<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .backup_volume(&self.module, &self.name)
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+            .and_then(move |archive| {
+                let write_copy = write.clone();
+                archive
+                    .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+                    .for_each(move |chunk| {
+                        let mut w = write.lock().unwrap();
+                        w.write_all(chunk.as_ref())
+                            .context(ErrorKind::WriteBackup)?;
+                        Ok(())
+                    })
+                    .and_then(move |_| {
+                        let mut w = write_copy.lock().unwrap();
+                        w.flush().context(ErrorKind::WriteBackup)?;
+                        Ok(())
+                    })
+            });
+        Box::new(result)
+    }
+}
+
+pub struct RemoveVolume<M, W> {
+    module: String,
+    name: String,
+    runtime: M,
+    output: Arc<Mutex<W>>,
+}
+
+impl<M, W> RemoveVolume<M, W> {
+    pub fn new(module: String, name: String, runtime: M, output: W) -> Self {
+        RemoveVolume {
+            module,
+            name,
+            runtime,
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+}
+
+impl<M, W> Command for RemoveVolume<M, W>
+where
+    M: 'static + ModuleRuntime + Clone,
+    W: 'static + Write + Send,
+{
+    type Future = Box<Future<Item = (), Error = Error> + Send>;
+
+    fn execute(&mut self) -> Self::Future {
+        let name = self.name.clone();
+        let write = self.output.clone();
+        let result = self
+            .runtime
+            .remove_volume(&self.module, &self.name)
+            .map_err(|err| Error::from(err.context(ErrorKind::ModuleRuntime)))
+            .and_then(move |_| {
+                let mut w = write.lock().unwrap();
+                writeln!(w, "{}", name).context(ErrorKind::WriteToStdout)?;
+                Ok(())
+            });
+        Box::new(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_volume_without_created_at() {
+        let volume = ModuleVolume::new(
+            "data".to_string(),
+            "mod1".to_string(),
+            "local".to_string(),
+            "/var/lib/docker/volumes/data/_data".to_string(),
+        );
+        let mut output = vec![];
+        write_volume(&mut output, &volume).unwrap();
+
+        assert_eq!(
+            "Name:        data\n\
+             Module:      mod1\n\
+             Driver:      local\n\
+             Mount point: /var/lib/docker/volumes/data/_data\n\
+             Created:     -\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+}
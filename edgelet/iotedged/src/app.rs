@@ -3,10 +3,11 @@
 use clap::{App, Arg, ArgMatches};
 use edgelet_core;
 use edgelet_docker::DockerConfig;
+use edgelet_settings::Settings;
+use failure::ResultExt;
 
-use error::Error;
+use error::{Error, ErrorKind, InitializeErrorReason};
 use logging;
-use settings::Settings;
 
 pub fn create_base_app<'a, 'b>() -> App<'a, 'b> {
     App::new(crate_name!())
@@ -48,24 +49,18 @@ pub fn log_banner() {
 
 pub fn init_common<'a>() -> Result<(Settings<DockerConfig>, ArgMatches<'a>), Error> {
     let matches = create_app().get_matches();
-    let settings = {
-        let config_file = matches
-            .value_of("config-file")
-            .and_then(|name| {
-                info!("Using config file: {}", name);
-                Some(name)
-            })
-            .or_else(|| {
-                info!("Using default configuration");
-                None
-            });
-
-        Settings::<DockerConfig>::new(config_file)?
-    };
-
+    let settings = Settings::<DockerConfig>::new(matches.value_of("config-file"))
+        .context(ErrorKind::Initialize(InitializeErrorReason::LoadSettings))?;
     Ok((settings, matches))
 }
 
+fn log_config_file(matches: &ArgMatches) {
+    match matches.value_of("config-file") {
+        Some(name) => info!("Using config file: {}", name),
+        None => info!("Using default configuration"),
+    }
+}
+
 #[cfg(target_os = "windows")]
 pub fn init() -> Result<Settings<DockerConfig>, Error> {
     let (settings, matches) = init_common()?;
@@ -73,24 +68,35 @@ pub fn init() -> Result<Settings<DockerConfig>, Error> {
     if matches.is_present("use-event-logger") {
         logging::init_win_log();
     } else {
-        logging::init();
+        logging::init(settings.logging());
     }
 
     log_banner();
+    log_config_file(&matches);
 
     Ok(settings)
 }
 
+/// Logging is set up from the settings, so they are loaded first. If that
+/// fails, the default logging is used to report the error.
 #[cfg(not(target_os = "windows"))]
 pub fn init() -> Result<Settings<DockerConfig>, Error> {
-    logging::init();
+    let (settings, matches) = init_common().map_err(|err| {
+        logging::init(None);
+        err
+    })?;
+
+    logging::init(settings.logging());
     log_banner();
-    init_common().map(|(settings, _)| settings)
+    log_config_file(&matches);
+    Ok(settings)
 }
 
 #[cfg(target_os = "windows")]
 pub fn init_win_svc() -> Result<Settings<DockerConfig>, Error> {
     logging::init_win_log();
     log_banner();
-    init_common().map(|(settings, _)| settings)
+    let (settings, matches) = init_common()?;
+    log_config_file(&matches);
+    Ok(settings)
 }
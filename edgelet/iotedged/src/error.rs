@@ -71,6 +71,7 @@ impl From<Context<ErrorKind>> for Error {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum InitializeErrorReason {
+    CertificateNotifier,
     CreateMasterEncryptionKey,
     CreateSettingsDirectory,
     DestroyWorkloadCa,
@@ -79,27 +80,41 @@ pub enum InitializeErrorReason {
     EdgeRuntime,
     Hsm,
     HttpClient,
+    IdentityCache,
+    ImageTrust,
+    InvalidDeviceCaCertificate,
     InvalidProxyUri,
     InvalidSocketUri,
+    KeyStore,
     LoadSettings,
     ManagementService,
     ManualProvisioningClient,
+    MasterKeyVersions,
     ModuleRuntime,
     NotConfigured,
+    Pkcs11,
     PrepareWorkloadCa,
+    PrivilegedGroup,
     #[cfg(windows)]
     RegisterWindowsService,
     RemoveExistingModules,
     SaveSettings,
+    SecretStore,
+    SoftwareCrypto,
     #[cfg(windows)]
     StartWindowsService,
     Tokio,
+    TrustBundleStore,
     WorkloadService,
 }
 
 impl fmt::Display for InitializeErrorReason {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            InitializeErrorReason::CertificateNotifier => {
+                write!(f, "Could not load the issued certificates")
+            }
+
             InitializeErrorReason::CreateMasterEncryptionKey => {
                 write!(f, "Could not create master encryption key")
             }
@@ -124,10 +139,20 @@ impl fmt::Display for InitializeErrorReason {
 
             InitializeErrorReason::HttpClient => write!(f, "Could not initialize HTTP client"),
 
+            InitializeErrorReason::IdentityCache => write!(f, "Could not load identity cache"),
+
+            InitializeErrorReason::ImageTrust => write!(f, "Could not load image trust root"),
+
+            InitializeErrorReason::InvalidDeviceCaCertificate => {
+                write!(f, "The configured device CA certificate is invalid")
+            }
+
             InitializeErrorReason::InvalidProxyUri => write!(f, "Invalid proxy URI"),
 
             InitializeErrorReason::InvalidSocketUri => write!(f, "Invalid socket URI"),
 
+            InitializeErrorReason::KeyStore => write!(f, "Could not initialize the key store"),
+
             InitializeErrorReason::LoadSettings => write!(f, "Could not load settings"),
 
             InitializeErrorReason::ManagementService => {
@@ -138,6 +163,10 @@ impl fmt::Display for InitializeErrorReason {
                 write!(f, "Could not initialize manual provisioning client")
             }
 
+            InitializeErrorReason::MasterKeyVersions => {
+                write!(f, "Could not load the master encryption key versions")
+            }
+
             InitializeErrorReason::ModuleRuntime => {
                 write!(f, "Could not initialize module runtime")
             }
@@ -154,10 +183,16 @@ impl fmt::Display for InitializeErrorReason {
                 }
             ),
 
+            InitializeErrorReason::Pkcs11 => write!(f, "Could not initialize PKCS#11 module"),
+
             InitializeErrorReason::PrepareWorkloadCa => {
                 write!(f, "Could not prepare workload CA certificate")
             }
 
+            InitializeErrorReason::PrivilegedGroup => {
+                write!(f, "Could not resolve the privileged group")
+            }
+
             #[cfg(windows)]
             InitializeErrorReason::RegisterWindowsService => {
                 write!(f, "Could not register Windows Service control handle")
@@ -169,6 +204,12 @@ impl fmt::Display for InitializeErrorReason {
 
             InitializeErrorReason::SaveSettings => write!(f, "Could not save settings file"),
 
+            InitializeErrorReason::SecretStore => write!(f, "Could not open the secret store"),
+
+            InitializeErrorReason::SoftwareCrypto => {
+                write!(f, "Could not initialize software crypto store")
+            }
+
             #[cfg(windows)]
             InitializeErrorReason::StartWindowsService => {
                 write!(f, "Could not start as Windows Service")
@@ -176,6 +217,10 @@ impl fmt::Display for InitializeErrorReason {
 
             InitializeErrorReason::Tokio => write!(f, "Could not initialize tokio runtime"),
 
+            InitializeErrorReason::TrustBundleStore => {
+                write!(f, "Could not open the trust bundle store")
+            }
+
             InitializeErrorReason::WorkloadService => write!(f, "Could not start workload service"),
         }
     }
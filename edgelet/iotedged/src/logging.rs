@@ -1,11 +1,17 @@
 // Copyright (c) Microsoft. All rights reserved.
 
 use std::env;
-use std::io::Write;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::process;
 
-use edgelet_utils::log_failure;
-use env_logger;
-use log::{Level, LevelFilter};
+use chrono::Utc;
+use edgelet_http::logging::{with_request_context, RequestContext};
+use edgelet_settings::{LogFormat, Logging};
+use edgelet_utils::{log_failure, LogLevels};
+use env_logger::filter::{Builder as FilterBuilder, Filter};
+use log::{self, Level, LevelFilter, Log, Metadata, Record};
 #[cfg(target_os = "windows")]
 use win_logger::EventLogger;
 
@@ -14,43 +20,39 @@ use error::Error;
 #[cfg(target_os = "windows")]
 const IOTEDGED_SERVICE_NAME: &str = crate_name!();
 const ENV_LOG: &str = "IOTEDGE_LOG";
+#[cfg(unix)]
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+const SYSLOG_IDENTIFIER: &str = "iotedged";
 
-pub fn init() {
-    env_logger::Builder::new()
-        .format(|fmt, record| {
-            let level = match record.level() {
-                Level::Trace => "TRCE",
-                Level::Debug => "DBUG",
-                Level::Info => "INFO",
-                Level::Warn => "WARN",
-                Level::Error => "ERR!",
-            };
-            let timestamp = fmt.timestamp();
-
-            if record.level() >= Level::Debug {
-                writeln!(
-                    fmt,
-                    "<{}>{} [{}] - [{}] {}",
-                    syslog_level(record.level()),
-                    timestamp,
-                    level,
-                    record.target(),
-                    record.args()
-                )
-            } else {
-                writeln!(
-                    fmt,
-                    "<{}>{} [{}] - {}",
-                    syslog_level(record.level()),
-                    timestamp,
-                    level,
-                    record.args()
-                )
-            }
-        })
-        .filter_level(LevelFilter::Info)
-        .parse(&env::var(ENV_LOG).unwrap_or_default())
-        .init();
+lazy_static! {
+    static ref LOG_LEVELS: LogLevels = LogLevels::new();
+}
+
+/// The levels that can be changed while the daemon is running. They take
+/// precedence over the configured levels.
+pub fn log_levels() -> LogLevels {
+    LOG_LEVELS.clone()
+}
+
+/// Sets up logging in the format and with the levels from `settings`. Levels
+/// from the `IOTEDGE_LOG` environment variable take precedence.
+pub fn init(settings: Option<&Logging>) {
+    let mut filter = FilterBuilder::new();
+    filter.filter_level(LevelFilter::Info);
+    if let Some(settings) = settings {
+        filter.parse(&settings.directives());
+    }
+    filter.parse(&env::var(ENV_LOG).unwrap_or_default());
+    let filter = filter.build();
+
+    let format = settings.map_or_else(LogFormat::default, Logging::format);
+    LOG_LEVELS.set_configured_level(filter.filter());
+    log::set_boxed_logger(Box::new(Logger {
+        filter,
+        levels: log_levels(),
+        sink: Sink::new(format),
+    }))
+    .expect("logger should only be initialized once");
 }
 
 #[cfg(target_os = "windows")]
@@ -66,6 +68,192 @@ pub fn init_win_log() {
         .expect("Could not initialize Windows EventLogger");
 }
 
+enum Sink {
+    Text,
+    Json,
+    #[cfg(unix)]
+    Journald(UnixDatagram),
+}
+
+impl Sink {
+    fn new(format: LogFormat) -> Self {
+        match format {
+            LogFormat::Text => Sink::Text,
+            LogFormat::Json => Sink::Json,
+            LogFormat::Journald => Sink::journald(),
+        }
+    }
+
+    #[cfg(unix)]
+    fn journald() -> Self {
+        let socket = UnixDatagram::unbound()
+            .and_then(|socket| socket.connect(JOURNALD_SOCKET).map(|_| socket));
+        match socket {
+            Ok(socket) => Sink::Journald(socket),
+            Err(err) => {
+                eprintln!(
+                    "Could not connect to journald, logging to stderr instead: {}",
+                    err
+                );
+                Sink::Text
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn journald() -> Self {
+        eprintln!("journald is not available, logging to stderr instead");
+        Sink::Text
+    }
+}
+
+struct Logger {
+    filter: Filter,
+    levels: LogLevels,
+    sink: Sink,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match self.levels.level(metadata.target()) {
+            Some(level) => metadata.level() <= level,
+            None => self.filter.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        let enabled = match self.levels.level(record.target()) {
+            Some(level) => record.level() <= level,
+            None => self.filter.matches(record),
+        };
+        if !enabled {
+            return;
+        }
+
+        with_request_context(|context| match self.sink {
+            Sink::Text => write_stderr(&format_text(record)),
+            Sink::Json => write_stderr(&format_json(record, context)),
+            #[cfg(unix)]
+            Sink::Journald(ref socket) => {
+                if socket.send(&journald_entry(record, context)).is_err() {
+                    write_stderr(&format_text(record));
+                }
+            }
+        })
+    }
+
+    fn flush(&self) {
+        let _ = io::stderr().flush();
+    }
+}
+
+fn write_stderr(line: &str) {
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+    let _ = writeln!(stderr, "{}", line);
+}
+
+fn timestamp() -> String {
+    Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn format_text(record: &Record) -> String {
+    let level = match record.level() {
+        Level::Trace => "TRCE",
+        Level::Debug => "DBUG",
+        Level::Info => "INFO",
+        Level::Warn => "WARN",
+        Level::Error => "ERR!",
+    };
+
+    if record.level() >= Level::Debug {
+        format!(
+            "<{}>{} [{}] - [{}] {}",
+            syslog_level(record.level()),
+            timestamp(),
+            level,
+            record.target(),
+            record.args()
+        )
+    } else {
+        format!(
+            "<{}>{} [{}] - {}",
+            syslog_level(record.level()),
+            timestamp(),
+            level,
+            record.args()
+        )
+    }
+}
+
+fn format_json(record: &Record, context: Option<&RequestContext>) -> String {
+    let mut entry = json!({
+        "timestamp": timestamp(),
+        "level": record.level().to_string(),
+        "target": record.target(),
+        "module": record.module_path(),
+        "pid": process::id(),
+        "message": record.args().to_string(),
+    });
+    if let Some(context) = context {
+        entry["request_id"] = json!(context.request_id());
+        entry["service"] = json!(context.service());
+        entry["route"] = json!(context.route());
+        entry["client_pid"] = json!(context.client_pid());
+    }
+    entry.to_string()
+}
+
+#[cfg(unix)]
+fn journald_entry(record: &Record, context: Option<&RequestContext>) -> Vec<u8> {
+    let mut entry = vec![];
+    add_journald_field(&mut entry, "MESSAGE", &record.args().to_string());
+    add_journald_field(
+        &mut entry,
+        "PRIORITY",
+        &syslog_level(record.level()).to_string(),
+    );
+    add_journald_field(&mut entry, "SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER);
+    add_journald_field(&mut entry, "TARGET", record.target());
+    if let Some(module) = record.module_path() {
+        add_journald_field(&mut entry, "CODE_MODULE", module);
+    }
+    if let Some(file) = record.file() {
+        add_journald_field(&mut entry, "CODE_FILE", file);
+    }
+    if let Some(line) = record.line() {
+        add_journald_field(&mut entry, "CODE_LINE", &line.to_string());
+    }
+    if let Some(context) = context {
+        add_journald_field(&mut entry, "REQUEST_ID", context.request_id());
+        add_journald_field(&mut entry, "SERVICE", context.service());
+        add_journald_field(&mut entry, "ROUTE", context.route());
+        if let Some(client_pid) = context.client_pid() {
+            add_journald_field(&mut entry, "CLIENT_PID", client_pid);
+        }
+    }
+    entry
+}
+
+/// Appends a field in the journald native protocol. Values that span several
+/// lines are sent with their length rather than terminated by a newline.
+#[cfg(unix)]
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+fn add_journald_field(entry: &mut Vec<u8>, name: &str, value: &str) {
+    entry.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        let len = value.len() as u64;
+        for i in 0..8 {
+            entry.push((len >> (i * 8)) as u8);
+        }
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
 fn syslog_level(level: Level) -> i8 {
     match level {
         Level::Error => 3,
@@ -78,3 +266,62 @@ fn syslog_level(level: Level) -> i8 {
 pub fn log_error(error: &Error) {
     log_failure(Level::Error, error);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::Value;
+
+    fn record<F>(f: F)
+    where
+        F: FnOnce(&Record),
+    {
+        f(&Record::builder()
+            .args(format_args!("multi\nline"))
+            .level(Level::Warn)
+            .target("iotedged::test")
+            .module_path(Some("iotedged::logging::tests"))
+            .file(Some("src/logging.rs"))
+            .line(Some(42))
+            .build())
+    }
+
+    #[test]
+    fn text_format_is_syslog_prefixed() {
+        record(|record| {
+            let line = format_text(record);
+            assert!(line.starts_with("<4>"));
+            assert!(line.ends_with(" [WARN] - multi\nline"));
+        });
+    }
+
+    #[test]
+    fn json_format_has_fields() {
+        record(|record| {
+            let entry: Value = serde_json::from_str(&format_json(record, None)).unwrap();
+            assert_eq!("WARN", entry["level"]);
+            assert_eq!("iotedged::test", entry["target"]);
+            assert_eq!("iotedged::logging::tests", entry["module"]);
+            assert_eq!(json!(process::id()), entry["pid"]);
+            assert_eq!("multi\nline", entry["message"]);
+            assert_eq!(Value::Null, entry["request_id"]);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn journald_entry_uses_native_protocol() {
+        record(|record| {
+            let entry = journald_entry(record, None);
+            let mut expected = b"MESSAGE\n\x0a\0\0\0\0\0\0\0multi\nline\n".to_vec();
+            expected.extend_from_slice(b"PRIORITY=4\n");
+            expected.extend_from_slice(b"SYSLOG_IDENTIFIER=iotedged\n");
+            expected.extend_from_slice(b"TARGET=iotedged::test\n");
+            expected.extend_from_slice(b"CODE_MODULE=iotedged::logging::tests\n");
+            expected.extend_from_slice(b"CODE_FILE=src/logging.rs\n");
+            expected.extend_from_slice(b"CODE_LINE=42\n");
+            assert_eq!(expected, entry);
+        });
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Tells systemd how the daemon is doing. Nothing is sent when the daemon
+//! wasn't started by systemd, and failing to notify it is only logged.
+
+#[cfg(not(target_os = "linux"))]
+use tokio::runtime::TaskExecutor;
+
+#[cfg(target_os = "linux")]
+pub use self::linux::{extend_start_timeout, ready, start_watchdog, status, stopping};
+
+#[cfg(not(target_os = "linux"))]
+pub fn status(_status: &str) {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn ready() {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn stopping() {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn start_watchdog(_executor: TaskExecutor) {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn extend_start_timeout() {}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use futures::future;
+    use log::Level;
+    use tokio::executor::Executor;
+    use tokio::runtime::TaskExecutor;
+
+    use edgelet_utils::log_failure;
+    use systemd::{self, READY, STOPPING, WATCHDOG};
+
+    /// How often the start timeout is extended while the daemon starts up.
+    const EXTEND_START_TIMEOUT_INTERVAL_SECS: u64 = 30;
+
+    /// Set once the daemon is ready or stopping, which ends the start timeout
+    /// extensions.
+    static STARTED: AtomicBool = AtomicBool::new(false);
+
+    fn notify(state: &str) {
+        if let Err(err) = systemd::notify(state) {
+            log_failure(Level::Warn, &err);
+        }
+    }
+
+    /// Sets the status that `systemctl status iotedge` shows.
+    pub fn status(status: &str) {
+        if let Err(err) = systemd::notify_status(status) {
+            log_failure(Level::Warn, &err);
+        }
+    }
+
+    /// Tells systemd that the device is provisioned and the management and
+    /// workload APIs are serving requests.
+    pub fn ready() {
+        STARTED.store(true, Ordering::SeqCst);
+        notify(&format!("{}\nSTATUS=Running", READY));
+    }
+
+    pub fn stopping() {
+        STARTED.store(true, Ordering::SeqCst);
+        notify(&format!("{}\nSTATUS=Shutting down", STOPPING));
+    }
+
+    /// Keeps extending the start timeout of the service until the daemon is
+    /// ready or stopping.
+    ///
+    /// Provisioning retries DPS and a reconfiguration removes and recreates
+    /// every module, which can take longer than `TimeoutStartSec`. Without
+    /// the extensions systemd would kill the daemon and restart it, only for
+    /// it to start provisioning again.
+    pub fn extend_start_timeout() {
+        let interval = Duration::from_secs(EXTEND_START_TIMEOUT_INTERVAL_SECS);
+        let extender = thread::Builder::new()
+            .name("start-timeout".to_string())
+            .spawn(move || {
+                while !STARTED.load(Ordering::SeqCst) {
+                    match systemd::notify_extend_timeout(interval * 2) {
+                        Ok(true) => thread::sleep(interval),
+                        Ok(false) => return,
+                        Err(err) => {
+                            log_failure(Level::Warn, &err);
+                            return;
+                        }
+                    }
+                }
+            });
+        if let Err(err) = extender {
+            warn!("Could not extend the systemd start timeout: {}", err);
+        }
+    }
+
+    /// Pings the systemd watchdog, if it is enabled, for as long as the tokio
+    /// runtime runs tasks.
+    ///
+    /// Before every ping a task is spawned on the runtime, and the ping is
+    /// only sent once that task has run. A wedged runtime therefore stops the
+    /// pings and lets systemd restart the daemon. The thread ends when the
+    /// runtime shuts down.
+    pub fn start_watchdog(mut executor: TaskExecutor) {
+        let interval = match systemd::watchdog_interval() {
+            Ok(Some(interval)) => interval / 2,
+            Ok(None) => return,
+            Err(err) => {
+                log_failure(Level::Warn, &err);
+                return;
+            }
+        };
+        info!(
+            "Notifying the systemd watchdog every {} ms",
+            interval.as_secs() * 1000 + u64::from(interval.subsec_millis())
+        );
+
+        let watchdog = thread::Builder::new()
+            .name("watchdog".to_string())
+            .spawn(move || loop {
+                let start = Instant::now();
+                let (tx, rx) = mpsc::channel();
+                let probe = future::lazy(move || tx.send(()).map_err(|_| ()));
+                if Executor::spawn(&mut executor, Box::new(probe)).is_err() {
+                    debug!("Runtime shut down, stopping the systemd watchdog");
+                    return;
+                }
+
+                match rx.recv_timeout(interval) {
+                    Ok(()) => {
+                        notify(WATCHDOG);
+                        if let Some(rest) = interval.checked_sub(start.elapsed()) {
+                            thread::sleep(rest);
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        warn!("The runtime did not run a task in time, not notifying the systemd watchdog");
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        debug!("Runtime shut down, stopping the systemd watchdog");
+                        return;
+                    }
+                }
+            });
+        if let Err(err) = watchdog {
+            warn!("Could not start the systemd watchdog: {}", err);
+        }
+    }
+}
@@ -7,9 +7,10 @@ use hyper::{Method, StatusCode};
 
 use edgelet_http::client::{Client, ClientImpl, TokenSource};
 use edgelet_http::error::ErrorKind as HttpErrorKind;
+use edgelet_http::Error as HttpError;
 use edgelet_utils::ensure_not_empty_with_context;
 use error::{Error, ErrorKind, ModuleOperationReason};
-use model::{AuthMechanism, Module};
+use model::{AuthMechanism, Module, Twin};
 
 pub struct DeviceClient<C, T> {
     client: Client<C, T>,
@@ -185,6 +186,50 @@ where
             Either::A(res)
         }
     }
+
+    pub fn get_twin(&self) -> impl Future<Item = Twin, Error = Error> {
+        self.twin(format!("/twins/{}", self.device_id), self.device_id.clone())
+    }
+
+    pub fn get_module_twin(&self, module_id: &str) -> impl Future<Item = Twin, Error = Error> {
+        if module_id.trim().is_empty() {
+            Either::B(future::err(Error::from(ErrorKind::GetTwinWithReason(
+                module_id.to_string(),
+                ModuleOperationReason::EmptyModuleId,
+            ))))
+        } else {
+            Either::A(self.twin(
+                format!("/twins/{}/modules/{}", self.device_id, module_id),
+                module_id.to_string(),
+            ))
+        }
+    }
+
+    fn twin(&self, path: String, id: String) -> impl Future<Item = Twin, Error = Error> {
+        self.client
+            .request::<(), Twin>(Method::GET, &path, None, None, false)
+            .then(move |twin: Result<Option<Twin>, HttpError>| match twin {
+                Ok(Some(twin)) => Ok(twin),
+
+                Ok(None) => Err(Error::from(ErrorKind::GetTwinWithReason(
+                    id,
+                    ModuleOperationReason::EmptyResponse,
+                ))),
+
+                Err(err) => Err({
+                    if let HttpErrorKind::HttpWithErrorResponse(StatusCode::NOT_FOUND, _) =
+                        err.kind()
+                    {
+                        Error::from(ErrorKind::GetTwinWithReason(
+                            id,
+                            ModuleOperationReason::ModuleNotFound,
+                        ))
+                    } else {
+                        Error::from(err.context(ErrorKind::GetTwin(id)))
+                    }
+                }),
+            })
+    }
 }
 
 impl<C, T> Clone for DeviceClient<C, T>
@@ -25,6 +25,12 @@ pub enum ErrorKind {
     #[fail(display = "Could not get module {}: {}", _0, _1)]
     GetModuleWithReason(String, ModuleOperationReason),
 
+    #[fail(display = "Could not get twin {}", _0)]
+    GetTwin(String),
+
+    #[fail(display = "Could not get twin {}: {}", _0, _1)]
+    GetTwinWithReason(String, ModuleOperationReason),
+
     #[fail(display = "IoT Hub service error: [{}] {}", _0, _1)]
     HubService(StatusCode, String),
 
@@ -37,6 +43,15 @@ pub enum ErrorKind {
     #[fail(display = "Could not list modules: {}", _0)]
     ListModulesWithReason(ModuleOperationReason),
 
+    #[fail(display = "Could not report properties of {}", _0)]
+    ReportProperties(String),
+
+    #[fail(
+        display = "Could not report properties of {}: IoT Hub responded with status {}",
+        _0, _1
+    )]
+    ReportPropertiesRejected(String, u16),
+
     #[fail(display = "Could not upsert module {}", _0)]
     UpsertModule(String),
 
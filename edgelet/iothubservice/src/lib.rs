@@ -14,11 +14,12 @@ extern crate chrono;
 extern crate failure;
 extern crate futures;
 extern crate hyper;
+extern crate native_tls;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
-#[cfg(test)]
 extern crate tokio;
+extern crate tokio_tls;
 #[cfg(test)]
 extern crate typed_headers;
 #[cfg(test)]
@@ -30,7 +31,9 @@ extern crate edgelet_utils;
 mod device;
 pub mod error;
 mod model;
+mod mqtt;
 
 pub use device::DeviceClient;
 pub use error::{Error, ErrorKind, ModuleOperationReason};
 pub use model::{AuthMechanism, AuthType, Module, Properties, SymmetricKey, Twin, X509Thumbprint};
+pub use mqtt::{Connector, TlsConnector, Transport, TwinReporter};
@@ -12,7 +12,7 @@ pub enum AuthType {
     X509,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Twin {
     device_id: String,
@@ -85,19 +85,39 @@ impl Twin {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
 pub struct Properties {
+    #[serde(default, skip_serializing_if = "Value::is_null")]
     desired: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    reported: Value,
 }
 
 impl Properties {
     pub fn new(desired: Value) -> Properties {
-        Properties { desired }
+        Properties {
+            desired,
+            reported: Value::Null,
+        }
+    }
+
+    pub fn with_desired(mut self, desired: Value) -> Self {
+        self.desired = desired;
+        self
     }
 
     pub fn desired(&self) -> &Value {
         &self.desired
     }
+
+    pub fn with_reported(mut self, reported: Value) -> Self {
+        self.reported = reported;
+        self
+    }
+
+    pub fn reported(&self) -> &Value {
+        &self.reported
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -0,0 +1,435 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Reports twin properties over the MQTT twin channel of IoT Hub. The service
+//! API can only change tags and desired properties; reported properties can
+//! only be sent by the device or module that owns the twin.
+
+use std::io;
+use std::net::ToSocketAddrs;
+use std::str;
+use std::sync::Arc;
+use std::time::Duration;
+
+use failure::Fail;
+use futures::future::{self, Either, Loop};
+use futures::Future;
+use native_tls;
+use serde_json::{self, Value};
+use tokio::io::{read_exact, write_all, AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::timer::Timeout;
+use tokio_tls;
+
+use error::{Error, ErrorKind};
+
+const API_VERSION: &str = "2018-06-30";
+const MQTTS_PORT: u16 = 8883;
+const RESPONSE_TIMEOUT_SECS: u64 = 60;
+
+const REQUEST_ID: &str = "1";
+const RESPONSE_TOPIC_FILTER: &str = "$iothub/twin/res/#";
+const RESPONSE_TOPIC_PREFIX: &str = "$iothub/twin/res/";
+const REPORTED_TOPIC: &str = "$iothub/twin/PATCH/properties/reported/";
+
+// Fixed header bytes of the MQTT 3.1.1 packets that are used here
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const DISCONNECT: u8 = 0xe0;
+
+// Protocol level 4 (MQTT 3.1.1); username, password and clean session; a keep
+// alive of 60 seconds
+const PROTOCOL_LEVEL: u8 = 4;
+const CONNECT_FLAGS: u8 = 0xc2;
+const KEEP_ALIVE: [u8; 2] = [0, 60];
+
+/// A connection to the MQTT endpoint of IoT Hub.
+pub trait Transport: AsyncRead + AsyncWrite + Send {}
+
+impl<T> Transport for T where T: AsyncRead + AsyncWrite + Send {}
+
+pub trait Connector: Send + Sync {
+    fn connect(&self) -> Box<Future<Item = Box<Transport>, Error = io::Error> + Send>;
+}
+
+/// Connects to the MQTT endpoint of an IoT Hub, which only accepts TLS.
+pub struct TlsConnector {
+    host: String,
+    port: u16,
+}
+
+impl TlsConnector {
+    pub fn new(host: String) -> Self {
+        TlsConnector {
+            host,
+            port: MQTTS_PORT,
+        }
+    }
+}
+
+impl Connector for TlsConnector {
+    fn connect(&self) -> Box<Future<Item = Box<Transport>, Error = io::Error> + Send> {
+        let host = self.host.clone();
+        let connector = native_tls::TlsConnector::new()
+            .map(tokio_tls::TlsConnector::from)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err));
+        let addr = (host.as_str(), self.port)
+            .to_socket_addrs()
+            .and_then(|mut addrs| {
+                addrs.next().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("Could not resolve {}", host),
+                    )
+                })
+            });
+
+        match (connector, addr) {
+            (Ok(connector), Ok(addr)) => Box::new(
+                TcpStream::connect(&addr)
+                    .and_then(move |stream| {
+                        connector
+                            .connect(&host, stream)
+                            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+                    })
+                    .map(|stream| Box::new(stream) as Box<Transport>),
+            ),
+            (Err(err), _) | (_, Err(err)) => Box::new(future::err(err)),
+        }
+    }
+}
+
+/// Reports properties to the twin of a device or of one of its modules.
+/// Each report uses its own short-lived connection.
+#[derive(Clone)]
+pub struct TwinReporter {
+    connector: Arc<Connector>,
+    hub_name: String,
+    device_id: String,
+}
+
+impl TwinReporter {
+    pub fn new<C>(connector: C, hub_name: String, device_id: String) -> Self
+    where
+        C: 'static + Connector,
+    {
+        TwinReporter {
+            connector: Arc::new(connector),
+            hub_name,
+            device_id,
+        }
+    }
+
+    /// Patches the reported properties of the device twin, or of the twin of
+    /// `module_id`. `sas_token` must have been signed with the key of that
+    /// device or module.
+    pub fn report(
+        &self,
+        module_id: Option<&str>,
+        sas_token: &str,
+        reported: &Value,
+    ) -> impl Future<Item = (), Error = Error> + Send {
+        let id = module_id.map_or_else(
+            || self.device_id.clone(),
+            |module_id| format!("{}/{}", self.device_id, module_id),
+        );
+        let username = format!("{}/{}/?api-version={}", self.hub_name, id, API_VERSION);
+        let password = format!("SharedAccessSignature {}", sas_token);
+        let topic = format!("{}?$rid={}", REPORTED_TOPIC, REQUEST_ID);
+
+        let packets = serde_json::to_vec(reported)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+            .and_then(|payload| {
+                Ok((
+                    connect_packet(&id, &username, &password)?,
+                    subscribe_packet(RESPONSE_TOPIC_FILTER)?,
+                    publish_packet(&topic, &payload)?,
+                ))
+            });
+        let status = match packets {
+            Ok((connect, subscribe, publish)) => Either::A(
+                self.connector
+                    .connect()
+                    .and_then(move |stream| exchange(stream, connect, subscribe, publish)),
+            ),
+            Err(err) => Either::B(future::err(err)),
+        };
+
+        Timeout::new(status, Duration::from_secs(RESPONSE_TIMEOUT_SECS))
+            .map_err(|err| {
+                if err.is_elapsed() {
+                    io::Error::new(io::ErrorKind::TimedOut, "IoT Hub did not respond in time")
+                } else {
+                    err.into_inner().unwrap_or_else(|| {
+                        io::Error::new(io::ErrorKind::Other, "The response timer failed")
+                    })
+                }
+            })
+            .then(move |status| match status {
+                Ok(status) if status < 300 => Ok(()),
+                Ok(status) => Err(Error::from(ErrorKind::ReportPropertiesRejected(id, status))),
+                Err(err) => Err(Error::from(err.context(ErrorKind::ReportProperties(id)))),
+            })
+    }
+}
+
+/// Connects, subscribes to twin responses, publishes the patch and waits for
+/// the status IoT Hub responds with.
+fn exchange<S>(
+    stream: S,
+    connect: Vec<u8>,
+    subscribe: Vec<u8>,
+    publish: Vec<u8>,
+) -> impl Future<Item = u16, Error = io::Error> + Send
+where
+    S: AsyncRead + AsyncWrite + Send,
+{
+    write_all(stream, connect)
+        .and_then(|(stream, _)| read_packet(stream))
+        .and_then(|(stream, connack)| {
+            if connack.header != CONNACK || connack.body.len() != 2 {
+                Err(protocol_error("Expected CONNACK"))
+            } else if connack.body[1] != 0 {
+                Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!(
+                        "IoT Hub refused the connection with return code {}",
+                        connack.body[1]
+                    ),
+                ))
+            } else {
+                Ok(stream)
+            }
+        })
+        .and_then(move |stream| write_all(stream, subscribe))
+        .and_then(|(stream, _)| {
+            read_until(stream, |packet| {
+                if packet.header == SUBACK {
+                    packet.body.get(2).cloned()
+                } else {
+                    None
+                }
+            })
+        })
+        .and_then(|(stream, granted)| {
+            if granted == 0x80 {
+                Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "IoT Hub refused the subscription to twin responses",
+                ))
+            } else {
+                Ok(stream)
+            }
+        })
+        .and_then(move |stream| write_all(stream, publish))
+        .and_then(|(stream, _)| read_until(stream, response_status))
+        .and_then(|(stream, status)| {
+            // the response is in, so a failed disconnect doesn't matter
+            write_all(stream, vec![DISCONNECT, 0]).then(move |_| Ok(status))
+        })
+}
+
+struct Packet {
+    header: u8,
+    body: Vec<u8>,
+}
+
+fn read_packet<S>(stream: S) -> impl Future<Item = (S, Packet), Error = io::Error> + Send
+where
+    S: AsyncRead + Send,
+{
+    read_exact(stream, [0_u8; 1])
+        .and_then(|(stream, header)| {
+            read_remaining_length(stream).map(move |(stream, length)| (stream, header[0], length))
+        })
+        .and_then(|(stream, header, length)| {
+            read_exact(stream, vec![0_u8; length])
+                .map(move |(stream, body)| (stream, Packet { header, body }))
+        })
+}
+
+fn read_remaining_length<S>(stream: S) -> impl Future<Item = (S, usize), Error = io::Error> + Send
+where
+    S: AsyncRead + Send,
+{
+    future::loop_fn((stream, 0_usize, 0_u32), |(stream, length, shift)| {
+        read_exact(stream, [0_u8; 1]).and_then(move |(stream, byte)| {
+            let length = length | (usize::from(byte[0] & 0x7f) << shift);
+            if byte[0] & 0x80 == 0 {
+                Ok(Loop::Break((stream, length)))
+            } else if shift >= 21 {
+                Err(protocol_error("Malformed remaining length"))
+            } else {
+                Ok(Loop::Continue((stream, length, shift + 7)))
+            }
+        })
+    })
+}
+
+/// Reads packets until `select` picks one, skipping anything else IoT Hub
+/// sends in the meantime.
+fn read_until<S, T, F>(stream: S, select: F) -> impl Future<Item = (S, T), Error = io::Error> + Send
+where
+    S: AsyncRead + Send,
+    T: Send,
+    F: Fn(&Packet) -> Option<T> + Send,
+{
+    future::loop_fn((stream, select), |(stream, select)| {
+        read_packet(stream).map(move |(stream, packet)| match select(&packet) {
+            Some(selected) => Loop::Break((stream, selected)),
+            None => Loop::Continue((stream, select)),
+        })
+    })
+}
+
+/// The status of the twin response to this request, e.g. from the topic
+/// `$iothub/twin/res/204/?$rid=1&$version=5`.
+fn response_status(packet: &Packet) -> Option<u16> {
+    if packet.header & 0xf0 != PUBLISH {
+        return None;
+    }
+
+    let topic_length = packet
+        .body
+        .get(..2)
+        .map(|length| usize::from(length[0]) << 8 | usize::from(length[1]))?;
+    let topic = packet
+        .body
+        .get(2..2 + topic_length)
+        .and_then(|topic| str::from_utf8(topic).ok())?;
+    if !topic.starts_with(RESPONSE_TOPIC_PREFIX) {
+        return None;
+    }
+
+    let mut parts = topic[RESPONSE_TOPIC_PREFIX.len()..].splitn(2, "/?");
+    let status = parts.next()?.parse().ok()?;
+    let request_id = format!("$rid={}", REQUEST_ID);
+    if parts.next()?.split('&').any(|param| param == request_id) {
+        Some(status)
+    } else {
+        None
+    }
+}
+
+fn connect_packet(client_id: &str, username: &str, password: &str) -> io::Result<Vec<u8>> {
+    let mut body = vec![];
+    put_string(&mut body, "MQTT")?;
+    body.push(PROTOCOL_LEVEL);
+    body.push(CONNECT_FLAGS);
+    body.extend_from_slice(&KEEP_ALIVE);
+    put_string(&mut body, client_id)?;
+    put_string(&mut body, username)?;
+    put_string(&mut body, password)?;
+    packet(CONNECT, &body)
+}
+
+fn subscribe_packet(topic_filter: &str) -> io::Result<Vec<u8>> {
+    // packet identifier 1, at most once delivery
+    let mut body = vec![0, 1];
+    put_string(&mut body, topic_filter)?;
+    body.push(0);
+    packet(SUBSCRIBE, &body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let mut body = vec![];
+    put_string(&mut body, topic)?;
+    body.extend_from_slice(payload);
+    packet(PUBLISH, &body)
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+fn put_string(buf: &mut Vec<u8>, value: &str) -> io::Result<()> {
+    if value.len() > usize::from(u16::max_value()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "String is too long for an MQTT packet",
+        ));
+    }
+
+    buf.push((value.len() >> 8) as u8);
+    buf.push(value.len() as u8);
+    buf.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+fn packet(header: u8, body: &[u8]) -> io::Result<Vec<u8>> {
+    // the remaining length takes at most 4 bytes of 7 bits each
+    if body.len() >= 1 << 28 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Payload is too large for an MQTT packet",
+        ));
+    }
+
+    let mut packet = vec![header];
+    let mut length = body.len();
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+        if length == 0 {
+            packet.push(byte);
+            break;
+        }
+        packet.push(byte | 0x80);
+    }
+    packet.extend_from_slice(body);
+    Ok(packet)
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remaining_length_is_encoded_seven_bits_at_a_time() {
+        assert_eq!(vec![CONNECT, 0], packet(CONNECT, &[]).unwrap());
+        assert_eq!(&[PUBLISH, 0x7f], &packet(PUBLISH, &[0; 127]).unwrap()[..2]);
+        assert_eq!(
+            &[PUBLISH, 0x80, 0x01],
+            &packet(PUBLISH, &[0; 128]).unwrap()[..3]
+        );
+        assert_eq!(
+            &[PUBLISH, 0xff, 0x7f],
+            &packet(PUBLISH, &[0; 16_383]).unwrap()[..3]
+        );
+    }
+
+    #[test]
+    fn response_status_matches_request_id() {
+        let response = |topic: &str| {
+            let mut body = vec![];
+            put_string(&mut body, topic).unwrap();
+            Packet {
+                header: PUBLISH,
+                body,
+            }
+        };
+
+        assert_eq!(
+            Some(204),
+            response_status(&response("$iothub/twin/res/204/?$rid=1&$version=5"))
+        );
+        assert_eq!(
+            Some(400),
+            response_status(&response("$iothub/twin/res/400/?$rid=1"))
+        );
+        assert_eq!(
+            None,
+            response_status(&response("$iothub/twin/res/204/?$rid=12"))
+        );
+        assert_eq!(
+            None,
+            response_status(&response(
+                "$iothub/twin/PATCH/properties/desired/?$version=3"
+            ))
+        );
+    }
+}
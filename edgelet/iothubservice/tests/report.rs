@@ -0,0 +1,237 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate futures;
+#[macro_use]
+extern crate serde_json;
+extern crate tokio;
+
+extern crate iothubservice;
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream as StdTcpStream};
+use std::thread;
+
+use futures::Future;
+use serde_json::Value;
+use tokio::net::TcpStream;
+
+use iothubservice::{Connector, ErrorKind, Transport, TwinReporter};
+
+struct LocalConnector(SocketAddr);
+
+impl Connector for LocalConnector {
+    fn connect(&self) -> Box<Future<Item = Box<Transport>, Error = io::Error> + Send> {
+        Box::new(TcpStream::connect(&self.0).map(|stream| Box::new(stream) as Box<Transport>))
+    }
+}
+
+/// What the fake hub received from the reporter.
+struct Received {
+    client_id: String,
+    username: String,
+    password: String,
+    topic_filter: String,
+    topic: String,
+    reported: Value,
+}
+
+fn read_packet(stream: &mut StdTcpStream) -> (u8, Vec<u8>) {
+    let mut header = [0_u8; 1];
+    stream.read_exact(&mut header).unwrap();
+
+    let mut length = 0;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0_u8; 1];
+        stream.read_exact(&mut byte).unwrap();
+        length |= usize::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+
+    let mut body = vec![0_u8; length];
+    stream.read_exact(&mut body).unwrap();
+    (header[0], body)
+}
+
+fn take_string(body: &[u8]) -> (String, &[u8]) {
+    let length = usize::from(body[0]) << 8 | usize::from(body[1]);
+    (
+        String::from_utf8(body[2..2 + length].to_vec()).unwrap(),
+        &body[2 + length..],
+    )
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+fn response(topic: &str) -> Vec<u8> {
+    let mut packet = vec![0x30, (topic.len() + 2) as u8, 0, topic.len() as u8];
+    packet.extend_from_slice(topic.as_bytes());
+    packet
+}
+
+/// Runs a hub that accepts one connection, refuses it with `connack_code` if
+/// that isn't 0 and otherwise answers the patch with `status`.
+fn run_hub(connack_code: u8, status: u16) -> (SocketAddr, thread::JoinHandle<Option<Received>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let hub = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let (header, body) = read_packet(&mut stream);
+        assert_eq!(0x10, header);
+        let (protocol, rest) = take_string(&body);
+        assert_eq!("MQTT", protocol);
+        let (client_id, rest) = take_string(&rest[4..]);
+        let (username, rest) = take_string(rest);
+        let (password, _) = take_string(rest);
+
+        stream.write_all(&[0x20, 2, 0, connack_code]).unwrap();
+        if connack_code != 0 {
+            return None;
+        }
+
+        let (header, body) = read_packet(&mut stream);
+        assert_eq!(0x82, header);
+        let (topic_filter, _) = take_string(&body[2..]);
+        stream.write_all(&[0x90, 3, body[0], body[1], 0]).unwrap();
+
+        // twin responses to other requests are ignored by the reporter
+        stream
+            .write_all(&response("$iothub/twin/res/200/?$rid=7"))
+            .unwrap();
+
+        let (header, body) = read_packet(&mut stream);
+        assert_eq!(0x30, header);
+        let (topic, payload) = take_string(&body);
+        let reported = serde_json::from_slice(payload).unwrap();
+        stream
+            .write_all(&response(&format!(
+                "$iothub/twin/res/{}/?$rid=1&$version=5",
+                status
+            )))
+            .unwrap();
+
+        let (header, _) = read_packet(&mut stream);
+        assert_eq!(0xe0, header);
+
+        Some(Received {
+            client_id,
+            username,
+            password,
+            topic_filter,
+            topic,
+            reported,
+        })
+    });
+
+    (addr, hub)
+}
+
+#[test]
+fn report_module_properties() {
+    let (addr, hub) = run_hub(0, 204);
+    let reporter = TwinReporter::new(
+        LocalConnector(addr),
+        "hub.example.com".to_string(),
+        "d1".to_string(),
+    );
+
+    let task = reporter.report(Some("m1"), "sr=token", &json!({ "version": "1.0" }));
+    tokio::runtime::current_thread::Runtime::new()
+        .unwrap()
+        .block_on(task)
+        .unwrap();
+
+    let received = hub.join().unwrap().unwrap();
+    assert_eq!("d1/m1", received.client_id);
+    assert_eq!(
+        "hub.example.com/d1/m1/?api-version=2018-06-30",
+        received.username
+    );
+    assert_eq!("SharedAccessSignature sr=token", received.password);
+    assert_eq!("$iothub/twin/res/#", received.topic_filter);
+    assert_eq!(
+        "$iothub/twin/PATCH/properties/reported/?$rid=1",
+        received.topic
+    );
+    assert_eq!(json!({ "version": "1.0" }), received.reported);
+}
+
+#[test]
+fn report_device_properties() {
+    let (addr, hub) = run_hub(0, 204);
+    let reporter = TwinReporter::new(
+        LocalConnector(addr),
+        "hub.example.com".to_string(),
+        "d1".to_string(),
+    );
+
+    let task = reporter.report(None, "sr=token", &json!({ "state": "ok" }));
+    tokio::runtime::current_thread::Runtime::new()
+        .unwrap()
+        .block_on(task)
+        .unwrap();
+
+    let received = hub.join().unwrap().unwrap();
+    assert_eq!("d1", received.client_id);
+    assert_eq!(
+        "hub.example.com/d1/?api-version=2018-06-30",
+        received.username
+    );
+}
+
+#[test]
+fn rejected_report_fails_with_status() {
+    let (addr, hub) = run_hub(0, 400);
+    let reporter = TwinReporter::new(
+        LocalConnector(addr),
+        "hub.example.com".to_string(),
+        "d1".to_string(),
+    );
+
+    let task = reporter.report(Some("m1"), "sr=token", &json!({ "version": "1.0" }));
+    let err = tokio::runtime::current_thread::Runtime::new()
+        .unwrap()
+        .block_on(task)
+        .unwrap_err();
+
+    assert_eq!(
+        &ErrorKind::ReportPropertiesRejected("d1/m1".to_string(), 400),
+        err.kind()
+    );
+    hub.join().unwrap();
+}
+
+#[test]
+fn refused_connection_fails() {
+    // 5: not authorized
+    let (addr, hub) = run_hub(5, 204);
+    let reporter = TwinReporter::new(
+        LocalConnector(addr),
+        "hub.example.com".to_string(),
+        "d1".to_string(),
+    );
+
+    let task = reporter.report(Some("m1"), "sr=bad", &json!({ "version": "1.0" }));
+    let err = tokio::runtime::current_thread::Runtime::new()
+        .unwrap()
+        .block_on(task)
+        .unwrap_err();
+
+    assert_eq!(
+        &ErrorKind::ReportProperties("d1/m1".to_string()),
+        err.kind()
+    );
+    assert!(hub.join().unwrap().is_none());
+}
@@ -0,0 +1,162 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+#![deny(unused_extern_crates, warnings)]
+// Remove this when clippy stops warning about old-style `allow()`,
+// which can only be silenced by enabling a feature and thus requires nightly
+//
+// Ref: https://github.com/rust-lang-nursery/rust-clippy/issues/3159#issuecomment-420530386
+#![allow(renamed_and_removed_lints)]
+#![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
+
+extern crate chrono;
+extern crate edgelet_http;
+extern crate edgelet_test_utils;
+extern crate futures;
+extern crate hyper;
+#[macro_use]
+extern crate serde_json;
+extern crate tokio;
+extern crate typed_headers;
+extern crate url;
+
+extern crate iothubservice;
+
+use chrono::{DateTime, Utc};
+use futures::future;
+use futures::prelude::*;
+use hyper::{Body, Client as HyperClient, Error as HyperError, Method, Request, Response};
+use typed_headers::{mime, ContentType, HeaderMapExt};
+use url::Url;
+
+use edgelet_http::client::{Client, TokenSource};
+use edgelet_test_utils::{get_unused_tcp_port, run_tcp_server};
+use iothubservice::{
+    AuthType, DeviceClient, Error, ErrorKind, ModuleOperationReason, Properties, Twin,
+};
+
+#[derive(Clone)]
+struct NullTokenSource;
+
+impl TokenSource for NullTokenSource {
+    type Error = Error;
+    fn get(&self, _expiry: &DateTime<Utc>) -> Result<String, Error> {
+        Ok("token".to_string())
+    }
+}
+
+fn twin(module_id: Option<&str>) -> Twin {
+    let twin = Twin::new(
+        "d1",
+        3,
+        AuthType::Sas,
+        Properties::new(json!({ "setting": "value" })).with_reported(json!({ "state": "ok" })),
+    );
+    match module_id {
+        Some(module_id) => twin.with_module_id(module_id.to_string()),
+        None => twin,
+    }
+}
+
+fn json_response(body: String) -> Response<Body> {
+    let mut response = Response::new(body.into());
+    response
+        .headers_mut()
+        .typed_insert(&ContentType(mime::APPLICATION_JSON));
+    response
+}
+
+fn twin_handler(
+    req: Request<Body>,
+) -> Box<Future<Item = Response<Body>, Error = HyperError> + Send> {
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/twins/d1") => Box::new(future::ok(json_response(
+            serde_json::to_string(&twin(None)).unwrap(),
+        ))),
+        (&Method::GET, "/twins/d1/modules/m1") => Box::new(future::ok(json_response(
+            serde_json::to_string(&twin(Some("m1"))).unwrap(),
+        ))),
+        _ => Box::new(future::ok(
+            Response::builder().status(404).body(Body::empty()).unwrap(),
+        )),
+    }
+}
+
+fn device_client(
+    port: u16,
+) -> DeviceClient<HyperClient<hyper::client::HttpConnector>, NullTokenSource> {
+    let client = Client::new(
+        HyperClient::new(),
+        Some(NullTokenSource),
+        "2018-06-30".to_string(),
+        Url::parse(&format!("http://localhost:{}", port)).unwrap(),
+    )
+    .unwrap();
+    DeviceClient::new(client, "d1".to_string()).unwrap()
+}
+
+#[test]
+fn get_device_twin() {
+    let port = get_unused_tcp_port();
+    let server =
+        run_tcp_server("127.0.0.1", port, twin_handler).map_err(|err| eprintln!("{}", err));
+
+    let task = device_client(port).get_twin();
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let twin = runtime.block_on(task).unwrap();
+
+    assert_eq!("d1", twin.device_id());
+    assert_eq!(None, twin.module_id());
+    assert_eq!(&json!({ "setting": "value" }), twin.properties().desired());
+    assert_eq!(&json!({ "state": "ok" }), twin.properties().reported());
+}
+
+#[test]
+fn get_module_twin() {
+    let port = get_unused_tcp_port();
+    let server =
+        run_tcp_server("127.0.0.1", port, twin_handler).map_err(|err| eprintln!("{}", err));
+
+    let task = device_client(port).get_module_twin("m1");
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let twin = runtime.block_on(task).unwrap();
+
+    assert_eq!(Some("m1"), twin.module_id());
+    assert_eq!(&3, twin.version());
+}
+
+#[test]
+fn get_module_twin_not_found() {
+    let port = get_unused_tcp_port();
+    let server =
+        run_tcp_server("127.0.0.1", port, twin_handler).map_err(|err| eprintln!("{}", err));
+
+    let task = device_client(port).get_module_twin("m2");
+
+    let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+    runtime.spawn(server);
+    let err = runtime.block_on(task).unwrap_err();
+
+    assert_eq!(
+        &ErrorKind::GetTwinWithReason("m2".to_string(), ModuleOperationReason::ModuleNotFound),
+        err.kind()
+    );
+}
+
+#[test]
+fn get_module_twin_empty_module_id_fails() {
+    let task = device_client(get_unused_tcp_port()).get_module_twin("  ");
+
+    let err = tokio::runtime::current_thread::Runtime::new()
+        .unwrap()
+        .block_on(task)
+        .unwrap_err();
+
+    assert_eq!(
+        &ErrorKind::GetTwinWithReason("  ".to_string(), ModuleOperationReason::EmptyModuleId),
+        err.kind()
+    );
+}
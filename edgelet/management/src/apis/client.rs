@@ -4,9 +4,12 @@ use super::configuration::Configuration;
 use hyper;
 
 pub struct APIClient {
+    encryption_api: Box<::apis::EncryptionApi>,
     identity_api: Box<::apis::IdentityApi>,
+    logging_api: Box<::apis::LoggingApi>,
     module_api: Box<::apis::ModuleApi>,
     system_information_api: Box<::apis::SystemInformationApi>,
+    trust_bundle_api: Box<::apis::TrustBundleApi>,
 }
 
 impl APIClient {
@@ -17,18 +20,29 @@ impl APIClient {
         let configuration = Arc::new(configuration);
 
         APIClient {
+            encryption_api: Box::new(::apis::EncryptionApiClient::new(configuration.clone())),
             identity_api: Box::new(::apis::IdentityApiClient::new(configuration.clone())),
+            logging_api: Box::new(::apis::LoggingApiClient::new(configuration.clone())),
             module_api: Box::new(::apis::ModuleApiClient::new(configuration.clone())),
             system_information_api: Box::new(::apis::SystemInformationApiClient::new(
                 configuration.clone(),
             )),
+            trust_bundle_api: Box::new(::apis::TrustBundleApiClient::new(configuration.clone())),
         }
     }
 
+    pub fn encryption_api(&self) -> &::apis::EncryptionApi {
+        self.encryption_api.as_ref()
+    }
+
     pub fn identity_api(&self) -> &::apis::IdentityApi {
         self.identity_api.as_ref()
     }
 
+    pub fn logging_api(&self) -> &::apis::LoggingApi {
+        self.logging_api.as_ref()
+    }
+
     pub fn module_api(&self) -> &::apis::ModuleApi {
         self.module_api.as_ref()
     }
@@ -36,4 +50,8 @@ impl APIClient {
     pub fn system_information_api(&self) -> &::apis::SystemInformationApi {
         self.system_information_api.as_ref()
     }
+
+    pub fn trust_bundle_api(&self) -> &::apis::TrustBundleApi {
+        self.trust_bundle_api.as_ref()
+    }
 }
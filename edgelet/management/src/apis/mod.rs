@@ -48,12 +48,18 @@ impl<T> From<serde_json::Error> for Error<T> {
     }
 }
 
+mod encryption_api;
+pub use self::encryption_api::{EncryptionApi, EncryptionApiClient};
 mod identity_api;
 pub use self::identity_api::{IdentityApi, IdentityApiClient};
+mod logging_api;
+pub use self::logging_api::{LoggingApi, LoggingApiClient};
 mod module_api;
 pub use self::module_api::{ModuleApi, ModuleApiClient};
 mod system_information_api;
 pub use self::system_information_api::{SystemInformationApi, SystemInformationApiClient};
+mod trust_bundle_api;
+pub use self::trust_bundle_api::{TrustBundleApi, TrustBundleApiClient};
 
 pub mod client;
 pub mod configuration;
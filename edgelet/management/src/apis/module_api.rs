@@ -34,17 +34,17 @@ pub trait ModuleApi: Send + Sync {
         &self,
         api_version: &str,
         module: ::models::ModuleSpec,
-    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>> + Send>;
     fn delete_module(
         &self,
         api_version: &str,
         name: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send>;
     fn get_module(
         &self,
         api_version: &str,
         name: &str,
-    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>> + Send>;
     fn list_modules(
         &self,
         api_version: &str,
@@ -76,7 +76,47 @@ pub trait ModuleApi: Send + Sync {
         api_version: &str,
         name: &str,
         module: ::models::ModuleSpec,
-    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>>>;
+    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>> + Send>;
+    fn list_volumes(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = ::models::VolumeList, Error = Error<serde_json::Value>> + Send>;
+    fn get_volume(
+        &self,
+        api_version: &str,
+        name: &str,
+        volume: &str,
+    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>> + Send>;
+    fn backup_volume(
+        &self,
+        api_version: &str,
+        name: &str,
+        volume: &str,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>> + Send>;
+    fn delete_volume(
+        &self,
+        api_version: &str,
+        name: &str,
+        volume: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send>;
+    fn exec_module(
+        &self,
+        api_version: &str,
+        name: &str,
+        exec: ::models::ExecRequest,
+    ) -> Box<Future<Item = ::models::ExecResponse, Error = Error<serde_json::Value>> + Send>;
+    fn start_exec(
+        &self,
+        api_version: &str,
+        id: &str,
+        tty: bool,
+    ) -> Box<Future<Item = hyper::upgrade::Upgraded, Error = Error<serde_json::Value>> + Send>;
+    fn inspect_exec(
+        &self,
+        api_version: &str,
+        id: &str,
+    ) -> Box<Future<Item = ::models::ExecStatus, Error = Error<serde_json::Value>> + Send>;
 }
 
 impl<C> ModuleApi for ModuleApiClient<C>
@@ -89,7 +129,7 @@ where
         &self,
         api_version: &str,
         module: ::models::ModuleSpec,
-    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::POST;
@@ -150,7 +190,7 @@ where
         &self,
         api_version: &str,
         name: &str,
-    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::DELETE;
@@ -201,7 +241,7 @@ where
         &self,
         api_version: &str,
         name: &str,
-    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::GET;
@@ -512,7 +552,7 @@ where
         api_version: &str,
         name: &str,
         module: ::models::ModuleSpec,
-    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>>> {
+    ) -> Box<Future<Item = ::models::ModuleDetails, Error = Error<serde_json::Value>> + Send> {
         let configuration: &configuration::Configuration<C> = self.configuration.borrow();
 
         let method = hyper::Method::PUT;
@@ -568,4 +608,396 @@ where
                 }),
         )
     }
+
+    fn list_volumes(
+        &self,
+        api_version: &str,
+        name: &str,
+    ) -> Box<Future<Item = ::models::VolumeList, Error = Error<serde_json::Value>> + Send> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::GET;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/volumes?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|body| {
+                    let parsed: Result<::models::VolumeList, _> = serde_json::from_slice(&body);
+                    parsed.map_err(Error::from)
+                }),
+        )
+    }
+
+    fn get_volume(
+        &self,
+        api_version: &str,
+        name: &str,
+        volume: &str,
+    ) -> Box<Future<Item = ::models::Volume, Error = Error<serde_json::Value>> + Send> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::GET;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!(
+            "/modules/{name}/volumes/{volume}?{}",
+            query,
+            name = name,
+            volume = volume
+        );
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|body| {
+                    let parsed: Result<::models::Volume, _> = serde_json::from_slice(&body);
+                    parsed.map_err(Error::from)
+                }),
+        )
+    }
+
+    fn backup_volume(
+        &self,
+        api_version: &str,
+        name: &str,
+        volume: &str,
+    ) -> Box<Future<Item = hyper::Body, Error = Error<serde_json::Value>> + Send> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::GET;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!(
+            "/modules/{name}/volumes/{volume}/backup?{}",
+            query,
+            name = name,
+            volume = volume
+        );
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        let b: &[u8] = &[];
+                        Err(Error::from((status, b)))
+                    }
+                }),
+        )
+    }
+
+    fn delete_volume(
+        &self,
+        api_version: &str,
+        name: &str,
+        volume: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>> + Send> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::DELETE;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!(
+            "/modules/{name}/volumes/{volume}?{}",
+            query,
+            name = name,
+            volume = volume
+        );
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|_| futures::future::ok(())),
+        )
+    }
+
+    fn exec_module(
+        &self,
+        api_version: &str,
+        name: &str,
+        exec: ::models::ExecRequest,
+    ) -> Box<Future<Item = ::models::ExecResponse, Error = Error<serde_json::Value>> + Send> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::POST;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/modules/{name}/exec?{}", query, name = name);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let serialized = serde_json::to_string(&exec).unwrap();
+        let serialized_len = serialized.len();
+
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let mut req = req
+            .body(hyper::Body::from(serialized))
+            .expect("could not build hyper::Request");
+        req.headers_mut()
+            .typed_insert(&typed_headers::ContentType(mime::APPLICATION_JSON));
+        req.headers_mut()
+            .typed_insert(&typed_headers::ContentLength(serialized_len as u64));
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|body| {
+                    let parsed: Result<::models::ExecResponse, _> = serde_json::from_slice(&body);
+                    parsed.map_err(Error::from)
+                }),
+        )
+    }
+
+    fn start_exec(
+        &self,
+        api_version: &str,
+        id: &str,
+        tty: bool,
+    ) -> Box<Future<Item = hyper::upgrade::Upgraded, Error = Error<serde_json::Value>> + Send> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::POST;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .append_pair("tty", &tty.to_string())
+            .finish();
+        let uri_str = format!("/exec/{id}/start?{}", query, id = id);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        req.header(http::header::CONNECTION, "Upgrade")
+            .header(http::header::UPGRADE, "tcp");
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    if status == http::StatusCode::SWITCHING_PROTOCOLS {
+                        futures::future::Either::A(body.on_upgrade().map_err(Error::from))
+                    } else {
+                        futures::future::Either::B(
+                            body.concat2()
+                                .map_err(Error::from)
+                                .and_then(move |body| Err(Error::from((status, &*body)))),
+                        )
+                    }
+                }),
+        )
+    }
+
+    fn inspect_exec(
+        &self,
+        api_version: &str,
+        id: &str,
+    ) -> Box<Future<Item = ::models::ExecStatus, Error = Error<serde_json::Value>> + Send> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::GET;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!("/exec/{id}?{}", query, id = id);
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|body| {
+                    let parsed: Result<::models::ExecStatus, _> = serde_json::from_slice(&body);
+                    parsed.map_err(Error::from)
+                }),
+        )
+    }
 }
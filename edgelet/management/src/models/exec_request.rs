@@ -0,0 +1,103 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExecRequest {
+    /// The command to run and its arguments.
+    #[serde(rename = "cmd")]
+    cmd: Vec<String>,
+    /// The user the command runs as.
+    #[serde(rename = "user", skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    /// Whether to attach to the stdin of the command.
+    #[serde(rename = "attachStdin", skip_serializing_if = "Option::is_none")]
+    attach_stdin: Option<bool>,
+    /// Whether to allocate a tty for the command.
+    #[serde(rename = "tty", skip_serializing_if = "Option::is_none")]
+    tty: Option<bool>,
+}
+
+impl ExecRequest {
+    pub fn new(cmd: Vec<String>) -> Self {
+        ExecRequest {
+            cmd,
+            user: None,
+            attach_stdin: None,
+            tty: None,
+        }
+    }
+
+    pub fn set_cmd(&mut self, cmd: Vec<String>) {
+        self.cmd = cmd;
+    }
+
+    pub fn with_cmd(mut self, cmd: Vec<String>) -> Self {
+        self.cmd = cmd;
+        self
+    }
+
+    pub fn cmd(&self) -> &[String] {
+        &self.cmd
+    }
+
+    pub fn set_user(&mut self, user: String) {
+        self.user = Some(user);
+    }
+
+    pub fn with_user(mut self, user: String) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_user(&mut self) {
+        self.user = None;
+    }
+
+    pub fn set_attach_stdin(&mut self, attach_stdin: bool) {
+        self.attach_stdin = Some(attach_stdin);
+    }
+
+    pub fn with_attach_stdin(mut self, attach_stdin: bool) -> Self {
+        self.attach_stdin = Some(attach_stdin);
+        self
+    }
+
+    pub fn attach_stdin(&self) -> Option<&bool> {
+        self.attach_stdin.as_ref()
+    }
+
+    pub fn reset_attach_stdin(&mut self) {
+        self.attach_stdin = None;
+    }
+
+    pub fn set_tty(&mut self, tty: bool) {
+        self.tty = Some(tty);
+    }
+
+    pub fn with_tty(mut self, tty: bool) -> Self {
+        self.tty = Some(tty);
+        self
+    }
+
+    pub fn tty(&self) -> Option<&bool> {
+        self.tty.as_ref()
+    }
+
+    pub fn reset_tty(&mut self) {
+        self.tty = None;
+    }
+}
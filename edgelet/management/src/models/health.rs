@@ -0,0 +1,75 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Health {
+    #[serde(rename = "status")]
+    status: String,
+    #[serde(rename = "failingStreak")]
+    failing_streak: i32,
+    #[serde(rename = "lastOutput", skip_serializing_if = "Option::is_none")]
+    last_output: Option<String>,
+}
+
+impl Health {
+    pub fn new(status: String, failing_streak: i32) -> Self {
+        Health {
+            status,
+            failing_streak,
+            last_output: None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn with_status(mut self, status: String) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn status(&self) -> &String {
+        &self.status
+    }
+
+    pub fn set_failing_streak(&mut self, failing_streak: i32) {
+        self.failing_streak = failing_streak;
+    }
+
+    pub fn with_failing_streak(mut self, failing_streak: i32) -> Self {
+        self.failing_streak = failing_streak;
+        self
+    }
+
+    pub fn failing_streak(&self) -> &i32 {
+        &self.failing_streak
+    }
+
+    pub fn set_last_output(&mut self, last_output: String) {
+        self.last_output = Some(last_output);
+    }
+
+    pub fn with_last_output(mut self, last_output: String) -> Self {
+        self.last_output = Some(last_output);
+        self
+    }
+
+    pub fn last_output(&self) -> Option<&str> {
+        self.last_output.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_last_output(&mut self) {
+        self.last_output = None;
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogLevel {
+    /// One of `off`, `error`, `warn`, `info`, `debug` or `trace`.
+    #[serde(rename = "level")]
+    level: String,
+    /// Seconds after which the configured level applies again. The level is kept until it is reset if this is left out.
+    #[serde(rename = "revertAfter", skip_serializing_if = "Option::is_none")]
+    revert_after: Option<i64>,
+}
+
+impl LogLevel {
+    pub fn new(level: String) -> Self {
+        LogLevel {
+            level,
+            revert_after: None,
+        }
+    }
+
+    pub fn set_level(&mut self, level: String) {
+        self.level = level;
+    }
+
+    pub fn with_level(mut self, level: String) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn level(&self) -> &str {
+        &self.level
+    }
+
+    pub fn set_revert_after(&mut self, revert_after: i64) {
+        self.revert_after = Some(revert_after);
+    }
+
+    pub fn with_revert_after(mut self, revert_after: i64) -> Self {
+        self.revert_after = Some(revert_after);
+        self
+    }
+
+    pub fn revert_after(&self) -> Option<i64> {
+        self.revert_after
+    }
+
+    pub fn reset_revert_after(&mut self) {
+        self.revert_after = None;
+    }
+}
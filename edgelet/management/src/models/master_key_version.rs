@@ -0,0 +1,97 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MasterKeyVersion {
+    #[serde(rename = "version")]
+    version: i64,
+    #[serde(rename = "createdAt")]
+    created_at: String,
+    /// When the version was replaced by a newer one. Left out for the current version.
+    #[serde(rename = "retiredAt", skip_serializing_if = "Option::is_none")]
+    retired_at: Option<String>,
+    /// When ciphertexts of the version can no longer be decrypted. Left out for the current version.
+    #[serde(rename = "expiresAt", skip_serializing_if = "Option::is_none")]
+    expires_at: Option<String>,
+}
+
+impl MasterKeyVersion {
+    pub fn new(version: i64, created_at: String) -> Self {
+        MasterKeyVersion {
+            version,
+            created_at,
+            retired_at: None,
+            expires_at: None,
+        }
+    }
+
+    pub fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    pub fn with_version(mut self, version: i64) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    pub fn set_created_at(&mut self, created_at: String) {
+        self.created_at = created_at;
+    }
+
+    pub fn with_created_at(mut self, created_at: String) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    pub fn created_at(&self) -> &str {
+        &self.created_at
+    }
+
+    pub fn set_retired_at(&mut self, retired_at: String) {
+        self.retired_at = Some(retired_at);
+    }
+
+    pub fn with_retired_at(mut self, retired_at: String) -> Self {
+        self.retired_at = Some(retired_at);
+        self
+    }
+
+    pub fn retired_at(&self) -> Option<&str> {
+        self.retired_at.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_retired_at(&mut self) {
+        self.retired_at = None;
+    }
+
+    pub fn set_expires_at(&mut self, expires_at: String) {
+        self.expires_at = Some(expires_at);
+    }
+
+    pub fn with_expires_at(mut self, expires_at: String) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn expires_at(&self) -> Option<&str> {
+        self.expires_at.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_expires_at(&mut self) {
+        self.expires_at = None;
+    }
+}
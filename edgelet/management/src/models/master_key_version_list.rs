@@ -0,0 +1,37 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MasterKeyVersionList {
+    #[serde(rename = "versions")]
+    versions: Vec<::models::MasterKeyVersion>,
+}
+
+impl MasterKeyVersionList {
+    pub fn new(versions: Vec<::models::MasterKeyVersion>) -> Self {
+        MasterKeyVersionList { versions }
+    }
+
+    pub fn set_versions(&mut self, versions: Vec<::models::MasterKeyVersion>) {
+        self.versions = versions;
+    }
+
+    pub fn with_versions(mut self, versions: Vec<::models::MasterKeyVersion>) -> Self {
+        self.versions = versions;
+        self
+    }
+
+    pub fn versions(&self) -> &[::models::MasterKeyVersion] {
+        &self.versions
+    }
+}
@@ -1,3 +1,5 @@
+mod add_trusted_root_request;
+pub use self::add_trusted_root_request::AddTrustedRootRequest;
 mod config;
 pub use self::config::Config;
 mod env_var;
@@ -6,6 +8,14 @@ mod error_response;
 pub use self::error_response::ErrorResponse;
 mod exit_status;
 pub use self::exit_status::ExitStatus;
+mod exec_request;
+pub use self::exec_request::ExecRequest;
+mod exec_response;
+pub use self::exec_response::ExecResponse;
+mod exec_status;
+pub use self::exec_status::ExecStatus;
+mod health;
+pub use self::health::Health;
 mod identity;
 pub use self::identity::Identity;
 mod identity_list;
@@ -14,18 +24,38 @@ mod identity_spec;
 pub use self::identity_spec::IdentitySpec;
 mod update_identity;
 pub use self::update_identity::UpdateIdentity;
+mod log_level;
+pub use self::log_level::LogLevel;
+mod master_key_version;
+pub use self::master_key_version::MasterKeyVersion;
+mod master_key_version_list;
+pub use self::master_key_version_list::MasterKeyVersionList;
 mod module_details;
 pub use self::module_details::ModuleDetails;
 mod module_list;
 pub use self::module_list::ModuleList;
 mod module_spec;
 pub use self::module_spec::ModuleSpec;
+mod rotate_master_key_request;
+pub use self::rotate_master_key_request::RotateMasterKeyRequest;
 mod runtime_status;
 pub use self::runtime_status::RuntimeStatus;
 mod status;
 pub use self::status::Status;
 mod system_info;
 pub use self::system_info::SystemInfo;
+mod trusted_root;
+pub use self::trusted_root::TrustedRoot;
+mod trusted_root_list;
+pub use self::trusted_root_list::TrustedRootList;
+mod twin;
+pub use self::twin::Twin;
+mod twin_properties;
+pub use self::twin_properties::TwinProperties;
+mod volume;
+pub use self::volume::Volume;
+mod volume_list;
+pub use self::volume_list::VolumeList;
 
 // TODO(farcaller): sort out files
 pub struct File;
@@ -0,0 +1,42 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RotateMasterKeyRequest {
+    /// Seconds during which ciphertexts of the retired version can still be decrypted. Defaults to 30 days.
+    #[serde(rename = "gracePeriod", skip_serializing_if = "Option::is_none")]
+    grace_period: Option<i64>,
+}
+
+impl RotateMasterKeyRequest {
+    pub fn new() -> Self {
+        RotateMasterKeyRequest { grace_period: None }
+    }
+
+    pub fn set_grace_period(&mut self, grace_period: i64) {
+        self.grace_period = Some(grace_period);
+    }
+
+    pub fn with_grace_period(mut self, grace_period: i64) -> Self {
+        self.grace_period = Some(grace_period);
+        self
+    }
+
+    pub fn grace_period(&self) -> Option<i64> {
+        self.grace_period
+    }
+
+    pub fn reset_grace_period(&mut self) {
+        self.grace_period = None;
+    }
+}
@@ -19,6 +19,8 @@ pub struct Status {
     exit_status: Option<::models::ExitStatus>,
     #[serde(rename = "runtimeStatus")]
     runtime_status: ::models::RuntimeStatus,
+    #[serde(rename = "health", skip_serializing_if = "Option::is_none")]
+    health: Option<::models::Health>,
 }
 
 impl Status {
@@ -27,6 +29,7 @@ impl Status {
             start_time: None,
             exit_status: None,
             runtime_status,
+            health: None,
         }
     }
 
@@ -76,4 +79,21 @@ impl Status {
     pub fn runtime_status(&self) -> &::models::RuntimeStatus {
         &self.runtime_status
     }
+
+    pub fn set_health(&mut self, health: ::models::Health) {
+        self.health = Some(health);
+    }
+
+    pub fn with_health(mut self, health: ::models::Health) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn health(&self) -> Option<&::models::Health> {
+        self.health.as_ref()
+    }
+
+    pub fn reset_health(&mut self) {
+        self.health = None;
+    }
 }
@@ -0,0 +1,37 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrustedRootList {
+    #[serde(rename = "roots")]
+    roots: Vec<::models::TrustedRoot>,
+}
+
+impl TrustedRootList {
+    pub fn new(roots: Vec<::models::TrustedRoot>) -> Self {
+        TrustedRootList { roots }
+    }
+
+    pub fn set_roots(&mut self, roots: Vec<::models::TrustedRoot>) {
+        self.roots = roots;
+    }
+
+    pub fn with_roots(mut self, roots: Vec<::models::TrustedRoot>) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    pub fn roots(&self) -> &[::models::TrustedRoot] {
+        &self.roots
+    }
+}
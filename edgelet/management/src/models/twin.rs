@@ -0,0 +1,55 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Twin {
+    #[serde(rename = "version")]
+    version: i64,
+    #[serde(rename = "properties")]
+    properties: ::models::TwinProperties,
+}
+
+impl Twin {
+    pub fn new(version: i64, properties: ::models::TwinProperties) -> Self {
+        Twin {
+            version,
+            properties,
+        }
+    }
+
+    pub fn set_version(&mut self, version: i64) {
+        self.version = version;
+    }
+
+    pub fn with_version(mut self, version: i64) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn version(&self) -> &i64 {
+        &self.version
+    }
+
+    pub fn set_properties(&mut self, properties: ::models::TwinProperties) {
+        self.properties = properties;
+    }
+
+    pub fn with_properties(mut self, properties: ::models::TwinProperties) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    pub fn properties(&self) -> &::models::TwinProperties {
+        &self.properties
+    }
+}
@@ -0,0 +1,63 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TwinProperties {
+    #[serde(rename = "desired", skip_serializing_if = "Option::is_none")]
+    desired: Option<Value>,
+    #[serde(rename = "reported", skip_serializing_if = "Option::is_none")]
+    reported: Option<Value>,
+}
+
+impl TwinProperties {
+    pub fn new() -> Self {
+        TwinProperties {
+            desired: None,
+            reported: None,
+        }
+    }
+
+    pub fn set_desired(&mut self, desired: Value) {
+        self.desired = Some(desired);
+    }
+
+    pub fn with_desired(mut self, desired: Value) -> Self {
+        self.desired = Some(desired);
+        self
+    }
+
+    pub fn desired(&self) -> Option<&Value> {
+        self.desired.as_ref()
+    }
+
+    pub fn reset_desired(&mut self) {
+        self.desired = None;
+    }
+
+    pub fn set_reported(&mut self, reported: Value) {
+        self.reported = Some(reported);
+    }
+
+    pub fn with_reported(mut self, reported: Value) -> Self {
+        self.reported = Some(reported);
+        self
+    }
+
+    pub fn reported(&self) -> Option<&Value> {
+        self.reported.as_ref()
+    }
+
+    pub fn reset_reported(&mut self) {
+        self.reported = None;
+    }
+}
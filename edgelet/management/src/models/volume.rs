@@ -0,0 +1,95 @@
+/*
+ * IoT Edge Management API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Volume {
+    /// The name of the volume.
+    #[serde(rename = "name")]
+    name: String,
+    /// The volume driver used by the volume.
+    #[serde(rename = "driver")]
+    driver: String,
+    /// Mount path of the volume on the host.
+    #[serde(rename = "mountPoint")]
+    mount_point: String,
+    /// Date/Time the volume was created.
+    #[serde(rename = "createdAt", skip_serializing_if = "Option::is_none")]
+    created_at: Option<String>,
+}
+
+impl Volume {
+    pub fn new(name: String, driver: String, mount_point: String) -> Self {
+        Volume {
+            name,
+            driver,
+            mount_point,
+            created_at: None,
+        }
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn set_driver(&mut self, driver: String) {
+        self.driver = driver;
+    }
+
+    pub fn with_driver(mut self, driver: String) -> Self {
+        self.driver = driver;
+        self
+    }
+
+    pub fn driver(&self) -> &String {
+        &self.driver
+    }
+
+    pub fn set_mount_point(&mut self, mount_point: String) {
+        self.mount_point = mount_point;
+    }
+
+    pub fn with_mount_point(mut self, mount_point: String) -> Self {
+        self.mount_point = mount_point;
+        self
+    }
+
+    pub fn mount_point(&self) -> &String {
+        &self.mount_point
+    }
+
+    pub fn set_created_at(&mut self, created_at: String) {
+        self.created_at = Some(created_at);
+    }
+
+    pub fn with_created_at(mut self, created_at: String) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    pub fn created_at(&self) -> Option<&str> {
+        self.created_at.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_created_at(&mut self) {
+        self.created_at = None;
+    }
+}
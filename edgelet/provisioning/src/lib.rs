@@ -9,14 +9,12 @@
 #![cfg_attr(feature = "cargo-clippy", deny(clippy, clippy_pedantic))]
 #![cfg_attr(feature = "cargo-clippy", allow(stutter, use_self))]
 
-extern crate base64;
 extern crate bytes;
 extern crate failure;
 extern crate futures;
 extern crate hsm;
 #[macro_use]
 extern crate log;
-extern crate regex;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
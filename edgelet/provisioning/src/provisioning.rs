@@ -1,17 +1,15 @@
 // Copyright (c) Microsoft. All rights reserved.
 
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::PathBuf;
+use std::time::Duration;
 
-use base64;
 use bytes::Bytes;
 
 use failure::{Fail, ResultExt};
 use futures::future::Either;
 use futures::{future, Future, IntoFuture};
-use regex::Regex;
 use serde_json;
 use url::Url;
 
@@ -19,18 +17,14 @@ use dps::registration::{DpsClient, DpsTokenSource};
 use edgelet_core::crypto::{Activate, KeyIdentity, KeyStore, MemoryKey, MemoryKeyStore};
 use edgelet_hsm::tpm::{TpmKey, TpmKeyStore};
 use edgelet_http::client::{Client as HttpClient, ClientImpl};
-use edgelet_utils::{ensure_not_empty_with_context, log_failure};
+use edgelet_http::retry::RetryPolicy;
+use edgelet_utils::log_failure;
 use error::{Error, ErrorKind};
 use hsm::TpmKey as HsmTpmKey;
 use log::Level;
 
-const DEVICEID_KEY: &str = "DeviceId";
-const HOSTNAME_KEY: &str = "HostName";
-const SHAREDACCESSKEY_KEY: &str = "SharedAccessKey";
-
-const DEVICEID_REGEX: &str = r"^[A-Za-z0-9\-:.+%_#*?!(),=@;$']{1,128}$";
-const HOSTNAME_REGEX: &str = r"^[a-zA-Z0-9_\-\.]+$";
-const SHAREDACCESSKEY_REGEX: &str = r"^.+$";
+/// How long a single request to DPS may take before it is abandoned
+const DPS_REQUEST_TIMEOUT_SECS: u64 = 30;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ProvisioningResult {
@@ -71,68 +65,12 @@ pub struct ManualProvisioning {
 }
 
 impl ManualProvisioning {
-    pub fn new(conn_string: &str) -> Result<Self, Error> {
-        ensure_not_empty_with_context(&conn_string, || ErrorKind::InvalidConnString)?;
-
-        let hash_map = ManualProvisioning::parse_conn_string(&conn_string)?;
-
-        let key = hash_map.get(SHAREDACCESSKEY_KEY).ok_or(
-            ErrorKind::ConnStringMissingRequiredParameter(SHAREDACCESSKEY_KEY),
-        )?;
-        let key_regex = Regex::new(SHAREDACCESSKEY_REGEX)
-            .expect("This hard-coded regex is expected to be valid.");
-        if !key_regex.is_match(&key) {
-            return Err(Error::from(ErrorKind::ConnStringMalformedParameter(
-                SHAREDACCESSKEY_REGEX,
-            )));
-        }
-        let key = MemoryKey::new(base64::decode(&key).context(
-            ErrorKind::ConnStringMalformedParameter(SHAREDACCESSKEY_REGEX),
-        )?);
-
-        let device_id = hash_map
-            .get(DEVICEID_KEY)
-            .ok_or(ErrorKind::ConnStringMissingRequiredParameter(DEVICEID_KEY))?;
-        let device_id_regex =
-            Regex::new(DEVICEID_REGEX).expect("This hard-coded regex is expected to be valid.");
-        if !device_id_regex.is_match(&device_id) {
-            return Err(Error::from(ErrorKind::ConnStringMalformedParameter(
-                DEVICEID_KEY,
-            )));
-        }
-
-        let hub = hash_map
-            .get(HOSTNAME_KEY)
-            .ok_or(ErrorKind::ConnStringMissingRequiredParameter(HOSTNAME_KEY))?;
-        let hub_regex =
-            Regex::new(HOSTNAME_REGEX).expect("This hard-coded regex is expected to be valid.");
-        if !hub_regex.is_match(&hub) {
-            return Err(Error::from(ErrorKind::ConnStringMalformedParameter(
-                HOSTNAME_KEY,
-            )));
-        }
-
-        let result = ManualProvisioning {
+    pub fn new(key: MemoryKey, device_id: String, hub: String) -> Self {
+        ManualProvisioning {
             key,
-            device_id: device_id.to_owned(),
-            hub: hub.to_owned(),
-        };
-        Ok(result)
-    }
-
-    fn parse_conn_string(conn_string: &str) -> Result<HashMap<String, String>, Error> {
-        let mut hash_map = HashMap::new();
-        let parts: Vec<&str> = conn_string.split(';').collect();
-        for p in parts {
-            let s: Vec<&str> = p.split('=').collect();
-            match s[0] {
-                SHAREDACCESSKEY_KEY | DEVICEID_KEY | HOSTNAME_KEY => {
-                    hash_map.insert(s[0].to_string(), s[1].to_string());
-                }
-                _ => (), // Ignore extraneous component in the connection string
-            }
+            device_id,
+            hub,
         }
-        Ok(hash_map)
     }
 }
 
@@ -195,7 +133,9 @@ where
             api_version,
             endpoint,
         )
-        .context(ErrorKind::DpsInitialization)?;
+        .context(ErrorKind::DpsInitialization)?
+        .with_retry_policy(RetryPolicy::default())
+        .with_timeout(Duration::from_secs(DPS_REQUEST_TIMEOUT_SECS));
 
         let result = DpsProvisioning {
             client,
@@ -206,6 +146,12 @@ where
         };
         Ok(result)
     }
+
+    /// Replaces the default policy for retrying failed requests to DPS.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.client = self.client.with_retry_policy(retry_policy);
+        self
+    }
 }
 
 impl<C> Provision for DpsProvisioning<C>
@@ -364,91 +310,33 @@ mod tests {
 
     #[test]
     fn manual_get_credentials_success() {
-        let provisioning =
-            ManualProvisioning::new("HostName=test.com;DeviceId=test;SharedAccessKey=test");
-        assert_eq!(provisioning.is_ok(), true);
-        let memory_hsm = MemoryKeyStore::new();
-        let task =
-            provisioning
-                .unwrap()
-                .provision(memory_hsm.clone())
-                .then(|result| match result {
-                    Ok(result) => {
-                        assert_eq!(result.hub_name, "test.com".to_string());
-                        assert_eq!(result.device_id, "test".to_string());
-                        Ok::<_, Error>(())
-                    }
-                    Err(err) => panic!("Unexpected {:?}", err),
-                });
-        tokio::runtime::current_thread::Runtime::new()
-            .unwrap()
-            .block_on(task)
-            .unwrap();
-    }
-
-    #[test]
-    fn manual_malformed_conn_string_gets_error() {
-        let test = ManualProvisioning::new("HostName=test.com;DeviceId=test;");
-        assert_eq!(test.is_err(), true);
-    }
-
-    #[test]
-    fn connection_string_split_success() {
-        let provisioning =
-            ManualProvisioning::new("HostName=test.com;DeviceId=test;SharedAccessKey=test");
-        assert_eq!(provisioning.is_ok(), true);
+        let provisioning = ManualProvisioning::new(
+            MemoryKey::new("key"),
+            "test".to_string(),
+            "test.com".to_string(),
+        );
         let memory_hsm = MemoryKeyStore::new();
         let task = provisioning
-            .unwrap()
             .provision(memory_hsm.clone())
-            .then(|result| {
-                let result = result.expect("Unexpected");
-                assert_eq!(result.hub_name, "test.com".to_string());
-                assert_eq!(result.device_id, "test".to_string());
-                Ok::<_, Error>(())
+            .then(|result| match result {
+                Ok(result) => {
+                    assert_eq!(result.hub_name, "test.com".to_string());
+                    assert_eq!(result.device_id, "test".to_string());
+                    Ok::<_, Error>(())
+                }
+                Err(err) => panic!("Unexpected {:?}", err),
             });
         tokio::runtime::current_thread::Runtime::new()
             .unwrap()
             .block_on(task)
             .unwrap();
-
-        let provisioning1 =
-            ManualProvisioning::new("DeviceId=test;SharedAccessKey=test;HostName=test.com");
-        assert_eq!(provisioning1.is_ok(), true);
-        let task1 = provisioning1
-            .unwrap()
-            .provision(memory_hsm.clone())
-            .then(|result| {
-                let result = result.expect("Unexpected");
-                assert_eq!(result.hub_name, "test.com".to_string());
-                assert_eq!(result.device_id, "test".to_string());
-                Ok::<_, Error>(())
-            });
-        tokio::runtime::current_thread::Runtime::new()
-            .unwrap()
-            .block_on(task1)
-            .unwrap();
-    }
-
-    #[test]
-    fn connection_string_split_error() {
-        let test1 = ManualProvisioning::new("DeviceId=test;SharedAccessKey=test");
-        assert_eq!(test1.is_err(), true);
-        let test2 = ManualProvisioning::new(
-            "HostName=test.com;Extra=something;DeviceId=test;SharedAccessKey=test",
+        assert_eq!(
+            b"key",
+            memory_hsm
+                .get(&KeyIdentity::Device, "primary")
+                .unwrap()
+                .as_ref()
         );
-        assert_eq!(test2.is_ok(), true);
-        let memory_hsm = MemoryKeyStore::new();
-        let task1 = test2.unwrap().provision(memory_hsm.clone()).then(|result| {
-            let result = result.expect("Unexpected");
-            assert_eq!(result.hub_name, "test.com".to_string());
-            assert_eq!(result.device_id, "test".to_string());
-            Ok::<_, Error>(())
-        });
-        tokio::runtime::current_thread::Runtime::new()
-            .unwrap()
-            .block_on(task1)
-            .unwrap();
     }
 
     #[test]
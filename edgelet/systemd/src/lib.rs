@@ -19,12 +19,17 @@ extern crate lazy_static;
 extern crate log;
 #[cfg(target_os = "linux")]
 extern crate nix;
+#[cfg(target_os = "linux")]
+#[cfg(test)]
+extern crate tempdir;
 
 use std::net::SocketAddr;
 
 mod error;
 #[cfg(target_os = "linux")]
 mod linux;
+#[cfg(target_os = "linux")]
+mod notify;
 
 pub use self::error::{Error, ErrorKind, SocketLookupType};
 
@@ -39,3 +44,7 @@ pub enum Socket {
 
 #[cfg(target_os = "linux")]
 pub use self::linux::{listener, listener_name, listeners_name};
+#[cfg(target_os = "linux")]
+pub use self::notify::{
+    notify, notify_extend_timeout, notify_status, watchdog_interval, READY, STOPPING, WATCHDOG,
+};
@@ -78,7 +78,7 @@ fn unsetenv_all() {
     env::remove_var(ENV_NAMES);
 }
 
-fn get_env(key: &str) -> Result<String, Error> {
+pub(crate) fn get_env(key: &str) -> Result<String, Error> {
     Ok(env::var(key).with_context(|_| ErrorKind::InvalidVar(key.to_string()))?)
 }
 
@@ -0,0 +1,178 @@
+// Copyright (c) Microsoft. All rights reserved.
+
+//! Implements the `sd_notify` protocol, which tells the service manager about
+//! the state of the daemon.
+//! Based off of [`sd-daemon`](https://github.com/systemd/systemd/tree/master/src/libsystemd/sd-daemon)
+
+use std::env;
+use std::os::unix::ffi::OsStrExt;
+use std::time::Duration;
+
+use failure::ResultExt;
+use nix::sys::socket::{self, AddressFamily, MsgFlags, SockAddr, SockFlag, SockType, UnixAddr};
+use nix::unistd::{self, Pid};
+
+use error::{Error, ErrorKind};
+use linux::get_env;
+
+const ENV_NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+const ENV_WATCHDOG_USEC: &str = "WATCHDOG_USEC";
+const ENV_WATCHDOG_PID: &str = "WATCHDOG_PID";
+
+/// The service is done starting up.
+pub const READY: &str = "READY=1";
+
+/// The service is shutting down.
+pub const STOPPING: &str = "STOPPING=1";
+
+/// Keeps the watchdog of the service from firing.
+pub const WATCHDOG: &str = "WATCHDOG=1";
+
+/// Sends newline-separated `VARIABLE=value` assignments to the service
+/// manager, for example `READY=1` or `STATUS=Provisioning`.
+///
+/// Returns `false` without doing anything when the daemon wasn't started by a
+/// service manager that listens for notifications.
+pub fn notify(state: &str) -> Result<bool, Error> {
+    let path = match env::var_os(ENV_NOTIFY_SOCKET) {
+        Some(path) => path,
+        None => return Ok(false),
+    };
+
+    // A leading '@' stands for a socket in the abstract namespace.
+    let path = path.as_bytes();
+    let addr = if path.first() == Some(&b'@') {
+        UnixAddr::new_abstract(&path[1..])
+    } else {
+        UnixAddr::new(path)
+    }
+    .with_context(|_| ErrorKind::InvalidVar(ENV_NOTIFY_SOCKET.to_string()))?;
+
+    let fd = socket::socket(
+        AddressFamily::Unix,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )
+    .context(ErrorKind::Syscall("socket"))?;
+    let sent = socket::sendto(
+        fd,
+        state.as_bytes(),
+        &SockAddr::Unix(addr),
+        MsgFlags::empty(),
+    )
+    .context(ErrorKind::Syscall("sendto"));
+    let _ = unistd::close(fd);
+    sent?;
+
+    Ok(true)
+}
+
+/// Sends a status line that the service manager shows next to the service,
+/// for example in `systemctl status`.
+pub fn notify_status(status: &str) -> Result<bool, Error> {
+    notify(&format!("STATUS={}", status))
+}
+
+/// Asks the service manager to allow `timeout` more, counted from now, for the
+/// service to finish starting up or shutting down. Service managers that
+/// don't support this keep their configured timeouts.
+pub fn notify_extend_timeout(timeout: Duration) -> Result<bool, Error> {
+    let usec = timeout.as_secs() * 1_000_000 + u64::from(timeout.subsec_micros());
+    notify(&format!("EXTEND_TIMEOUT_USEC={}", usec))
+}
+
+/// Returns how often the service manager expects `WATCHDOG=1`, or `None` if
+/// the watchdog isn't enabled for this process.
+pub fn watchdog_interval() -> Result<Option<Duration>, Error> {
+    if env::var_os(ENV_WATCHDOG_USEC).is_none() {
+        return Ok(None);
+    }
+    let usec = get_env(ENV_WATCHDOG_USEC)?;
+    debug!("{} {}", ENV_WATCHDOG_USEC, usec);
+    let usec = usec
+        .parse::<u64>()
+        .with_context(|_| ErrorKind::InvalidVar(ENV_WATCHDOG_USEC.to_string()))?;
+
+    // The watchdog is meant for a different process, for example the parent
+    // that this daemon was started from.
+    if let Ok(pid) = env::var(ENV_WATCHDOG_PID) {
+        let pid = Pid::from_raw(
+            pid.parse::<i32>()
+                .context(ErrorKind::ParsePid(ENV_WATCHDOG_PID.to_string()))?,
+        );
+        if pid != Pid::this() {
+            return Ok(None);
+        }
+    }
+
+    if usec == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(Duration::from_micros(usec)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::os::unix::net::UnixDatagram;
+    use std::sync::{Mutex, MutexGuard};
+
+    use tempdir::TempDir;
+
+    lazy_static! {
+        static ref LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    fn lock_env<'a>() -> MutexGuard<'a, ()> {
+        LOCK.lock().unwrap()
+    }
+
+    #[test]
+    fn notify_sends_state_to_socket() {
+        let _l = lock_env();
+        let dir = TempDir::new("notify").unwrap();
+        let path = dir.path().join("notify.sock");
+        let listener = UnixDatagram::bind(&path).unwrap();
+        env::set_var(ENV_NOTIFY_SOCKET, &path);
+
+        assert!(notify(READY).unwrap());
+        assert!(notify_status("Provisioning").unwrap());
+        assert!(notify_extend_timeout(Duration::from_secs(90)).unwrap());
+
+        let mut buf = [0; 64];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(b"READY=1", &buf[..len]);
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(b"STATUS=Provisioning", &buf[..len]);
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(b"EXTEND_TIMEOUT_USEC=90000000", &buf[..len]);
+
+        env::remove_var(ENV_NOTIFY_SOCKET);
+    }
+
+    #[test]
+    fn notify_without_socket_does_nothing() {
+        let _l = lock_env();
+        env::remove_var(ENV_NOTIFY_SOCKET);
+
+        assert!(!notify(READY).unwrap());
+    }
+
+    #[test]
+    fn watchdog_interval_for_this_process() {
+        let _l = lock_env();
+        env::set_var(ENV_WATCHDOG_USEC, "30000000");
+        env::set_var(ENV_WATCHDOG_PID, format!("{}", Pid::this()));
+        assert_eq!(Some(Duration::from_secs(30)), watchdog_interval().unwrap());
+
+        env::set_var(ENV_WATCHDOG_PID, "1");
+        assert_eq!(None, watchdog_interval().unwrap());
+
+        env::remove_var(ENV_WATCHDOG_USEC);
+        env::remove_var(ENV_WATCHDOG_PID);
+        assert_eq!(None, watchdog_interval().unwrap());
+    }
+}
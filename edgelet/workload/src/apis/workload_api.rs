@@ -49,6 +49,12 @@ pub trait WorkloadApi {
         genid: &str,
         payload: ::models::DecryptRequest,
     ) -> Box<Future<Item = ::models::DecryptResponse, Error = Error<serde_json::Value>>>;
+    fn delete_secret(
+        &self,
+        api_version: &str,
+        name: &str,
+        key: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
     fn encrypt(
         &self,
         api_version: &str,
@@ -56,6 +62,28 @@ pub trait WorkloadApi {
         genid: &str,
         payload: ::models::EncryptRequest,
     ) -> Box<Future<Item = ::models::EncryptResponse, Error = Error<serde_json::Value>>>;
+    fn get_certificate_notifications(
+        &self,
+        api_version: &str,
+        name: &str,
+        after: Option<i64>,
+        timeout: Option<i64>,
+    ) -> Box<
+        Future<Item = ::models::CertificateNotificationsResponse, Error = Error<serde_json::Value>>,
+    >;
+    fn get_secret(
+        &self,
+        api_version: &str,
+        name: &str,
+        key: &str,
+    ) -> Box<Future<Item = ::models::Secret, Error = Error<serde_json::Value>>>;
+    fn set_secret(
+        &self,
+        api_version: &str,
+        name: &str,
+        key: &str,
+        secret: ::models::Secret,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>>;
     fn sign(
         &self,
         api_version: &str,
@@ -280,6 +308,63 @@ where
         )
     }
 
+    fn delete_secret(
+        &self,
+        api_version: &str,
+        name: &str,
+        key: &str,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::DELETE;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!(
+            "/modules/{name}/secrets/{key}?{}",
+            query,
+            name = name,
+            key = key
+        );
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|_| futures::future::ok(())),
+        )
+    }
+
     fn encrypt(
         &self,
         api_version: &str,
@@ -349,6 +434,200 @@ where
         )
     }
 
+    fn get_certificate_notifications(
+        &self,
+        api_version: &str,
+        name: &str,
+        after: Option<i64>,
+        timeout: Option<i64>,
+    ) -> Box<
+        Future<Item = ::models::CertificateNotificationsResponse, Error = Error<serde_json::Value>>,
+    > {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::GET;
+
+        let mut query = ::url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("api-version", &api_version.to_string());
+        if let Some(after) = after {
+            query.append_pair("after", &after.to_string());
+        }
+        if let Some(timeout) = timeout {
+            query.append_pair("timeout", &timeout.to_string());
+        }
+        let query = query.finish();
+        let uri_str = format!(
+            "/modules/{name}/certificate/notifications?{}",
+            query,
+            name = name
+        );
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|body| {
+                    let parsed: Result<::models::CertificateNotificationsResponse, _> =
+                        serde_json::from_slice(&body);
+                    parsed.map_err(Error::from)
+                }),
+        )
+    }
+
+    fn get_secret(
+        &self,
+        api_version: &str,
+        name: &str,
+        key: &str,
+    ) -> Box<Future<Item = ::models::Secret, Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::GET;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!(
+            "/modules/{name}/secrets/{key}?{}",
+            query,
+            name = name,
+            key = key
+        );
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let req = req
+            .body(hyper::Body::empty())
+            .expect("could not build hyper::Request");
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|body| {
+                    let parsed: Result<::models::Secret, _> = serde_json::from_slice(&body);
+                    parsed.map_err(Error::from)
+                }),
+        )
+    }
+
+    fn set_secret(
+        &self,
+        api_version: &str,
+        name: &str,
+        key: &str,
+        secret: ::models::Secret,
+    ) -> Box<Future<Item = (), Error = Error<serde_json::Value>>> {
+        let configuration: &configuration::Configuration<C> = self.configuration.borrow();
+
+        let method = hyper::Method::PUT;
+
+        let query = ::url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("api-version", &api_version.to_string())
+            .finish();
+        let uri_str = format!(
+            "/modules/{name}/secrets/{key}?{}",
+            query,
+            name = name,
+            key = key
+        );
+
+        let uri = (configuration.uri_composer)(&configuration.base_path, &uri_str);
+        // TODO(farcaller): handle error
+        // if let Err(e) = uri {
+        //     return Box::new(futures::future::err(e));
+        // }
+        let serialized = serde_json::to_string(&secret).unwrap();
+        let serialized_len = serialized.len();
+
+        let mut req = hyper::Request::builder();
+        req.method(method).uri(uri.unwrap());
+        if let Some(ref user_agent) = configuration.user_agent {
+            req.header(http::header::USER_AGENT, &**user_agent);
+        }
+        let mut req = req
+            .body(hyper::Body::from(serialized))
+            .expect("could not build hyper::Request");
+        req.headers_mut()
+            .typed_insert(&typed_headers::ContentType(mime::APPLICATION_JSON));
+        req.headers_mut()
+            .typed_insert(&typed_headers::ContentLength(serialized_len as u64));
+
+        // send request
+        Box::new(
+            configuration
+                .client
+                .request(req)
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    let (http::response::Parts { status, .. }, body) = resp.into_parts();
+                    body.concat2()
+                        .and_then(move |body| Ok((status, body)))
+                        .map_err(Error::from)
+                })
+                .and_then(|(status, body)| {
+                    if status.is_success() {
+                        Ok(body)
+                    } else {
+                        Err(Error::from((status, &*body)))
+                    }
+                })
+                .and_then(|_| futures::future::ok(())),
+        )
+    }
+
     fn sign(
         &self,
         api_version: &str,
@@ -0,0 +1,116 @@
+/*
+ * IoT Edge Module Workload API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CertificateNotification {
+    /// Sequence number of the notification.
+    #[serde(rename = "sequence")]
+    sequence: i64,
+    /// What happened. One of `expiring`, `caRotated` or `trustBundleChanged`.
+    #[serde(rename = "type")]
+    kind: String,
+    /// The type of the certificate the notification concerns, `identity` or `server`.
+    #[serde(rename = "certificateType", skip_serializing_if = "Option::is_none")]
+    certificate_type: Option<String>,
+    /// Expiration date-time (ISO 8601) of the certificate the notification concerns.
+    #[serde(rename = "expiration", skip_serializing_if = "Option::is_none")]
+    expiration: Option<String>,
+    /// Date-time (ISO 8601) of the notification.
+    #[serde(rename = "time")]
+    time: String,
+}
+
+impl CertificateNotification {
+    pub fn new(sequence: i64, kind: String, time: String) -> Self {
+        CertificateNotification {
+            sequence,
+            kind,
+            certificate_type: None,
+            expiration: None,
+            time,
+        }
+    }
+
+    pub fn set_sequence(&mut self, sequence: i64) {
+        self.sequence = sequence;
+    }
+
+    pub fn with_sequence(mut self, sequence: i64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    pub fn sequence(&self) -> i64 {
+        self.sequence
+    }
+
+    pub fn set_kind(&mut self, kind: String) {
+        self.kind = kind;
+    }
+
+    pub fn with_kind(mut self, kind: String) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn set_certificate_type(&mut self, certificate_type: String) {
+        self.certificate_type = Some(certificate_type);
+    }
+
+    pub fn with_certificate_type(mut self, certificate_type: String) -> Self {
+        self.certificate_type = Some(certificate_type);
+        self
+    }
+
+    pub fn certificate_type(&self) -> Option<&str> {
+        self.certificate_type.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_certificate_type(&mut self) {
+        self.certificate_type = None;
+    }
+
+    pub fn set_expiration(&mut self, expiration: String) {
+        self.expiration = Some(expiration);
+    }
+
+    pub fn with_expiration(mut self, expiration: String) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    pub fn expiration(&self) -> Option<&str> {
+        self.expiration.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn reset_expiration(&mut self) {
+        self.expiration = None;
+    }
+
+    pub fn set_time(&mut self, time: String) {
+        self.time = time;
+    }
+
+    pub fn with_time(mut self, time: String) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+}
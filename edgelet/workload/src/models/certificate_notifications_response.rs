@@ -0,0 +1,59 @@
+/*
+ * IoT Edge Module Workload API
+ *
+ * No description provided (generated by Swagger Codegen https://github.com/swagger-api/swagger-codegen)
+ *
+ * OpenAPI spec version: 2018-06-28
+ *
+ * Generated by: https://github.com/swagger-api/swagger-codegen.git
+ */
+
+#[allow(unused_imports)]
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CertificateNotificationsResponse {
+    /// Sequence number to pass as `after` when polling for the next notifications.
+    #[serde(rename = "sequence")]
+    sequence: i64,
+    #[serde(rename = "notifications")]
+    notifications: Vec<::models::CertificateNotification>,
+}
+
+impl CertificateNotificationsResponse {
+    pub fn new(sequence: i64, notifications: Vec<::models::CertificateNotification>) -> Self {
+        CertificateNotificationsResponse {
+            sequence,
+            notifications,
+        }
+    }
+
+    pub fn set_sequence(&mut self, sequence: i64) {
+        self.sequence = sequence;
+    }
+
+    pub fn with_sequence(mut self, sequence: i64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    pub fn sequence(&self) -> i64 {
+        self.sequence
+    }
+
+    pub fn set_notifications(&mut self, notifications: Vec<::models::CertificateNotification>) {
+        self.notifications = notifications;
+    }
+
+    pub fn with_notifications(
+        mut self,
+        notifications: Vec<::models::CertificateNotification>,
+    ) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    pub fn notifications(&self) -> &[::models::CertificateNotification] {
+        &self.notifications
+    }
+}
@@ -1,3 +1,7 @@
+mod certificate_notification;
+pub use self::certificate_notification::CertificateNotification;
+mod certificate_notifications_response;
+pub use self::certificate_notifications_response::CertificateNotificationsResponse;
 mod certificate_response;
 pub use self::certificate_response::CertificateResponse;
 mod decrypt_request;
@@ -14,6 +18,8 @@ mod identity_certificate_request;
 pub use self::identity_certificate_request::IdentityCertificateRequest;
 mod private_key;
 pub use self::private_key::PrivateKey;
+mod secret;
+pub use self::secret::Secret;
 mod server_certificate_request;
 pub use self::server_certificate_request::ServerCertificateRequest;
 mod sign_request;